@@ -0,0 +1,92 @@
+//! Graph construction times for a random road-like edge list, run with
+//! `cargo bench --bench load`.
+//!
+//! `linear-lookup` builds the graph the way `get_or_insert_node` did before the name
+//! index, scanning every name so far for each endpoint. It runs on a smaller input since
+//! it is quadratic.
+
+use cs365_bonus::rng::Rng;
+use cs365_bonus::{parse_parallel, Graph, NodeId, Weight};
+use std::hint::black_box;
+use std::time::Instant;
+
+const NODES: usize = 200_000;
+const EDGES: usize = 500_000;
+const LINEAR_EDGES: usize = 5_000;
+
+fn edge_list(rng: &mut Rng) -> Vec<(String, String, Weight)> {
+    (0..EDGES)
+        .map(|_| {
+            let src = format!("n{}", rng.below(NODES));
+            let dest = format!("n{}", rng.below(NODES));
+            (src, dest, 1 + rng.below(1000) as Weight)
+        })
+        .collect()
+}
+
+fn linear_lookup(edges: &[(String, String, Weight)]) -> usize {
+    let mut names: Vec<&str> = Vec::new();
+    let mut lookup = |name| match names.iter().position(|&known| known == name) {
+        Some(i) => i,
+        None => {
+            names.push(name);
+            names.len() - 1
+        }
+    };
+    let mut total = 0;
+    for (src, dest, _) in edges {
+        total += lookup(src.as_str()) + lookup(dest.as_str());
+    }
+    total
+}
+
+fn time(name: &str, edges: usize, build: impl FnOnce() -> usize) {
+    let started = Instant::now();
+    let checksum = black_box(build());
+    let elapsed = started.elapsed();
+    println!(
+        "{:<16} {:>10.1} ms for {} edges  (checksum {})",
+        name,
+        elapsed.as_secs_f64() * 1e3,
+        edges,
+        checksum
+    );
+}
+
+fn main() {
+    let mut rng = Rng::new(365);
+    let edges = edge_list(&mut rng);
+    let text: String = edges
+        .iter()
+        .map(|(src, dest, cost)| format!("{} {} {}\n", src, dest, cost))
+        .collect();
+
+    time("linear-lookup", LINEAR_EDGES, || {
+        linear_lookup(&edges[..LINEAR_EDGES])
+    });
+    time("from-edges", EDGES, || {
+        let graph: Graph = edges
+            .iter()
+            .map(|(src, dest, cost)| (src.as_str(), dest.as_str(), *cost))
+            .collect();
+        graph.node_count()
+    });
+    time("parse", EDGES, || {
+        let graph: Graph = text.parse().expect("generated input is valid");
+        graph.node_count()
+    });
+    time("parse-parallel", EDGES, || {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let graph = parse_parallel(&text, threads).expect("generated input is valid");
+        graph.node_count()
+    });
+
+    let graph: Graph = text.parse().expect("generated input is valid");
+    time("get-node", EDGES, || {
+        edges
+            .iter()
+            .filter_map(|(src, _, _)| graph.get_node(src))
+            .map(NodeId::index)
+            .sum()
+    });
+}
@@ -0,0 +1,147 @@
+//! Point-to-point query times on a random grid, run with `cargo bench --bench search`.
+//!
+//! `option-vectors` is the search as it was before `SearchContext`: fresh
+//! `Vec<Option<Weight>>` distances per query and stale heap entries detected by comparing
+//! distances. The other rows use the generation-stamped arrays and the settled bitset.
+//!
+//! The `csr-*` rows search a [`Csr`] copy of the grid: with its nodes numbered at random,
+//! in the row-major order it was built in, and renumbered from the random numbering by BFS
+//! and by RCM, to show how much node order matters for locality.
+
+use cs365_bonus::csr::Csr;
+use cs365_bonus::reorder::{node_order, Reordering};
+use cs365_bonus::rng::Rng;
+use cs365_bonus::{find_shortest_path, Graph, NodeId, SearchContext, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::hint::black_box;
+use std::time::Instant;
+
+const SIDE: usize = 300;
+const QUERIES: usize = 200;
+
+fn grid(rng: &mut Rng) -> Graph {
+    let mut graph = Graph::with_capacity(SIDE * SIDE, 2 * SIDE * SIDE);
+    let ids: Vec<NodeId> = (0..SIDE * SIDE)
+        .map(|i| graph.get_or_insert_node(&format!("n{}", i)))
+        .collect();
+    for y in 0..SIDE {
+        for x in 0..SIDE {
+            let i = y * SIDE + x;
+            if x + 1 < SIDE {
+                graph.add_bidirectional_edge(ids[i], ids[i + 1], 1 + rng.below(100) as Weight);
+            }
+            if y + 1 < SIDE {
+                graph.add_bidirectional_edge(ids[i], ids[i + SIDE], 1 + rng.below(100) as Weight);
+            }
+        }
+    }
+    graph
+}
+
+fn option_vectors(graph: &Graph, start: NodeId, end: NodeId) -> Option<Weight> {
+    let mut distance: Vec<Option<Weight>> = vec![None; graph.node_count()];
+    let mut parent: Vec<Option<NodeId>> = vec![None; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    distance[start.index()] = Some(0);
+    heap.push(Reverse((0, start)));
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if distance[node.index()].is_some_and(|best| cost > best) {
+            continue;
+        }
+        if node == end {
+            return Some(cost);
+        }
+        for edge in graph.edges_of(node) {
+            let next = cost + edge.cost;
+            if distance[edge.node.index()].is_none_or(|best| next < best) {
+                distance[edge.node.index()] = Some(next);
+                parent[edge.node.index()] = Some(node);
+                heap.push(Reverse((next, edge.node)));
+            }
+        }
+    }
+    None
+}
+
+/// `graph` renumbered so that `order[i]` becomes node `i`, as a CSR, with `queries`
+/// moved to the new ids.
+fn renumbered(
+    graph: &Graph,
+    order: &[NodeId],
+    queries: &[(NodeId, NodeId)],
+) -> (Graph, Csr, Vec<(NodeId, NodeId)>) {
+    let mut position = vec![NodeId::new(0); order.len()];
+    for (i, node) in order.iter().enumerate() {
+        position[node.index()] = NodeId::new(i);
+    }
+    let permuted = graph.permuted(order).expect("orders are permutations");
+    let csr = Csr::from_graph(&permuted);
+    let queries = queries
+        .iter()
+        .map(|&(start, end)| (position[start.index()], position[end.index()]))
+        .collect();
+    (permuted, csr, queries)
+}
+
+fn time(
+    name: &str,
+    queries: &[(NodeId, NodeId)],
+    mut query: impl FnMut(NodeId, NodeId) -> Option<Weight>,
+) {
+    let started = Instant::now();
+    let mut total = 0;
+    for &(start, end) in queries {
+        total += black_box(query(start, end)).unwrap_or(0);
+    }
+    let elapsed = started.elapsed();
+    println!(
+        "{:<16} {:>10.1} us/query  (checksum {})",
+        name,
+        elapsed.as_secs_f64() * 1e6 / queries.len() as f64,
+        total
+    );
+}
+
+fn main() {
+    let mut rng = Rng::new(365);
+    let graph = grid(&mut rng);
+    let queries: Vec<(NodeId, NodeId)> = (0..QUERIES)
+        .map(|_| {
+            let n = graph.node_count();
+            (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)))
+        })
+        .collect();
+    println!("{} nodes, {} queries", graph.node_count(), queries.len());
+
+    time("option-vectors", &queries, |start, end| {
+        option_vectors(&graph, start, end)
+    });
+    time("shortest-path", &queries, |start, end| {
+        find_shortest_path(&graph, start, end).map(|path| path.cost)
+    });
+    let mut context = SearchContext::new();
+    time("search-context", &queries, |start, end| {
+        context
+            .find_shortest_path(&graph, start, end)
+            .map(|path| path.cost)
+    });
+
+    let mut shuffled: Vec<NodeId> = graph.node_ids().collect();
+    rng.shuffle(&mut shuffled);
+    let (random, csr, random_queries) = renumbered(&graph, &shuffled, &queries);
+    time("csr-random", &random_queries, |start, end| {
+        find_shortest_path(&csr, start, end).map(|path| path.cost)
+    });
+    let csr = Csr::from_graph(&graph);
+    time("csr-grid", &queries, |start, end| {
+        find_shortest_path(&csr, start, end).map(|path| path.cost)
+    });
+    for (name, reordering) in [("csr-bfs", Reordering::Bfs), ("csr-rcm", Reordering::Rcm)] {
+        let order = node_order(&random, reordering);
+        let (_, csr, queries) = renumbered(&random, &order, &random_queries);
+        time(name, &queries, |start, end| {
+            find_shortest_path(&csr, start, end).map(|path| path.cost)
+        });
+    }
+}
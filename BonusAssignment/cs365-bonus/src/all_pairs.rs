@@ -0,0 +1,126 @@
+use crate::bellman_ford::{bellman_ford, NegativeCycle};
+use crate::csr::Csr;
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::has_negative_edges;
+
+/// Shortest path distances between every pair of nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceMatrix {
+    n: usize,
+    /// Row-major, `distance[u * n + v]` is the distance from `u` to `v`.
+    distance: Vec<Option<Weight>>,
+}
+
+impl DistanceMatrix {
+    pub fn node_count(&self) -> usize {
+        self.n
+    }
+
+    /// The distance from `u` to `v`, or `None` if `v` is unreachable.
+    pub fn get(&self, u: NodeId, v: NodeId) -> Option<Weight> {
+        self.row(u)[v.index()]
+    }
+
+    /// The distances from `u` to every node.
+    pub fn row(&self, u: NodeId) -> &[Option<Weight>] {
+        &self.distance[u.index() * self.n..(u.index() + 1) * self.n]
+    }
+}
+
+/// Compute all-pairs distances with whichever of [`floyd_warshall`] and
+/// [`repeated_dijkstra`] should be faster.
+///
+/// Floyd-Warshall takes O(n^3) and repeated Dijkstra O(nm log n), so the former is used
+/// once `m log n` reaches `n^2`, and always if any cost is negative.
+pub fn all_pairs(graph: &Graph) -> Result<DistanceMatrix, NegativeCycle> {
+    if prefers_floyd_warshall(graph) {
+        floyd_warshall(graph)
+    } else {
+        Ok(repeated_dijkstra(graph))
+    }
+}
+
+/// Whether [`all_pairs`] picks Floyd-Warshall for `graph`.
+pub fn prefers_floyd_warshall(graph: &Graph) -> bool {
+    let n = graph.node_count() as f64;
+    let m = graph.edge_count() as f64;
+    has_negative_edges(graph) || m * n.log2().max(1.0) >= n * n
+}
+
+/// All-pairs distances by Floyd-Warshall, which allows negative costs.
+pub fn floyd_warshall(graph: &Graph) -> Result<DistanceMatrix, NegativeCycle> {
+    let n = graph.node_count();
+    let mut distance: Vec<Option<Weight>> = vec![None; n * n];
+    for u in graph.node_ids() {
+        distance[u.index() * n + u.index()] = Some(0);
+        for edge in graph.edges_of(u) {
+            let cell = &mut distance[u.index() * n + edge.node.index()];
+            if cell.is_none_or(|best| edge.cost < best) {
+                *cell = Some(edge.cost);
+            }
+        }
+    }
+
+    for k in 0..n {
+        for i in 0..n {
+            let to_k = match distance[i * n + k] {
+                Some(cost) => cost,
+                None => continue,
+            };
+            for j in 0..n {
+                if let Some(from_k) = distance[k * n + j] {
+                    let through = to_k + from_k;
+                    let cell = &mut distance[i * n + j];
+                    if cell.is_none_or(|best| through < best) {
+                        *cell = Some(through);
+                    }
+                }
+            }
+        }
+    }
+
+    // A node that can reach itself at negative cost lies on a negative cycle, which
+    // Bellman-Ford from it will find and report.
+    if let Some(i) = (0..n).find(|&i| distance[i * n + i].is_some_and(|cost| cost < 0)) {
+        bellman_ford(graph, NodeId::new(i))?;
+    }
+    Ok(DistanceMatrix { n, distance })
+}
+
+/// All-pairs distances by a Dijkstra search from every node. Costs must not be negative.
+pub fn repeated_dijkstra(graph: &Graph) -> DistanceMatrix {
+    let n = graph.node_count();
+    let csr = Csr::from_graph(graph);
+    let mut distance = Vec::with_capacity(n * n);
+    for source in graph.node_ids() {
+        distance.extend_from_slice(csr.shortest_path_tree(source).distances());
+    }
+    DistanceMatrix { n, distance }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn methods_agree() {
+        let graph: Graph = "a b 4\na c 1\nc b 2\nb d 5\nx y 1".parse().unwrap();
+        let dense = floyd_warshall(&graph).unwrap();
+        assert_eq!(dense, repeated_dijkstra(&graph));
+        assert_eq!(dense, all_pairs(&graph).unwrap());
+        let node = |name| graph.get_node(name).unwrap();
+        assert_eq!(dense.get(node("a"), node("d")), Some(8));
+        assert_eq!(dense.get(node("d"), node("x")), None);
+    }
+
+    #[test]
+    fn negative_costs_use_floyd_warshall() {
+        let graph = Graph::from_directed_edges([("a", "b", 3), ("b", "c", -2), ("a", "c", 2)]);
+        let matrix = all_pairs(&graph).unwrap();
+        assert_eq!(matrix.row(NodeId::new(0)), [Some(0), Some(3), Some(1)]);
+        assert_eq!(matrix.get(NodeId::new(2), NodeId::new(0)), None);
+
+        let cycle = Graph::from_directed_edges([("a", "b", 1), ("b", "a", -2)]);
+        assert_eq!(all_pairs(&cycle).unwrap_err().nodes().len(), 2);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::{find_shortest_path, BudgetExceeded, Path, SearchContext};
+use crate::replacement::path_edges;
+use crate::similarity::weighted_overlap;
+
+/// How many searches to run per requested route before giving up on finding more.
+const ATTEMPTS_PER_ROUTE: usize = 4;
+
+/// Up to `count` routes from `start` to `end` that differ meaningfully from each other.
+///
+/// The penalty method: after each search, every edge of the route found has its weight
+/// multiplied by `penalty` for the following searches, pushing them onto other roads. A
+/// candidate is kept only if at most `max_overlap` of its cost lies on edges of a route
+/// already kept. Penalties compound, and a rejected candidate is still penalized, so the
+/// search keeps moving away from known routes. Unlike ranked k-shortest paths this skips
+/// routes that only differ by a small detour.
+///
+/// Costs in the returned paths are the real, unpenalized ones. The first route is always
+/// the shortest path; the result is empty if `end` is unreachable.
+pub fn penalty_alternatives(
+    graph: &Graph,
+    start: NodeId,
+    end: NodeId,
+    count: usize,
+    penalty: f64,
+    max_overlap: f64,
+) -> Vec<Path> {
+    let mut multiplier = vec![1.0; graph.edge_count()];
+    let mut routes: Vec<Path> = Vec::new();
+    for _ in 0..count * ATTEMPTS_PER_ROUTE {
+        if routes.len() == count {
+            break;
+        }
+        let penalized =
+            graph.map_weights(|id, cost| (cost as f64 * multiplier[id.index()]).round() as Weight);
+        let found = match find_shortest_path(&penalized, start, end) {
+            Some(found) => found,
+            None => break,
+        };
+        for edge in path_edges(&penalized, &found) {
+            multiplier[edge.index()] *= penalty;
+        }
+
+        let path = match Path::from_nodes(graph, found.nodes) {
+            Some(path) => path,
+            None => break,
+        };
+        let distinct = routes
+            .iter()
+            .all(|kept| weighted_overlap(graph, &path, kept) <= max_overlap);
+        if distinct {
+            routes.push(path);
+        }
+    }
+    routes
+}
+
+/// The `k` shortest loopless paths from `start` to `end` in order of cost, by Yen's
+/// algorithm. Fewer are returned if fewer exist, and none if `end` is unreachable.
+///
+/// Each path after the first deviates from the previous one at some spur node: it
+/// follows the previous path up to there, then takes the shortest path from the spur that
+/// avoids the nodes before it and the next edges of every known path sharing that root.
+/// That is one Dijkstra per node of each path found, O(kn) searches in all. Paths are
+/// node sequences, so parallel edges count as one route using the cheapest of them.
+pub fn find_k_shortest_paths(graph: &Graph, start: NodeId, end: NodeId, k: usize) -> Vec<Path> {
+    yen(graph, start, end, k, None).unwrap_or_default()
+}
+
+/// Like [`find_k_shortest_paths`], but gives up once its searches have expanded `budget`
+/// nodes between them.
+pub fn find_k_shortest_paths_within(
+    graph: &Graph,
+    start: NodeId,
+    end: NodeId,
+    k: usize,
+    budget: usize,
+) -> Result<Vec<Path>, BudgetExceeded> {
+    let mut remaining = budget;
+    yen(graph, start, end, k, Some(&mut remaining)).ok_or(BudgetExceeded { budget })
+}
+
+/// Yen's algorithm, or `None` if it runs out of `budget`.
+fn yen(
+    graph: &Graph,
+    start: NodeId,
+    end: NodeId,
+    k: usize,
+    mut budget: Option<&mut usize>,
+) -> Option<Vec<Path>> {
+    let mut found: Vec<Path> = Vec::new();
+    if k == 0 {
+        return Some(found);
+    }
+    let mut context = SearchContext::new();
+    if !context.run(
+        graph,
+        start,
+        Some(end),
+        None,
+        budget.as_deref_mut(),
+        &mut (),
+        |_, _| true,
+    ) {
+        return None;
+    }
+    match context.path_to(end) {
+        Some(path) => found.push(path),
+        None => return Some(found),
+    }
+    let mut candidates: Vec<Path> = Vec::new();
+    let mut removed = vec![false; graph.node_count()];
+
+    while found.len() < k {
+        let last = &found[found.len() - 1];
+        for i in 0..last.nodes.len() - 1 {
+            let spur = last.nodes[i];
+            let root = &last.nodes[..=i];
+            let blocked: Vec<NodeId> = found
+                .iter()
+                .filter(|path| path.nodes.len() > i + 1 && path.nodes[..=i] == *root)
+                .map(|path| path.nodes[i + 1])
+                .collect();
+            for &node in &root[..i] {
+                removed[node.index()] = true;
+            }
+            let finished = context.run(
+                graph,
+                spur,
+                Some(end),
+                None,
+                budget.as_deref_mut(),
+                &mut (),
+                |from, edge| {
+                    let deviates = from != spur || !blocked.contains(&edge.node);
+                    !removed[edge.node.index()] && deviates
+                },
+            );
+            for &node in &root[..i] {
+                removed[node.index()] = false;
+            }
+            if !finished {
+                return None;
+            }
+
+            let spur_path = match context.path_to(end) {
+                Some(path) => path,
+                None => continue,
+            };
+            let offset = last.distance[i];
+            let mut nodes = root[..i].to_vec();
+            nodes.extend_from_slice(&spur_path.nodes);
+            let mut distance = last.distance[..i].to_vec();
+            distance.extend(spur_path.distance.iter().map(|d| offset + d));
+            let candidate = Path {
+                nodes,
+                distance,
+                cost: offset + spur_path.cost,
+            };
+            let known = |path: &Path| path.nodes == candidate.nodes;
+            if !candidates.iter().any(known) && !found.iter().any(known) {
+                candidates.push(candidate);
+            }
+        }
+
+        let best = candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, path)| (path.cost, path.nodes.len()))
+            .map(|(i, _)| i);
+        match best {
+            Some(i) => found.push(candidates.swap_remove(i)),
+            None => break,
+        }
+    }
+    Some(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yen_ranks_loopless_paths() {
+        // The classic example from Yen's paper, as a directed graph.
+        let graph = Graph::from_directed_edges([
+            ("c", "d", 3),
+            ("c", "e", 2),
+            ("d", "f", 4),
+            ("e", "d", 1),
+            ("e", "f", 2),
+            ("e", "g", 3),
+            ("f", "g", 2),
+            ("f", "h", 1),
+            ("g", "h", 2),
+        ]);
+        let (c, h) = (graph.get_node("c").unwrap(), graph.get_node("h").unwrap());
+        let paths = find_k_shortest_paths(&graph, c, h, 10);
+        let costs: Vec<Weight> = paths.iter().map(|path| path.cost).collect();
+        assert_eq!(costs, [5, 7, 8, 8, 8, 11, 11]);
+        let name = |node| graph.get_node_name(node).unwrap();
+        let first: Vec<&str> = paths[0].nodes.iter().map(|&n| name(n)).collect();
+        assert_eq!(first, ["c", "e", "f", "h"]);
+        for path in &paths {
+            let rebuilt = Path::from_nodes(&graph, path.nodes.clone()).unwrap();
+            assert_eq!(rebuilt.distance, path.distance);
+        }
+        assert!(find_k_shortest_paths(&graph, h, c, 3).is_empty());
+        assert!(find_k_shortest_paths_within(&graph, c, h, 10, 20).is_err());
+        let within = find_k_shortest_paths_within(&graph, c, h, 10, 1000).unwrap();
+        assert_eq!(within.len(), paths.len());
+    }
+
+    #[test]
+    fn penalty_routes_start_shortest_and_stay_apart() {
+        let mut rng = crate::rng::Rng::new(30);
+        for _ in 0..40 {
+            let n = 2 + rng.below(10);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(4 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                graph.add_bidirectional_edge(a, b, 1 + rng.below(10) as Weight);
+            }
+            let (start, end) = (NodeId::new(0), NodeId::new(n - 1));
+            let max_overlap = rng.next_f64();
+            let routes = penalty_alternatives(&graph, start, end, 3, 1.5, max_overlap);
+            let shortest = find_shortest_path(&graph, start, end);
+            assert_eq!(routes.first().map(|r| r.cost), shortest.map(|p| p.cost));
+            assert!(routes.len() <= 3);
+            for (i, route) in routes.iter().enumerate() {
+                assert_eq!(route.nodes.first(), Some(&start));
+                assert_eq!(route.nodes.last(), Some(&end));
+                let rebuilt = Path::from_nodes(&graph, route.nodes.clone()).unwrap();
+                assert_eq!(rebuilt.cost, route.cost);
+                for kept in &routes[..i] {
+                    assert!(weighted_overlap(&graph, route, kept) <= max_overlap);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn penalties_find_the_parallel_road() {
+        // Two roads from a to z, and a detour that shares most of the first.
+        let graph: Graph = "a b 5\nb z 5\na c 6\nc z 6\nb d 1\nd z 5".parse().unwrap();
+        let (a, z) = (graph.get_node("a").unwrap(), graph.get_node("z").unwrap());
+        let routes = penalty_alternatives(&graph, a, z, 2, 2.0, 0.5);
+        let costs: Vec<Weight> = routes.iter().map(|route| route.cost).collect();
+        assert_eq!(costs, [10, 12]);
+        assert!(penalty_alternatives(&graph, a, z, 0, 2.0, 0.5).is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::bellman_ford::find_negative_cycle;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::parse::{ParseErrorKind, ParseGraphError};
+use std::collections::HashSet;
+
+/// Currencies and the directed exchange rates between them.
+#[derive(Debug, Clone)]
+pub struct RateTable {
+    graph: Graph,
+    rates: Vec<f64>,
+}
+
+impl RateTable {
+    /// Parse `from to rate` lines, where one unit of `from` buys `rate` units of `to`.
+    ///
+    /// Blank lines and `#` comments are skipped. Rates must be positive and finite.
+    pub fn parse(input: &str) -> Result<Self, ParseGraphError> {
+        let mut graph = Graph::new();
+        let mut rates = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [] => continue,
+                [word, ..] if word.starts_with('#') => continue,
+                [from, to, rate] => {
+                    let rate: f64 = rate
+                        .parse()
+                        .ok()
+                        .filter(|r: &f64| r.is_finite() && *r > 0.0)
+                        .ok_or_else(|| {
+                            ParseGraphError::new(
+                                i + 1,
+                                ParseErrorKind::InvalidCost(rate.to_string()),
+                            )
+                        })?;
+                    let from = graph.get_or_insert_node(from);
+                    let to = graph.get_or_insert_node(to);
+                    graph.add_edge(from, to, 0);
+                    rates.push(rate);
+                }
+                words => {
+                    let kind = ParseErrorKind::WrongFieldCount(words.len());
+                    return Err(ParseGraphError::new(i + 1, kind));
+                }
+            }
+        }
+        Ok(Self { graph, rates })
+    }
+
+    /// The currencies as nodes, with one directed edge per rate.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn rate(&self, edge: EdgeId) -> f64 {
+        self.rates[edge.index()]
+    }
+}
+
+/// A sequence of trades that ends with more of the starting currency than it began with.
+#[derive(Debug, Clone)]
+pub struct Arbitrage {
+    /// The currencies visited, starting and ending with the same one.
+    pub currencies: Vec<NodeId>,
+    pub trades: Vec<EdgeId>,
+    /// The amount received for each unit traded in, greater than one.
+    pub profit: f64,
+}
+
+/// Find up to `limit` distinct profitable trade cycles.
+///
+/// With weights of `-ln(rate)`, a cycle is profitable exactly when its total weight is
+/// negative, so Bellman-Ford finds one. Its worst trade is then excluded and the search
+/// repeated, which surfaces other cycles without revisiting the same one.
+pub fn find_arbitrage(table: &RateTable, limit: usize) -> Vec<Arbitrage> {
+    let graph = table.graph();
+    let mut excluded = vec![false; graph.edge_count()];
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+
+    while found.len() < limit {
+        let trades = match find_negative_cycle(graph, |id| {
+            (!excluded[id.index()]).then(|| -table.rate(id).ln())
+        }) {
+            Some(trades) => trades,
+            None => break,
+        };
+        let worst = *trades
+            .iter()
+            .min_by(|&&a, &&b| table.rate(a).total_cmp(&table.rate(b)))
+            .expect("cycles have edges");
+        excluded[worst.index()] = true;
+
+        // Rotate so the cycle starts at its smallest trade, making duplicates comparable.
+        let first = (0..trades.len()).min_by_key(|&i| trades[i]).unwrap_or(0);
+        let mut trades = trades;
+        trades.rotate_left(first);
+        if !seen.insert(trades.clone()) {
+            continue;
+        }
+
+        let mut currencies: Vec<NodeId> = trades
+            .iter()
+            .map(|&id| graph.edge_endpoints(id).expect("edge exists").0)
+            .collect();
+        currencies.push(currencies[0]);
+        let profit = trades.iter().map(|&id| table.rate(id)).product();
+        found.push(Arbitrage {
+            currencies,
+            trades,
+            profit,
+        });
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn finds_arbitrage_exactly_when_floyd_warshall_does() {
+        let mut rng = Rng::new(20);
+        for _ in 0..50 {
+            let n = 2 + rng.below(5);
+            let mut input = String::new();
+            for a in 0..n {
+                for b in 0..n {
+                    if a != b && rng.chance(0.5) {
+                        let rate = 0.6 + 0.8 * rng.next_f64();
+                        input.push_str(&format!("c{} c{} {}\n", a, b, rate));
+                    }
+                }
+            }
+            let table = RateTable::parse(&input).unwrap();
+            let graph = table.graph();
+            let n = graph.node_count();
+            let mut dist = vec![vec![f64::INFINITY; n]; n];
+            for (id, src, dest, _) in graph.edges() {
+                let w = -table.rate(id).ln();
+                dist[src.index()][dest.index()] = dist[src.index()][dest.index()].min(w);
+            }
+            for k in 0..n {
+                for i in 0..n {
+                    for j in 0..n {
+                        dist[i][j] = dist[i][j].min(dist[i][k] + dist[k][j]);
+                    }
+                }
+            }
+            let profitable = (0..n).any(|i| dist[i][i] < -1e-9);
+
+            let found = find_arbitrage(&table, 3);
+            assert_eq!(!found.is_empty(), profitable);
+            for cycle in found.iter() {
+                assert!(cycle.profit > 1.0);
+                assert_eq!(cycle.currencies.len(), cycle.trades.len() + 1);
+                assert_eq!(cycle.currencies.first(), cycle.currencies.last());
+                for (i, &trade) in cycle.trades.iter().enumerate() {
+                    let endpoints = (cycle.currencies[i], cycle.currencies[i + 1]);
+                    assert_eq!(graph.edge_endpoints(trade), Some(endpoints));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn finds_a_known_cycle() {
+        let table = RateTable::parse("# rates\nusd eur 0.9\neur gbp 0.9\ngbp usd 1.3\nusd gbp 0.5")
+            .unwrap();
+        let found = find_arbitrage(&table, 5);
+        assert_eq!(found.len(), 1);
+        assert!((found[0].profit - 0.9 * 0.9 * 1.3).abs() < 1e-12);
+        assert_eq!(
+            found[0].trades,
+            [EdgeId::new(0), EdgeId::new(1), EdgeId::new(2)]
+        );
+
+        assert!(RateTable::parse("usd eur 0").is_err());
+        assert!(RateTable::parse("usd eur").is_err());
+    }
+}
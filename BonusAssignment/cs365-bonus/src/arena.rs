@@ -0,0 +1,119 @@
+use crate::graph::Weight;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+
+const NO_NODE: u32 = u32::MAX;
+
+/// Bump arena for parse-time temporaries.
+///
+/// Node names are appended to a single string buffer and edges to a single flat
+/// buffer, so parsing a file does not allocate per node or per edge. The final
+/// graph is built from the arena in one pass with right-sized allocations.
+pub struct ParseArena {
+    names: String,
+    name_ends: Vec<usize>,
+    buckets: HashMap<u64, u32>,
+    chain: Vec<u32>,
+    edges: Vec<(u32, u32, Weight, bool)>,
+}
+
+impl ParseArena {
+    /// Create an arena sized for an input of `bytes` bytes.
+    pub fn with_capacity(bytes: usize) -> Self {
+        // Roughly 8 bytes per edge line is a conservative lower bound for "a b 1\n".
+        let lines = bytes / 8;
+        Self {
+            names: String::with_capacity(bytes / 4),
+            name_ends: Vec::new(),
+            buckets: HashMap::new(),
+            chain: Vec::new(),
+            edges: Vec::with_capacity(lines),
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.name_ends.len()
+    }
+
+    pub fn name(&self, id: u32) -> &str {
+        let id = id as usize;
+        let start = if id == 0 { 0 } else { self.name_ends[id - 1] };
+        &self.names[start..self.name_ends[id]]
+    }
+
+    /// Intern a name, copying it into the arena on first sight.
+    ///
+    /// Returns `None` when the name is new but every id below the `NO_NODE` sentinel is
+    /// already taken.
+    pub fn intern(&mut self, name: &str) -> Option<u32> {
+        let hash = hash_str(name);
+        let mut cursor = self.buckets.get(&hash).copied().unwrap_or(NO_NODE);
+        while cursor != NO_NODE {
+            if self.name(cursor) == name {
+                return Some(cursor);
+            }
+            cursor = self.chain[cursor as usize];
+        }
+
+        let id = u32::try_from(self.name_ends.len())
+            .ok()
+            .filter(|&id| id != NO_NODE)?;
+        self.names.push_str(name);
+        self.name_ends.push(self.names.len());
+        self.chain
+            .push(self.buckets.get(&hash).copied().unwrap_or(NO_NODE));
+        self.buckets.insert(hash, id);
+        Some(id)
+    }
+
+    pub fn push_edge(&mut self, src: u32, dest: u32, cost: Weight, directed: bool) {
+        self.edges.push((src, dest, cost, directed));
+    }
+
+    /// Node names in insertion order, each allocated at its exact length.
+    pub fn take_names(&self) -> Vec<String> {
+        (0..self.node_count() as u32)
+            .map(|id| self.name(id).to_string())
+            .collect()
+    }
+
+    pub fn edges(&self) -> &[(u32, u32, Weight, bool)] {
+        &self.edges
+    }
+}
+
+fn hash_str(s: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    s.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interns_each_name_once() {
+        let mut arena = ParseArena::with_capacity(0);
+        let names: Vec<String> = (0..1000).map(|i| format!("n{}", i % 300)).collect();
+        let ids: Vec<u32> = names
+            .iter()
+            .map(|name| arena.intern(name).unwrap())
+            .collect();
+        assert_eq!(arena.node_count(), 300);
+        for (name, &id) in names.iter().zip(&ids) {
+            assert_eq!(arena.name(id), name);
+            assert_eq!(arena.intern(name), Some(id));
+        }
+        // Ids follow first sight, and the empty name is a name like any other.
+        assert_eq!(ids[..3], [0, 1, 2]);
+        assert_eq!(arena.intern(""), Some(300));
+        assert_eq!(arena.take_names()[299], "n299");
+        assert_eq!(arena.take_names()[300], "");
+
+        arena.push_edge(ids[0], ids[1], -4, true);
+        assert_eq!(arena.edges(), [(0, 1, -4, true)]);
+    }
+}
@@ -0,0 +1,384 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::{shortest_path_tree, Path};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+
+/// Lower bounds on distances from precomputed distances to and from a few landmark nodes,
+/// the ALT heuristic (Goldberg and Harrelson, 2005).
+///
+/// By the triangle inequality, `d(u, t) >= d(l, t) - d(l, u)` and `d(u, t) >= d(u, l) -
+/// d(t, l)` for every landmark `l`, and the largest of these bounds is a consistent
+/// heuristic for A*. Landmarks are picked one at a time as the node farthest from those
+/// already picked, which tends to put them on the edges of the graph, behind the targets.
+/// Costs must not be negative.
+///
+/// ```
+/// use cs365_bonus::astar::Landmarks;
+///
+/// let graph: cs365_bonus::Graph = "a b 2\nb c 3\nc d 4".parse().unwrap();
+/// let landmarks = Landmarks::select(&graph, 1);
+/// let [a, b, d] = ["a", "b", "d"].map(|name| graph.get_node(name).unwrap());
+/// assert_eq!(landmarks.nodes(), [d]);
+/// assert_eq!(landmarks.lower_bound(b, d), 7);
+/// assert_eq!(landmarks.lower_bound(a, b), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Landmarks {
+    nodes: Vec<NodeId>,
+    /// `from[i][v]` is the distance from landmark `i` to `v`.
+    from: Vec<Vec<Option<Weight>>>,
+    /// `to[i][v]` is the distance from `v` to landmark `i`.
+    to: Vec<Vec<Option<Weight>>>,
+}
+
+impl Landmarks {
+    /// Pick up to `count` landmarks and find the distances to and from each.
+    pub fn select(graph: &Graph, count: usize) -> Self {
+        let mut landmarks = Landmarks::default();
+        let first = match graph.node_ids().next() {
+            Some(first) if count > 0 => first,
+            _ => return landmarks,
+        };
+        let reversed = graph.reversed();
+        // The distance from the nearest landmark so far, starting from an arbitrary node
+        // so that the first landmark is already far out. Unreached nodes count as farthest.
+        let mut nearest = shortest_path_tree(graph, first).distances().to_vec();
+        while landmarks.nodes.len() < count {
+            let farthest = graph
+                .node_ids()
+                .filter(|node| !landmarks.nodes.contains(node))
+                .max_by_key(|node| (nearest[node.index()].unwrap_or(Weight::MAX), Reverse(*node)));
+            let landmark = match farthest {
+                Some(landmark) => landmark,
+                None => break,
+            };
+            let from = shortest_path_tree(graph, landmark).distances().to_vec();
+            for (nearest, &distance) in nearest.iter_mut().zip(&from) {
+                if let Some(distance) = distance {
+                    *nearest = Some(nearest.map_or(distance, |old| old.min(distance)));
+                }
+            }
+            landmarks.nodes.push(landmark);
+            landmarks.from.push(from);
+            landmarks
+                .to
+                .push(shortest_path_tree(&reversed, landmark).distances().to_vec());
+        }
+        landmarks
+    }
+
+    pub fn nodes(&self) -> &[NodeId] {
+        &self.nodes
+    }
+
+    /// A lower bound on the distance from `node` to `target`, or 0 when no landmark gives
+    /// one.
+    pub fn lower_bound(&self, node: NodeId, target: NodeId) -> Weight {
+        let (u, t) = (node.index(), target.index());
+        (0..self.nodes.len())
+            .filter_map(|i| {
+                let (from, to) = (&self.from[i], &self.to[i]);
+                let ahead = from[t].zip(from[u]).map(|(lt, lu)| lt - lu);
+                let behind = to[u].zip(to[t]).map(|(ul, tl)| ul - tl);
+                ahead.max(behind)
+            })
+            .max()
+            .unwrap_or(0)
+            .max(0)
+    }
+}
+
+/// A path found by a bounded-suboptimal search, and how close to optimal it is known to be.
+#[derive(Debug)]
+pub struct Improvement {
+    pub path: Path,
+    /// The weight the heuristic was inflated by.
+    pub epsilon: f64,
+    /// A proven bound on the path's cost over the optimal cost, 1 when the path is optimal.
+    /// It is at most `epsilon` unless a deadline cut the round short, and may then be
+    /// infinite.
+    pub suboptimality: f64,
+    /// The nodes expanded in finding this path.
+    pub expanded: usize,
+}
+
+/// Where a node stands in the current round of an [`AnytimeSearch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Idle,
+    Open,
+    Closed,
+    /// Closed, but its distance has improved since, so its successors are out of date.
+    Inconsistent,
+}
+
+/// Scales keys so that `epsilon` can be applied in integers, to three decimal places.
+const SCALE: i128 = 1000;
+
+/// Anytime Repairing A* (Likhachev, Gordon, and Thrun, 2003): a series of weighted A*
+/// searches with a shrinking inflation factor, each reusing the work of the last.
+///
+/// A search with heuristic weight `epsilon` expands fewer nodes than A* but finds a path
+/// costing at most `epsilon` times the optimal, provided the heuristic is consistent and
+/// costs are non-negative. Each round yields a path no worse than the last, along with the
+/// suboptimality it actually achieved, which is often much less than `epsilon`: the ratio
+/// of its cost to the smallest `g + h` among the nodes left open. The first round alone is
+/// plain weighted A*. The search ends once a path is proven optimal, or at once if the
+/// target can't be reached.
+///
+/// With a [deadline](Self::with_deadline), a round still running when time is up stops
+/// early and yields its path if it is cheaper than the last, and the search ends there.
+///
+/// ```
+/// use cs365_bonus::astar::{AnytimeSearch, Landmarks};
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb z 9\na c 3\nc z 3".parse().unwrap();
+/// let [a, z] = ["a", "z"].map(|name| graph.get_node(name).unwrap());
+/// let landmarks = Landmarks::select(&graph, 2);
+/// let search = AnytimeSearch::new(&graph, a, z, |node| landmarks.lower_bound(node, z), 3.0, 1.0);
+/// let last = search.last().unwrap();
+/// assert_eq!((last.path.cost, last.suboptimality), (6, 1.0));
+/// ```
+pub struct AnytimeSearch<'a, H> {
+    graph: &'a Graph,
+    target: NodeId,
+    heuristic: H,
+    epsilon: f64,
+    step: f64,
+    distance: Vec<Weight>,
+    parent: Vec<Option<NodeId>>,
+    state: Vec<State>,
+    deadline: Option<Instant>,
+    /// The best proven lower bound on the optimal cost.
+    lower: Weight,
+    /// The cost of the last path yielded.
+    best: Weight,
+    finished: bool,
+}
+
+impl<'a, H: Fn(NodeId) -> Weight> AnytimeSearch<'a, H> {
+    /// Search from `start` to `target`, first with heuristic weight `epsilon` and then
+    /// `step` less each round, down to 1. `heuristic` estimates the distance from a node to
+    /// `target` and must be consistent.
+    pub fn new(
+        graph: &'a Graph,
+        start: NodeId,
+        target: NodeId,
+        heuristic: H,
+        epsilon: f64,
+        step: f64,
+    ) -> Self {
+        assert!(epsilon >= 1.0, "epsilon must be at least 1");
+        let n = graph.node_count();
+        let mut search = Self {
+            graph,
+            target,
+            heuristic,
+            epsilon,
+            step,
+            distance: vec![Weight::MAX; n],
+            parent: vec![None; n],
+            state: vec![State::Idle; n],
+            deadline: None,
+            lower: 0,
+            best: Weight::MAX,
+            finished: false,
+        };
+        search.lower = (search.heuristic)(start);
+        search.distance[start.index()] = 0;
+        search.state[start.index()] = State::Open;
+        search
+    }
+
+    /// Stop searching at `deadline`.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    fn key(&self, node: NodeId, epsilon: i128) -> i128 {
+        let h = (self.heuristic)(node) as i128;
+        (self.distance[node.index()] as i128 * SCALE).saturating_add(epsilon.saturating_mul(h))
+    }
+
+    /// Expand nodes until none left open could lead to a path cheaper than the current one
+    /// by more than a factor of `epsilon`, or until the deadline. Returns how many were
+    /// expanded and whether the deadline cut the round short.
+    fn improve_path(&mut self, epsilon: f64) -> (usize, bool) {
+        let epsilon = (epsilon * SCALE as f64).round() as i128;
+        let mut open: BinaryHeap<Reverse<(i128, NodeId)>> = self
+            .graph
+            .node_ids()
+            .filter(|node| self.state[node.index()] == State::Open)
+            .map(|node| Reverse((self.key(node, epsilon), node)))
+            .collect();
+        let mut expanded = 0;
+        while let Some(&Reverse((key, node))) = open.peek() {
+            let target = self.distance[self.target.index()];
+            if target != Weight::MAX && key >= target as i128 * SCALE {
+                break;
+            }
+            // Reading the clock on every expansion would be a noticeable share of the work.
+            if expanded % 256 == 0 && self.deadline.is_some_and(|at| Instant::now() >= at) {
+                return (expanded, true);
+            }
+            open.pop();
+            if self.state[node.index()] != State::Open || key != self.key(node, epsilon) {
+                continue;
+            }
+            self.state[node.index()] = State::Closed;
+            expanded += 1;
+            let distance = self.distance[node.index()];
+            for edge in self.graph.edges_of(node) {
+                let next = edge.node.index();
+                if distance + edge.cost >= self.distance[next] {
+                    continue;
+                }
+                self.distance[next] = distance + edge.cost;
+                self.parent[next] = Some(node);
+                match self.state[next] {
+                    State::Closed => self.state[next] = State::Inconsistent,
+                    State::Inconsistent => {}
+                    State::Idle | State::Open => {
+                        self.state[next] = State::Open;
+                        open.push(Reverse((self.key(edge.node, epsilon), edge.node)));
+                    }
+                }
+            }
+        }
+        (expanded, false)
+    }
+
+    /// The path to the target along parent pointers.
+    fn path(&self) -> Option<Path> {
+        let mut nodes = vec![self.target];
+        let mut node = self.target;
+        while let Some(parent) = self.parent[node.index()] {
+            nodes.push(parent);
+            node = parent;
+            if nodes.len() > self.graph.node_count() {
+                return None;
+            }
+        }
+        nodes.reverse();
+        Path::from_nodes(self.graph, nodes)
+    }
+}
+
+impl<H: Fn(NodeId) -> Weight> Iterator for AnytimeSearch<'_, H> {
+    type Item = Improvement;
+
+    fn next(&mut self) -> Option<Improvement> {
+        if self.finished {
+            return None;
+        }
+        let (expanded, interrupted) = self.improve_path(self.epsilon);
+        let cost = self.distance[self.target.index()];
+        if cost == Weight::MAX || interrupted && cost >= self.best {
+            self.finished = true;
+            return None;
+        }
+        let path = self.path()?;
+        self.best = path.cost;
+        if interrupted {
+            // The path may be cheaper than the last, but the open nodes prove nothing new
+            // until the round is done.
+            self.finished = true;
+            return Some(Improvement {
+                suboptimality: ratio(path.cost, self.lower),
+                path,
+                epsilon: self.epsilon,
+                expanded,
+            });
+        }
+
+        // Any cheaper path passes through a node left open or made inconsistent.
+        let lower = self
+            .graph
+            .node_ids()
+            .filter(|node| matches!(self.state[node.index()], State::Open | State::Inconsistent))
+            .map(|node| self.distance[node.index()] + (self.heuristic)(node))
+            .min()
+            .map_or(path.cost, |lower| lower.min(path.cost));
+        self.lower = self.lower.max(lower);
+        let suboptimality = ratio(path.cost, self.lower).min(self.epsilon);
+        let found = Improvement {
+            path,
+            epsilon: self.epsilon,
+            suboptimality,
+            expanded,
+        };
+
+        self.finished = suboptimality <= 1.0 || self.epsilon <= 1.0 || self.step <= 0.0;
+        self.epsilon = (self.epsilon - self.step).max(1.0);
+        for state in self.state.iter_mut() {
+            *state = match *state {
+                State::Closed => State::Idle,
+                State::Inconsistent => State::Open,
+                state => state,
+            };
+        }
+        Some(found)
+    }
+}
+
+/// How many times `lower` `cost` is, infinite if `lower` is 0 and `cost` isn't.
+fn ratio(cost: Weight, lower: Weight) -> f64 {
+    if cost == lower {
+        1.0
+    } else {
+        cost as f64 / lower as f64
+    }
+}
+
+/// The path weighted A* finds from `start` to `target`, costing at most `epsilon` times the
+/// optimal, or `None` if there is none.
+pub fn weighted_astar(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    heuristic: impl Fn(NodeId) -> Weight,
+    epsilon: f64,
+) -> Option<Improvement> {
+    AnytimeSearch::new(graph, start, target, heuristic, epsilon, 0.0).next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn stays_within_the_bound() {
+        let mut rng = Rng::new(7);
+        let mut graph = Graph::new();
+        let nodes: Vec<NodeId> = (0..80)
+            .map(|i| graph.get_or_insert_node(&i.to_string()))
+            .collect();
+        for _ in 0..300 {
+            let cost = rng.below(20) as Weight;
+            graph.add_edge(nodes[rng.below(80)], nodes[rng.below(80)], cost);
+        }
+        let landmarks = Landmarks::select(&graph, 3);
+        for &start in &nodes[..10] {
+            let tree = shortest_path_tree(&graph, start);
+            for &target in &nodes {
+                let h = |node| landmarks.lower_bound(node, target);
+                let optimal = tree.distance(target);
+                assert!(optimal.is_none_or(|d| h(start) <= d));
+                let rounds: Vec<Improvement> =
+                    AnytimeSearch::new(&graph, start, target, h, 2.5, 0.5).collect();
+                assert_eq!(rounds.is_empty(), optimal.is_none());
+                for round in &rounds {
+                    let optimal = optimal.unwrap() as f64;
+                    assert!(round.path.cost as f64 <= round.suboptimality * optimal + 1e-9);
+                    assert!(round.suboptimality <= round.epsilon);
+                }
+                if let Some(last) = rounds.last() {
+                    assert_eq!(Some(last.path.cost), optimal);
+                }
+            }
+        }
+    }
+}
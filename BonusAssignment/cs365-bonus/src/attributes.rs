@@ -0,0 +1,308 @@
+use crate::decimal::FixedPoint;
+use crate::expr::Expr;
+use crate::graph::{Edge, EdgeId, Graph, NodeId, Weight};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// Named values attached to nodes and edges, such as an edge's `speed` or a node's
+/// `elevation`, kept beside a graph rather than in it.
+///
+/// ```
+/// use cs365_bonus::attributes::Attributes;
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1".parse().unwrap();
+/// let attributes = Attributes::parse(&graph, "node a elevation=120\nedge a b label=ferry").unwrap();
+/// let (a, b) = (graph.get_node("a").unwrap(), graph.get_node("b").unwrap());
+/// assert_eq!(attributes.node(a, "elevation"), Some("120"));
+/// assert_eq!(attributes.edge(graph.find_edge(b, a).unwrap(), "label"), Some("ferry"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attributes {
+    nodes: HashMap<NodeId, BTreeMap<String, String>>,
+    edges: HashMap<EdgeId, BTreeMap<String, String>>,
+}
+
+impl Attributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read attributes for the nodes and edges of `graph`, one node or edge per line:
+    ///
+    /// ```text
+    /// node <name> <key>=<value> ...
+    /// edge <from> <to> <key>=<value> ...
+    /// ```
+    ///
+    /// An edge line applies to every edge from `from` to `to`, which includes both
+    /// directions of an undirected edge. Blank lines and lines starting with `#` are
+    /// skipped, and later values replace earlier ones.
+    pub fn parse(graph: &Graph, text: &str) -> Result<Self, String> {
+        let mut attributes = Self::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| format!("line {}: {}", i + 1, message);
+            let node = |name: &str| {
+                graph
+                    .get_node(name)
+                    .ok_or_else(|| error(format!("unknown node '{}'", name)))
+            };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (edges, pairs) = match words.as_slice() {
+                ["node", name, pairs @ ..] => {
+                    let node = node(name)?;
+                    for (key, value) in key_values(pairs).map_err(error)? {
+                        attributes.set_node(node, key, value);
+                    }
+                    continue;
+                }
+                ["edge", from, to, pairs @ ..] => {
+                    let (from, to) = (node(from)?, node(to)?);
+                    let edges: Vec<EdgeId> = graph
+                        .edges_of(from)
+                        .iter()
+                        .filter(|edge| edge.node == to)
+                        .map(|edge| edge.id)
+                        .collect();
+                    if edges.is_empty() {
+                        return Err(error(format!(
+                            "there is no edge '{}-{}'",
+                            words[1], words[2]
+                        )));
+                    }
+                    (edges, pairs)
+                }
+                _ => {
+                    return Err(error(
+                        "expected 'node <name> ...' or 'edge <from> <to> ...'".into(),
+                    ))
+                }
+            };
+            for (key, value) in key_values(pairs).map_err(error)? {
+                for &edge in &edges {
+                    attributes.set_edge(edge, key, value);
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    pub fn set_node(&mut self, node: NodeId, key: &str, value: &str) {
+        let values = self.nodes.entry(node).or_default();
+        values.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn set_edge(&mut self, edge: EdgeId, key: &str, value: &str) {
+        let values = self.edges.entry(edge).or_default();
+        values.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn node(&self, node: NodeId, key: &str) -> Option<&str> {
+        self.nodes.get(&node)?.get(key).map(String::as_str)
+    }
+
+    pub fn edge(&self, edge: EdgeId, key: &str) -> Option<&str> {
+        self.edges.get(&edge)?.get(key).map(String::as_str)
+    }
+
+    /// The value of `name` for the edge `id` from `src` to `dest` costing `cost`, as
+    /// [`weigh`] evaluates it.
+    fn lookup(&self, name: &str, id: EdgeId, src: NodeId, dest: NodeId, cost: f64) -> Option<f64> {
+        let value = if name == "cost" {
+            return Some(cost);
+        } else if let Some(key) = name.strip_prefix("src.") {
+            self.node(src, key)
+        } else if let Some(key) = name.strip_prefix("dest.") {
+            self.node(dest, key)
+        } else {
+            self.edge(id, name)
+        };
+        value?.parse().ok()
+    }
+}
+
+fn key_values<'a>(pairs: &[&'a str]) -> Result<Vec<(&'a str, &'a str)>, String> {
+    pairs
+        .iter()
+        .map(|pair| {
+            pair.split_once('=')
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| format!("expected 'key=value', got '{}'", pair))
+        })
+        .collect()
+}
+
+/// A copy of `graph` with each edge's weight replaced by the value of `expr`.
+///
+/// In `expr`, `cost` is the edge's current cost, `src.<key>` and `dest.<key>` are
+/// attributes of the nodes it leaves and enters, and any other name is an attribute of the
+/// edge itself. Undirected edges are evaluated in both directions, and become a pair of
+/// one-way edges if the two differ, e.g. when climbing costs more than descending; edge
+/// ids after the first such edge then shift. Costs are read and written with `scale`.
+/// Fails on the first edge missing a numeric attribute the expression needs, or whose new
+/// weight is not finite or too large to store.
+///
+/// ```
+/// use cs365_bonus::attributes::{weigh, Attributes};
+/// use cs365_bonus::decimal::FixedPoint;
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1".parse().unwrap();
+/// let attributes =
+///     Attributes::parse(&graph, "edge a b distance=60 speed=30\nedge b c distance=60 speed=60")
+///         .unwrap();
+/// let expr = "distance / speed".parse().unwrap();
+/// let timed = weigh(&graph, &attributes, &expr, FixedPoint::INTEGER).unwrap();
+/// let costs: Vec<_> = timed.edges().map(|(_, _, _, cost)| cost).collect();
+/// assert_eq!(costs, [2, 1]);
+/// ```
+pub fn weigh(
+    graph: &Graph,
+    attributes: &Attributes,
+    expr: &Expr,
+    scale: FixedPoint,
+) -> Result<Graph, String> {
+    let mut weighed = Graph::with_capacity(graph.node_count(), graph.edge_count());
+    for node in graph.node_ids() {
+        weighed.get_or_insert_node(graph.get_node_name(node).unwrap_or(""));
+    }
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+    let weight = |id, src, dest, cost| {
+        let edge = || format!("edge '{}-{}'", name(src), name(dest));
+        let value = expr
+            .eval(&mut |key| attributes.lookup(key, id, src, dest, scale.to_f64(cost)))
+            .map_err(|e| format!("{}: {}", edge(), e))?;
+        scale
+            .from_f64(value)
+            .ok_or_else(|| format!("{} would get invalid weight {}", edge(), value))
+    };
+    for (id, src, dest, cost) in graph.edges() {
+        let forward = weight(id, src, dest, cost)?;
+        if graph.is_edge_directed(id) == Some(true) {
+            weighed.add_edge(src, dest, forward);
+            continue;
+        }
+        let backward = weight(id, dest, src, cost)?;
+        if forward == backward {
+            weighed.add_bidirectional_edge(src, dest, forward);
+        } else {
+            weighed.add_edge(src, dest, forward);
+            weighed.add_edge(dest, src, backward);
+        }
+    }
+    Ok(weighed)
+}
+
+/// How much a climb adds to an edge's cost, for cyclist-style routes that avoid hills,
+/// given each node's `elevation` attribute.
+///
+/// Crossing an edge costs its own cost, plus `uphill` per unit of elevation gained, plus
+/// `downhill` per unit lost.
+///
+/// ```
+/// use cs365_bonus::attributes::{weigh, Attributes, ClimbModel};
+/// use cs365_bonus::decimal::FixedPoint;
+/// use cs365_bonus::find_shortest_path;
+///
+/// // Over the hill at b is shorter, but around through c stays flat.
+/// let graph: cs365_bonus::Graph = "a b 1\nb z 1\na c 2\nc z 2".parse().unwrap();
+/// let heights = "node a elevation=0\nnode b elevation=30\nnode c elevation=0\nnode z elevation=0";
+/// let attributes = Attributes::parse(&graph, heights).unwrap();
+/// let model = ClimbModel { uphill: 0.1, downhill: 0.0 };
+/// let cycling = weigh(&graph, &attributes, &model.expr(), FixedPoint::INTEGER).unwrap();
+/// let (a, z) = (cycling.get_node("a").unwrap(), cycling.get_node("z").unwrap());
+/// let path = find_shortest_path(&cycling, a, z).unwrap();
+/// assert_eq!((path.nodes[1], path.cost), (cycling.get_node("c").unwrap(), 4));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClimbModel {
+    pub uphill: f64,
+    pub downhill: f64,
+}
+
+impl ClimbModel {
+    /// This model as an expression for [`weigh`].
+    pub fn expr(&self) -> Expr {
+        format!(
+            "cost + {} * max(0, dest.elevation - src.elevation) \
+             + {} * max(0, src.elevation - dest.elevation)",
+            self.uphill, self.downhill
+        )
+        .parse()
+        .expect("climb model expressions are well formed")
+    }
+}
+
+/// Multipliers for the cost of edges by their `label` attribute, such as
+/// `highway=0.8,ferry=3.0`, for routing by a user's preferences.
+///
+/// Edges without a label, or with one not listed, keep their cost.
+///
+/// ```
+/// use cs365_bonus::attributes::{Attributes, WeightFactors};
+/// use cs365_bonus::view::Reweighted;
+/// use cs365_bonus::find_shortest_path;
+///
+/// let graph: cs365_bonus::Graph = "a b 2\nb z 2\na z 3".parse().unwrap();
+/// let attributes = Attributes::parse(&graph, "edge a z label=ferry").unwrap();
+/// let factors: WeightFactors = "ferry=2".parse().unwrap();
+/// let view = Reweighted::new(&graph, |edge| factors.apply(&attributes, edge));
+/// let (a, z) = (graph.get_node("a").unwrap(), graph.get_node("z").unwrap());
+/// assert_eq!(find_shortest_path(&view, a, z).unwrap().nodes.len(), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WeightFactors {
+    factors: HashMap<String, f64>,
+}
+
+impl WeightFactors {
+    /// Multiply the cost of edges labelled `label` by `factor`, which must be finite and
+    /// not negative, so searches can still use Dijkstra.
+    pub fn insert(&mut self, label: &str, factor: f64) -> Result<(), String> {
+        if !(factor >= 0.0 && factor.is_finite()) {
+            return Err(format!(
+                "the factor for '{}' must be a non-negative number, got {}",
+                label, factor
+            ));
+        }
+        self.factors.insert(label.to_string(), factor);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+
+    /// The cost of `edge` after applying the factor for its label, rounded to a whole
+    /// weight.
+    pub fn apply(&self, attributes: &Attributes, edge: &Edge) -> Weight {
+        match attributes
+            .edge(edge.id, "label")
+            .and_then(|label| self.factors.get(label))
+        {
+            Some(factor) => (edge.cost as f64 * factor).round() as Weight,
+            None => edge.cost,
+        }
+    }
+}
+
+/// Parses comma-separated `label=factor` pairs.
+impl FromStr for WeightFactors {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut factors = Self::default();
+        for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (label, factor) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("expected 'label=factor', got '{}'", pair))?;
+            let factor = factor
+                .parse()
+                .map_err(|_| format!("invalid factor '{}' for '{}'", factor, label))?;
+            factors.insert(label, factor)?;
+        }
+        Ok(factors)
+    }
+}
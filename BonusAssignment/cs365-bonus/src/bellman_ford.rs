@@ -0,0 +1,233 @@
+use crate::graph::{EdgeId, Graph, GraphRef, NodeId, Weight};
+use crate::path::ShortestPathTree;
+
+/// Differences smaller than this are treated as rounding noise rather than improvements.
+const EPSILON: f64 = 1e-12;
+
+/// Find a cycle of negative total weight with Bellman-Ford, returning its edges in order.
+///
+/// Weights come from `weight`, and edges it maps to `None` are ignored. Every node starts
+/// at distance zero, as if joined to a virtual source, so cycles are found anywhere in the
+/// graph. Undirected edges can be followed both ways, so any negative undirected edge is
+/// itself a negative cycle.
+pub fn find_negative_cycle(
+    graph: &Graph,
+    weight: impl Fn(EdgeId) -> Option<f64>,
+) -> Option<Vec<EdgeId>> {
+    let n = graph.node_count();
+    let mut distance = vec![0.0; n];
+    let mut parent: Vec<Option<(NodeId, EdgeId)>> = vec![None; n];
+
+    let mut last = None;
+    for _ in 0..=n {
+        let mut changed = false;
+        for node in graph.node_ids() {
+            for edge in graph.edges_of(node) {
+                let w = match weight(edge.id) {
+                    Some(w) => w,
+                    None => continue,
+                };
+                let next = distance[node.index()] + w;
+                if next < distance[edge.node.index()] - EPSILON {
+                    distance[edge.node.index()] = next;
+                    parent[edge.node.index()] = Some((node, edge.id));
+                    last = Some(edge.node);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return None;
+        }
+    }
+
+    // Still relaxing after n rounds, so following parents from the last updated node
+    // eventually enters a cycle. Walking n steps guarantees we are on it.
+    let mut node = last?;
+    for _ in 0..n {
+        node = parent[node.index()]?.0;
+    }
+    let start = node;
+    let mut cycle = Vec::new();
+    loop {
+        let (prev, edge) = parent[node.index()]?;
+        cycle.push(edge);
+        node = prev;
+        if node == start {
+            break;
+        }
+    }
+    cycle.reverse();
+    Some(cycle)
+}
+
+/// Error returned when a cycle of negative total cost is reachable from the source, so
+/// some distances are unbounded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle {
+    nodes: Vec<NodeId>,
+}
+
+impl NegativeCycle {
+    /// The nodes of the cycle in order; the last one leads back to the first.
+    pub fn nodes(&self) -> &[NodeId] {
+        &self.nodes
+    }
+}
+
+impl std::fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "a negative cycle of {} nodes is reachable from the source",
+            self.nodes.len()
+        )
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
+
+/// Shortest paths from `start` to every reachable node with Bellman-Ford, allowing
+/// negative edge costs.
+///
+/// Takes O(nm) time, against Dijkstra's O(m log n), but stops early once a round changes
+/// nothing. As with [`find_negative_cycle`], a negative undirected edge is a negative
+/// cycle on its own.
+pub fn bellman_ford<G: GraphRef + ?Sized>(
+    graph: &G,
+    start: NodeId,
+) -> Result<ShortestPathTree, NegativeCycle> {
+    let n = graph.node_count();
+    let mut distance: Vec<Option<Weight>> = vec![None; n];
+    let mut parent: Vec<Option<NodeId>> = vec![None; n];
+    if !graph.contains_node(start) {
+        return Ok(ShortestPathTree::new(start, distance, parent));
+    }
+    distance[start.index()] = Some(0);
+
+    // Shortest simple paths have at most n - 1 edges, so a change in round n means a
+    // negative cycle.
+    for round in 0..n {
+        let mut last = None;
+        for i in 0..n {
+            let node = NodeId::new(i);
+            let cost = match distance[i] {
+                Some(cost) => cost,
+                None => continue,
+            };
+            for edge in graph.edges_of(node) {
+                let next = cost + graph.cost(edge);
+                if distance[edge.node.index()].is_none_or(|best| next < best) {
+                    distance[edge.node.index()] = Some(next);
+                    parent[edge.node.index()] = Some(node);
+                    last = Some(edge.node);
+                }
+            }
+        }
+        match last {
+            None => break,
+            Some(node) if round == n - 1 => return Err(cycle_through(&parent, node, n)),
+            Some(_) => {}
+        }
+    }
+    Ok(ShortestPathTree::new(start, distance, parent))
+}
+
+/// The parent cycle reached from `node`, which was relaxed in the last Bellman-Ford round.
+fn cycle_through(parent: &[Option<NodeId>], mut node: NodeId, n: usize) -> NegativeCycle {
+    for _ in 0..n {
+        node = parent[node.index()].unwrap_or(node);
+    }
+    let start = node;
+    let mut nodes = vec![start];
+    while let Some(prev) = parent[node.index()].filter(|&prev| prev != start) {
+        nodes.push(prev);
+        node = prev;
+    }
+    nodes.reverse();
+    NegativeCycle { nodes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn matches_floyd_warshall() {
+        let mut rng = Rng::new(35);
+        for _ in 0..100 {
+            let n = 1 + rng.below(8);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(2 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                graph.add_edge(a, b, rng.below(14) as Weight - 3);
+            }
+            let mut dist: Vec<Vec<Option<Weight>>> = vec![vec![None; n]; n];
+            for (_, src, dest, cost) in graph.edges() {
+                let d = &mut dist[src.index()][dest.index()];
+                *d = Some(d.map_or(cost, |d| d.min(cost)));
+            }
+            for (i, row) in dist.iter_mut().enumerate() {
+                row[i] = Some(row[i].map_or(0, |d| d.min(0)));
+            }
+            for k in 0..n {
+                for i in 0..n {
+                    for j in 0..n {
+                        if let (Some(a), Some(b)) = (dist[i][k], dist[k][j]) {
+                            dist[i][j] = Some(dist[i][j].map_or(a + b, |d| d.min(a + b)));
+                        }
+                    }
+                }
+            }
+            let on_cycle = |v: usize| dist[v][v].is_some_and(|d| d < 0);
+
+            let start = NodeId::new(rng.below(n));
+            let reaches_cycle = (0..n).any(|v| dist[start.index()][v].is_some() && on_cycle(v));
+            match bellman_ford(&graph, start) {
+                Ok(tree) => {
+                    assert!(!reaches_cycle);
+                    assert_eq!(tree.distances(), dist[start.index()]);
+                }
+                Err(cycle) => {
+                    assert!(reaches_cycle);
+                    let nodes = cycle.nodes();
+                    let mut total = 0;
+                    for (i, &node) in nodes.iter().enumerate() {
+                        let next = nodes[(i + 1) % nodes.len()];
+                        let edges = graph.edges_of(node).iter().filter(|e| e.node == next);
+                        total += edges.map(|e| e.cost).min().expect("cycle follows edges");
+                    }
+                    assert!(total < 0);
+                }
+            }
+
+            let found = find_negative_cycle(&graph, |id| graph.edge_weight(id).map(|w| w as f64));
+            assert_eq!(found.is_some(), (0..n).any(on_cycle));
+            if let Some(edges) = found {
+                for (i, &edge) in edges.iter().enumerate() {
+                    let (_, dest) = graph.edge_endpoints(edge).unwrap();
+                    let next = edges[(i + 1) % edges.len()];
+                    assert_eq!(graph.edge_endpoints(next).unwrap().0, dest);
+                }
+                let total: Weight = edges.iter().map(|&e| graph.edge_weight(e).unwrap()).sum();
+                assert!(total < 0);
+            }
+        }
+    }
+
+    #[test]
+    fn negative_undirected_edges_are_cycles() {
+        let graph: Graph = "a b 2\nb c -1".parse().unwrap();
+        let a = graph.get_node("a").unwrap();
+        assert_eq!(bellman_ford(&graph, a).unwrap_err().nodes().len(), 2);
+        let found = find_negative_cycle(&graph, |id| graph.edge_weight(id).map(|w| w as f64));
+        assert_eq!(found.unwrap().len(), 2);
+        // Ignoring the negative edge leaves nothing to find.
+        let found = find_negative_cycle(&graph, |id| (id.index() == 0).then_some(2.0));
+        assert!(found.is_none());
+    }
+}
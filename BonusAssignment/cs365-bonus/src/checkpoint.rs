@@ -0,0 +1,174 @@
+use crate::graph::{Graph, Weight};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &str = "cs365-checkpoint 1";
+
+/// A stable fingerprint of a graph's names, edges, and weights.
+///
+/// Checkpoints record this so a job can't be resumed against a different input.
+pub fn graph_fingerprint(graph: &Graph) -> u64 {
+    // FNV-1a, chosen over `DefaultHasher` because it is stable across builds.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for &b in bytes {
+            hash ^= u64::from(b);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    for node in graph.node_ids() {
+        feed(graph.get_node_name(node).unwrap_or("").as_bytes());
+        feed(&[0]);
+    }
+    for (_, src, dest, cost) in graph.edges() {
+        feed(&(src.index() as u64).to_le_bytes());
+        feed(&(dest.index() as u64).to_le_bytes());
+        feed(cost.to_string().as_bytes());
+    }
+    hash
+}
+
+/// The rows computed so far by a per-source job, indexed by source.
+///
+/// Each row holds one value per node, such as the distances from one source.
+pub type Rows = Vec<Option<Vec<Option<Weight>>>>;
+
+/// A checkpoint file for a per-source job, so interrupted runs can resume.
+#[derive(Debug)]
+pub struct RowCheckpoint {
+    path: PathBuf,
+    job: String,
+    fingerprint: u64,
+}
+
+impl RowCheckpoint {
+    pub fn new(path: impl Into<PathBuf>, job: &str, fingerprint: u64) -> Self {
+        Self {
+            path: path.into(),
+            job: job.into(),
+            fingerprint,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the rows saved by a previous run of the same job on the same graph.
+    pub fn load(&self, sources: usize) -> std::io::Result<Rows> {
+        let reader = BufReader::new(File::open(&self.path)?);
+        let invalid =
+            |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string());
+
+        let mut lines = reader.lines();
+        if lines.next().transpose()?.as_deref() != Some(MAGIC) {
+            return Err(invalid("not a checkpoint file"));
+        }
+        if lines.next().transpose()? != Some(format!("job {}", self.job)) {
+            return Err(invalid("checkpoint belongs to a different job"));
+        }
+        if lines.next().transpose()? != Some(format!("fingerprint {:016x}", self.fingerprint)) {
+            return Err(invalid("checkpoint was written for a different graph"));
+        }
+
+        let mut rows = vec![None; sources];
+        for line in lines {
+            let line = line?;
+            let mut iter = line.split(' ');
+            if iter.next() != Some("row") {
+                return Err(invalid("malformed checkpoint row"));
+            }
+            let source: usize = iter
+                .next()
+                .and_then(|s| s.parse().ok())
+                .filter(|&source| source < sources)
+                .ok_or_else(|| invalid("malformed checkpoint row"))?;
+            let row = iter
+                .map(|value| match value {
+                    "-" => Ok(None),
+                    value => value.parse().map(Some),
+                })
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid("malformed checkpoint value"))?;
+            rows[source] = Some(row);
+        }
+
+        Ok(rows)
+    }
+
+    /// Write all completed rows atomically, by writing a sibling file and renaming it.
+    pub fn save(&self, rows: &[Option<Vec<Option<Weight>>>]) -> std::io::Result<()> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+
+        {
+            let mut writer = BufWriter::new(File::create(&tmp)?);
+            writeln!(writer, "{}", MAGIC)?;
+            writeln!(writer, "job {}", self.job)?;
+            writeln!(writer, "fingerprint {:016x}", self.fingerprint)?;
+            for (source, row) in rows.iter().enumerate() {
+                let row = match row {
+                    Some(row) => row,
+                    None => continue,
+                };
+                write!(writer, "row {}", source)?;
+                for value in row {
+                    match value {
+                        Some(value) => write!(writer, " {}", value)?,
+                        None => write!(writer, " -")?,
+                    }
+                }
+                writeln!(writer)?;
+            }
+            writer.into_inner()?.sync_all()?;
+        }
+
+        std::fs::rename(&tmp, &self.path)
+    }
+
+    /// Delete the checkpoint file once the job has finished.
+    pub fn remove(&self) -> std::io::Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_rows_round_trip() {
+        let graph: Graph = "a b 1\nb c 2".parse().unwrap();
+        let fingerprint = graph_fingerprint(&graph);
+        let file = std::env::temp_dir().join(format!("cs365-checkpoint-{}", std::process::id()));
+        let checkpoint = RowCheckpoint::new(&file, "job", fingerprint);
+        let rows: Rows = vec![
+            Some(vec![Some(0), Some(1), Some(3)]),
+            None,
+            Some(vec![None; 3]),
+        ];
+        checkpoint.save(&rows).unwrap();
+        assert_eq!(checkpoint.load(3).unwrap(), rows);
+
+        let error = |checkpoint: RowCheckpoint| checkpoint.load(3).unwrap_err().to_string();
+        let other_job = RowCheckpoint::new(&file, "other", fingerprint);
+        assert_eq!(error(other_job), "checkpoint belongs to a different job");
+        let changed: Graph = "a b 1\nb c 5".parse().unwrap();
+        assert_ne!(graph_fingerprint(&changed), fingerprint);
+        let changed = RowCheckpoint::new(&file, "job", graph_fingerprint(&changed));
+        assert_eq!(
+            error(changed),
+            "checkpoint was written for a different graph"
+        );
+        assert!(checkpoint.load(2).is_err());
+
+        checkpoint.remove().unwrap();
+        assert!(checkpoint.load(3).is_err());
+        checkpoint.remove().unwrap();
+    }
+}
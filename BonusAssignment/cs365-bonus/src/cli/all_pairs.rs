@@ -0,0 +1,162 @@
+use super::query::cycle_names;
+use super::{write_output, Args, Input};
+use cs365_bonus::checkpoint::{graph_fingerprint, RowCheckpoint, Rows};
+use cs365_bonus::csr::Csr;
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::path::{has_negative_edges, shortest_path_tree_using, Algorithm};
+use cs365_bonus::{interrupt, Graph};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+const JOB: &str = "all-pairs";
+
+/// Where interrupted runs save their state when no `--checkpoint` was given.
+const DEFAULT_CHECKPOINT: &str = "all-pairs.checkpoint";
+
+pub fn run(args: Args) -> Result<(), String> {
+    if !run_job(args)? {
+        std::process::exit(interrupt::EXIT_CODE);
+    }
+    Ok(())
+}
+
+/// Run the job, returning `false` if it was interrupted after saving partial results.
+pub(super) fn run_job(mut args: Args) -> Result<bool, String> {
+    let checkpoint_path = args.value("--checkpoint")?;
+    let interval = args.parsed::<u64>("--checkpoint-interval")?.unwrap_or(60);
+    let resume = args.flag("--resume");
+    let output = args.value("--output")?;
+    let input = Input::from_args(&mut args)?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let fingerprint = graph_fingerprint(&graph);
+    let checkpoint = match checkpoint_path {
+        Some(path) => Some(RowCheckpoint::new(path, JOB, fingerprint)),
+        None if resume => return Err("'--resume' requires '--checkpoint <file>'".into()),
+        None => None,
+    };
+
+    let mut rows: Rows = match &checkpoint {
+        Some(checkpoint) if resume => checkpoint.load(graph.node_count()).map_err(|e| {
+            format!(
+                "Failed to resume from '{}': {}",
+                checkpoint.path().display(),
+                e
+            )
+        })?,
+        _ => vec![None; graph.node_count()],
+    };
+
+    // Rows are searched one source at a time so they can be saved, so negative costs take
+    // Bellman-Ford from each source rather than Floyd-Warshall as in `query --all-pairs`.
+    let csr = Csr::from_graph(&graph);
+    let algorithm = if has_negative_edges(&csr) {
+        Algorithm::BellmanFord
+    } else {
+        Algorithm::Dijkstra
+    };
+    interrupt::install_handler();
+    let interval = Duration::from_secs(interval);
+    let mut last_save = Instant::now();
+    for source in graph.node_ids() {
+        if interrupt::interrupted() {
+            break;
+        }
+        if rows[source.index()].is_some() {
+            continue;
+        }
+        let tree = shortest_path_tree_using(&csr, source, algorithm)
+            .map_err(|cycle| format!("negative cycle: {}", cycle_names(&graph, &cycle)))?;
+        rows[source.index()] = Some(tree.distances().to_vec());
+
+        if let Some(checkpoint) = &checkpoint {
+            if last_save.elapsed() >= interval {
+                save(checkpoint, &rows)?;
+                last_save = Instant::now();
+            }
+        }
+    }
+
+    let done = rows.iter().filter(|row| row.is_some()).count();
+    let partial = if done < rows.len() {
+        Some((done, rows.len()))
+    } else {
+        None
+    };
+
+    if partial.is_some() {
+        let checkpoint =
+            checkpoint.unwrap_or_else(|| RowCheckpoint::new(DEFAULT_CHECKPOINT, JOB, fingerprint));
+        save(&checkpoint, &rows)?;
+        write_output(output.as_deref(), |w| {
+            write_matrix(w, &graph, &rows, input.scale, partial)
+        })?;
+        eprintln!(
+            "Interrupted after {} of {} sources; resume with '--checkpoint {} --resume'",
+            done,
+            rows.len(),
+            checkpoint.path().display()
+        );
+        return Ok(false);
+    }
+
+    write_output(output.as_deref(), |w| {
+        write_matrix(w, &graph, &rows, input.scale, None)
+    })?;
+    if let Some(checkpoint) = &checkpoint {
+        checkpoint
+            .remove()
+            .map_err(|e| format!("Failed to remove checkpoint: {}", e))?;
+    }
+
+    Ok(true)
+}
+
+fn save(checkpoint: &RowCheckpoint, rows: &Rows) -> Result<(), String> {
+    checkpoint.save(rows).map_err(|e| {
+        format!(
+            "Failed to write checkpoint '{}': {}",
+            checkpoint.path().display(),
+            e
+        )
+    })
+}
+
+/// Write the distance matrix as CSV with node names as headers, printing distances with
+/// `scale` and leaving unreachable pairs empty.
+///
+/// Partial results start with a `# partial` comment line and only contain finished rows.
+pub(super) fn write_matrix(
+    writer: &mut dyn Write,
+    graph: &Graph,
+    rows: &Rows,
+    scale: FixedPoint,
+    partial: Option<(usize, usize)>,
+) -> std::io::Result<()> {
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+    if let Some((done, total)) = partial {
+        writeln!(writer, "# partial: {} of {} sources", done, total)?;
+    }
+    for node in graph.node_ids() {
+        write!(writer, ",{}", name(node))?;
+    }
+    writeln!(writer)?;
+
+    for (source, row) in graph.node_ids().zip(rows.iter()) {
+        let row = match row {
+            Some(row) => row,
+            None => continue,
+        };
+        write!(writer, "{}", name(source))?;
+        for value in row {
+            match value {
+                Some(value) => write!(writer, ",{}", scale.format(*value))?,
+                None => write!(writer, ",")?,
+            }
+        }
+        writeln!(writer)?;
+    }
+
+    writer.flush()
+}
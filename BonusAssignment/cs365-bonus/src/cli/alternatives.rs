@@ -0,0 +1,44 @@
+use super::{find_node, format_route, Args, Input};
+use cs365_bonus::alternatives::penalty_alternatives;
+
+/// Print up to `--count` (default 3) meaningfully different routes from `--from` to
+/// `--to` (default `a` to `z`).
+///
+/// Each route found multiplies the weights of its edges by `--penalty` (default 1.5) for
+/// later searches, and a route is only reported if at most `--max-overlap` (default 0.5)
+/// of its cost is shared with an earlier one.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let from = args.value("--from")?.unwrap_or_else(|| "a".into());
+    let to = args.value("--to")?.unwrap_or_else(|| "z".into());
+    let count = args.parsed::<usize>("--count")?.unwrap_or(3);
+    let penalty = args.parsed::<f64>("--penalty")?.unwrap_or(1.5);
+    let max_overlap = args.parsed::<f64>("--max-overlap")?.unwrap_or(0.5);
+    let files = args.finish()?;
+    if penalty <= 1.0 {
+        return Err("'--penalty' must be greater than 1".into());
+    }
+    if !(0.0..=1.0).contains(&max_overlap) {
+        return Err("'--max-overlap' must be between 0 and 1".into());
+    }
+
+    let graph = input.load(&files)?;
+    let (start, end) = (find_node(&graph, &from)?, find_node(&graph, &to)?);
+    let routes = penalty_alternatives(&graph, start, end, count, penalty, max_overlap);
+    if routes.is_empty() {
+        eprintln!("There is no path from '{}' to '{}'.", from, to);
+        return Ok(());
+    }
+    for (i, route) in routes.iter().enumerate() {
+        println!(
+            "Route {} of cost {}: {}",
+            i + 1,
+            input.scale.format(route.cost),
+            format_route(&graph, route, input.scale)
+        );
+    }
+    if routes.len() < count {
+        eprintln!("Only found {} sufficiently different routes.", routes.len());
+    }
+    Ok(())
+}
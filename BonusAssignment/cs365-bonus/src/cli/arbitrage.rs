@@ -0,0 +1,36 @@
+use super::Args;
+use cs365_bonus::arbitrage::{find_arbitrage, RateTable};
+
+/// The rate file read when none is given.
+const DEFAULT_RATES: &str = "rates.txt";
+
+/// Print profitable exchange cycles from a file of `from to rate` lines.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let limit = args.parsed::<usize>("--limit")?.unwrap_or(10);
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [] => DEFAULT_RATES,
+        [path] => path.as_str(),
+        _ => return Err("'arbitrage' takes a single rate file".into()),
+    };
+
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let table =
+        RateTable::parse(&data).map_err(|e| format!("Failed to parse '{}': {}", path, e))?;
+    let graph = table.graph();
+
+    let cycles = find_arbitrage(&table, limit);
+    if cycles.is_empty() {
+        eprintln!("No arbitrage opportunity found.");
+    }
+    for cycle in cycles {
+        let route: Vec<&str> = cycle
+            .currencies
+            .iter()
+            .map(|&node| graph.get_node_name(node).unwrap_or(""))
+            .collect();
+        println!("{:.6}x: {}", cycle.profit, route.join(" -> "));
+    }
+    Ok(())
+}
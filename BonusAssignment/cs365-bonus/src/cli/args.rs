@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+/// A minimal command line parser.
+///
+/// Commands pull the flags they understand out of the argument list, and whatever is
+/// left over must be positional arguments.
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    pub fn from_env() -> Self {
+        Self {
+            args: std::env::args().skip(1).collect(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn new(args: &[&str]) -> Self {
+        Self {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    /// Remove and return the first argument if it names one of `commands`.
+    pub fn subcommand(&mut self, commands: &[&'static str]) -> Option<&'static str> {
+        let first = self.args.first()?;
+        let command = commands.iter().find(|command| *command == first)?;
+        self.args.remove(0);
+        Some(command)
+    }
+
+    /// Remove every occurrence of a boolean flag, returning whether it was present.
+    pub fn flag(&mut self, name: &str) -> bool {
+        let len = self.args.len();
+        self.args.retain(|arg| arg != name);
+        self.args.len() != len
+    }
+
+    /// Remove every occurrence of `--name value` or `--name=value`, returning the values.
+    pub fn values(&mut self, name: &str) -> Result<Vec<String>, String> {
+        let prefix = format!("{}=", name);
+        let mut values = Vec::new();
+        let mut i = 0;
+        while i < self.args.len() {
+            if self.args[i] == name {
+                if i + 1 >= self.args.len() {
+                    return Err(format!("missing value for '{}'", name));
+                }
+                values.push(self.args.remove(i + 1));
+                self.args.remove(i);
+            } else if let Some(value) = self.args[i].strip_prefix(&prefix) {
+                values.push(value.to_string());
+                self.args.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        Ok(values)
+    }
+
+    /// Remove every occurrence of `--name first second`, returning the pairs of values.
+    ///
+    /// Taking two arguments avoids splitting one on a separator that could also appear in
+    /// the values, such as the `-` in a node name.
+    pub fn pairs(&mut self, name: &str) -> Result<Vec<(String, String)>, String> {
+        let prefix = format!("{}=", name);
+        let mut pairs = Vec::new();
+        let mut i = 0;
+        while i < self.args.len() {
+            if self.args[i] == name {
+                if i + 2 >= self.args.len() {
+                    return Err(format!("'{}' expects two values", name));
+                }
+                let second = self.args.remove(i + 2);
+                let first = self.args.remove(i + 1);
+                self.args.remove(i);
+                pairs.push((first, second));
+            } else if self.args[i].starts_with(&prefix) {
+                return Err(format!(
+                    "'{}' expects two values, as '{} <a> <b>'",
+                    name, name
+                ));
+            } else {
+                i += 1;
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Remove a flag that may be given at most once.
+    pub fn value(&mut self, name: &str) -> Result<Option<String>, String> {
+        let mut values = self.values(name)?;
+        if values.len() > 1 {
+            return Err(format!("'{}' may only be given once", name));
+        }
+        Ok(values.pop())
+    }
+
+    /// Remove a flag that may be given at most once and parse its value.
+    pub fn parsed<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, String> {
+        self.value(name)?
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid value '{}' for '{}'", value, name))
+            })
+            .transpose()
+    }
+
+    /// Finish parsing, returning the remaining positional arguments.
+    pub fn finish(self) -> Result<Vec<String>, String> {
+        if let Some(arg) = self.args.iter().find(|arg| arg.starts_with("--")) {
+            return Err(format!("unknown option '{}'", arg));
+        }
+        Ok(self.args)
+    }
+}
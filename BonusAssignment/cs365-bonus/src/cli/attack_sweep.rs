@@ -0,0 +1,49 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::rng::Rng;
+use cs365_bonus::robustness::{attack_sweep, node_order, Target};
+
+/// Remove every node one at a time and write the damage as a CSV curve, one row per step
+/// from the intact graph on: how many nodes are gone, the one just removed, the size of
+/// the largest connected component and its share of the original nodes, and the average
+/// number of hops between nodes still connected. Direction and costs are ignored.
+///
+/// `--target` picks the order as for `perturb`: `random` (the default, repeatable with
+/// `--seed`), or the highest `degree` or `betweenness` first, scored once on the intact
+/// graph. Path lengths take a search from every node left, so on large graphs
+/// `--path-every <k>` only measures them every `k` steps, leaving the rest blank, or never
+/// with 0.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let target = args.parsed::<Target>("--target")?.unwrap_or(Target::Random);
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let path_every = args.parsed::<usize>("--path-every")?.unwrap_or(1);
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let order = node_order(&graph, target, &mut Rng::new(seed));
+    let steps = attack_sweep(&graph, &order, path_every);
+    let total = graph.node_count().max(1) as f64;
+    write_output(output.as_deref(), |w| {
+        writeln!(w, "removed,node,giant,giant_fraction,average_path")?;
+        for step in &steps {
+            let node = match step.removed {
+                0 => "",
+                i => graph.get_node_name(order[i - 1]).unwrap_or(""),
+            };
+            write!(
+                w,
+                "{},{},{},{:.6},",
+                step.removed,
+                node,
+                step.giant,
+                step.giant as f64 / total
+            )?;
+            if let Some(average) = step.average_path {
+                write!(w, "{:.6}", average)?;
+            }
+            writeln!(w)?;
+        }
+        w.flush()
+    })
+}
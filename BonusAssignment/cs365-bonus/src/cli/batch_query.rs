@@ -0,0 +1,92 @@
+use super::{find_node, format_route, Args, Input};
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::reachability::ReachabilityIndex;
+use cs365_bonus::router::ConcurrentRouter;
+use std::io::{BufRead, Write};
+
+/// Answer `u v` shortest path queries, one per line, read from the `--queries` file or
+/// stdin, on `--threads` threads (default all cores). Answers are printed in input order
+/// as the cost and route, or `none` for disconnected pairs.
+///
+/// `--reachability-labels <d>` first labels the graph with `d` random traversals, which
+/// answers many disconnected pairs of a directed graph with `none` without searching.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let queries = args.value("--queries")?;
+    let threads = match args.parsed::<usize>("--threads")? {
+        Some(threads) => threads.max(1),
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let labelings = args.parsed::<usize>("--reachability-labels")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let reader: Box<dyn BufRead> = match &queries {
+        Some(path) => Box::new(std::io::BufReader::new(
+            std::fs::File::open(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?,
+        )),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let mut pairs = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read queries: {}", e))?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            [word, ..] if word.starts_with('#') => continue,
+            [u, v] => pairs.push((find_node(&graph, u)?, find_node(&graph, v)?)),
+            _ => return Err(format!("line {}: expected 'u v'", i + 1)),
+        }
+    }
+
+    let index = labelings.map(|labelings| ReachabilityIndex::build(&graph, labelings, 0));
+    let index = index.as_ref();
+    if has_negative_edges(&graph) {
+        return Err("'batch-query' does not support negative costs".into());
+    }
+    let router = ConcurrentRouter::new(graph.freeze());
+    let scale = input.scale;
+    let chunk = pairs.len().div_ceil(threads).max(1);
+    let answers: Vec<String> = std::thread::scope(|scope| {
+        let handles: Vec<_> = pairs
+            .chunks(chunk)
+            .map(|pairs| {
+                let router = router.clone();
+                scope.spawn(move || {
+                    let graph = router.graph().graph();
+                    pairs
+                        .iter()
+                        .map(|&(u, v)| {
+                            let ruled_out = index.is_some_and(|index| !index.may_reach(u, v));
+                            if ruled_out {
+                                None
+                            } else {
+                                router.route(u, v)
+                            }
+                        })
+                        .map(|path| match path {
+                            Some(path) => format!(
+                                "{}: {}",
+                                scale.format(path.cost),
+                                format_route(graph, &path, scale)
+                            ),
+                            None => "none".to_string(),
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("query thread panicked"))
+            .collect()
+    });
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for answer in answers {
+        writeln!(out, "{}", answer).map_err(|e| format!("Failed to write output: {}", e))?;
+    }
+    out.flush()
+        .map_err(|e| format!("Failed to write output: {}", e))
+}
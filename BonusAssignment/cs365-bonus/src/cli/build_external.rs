@@ -0,0 +1,27 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::external::{convert_edge_list, write_external};
+
+/// Write the input graph as an on-disk CSR file for `external-query` to `--output`.
+///
+/// With `--streaming` a single edge list is converted without loading its edges, for
+/// inputs too large to fit in memory; otherwise the graph is loaded first, so the usual
+/// input options such as `--reorder bfs` apply.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let streaming = args.flag("--streaming");
+    let input = Input::from_args(&mut args)?;
+    let output = args
+        .value("--output")?
+        .ok_or("'build-external' requires '--output <file>'")?;
+    let files = args.finish()?;
+
+    if streaming {
+        let path = match files.as_slice() {
+            [path] => path,
+            _ => return Err("'--streaming' converts exactly one edge list file".into()),
+        };
+        return convert_edge_list(path, &output)
+            .map_err(|e| format!("Failed to convert '{}': {}", path, e));
+    }
+    let graph = input.load(&files)?;
+    write_output(Some(&output), |w| write_external(&graph, w))
+}
@@ -0,0 +1,117 @@
+use super::{write_output, Args};
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::geo::{knn_graph, Metric};
+
+/// Build a graph joining each point of `--points <file>` to its `--k` nearest neighbors
+/// (default 8), and write it as an edge list any other command can read.
+///
+/// The file holds one point per line as `name,x,y`, or `name,lat,lon` with
+/// `--metric haversine` for distances in meters over the Earth's surface rather than
+/// straight lines (default euclidean). Blank lines, `#` comments, and a header line that
+/// doesn't parse as a point are skipped. Costs are written with `--decimals <places>`
+/// digits after the point, so the same option reads them back.
+///
+/// `--attributes-out <file>` also writes each point's coordinates as node attributes, `x`
+/// and `y` or `lat` and `lon`, ready for `--attributes` and the options that use them.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let points = args
+        .value("--points")?
+        .ok_or("'build-knn' requires '--points <file>'")?;
+    let k = args.parsed::<usize>("--k")?.unwrap_or(8);
+    let metric = args
+        .parsed::<Metric>("--metric")?
+        .unwrap_or(Metric::Euclidean);
+    let places = args.parsed::<u32>("--decimals")?.unwrap_or(0);
+    let scale = FixedPoint::new(places).ok_or_else(|| {
+        format!(
+            "'--decimals' may be at most {}",
+            cs365_bonus::decimal::MAX_PLACES
+        )
+    })?;
+    let attributes_out = args.value("--attributes-out")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    if !files.is_empty() {
+        return Err("'build-knn' reads its points from '--points'".into());
+    }
+    if k == 0 {
+        return Err("'--k' must be at least 1".into());
+    }
+
+    let data = std::fs::read_to_string(&points)
+        .map_err(|e| format!("Failed to open '{}': {}", points, e))?;
+    let points = parse_points(&data).map_err(|e| format!("Failed to parse '{}': {}", points, e))?;
+    let graph = knn_graph(&points, k, metric, scale)?;
+    eprintln!(
+        "{} points, {} edges joining each to its {} nearest",
+        graph.node_count(),
+        graph.edge_count(),
+        k
+    );
+
+    if let Some(file) = attributes_out {
+        let keys = match metric {
+            Metric::Haversine => ("lat", "lon"),
+            Metric::Euclidean => ("x", "y"),
+        };
+        write_output(Some(&file), |w| {
+            for (name, first, second) in &points {
+                writeln!(
+                    w,
+                    "node {} {}={} {}={}",
+                    name, keys.0, first, keys.1, second
+                )?;
+            }
+            w.flush()
+        })?;
+    }
+    write_output(output.as_deref(), |w| {
+        for (_, from, to, cost) in graph.edges() {
+            writeln!(
+                w,
+                "{} {} {}",
+                graph.get_node_name(from).unwrap_or_default(),
+                graph.get_node_name(to).unwrap_or_default(),
+                scale.format(cost)
+            )?;
+        }
+        w.flush()
+    })
+}
+
+/// Read `name,first,second` lines, skipping blank lines, `#` comments, and a header.
+fn parse_points(text: &str) -> Result<Vec<(String, f64, f64)>, String> {
+    let mut points = Vec::new();
+    let mut header = true;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let point = match fields.as_slice() {
+            [name, first, second] if !name.is_empty() && !name.contains(char::is_whitespace) => {
+                first
+                    .parse()
+                    .ok()
+                    .zip(second.parse().ok())
+                    .filter(|(first, second): &(f64, f64)| first.is_finite() && second.is_finite())
+                    .map(|(first, second)| (name.to_string(), first, second))
+            }
+            _ => None,
+        };
+        let first = std::mem::replace(&mut header, false);
+        match point {
+            Some(point) => points.push(point),
+            None if first => {}
+            None => {
+                return Err(format!(
+                    "line {}: expected '<name>,<x>,<y>', got '{}'",
+                    i + 1,
+                    line
+                ))
+            }
+        }
+    }
+    Ok(points)
+}
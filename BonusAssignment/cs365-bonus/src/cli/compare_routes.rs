@@ -0,0 +1,65 @@
+use super::{find_node, format_route, Args, Input};
+use cs365_bonus::similarity::{compare, parse_coordinates};
+use cs365_bonus::{find_shortest_path, Graph, Path};
+
+/// Compare a submitted `--route a,b,...,z` against `--reference` (default the shortest
+/// path between the same endpoints), reporting how much of it they share.
+///
+/// With `--coordinates <file>` of `name x y` lines, also report how far apart the two
+/// routes get.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let route = args
+        .value("--route")?
+        .ok_or("'compare-routes' requires '--route a,b,...'")?;
+    let reference = args.value("--reference")?;
+    let coordinates = args.value("--coordinates")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let route = parse_route(&graph, &route)?;
+    let reference = match reference {
+        Some(reference) => parse_route(&graph, &reference)?,
+        None => {
+            let (start, end) = (route.nodes[0], route.nodes[route.nodes.len() - 1]);
+            find_shortest_path(&graph, start, end)
+                .ok_or("the route's endpoints are disconnected")?
+        }
+    };
+    let coordinates = match coordinates {
+        Some(path) => {
+            let data = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+            Some(parse_coordinates(&graph, &data).map_err(|e| format!("{}: {}", path, e))?)
+        }
+        None => None,
+    };
+
+    let similarity = compare(&graph, &route, &reference, coordinates.as_deref());
+    println!(
+        "Route of cost {}: {}",
+        input.scale.format(route.cost),
+        format_route(&graph, &route, input.scale)
+    );
+    println!(
+        "Reference of cost {}: {}",
+        input.scale.format(reference.cost),
+        format_route(&graph, &reference, input.scale)
+    );
+    println!("Shared edges: {:.3}", similarity.shared_edges);
+    println!("Weighted overlap: {:.3}", similarity.weighted_overlap);
+    if let Some(divergence) = similarity.divergence {
+        println!("Divergence: {:.3}", divergence);
+    }
+    Ok(())
+}
+
+/// Parse a comma separated list of node names into a path along existing edges.
+fn parse_route(graph: &Graph, spec: &str) -> Result<Path, String> {
+    let nodes = spec
+        .split(',')
+        .map(|name| find_node(graph, name))
+        .collect::<Result<Vec<_>, _>>()?;
+    Path::from_nodes(graph, nodes)
+        .ok_or_else(|| format!("'{}' doesn't follow the graph's edges", spec))
+}
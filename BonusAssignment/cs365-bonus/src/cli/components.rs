@@ -0,0 +1,26 @@
+use super::{write_output, Args, Input};
+
+/// Print the connected components of the input, ignoring edge direction, one per line as
+/// the names of its nodes. `--sizes` prints each component's node count before its nodes.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let sizes = args.flag("--sizes");
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let components = graph.connected_components();
+    write_output(output.as_deref(), |w| {
+        for component in &components {
+            if sizes {
+                write!(w, "{} ", component.len())?;
+            }
+            let names: Vec<&str> = component
+                .iter()
+                .map(|&node| graph.get_node_name(node).unwrap_or(""))
+                .collect();
+            writeln!(w, "{}", names.join(" "))?;
+        }
+        w.flush()
+    })
+}
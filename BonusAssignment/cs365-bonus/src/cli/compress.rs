@@ -0,0 +1,17 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::compressed::write_compressed;
+
+/// Write the input graph in the compressed binary format to `--output`.
+///
+/// Every command accepts compressed files as input, and `compressed-query` searches them
+/// without decompressing. Reorder with `--reorder bfs` first for a smaller file.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let output = args
+        .value("--output")?
+        .ok_or("'compress' requires '--output <file>'")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    write_output(Some(&output), |w| write_compressed(&graph, w))
+}
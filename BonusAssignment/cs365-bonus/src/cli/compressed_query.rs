@@ -0,0 +1,43 @@
+use super::Args;
+use cs365_bonus::compressed::CompressedGraph;
+
+/// Find the shortest path from `--from` to `--to` (default `a` to `z`) in a file written
+/// by `compress`, decoding only the adjacency lists the search visits.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let from = args.value("--from")?.unwrap_or_else(|| "a".into());
+    let to = args.value("--to")?.unwrap_or_else(|| "z".into());
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [path] => path,
+        _ => return Err("'compressed-query' expects one compressed graph file".into()),
+    };
+
+    let data = std::fs::read(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let graph = CompressedGraph::from_bytes(data).map_err(|e| format!("'{}': {}", path, e))?;
+    let find = |name: &str| {
+        graph
+            .get_node(name)
+            .ok_or_else(|| format!("node '{}' does not exist in the graph", name))
+    };
+    let (start, end) = (find(&from)?, find(&to)?);
+    let tree = graph
+        .shortest_path_tree(start)
+        .map_err(|e| format!("'{}': {}", path, e))?;
+
+    match tree.path_to(end) {
+        Some(path) => {
+            println!("Located a minimum path of cost: {}", path.cost);
+            let route: Vec<String> = path
+                .nodes
+                .iter()
+                .zip(path.distance.iter())
+                .map(|(node, cost)| {
+                    format!("{} ({})", graph.get_node_name(*node).unwrap_or(""), cost)
+                })
+                .collect();
+            println!("{}", route.join(" -> "));
+        }
+        None => eprintln!("There is no path from '{}' to '{}'.", from, to),
+    }
+    Ok(())
+}
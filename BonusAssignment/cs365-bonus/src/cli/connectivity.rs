@@ -0,0 +1,81 @@
+use super::{Args, Input};
+use cs365_bonus::connectivity::{offline_connectivity, Answer, Command, IncrementalConnectivity};
+use cs365_bonus::Graph;
+use std::io::{BufRead, Write};
+
+/// Read `add`, `remove`, `connected`, and `components` commands from stdin.
+///
+/// Queries are answered as soon as they are read unless `--offline` is given, in which
+/// case the whole script is read first so that `remove` can be supported. Graph files
+/// given as arguments are loaded first; otherwise the graph starts empty.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let offline = args.flag("--offline");
+    let input = Input::from_args(&mut args)?;
+    let files = args.finish()?;
+
+    let graph = if files.is_empty() {
+        Graph::new()
+    } else {
+        input.load(&files)?
+    };
+    let commands = read_commands();
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut print = |answer: Answer| {
+        let result = match answer {
+            Answer::Connected(true) => writeln!(out, "yes"),
+            Answer::Connected(false) => writeln!(out, "no"),
+            Answer::Components(count) => writeln!(out, "{}", count),
+        };
+        result
+            .and_then(|_| out.flush())
+            .map_err(|e| format!("Failed to write output: {}", e))
+    };
+
+    if offline {
+        let (lines, commands): (Vec<usize>, Vec<Command>) =
+            commands.collect::<Result<Vec<_>, _>>()?.into_iter().unzip();
+        let answers = offline_connectivity(&graph, &commands)
+            .map_err(|e| format!("line {}: no edge to remove", lines[e.command]))?;
+        return answers.into_iter().try_for_each(print);
+    }
+
+    let mut connectivity = IncrementalConnectivity::from_graph(&graph);
+    for command in commands {
+        let (line, command) = command?;
+        match command {
+            Command::Add(a, b) => {
+                connectivity.add_edge(&a, &b);
+            }
+            Command::Remove(..) => {
+                return Err(format!("line {}: 'remove' requires '--offline'", line));
+            }
+            Command::Connected(a, b) => print(Answer::Connected(connectivity.connected(&a, &b)))?,
+            Command::Components => print(Answer::Components(connectivity.component_count()))?,
+        }
+    }
+    Ok(())
+}
+
+/// The commands on stdin with their line numbers, skipping blank lines and `#` comments.
+fn read_commands() -> impl Iterator<Item = Result<(usize, Command), String>> {
+    std::io::stdin()
+        .lock()
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(format!("Failed to read stdin: {}", e))),
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            Some(
+                line.parse()
+                    .map(|command| (i + 1, command))
+                    .map_err(|e| format!("line {}: {}", i + 1, e)),
+            )
+        })
+}
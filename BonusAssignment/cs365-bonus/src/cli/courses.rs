@@ -0,0 +1,37 @@
+use super::{write_output, Args};
+use cs365_bonus::courses::Catalog;
+
+/// Plan courses read from a file with one `course credits prerequisite...` line each.
+///
+/// Prints the fewest semesters the longest prerequisite chain allows, then a schedule
+/// taking at most `--cap <credits>` a semester, or without a cap, as many as possible.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let cap = args.parsed::<u32>("--cap")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [path] => path.as_str(),
+        _ => return Err("'courses' takes a single course file".into()),
+    };
+
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let catalog =
+        Catalog::parse(&data).map_err(|e| format!("Failed to parse '{}': {}", path, e))?;
+    let fewest = catalog.min_semesters()?;
+    let plan = catalog.schedule(cap.unwrap_or(u32::MAX))?;
+    write_output(output.as_deref(), |w| {
+        writeln!(w, "minimum semesters {}", fewest)?;
+        for (i, semester) in plan.iter().enumerate() {
+            let credits: u32 = semester.iter().map(|&course| catalog.credits(course)).sum();
+            writeln!(
+                w,
+                "semester {} ({} credits): {}",
+                i + 1,
+                credits,
+                catalog.names(semester).join(" ")
+            )?;
+        }
+        w.flush()
+    })
+}
@@ -0,0 +1,31 @@
+use super::{format_route, Args, Input};
+use cs365_bonus::cycle::minimum_cycle_basis;
+use cs365_bonus::Weight;
+
+/// Print a minimum cycle basis of an undirected graph, one cycle per line with its cost,
+/// cheapest first, followed by the basis's total cost.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let basis = minimum_cycle_basis(&graph)?;
+    if basis.is_empty() {
+        eprintln!("The graph is acyclic.");
+        return Ok(());
+    }
+    for cycle in &basis {
+        println!(
+            "{}: {}",
+            input.scale.format(cycle.cost),
+            format_route(&graph, cycle, input.scale)
+        );
+    }
+    let total: Weight = basis.iter().map(|cycle| cycle.cost).sum();
+    println!(
+        "{} cycles, total weight {}",
+        basis.len(),
+        input.scale.format(total)
+    );
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use super::{find_node, format_route, Args, Input};
+use cs365_bonus::cycle::shortest_cycle_through;
+
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let node = args
+        .value("--node")?
+        .ok_or("'cycle-through' requires '--node <name>'")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let node = find_node(&graph, &node)?;
+    match shortest_cycle_through(&graph, node)? {
+        Some(cycle) => {
+            println!(
+                "Located a minimum cycle of cost: {}",
+                input.scale.format(cycle.cost)
+            );
+            println!("{}", format_route(&graph, &cycle, input.scale));
+        }
+        None => {
+            eprintln!(
+                "There is no cycle through '{}'.",
+                graph.get_node_name(node).unwrap_or("")
+            );
+        }
+    }
+    Ok(())
+}
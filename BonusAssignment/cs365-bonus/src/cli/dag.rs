@@ -0,0 +1,90 @@
+use super::{find_node, format_route, write_output, Args, Input};
+use cs365_bonus::dag::{dag_paths, dag_paths_by_node_value, Objective};
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::{Graph, Weight};
+
+/// Shortest or, with `--objective longest`, longest paths from `--from` in a DAG.
+///
+/// Prints the route to `--to` if given, and otherwise every node's distance. Edges are
+/// usually read with `--directed`, since an undirected edge is a two-node cycle.
+///
+/// `--node-values <file>`, with `node value` lines, scores paths by the total value of the
+/// nodes they visit instead of their edge costs. Unlisted nodes are worth nothing.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let from = args
+        .value("--from")?
+        .ok_or("'dag' requires '--from <name>'")?;
+    let to = args.value("--to")?;
+    let objective = args
+        .value("--objective")?
+        .map(|s| s.parse::<Objective>())
+        .transpose()?
+        .unwrap_or(Objective::Shortest);
+    let node_values = args.value("--node-values")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let start = find_node(&graph, &from)?;
+    let tree = match node_values {
+        Some(path) => {
+            let values = read_node_values(&graph, &path, input.scale)?;
+            dag_paths_by_node_value(&graph, &values, start, objective)
+        }
+        None => dag_paths(&graph, start, objective),
+    };
+    let tree = tree.ok_or("the graph has a cycle; pass '--directed' if its edges are one-way")?;
+    let kind = match objective {
+        Objective::Shortest => "minimum",
+        Objective::Longest => "maximum",
+    };
+
+    match to {
+        Some(to) => {
+            let end = find_node(&graph, &to)?;
+            match tree.path_to(end) {
+                Some(path) => {
+                    println!(
+                        "Located a {} path of cost: {}",
+                        kind,
+                        input.scale.format(path.cost)
+                    );
+                    println!("{}", format_route(&graph, &path, input.scale));
+                }
+                None => eprintln!("There is no path from '{}' to '{}'.", from, to),
+            }
+            Ok(())
+        }
+        None => write_output(output.as_deref(), |w| {
+            for node in graph.node_ids() {
+                let name = graph.get_node_name(node).unwrap_or("");
+                match tree.distance(node) {
+                    Some(distance) => writeln!(w, "{} {}", name, input.scale.format(distance))?,
+                    None => writeln!(w, "{} -", name)?,
+                }
+            }
+            w.flush()
+        }),
+    }
+}
+
+fn read_node_values(graph: &Graph, path: &str, scale: FixedPoint) -> Result<Vec<Weight>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let mut values = vec![0; graph.node_count()];
+    for (i, line) in data.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            [word, ..] if word.starts_with('#') => continue,
+            [node, value] => {
+                values[find_node(graph, node)?.index()] = scale
+                    .parse(value)
+                    .ok_or_else(|| format!("invalid value on line {} of '{}'", i + 1, path))?;
+            }
+            _ => return Err(format!("invalid entry on line {} of '{}'", i + 1, path)),
+        }
+    }
+    Ok(values)
+}
@@ -0,0 +1,57 @@
+use super::{write_output, Args};
+use cs365_bonus::dependencies::Dependencies;
+use cs365_bonus::Weight;
+
+/// Print an order to install packages in, read from a file with one package per line
+/// followed by its dependencies, or fail naming the shortest dependency cycle.
+///
+/// `--weights <file>`, with `package weight` lines, breaks ties between packages that are
+/// ready at the same time in favor of the lowest weight; unlisted packages weigh 0.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let weights = args.value("--weights")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [path] => path.as_str(),
+        _ => return Err("'deps' takes a single dependency file".into()),
+    };
+
+    let deps = Dependencies::parse(&read(path)?);
+    let mut weight: Vec<Weight> = vec![0; deps.graph().node_count()];
+    if let Some(path) = weights {
+        for (i, line) in read(&path)?.lines().enumerate() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                [] => continue,
+                [word, ..] if word.starts_with('#') => continue,
+                [name, value] => {
+                    let package = deps
+                        .get(name)
+                        .ok_or_else(|| format!("package '{}' does not exist", name))?;
+                    weight[package.index()] = value
+                        .parse()
+                        .map_err(|_| format!("invalid weight on line {} of '{}'", i + 1, path))?;
+                }
+                _ => return Err(format!("invalid entry on line {} of '{}'", i + 1, path)),
+            }
+        }
+    }
+
+    match deps.install_order(|package| weight[package.index()]) {
+        Ok(order) => write_output(output.as_deref(), |w| {
+            for name in deps.names(&order) {
+                writeln!(w, "{}", name)?;
+            }
+            w.flush()
+        }),
+        Err(cycle) => {
+            let mut names = deps.names(&cycle);
+            names.push(names[0]);
+            Err(format!("dependency cycle: {}", names.join(" -> ")))
+        }
+    }
+}
+
+fn read(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))
+}
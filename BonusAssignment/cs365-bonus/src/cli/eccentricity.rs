@@ -0,0 +1,32 @@
+use super::{Args, Input};
+use cs365_bonus::eccentricity::{center, diameter, eccentricities, periphery, radius};
+use cs365_bonus::{Graph, NodeId, Weight};
+
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let ecc = eccentricities(&graph);
+    for (node, &ecc) in graph.node_ids().zip(ecc.iter()) {
+        println!("{} {}", graph.get_node_name(node).unwrap_or(""), show(ecc));
+    }
+
+    println!("radius {}", show(radius(&ecc)));
+    println!("center {}", names(&graph, &center(&ecc)));
+    println!("diameter {}", show(diameter(&ecc)));
+    println!("periphery {}", names(&graph, &periphery(&ecc)));
+    Ok(())
+}
+
+fn show(value: Option<Weight>) -> String {
+    value.map_or_else(|| "inf".to_string(), |value| value.to_string())
+}
+
+fn names(graph: &Graph, nodes: &[NodeId]) -> String {
+    let names: Vec<&str> = nodes
+        .iter()
+        .map(|&node| graph.get_node_name(node).unwrap_or(""))
+        .collect();
+    names.join(" ")
+}
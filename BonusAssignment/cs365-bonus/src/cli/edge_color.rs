@@ -0,0 +1,37 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::edge_coloring::edge_coloring;
+
+/// Color the edges of a simple graph so that no two edges at a node share a color, using at
+/// most one color more than the maximum degree, and print the colors as rounds: each
+/// round's edges, such as the matches of a tournament, can all happen at once since no
+/// node takes part in two of them.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let coloring = edge_coloring(&graph)?;
+    let edges: Vec<_> = graph.edges().collect();
+    eprintln!(
+        "{} colors for a maximum degree of {}",
+        coloring.color_count, coloring.max_degree
+    );
+    write_output(output.as_deref(), |w| {
+        for (i, round) in coloring.rounds().iter().enumerate() {
+            let pairs: Vec<String> = round
+                .iter()
+                .map(|id| {
+                    let (_, u, v, _) = edges[id.index()];
+                    format!(
+                        "{}-{}",
+                        graph.get_node_name(u).unwrap_or(""),
+                        graph.get_node_name(v).unwrap_or("")
+                    )
+                })
+                .collect();
+            writeln!(w, "Round {}: {}", i + 1, pairs.join(" "))?;
+        }
+        w.flush()
+    })
+}
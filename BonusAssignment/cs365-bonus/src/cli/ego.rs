@@ -0,0 +1,54 @@
+use super::{find_node, write_output, Args, Input};
+use cs365_bonus::ego::{ego_graph, Radius};
+use cs365_bonus::write_edge_list_scaled;
+
+/// Write the neighborhood of `--node` as an edge list, or as DOT with `--dot`.
+///
+/// The neighborhood is every node at most `--radius <n>` edges away (default 1), or with
+/// `--by cost`, at most `--radius <cost>` away by shortest path, along with the edges
+/// between those nodes.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let node = args
+        .value("--node")?
+        .ok_or("'ego' requires '--node <name>'")?;
+    let radius = args.value("--radius")?;
+    let by = args.value("--by")?;
+    let dot = args.flag("--dot");
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let radius = match (by.as_deref(), radius) {
+        (None | Some("hops"), None) => Radius::Hops(1),
+        (None | Some("hops"), Some(text)) => Radius::Hops(
+            text.parse()
+                .map_err(|_| format!("invalid value '{}' for '--radius'", text))?,
+        ),
+        (Some("cost"), None) => return Err("'--by cost' requires '--radius <cost>'".into()),
+        (Some("cost"), Some(text)) => Radius::Cost(
+            input
+                .scale
+                .parse(&text)
+                .filter(|&cost| cost >= 0)
+                .ok_or_else(|| format!("invalid value '{}' for '--radius'", text))?,
+        ),
+        (Some(by), _) => {
+            return Err(format!(
+                "unknown radius '{}', expected 'hops' or 'cost'",
+                by
+            ))
+        }
+    };
+
+    let graph = input.load(&files)?;
+    let center = find_node(&graph, &node)?;
+    let ego = ego_graph(&graph, center, radius);
+    write_output(output.as_deref(), |w| {
+        if dot {
+            w.write_all(ego.to_dot(None).as_bytes())?;
+            w.flush()
+        } else {
+            write_edge_list_scaled(&ego, input.scale, w)
+        }
+    })
+}
@@ -0,0 +1,641 @@
+use super::{read_graph, Args};
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::generate::{grid_graph, random_graph};
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::rng::Rng;
+use cs365_bonus::stats::{welch_t_test, Summary};
+use cs365_bonus::{
+    shortest_path_tree_using, Algorithm, Graph, NodeId, SearchContext, SearchVisitor, Weight,
+};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const METRICS: &[&str] = &["time", "found", "cost", "settled"];
+
+/// Run the experiment described by a manifest and write its results to a directory:
+/// `experiment run manifest.toml [--output <dir>]`.
+///
+/// A manifest lists the graphs to test, either read from files or generated from each
+/// seed, the algorithms to compare, the seeds, and the metrics to collect:
+///
+/// ```toml
+/// name = "dijkstra-vs-bellman-ford"
+/// seeds = [1, 2, 3]
+/// queries = 100
+/// repeats = 10
+/// algorithms = ["dijkstra", "bellman-ford"]
+/// metrics = ["time", "found", "cost", "settled"]
+///
+/// [[input]]
+/// path = "input.txt"
+///
+/// [[generator]]
+/// kind = "random"   # nodes, edges, and max_cost
+/// nodes = 1000
+/// edges = 4000
+/// max_cost = 100
+///
+/// [[generator]]
+/// kind = "grid"     # width, height, and max_cost
+/// width = 30
+/// height = 30
+/// max_cost = 10
+/// ```
+///
+/// Input paths are relative to the manifest. For every graph and seed, `queries` random
+/// pairs of nodes are drawn from the seed and answered with every algorithm.
+/// `results.csv` in the output directory, by default `results/<name>`, gets one row per
+/// graph, seed, and algorithm: `time` is the mean query time in microseconds, `found` the
+/// number of pairs joined by a path, `cost` the sum of their costs, and `settled` the
+/// mean number of nodes Dijkstra settled. The manifest is copied alongside, so the
+/// directory records how to reproduce it. Every column but `time` is the same on each
+/// run.
+///
+/// With `repeats`, each algorithm answers the same queries that many times. `time` is
+/// then the mean over the repeats, `time_ci` the half-width of its 95% confidence
+/// interval, and `comparisons.csv` compares every pair of algorithms on each graph and
+/// seed with Welch's t-test: a `p` below 0.05 marks the difference in mean time as
+/// significant.
+///
+/// Manifests are read as a subset of TOML: `key = value` pairs whose values are strings,
+/// integers, or arrays of them, which may span several lines, and `[[section]]` headers.
+/// Inline tables, floats, and other TOML features are rejected.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [command, path] if command == "run" => path,
+        _ => return Err("expected 'experiment run <manifest.toml>'".into()),
+    };
+    let text =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let experiment = Experiment::parse(&text).map_err(|e| format!("In '{}': {}", path, e))?;
+    let base = Path::new(path).parent().unwrap_or(Path::new(""));
+    let dir = output
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("results").join(&experiment.name));
+
+    let mut csv = String::from("graph,seed,algorithm,queries");
+    for metric in &experiment.metrics {
+        write!(csv, ",{}", metric).unwrap();
+        if metric == "time" {
+            csv.push_str(",time_ci");
+        }
+    }
+    csv.push('\n');
+    let mut comparisons = String::from("graph,seed,first,second,difference,t,df,p,significant\n");
+    let mut rows = 0;
+    for source in &experiment.sources {
+        for &seed in &experiment.seeds {
+            let mut rng = Rng::new(seed);
+            let graph = source.load(base, &mut rng)?;
+            if graph.node_count() == 0 {
+                return Err(format!("graph '{}' has no nodes to query", source.label()));
+            }
+            let pairs: Vec<(NodeId, NodeId)> = (0..experiment.queries)
+                .map(|_| {
+                    let from = NodeId::new(rng.below(graph.node_count()));
+                    (from, NodeId::new(rng.below(graph.node_count())))
+                })
+                .collect();
+            let mut times = Vec::new();
+            for (name, algorithm) in &experiment.algorithms {
+                let mut micros = Vec::with_capacity(experiment.repeats);
+                let mut measured = None;
+                for _ in 0..experiment.repeats {
+                    let run = measure(&graph, *algorithm, &pairs)
+                        .map_err(|e| format!("{} on '{}': {}", name, source.label(), e))?;
+                    micros.push(run.micros / pairs.len() as f64);
+                    measured.get_or_insert(run);
+                }
+                let measured = measured.expect("experiments repeat at least once");
+                let time = Summary::of(&micros);
+                write!(csv, "{},{},{},{}", source.label(), seed, name, pairs.len()).unwrap();
+                for metric in &experiment.metrics {
+                    let value = match metric.as_str() {
+                        "time" => match time.confidence_interval(0.95) {
+                            ci if ci.is_nan() => format!("{:.3},", time.mean),
+                            ci => format!("{:.3},{:.3}", time.mean, ci),
+                        },
+                        "found" => measured.found.to_string(),
+                        "cost" => measured.cost.to_string(),
+                        _ => measured.settled.map_or(String::new(), |settled| {
+                            format!("{:.1}", settled as f64 / pairs.len().max(1) as f64)
+                        }),
+                    };
+                    write!(csv, ",{}", value).unwrap();
+                }
+                csv.push('\n');
+                rows += 1;
+                times.push((name, time));
+            }
+            for (i, (first, a)) in times.iter().enumerate() {
+                for (second, b) in &times[i + 1..] {
+                    if let Some(test) = welch_t_test(a, b) {
+                        writeln!(
+                            comparisons,
+                            "{},{},{},{},{:.3},{:.3},{:.1},{:.4},{}",
+                            source.label(),
+                            seed,
+                            first,
+                            second,
+                            a.mean - b.mean,
+                            test.t,
+                            test.df,
+                            test.p,
+                            if test.p < 0.05 { "yes" } else { "no" }
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    let mut outputs = vec![("manifest.toml", text.as_str()), ("results.csv", &csv)];
+    if experiment.repeats > 1 && experiment.algorithms.len() > 1 {
+        outputs.push(("comparisons.csv", &comparisons));
+    }
+    for (name, contents) in outputs {
+        let file = dir.join(name);
+        std::fs::write(&file, contents)
+            .map_err(|e| format!("Failed to write '{}': {}", file.display(), e))?;
+    }
+    println!("Wrote {} results to '{}'.", rows, dir.display());
+    Ok(())
+}
+
+/// A parsed and checked manifest.
+struct Experiment {
+    name: String,
+    seeds: Vec<u64>,
+    queries: usize,
+    repeats: usize,
+    algorithms: Vec<(String, Algorithm)>,
+    metrics: Vec<String>,
+    sources: Vec<Source>,
+}
+
+/// Where an experiment's graphs come from.
+enum Source {
+    File(String),
+    Random {
+        nodes: usize,
+        edges: usize,
+        max_cost: Weight,
+    },
+    Grid {
+        width: usize,
+        height: usize,
+        max_cost: Weight,
+    },
+}
+
+impl Source {
+    fn label(&self) -> String {
+        match self {
+            Source::File(path) => path.clone(),
+            Source::Random { nodes, edges, .. } => format!("random-{}x{}", nodes, edges),
+            Source::Grid { width, height, .. } => format!("grid-{}x{}", width, height),
+        }
+    }
+
+    fn load(&self, base: &Path, rng: &mut Rng) -> Result<Graph, String> {
+        Ok(match *self {
+            Source::File(ref path) => {
+                let path = base.join(path);
+                read_graph(&path.to_string_lossy(), FixedPoint::INTEGER)?
+            }
+            Source::Random {
+                nodes,
+                edges,
+                max_cost,
+            } => random_graph(nodes, edges, max_cost, rng),
+            Source::Grid {
+                width,
+                height,
+                max_cost,
+            } => grid_graph(width, height, max_cost, rng),
+        })
+    }
+}
+
+impl Experiment {
+    fn parse(text: &str) -> Result<Self, String> {
+        let manifest = Manifest::parse(text)?;
+        let root = &manifest.root;
+        root.only(&[
+            "name",
+            "seeds",
+            "queries",
+            "repeats",
+            "algorithms",
+            "metrics",
+        ])?;
+        let name = root.string("name")?.ok_or("missing 'name'")?;
+        if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(format!("'{}' can't be used as a directory name", name));
+        }
+        let seeds = match root.get("seeds") {
+            Some(seeds) => seeds
+                .array("seeds")?
+                .iter()
+                .map(|seed| seed.integer("seeds").map(|seed| seed as u64))
+                .collect::<Result<_, _>>()?,
+            None => vec![0],
+        };
+        let queries = match root.integer("queries")?.unwrap_or(100) {
+            queries if queries > 0 => queries as usize,
+            _ => return Err("'queries' must be positive".into()),
+        };
+        let repeats = match root.integer("repeats")?.unwrap_or(1) {
+            repeats if repeats > 0 => repeats as usize,
+            _ => return Err("'repeats' must be positive".into()),
+        };
+        let algorithms = match root.get("algorithms") {
+            Some(list) => list
+                .array("algorithms")?
+                .iter()
+                .map(|name| {
+                    let name = name.string("algorithms")?;
+                    Ok((name.to_string(), name.parse()?))
+                })
+                .collect::<Result<_, String>>()?,
+            None => vec![("auto".to_string(), Algorithm::Auto)],
+        };
+        let metrics = match root.get("metrics") {
+            Some(list) => list
+                .array("metrics")?
+                .iter()
+                .map(|metric| {
+                    let metric = metric.string("metrics")?;
+                    match METRICS.contains(&metric) {
+                        true => Ok(metric.to_string()),
+                        false => Err(format!(
+                            "unknown metric '{}', expected one of {}",
+                            metric,
+                            METRICS.join(", ")
+                        )),
+                    }
+                })
+                .collect::<Result<_, String>>()?,
+            None => METRICS.iter().map(|metric| metric.to_string()).collect(),
+        };
+
+        let mut sources = Vec::new();
+        for (section, table) in &manifest.sections {
+            let positive = |key| match table.integer(key)? {
+                Some(n) if n > 0 => Ok(n),
+                Some(_) => Err(format!("'{}' must be positive", key)),
+                None => Err(format!("[[{}]] is missing '{}'", section, key)),
+            };
+            let source = match (section.as_str(), table.string("kind")?) {
+                ("input", _) => {
+                    table.only(&["path"])?;
+                    let path = table.string("path")?.ok_or("[[input]] is missing 'path'")?;
+                    Source::File(path.to_string())
+                }
+                ("generator", Some("random")) => {
+                    table.only(&["kind", "nodes", "edges", "max_cost"])?;
+                    Source::Random {
+                        nodes: positive("nodes")? as usize,
+                        edges: table.integer("edges")?.unwrap_or(0).max(0) as usize,
+                        max_cost: positive("max_cost")?,
+                    }
+                }
+                ("generator", Some("grid")) => {
+                    table.only(&["kind", "width", "height", "max_cost"])?;
+                    Source::Grid {
+                        width: positive("width")? as usize,
+                        height: positive("height")? as usize,
+                        max_cost: positive("max_cost")?,
+                    }
+                }
+                ("generator", kind) => {
+                    return Err(format!(
+                        "unknown generator kind {:?}, expected 'random' or 'grid'",
+                        kind.unwrap_or("")
+                    ))
+                }
+                (section, _) => return Err(format!("unknown section [[{}]]", section)),
+            };
+            sources.push(source);
+        }
+        if sources.is_empty() {
+            return Err("no [[input]] or [[generator]] to run on".into());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            seeds,
+            queries,
+            repeats,
+            algorithms,
+            metrics,
+            sources,
+        })
+    }
+}
+
+/// What one algorithm did on a set of queries.
+struct Measured {
+    micros: f64,
+    found: usize,
+    cost: Weight,
+    /// Nodes settled in total, for searches that settle nodes one at a time.
+    settled: Option<usize>,
+}
+
+struct Settled(usize);
+
+impl SearchVisitor for Settled {
+    fn settled(&mut self, _node: NodeId, _distance: Weight, _frontier: usize) {
+        self.0 += 1;
+    }
+}
+
+fn measure(
+    graph: &Graph,
+    algorithm: Algorithm,
+    pairs: &[(NodeId, NodeId)],
+) -> Result<Measured, String> {
+    let dijkstra = match algorithm {
+        Algorithm::Auto => !has_negative_edges(graph),
+        Algorithm::Dijkstra => true,
+        Algorithm::BellmanFord => false,
+    };
+    let mut context = SearchContext::new();
+    let mut settled = Settled(0);
+    let (mut found, mut cost) = (0, 0);
+    let started = Instant::now();
+    for &(start, end) in pairs {
+        let path = if dijkstra {
+            context
+                .find_shortest_path_visited(graph, start, end, None, &mut settled)
+                .unwrap_or(None)
+        } else {
+            shortest_path_tree_using(graph, start, Algorithm::BellmanFord)
+                .map_err(|e| e.to_string())?
+                .path_to(end)
+        };
+        if let Some(path) = path {
+            found += 1;
+            cost += path.cost;
+        }
+    }
+    Ok(Measured {
+        micros: started.elapsed().as_secs_f64() * 1e6,
+        found,
+        cost,
+        settled: dijkstra.then_some(settled.0),
+    })
+}
+
+/// The subset of TOML manifests use: `key = value` pairs with strings, integers, and
+/// arrays, which may span lines, and `[[section]]` headers starting a new table in a
+/// list.
+struct Manifest {
+    root: Table,
+    sections: Vec<(String, Table)>,
+}
+
+#[derive(Default)]
+struct Table {
+    entries: Vec<(String, Value)>,
+}
+
+enum Value {
+    String(String),
+    Integer(i64),
+    Array(Vec<Value>),
+}
+
+impl Manifest {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut manifest = Manifest {
+            root: Table::default(),
+            sections: Vec::new(),
+        };
+        let mut lines = text.lines().enumerate();
+        while let Some((i, line)) = lines.next() {
+            let error = |message: &str| format!("line {}: {}", i + 1, message);
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix("[[").and_then(|s| s.strip_suffix("]]")) {
+                manifest
+                    .sections
+                    .push((section.trim().to_string(), Table::default()));
+                continue;
+            }
+            if line.starts_with('[') {
+                return Err(error("only [[section]] lists of tables are supported"));
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected 'key = value'"))?;
+            let key = key.trim();
+            // An array continues over the following lines until its brackets are closed.
+            let mut value = value.trim().to_string();
+            while open_brackets(&value) > 0 {
+                let (_, next) = lines.next().ok_or_else(|| error("unterminated array"))?;
+                value.push(' ');
+                value.push_str(strip_comment(next).trim());
+            }
+            let mut parser = ValueParser { text: &value };
+            let value = parser.value().map_err(|e| error(&e))?;
+            if !parser.text.trim().is_empty() {
+                return Err(error("unexpected text after the value"));
+            }
+            let table = match manifest.sections.last_mut() {
+                Some((_, table)) => table,
+                None => &mut manifest.root,
+            };
+            if table.get(key).is_some() {
+                return Err(error(&format!("'{}' is set twice", key)));
+            }
+            table.entries.push((key.to_string(), value));
+        }
+        Ok(manifest)
+    }
+}
+
+/// `line` up to a `#` that isn't inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// How many `[` in `text` are still waiting for their `]`, ignoring those in strings.
+fn open_brackets(text: &str) -> usize {
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '[' if !quoted => depth += 1,
+            ']' if !quoted => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth
+}
+
+struct ValueParser<'a> {
+    text: &'a str,
+}
+
+impl ValueParser<'_> {
+    fn value(&mut self) -> Result<Value, String> {
+        self.text = self.text.trim_start();
+        if let Some(rest) = self.text.strip_prefix('"') {
+            let mut out = String::new();
+            let mut chars = rest.char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        self.text = &rest[i + 1..];
+                        return Ok(Value::String(out));
+                    }
+                    '\\' => match chars.next() {
+                        Some((_, '"')) => out.push('"'),
+                        Some((_, '\\')) => out.push('\\'),
+                        Some((_, 'n')) => out.push('\n'),
+                        Some((_, 't')) => out.push('\t'),
+                        _ => return Err("unsupported escape in string".into()),
+                    },
+                    c => out.push(c),
+                }
+            }
+            return Err("unterminated string".into());
+        }
+        if let Some(rest) = self.text.strip_prefix('[') {
+            self.text = rest;
+            let mut items = Vec::new();
+            loop {
+                self.text = self.text.trim_start();
+                if let Some(rest) = self.text.strip_prefix(']') {
+                    self.text = rest;
+                    return Ok(Value::Array(items));
+                }
+                items.push(self.value()?);
+                self.text = self.text.trim_start();
+                if let Some(rest) = self.text.strip_prefix(',') {
+                    self.text = rest;
+                } else if !self.text.starts_with(']') {
+                    return Err("expected ',' or ']' in array".into());
+                }
+            }
+        }
+        let end = self
+            .text
+            .find(|c: char| c == ',' || c == ']' || c.is_whitespace())
+            .unwrap_or(self.text.len());
+        let (word, rest) = self.text.split_at(end);
+        self.text = rest;
+        word.replace('_', "")
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| format!("invalid value '{}'", word))
+    }
+}
+
+impl Table {
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Fail if any key isn't one of `known`, which catches misspelled settings.
+    fn only(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .entries
+            .iter()
+            .find(|(key, _)| !known.contains(&key.as_str()))
+        {
+            Some((key, _)) => Err(format!("unknown setting '{}'", key)),
+            None => Ok(()),
+        }
+    }
+
+    fn string(&self, key: &str) -> Result<Option<&str>, String> {
+        self.get(key).map(|value| value.string(key)).transpose()
+    }
+
+    fn integer(&self, key: &str) -> Result<Option<i64>, String> {
+        self.get(key).map(|value| value.integer(key)).transpose()
+    }
+}
+
+impl Value {
+    fn string(&self, key: &str) -> Result<&str, String> {
+        match self {
+            Value::String(text) => Ok(text),
+            _ => Err(format!("'{}' must be a string", key)),
+        }
+    }
+
+    fn integer(&self, key: &str) -> Result<i64, String> {
+        match *self {
+            Value::Integer(n) => Ok(n),
+            _ => Err(format!("'{}' must be an integer", key)),
+        }
+    }
+
+    fn array(&self, key: &str) -> Result<&[Value], String> {
+        match self {
+            Value::Array(items) => Ok(items),
+            _ => Err(format!("'{}' must be an array", key)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrays_may_span_lines() {
+        let manifest = Manifest::parse(
+            "seeds = [\n  1,  # first\n  2,\n]\nnames = [\"[a]\", [\n\"#b\"]]\nqueries = 3",
+        )
+        .unwrap();
+        let root = &manifest.root;
+        assert!(matches!(
+            root.get("seeds"),
+            Some(Value::Array(seeds)) if matches!(seeds.as_slice(), [Value::Integer(1), Value::Integer(2)])
+        ));
+        match root.get("names") {
+            Some(Value::Array(names)) => match names.as_slice() {
+                [Value::String(a), Value::Array(b)] => {
+                    assert_eq!(a, "[a]");
+                    assert!(matches!(b.as_slice(), [Value::String(b)] if b == "#b"));
+                }
+                _ => panic!("wrong array items"),
+            },
+            _ => panic!("'names' is not an array"),
+        }
+        assert_eq!(root.integer("queries"), Ok(Some(3)));
+
+        let unterminated = Manifest::parse("seeds = [1,\n2").err();
+        assert_eq!(unterminated.as_deref(), Some("line 1: unterminated array"));
+    }
+}
@@ -0,0 +1,46 @@
+use super::Args;
+use cs365_bonus::external::ExternalGraph;
+
+/// Find the shortest path from `--from` to `--to` (default `a` to `z`) in a file written
+/// by `build-external`, reading it through a page cache of at most `--cache-mb`
+/// megabytes (default 64) in pages of `--page-kb` kilobytes (default 64).
+pub fn run(mut args: Args) -> Result<(), String> {
+    let from = args.value("--from")?.unwrap_or_else(|| "a".into());
+    let to = args.value("--to")?.unwrap_or_else(|| "z".into());
+    let cache_mb = args.parsed::<usize>("--cache-mb")?.unwrap_or(64);
+    let page_kb = args.parsed::<usize>("--page-kb")?.unwrap_or(64);
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [path] => path,
+        _ => return Err("'external-query' expects one external graph file".into()),
+    };
+
+    let io = |e: std::io::Error| format!("Failed to read '{}': {}", path, e);
+    let mut graph = ExternalGraph::open(path, page_kb << 10, cache_mb << 20).map_err(io)?;
+    let mut find = |name: &str| match graph.get_node(name) {
+        Ok(Some(node)) => Ok(node),
+        Ok(None) => Err(format!("node '{}' does not exist in the graph", name)),
+        Err(e) => Err(io(e)),
+    };
+    let (start, end) = (find(&from)?, find(&to)?);
+
+    match graph.shortest_path(start, end).map_err(io)? {
+        Some(path) => {
+            let names = graph.node_names(&path.nodes).map_err(io)?;
+            println!("Located a minimum path of cost: {}", path.cost);
+            let route: Vec<String> = names
+                .iter()
+                .zip(path.distance.iter())
+                .map(|(name, cost)| format!("{} ({})", name, cost))
+                .collect();
+            println!("{}", route.join(" -> "));
+        }
+        None => eprintln!("There is no path from '{}' to '{}'.", from, to),
+    }
+    let stats = graph.stats();
+    eprintln!(
+        "Page cache: {} hits, {} misses, {} bytes read.",
+        stats.hits, stats.misses, stats.bytes_read
+    );
+    Ok(())
+}
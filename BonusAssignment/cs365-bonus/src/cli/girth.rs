@@ -0,0 +1,19 @@
+use super::{format_route, Args, Input};
+use cs365_bonus::cycle::girth;
+
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    match girth(&graph)? {
+        Some(cycle) => {
+            println!("Girth: {}", input.scale.format(cycle.cost));
+            println!("{}", format_route(&graph, &cycle, input.scale));
+        }
+        None => {
+            eprintln!("The graph is acyclic.");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use super::{write_output, Args};
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::grid_search::{
+    astar, jump_point_search, smoothed, theta_star, GridRoute, STRAIGHT,
+};
+use cs365_bonus::gridworld::{Belief, GridWorld};
+use cs365_bonus::rng::Rng;
+use std::time::Instant;
+
+/// Solve a grid world read from a map of `#` walls, `.` free cells, a start `S`, and a
+/// goal `G`, printing the shortest route's cost and the map with the route drawn as `*`.
+///
+/// `--fog <radius>` instead has an agent walk from the start seeing only the cells within
+/// `radius` steps, assuming unseen cells are free and replanning with D* Lite as walls
+/// come into view. It prints the cost the agent traveled against the optimum for an agent
+/// that knows the map, how often it replanned and how many nodes that expanded, and the
+/// map as the agent last saw it, with cells it never saw as `?`.
+///
+/// `--diagonal` also allows diagonal moves, costing the square root of 2, though never
+/// across the corner of a wall. `--benchmark` finds such a route by both plain A* and Jump
+/// Point Search, and prints how many cells each expanded and how long each took, along
+/// with the cost and effort of an any-angle route.
+///
+/// `--any-angle` finds a route by Theta*, which is not limited to eight directions: it
+/// follows straight lines at any angle between the corners of walls it has to go around.
+/// The map then marks where the route turns with `+`, and the cells its lines cross with
+/// `*`. `--smooth` instead straightens the route it found afterwards, cutting corners
+/// wherever a straight line is clear and no longer, and marks its turns the same way.
+/// `--svg <file>` also draws the map and the route as an SVG image.
+///
+/// `--maze <width>x<height>` writes a random maze in the map format instead of reading
+/// one, drawn from `--seed`, with `--loops <fraction>` of its inner walls knocked down to
+/// open alternative routes (default 0.05).
+pub fn run(mut args: Args) -> Result<(), String> {
+    let fog = args.parsed::<usize>("--fog")?;
+    let diagonal = args.flag("--diagonal");
+    let any_angle = args.flag("--any-angle");
+    let smooth = args.flag("--smooth");
+    let svg = args.value("--svg")?;
+    let benchmark = args.flag("--benchmark");
+    let maze = args.value("--maze")?;
+    let loops = args.parsed::<f64>("--loops")?.unwrap_or(0.05);
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    if let Some(size) = maze {
+        if !files.is_empty() {
+            return Err("'--maze' writes a new map rather than reading one".into());
+        }
+        let (width, height) = size
+            .split_once('x')
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .ok_or_else(|| format!("'--maze' expects '<width>x<height>', got '{}'", size))?;
+        let world = GridWorld::maze(width, height, loops, &mut Rng::new(seed));
+        return write_output(output.as_deref(), |w| {
+            write!(w, "{}", world)?;
+            w.flush()
+        });
+    }
+    let path = match files.as_slice() {
+        [path] => path.as_str(),
+        _ => return Err("'grid' takes a single map file".into()),
+    };
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let world =
+        GridWorld::parse(&data).map_err(|e| format!("Failed to parse '{}': {}", path, e))?;
+
+    if benchmark {
+        if fog.is_some() {
+            return Err("'--benchmark' can't be combined with '--fog'".into());
+        }
+        return compare(&world);
+    }
+    if fog.is_some() && (diagonal || any_angle || smooth) {
+        return Err("'--fog' agents only move horizontally and vertically".into());
+    }
+    if diagonal && any_angle {
+        return Err("'--diagonal' and '--any-angle' can't be combined".into());
+    }
+
+    let optimal = world.optimal_route();
+    let mut turns = None;
+    let (route, belief) = match fog {
+        Some(radius) => {
+            let walk = world.explore(radius);
+            match &optimal {
+                Some(optimal) if walk.reached => println!(
+                    "Reached the goal at cost {}, against {} knowing the map",
+                    walk.cost(),
+                    optimal.len() - 1
+                ),
+                _ => println!(
+                    "Gave up after {} steps: the goal is walled off",
+                    walk.cost()
+                ),
+            }
+            println!(
+                "Replanned {} times, expanding {} nodes in all",
+                walk.replans, walk.expanded
+            );
+            (walk.route, Some(walk.belief))
+        }
+        None if diagonal || any_angle || smooth => match search(&world, diagonal, any_angle) {
+            Some(mut route) => {
+                println!("Shortest route: cost {}", units(route.cost));
+                if smooth {
+                    route = smoothed(&world, &route);
+                    println!("Smoothed: cost {}", units(route.cost));
+                }
+                if any_angle || smooth {
+                    println!("Turning points: {}", route.waypoints.len() - 2);
+                    turns = Some(route.waypoints.clone());
+                }
+                (route.cells, None)
+            }
+            None => {
+                println!("The goal is walled off");
+                (Vec::new(), None)
+            }
+        },
+        None => match optimal {
+            Some(route) => {
+                println!("Shortest route: cost {}", route.len() - 1);
+                (route, None)
+            }
+            None => {
+                println!("The goal is walled off");
+                (Vec::new(), None)
+            }
+        },
+    };
+
+    let mut rows: Vec<Vec<char>> = (0..world.height())
+        .map(|y| {
+            (0..world.width())
+                .map(|x| {
+                    let seen = match &belief {
+                        Some(belief) => belief[y * world.width() + x],
+                        None if world.is_wall((x, y)) => Belief::Wall,
+                        None => Belief::Free,
+                    };
+                    match seen {
+                        Belief::Unknown => '?',
+                        Belief::Free => '.',
+                        Belief::Wall => '#',
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    for &(x, y) in &route {
+        rows[y][x] = '*';
+    }
+    for &(x, y) in turns.iter().flatten() {
+        rows[y][x] = '+';
+    }
+    let ((sx, sy), (gx, gy)) = (world.start(), world.goal());
+    rows[sy][sx] = 'S';
+    rows[gy][gx] = 'G';
+    if let Some(file) = svg {
+        let drawing = world.to_svg(turns.as_deref().unwrap_or(&route));
+        std::fs::write(&file, drawing).map_err(|e| format!("Failed to write '{}': {}", file, e))?;
+    }
+    write_output(output.as_deref(), |w| {
+        for row in &rows {
+            writeln!(w, "{}", row.iter().collect::<String>())?;
+        }
+        w.flush()
+    })
+}
+
+/// The route across `world` by A*, moving diagonally too if `diagonal`, or by Theta* if
+/// `any_angle`.
+fn search(world: &GridWorld, diagonal: bool, any_angle: bool) -> Option<GridRoute> {
+    if any_angle {
+        theta_star(world)
+    } else {
+        astar(world, diagonal)
+    }
+}
+
+/// Route across `world` by plain A* and by Jump Point Search, and print what each took,
+/// then the same for an any-angle route by Theta*.
+fn compare(world: &GridWorld) -> Result<(), String> {
+    let time = |search: &dyn Fn(&GridWorld) -> Option<GridRoute>| {
+        let timer = Instant::now();
+        let route = search(world);
+        (route, timer.elapsed().as_secs_f64() * 1000.0)
+    };
+    let (plain, plain_ms) = time(&|world| astar(world, true));
+    let (jps, jps_ms) = time(&jump_point_search);
+    let (plain, jps) = match (plain, jps) {
+        (Some(plain), Some(jps)) => (plain, jps),
+        _ => {
+            println!("The goal is walled off");
+            return Ok(());
+        }
+    };
+    for (name, route, ms) in [("A*", &plain, plain_ms), ("JPS", &jps, jps_ms)] {
+        println!(
+            "{}: cost {}, {} cells expanded, {:.3} ms",
+            name,
+            units(route.cost),
+            route.expanded,
+            ms
+        );
+    }
+    println!(
+        "JPS expanded {:.1} times fewer cells and ran {:.1} times faster",
+        plain.expanded as f64 / jps.expanded as f64,
+        plain_ms / jps_ms
+    );
+    if let (Some(theta), theta_ms) = time(&theta_star) {
+        println!(
+            "Theta*: cost {} ({:.1}% shorter), {} turns, {} cells expanded, {:.3} ms",
+            units(theta.cost),
+            100.0 * (plain.cost - theta.cost) as f64 / plain.cost as f64,
+            theta.waypoints.len() - 2,
+            theta.expanded,
+            theta_ms
+        );
+    }
+    Ok(())
+}
+
+/// A grid search cost in steps, with three decimal places for diagonal moves.
+fn units(cost: cs365_bonus::Weight) -> String {
+    let places = STRAIGHT.to_string().len() as u32 - 1;
+    FixedPoint::new(places)
+        .expect("a step has few decimal places")
+        .format(cost)
+}
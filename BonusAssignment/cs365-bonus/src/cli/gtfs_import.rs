@@ -0,0 +1,36 @@
+use super::{write_output, Args};
+use cs365_bonus::gtfs::load_gtfs;
+use cs365_bonus::write_edge_list;
+
+/// Convert an extracted GTFS feed into a directed edge list and a travel times file, for
+/// use with `profile --directed --travel-times <file>`.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let output = args.value("--output")?;
+    let travel_times = args
+        .value("--travel-times-out")?
+        .ok_or("'gtfs-import' requires '--travel-times-out <file>'")?;
+    let files = args.finish()?;
+    let dir = match files.as_slice() {
+        [dir] => dir,
+        _ => return Err("'gtfs-import' takes the directory of an extracted feed".into()),
+    };
+
+    let network = load_gtfs(std::path::Path::new(dir))?;
+    let graph = &network.graph;
+    write_output(Some(&travel_times), |w| {
+        let name = |node| graph.get_node_name(node).unwrap_or("");
+        for (id, src, dest, _) in graph.edges() {
+            if let Some(profile) = &network.profiles[id.index()] {
+                writeln!(w, "{} {} {}", name(src), name(dest), profile)?;
+            }
+        }
+        w.flush()
+    })?;
+    write_output(output.as_deref(), |w| write_edge_list(graph, w))?;
+    eprintln!(
+        "Imported {} stops and {} connections.",
+        graph.node_count(),
+        graph.edge_count()
+    );
+    Ok(())
+}
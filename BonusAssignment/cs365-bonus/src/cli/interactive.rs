@@ -0,0 +1,120 @@
+use super::{format_route, route_json, Format};
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::{shortest_path_tree_using, Algorithm, Graph, Path, SearchContext};
+use std::io::{BufRead, Write};
+
+const HELP: &str = "\
+<from> <to>     print the shortest path between two nodes
+:nodes          list every node
+:edges <node>   list the edges leaving a node
+:help           show this message
+:quit           stop";
+
+/// Answer `from to` queries read from stdin until it ends or `:quit`, reusing one
+/// search context so each query only pays for the nodes it visits.
+///
+/// Mistakes such as unknown nodes are reported on stderr without stopping the loop.
+pub fn repl(
+    graph: &Graph,
+    algorithm: Algorithm,
+    scale: FixedPoint,
+    format: Format,
+) -> Result<(), String> {
+    let dijkstra = match algorithm {
+        Algorithm::Auto => !has_negative_edges(graph),
+        Algorithm::Dijkstra => true,
+        Algorithm::BellmanFord => false,
+    };
+    let mut context = SearchContext::new();
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let stdout = std::io::stdout();
+    eprintln!(
+        "Loaded {} nodes and {} edges. Type :help for commands.",
+        graph.node_count(),
+        graph.edge_count()
+    );
+    loop {
+        eprint!("> ");
+        let line = match lines.next() {
+            Some(line) => line.map_err(|e| format!("Failed to read stdin: {}", e))?,
+            None => return Ok(()),
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let mut out = stdout.lock();
+        let written = match words.as_slice() {
+            [] => Ok(()),
+            [":quit"] | [":q"] => return Ok(()),
+            [":help"] => writeln!(out, "{}", HELP),
+            [":nodes"] => graph
+                .node_ids()
+                .try_for_each(|node| writeln!(out, "{}", graph.get_node_name(node).unwrap_or(""))),
+            [":edges", name] => match graph.get_node(name) {
+                Some(node) => graph.edges_of(node).iter().try_for_each(|edge| {
+                    let dest = graph.get_node_name(edge.node).unwrap_or("");
+                    writeln!(out, "{} -> {} ({})", name, dest, scale.format(edge.cost))
+                }),
+                None => unknown(name),
+            },
+            [from, to] if !from.starts_with(':') => {
+                match (graph.get_node(from), graph.get_node(to)) {
+                    (Some(start), Some(end)) => {
+                        let path = if dijkstra {
+                            context.find_shortest_path(graph, start, end)
+                        } else {
+                            match shortest_path_tree_using(graph, start, algorithm) {
+                                Ok(tree) => tree.path_to(end),
+                                Err(_) => {
+                                    eprintln!("A negative cycle is reachable from '{}'.", from);
+                                    continue;
+                                }
+                            }
+                        };
+                        write_path(&mut out, graph, path.as_ref(), scale, format, from, to)
+                    }
+                    (None, _) => unknown(from),
+                    (_, None) => unknown(to),
+                }
+            }
+            _ => {
+                eprintln!("Expected '<from> <to>' or a command, see :help.");
+                Ok(())
+            }
+        };
+        written
+            .and_then(|()| out.flush())
+            .map_err(|e| format!("Failed to write output: {}", e))?;
+    }
+}
+
+fn unknown(name: &str) -> std::io::Result<()> {
+    eprintln!("Node '{}' does not exist in the graph.", name);
+    Ok(())
+}
+
+fn write_path(
+    out: &mut impl Write,
+    graph: &Graph,
+    path: Option<&Path>,
+    scale: FixedPoint,
+    format: Format,
+    from: &str,
+    to: &str,
+) -> std::io::Result<()> {
+    match (path, format) {
+        (Some(path), Format::Json) => writeln!(
+            out,
+            "{{\"status\":\"found\",{}",
+            &route_json(graph, path, scale)[1..]
+        ),
+        (None, Format::Json) => writeln!(out, "{{\"status\":\"unreachable\"}}"),
+        (Some(path), Format::Text) => writeln!(
+            out,
+            "Cost {}: {}",
+            scale.format(path.cost),
+            format_route(graph, path, scale)
+        ),
+        (None, Format::Text) => writeln!(out, "There is no path from '{}' to '{}'.", from, to),
+    }
+}
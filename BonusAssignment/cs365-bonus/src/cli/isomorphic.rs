@@ -0,0 +1,64 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::isomorphism::{find_isomorphism, tree_canonical_form};
+
+/// Check whether two graphs have the same shape once their nodes are renamed, and if so
+/// write which node of the second each node of the first maps onto, one pair per line.
+///
+/// Edge directions must match, and with `--weighted` so must costs. Two trees are compared
+/// by their canonical forms first, and other graphs searched for a mapping directly, which
+/// is only practical for small ones.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let weighted = args.flag("--weighted");
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    let (a, b) = match files.as_slice() {
+        [a, b] => (
+            input.load(std::slice::from_ref(a))?,
+            input.load(std::slice::from_ref(b))?,
+        ),
+        _ => return Err("'isomorphic' takes exactly two graph files".into()),
+    };
+
+    println!(
+        "{} nodes and {} edges against {} nodes and {} edges",
+        a.node_count(),
+        a.edge_count(),
+        b.node_count(),
+        b.edge_count()
+    );
+    match (
+        tree_canonical_form(&a, weighted),
+        tree_canonical_form(&b, weighted),
+    ) {
+        (Some(form_a), Some(form_b)) if form_a != form_b => {
+            println!("Not isomorphic: the trees' canonical forms differ");
+            return Ok(());
+        }
+        (Some(_), Some(_)) => println!("Both are trees with the same canonical form"),
+        (Some(_), None) | (None, Some(_)) => {
+            println!("Not isomorphic: only one of them is a tree");
+            return Ok(());
+        }
+        (None, None) => {}
+    }
+    let mapping = match find_isomorphism(&a, &b, weighted) {
+        Some(mapping) => mapping,
+        None => {
+            println!("Not isomorphic");
+            return Ok(());
+        }
+    };
+    println!("Isomorphic");
+    write_output(output.as_deref(), |w| {
+        for (node, image) in a.node_ids().zip(&mapping) {
+            writeln!(
+                w,
+                "{} {}",
+                a.get_node_name(node).unwrap_or(""),
+                b.get_node_name(*image).unwrap_or("")
+            )?;
+        }
+        w.flush()
+    })
+}
@@ -0,0 +1,30 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::kcore::{core_numbers, k_core};
+use cs365_bonus::write_edge_list_scaled;
+
+/// Print every node's core number, or with `--k` write the induced k-core as an edge list.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let k = args.parsed::<usize>("--k")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    match k {
+        Some(k) => {
+            let core = k_core(&graph, k);
+            write_output(output.as_deref(), |w| {
+                write_edge_list_scaled(&core, input.scale, w)
+            })
+        }
+        None => {
+            let cores = core_numbers(&graph);
+            write_output(output.as_deref(), |w| {
+                for (node, core) in graph.node_ids().zip(cores.iter()) {
+                    writeln!(w, "{} {}", graph.get_node_name(node).unwrap_or(""), core)?;
+                }
+                w.flush()
+            })
+        }
+    }
+}
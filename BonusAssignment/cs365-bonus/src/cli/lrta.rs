@@ -0,0 +1,90 @@
+use super::{find_node, format_route, Args, Input};
+use cs365_bonus::astar::Landmarks;
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::realtime::RealTimeAgent;
+use cs365_bonus::{shortest_path_tree, Weight};
+
+/// Simulate an agent running Learning Real-Time A* from `--from` to `--to`, looking
+/// `--lookahead <d>` moves ahead (default 1) before each move.
+///
+/// The agent starts from a landmark heuristic over `--landmarks <n>` landmarks (default 2,
+/// 0 for none) and keeps what it learns across `--trials <n>` runs (default 1), stopping
+/// early once a run learns nothing. Each run is cut off after `--max-moves <n>` moves
+/// (default 100 times the number of nodes). For every run it prints the moves made, the
+/// cost of the route walked against the optimal cost, and how many heuristic values it
+/// raised, followed by the last route.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let from = args
+        .value("--from")?
+        .ok_or("'lrta' requires '--from <node>'")?;
+    let to = args.value("--to")?.ok_or("'lrta' requires '--to <node>'")?;
+    let lookahead = args.parsed::<usize>("--lookahead")?.unwrap_or(1);
+    let landmarks = args.parsed::<usize>("--landmarks")?.unwrap_or(2);
+    let trials = args.parsed::<usize>("--trials")?.unwrap_or(1);
+    let max_moves = args.parsed::<usize>("--max-moves")?;
+    let files = args.finish()?;
+    if lookahead == 0 {
+        return Err("'--lookahead' must be at least 1".into());
+    }
+
+    let graph = input.load(&files)?;
+    if has_negative_edges(&graph) {
+        return Err("'lrta' does not support negative costs".into());
+    }
+    let (start, end) = (find_node(&graph, &from)?, find_node(&graph, &to)?);
+    let optimal = match shortest_path_tree(&graph, start).distance(end) {
+        Some(optimal) => optimal,
+        None => {
+            eprintln!("There is no path from '{}' to '{}'.", from, to);
+            return Ok(());
+        }
+    };
+    println!("Optimal cost: {}", input.scale.format(optimal));
+
+    let landmarks = Landmarks::select(&graph, landmarks);
+    let heuristic = graph
+        .node_ids()
+        .map(|node| landmarks.lower_bound(node, end))
+        .collect();
+    let mut agent = RealTimeAgent::new(&graph, end, heuristic, lookahead);
+    let max_moves = max_moves.unwrap_or(100 * graph.node_count());
+    let mut last = None;
+    for i in 1..=trials {
+        let trial = agent.trial(start, max_moves);
+        match &trial.path {
+            Some(path) => println!(
+                "Trial {}: reached '{}' in {} moves at cost {} ({:.3} times optimal), {} \
+                 heuristic updates",
+                i,
+                to,
+                trial.moves,
+                input.scale.format(path.cost),
+                ratio(path.cost, optimal),
+                trial.updates
+            ),
+            None => println!(
+                "Trial {}: stopped short of '{}' after {} moves, {} heuristic updates",
+                i, to, trial.moves, trial.updates
+            ),
+        }
+        let converged = trial.updates == 0;
+        last = trial.path;
+        if converged {
+            println!("The heuristic has converged.");
+            break;
+        }
+    }
+    if let Some(path) = last {
+        println!("{}", format_route(&graph, &path, input.scale));
+    }
+    Ok(())
+}
+
+fn ratio(cost: Weight, optimal: Weight) -> f64 {
+    if cost == optimal {
+        1.0
+    } else {
+        cost as f64 / optimal as f64
+    }
+}
@@ -0,0 +1,62 @@
+use super::{find_node, write_output, Args, Input};
+use cs365_bonus::markov::{MarkovChain, Solution};
+
+/// Treat edge weights as transition propensities and print, for each node, its share of
+/// the stationary distribution, or with `--hitting <node>` the expected steps to reach that
+/// node, or with `--absorb <node>` the probability of ending up absorbed there.
+///
+/// `--iterations` and `--tolerance` bound the iterative solve, as for `spectral`.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let hitting = args.value("--hitting")?;
+    let absorb = args.value("--absorb")?;
+    let iterations = args.parsed::<usize>("--iterations")?.unwrap_or(10_000);
+    let tolerance = args.parsed::<f64>("--tolerance")?.unwrap_or(1e-9);
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let chain = MarkovChain::new(&graph)?;
+    let solution: Solution = match (hitting, absorb) {
+        (Some(_), Some(_)) => {
+            return Err("only one of '--hitting' and '--absorb' may be given".into())
+        }
+        (Some(target), None) => {
+            chain.hitting_times(&[find_node(&graph, &target)?], iterations, tolerance)
+        }
+        (None, Some(state)) => {
+            let state = find_node(&graph, &state)?;
+            if !chain.is_absorbing(state.index()) {
+                eprintln!(
+                    "note: '{}' has a way out, so this is the chance of ever visiting it",
+                    graph.get_node_name(state).unwrap_or("")
+                );
+            }
+            chain.absorption_probabilities(state, iterations, tolerance)
+        }
+        (None, None) => {
+            if chain.is_empty() {
+                return Err("the graph has no nodes".into());
+            }
+            chain.stationary(iterations, tolerance)
+        }
+    };
+    if !solution.converged {
+        eprintln!(
+            "warning: the iteration did not converge within {} iterations",
+            solution.iterations
+        );
+    }
+
+    write_output(output.as_deref(), |w| {
+        for (node, value) in graph.node_ids().zip(&solution.values) {
+            writeln!(
+                w,
+                "{} {:.9}",
+                graph.get_node_name(node).unwrap_or(""),
+                value
+            )?;
+        }
+        w.flush()
+    })
+}
@@ -0,0 +1,48 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::matching::{bipartition, max_weight_matching};
+
+/// Find a matching of the greatest total cost, ignoring edge direction, and write its
+/// edges along with how many there are and their total cost.
+///
+/// The graph must be bipartite unless `--general` is given, which also allows odd cycles,
+/// such as pairing people from a single pool rather than across two. `--max-cardinality`
+/// matches as many edges as possible first, and only then maximizes their cost.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let general = args.flag("--general");
+    let max_cardinality = args.flag("--max-cardinality");
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    if !general {
+        if let Err(node) = bipartition(&graph) {
+            return Err(format!(
+                "the graph isn't bipartite, with an odd cycle through '{}'; \
+                 use '--general' to match it anyway",
+                graph.get_node_name(node).unwrap_or("")
+            ));
+        }
+    }
+    let matching = max_weight_matching(&graph, max_cardinality);
+    eprintln!(
+        "Matched {} pairs of {} nodes, total weight {}",
+        matching.edges.len(),
+        graph.node_count(),
+        input.scale.format(matching.weight)
+    );
+    let edges: Vec<_> = graph.edges().collect();
+    write_output(output.as_deref(), |w| {
+        for id in &matching.edges {
+            let (_, u, v, cost) = edges[id.index()];
+            writeln!(
+                w,
+                "{} {} {}",
+                graph.get_node_name(u).unwrap_or(""),
+                graph.get_node_name(v).unwrap_or(""),
+                input.scale.format(cost)
+            )?;
+        }
+        w.flush()
+    })
+}
@@ -0,0 +1,917 @@
+mod all_pairs;
+mod alternatives;
+mod arbitrage;
+mod args;
+mod attack_sweep;
+mod batch_query;
+mod build_external;
+mod build_knn;
+mod compare_routes;
+mod components;
+mod compress;
+mod compressed_query;
+mod connectivity;
+mod courses;
+mod cycle_basis;
+mod cycle_through;
+mod dag;
+mod deps;
+mod eccentricity;
+mod edge_color;
+mod ego;
+mod embed;
+mod experiment;
+mod external_query;
+mod girth;
+mod graph_hash;
+mod grid;
+#[cfg(feature = "gtfs")]
+mod gtfs_import;
+mod interactive;
+mod isomorphic;
+mod kcore;
+mod lrta;
+mod markov;
+mod matching;
+mod mst;
+mod node2vec;
+mod oracle;
+mod p_median;
+mod perturb;
+mod predict_links;
+mod preview;
+mod profile;
+mod query;
+mod random_spanning_tree;
+mod relabel;
+mod replacement_paths;
+mod replan;
+mod schedule;
+mod serve;
+mod signed_query;
+mod similarity;
+mod spanner;
+mod spectral;
+mod stable_matching;
+mod stream;
+mod temporal;
+mod trace;
+mod transform;
+mod tree_query;
+mod triangles;
+mod two_sat;
+mod vital;
+mod vrp;
+
+pub use self::args::Args;
+use cs365_bonus::attributes::{weigh, Attributes, ClimbModel};
+use cs365_bonus::compressed::{CompressedGraph, MAGIC};
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::expr::Expr;
+use cs365_bonus::geo::{distance_weights, Metric};
+use cs365_bonus::reorder::{reorder, Reordering};
+use cs365_bonus::server::http::json_string;
+use cs365_bonus::spans::{self, span};
+use cs365_bonus::{parse_scaled, Graph, NodeId, ParseErrorKind, ParseGraphError, Path};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::str::FromStr;
+
+pub const COMMANDS: &[&str] = &[
+    "2sat",
+    "all-pairs",
+    "alternatives",
+    "arbitrage",
+    "attack-sweep",
+    "batch-query",
+    "build-external",
+    "build-knn",
+    "compare-routes",
+    "components",
+    "compress",
+    "compressed-query",
+    "connectivity",
+    "courses",
+    "cycle-basis",
+    "cycle-through",
+    "dag",
+    "deps",
+    "eccentricity",
+    "edge-color",
+    "ego",
+    "embed",
+    "experiment",
+    "external-query",
+    "girth",
+    "graph-hash",
+    "grid",
+    #[cfg(feature = "gtfs")]
+    "gtfs-import",
+    "isomorphic",
+    "kcore",
+    "lrta",
+    "markov",
+    "matching",
+    "mst",
+    "node2vec",
+    "oracle",
+    "p-median",
+    "perturb",
+    "predict-links",
+    "preview",
+    "profile",
+    "random-spanning-tree",
+    "relabel",
+    "replan",
+    "replacement-paths",
+    "schedule",
+    "serve",
+    "signed-query",
+    "similarity",
+    "spanner",
+    "spectral",
+    "stable-matching",
+    "stream",
+    "temporal",
+    "trace",
+    "transform",
+    "tree-query",
+    "triangles",
+    "vital",
+    "vrp",
+];
+
+/// Run the command named by `args`, with `--trace-spans <file>` writing how long each
+/// phase of it took, in microseconds, as folded stacks: one `phase;subphase time` line
+/// per stack, as read by flame graph tools such as `inferno-flamegraph` and speedscope.
+///
+/// This is wall-clock time in the phases marked with [`span`], not a sampled CPU profile,
+/// and the output is not in the pprof format.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let trace = args.value("--trace-spans")?;
+    if trace.is_none() {
+        return dispatch(args);
+    }
+    spans::start();
+    let result = {
+        let _run = span("run");
+        dispatch(args)
+    };
+    let recording = spans::finish().unwrap_or_default();
+    if let Some(path) = trace {
+        let file =
+            File::create(&path).map_err(|e| format!("Failed to create '{}': {}", path, e))?;
+        recording
+            .write_folded(&mut std::io::BufWriter::new(file))
+            .map_err(|e| format!("Failed to write '{}': {}", path, e))?;
+        eprintln!(
+            "Wrote span timings of {:?} to '{}'.",
+            recording.total(),
+            path
+        );
+    }
+    result
+}
+
+fn dispatch(mut args: Args) -> Result<(), String> {
+    match args.subcommand(COMMANDS) {
+        Some("2sat") => two_sat::run(args),
+        Some("all-pairs") => all_pairs::run(args),
+        Some("alternatives") => alternatives::run(args),
+        Some("arbitrage") => arbitrage::run(args),
+        Some("attack-sweep") => attack_sweep::run(args),
+        Some("batch-query") => batch_query::run(args),
+        Some("build-external") => build_external::run(args),
+        Some("build-knn") => build_knn::run(args),
+        Some("compare-routes") => compare_routes::run(args),
+        Some("components") => components::run(args),
+        Some("compress") => compress::run(args),
+        Some("compressed-query") => compressed_query::run(args),
+        Some("connectivity") => connectivity::run(args),
+        Some("courses") => courses::run(args),
+        Some("cycle-basis") => cycle_basis::run(args),
+        Some("cycle-through") => cycle_through::run(args),
+        Some("dag") => dag::run(args),
+        Some("deps") => deps::run(args),
+        Some("eccentricity") => eccentricity::run(args),
+        Some("edge-color") => edge_color::run(args),
+        Some("ego") => ego::run(args),
+        Some("embed") => embed::run(args),
+        Some("experiment") => experiment::run(args),
+        Some("external-query") => external_query::run(args),
+        Some("girth") => girth::run(args),
+        Some("graph-hash") => graph_hash::run(args),
+        Some("grid") => grid::run(args),
+        #[cfg(feature = "gtfs")]
+        Some("gtfs-import") => gtfs_import::run(args),
+        Some("isomorphic") => isomorphic::run(args),
+        Some("kcore") => kcore::run(args),
+        Some("lrta") => lrta::run(args),
+        Some("markov") => markov::run(args),
+        Some("matching") => matching::run(args),
+        Some("mst") => mst::run(args),
+        Some("node2vec") => node2vec::run(args),
+        Some("oracle") => oracle::run(args),
+        Some("p-median") => p_median::run(args),
+        Some("perturb") => perturb::run(args),
+        Some("predict-links") => predict_links::run(args),
+        Some("preview") => preview::run(args),
+        Some("profile") => profile::run(args),
+        Some("random-spanning-tree") => random_spanning_tree::run(args),
+        Some("relabel") => relabel::run(args),
+        Some("replan") => replan::run(args),
+        Some("replacement-paths") => replacement_paths::run(args),
+        Some("schedule") => schedule::run(args),
+        Some("serve") => serve::run(args),
+        Some("signed-query") => signed_query::run(args),
+        Some("similarity") => similarity::run(args),
+        Some("spanner") => spanner::run(args),
+        Some("spectral") => spectral::run(args),
+        Some("stable-matching") => stable_matching::run(args),
+        Some("stream") => stream::run(args),
+        Some("temporal") => temporal::run(args),
+        Some("trace") => trace::run(args),
+        Some("transform") => transform::run(args),
+        Some("tree-query") => tree_query::run(args),
+        Some("triangles") => triangles::run(args),
+        Some("vital") => vital::run(args),
+        Some("vrp") => vrp::run(args),
+        _ => query::run(args),
+    }
+}
+
+/// The file read when a command isn't given one.
+const DEFAULT_INPUT: &str = "input.txt";
+
+/// Edge lists larger than this are parsed as they are read instead of loaded whole and
+/// parsed in parallel.
+const STREAM_ABOVE_BYTES: u64 = 256 << 20;
+
+/// Options controlling how input files are loaded and merged into one graph.
+struct Input {
+    namespaces: Vec<String>,
+    connectors: Option<String>,
+    directed: bool,
+    reorder: Option<Reordering>,
+    /// How edge list costs are read, and how costs are printed.
+    scale: FixedPoint,
+    attributes: Option<String>,
+    weight_expr: Option<Expr>,
+    distance_weights: Option<Metric>,
+}
+
+impl Input {
+    /// Pull `--namespace <prefix>` (once per input file), `--connectors <file>`,
+    /// `--directed`, `--reorder <bfs|rcm>`, `--decimals <places>`, `--attributes <file>`,
+    /// `--weight-expr <expr>`, `--uphill-factor <k>`, `--downhill-factor <k>`, and
+    /// `--distance-weights <haversine|euclidean>` from `args`.
+    ///
+    /// With `--decimals`, edge list costs keep up to that many digits after the point,
+    /// more being rounded with a warning, and costs are printed the same way. The uphill
+    /// and downhill factors stand for the weight expression of a [`ClimbModel`], charging
+    /// that much per unit of the `elevation` attribute climbed or descended.
+    fn from_args(args: &mut Args) -> Result<Self, String> {
+        let places = args.parsed::<u32>("--decimals")?.unwrap_or(0);
+        let scale = FixedPoint::new(places).ok_or_else(|| {
+            format!(
+                "'--decimals' may be at most {}",
+                cs365_bonus::decimal::MAX_PLACES
+            )
+        })?;
+        let mut weight_expr = args
+            .value("--weight-expr")?
+            .map(|expr| {
+                expr.parse()
+                    .map_err(|e| format!("invalid '--weight-expr': {}", e))
+            })
+            .transpose()?;
+        let factor = |args: &mut Args, name| match args.parsed::<f64>(name)? {
+            Some(factor) if !(factor >= 0.0 && factor.is_finite()) => {
+                Err(format!("'{}' must be a non-negative number", name))
+            }
+            factor => Ok(factor),
+        };
+        let uphill = factor(args, "--uphill-factor")?;
+        let downhill = factor(args, "--downhill-factor")?;
+        if uphill.is_some() || downhill.is_some() {
+            if weight_expr.is_some() {
+                return Err(
+                    "'--weight-expr' can't be combined with '--uphill-factor' or \
+                     '--downhill-factor'"
+                        .into(),
+                );
+            }
+            let model = ClimbModel {
+                uphill: uphill.unwrap_or(0.0),
+                downhill: downhill.unwrap_or(0.0),
+            };
+            weight_expr = Some(model.expr());
+        }
+        let distance_weights = args.parsed::<Metric>("--distance-weights")?;
+        if distance_weights.is_some() && weight_expr.is_some() {
+            return Err(
+                "'--distance-weights' can't be combined with other edge cost options".into(),
+            );
+        }
+        Ok(Self {
+            namespaces: args.values("--namespace")?,
+            connectors: args.value("--connectors")?,
+            directed: args.flag("--directed"),
+            reorder: args.value("--reorder")?.map(|s| s.parse()).transpose()?,
+            scale,
+            attributes: args.value("--attributes")?,
+            weight_expr,
+            distance_weights,
+        })
+    }
+
+    /// The `--attributes` file read for the nodes and edges of `graph`, as returned by
+    /// [`load`](Self::load), if one was given.
+    fn load_attributes(&self, graph: &Graph) -> Result<Option<Attributes>, String> {
+        self.attributes
+            .as_deref()
+            .map(|path| read_attributes(graph, path))
+            .transpose()
+    }
+
+    /// Every file [`load`](Self::load) reads for the given files.
+    fn sources(&self, files: &[String]) -> Vec<String> {
+        let mut sources = if files.is_empty() {
+            vec![DEFAULT_INPUT.to_string()]
+        } else {
+            files.to_vec()
+        };
+        sources.extend(self.connectors.iter().cloned());
+        sources.extend(self.attributes.iter().cloned());
+        sources
+    }
+
+    /// Load and merge the given files, defaulting to `input.txt`, with `-` for stdin.
+    ///
+    /// Each file's node names get the matching `--namespace` prefix. Connector edges are
+    /// then added between the layers and must refer to namespaced names. With
+    /// `--weight-expr` every edge's cost is then recomputed from the `--attributes` file,
+    /// see [`weigh`], or with `--distance-weights` measured from the positions of its ends,
+    /// see [`distance_weights`]. With `--directed` every edge then only runs from its first node to its
+    /// second. Finally the nodes are renumbered if `--reorder` was given.
+    fn load(&self, files: &[String]) -> Result<Graph, String> {
+        let _load = span("load");
+        let default = [DEFAULT_INPUT.to_string()];
+        let files = if files.is_empty() { &default } else { files };
+        if !self.namespaces.is_empty() && self.namespaces.len() != files.len() {
+            return Err(format!(
+                "got {} '--namespace' prefixes for {} input files",
+                self.namespaces.len(),
+                files.len()
+            ));
+        }
+
+        let mut graph = read_graph(&files[0], self.scale)?;
+        if files.len() > 1 || !self.namespaces.is_empty() {
+            let _merge = span("merge");
+            let mut merged = Graph::new();
+            for (i, file) in files.iter().enumerate() {
+                let layer = if i == 0 {
+                    std::mem::take(&mut graph)
+                } else {
+                    read_graph(file, self.scale)?
+                };
+                let prefix = self.namespaces.get(i).map_or("", |s| s.as_str());
+                merged.merge(&layer, prefix);
+            }
+            graph = merged;
+        }
+
+        if let Some(path) = &self.connectors {
+            let _connect = span("connectors");
+            let connectors = read_graph(path, self.scale)?;
+            for (_, src, dest, cost) in connectors.edges() {
+                let lookup = |node| {
+                    let name = connectors.get_node_name(node).unwrap_or("");
+                    graph.get_node(name).ok_or_else(|| {
+                        format!("connector '{}' refers to unknown node '{}'", path, name)
+                    })
+                };
+                let (src, dest) = (lookup(src)?, lookup(dest)?);
+                graph.add_bidirectional_edge(src, dest, cost);
+            }
+        }
+
+        if let Some(expr) = &self.weight_expr {
+            let _weigh = span("weigh");
+            let attributes = match &self.attributes {
+                Some(path) => read_attributes(&graph, path)?,
+                None => Attributes::new(),
+            };
+            graph = weigh(&graph, &attributes, expr, self.scale)
+                .map_err(|e| format!("Failed to compute edge costs: {}", e))?;
+        }
+        if let Some(metric) = self.distance_weights {
+            let _weigh = span("weigh");
+            let path = self
+                .attributes
+                .as_deref()
+                .ok_or("'--distance-weights' requires '--attributes <file>'")?;
+            graph = distance_weights(&graph, &read_attributes(&graph, path)?, metric, self.scale)
+                .map_err(|e| format!("Failed to compute edge costs: {}", e))?;
+        }
+
+        if self.directed {
+            let _direct = span("directed");
+            graph = graph.to_directed();
+        }
+        if let Some(reordering) = self.reorder {
+            let _reorder = span("reorder");
+            graph = reorder(&graph, reordering);
+        }
+
+        Ok(graph)
+    }
+}
+
+/// Read an edge list, a Graphviz file ending in `.dot` or `.gv`, or a graph written by
+/// `compress`, reading edge list costs with `scale`. `-` reads an edge list from stdin.
+///
+/// Edge list costs with more digits after the point than `scale` keeps are rounded, with
+/// a warning, rather than rejected.
+fn read_graph(path: &str, scale: FixedPoint) -> Result<Graph, String> {
+    let _read = span("read");
+    if path == "-" {
+        // Read whole, as stdin can't be parsed a second time if costs need rounding.
+        let mut data = String::new();
+        std::io::stdin()
+            .read_to_string(&mut data)
+            .map_err(|e| format!("Failed to read input graph from stdin: {}", e))?;
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        return parse_rounding("stdin", scale, |scale| parse_text(&data, scale, threads))
+            .map_err(|e| format!("Failed to parse input graph from stdin: {}", e));
+    }
+    let file = File::open(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let large = file
+        .metadata()
+        .is_ok_and(|metadata| metadata.len() > STREAM_ABOVE_BYTES);
+    let mut reader = BufReader::new(file);
+    let read_error = |e: std::io::Error| format!("Failed to read '{}': {}", path, e);
+    let compressed = reader.fill_buf().map_err(read_error)?.starts_with(MAGIC);
+    let dot = path.ends_with(".dot") || path.ends_with(".gv");
+    if large && !compressed && !dot {
+        let _stream = span("stream");
+        let mut reader = Some(reader);
+        return parse_rounding(path, scale, |scale| {
+            let reader = match reader.take() {
+                Some(reader) => reader,
+                None => BufReader::new(File::open(path)?),
+            };
+            Graph::from_reader_scaled(reader, scale)
+        })
+        .map_err(|e| format!("Failed to parse input graph '{}': {}", path, e));
+    }
+
+    let mut data = Vec::new();
+    {
+        let _io = span("io");
+        reader.read_to_end(&mut data).map_err(read_error)?;
+    }
+    if compressed {
+        let _decompress = span("decompress");
+        return CompressedGraph::from_bytes(data)
+            .and_then(|graph| graph.to_graph())
+            .map_err(|e| format!("Failed to decompress '{}': {}", path, e));
+    }
+    let data = String::from_utf8(data)
+        .map_err(|_| format!("Failed to parse input graph '{}': not UTF-8 text", path))?;
+    if dot {
+        let _parse = span("parse-dot");
+        return Graph::from_dot(&data)
+            .map_err(|e| format!("Failed to parse input graph '{}': {}", path, e));
+    }
+    let _parse = span("parse");
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    parse_rounding(path, scale, |scale| parse_text(&data, scale, threads))
+        .map_err(|e| format!("Failed to parse input graph '{}': {}", path, e))
+}
+
+/// [`parse_scaled`] with its errors wrapped like those of [`Graph::from_reader_scaled`].
+fn parse_text(data: &str, scale: FixedPoint, threads: usize) -> std::io::Result<Graph> {
+    parse_scaled(data, scale, threads)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Parse the edge list `name` with `parse`, and if that fails on a cost with more digits
+/// after the point than `scale` keeps, warn and parse it again rounding such costs.
+fn parse_rounding(
+    name: &str,
+    scale: FixedPoint,
+    mut parse: impl FnMut(FixedPoint) -> std::io::Result<Graph>,
+) -> std::io::Result<Graph> {
+    let error = match parse(scale) {
+        Err(error) => error,
+        graph => return graph,
+    };
+    let parse_error = error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<ParseGraphError>());
+    let (cost, line) = match parse_error {
+        Some(e) => match e.kind() {
+            ParseErrorKind::InvalidCost(cost) if scale.rounding().parse(cost).is_some() => {
+                (cost.clone(), e.line())
+            }
+            _ => return Err(error),
+        },
+        None => return Err(error),
+    };
+    eprintln!(
+        "Warning: '{}' has costs with more than {} decimal places, such as '{}' on line {}; \
+         rounding them. Pass '--decimals <places>' to keep more.",
+        name,
+        scale.places(),
+        cost,
+        line
+    );
+    parse(scale.rounding())
+}
+
+/// Read the `--attributes` file at `path` for the nodes and edges of `graph`.
+fn read_attributes(graph: &Graph, path: &str) -> Result<Attributes, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    Attributes::parse(graph, &data).map_err(|e| format!("Invalid attributes in '{}': {}", path, e))
+}
+
+/// Run `write` against the file at `output`, or stdout if no file was given.
+fn write_output(
+    output: Option<&str>,
+    write: impl FnOnce(&mut dyn Write) -> std::io::Result<()>,
+) -> Result<(), String> {
+    let result = match output {
+        Some(path) => {
+            let file =
+                File::create(path).map_err(|e| format!("Failed to create '{}': {}", path, e))?;
+            write(&mut std::io::BufWriter::new(file))
+        }
+        None => write(&mut std::io::stdout().lock()),
+    };
+    result.map_err(|e| format!("Failed to write output: {}", e))
+}
+
+/// Look up a node by name, failing with a readable error if it doesn't exist.
+fn find_node(graph: &Graph, name: &str) -> Result<NodeId, String> {
+    graph
+        .get_node(name)
+        .ok_or_else(|| format!("node '{}' does not exist in the graph", name))
+}
+
+/// How a command prints its results, chosen with `--format <text|json>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format '{}', expected 'text' or 'json'", s)),
+        }
+    }
+}
+
+impl Format {
+    /// Print a line describing a result beyond the result itself. It goes to stderr in
+    /// JSON mode, so that stdout holds nothing but the JSON.
+    fn note(self, line: &str) {
+        match self {
+            Format::Text => println!("{}", line),
+            Format::Json => eprintln!("{}", line),
+        }
+    }
+}
+
+/// Format a path as `{"cost": 3, "route": [{"node": "a", "cumulative_distance": 0}, ...]}`
+/// with costs printed with `scale`.
+fn route_json(graph: &Graph, path: &Path, scale: FixedPoint) -> String {
+    let route: Vec<String> = path
+        .nodes
+        .iter()
+        .zip(path.distance.iter())
+        .map(|(node, &cost)| {
+            let name = graph.get_node_name(*node).unwrap_or("");
+            format!(
+                "{{\"node\":{},\"cumulative_distance\":{}}}",
+                json_string(name),
+                scale.format(cost)
+            )
+        })
+        .collect();
+    format!(
+        "{{\"cost\":{},\"route\":[{}]}}",
+        scale.format(path.cost),
+        route.join(",")
+    )
+}
+
+/// Format a path as `a (0) -> b (2) -> ...` with cumulative costs printed with `scale`.
+fn format_route(graph: &Graph, path: &Path, scale: FixedPoint) -> String {
+    let route: Vec<String> = path
+        .nodes
+        .iter()
+        .zip(path.distance.iter())
+        .map(|(node, &cost)| {
+            let name = graph.get_node_name(*node).unwrap_or("");
+            format!("{} ({})", name, scale.format(cost))
+        })
+        .collect();
+    route.join(" -> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A fresh path in the temporary directory ending in `extension`.
+    fn temp_path(extension: &str) -> std::path::PathBuf {
+        static FILES: AtomicUsize = AtomicUsize::new(0);
+        let id = FILES.fetch_add(1, Ordering::Relaxed);
+        let name = format!("cs365-{}-{}.{}", std::process::id(), id, extension);
+        std::env::temp_dir().join(name)
+    }
+
+    /// Run `args` with the edge list `graph` as the input file.
+    fn run_with_input(graph: &str, args: &[&str]) -> Result<(), String> {
+        let input = temp_path("txt");
+        std::fs::write(&input, graph).unwrap();
+        let mut args = args.to_vec();
+        args.push(input.to_str().unwrap());
+        let result = run(Args::new(&args));
+        let _ = std::fs::remove_file(input);
+        result
+    }
+
+    /// Run `args` with the edge list `graph` as the input file, returning what was written
+    /// to `--output`.
+    fn run_on(graph: &str, args: &[&str]) -> Result<String, String> {
+        let output = temp_path("out");
+        let mut args = args.to_vec();
+        args.extend(["--output", output.to_str().unwrap()]);
+        let result = run_with_input(graph, &args);
+        let written = std::fs::read_to_string(&output).unwrap_or_default();
+        let _ = std::fs::remove_file(output);
+        result.map(|()| written)
+    }
+
+    #[test]
+    fn all_pairs_allows_negative_costs() {
+        let graph = "a b 4 ->\na c 1 ->\nb c -5 ->";
+        let matrix = run_on(graph, &["all-pairs"]).unwrap();
+        assert_eq!(matrix, ",a,b,c\na,0,4,-1\nb,,0,-5\nc,,,0\n");
+        let cycle = run_on("a b 1 ->\nb a -2 ->", &["all-pairs"]);
+        assert!(cycle.unwrap_err().starts_with("negative cycle"));
+    }
+
+    #[test]
+    fn writers_print_decimal_costs() {
+        let graph = "a b 1.5\nb c 3.25";
+        let transformed = run_on(graph, &["transform", "--decimals", "2", "--op", "scale:2"]);
+        assert_eq!(transformed.unwrap(), "a b 3\nb c 6.5\n");
+        let args = ["transform", "--decimals", "2", "--op", "clamp:0:2.25"];
+        assert_eq!(run_on(graph, &args).unwrap(), "a b 1.5\nb c 2.25\n");
+        let core = run_on(graph, &["kcore", "--decimals", "2", "--k", "1"]);
+        assert_eq!(core.unwrap(), "a b 1.5\nb c 3.25\n");
+        let matrix = run_on(graph, &["all-pairs", "--decimals", "2"]);
+        assert_eq!(
+            matrix.unwrap(),
+            ",a,b,c\na,0,1.5,4.75\nb,1.5,0,3.25\nc,4.75,3.25,0\n"
+        );
+    }
+
+    #[test]
+    fn queries_take_their_ends_from_options() {
+        let graph = "a b 1\nb c 2";
+        assert_eq!(run_with_input(graph, &["--from", "c", "--to", "a"]), Ok(()));
+        // Neither end defaults to a node that is missing from the graph.
+        let missing = run_with_input(graph, &["--from", "b"]);
+        assert_eq!(missing.unwrap_err(), "node 'z' does not exist in the graph");
+        let missing = run_with_input(graph, &["--from", "x", "--to", "c"]);
+        assert_eq!(missing.unwrap_err(), "node 'x' does not exist in the graph");
+    }
+
+    #[test]
+    fn all_pairs_resumes_from_a_checkpoint() {
+        use cs365_bonus::checkpoint::{graph_fingerprint, RowCheckpoint};
+
+        let graph = "a b 1\nb c 2";
+        let file = temp_path("checkpoint");
+        let path = file.to_str().unwrap();
+        let fingerprint = graph_fingerprint(&graph.parse().unwrap());
+        // A made-up saved row shows that finished sources are not searched again.
+        let saved = vec![Some(vec![Some(0), Some(7), Some(9)]), None, None];
+        RowCheckpoint::new(&file, "all-pairs", fingerprint)
+            .save(&saved)
+            .unwrap();
+        let args = ["all-pairs", "--checkpoint", path, "--resume"];
+        let matrix = run_on(graph, &args).unwrap();
+        assert_eq!(matrix, ",a,b,c\na,0,7,9\nb,1,0,2\nc,3,2,0\n");
+        assert!(!file.exists());
+
+        RowCheckpoint::new(&file, "all-pairs", fingerprint)
+            .save(&saved)
+            .unwrap();
+        let changed = run_on("a b 1\nb c 5", &args).unwrap_err();
+        assert!(changed.ends_with("checkpoint was written for a different graph"));
+        let _ = std::fs::remove_file(file);
+        let missing = run_on(graph, &["all-pairs", "--resume"]);
+        assert_eq!(
+            missing.unwrap_err(),
+            "'--resume' requires '--checkpoint <file>'"
+        );
+    }
+
+    #[test]
+    fn interrupted_all_pairs_writes_finished_rows() {
+        use cs365_bonus::checkpoint::{graph_fingerprint, RowCheckpoint};
+        use cs365_bonus::interrupt;
+
+        let graph = "a b 1\nb c 2";
+        let (input, output, file) = (temp_path("txt"), temp_path("out"), temp_path("checkpoint"));
+        std::fs::write(&input, graph).unwrap();
+        let checkpoint = RowCheckpoint::new(
+            &file,
+            "all-pairs",
+            graph_fingerprint(&graph.parse().unwrap()),
+        );
+        let saved = vec![Some(vec![Some(0), Some(1), Some(3)]), None, None];
+        checkpoint.save(&saved).unwrap();
+
+        // Interrupted before the first search, so only the resumed row is written.
+        let args = [
+            "--checkpoint",
+            file.to_str().unwrap(),
+            "--resume",
+            "--output",
+            output.to_str().unwrap(),
+            input.to_str().unwrap(),
+        ];
+        interrupt::simulate(true);
+        let finished = all_pairs::run_job(Args::new(&args));
+        interrupt::simulate(false);
+        assert_eq!(finished, Ok(false));
+        let matrix = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(matrix, "# partial: 1 of 3 sources\n,a,b,c\na,0,1,3\n");
+        assert_eq!(checkpoint.load(3).unwrap(), saved);
+
+        assert_eq!(all_pairs::run_job(Args::new(&args)), Ok(true));
+        let matrix = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(matrix, ",a,b,c\na,0,1,3\nb,1,0,2\nc,3,2,0\n");
+        assert!(!file.exists());
+        for path in [input, output] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn explain_works_with_every_kind_of_query() {
+        let graph = "a b 1\nb c 2";
+        let (sources, targets) = (temp_path("txt"), temp_path("txt"));
+        std::fs::write(&sources, "a\n").unwrap();
+        std::fs::write(&targets, "c\n").unwrap();
+        let queries: [&[&str]; 5] = [
+            &["--from", "a", "--to", "c"],
+            &["--from", "a", "--to", "c", "--epsilon", "1.5"],
+            &["--from", "a", "--to", "c", "--epsilon", "2", "--ara"],
+            &["--from", "a", "--to", "c", "--k", "2"],
+            &[
+                "--from-set",
+                sources.to_str().unwrap(),
+                "--to-set",
+                targets.to_str().unwrap(),
+            ],
+        ];
+        for query in queries {
+            let mut args = vec!["--explain"];
+            args.extend_from_slice(query);
+            assert_eq!(run_with_input(graph, &args), Ok(()), "{:?}", args);
+        }
+        for path in [sources, targets] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn dot_highlights_every_ranked_route() {
+        let graph = "a b 1\nb z 1\na c 2\nc z 2\na z 9";
+        let dot = temp_path("dot");
+        let args = ["--k", "2", "--dot", dot.to_str().unwrap()];
+        assert_eq!(run_with_input(graph, &args), Ok(()));
+        let written = std::fs::read_to_string(&dot).unwrap();
+        let _ = std::fs::remove_file(&dot);
+        let highlighted = written.lines().filter(|line| line.contains("color=red"));
+        // Nodes a, b, c, z and the four edges of the two cheapest routes.
+        assert_eq!(highlighted.count(), 8);
+        assert!(written.contains("\"a\" -- \"z\" [weight=9, label=\"9\"];"));
+
+        let args = ["--all-pairs", "--dot", dot.to_str().unwrap()];
+        let error = run_with_input(graph, &args).unwrap_err();
+        assert_eq!(error, "'--dot' can't be combined with '--all-pairs'");
+        assert!(!dot.exists());
+    }
+
+    #[test]
+    fn edge_options_take_hyphenated_names() {
+        let graph = "new-york a 1\na z 1\nnew-york z 5";
+        let args = [
+            "--from",
+            "new-york",
+            "--to",
+            "z",
+            "--must-use-edge",
+            "new-york",
+            "z",
+        ];
+        assert_eq!(run_with_input(graph, &args), Ok(()));
+        let args = ["--from", "new-york", "--must-use-edge", "a", "new-york-z"];
+        let missing = run_with_input(graph, &args).unwrap_err();
+        assert_eq!(missing, "node 'new-york-z' does not exist in the graph");
+        let args = ["--from", "new-york", "--must-use-edge=new-york-z"];
+        assert!(run_with_input(graph, &args).is_err());
+    }
+
+    #[test]
+    fn avoided_edges_take_hyphenated_names() {
+        let graph = "new-york boston 1\nboston z 1\nnew-york z 5";
+        let avoid = |edge: [&str; 2]| {
+            let args = ["--from", "new-york", "--to", "z", "--format", "json"];
+            let mut args = args.to_vec();
+            args.extend(["--avoid-edge", edge[0], edge[1]]);
+            run_with_input(graph, &args)
+        };
+        assert_eq!(avoid(["new-york", "boston"]), Ok(()));
+        let missing = avoid(["new-york", "chicago"]).unwrap_err();
+        assert_eq!(missing, "node 'chicago' does not exist in the graph");
+        let absent = avoid(["boston", "boston"]).unwrap_err();
+        assert_eq!(absent, "there is no edge from 'boston' to 'boston'");
+    }
+
+    #[test]
+    fn span_traces_do_not_shadow_the_profile_subcommand() {
+        let trace = temp_path("folded");
+        let args = [
+            "--trace-spans",
+            trace.to_str().unwrap(),
+            "--from",
+            "a",
+            "--to",
+            "b",
+        ];
+        assert_eq!(run_with_input("a b 1", &args), Ok(()));
+        let folded = std::fs::read_to_string(&trace).unwrap();
+        let _ = std::fs::remove_file(&trace);
+        assert!(folded.lines().any(|line| line.starts_with("run;query ")));
+        // The old flag name is no longer taken, so it can't swallow a subcommand.
+        let error = run_with_input("a b 1", &["--profile", "profile"]).unwrap_err();
+        assert_eq!(error, "unknown option '--profile'");
+    }
+
+    #[test]
+    fn extra_decimal_places_are_rounded() {
+        let graph = "a b 1.25\nb c 2";
+        let matrix = run_on(graph, &["all-pairs"]).unwrap();
+        assert_eq!(matrix, ",a,b,c\na,0,1,3\nb,1,0,2\nc,3,2,0\n");
+        let matrix = run_on(graph, &["all-pairs", "--decimals", "1"]).unwrap();
+        assert_eq!(matrix, ",a,b,c\na,0,1.3,3.3\nb,1.3,0,2\nc,3.3,2,0\n");
+        let exact = run_on(graph, &["all-pairs", "--decimals", "2"]).unwrap();
+        assert_eq!(exact, ",a,b,c\na,0,1.25,3.25\nb,1.25,0,2\nc,3.25,2,0\n");
+        let invalid = run_on("a b 1.2x", &["all-pairs"]).unwrap_err();
+        assert!(invalid.starts_with("Failed to parse input graph"));
+    }
+
+    #[test]
+    fn unsatisfiable_formulas_fail_after_writing_output() {
+        let satisfied = run_on("a b\n-a", &["2sat"]).unwrap();
+        assert_eq!(satisfied, "satisfiable\na false\nb true\n");
+
+        let (clauses, output) = (temp_path("txt"), temp_path("out"));
+        std::fs::write(&clauses, "a\n-a").unwrap();
+        let path = clauses.to_str().unwrap();
+        let result = run(Args::new(&[
+            "2sat",
+            "--output",
+            output.to_str().unwrap(),
+            path,
+        ]));
+        assert_eq!(result, Err(format!("'{}' is unsatisfiable", path)));
+        let written = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(
+            written,
+            "unsatisfiable: 'a' implies its own negation and is implied by it\n"
+        );
+        for file in [clauses, output] {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::Weight;
+
+/// Write a minimum spanning tree of the input as an edge list, ignoring edge direction,
+/// and report its total cost. A disconnected input gets a spanning forest.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let tree = graph.minimum_spanning_tree();
+    let mut total: Weight = 0;
+    write_output(output.as_deref(), |w| {
+        let edges = tree.iter().filter_map(|&id| {
+            let (src, dest) = graph.edge_endpoints(id)?;
+            Some((src, dest, graph.edge_weight(id)?))
+        });
+        for (src, dest, cost) in edges {
+            total += cost;
+            writeln!(
+                w,
+                "{} {} {}",
+                graph.get_node_name(src).unwrap_or(""),
+                graph.get_node_name(dest).unwrap_or(""),
+                input.scale.format(cost)
+            )?;
+        }
+        w.flush()
+    })?;
+    eprintln!(
+        "Total cost {} over {} edges.",
+        input.scale.format(total),
+        tree.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::node2vec::{biased_walks, SkipGram, Walks};
+use cs365_bonus::rng::Rng;
+
+/// Embed each node as in node2vec: take `--walks <n>` random walks (default 10) of
+/// `--walk-length <n>` nodes (default 80) from every node, then train a skip-gram model on
+/// them, and write the vectors as CSV like `embed` does, one row per node: its name, then
+/// `x1` to `xd`.
+///
+/// `--p` and `--q` (both default 1) bias each step by where the walk came from: a high
+/// `--p` makes walks less likely to turn straight back, and a high `--q` keeps them near
+/// where they were, while a low one sends them further out. With `--weighted`, walks
+/// follow edges in proportion to their cost.
+///
+/// The model has `--dimensions` (default 16), a context `--window` (default 5),
+/// `--negative` samples per pair (default 5), `--epochs` (default 1) and a
+/// `--learning-rate` (default 0.025). `--corpus <file>` also writes the walks, one per line
+/// as node names, for training elsewhere, and with `--no-train` that's all that's done.
+/// `--seed` makes the walks and the training repeatable.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let defaults = Walks::default();
+    let walks = Walks {
+        length: args.parsed("--walk-length")?.unwrap_or(defaults.length),
+        per_node: args.parsed("--walks")?.unwrap_or(defaults.per_node),
+        p: args.parsed("--p")?.unwrap_or(defaults.p),
+        q: args.parsed("--q")?.unwrap_or(defaults.q),
+        weighted: args.flag("--weighted"),
+    };
+    let defaults = SkipGram::default();
+    let model = SkipGram {
+        dimensions: args.parsed("--dimensions")?.unwrap_or(defaults.dimensions),
+        window: args.parsed("--window")?.unwrap_or(defaults.window),
+        negative: args.parsed("--negative")?.unwrap_or(defaults.negative),
+        epochs: args.parsed("--epochs")?.unwrap_or(defaults.epochs),
+        learning_rate: args
+            .parsed("--learning-rate")?
+            .unwrap_or(defaults.learning_rate),
+    };
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let corpus = args.value("--corpus")?;
+    let train = !args.flag("--no-train");
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    if walks.length == 0 || walks.per_node == 0 {
+        return Err("'--walk-length' and '--walks' must be at least 1".into());
+    }
+    if !(walks.p > 0.0 && walks.q > 0.0) {
+        return Err("'--p' and '--q' must be positive".into());
+    }
+    if model.dimensions == 0 {
+        return Err("'--dimensions' must be at least 1".into());
+    }
+    if !train && corpus.is_none() {
+        return Err("'--no-train' needs '--corpus' to write the walks to".into());
+    }
+
+    let graph = input.load(&files)?;
+    let mut rng = Rng::new(seed);
+    let corpus_walks = biased_walks(&graph, &walks, &mut rng);
+    let steps: usize = corpus_walks.iter().map(|walk| walk.len() - 1).sum();
+    eprintln!("{} walks, {} steps", corpus_walks.len(), steps);
+
+    if let Some(file) = corpus {
+        write_output(Some(&file), |w| {
+            for walk in &corpus_walks {
+                let names: Vec<&str> = walk
+                    .iter()
+                    .map(|&node| graph.get_node_name(node).unwrap_or(""))
+                    .collect();
+                writeln!(w, "{}", names.join(" "))?;
+            }
+            w.flush()
+        })?;
+    }
+    if !train {
+        return Ok(());
+    }
+
+    let vectors = model.train(&corpus_walks, graph.node_count(), &mut rng);
+    write_output(output.as_deref(), |w| {
+        write!(w, "node")?;
+        for i in 1..=model.dimensions {
+            write!(w, ",x{}", i)?;
+        }
+        writeln!(w)?;
+        for node in graph.node_ids() {
+            write!(w, "{}", graph.get_node_name(node).unwrap_or(""))?;
+            for x in &vectors[node.index()] {
+                write!(w, ",{:.9}", x)?;
+            }
+            writeln!(w)?;
+        }
+        w.flush()
+    })
+}
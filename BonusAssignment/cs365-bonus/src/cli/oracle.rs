@@ -0,0 +1,63 @@
+use super::{find_node, Args, Input};
+use cs365_bonus::oracle::DistanceOracle;
+use cs365_bonus::rng::Rng;
+use std::io::{BufRead, Write};
+
+/// Build an approximate distance oracle and answer `u v` queries, one per line, read
+/// from the `--queries` file or stdin.
+///
+/// `--stretch` (default 3) must be odd: stretch `2k - 1` stores about `n^(1 + 1/k)`
+/// entries, so larger stretches trade accuracy for space. Disconnected pairs print `none`.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let stretch = args.parsed::<usize>("--stretch")?.unwrap_or(3);
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let queries = args.value("--queries")?;
+    let files = args.finish()?;
+    if stretch % 2 == 0 {
+        return Err("'--stretch' must be an odd number".into());
+    }
+
+    let graph = input.load(&files)?;
+    if graph
+        .edges()
+        .any(|(id, ..)| graph.is_edge_directed(id) == Some(true))
+    {
+        return Err("'oracle' requires an undirected graph".into());
+    }
+    let oracle = DistanceOracle::new(&graph, stretch.div_ceil(2), &mut Rng::new(seed));
+    eprintln!(
+        "Built a stretch {} oracle with {} entries for {} nodes.",
+        oracle.stretch(),
+        oracle.size(),
+        graph.node_count()
+    );
+
+    let reader: Box<dyn BufRead> = match &queries {
+        Some(path) => Box::new(std::io::BufReader::new(
+            std::fs::File::open(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?,
+        )),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read queries: {}", e))?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (u, v) = match words.as_slice() {
+            [] => continue,
+            [word, ..] if word.starts_with('#') => continue,
+            [u, v] => (find_node(&graph, u)?, find_node(&graph, v)?),
+            _ => return Err(format!("line {}: expected 'u v'", i + 1)),
+        };
+        let answer = oracle.distance(u, v);
+        writeln!(
+            out,
+            "{}",
+            answer.map_or_else(|| "none".to_string(), |d| d.to_string())
+        )
+        .and_then(|_| out.flush())
+        .map_err(|e| format!("Failed to write output: {}", e))?;
+    }
+    Ok(())
+}
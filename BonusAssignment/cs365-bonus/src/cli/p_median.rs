@@ -0,0 +1,36 @@
+use super::{Args, Input};
+use cs365_bonus::facility::{p_median, Objective};
+use cs365_bonus::path::has_negative_edges;
+
+/// Choose `--k <n>` facility nodes (default 1) so the total distance from every node to its
+/// nearest facility is small, or with `--objective max`, the largest such distance.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let k = args.parsed::<usize>("--k")?.unwrap_or(1);
+    let objective = args.parsed::<Objective>("--objective")?.unwrap_or_default();
+    let files = args.finish()?;
+    if k == 0 {
+        return Err("'--k' must be at least 1".into());
+    }
+
+    let graph = input.load(&files)?;
+    if has_negative_edges(&graph) {
+        return Err("'p-median' does not support negative costs".into());
+    }
+    let facilities = p_median(&graph, k, objective);
+    let names: Vec<&str> = facilities
+        .nodes
+        .iter()
+        .map(|&node| graph.get_node_name(node).unwrap_or(""))
+        .collect();
+    println!("facilities {}", names.join(" "));
+    let kind = match objective {
+        Objective::Total => "total",
+        Objective::Max => "max",
+    };
+    println!("{} distance {}", kind, input.scale.format(facilities.cost));
+    if facilities.unreached > 0 {
+        println!("unreached {}", facilities.unreached);
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use super::{find_node, write_output, Args, Input};
+use cs365_bonus::rng::Rng;
+use cs365_bonus::robustness::{edge_order, node_order, Target};
+use cs365_bonus::{find_shortest_path, write_edge_list_scaled, Graph};
+
+/// Damage the input for a robustness experiment and write what's left as an edge list,
+/// where nodes left without edges drop out.
+///
+/// `--remove-nodes <fraction>` removes that share of the nodes with their edges, then
+/// `--remove-edges <fraction>` that share of the remaining edges.
+///
+/// `--target` picks what fails: `random` (the default, repeatable with `--seed`), or an
+/// attack on the highest `degree` or `betweenness` first. Edges can only be targeted at
+/// random or by betweenness.
+///
+/// A report of the damage goes to stderr: how the connected components changed, ignoring
+/// direction, and with `--from` and `--to`, how the distance between them changed.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let remove_nodes = args.parsed::<f64>("--remove-nodes")?.unwrap_or(0.0);
+    let remove_edges = args.parsed::<f64>("--remove-edges")?.unwrap_or(0.0);
+    let target = args.parsed::<Target>("--target")?.unwrap_or(Target::Random);
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let from = args.value("--from")?;
+    let to = args.value("--to")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    for (name, fraction) in [
+        ("--remove-nodes", remove_nodes),
+        ("--remove-edges", remove_edges),
+    ] {
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(format!("'{}' must be in [0, 1], got {}", name, fraction));
+        }
+    }
+    if remove_nodes == 0.0 && remove_edges == 0.0 {
+        return Err("'perturb' requires '--remove-nodes' or '--remove-edges'".into());
+    }
+    if remove_edges > 0.0 && target == Target::Degree {
+        return Err("edges can't be targeted by degree, only nodes".into());
+    }
+    if from.is_some() != to.is_some() {
+        return Err("'--from' and '--to' must be given together".into());
+    }
+
+    let original = input.load(&files)?;
+    let mut rng = Rng::new(seed);
+    let count = |fraction: f64, total: usize| (fraction * total as f64).round() as usize;
+
+    let mut removed = vec![false; original.node_count()];
+    let victims = count(remove_nodes, original.node_count());
+    for node in node_order(&original, target, &mut rng)
+        .into_iter()
+        .take(victims)
+    {
+        removed[node.index()] = true;
+    }
+    let graph = original.induced_subgraph(|node| !removed[node.index()]);
+    let victims = count(remove_edges, graph.edge_count());
+    let mut cut = vec![false; graph.edge_count()];
+    if victims > 0 {
+        for edge in edge_order(&graph, target, &mut rng)?
+            .into_iter()
+            .take(victims)
+        {
+            cut[edge.index()] = true;
+        }
+    }
+    let damaged = graph.edge_subgraph(|edge| !cut[edge.index()]);
+
+    eprintln!(
+        "Removed {} of {} nodes and {} of {} edges {}.",
+        original.node_count() - damaged.node_count(),
+        original.node_count(),
+        original.edge_count() - damaged.edge_count(),
+        original.edge_count(),
+        match target {
+            Target::Random => "at random",
+            Target::Degree => "by degree",
+            Target::Betweenness => "by betweenness",
+        }
+    );
+    let components = |graph: &Graph| {
+        let components = graph.connected_components();
+        let largest = components.iter().map(Vec::len).max().unwrap_or(0);
+        (components.len(), largest)
+    };
+    let (before, after) = (components(&original), components(&damaged));
+    eprintln!("Components: {} -> {}", before.0, after.0);
+    eprintln!("Largest component: {} -> {} nodes", before.1, after.1);
+    if let (Some(from), Some(to)) = (from, to) {
+        find_node(&original, &from)?;
+        find_node(&original, &to)?;
+        let distance = |graph: &Graph| match (graph.get_node(&from), graph.get_node(&to)) {
+            (Some(start), Some(end)) => match find_shortest_path(graph, start, end) {
+                Some(path) => input.scale.format(path.cost),
+                None => "unreachable".into(),
+            },
+            _ => "removed".into(),
+        };
+        eprintln!(
+            "Distance {} -> {}: {} -> {}",
+            from,
+            to,
+            distance(&original),
+            distance(&damaged)
+        );
+    }
+
+    write_output(output.as_deref(), |w| {
+        write_edge_list_scaled(&damaged, input.scale, w)
+    })
+}
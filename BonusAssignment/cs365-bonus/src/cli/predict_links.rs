@@ -0,0 +1,34 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::link_prediction::{predict_links, LinkIndex};
+
+/// Predict the `--top <k>` most likely missing edges (default 10), scoring each pair of
+/// nodes not already joined by the neighbors they share, and print them best first as
+/// `a b score`. Direction is ignored.
+///
+/// `--index` picks the score: `adamic-adar` (the default) counts each shared neighbor as
+/// `1 / ln(degree)`, so rarer ones count for more, `common-neighbors` just counts them, and
+/// `jaccard` divides them by the neighbors of either node.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let index = args
+        .parsed::<LinkIndex>("--index")?
+        .unwrap_or(LinkIndex::AdamicAdar);
+    let top = args.parsed::<usize>("--top")?.unwrap_or(10);
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let links = predict_links(&graph, index, top);
+    write_output(output.as_deref(), |w| {
+        for (a, b, score) in &links {
+            writeln!(
+                w,
+                "{} {} {:.6}",
+                graph.get_node_name(*a).unwrap_or(""),
+                graph.get_node_name(*b).unwrap_or(""),
+                score
+            )?;
+        }
+        w.flush()
+    })
+}
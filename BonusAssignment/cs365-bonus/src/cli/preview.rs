@@ -0,0 +1,150 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::rng::Rng;
+use cs365_bonus::{Graph, NodeId};
+
+/// Labels in the sketch are cut to this many characters.
+const LABEL_WIDTH: usize = 6;
+
+/// Print a short description of the input before running anything heavy on it: its size,
+/// cost range, the `--top <n>` best connected nodes (default 5), a `--sample <n>` of
+/// random edges (default 5, chosen with `--seed`), and a sketch of the neighborhood of
+/// the best connected node.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let top = args.parsed::<usize>("--top")?.unwrap_or(5);
+    let sample = args.parsed::<usize>("--sample")?.unwrap_or(5);
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+    let mut degree = vec![0usize; graph.node_count()];
+    let mut directed = 0;
+    for (id, src, dest, _) in graph.edges() {
+        degree[src.index()] += 1;
+        degree[dest.index()] += 1;
+        if graph.is_edge_directed(id) == Some(true) {
+            directed += 1;
+        }
+    }
+    let mut ranked: Vec<NodeId> = graph.node_ids().collect();
+    ranked.sort_by_key(|node| std::cmp::Reverse(degree[node.index()]));
+    let mut rng = Rng::new(seed);
+    let mut edges: Vec<_> = graph.edges().collect();
+    let sample = sample.min(edges.len());
+    // A partial Fisher-Yates shuffle leaves a uniform sample at the front.
+    for i in 0..sample {
+        let j = i + rng.below(edges.len() - i);
+        edges.swap(i, j);
+    }
+
+    write_output(output.as_deref(), |w| {
+        writeln!(
+            w,
+            "{} nodes, {} edges ({} directed), {} connected components",
+            graph.node_count(),
+            graph.edge_count(),
+            directed,
+            graph.connected_components().len()
+        )?;
+        let costs = || graph.edges().map(|(_, _, _, cost)| cost);
+        if let (Some(min), Some(max)) = (costs().min(), costs().max()) {
+            let total: f64 = costs().map(|cost| input.scale.to_f64(cost)).sum();
+            writeln!(
+                w,
+                "costs from {} to {}, {:.2} on average",
+                input.scale.format(min),
+                input.scale.format(max),
+                total / graph.edge_count() as f64
+            )?;
+        }
+        if top > 0 && !ranked.is_empty() {
+            writeln!(w, "\nbest connected:")?;
+            for &node in ranked.iter().take(top) {
+                writeln!(w, "  {} ({} edges)", name(node), degree[node.index()])?;
+            }
+        }
+        if sample > 0 {
+            writeln!(w, "\nsample of edges:")?;
+            for &(id, src, dest, cost) in &edges[..sample] {
+                let arrow = match graph.is_edge_directed(id) {
+                    Some(true) => "->",
+                    _ => "--",
+                };
+                let cost = input.scale.format(cost);
+                writeln!(w, "  {} {} {} ({})", name(src), arrow, name(dest), cost)?;
+            }
+        }
+        if let Some(&center) = ranked.first().filter(|node| degree[node.index()] > 0) {
+            writeln!(w, "\naround {}:", name(center))?;
+            for line in sketch(&graph, center) {
+                writeln!(w, "  {}", line.trim_end())?;
+            }
+        }
+        w.flush()
+    })
+}
+
+/// Draw `center` with up to eight of its neighbors around it, noting how many others
+/// there are and how many edges join the neighbors to each other.
+fn sketch(graph: &Graph, center: NodeId) -> Vec<String> {
+    let mut neighbors: Vec<NodeId> = graph
+        .edges()
+        .filter_map(|(_, src, dest, _)| match (src == center, dest == center) {
+            (true, false) => Some(dest),
+            (false, true) => Some(src),
+            _ => None,
+        })
+        .collect();
+    neighbors.sort();
+    neighbors.dedup();
+    let linked = graph
+        .edges()
+        .filter(|(_, src, dest, _)| {
+            src != dest
+                && neighbors.binary_search(src).is_ok()
+                && neighbors.binary_search(dest).is_ok()
+        })
+        .count();
+
+    let label = |node: Option<&NodeId>| {
+        let name = node
+            .and_then(|&node| graph.get_node_name(node))
+            .unwrap_or("");
+        let name: String = name.chars().take(LABEL_WIDTH).collect();
+        format!("{:^width$}", name, width = LABEL_WIDTH)
+    };
+    // Compass points, filled in this order so a few neighbors still spread out.
+    let [n, s, w, e, nw, se, ne, sw] = [0, 1, 2, 3, 4, 5, 6, 7];
+    let at = |i: usize| label(neighbors.get(i));
+    let link = |i: usize, symbol| if i < neighbors.len() { symbol } else { ' ' };
+    // Spokes sit between the label columns, which start every LABEL_WIDTH + 2 characters.
+    let spokes = |left: (usize, char), middle: (usize, char), right: (usize, char)| {
+        let mut line = vec![' '; 3 * LABEL_WIDTH + 4];
+        line[LABEL_WIDTH + 1] = link(left.0, left.1);
+        line[LABEL_WIDTH + 2 + LABEL_WIDTH / 2] = link(middle.0, middle.1);
+        line[2 * LABEL_WIDTH + 2] = link(right.0, right.1);
+        line.into_iter().collect::<String>()
+    };
+    let dash = |i: usize| if i < neighbors.len() { "--" } else { "  " };
+    let mut lines = vec![
+        format!("{}  {}  {}", at(nw), at(n), at(ne)),
+        spokes((nw, '\\'), (n, '|'), (ne, '/')),
+        format!(
+            "{}{}{}{}{}",
+            at(w),
+            dash(w),
+            label(Some(&center)),
+            dash(e),
+            at(e)
+        ),
+        spokes((sw, '/'), (s, '|'), (se, '\\')),
+        format!("{}  {}  {}", at(sw), at(s), at(se)),
+    ];
+    if neighbors.len() > 8 {
+        lines.push(format!("and {} more neighbors", neighbors.len() - 8));
+    }
+    lines.push(format!("{} edges between these neighbors", linked));
+    lines
+}
@@ -0,0 +1,87 @@
+use super::{find_node, format_route, write_output, Args, Input};
+use cs365_bonus::time_dependent::{TimeDependentGraph, TravelTime};
+use cs365_bonus::{Graph, Weight};
+
+/// Time-dependent routing from `--from` to `--to` (default `a` to `z`), with edge travel
+/// times read from the `--travel-times` file.
+///
+/// `--depart T` prints the fastest route when leaving at `T`. `--window T0:T1` instead
+/// prints the travel time as a function of departure time, as `departure travel` lines.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let travel_times = args.value("--travel-times")?;
+    let from = args.value("--from")?.unwrap_or_else(|| "a".into());
+    let to = args.value("--to")?.unwrap_or_else(|| "z".into());
+    let depart = args.parsed::<Weight>("--depart")?;
+    let window = args.value("--window")?;
+    let step = args.parsed::<Weight>("--step")?.unwrap_or(1);
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let profiles = match &travel_times {
+        Some(path) => read_travel_times(&graph, path)?,
+        None => Vec::new(),
+    };
+    let network = TimeDependentGraph::new(&graph, profiles);
+    let (start, end) = (find_node(&graph, &from)?, find_node(&graph, &to)?);
+
+    match (depart, window) {
+        (Some(departure), None) => {
+            match network.earliest_arrival(start, end, departure) {
+                Some(path) => {
+                    println!(
+                        "Located a minimum travel time of: {}",
+                        input.scale.format(path.cost)
+                    );
+                    println!("{}", format_route(&graph, &path, input.scale));
+                }
+                None => eprintln!("There is no path from '{}' to '{}'.", from, to),
+            }
+            Ok(())
+        }
+        (None, Some(window)) => {
+            let (t0, t1) = window
+                .split_once(':')
+                .and_then(|(t0, t1)| Some((t0.parse().ok()?, t1.parse().ok()?)))
+                .filter(|(t0, t1): &(Weight, Weight)| t0 <= t1)
+                .ok_or_else(|| format!("'--window' expects 'start:end', got '{}'", window))?;
+            let profile = network.profile(start, end, t0, t1, step);
+            write_output(output.as_deref(), |w| {
+                for (departure, travel) in profile {
+                    match travel {
+                        Some(travel) => writeln!(w, "{} {}", departure, travel)?,
+                        None => writeln!(w, "{} -", departure)?,
+                    }
+                }
+                w.flush()
+            })
+        }
+        _ => Err(
+            "'profile' requires exactly one of '--depart <time>' and '--window <start:end>'".into(),
+        ),
+    }
+}
+
+/// Read `u v departure:travel...` lines, giving the travel time of the edges from `u` to `v`.
+fn read_travel_times(graph: &Graph, path: &str) -> Result<Vec<Option<TravelTime>>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let mut profiles = vec![None; graph.edge_count()];
+    for (i, line) in data.lines().enumerate() {
+        let mut words = line.splitn(3, ' ');
+        let (u, v, points) = match (words.next(), words.next(), words.next()) {
+            (Some(""), None, None) => continue,
+            (Some(u), Some(v), Some(points)) => (u, v, points),
+            _ => return Err(format!("invalid entry on line {} of '{}'", i + 1, path)),
+        };
+        let profile: TravelTime = points
+            .parse()
+            .map_err(|e| format!("line {} of '{}': {}", i + 1, path, e))?;
+        let (u, v) = (find_node(graph, u)?, find_node(graph, v)?);
+        for edge in graph.edges_of(u).iter().filter(|edge| edge.node == v) {
+            profiles[edge.id.index()] = Some(profile.clone());
+        }
+    }
+    Ok(profiles)
+}
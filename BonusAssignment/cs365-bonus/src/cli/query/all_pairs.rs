@@ -0,0 +1,19 @@
+use super::super::all_pairs::write_matrix;
+use super::{cycle_names, write_output, Format, Query};
+
+/// Answer `--all-pairs`: the distance matrix between every pair of nodes, as CSV.
+pub(super) fn run(query: &Query) -> Result<(), String> {
+    let graph = &query.graph;
+    if query.options.format == Format::Json {
+        return Err("'--all-pairs' only prints CSV".into());
+    }
+    let matrix = cs365_bonus::all_pairs::all_pairs(graph)
+        .map_err(|cycle| format!("negative cycle: {}", cycle_names(graph, &cycle)))?;
+    let rows = graph
+        .node_ids()
+        .map(|node| Some(matrix.row(node).to_vec()))
+        .collect();
+    write_output(None, |w| {
+        write_matrix(w, graph, &rows, query.input.scale, None)
+    })
+}
@@ -0,0 +1,46 @@
+use super::{find_node, format_route, read_node_list, route_json, write_output};
+use super::{Format, Options, Query};
+use cs365_bonus::find_shortest_paths_from_sources;
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::server::http::json_string;
+
+/// Answer `--batch <path>`: the shortest path to `--to` from every source listed in the
+/// file at `path`, searched on `--threads` threads.
+pub(super) fn run(query: &Query, path: &str) -> Result<(), String> {
+    let (graph, scale) = (&query.graph, query.input.scale);
+    let Options { to, format, .. } = &query.options;
+    if has_negative_edges(graph) {
+        return Err("'--batch' does not support negative costs".into());
+    }
+    let sources = read_node_list(graph, path)?;
+    let target = find_node(graph, to)?;
+    let threads = query.options.threads();
+    let paths = find_shortest_paths_from_sources(graph, &sources, target, threads);
+    write_output(None, |w| {
+        for (&source, path) in sources.iter().zip(&paths) {
+            let name = graph.get_node_name(source).unwrap_or("");
+            match (path, format) {
+                (Some(path), Format::Json) => writeln!(
+                    w,
+                    "{{\"source\":{},\"status\":\"found\",{}",
+                    json_string(name),
+                    &route_json(graph, path, scale)[1..]
+                )?,
+                (None, Format::Json) => writeln!(
+                    w,
+                    "{{\"source\":{},\"status\":\"unreachable\"}}",
+                    json_string(name)
+                )?,
+                (Some(path), Format::Text) => writeln!(
+                    w,
+                    "{}: {}: {}",
+                    name,
+                    scale.format(path.cost),
+                    format_route(graph, path, scale)
+                )?,
+                (None, Format::Text) => writeln!(w, "{}: none", name)?,
+            }
+        }
+        w.flush()
+    })
+}
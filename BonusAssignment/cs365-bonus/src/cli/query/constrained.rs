@@ -0,0 +1,244 @@
+use super::{edge_ends, find_node, Args, Format, Options, Query};
+use cs365_bonus::constrained::shortest_path_through_edge;
+use cs365_bonus::multimodal::{multimodal_path, Modes};
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::resource::{edge_resources, range_constrained_path, resource_constrained_path};
+use cs365_bonus::turns::{turn_restricted_path, TurnRestrictions};
+use cs365_bonus::{Graph, NodeId, Path, Weight};
+
+/// A restriction on which paths a query may return.
+pub(super) enum Constraint {
+    MustUseEdge(String, String),
+    ResourceBudget(Weight),
+    Range(Weight),
+    Modes(String),
+    TurnRestrictions(String),
+}
+
+impl Constraint {
+    /// Remove `--must-use-edge <u> <v>`, `--resource-budget <budget>`, `--range <range>`,
+    /// `--modes <file>`, and `--turn-restrictions <file>` from `args`.
+    pub(super) fn from_args(args: &mut Args) -> Result<Vec<Self>, String> {
+        let mut constraints = Vec::new();
+        let mut must_use = args.pairs("--must-use-edge")?;
+        if must_use.len() > 1 {
+            return Err("'--must-use-edge' may only be given once".into());
+        }
+        if let Some((u, v)) = must_use.pop() {
+            constraints.push(Constraint::MustUseEdge(u, v));
+        }
+        if let Some(budget) = args.parsed::<Weight>("--resource-budget")? {
+            constraints.push(Constraint::ResourceBudget(budget));
+        }
+        if let Some(range) = args.parsed::<Weight>("--range")? {
+            constraints.push(Constraint::Range(range));
+        }
+        if let Some(modes) = args.value("--modes")? {
+            constraints.push(Constraint::Modes(modes));
+        }
+        if let Some(turns) = args.value("--turn-restrictions")? {
+            constraints.push(Constraint::TurnRestrictions(turns));
+        }
+        Ok(constraints)
+    }
+
+    /// The option giving this constraint.
+    pub(super) fn option(&self) -> &'static str {
+        match self {
+            Constraint::MustUseEdge(..) => "--must-use-edge",
+            Constraint::ResourceBudget(_) => "--resource-budget",
+            Constraint::Range(_) => "--range",
+            Constraint::Modes(_) => "--modes",
+            Constraint::TurnRestrictions(_) => "--turn-restrictions",
+        }
+    }
+
+    /// The options that only this constraint uses.
+    pub(super) fn accepts(&self) -> &'static [&'static str] {
+        match self {
+            Constraint::ResourceBudget(_) => &["--resources"],
+            Constraint::Range(_) => &["--resources", "--stations"],
+            Constraint::Modes(_) => &["--allowed-modes", "--transfer-penalty"],
+            Constraint::MustUseEdge(..) | Constraint::TurnRestrictions(_) => &[],
+        }
+    }
+}
+
+/// The shortest path from `start` to `end` subject to `constraint`.
+pub(super) fn path(
+    query: &Query,
+    constraint: &Constraint,
+    start: NodeId,
+    end: NodeId,
+) -> Result<Option<Path>, String> {
+    let graph = &query.graph;
+    let Options {
+        resources,
+        stations,
+        allowed_modes,
+        penalty,
+        format,
+        ..
+    } = &query.options;
+    if has_negative_edges(graph) {
+        return Err("constrained queries do not support negative costs".into());
+    }
+    Ok(match constraint {
+        Constraint::MustUseEdge(u, v) => {
+            let (src, dest) = edge_ends(graph, u, v)?;
+            let edge = graph
+                .find_edge(src, dest)
+                .ok_or_else(|| format!("there is no edge from '{}' to '{}'", u, v))?;
+            shortest_path_through_edge(graph, start, end, edge)
+        }
+        &Constraint::ResourceBudget(budget) => {
+            let resources = resources
+                .as_ref()
+                .ok_or("'--resource-budget' requires '--resources <file>'")?;
+            let resource = read_resources(graph, resources)?;
+            resource_constrained_path(graph, &resource, start, end, budget).map(|found| {
+                format.note(&format!("Resource used: {} of {}", found.resource, budget));
+                found.path
+            })
+        }
+        &Constraint::Range(range) => {
+            let stations = stations
+                .as_ref()
+                .ok_or("'--range' requires '--stations <file>'")?;
+            let stations = read_stations(graph, stations)?;
+            // Without a resource file, a vehicle uses up one unit of range per unit of cost.
+            let consumption = match resources {
+                Some(resources) => read_resources(graph, resources)?,
+                None => graph.edges().map(|(_, _, _, cost)| cost).collect(),
+            };
+            range_constrained_path(graph, &consumption, &stations, start, end, range).map(|found| {
+                let charged: Vec<&str> = found.path.nodes[1..]
+                    .iter()
+                    .filter(|node| stations[node.index()])
+                    .map(|&node| graph.get_node_name(node).unwrap_or(""))
+                    .collect();
+                format.note(&format!("Recharged at: {}", charged.join(", ")));
+                found.path
+            })
+        }
+        Constraint::Modes(path) => {
+            let allowed = allowed_modes.as_deref();
+            let penalty = penalty.unwrap_or(0);
+            modal_query(graph, path, allowed, penalty, start, end, *format)?
+        }
+        Constraint::TurnRestrictions(path) => {
+            let restrictions = read_turn_restrictions(graph, path)?;
+            turn_restricted_path(graph, &restrictions, start, end)
+        }
+    })
+}
+
+/// Route with the `u v mode` tags in the file at `path`, restricted to the comma
+/// separated `allowed` modes if given.
+fn modal_query(
+    graph: &Graph,
+    path: &str,
+    allowed: Option<&str>,
+    penalty: Weight,
+    start: NodeId,
+    end: NodeId,
+    format: Format,
+) -> Result<Option<Path>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let mut modes = Modes::new(graph);
+    for (i, line) in data.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            [u, v, mode] => {
+                let (u, v) = (find_node(graph, u)?, find_node(graph, v)?);
+                let mode = modes.mode(mode);
+                for edge in graph.edges_of(u).iter().filter(|edge| edge.node == v) {
+                    modes.tag(edge.id, mode);
+                }
+            }
+            _ => return Err(format!("invalid entry on line {} of '{}'", i + 1, path)),
+        }
+    }
+
+    let allowed = match allowed {
+        Some(list) => {
+            let mut allowed = vec![false; modes.len()];
+            for name in list.split(',') {
+                let mode = modes
+                    .find(name)
+                    .ok_or_else(|| format!("unknown mode '{}'", name))?;
+                allowed[mode] = true;
+            }
+            allowed
+        }
+        None => vec![true; modes.len()],
+    };
+
+    Ok(
+        multimodal_path(graph, &modes, start, end, &allowed, penalty).map(|found| {
+            let legs: Vec<&str> = found
+                .legs
+                .iter()
+                .map(|leg| leg.map_or("-", |mode| modes.name(mode)))
+                .collect();
+            format.note(&format!("Modes: {}", legs.join(", ")));
+            format.note(&format!("Transfers: {}", found.transfers));
+            found.path
+        }),
+    )
+}
+
+/// Read `via from to` lines, each forbidding the turn from `from` through `via` to `to`.
+fn read_turn_restrictions(graph: &Graph, path: &str) -> Result<TurnRestrictions, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let mut restrictions = TurnRestrictions::new();
+    for (i, line) in data.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            [via, from, to] => restrictions.forbid(
+                find_node(graph, from)?,
+                find_node(graph, via)?,
+                find_node(graph, to)?,
+            ),
+            _ => return Err(format!("invalid entry on line {} of '{}'", i + 1, path)),
+        }
+    }
+    Ok(restrictions)
+}
+
+/// Read a file listing one charging station name per line.
+fn read_stations(graph: &Graph, path: &str) -> Result<Vec<bool>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let mut stations = vec![false; graph.node_count()];
+    for name in data.split_whitespace() {
+        stations[find_node(graph, name)?.index()] = true;
+    }
+    Ok(stations)
+}
+
+/// Read `u v amount` lines giving the resource consumed by the edges between two nodes.
+fn read_resources(graph: &Graph, path: &str) -> Result<Vec<Weight>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let mut entries = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            [u, v, amount] => entries.push((
+                find_node(graph, u)?,
+                find_node(graph, v)?,
+                amount
+                    .parse()
+                    .map_err(|_| format!("invalid amount on line {} of '{}'", i + 1, path))?,
+            )),
+            _ => return Err(format!("invalid entry on line {} of '{}'", i + 1, path)),
+        }
+    }
+    Ok(edge_resources(graph, &entries))
+}
@@ -0,0 +1,280 @@
+use super::constrained::Constraint;
+use super::Input;
+use cs365_bonus::all_pairs::prefers_floyd_warshall;
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::reorder::Reordering;
+use cs365_bonus::{Algorithm, Graph};
+
+/// The kind of search a query runs, as described by `--explain`.
+pub(super) enum Search<'a> {
+    /// The cheapest path between two sets of nodes.
+    Sets,
+    /// Weighted A*, or ARA* or an anytime search, starting from `epsilon` and lowering it
+    /// by `step` each round.
+    Weighted {
+        epsilon: f64,
+        step: f64,
+        landmarks: usize,
+        ara: bool,
+        anytime: bool,
+    },
+    /// One path to the target from every source of a batch file.
+    Batch {
+        threads: usize,
+    },
+    AllPairs,
+    /// The `k` shortest loopless paths.
+    Ranked(usize),
+    /// A single path, also used for each line read by `--interactive`.
+    Single(Option<&'a Constraint>),
+}
+
+/// The lines `--explain` prints: how the query will be answered, and why.
+pub(super) fn plan(
+    graph: &Graph,
+    input: &Input,
+    search: &Search,
+    algorithm: Algorithm,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let negative = has_negative_edges(graph);
+    let directed = graph
+        .edges()
+        .filter(|&(id, ..)| graph.is_edge_directed(id) == Some(true))
+        .count();
+    let costs = || graph.edges().map(|(_, _, _, cost)| cost);
+    let range = match (costs().min(), costs().max()) {
+        (Some(min), Some(max)) => format!(
+            "costs from {} to {}",
+            input.scale.format(min),
+            input.scale.format(max)
+        ),
+        _ => "no costs".to_string(),
+    };
+    lines.push(format!(
+        "Graph: {} nodes, {} edges ({} directed), {}",
+        graph.node_count(),
+        graph.edge_count(),
+        directed,
+        range
+    ));
+
+    const HEAP: &str = "binary heap with lazy deletion";
+    let (chosen, reason, queue) = match search {
+        Search::Sets => (
+            "Dijkstra from every node of the first set at once, stopping at the second",
+            "--from-set and --to-set".into(),
+            HEAP,
+        ),
+        Search::Weighted { anytime: true, .. } => (
+            "anytime weighted A*, printing each cheaper path as it is found",
+            "--anytime".into(),
+            "binary heap ordered by cost plus inflated heuristic",
+        ),
+        Search::Weighted { ara: true, .. } => (
+            "Anytime Repairing A*, lowering epsilon each round",
+            "--epsilon and --ara".into(),
+            "binary heap ordered by cost plus inflated heuristic",
+        ),
+        Search::Weighted { .. } => (
+            "weighted A*",
+            "--epsilon".into(),
+            "binary heap ordered by cost plus inflated heuristic",
+        ),
+        Search::Batch { threads } => (
+            "Dijkstra from each source of the batch",
+            format!("--batch, on {} threads", threads),
+            HEAP,
+        ),
+        Search::AllPairs => {
+            if prefers_floyd_warshall(graph) && negative {
+                ("Floyd-Warshall", "some costs are negative".into(), "none")
+            } else if prefers_floyd_warshall(graph) {
+                (
+                    "Floyd-Warshall",
+                    "m log n >= n^2, so the graph is dense".into(),
+                    "none",
+                )
+            } else {
+                (
+                    "Dijkstra from every node",
+                    "the graph is sparse and no cost is negative".into(),
+                    HEAP,
+                )
+            }
+        }
+        Search::Ranked(k) => (
+            "Yen's k shortest loopless paths",
+            format!("--k {}", k),
+            HEAP,
+        ),
+        Search::Single(Some(Constraint::MustUseEdge(u, v))) => (
+            "Dijkstra from the start and back from the end, joined across the edge",
+            format!("--must-use-edge {} {}", u, v),
+            HEAP,
+        ),
+        Search::Single(Some(Constraint::ResourceBudget(budget))) => (
+            "label-setting search, pruning dominated labels",
+            format!("--resource-budget {}", budget),
+            "binary heap of labels",
+        ),
+        Search::Single(Some(Constraint::Range(range))) => (
+            "label-setting search, recharging at stations",
+            format!("--range {}", range),
+            "binary heap of labels",
+        ),
+        Search::Single(Some(Constraint::Modes(_))) => (
+            "Dijkstra over nodes and the mode they were reached by",
+            "--modes".into(),
+            HEAP,
+        ),
+        Search::Single(Some(Constraint::TurnRestrictions(_))) => (
+            "Dijkstra over edges instead of nodes",
+            "--turn-restrictions".into(),
+            HEAP,
+        ),
+        Search::Single(None) => match algorithm {
+            Algorithm::BellmanFord => (
+                "Bellman-Ford",
+                "--algorithm bellman-ford".into(),
+                "none, every edge is relaxed each round",
+            ),
+            Algorithm::Auto if negative => (
+                "Bellman-Ford",
+                "auto, and some costs are negative".into(),
+                "none, every edge is relaxed each round",
+            ),
+            Algorithm::Auto => ("Dijkstra", "auto, and no cost is negative".into(), HEAP),
+            Algorithm::Dijkstra if negative => (
+                "Dijkstra",
+                "--algorithm dijkstra, although some costs are negative, so distances may \
+                 be wrong"
+                    .into(),
+                HEAP,
+            ),
+            Algorithm::Dijkstra => ("Dijkstra", "--algorithm dijkstra".into(), HEAP),
+        },
+    };
+    lines.push(format!("Algorithm: {} ({})", chosen, reason));
+    lines.push(format!("Queue: {}", queue));
+    let landmarks = match *search {
+        Search::Weighted {
+            epsilon,
+            step,
+            landmarks,
+            ara,
+            anytime,
+        } => {
+            // Landmarks are distinct nodes, so small graphs get fewer.
+            let landmarks = landmarks.min(graph.node_count());
+            let rounds = if ara || anytime {
+                format!(", lowered by {} each round until proven optimal", step)
+            } else {
+                String::new()
+            };
+            lines.push(format!(
+                "Heuristic: landmark lower bounds (ALT) from {} landmarks, inflated by \
+                 epsilon {}{}",
+                landmarks, epsilon, rounds
+            ));
+            Some(format!(
+                "{} landmarks picked, with shortest path trees to and from each (--landmarks)",
+                landmarks
+            ))
+        }
+        _ => {
+            lines.push(
+                "Heuristic: none (edge lists carry no coordinates to estimate distances from)"
+                    .into(),
+            );
+            None
+        }
+    };
+
+    let mut preprocessing = Vec::new();
+    if input.directed {
+        preprocessing.push("undirected edges split into directed pairs (--directed)");
+    }
+    match input.reorder {
+        Some(Reordering::Bfs) => preprocessing.push("nodes renumbered breadth-first (--reorder)"),
+        Some(Reordering::Rcm) => {
+            preprocessing.push("nodes renumbered by reverse Cuthill-McKee (--reorder)")
+        }
+        None => {}
+    }
+    if let Some(landmarks) = &landmarks {
+        preprocessing.push(landmarks);
+    }
+    if preprocessing.is_empty() {
+        preprocessing.push("none");
+    }
+    lines.push(format!("Preprocessing: {}", preprocessing.join(", ")));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Args;
+
+    #[test]
+    fn plans_describe_every_kind_of_query() {
+        let graph: Graph = "a b 1\nb c 2\nc a -1 ->".parse().unwrap();
+        let input = Input::from_args(&mut Args::new(&[])).unwrap();
+        let lines = plan(&graph, &input, &Search::Single(None), Algorithm::Auto);
+        assert_eq!(
+            lines,
+            [
+                "Graph: 3 nodes, 3 edges (1 directed), costs from -1 to 2",
+                "Algorithm: Bellman-Ford (auto, and some costs are negative)",
+                "Queue: none, every edge is relaxed each round",
+                "Heuristic: none (edge lists carry no coordinates to estimate distances from)",
+                "Preprocessing: none",
+            ]
+        );
+
+        let weighted = |ara, anytime| Search::Weighted {
+            epsilon: 1.5,
+            step: 0.25,
+            landmarks: 4,
+            ara,
+            anytime,
+        };
+        let algorithm =
+            |search: Search| plan(&graph, &input, &search, Algorithm::Dijkstra)[1].clone();
+        let searches = [
+            (
+                Search::Sets,
+                "Dijkstra from every node of the first set at once",
+            ),
+            (weighted(false, false), "weighted A* (--epsilon)"),
+            (weighted(true, false), "Anytime Repairing A*"),
+            (weighted(false, true), "anytime weighted A*"),
+            (Search::Batch { threads: 2 }, "(--batch, on 2 threads)"),
+            (Search::AllPairs, "Floyd-Warshall"),
+            (Search::Ranked(3), "Yen's k shortest loopless paths (--k 3)"),
+            (
+                Search::Single(None),
+                "Dijkstra (--algorithm dijkstra, although",
+            ),
+        ];
+        for (search, expected) in searches {
+            let line = algorithm(search);
+            assert!(line.contains(expected), "{:?} lacks {:?}", line, expected);
+        }
+
+        // Weighted searches name their heuristic, capping the landmarks at the node count.
+        let lines = plan(&graph, &input, &weighted(false, false), Algorithm::Auto);
+        assert_eq!(
+            lines[3],
+            "Heuristic: landmark lower bounds (ALT) from 3 landmarks, inflated by epsilon 1.5"
+        );
+        assert_eq!(
+            lines[4],
+            "Preprocessing: 3 landmarks picked, with shortest path trees to and from each \
+             (--landmarks)"
+        );
+        let lines = plan(&graph, &input, &weighted(true, false), Algorithm::Auto);
+        assert!(lines[3].ends_with("epsilon 1.5, lowered by 0.25 each round until proven optimal"));
+    }
+}
@@ -0,0 +1,179 @@
+use super::{route_json, write_output, Format, Query};
+use cs365_bonus::attributes::Attributes;
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::geo::{
+    feature, feature_collection, hull_geometry, line_string, Coordinates, NodeIndex, Point,
+};
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::{shortest_path_tree, Graph, NodeId, Path, Weight};
+
+/// The GeoJSON file `--emit-geojson` writes once a single path query is answered.
+pub(super) struct GeoJson {
+    file: String,
+    coordinates: Coordinates,
+    /// The costs given by `--isochrone`.
+    isochrones: Vec<Weight>,
+}
+
+impl GeoJson {
+    /// Check `--emit-geojson <file>` and `--isochrone <cost>[,<cost>...]` against the graph
+    /// of `query`, if given.
+    pub(super) fn from_query(query: &Query) -> Result<Option<Self>, String> {
+        let (graph, options) = (&query.graph, &query.options);
+        let file = match (&options.emit_geojson, &options.isochrones) {
+            (Some(file), _) => file.clone(),
+            (None, Some(_)) => return Err("'--isochrone' requires '--emit-geojson <file>'".into()),
+            (None, None) => return Ok(None),
+        };
+        let coordinates = node_coordinates(graph, query.attributes.as_ref(), "--emit-geojson")?;
+        let isochrones = match &options.isochrones {
+            Some(costs) => costs
+                .split(',')
+                .map(|cost| {
+                    query
+                        .input
+                        .scale
+                        .parse(cost.trim())
+                        .filter(|&cost| cost >= 0)
+                        .ok_or_else(|| format!("invalid cost '{}' for '--isochrone'", cost))
+                })
+                .collect::<Result<Vec<Weight>, String>>()?,
+            None => Vec::new(),
+        };
+        if !isochrones.is_empty() && has_negative_edges(graph) {
+            return Err("'--isochrone' does not support negative costs".into());
+        }
+        Ok(Some(Self {
+            file,
+            coordinates,
+            isochrones,
+        }))
+    }
+
+    /// Write `path` and the area within each isochrone cost of `start`.
+    pub(super) fn write(
+        &self,
+        query: &Query,
+        path: Option<&Path>,
+        start: NodeId,
+    ) -> Result<(), String> {
+        let geojson = geojson(
+            &query.graph,
+            &self.coordinates,
+            path,
+            start,
+            &self.isochrones,
+            query.input.scale,
+            query.options.format,
+        );
+        write_output(Some(&self.file), |w| {
+            writeln!(w, "{}", geojson)?;
+            w.flush()
+        })
+    }
+}
+
+/// Replace `name`, given for `option`, with the name of the node nearest to it if it isn't
+/// a node but reads as `<lat>,<lon>`, building `index` the first time one is needed.
+pub(super) fn snap(
+    graph: &Graph,
+    attributes: Option<&Attributes>,
+    index: &mut Option<NodeIndex>,
+    option: &str,
+    name: &mut String,
+    format: Format,
+) -> Result<(), String> {
+    let point = match Point::parse_lat_lon(name) {
+        Some(point) if graph.get_node(name).is_none() => point,
+        _ => return Ok(()),
+    };
+    if index.is_none() {
+        let coordinates = node_coordinates(graph, attributes, &format!("{} {}", option, name))?;
+        *index = Some(NodeIndex::new(&coordinates));
+    }
+    let (node, meters) = index
+        .as_ref()
+        .and_then(|index| index.nearest(point))
+        .ok_or("no node has coordinates")?;
+    let nearest = graph.get_node_name(node).unwrap_or_default().to_string();
+    format.note(&format!(
+        "Snapped {} to '{}', {:.0} m away",
+        name, nearest, meters
+    ));
+    *name = nearest;
+    Ok(())
+}
+
+/// The positions of the nodes of `graph` from the `lat` and `lon` attributes, which
+/// `option` needs.
+pub(super) fn node_coordinates(
+    graph: &Graph,
+    attributes: Option<&Attributes>,
+    option: &str,
+) -> Result<Coordinates, String> {
+    let attributes =
+        attributes.ok_or_else(|| format!("'{}' requires '--attributes <file>'", option))?;
+    let coordinates = Coordinates::from_attributes(graph, attributes)?;
+    if coordinates.is_empty() {
+        return Err(format!(
+            "'{}' needs nodes with 'lat' and 'lon' attributes",
+            option
+        ));
+    }
+    Ok(coordinates)
+}
+
+/// A GeoJSON `FeatureCollection` of `path`, if there is one, and the area within each of
+/// the `isochrones` costs of `start`, noting how many nodes each area covers.
+fn geojson(
+    graph: &Graph,
+    coordinates: &Coordinates,
+    path: Option<&Path>,
+    start: NodeId,
+    isochrones: &[Weight],
+    scale: FixedPoint,
+    format: Format,
+) -> String {
+    let mut features = Vec::new();
+    if let Some(path) = path {
+        let points: Vec<_> = path
+            .nodes
+            .iter()
+            .filter_map(|&node| coordinates.get(node))
+            .collect();
+        let geometry = if points.len() < 2 {
+            "null".to_string()
+        } else {
+            line_string(&points)
+        };
+        features.push(feature(&geometry, &route_json(graph, path, scale)));
+    }
+    if !isochrones.is_empty() {
+        let tree = shortest_path_tree(graph, start);
+        for &cost in isochrones {
+            let nodes: Vec<NodeId> = graph
+                .node_ids()
+                .filter(|&node| tree.distance(node).is_some_and(|distance| distance <= cost))
+                .collect();
+            let points: Vec<_> = nodes
+                .iter()
+                .filter_map(|&node| coordinates.get(node))
+                .collect();
+            format.note(&format!(
+                "Within {}: {} nodes, {} with coordinates",
+                scale.format(cost),
+                nodes.len(),
+                points.len()
+            ));
+            features.push(feature(
+                &hull_geometry(&points),
+                &format!(
+                    "{{\"isochrone\":{},\"nodes\":{}}}",
+                    scale.format(cost),
+                    nodes.len()
+                ),
+            ));
+        }
+    }
+    feature_collection(&features)
+}
@@ -0,0 +1,50 @@
+use super::{format_route, route_json, write_output, Format, Options, Query};
+use cs365_bonus::find_k_shortest_paths;
+use cs365_bonus::path::has_negative_edges;
+
+/// Answer `--k <k>`: the `k` shortest loopless paths from `--from` to `--to`, cheapest
+/// first, highlighting all of them with `--dot`.
+pub(super) fn run(query: &Query, k: usize) -> Result<(), String> {
+    let (graph, scale) = (&query.graph, query.input.scale);
+    let Options { format, dot, .. } = &query.options;
+    if has_negative_edges(graph) {
+        return Err("'--k' does not support negative costs".into());
+    }
+    let (start, end) = query.ends()?;
+    let routes = find_k_shortest_paths(graph, start, end, k);
+    if let Some(dot) = dot {
+        write_output(Some(dot), |w| {
+            w.write_all(graph.to_dot_with_paths(&routes).as_bytes())?;
+            w.flush()
+        })?;
+    }
+    if *format == Format::Json {
+        let status = if routes.is_empty() {
+            "unreachable"
+        } else {
+            "found"
+        };
+        let routes: Vec<String> = routes
+            .iter()
+            .map(|route| route_json(graph, route, scale))
+            .collect();
+        println!(
+            "{{\"status\":\"{}\",\"routes\":[{}]}}",
+            status,
+            routes.join(",")
+        );
+        return Ok(());
+    }
+    if routes.is_empty() {
+        eprintln!("{}", query.unreachable());
+    }
+    for (i, route) in routes.iter().enumerate() {
+        println!(
+            "Route {} of cost {}: {}",
+            i + 1,
+            scale.format(route.cost),
+            format_route(graph, route, scale)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,487 @@
+mod all_pairs;
+mod batch;
+mod constrained;
+mod explain;
+mod geo;
+mod k_shortest;
+mod sets;
+mod single;
+mod weighted;
+
+use super::interactive::repl;
+use super::{find_node, format_route, route_json, write_output, Args, Format, Input};
+use constrained::Constraint;
+use cs365_bonus::attributes::{Attributes, WeightFactors};
+use cs365_bonus::bellman_ford::NegativeCycle;
+use cs365_bonus::spans::span;
+use cs365_bonus::{Algorithm, Graph, NodeId, Path, Weight};
+use explain::{plan, Search};
+use geo::{snap, GeoJson};
+
+/// What a query computes, picked by at most one of the options that select a mode.
+enum Mode {
+    /// A single path, possibly avoiding or reweighting parts of the graph.
+    Single,
+    /// The cheapest path between the sets of nodes listed in two files.
+    Sets(String, String),
+    /// Weighted A*, starting from `epsilon` if given, as an anytime search if `anytime`.
+    Weighted {
+        epsilon: Option<f64>,
+        anytime: bool,
+    },
+    /// A path to `--to` from every source listed in a file.
+    Batch(String),
+    Interactive,
+    AllPairs,
+    /// The `k` shortest loopless paths.
+    KShortest(usize),
+    /// A single path subject to a constraint.
+    Constrained(Constraint),
+}
+
+impl Mode {
+    /// Remove the options that select a mode from `args`, failing if they select more
+    /// than one.
+    fn from_args(args: &mut Args) -> Result<Self, String> {
+        let mut modes = Vec::new();
+        match (args.value("--from-set")?, args.value("--to-set")?) {
+            (Some(from), Some(to)) => modes.push(Mode::Sets(from, to)),
+            (None, None) => {}
+            _ => return Err("'--from-set' and '--to-set' must be given together".into()),
+        }
+        let epsilon = args.parsed::<f64>("--epsilon")?;
+        let anytime = args.flag("--anytime");
+        if epsilon.is_some() || anytime {
+            modes.push(Mode::Weighted { epsilon, anytime });
+        }
+        if let Some(path) = args.value("--batch")? {
+            modes.push(Mode::Batch(path));
+        }
+        if args.flag("--interactive") {
+            modes.push(Mode::Interactive);
+        }
+        if args.flag("--all-pairs") {
+            modes.push(Mode::AllPairs);
+        }
+        if let Some(k) = args.parsed::<usize>("--k")? {
+            modes.push(Mode::KShortest(k));
+        }
+        modes.extend(
+            Constraint::from_args(args)?
+                .into_iter()
+                .map(Mode::Constrained),
+        );
+
+        match modes.len() {
+            0 => Ok(Mode::Single),
+            1 => Ok(modes.remove(0)),
+            _ => Err(format!(
+                "'{}' and '{}' can't be combined, since a query runs at most one mode",
+                modes[0].option(),
+                modes[1].option()
+            )),
+        }
+    }
+
+    /// The option that selected this mode.
+    fn option(&self) -> &'static str {
+        match self {
+            Mode::Single => "",
+            Mode::Sets(..) => "--from-set",
+            Mode::Weighted { anytime: true, .. } => "--anytime",
+            Mode::Weighted { .. } => "--epsilon",
+            Mode::Batch(_) => "--batch",
+            Mode::Interactive => "--interactive",
+            Mode::AllPairs => "--all-pairs",
+            Mode::KShortest(_) => "--k",
+            Mode::Constrained(constraint) => constraint.option(),
+        }
+    }
+
+    /// The options, out of those listed by [`Options::modifiers`], that this mode uses.
+    fn accepts(&self) -> Vec<&'static str> {
+        const GEOJSON: [&str; 3] = ["--dot", "--emit-geojson", "--isochrone"];
+        match self {
+            Mode::Single => [
+                &GEOJSON[..],
+                &[
+                    "--avoid-node",
+                    "--avoid-edge",
+                    "--avoid-polygon",
+                    "--max-cost",
+                ],
+                &["--weight-factor", "--snapshot", "--snapshot-step"],
+                &["--check-invariants"],
+            ]
+            .concat(),
+            Mode::Weighted { .. } => {
+                vec![
+                    "--dot",
+                    "--ara",
+                    "--ara-step",
+                    "--landmarks",
+                    "--time-budget",
+                ]
+            }
+            Mode::Batch(_) => vec!["--threads"],
+            Mode::KShortest(_) => vec!["--dot"],
+            Mode::Constrained(constraint) => [&GEOJSON[..], constraint.accepts()].concat(),
+            Mode::Sets(..) | Mode::Interactive | Mode::AllPairs => Vec::new(),
+        }
+    }
+
+    /// The search `--explain` describes for this mode.
+    fn search(&self, options: &Options) -> Search<'_> {
+        match self {
+            Mode::Single => Search::Single(None),
+            Mode::Sets(..) => Search::Sets,
+            Mode::Weighted { epsilon, anytime } => Search::Weighted {
+                epsilon: epsilon.unwrap_or(3.0),
+                step: options.ara_step.unwrap_or(0.5),
+                landmarks: options.landmarks.unwrap_or(4),
+                ara: options.ara,
+                anytime: *anytime,
+            },
+            Mode::Batch(_) => Search::Batch {
+                threads: options.threads(),
+            },
+            Mode::Interactive => Search::Single(None),
+            Mode::AllPairs => Search::AllPairs,
+            Mode::KShortest(k) => Search::Ranked(*k),
+            Mode::Constrained(constraint) => Search::Single(Some(constraint)),
+        }
+    }
+}
+
+/// The options of a query other than those selecting its mode.
+struct Options {
+    from: String,
+    to: String,
+    algorithm: Algorithm,
+    format: Format,
+    explain: bool,
+    dot: Option<String>,
+    threads: Option<usize>,
+    avoid_nodes: Vec<String>,
+    avoid_edges: Vec<(String, String)>,
+    avoid_polygons: Vec<String>,
+    max_cost: Option<String>,
+    factors: WeightFactors,
+    snapshot: Option<String>,
+    snapshot_step: Option<usize>,
+    check_invariants: bool,
+    ara: bool,
+    ara_step: Option<f64>,
+    landmarks: Option<usize>,
+    time_budget: Option<f64>,
+    resources: Option<String>,
+    stations: Option<String>,
+    allowed_modes: Option<String>,
+    penalty: Option<Weight>,
+    emit_geojson: Option<String>,
+    isochrones: Option<String>,
+}
+
+impl Options {
+    fn from_args(args: &mut Args) -> Result<Self, String> {
+        Ok(Self {
+            from: args.value("--from")?.unwrap_or_else(|| "a".into()),
+            to: args.value("--to")?.unwrap_or_else(|| "z".into()),
+            algorithm: args.parsed::<Algorithm>("--algorithm")?.unwrap_or_default(),
+            format: args.parsed::<Format>("--format")?.unwrap_or_default(),
+            explain: args.flag("--explain"),
+            dot: args.value("--dot")?,
+            threads: args.parsed::<usize>("--threads")?,
+            avoid_nodes: args.values("--avoid-node")?,
+            avoid_edges: args.pairs("--avoid-edge")?,
+            avoid_polygons: args.values("--avoid-polygon")?,
+            max_cost: args.value("--max-cost")?,
+            factors: match args.value("--weight-factor")? {
+                Some(factors) => factors
+                    .parse::<WeightFactors>()
+                    .map_err(|e| format!("invalid '--weight-factor': {}", e))?,
+                None => WeightFactors::default(),
+            },
+            snapshot: args.value("--snapshot")?,
+            snapshot_step: args.parsed::<usize>("--snapshot-step")?,
+            check_invariants: args.flag("--check-invariants"),
+            ara: args.flag("--ara"),
+            ara_step: args.parsed::<f64>("--ara-step")?,
+            landmarks: args.parsed::<usize>("--landmarks")?,
+            time_budget: args.parsed::<f64>("--time-budget")?,
+            resources: args.value("--resources")?,
+            stations: args.value("--stations")?,
+            allowed_modes: args.value("--allowed-modes")?,
+            penalty: args.parsed::<Weight>("--transfer-penalty")?,
+            emit_geojson: args.value("--emit-geojson")?,
+            isochrones: args.value("--isochrone")?,
+        })
+    }
+
+    /// The options given that only some modes use.
+    fn modifiers(&self) -> Vec<&'static str> {
+        let given = [
+            ("--dot", self.dot.is_some()),
+            ("--threads", self.threads.is_some()),
+            ("--avoid-node", !self.avoid_nodes.is_empty()),
+            ("--avoid-edge", !self.avoid_edges.is_empty()),
+            ("--avoid-polygon", !self.avoid_polygons.is_empty()),
+            ("--max-cost", self.max_cost.is_some()),
+            ("--weight-factor", !self.factors.is_empty()),
+            ("--snapshot", self.snapshot.is_some()),
+            ("--snapshot-step", self.snapshot_step.is_some()),
+            ("--check-invariants", self.check_invariants),
+            ("--ara", self.ara),
+            ("--ara-step", self.ara_step.is_some()),
+            ("--landmarks", self.landmarks.is_some()),
+            ("--time-budget", self.time_budget.is_some()),
+            ("--resources", self.resources.is_some()),
+            ("--stations", self.stations.is_some()),
+            ("--allowed-modes", self.allowed_modes.is_some()),
+            ("--transfer-penalty", self.penalty.is_some()),
+            ("--emit-geojson", self.emit_geojson.is_some()),
+            ("--isochrone", self.isochrones.is_some()),
+        ];
+        given
+            .iter()
+            .filter(|(_, given)| *given)
+            .map(|&(option, _)| option)
+            .collect()
+    }
+
+    /// The number of threads `--batch` runs on, by default all cores.
+    fn threads(&self) -> usize {
+        match self.threads {
+            Some(threads) => threads.max(1),
+            None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+}
+
+/// A loaded graph and the options to query it with.
+struct Query {
+    input: Input,
+    graph: Graph,
+    attributes: Option<Attributes>,
+    options: Options,
+}
+
+impl Query {
+    /// The nodes named by `--from` and `--to`.
+    fn ends(&self) -> Result<(NodeId, NodeId), String> {
+        let Options { from, to, .. } = &self.options;
+        Ok((find_node(&self.graph, from)?, find_node(&self.graph, to)?))
+    }
+
+    /// The note printed when there is no path from `--from` to `--to`.
+    fn unreachable(&self) -> String {
+        let Options { from, to, .. } = &self.options;
+        format!("There is no path from '{}' to '{}'.", from, to)
+    }
+}
+
+/// Find the shortest path from `--from` to `--to` (default `a` to `z`), subject to at
+/// most one constraint. Either end may also be given as `<lat>,<lon>` instead of a node,
+/// to start or end at the node nearest to that point by the `lat` and `lon` attributes in
+/// the `--attributes` file. Unconstrained queries pick Dijkstra or Bellman-Ford with
+/// `--algorithm`, by default Bellman-Ford only if some cost is negative. `--dot <file>`
+/// also writes the graph in the DOT format with the path highlighted.
+///
+/// `--k <n>` prints the `n` shortest loopless routes instead of one, highlighting all of
+/// them with `--dot`, and `--all-pairs` the distance matrix between every pair of nodes as
+/// CSV.
+///
+/// `--format json` prints `{"status": "found", "cost": 3, "route": [{"node": "a",
+/// "cumulative_distance": 0}, ...]}`, `{"status": "found", "routes": [...]}` with `--k`,
+/// or `{"status": "unreachable"}`, sending any other output to stderr.
+///
+/// `--explain` first prints the algorithm, queue, heuristic, and preprocessing the query
+/// will use, and the properties of the graph that decided them.
+///
+/// `--batch <file>` finds the shortest path to `--to` from every source named in the file,
+/// one per line, on `--threads` threads (default all cores), printing `source: cost:
+/// route` or `source: none` for each. With `--format json` each is a JSON object on its
+/// own line, with a `source` member added.
+///
+/// `--must-use-edge <u> <v>` only accepts paths through the edge from `u` to `v`. The
+/// ends are separate arguments, so node names may contain any character but whitespace.
+///
+/// `--avoid-node <node>` and `--avoid-edge <u> <v>`, each repeatable, keep the path off
+/// those nodes and edges, and `--max-cost <cost>` rejects paths costing more.
+/// `--avoid-polygon <file>`, also repeatable, keeps the path out of the areas outlined by
+/// the polygons of a GeoJSON file, avoiding the nodes inside them and the edges passing
+/// through them as straight lines. Node positions come from the `lat` and `lon` node
+/// attributes of the `--attributes` file. These need Dijkstra, so costs must not be
+/// negative.
+///
+/// `--emit-geojson <file>` also writes the path to a file as a GeoJSON `FeatureCollection`,
+/// ready to drop onto a map, with the route as a `LineString` through the nodes that have
+/// `lat` and `lon` attributes. `--isochrone <cost>[,<cost>...]` adds an area for each
+/// cost: the convex hull of the nodes within that cost of `--from`, as a `Polygon`.
+///
+/// `--weight-factor highway=0.8,ferry=3` multiplies the cost of each edge by the factor
+/// for its `label` in the `--attributes` file for this query only.
+///
+/// `--snapshot <file>` writes the search's distances, parents, and queue as JSON once it
+/// ends, after expanding `--snapshot-step <n>` nodes if given, or as it panics.
+/// `--check-invariants` checks the search's queue, distances, and parents after every
+/// step, panicking at the first inconsistency.
+///
+/// `--from-set <file>` and `--to-set <file>` replace `--from` and `--to` with sets of
+/// nodes, one per line, and find the cheapest path from any node of the first set to any
+/// node of the second.
+///
+/// `--epsilon <e>` runs weighted A* instead, inflating a landmark heuristic by `e` to find a
+/// path costing at most `e` times the optimal while expanding fewer nodes, and reports the
+/// suboptimality it actually proved. `--ara` then keeps going as Anytime Repairing A*,
+/// lowering `e` by `--ara-step <d>` (default 0.5) each round until the path is proven
+/// optimal, noting every round. `--landmarks <n>` sets how many landmarks the heuristic
+/// uses (default 4). These need non-negative costs.
+///
+/// `--anytime` runs the same search but prints each cheaper path as soon as it is found,
+/// with how long it took and how close to optimal it is proven to be, so that a long query
+/// has a usable answer early. It starts from `--epsilon` (default 3), and with
+/// `--time-budget <seconds>` it stops at the deadline with the best path so far. With
+/// `--format json` each path is a line `{"status": "improved", "elapsed_ms": 1.5,
+/// "suboptimality": 1.2, "cost": ..., "route": [...]}`, the bound `null` while unknown.
+///
+/// `--interactive` loads the graph once and then answers `from to` lines from stdin,
+/// along with `:nodes`, `:edges <node>`, and `:quit`.
+///
+/// A query runs at most one mode: `--from-set`, `--epsilon` or `--anytime`, `--batch`,
+/// `--interactive`, `--all-pairs`, `--k`, or one of the constraints `--must-use-edge`,
+/// `--resource-budget`, `--range`, `--modes`, and `--turn-restrictions`. Without any it
+/// finds a single path. Options a mode doesn't use, such as `--dot` with `--all-pairs`,
+/// are rejected.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let mode = Mode::from_args(&mut args)?;
+    let mut options = Options::from_args(&mut args)?;
+    let files = args.finish()?;
+    let accepted = mode.accepts();
+    if let Some(option) = options
+        .modifiers()
+        .into_iter()
+        .find(|option| !accepted.contains(option))
+    {
+        return Err(match mode {
+            Mode::Single => format!("'{}' isn't supported by single path queries", option),
+            _ => format!("'{}' can't be combined with '{}'", option, mode.option()),
+        });
+    }
+
+    let graph = input.load(&files)?;
+    let _query = span("query");
+    let attributes = input.load_attributes(&graph)?;
+    let mut index = None;
+    for (option, end) in [("--from", &mut options.from), ("--to", &mut options.to)] {
+        snap(
+            &graph,
+            attributes.as_ref(),
+            &mut index,
+            option,
+            end,
+            options.format,
+        )?;
+    }
+    if options.explain {
+        for line in plan(&graph, &input, &mode.search(&options), options.algorithm) {
+            options.format.note(&line);
+        }
+    }
+    let query = Query {
+        input,
+        graph,
+        attributes,
+        options,
+    };
+
+    match &mode {
+        Mode::Sets(from, to) => sets::run(&query, from, to),
+        &Mode::Weighted { epsilon, anytime } => weighted::run(&query, epsilon, anytime),
+        Mode::Batch(path) => batch::run(&query, path),
+        Mode::Interactive => {
+            let Options {
+                algorithm, format, ..
+            } = query.options;
+            repl(&query.graph, algorithm, query.input.scale, format)
+        }
+        Mode::AllPairs => all_pairs::run(&query),
+        &Mode::KShortest(k) => k_shortest::run(&query, k),
+        Mode::Constrained(constraint) => single_path(&query, Some(constraint)),
+        Mode::Single => single_path(&query, None),
+    }
+}
+
+/// Answer a query for one path from `--from` to `--to`, subject to `constraint` if given,
+/// also writing it to `--emit-geojson`.
+fn single_path(query: &Query, constraint: Option<&Constraint>) -> Result<(), String> {
+    let geojson = GeoJson::from_query(query)?;
+    let (start, end) = query.ends()?;
+    let path = match constraint {
+        Some(constraint) => constrained::path(query, constraint, start, end)?,
+        None => single::path(query, start, end)?,
+    };
+    if let Some(geojson) = geojson {
+        geojson.write(query, path.as_ref(), start)?;
+    }
+    report(query, path, &query.unreachable())
+}
+
+/// Print `path`, or `unreachable` if there is none, and write the graph with the path
+/// highlighted to `--dot` if given.
+fn report(query: &Query, path: Option<Path>, unreachable: &str) -> Result<(), String> {
+    let (graph, scale) = (&query.graph, query.input.scale);
+    match &path {
+        Some(path) if query.options.format == Format::Json => println!(
+            "{{\"status\":\"found\",{}",
+            &route_json(graph, path, scale)[1..]
+        ),
+        None if query.options.format == Format::Json => println!("{{\"status\":\"unreachable\"}}"),
+        Some(path) => {
+            println!(
+                "Located a minimum path of cost: {}",
+                scale.format(path.cost)
+            );
+            println!("{}", format_route(graph, path, scale));
+        }
+        None => {
+            eprintln!("{}", unreachable);
+        }
+    }
+
+    match &query.options.dot {
+        Some(dot) => write_output(Some(dot), |w| {
+            w.write_all(graph.to_dot(path.as_ref()).as_bytes())?;
+            w.flush()
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Look up the nodes at both ends of an edge given by name, as `--must-use-edge <u> <v>`
+/// and `--avoid-edge <u> <v>`.
+fn edge_ends(graph: &Graph, u: &str, v: &str) -> Result<(NodeId, NodeId), String> {
+    Ok((find_node(graph, u)?, find_node(graph, v)?))
+}
+
+/// Read node names from `path`, one per line, skipping blank lines and `#` comments.
+fn read_node_list(graph: &Graph, path: &str) -> Result<Vec<NodeId>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    data.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|name| find_node(graph, name))
+        .collect()
+}
+
+/// The nodes of `cycle` as `a -> b -> ...`.
+pub(super) fn cycle_names(graph: &Graph, cycle: &NegativeCycle) -> String {
+    let names: Vec<&str> = cycle
+        .nodes()
+        .iter()
+        .map(|&node| graph.get_node_name(node).unwrap_or(""))
+        .collect();
+    names.join(" -> ")
+}
@@ -0,0 +1,19 @@
+use super::{read_node_list, report, Query};
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::{Algorithm, SearchContext};
+
+/// Answer `--from-set <from> --to-set <to>`: the cheapest path from any node listed in the
+/// file `from` to any node listed in `to`.
+pub(super) fn run(query: &Query, from: &str, to: &str) -> Result<(), String> {
+    let graph = &query.graph;
+    if has_negative_edges(graph) || query.options.algorithm == Algorithm::BellmanFord {
+        return Err("'--from-set' needs Dijkstra and non-negative costs".into());
+    }
+    let (sources, targets) = (read_node_list(graph, from)?, read_node_list(graph, to)?);
+    let path = SearchContext::new().find_path_between(graph, &sources, &targets);
+    let unreachable = format!(
+        "There is no path from any node in '{}' to any node in '{}'.",
+        from, to
+    );
+    report(query, path, &unreachable)
+}
@@ -0,0 +1,171 @@
+use super::geo::node_coordinates;
+use super::{cycle_names, edge_ends, find_node, write_output, Options, Query};
+use cs365_bonus::attributes::Attributes;
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::geo::{inside_polygons, parse_polygons};
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::view::Reweighted;
+use cs365_bonus::{
+    shortest_path_tree_using, Algorithm, Graph, NodeId, Path, PathConstraints, SearchContext,
+};
+use std::panic::AssertUnwindSafe;
+
+/// The shortest path from `start` to `end` of a query without a mode: found with
+/// `--algorithm`, or with Dijkstra when avoiding parts of the graph, reweighting it by
+/// `--weight-factor`, or snapshotting or checking the search.
+pub(super) fn path(query: &Query, start: NodeId, end: NodeId) -> Result<Option<Path>, String> {
+    let (graph, attributes) = (&query.graph, query.attributes.as_ref());
+    let options = &query.options;
+    let limits = path_constraints(graph, options, attributes, query.input.scale)?;
+    let reweighted = !options.factors.is_empty();
+    if reweighted && attributes.is_none() {
+        return Err("'--weight-factor' requires '--attributes <file>'".into());
+    }
+    if options.snapshot_step.is_some() && options.snapshot.is_none() {
+        return Err("'--snapshot-step' requires '--snapshot <file>'".into());
+    }
+    let bellman_ford = has_negative_edges(graph) || options.algorithm == Algorithm::BellmanFord;
+    let debug = options.snapshot.is_some() || options.check_invariants;
+    if reweighted || !limits.is_empty() {
+        if debug {
+            return Err(
+                "'--snapshot' and '--check-invariants' can't be combined with avoiding or \
+                 reweighting parts of the graph"
+                    .into(),
+            );
+        }
+        if bellman_ford {
+            return Err(
+                "'--avoid-node', '--avoid-edge', '--avoid-polygon', '--max-cost', and \
+                 '--weight-factor' need Dijkstra and non-negative costs"
+                    .into(),
+            );
+        }
+        let none = Attributes::default();
+        let attributes = attributes.unwrap_or(&none);
+        let view = Reweighted::new(graph, |edge| options.factors.apply(attributes, edge));
+        return Ok(SearchContext::new().find_constrained_path(&view, start, end, &limits));
+    }
+    if debug {
+        if options.dot.is_some() {
+            return Err(
+                "'--snapshot' and '--check-invariants' can't be combined with '--dot'".into(),
+            );
+        }
+        if bellman_ford {
+            return Err(
+                "'--snapshot' and '--check-invariants' need Dijkstra and non-negative costs".into(),
+            );
+        }
+        let mut context = SearchContext::new();
+        context.set_invariant_checks(options.check_invariants);
+        let snapshot = options.snapshot.as_deref();
+        return debug_query(
+            &mut context,
+            graph,
+            start,
+            end,
+            options.snapshot_step,
+            snapshot,
+        );
+    }
+    let tree = shortest_path_tree_using(graph, start, options.algorithm).map_err(|cycle| {
+        format!(
+            "negative cycle reachable from '{}': {}",
+            options.from,
+            cycle_names(graph, &cycle)
+        )
+    })?;
+    Ok(tree.path_to(end))
+}
+
+/// Build the constraints given by `--avoid-node`, `--avoid-edge`, `--avoid-polygon`, and
+/// `--max-cost`.
+///
+/// Avoiding the edge from `u` to `v` forbids every such edge, including parallel ones.
+fn path_constraints(
+    graph: &Graph,
+    options: &Options,
+    attributes: Option<&Attributes>,
+    scale: FixedPoint,
+) -> Result<PathConstraints, String> {
+    let mut constraints = PathConstraints::default();
+    for name in &options.avoid_nodes {
+        constraints.forbidden_nodes.insert(find_node(graph, name)?);
+    }
+    for (u, v) in &options.avoid_edges {
+        let (src, dest) = edge_ends(graph, u, v)?;
+        let edges: Vec<_> = graph
+            .edges_of(src)
+            .iter()
+            .filter(|edge| edge.node == dest)
+            .collect();
+        if edges.is_empty() {
+            return Err(format!("there is no edge from '{}' to '{}'", u, v));
+        }
+        constraints
+            .forbidden_edges
+            .extend(edges.into_iter().map(|edge| edge.id));
+    }
+    if !options.avoid_polygons.is_empty() {
+        let coordinates = node_coordinates(graph, attributes, "--avoid-polygon")?;
+        let mut areas = Vec::new();
+        for path in &options.avoid_polygons {
+            let data = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+            areas.extend(
+                parse_polygons(&data)
+                    .map_err(|e| format!("Invalid GeoJSON in '{}': {}", path, e))?,
+            );
+        }
+        let (nodes, edges) = inside_polygons(graph, &coordinates, &areas);
+        constraints.forbidden_nodes.extend(nodes);
+        constraints.forbidden_edges.extend(edges);
+    }
+    if let Some(text) = &options.max_cost {
+        constraints.max_cost = Some(
+            scale
+                .parse(text)
+                .ok_or_else(|| format!("invalid value '{}' for '--max-cost'", text))?,
+        );
+    }
+    Ok(constraints)
+}
+
+/// Search from `start` to `end` with `context`, writing a snapshot of the search to
+/// `snapshot` if given once it ends, has expanded `steps` nodes, or panics. Stopping early
+/// is reported as an error.
+fn debug_query(
+    context: &mut SearchContext,
+    graph: &Graph,
+    start: NodeId,
+    end: NodeId,
+    steps: Option<usize>,
+    snapshot: Option<&str>,
+) -> Result<Option<Path>, String> {
+    let searched = std::panic::catch_unwind(AssertUnwindSafe(|| match steps {
+        Some(steps) => context.find_shortest_path_within(graph, start, end, steps),
+        None => Ok(context.find_shortest_path(graph, start, end)),
+    }));
+    let file = match snapshot {
+        Some(file) => file,
+        None => {
+            return searched
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                .map_err(|stopped| stopped.to_string())
+        }
+    };
+    let json = context.snapshot().to_json(graph);
+    write_output(Some(file), |w| {
+        writeln!(w, "{}", json)?;
+        w.flush()
+    })?;
+    match searched {
+        Ok(Ok(path)) => Ok(path),
+        Ok(Err(stopped)) => Err(format!("{}; its state was written to '{}'", stopped, file)),
+        Err(panic) => {
+            eprintln!("The search panicked; its state was written to '{}'.", file);
+            std::panic::resume_unwind(panic)
+        }
+    }
+}
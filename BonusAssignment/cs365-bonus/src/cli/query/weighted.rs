@@ -0,0 +1,141 @@
+use super::{format_route, report, route_json, write_output, Format, Options, Query};
+use cs365_bonus::astar::{weighted_astar, AnytimeSearch, Landmarks};
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::{Algorithm, Graph, NodeId, Path, Weight};
+use std::time::{Duration, Instant};
+
+/// Answer `--epsilon` and `--anytime`: weighted A* from `--from` to `--to` with a landmark
+/// heuristic inflated by `epsilon`, by default 3, repeated with smaller `epsilon` by
+/// `--ara`, or printing each cheaper path as it is found if `anytime`.
+pub(super) fn run(query: &Query, epsilon: Option<f64>, anytime: bool) -> Result<(), String> {
+    let started = Instant::now();
+    let (graph, scale) = (&query.graph, query.input.scale);
+    let Options {
+        algorithm,
+        format,
+        ara,
+        ara_step,
+        landmarks,
+        time_budget,
+        ..
+    } = query.options;
+    if anytime && ara {
+        return Err("'--anytime' and '--ara' can't be combined".into());
+    }
+    let epsilon = epsilon.unwrap_or(3.0);
+    if !epsilon.is_finite() || epsilon < 1.0 {
+        return Err("'--epsilon' must be at least 1".into());
+    }
+    if has_negative_edges(graph) || algorithm == Algorithm::BellmanFord {
+        return Err("'--epsilon' and '--anytime' need non-negative costs".into());
+    }
+    let deadline = match time_budget {
+        Some(seconds) if seconds.is_finite() && seconds >= 0.0 => {
+            Some(started + Duration::from_secs_f64(seconds))
+        }
+        Some(_) => return Err("'--time-budget' must be a number of seconds".into()),
+        None => None,
+    };
+    if deadline.is_some() && !anytime {
+        return Err("'--time-budget' requires '--anytime'".into());
+    }
+    let step = ara_step.unwrap_or(0.5);
+    if step.is_nan() || step <= 0.0 {
+        return Err("'--ara-step' must be positive".into());
+    }
+    let (start, end) = query.ends()?;
+    let landmarks = Landmarks::select(graph, landmarks.unwrap_or(4));
+    let heuristic = |node| landmarks.lower_bound(node, end);
+    if anytime {
+        let mut search = AnytimeSearch::new(graph, start, end, heuristic, epsilon, step);
+        if let Some(deadline) = deadline {
+            search = search.with_deadline(deadline);
+        }
+        let path = stream_improvements(graph, search, started, scale, format);
+        let out_of_time = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        return match (&path, &query.options.dot) {
+            (None, _) if out_of_time => Err("no path was found within the time budget".into()),
+            (None, _) => report(query, None, &query.unreachable()),
+            (Some(_), Some(dot)) => write_output(Some(dot), |w| {
+                w.write_all(graph.to_dot(path.as_ref()).as_bytes())?;
+                w.flush()
+            }),
+            (Some(_), None) => Ok(()),
+        };
+    }
+    let path = if ara {
+        let mut last = None;
+        for round in AnytimeSearch::new(graph, start, end, heuristic, epsilon, step) {
+            format.note(&format!(
+                "Epsilon {}: cost {}, within {:.3} of optimal, {} nodes expanded",
+                round.epsilon,
+                scale.format(round.path.cost),
+                round.suboptimality,
+                round.expanded
+            ));
+            last = Some(round.path);
+        }
+        last
+    } else {
+        weighted_astar(graph, start, end, heuristic, epsilon).map(|found| {
+            format.note(&format!(
+                "Suboptimality: at most {:.3} (epsilon {}, {} nodes expanded)",
+                found.suboptimality, epsilon, found.expanded
+            ));
+            found.path
+        })
+    };
+    report(query, path, &query.unreachable())
+}
+
+/// Print each path `search` finds that is cheaper than the last as soon as it is found,
+/// with the time since `started`, returning the last.
+fn stream_improvements<H: Fn(NodeId) -> Weight>(
+    graph: &Graph,
+    search: AnytimeSearch<H>,
+    started: Instant,
+    scale: FixedPoint,
+    format: Format,
+) -> Option<Path> {
+    let mut best: Option<Path> = None;
+    let mut proven = f64::INFINITY;
+    for found in search {
+        let elapsed = started.elapsed().as_secs_f64() * 1000.0;
+        proven = found.suboptimality;
+        if best
+            .as_ref()
+            .is_some_and(|best| best.cost <= found.path.cost)
+        {
+            format.note(&format!(
+                "After {:.1} ms: now proven within {:.3} of optimal",
+                elapsed, proven
+            ));
+            continue;
+        }
+        match format {
+            Format::Json => println!(
+                "{{\"status\":\"improved\",\"elapsed_ms\":{:.1},\"suboptimality\":{},{}",
+                elapsed,
+                if proven.is_finite() {
+                    format!("{:.3}", proven)
+                } else {
+                    "null".into()
+                },
+                &route_json(graph, &found.path, scale)[1..]
+            ),
+            Format::Text => println!(
+                "After {:.1} ms: cost {}, within {:.3} of optimal: {}",
+                elapsed,
+                scale.format(found.path.cost),
+                proven,
+                format_route(graph, &found.path, scale)
+            ),
+        }
+        best = Some(found.path);
+    }
+    if best.is_some() && proven > 1.0 {
+        format.note("Stopped at the time budget before proving the last path optimal.");
+    }
+    best
+}
@@ -0,0 +1,22 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::rng::Rng;
+use cs365_bonus::spanning_tree::random_spanning_tree;
+use cs365_bonus::write_edge_list_scaled;
+use std::collections::HashSet;
+
+/// Write a uniformly random spanning tree of the input as an edge list.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let tree: HashSet<_> = random_spanning_tree(&graph, &mut Rng::new(seed))
+        .into_iter()
+        .collect();
+    let tree = graph.edge_subgraph(|id| tree.contains(&id));
+    write_output(output.as_deref(), |w| {
+        write_edge_list_scaled(&tree, input.scale, w)
+    })
+}
@@ -0,0 +1,67 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::write_edge_list_scaled;
+use std::collections::HashMap;
+
+/// Rename nodes, either to sequential ids or through a user-provided `old new` mapping file.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let mapping = args.value("--mapping")?;
+    let mapping_out = args.value("--mapping-out")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let names: Vec<String> = match &mapping {
+        Some(path) => {
+            let mapping = read_mapping(path)?;
+            graph
+                .node_ids()
+                .map(|node| {
+                    let name = graph.get_node_name(node).unwrap_or("");
+                    mapping
+                        .get(name)
+                        .cloned()
+                        .ok_or_else(|| format!("'{}' has no mapping for node '{}'", path, name))
+                })
+                .collect::<Result<_, _>>()?
+        }
+        None => graph.node_ids().map(|node| node.to_string()).collect(),
+    };
+
+    if let Some(path) = &mapping_out {
+        write_output(Some(path), |w| {
+            for (node, name) in graph.node_ids().zip(names.iter()) {
+                writeln!(w, "{} {}", graph.get_node_name(node).unwrap_or(""), name)?;
+            }
+            w.flush()
+        })?;
+    }
+
+    let relabeled = graph
+        .relabeled(names)
+        .ok_or("the mapping assigns the same name to more than one node")?;
+    write_output(output.as_deref(), |w| {
+        write_edge_list_scaled(&relabeled, input.scale, w)
+    })
+}
+
+/// Read `old new` pairs, one per line.
+fn read_mapping(path: &str) -> Result<HashMap<String, String>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let mut mapping = HashMap::new();
+    for (i, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut iter = line.split(' ');
+        match (iter.next(), iter.next(), iter.next()) {
+            (Some(old), Some(new), None) => {
+                mapping.insert(old.to_string(), new.to_string());
+            }
+            _ => return Err(format!("invalid mapping on line {} of '{}'", i + 1, path)),
+        }
+    }
+    Ok(mapping)
+}
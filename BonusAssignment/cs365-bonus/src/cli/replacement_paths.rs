@@ -0,0 +1,47 @@
+use super::{find_node, format_route, Args, Input};
+use cs365_bonus::replacement::replacement_paths;
+
+/// For each edge of the shortest path from `--from` to `--to` (default `a` to `z`), print
+/// the cost of the best detour if that edge fails.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let from = args.value("--from")?.unwrap_or_else(|| "a".into());
+    let to = args.value("--to")?.unwrap_or_else(|| "z".into());
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let (start, end) = (find_node(&graph, &from)?, find_node(&graph, &to)?);
+    let (path, replacements) = match replacement_paths(&graph, start, end) {
+        Some(result) => result,
+        None => {
+            eprintln!("There is no path from '{}' to '{}'.", from, to);
+            return Ok(());
+        }
+    };
+
+    println!(
+        "Located a minimum path of cost: {}",
+        input.scale.format(path.cost)
+    );
+    println!("{}", format_route(&graph, &path, input.scale));
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+    for (pair, replacement) in path.nodes.windows(2).zip(replacements) {
+        let (src, dest) = (pair[0], pair[1]);
+        match replacement.path {
+            Some(detour) => println!(
+                "{} - {}: detour of cost {} (+{}): {}",
+                name(src),
+                name(dest),
+                input.scale.format(detour.cost),
+                input.scale.format(detour.cost - path.cost),
+                format_route(&graph, &detour, input.scale)
+            ),
+            None => println!(
+                "{} - {}: no detour, the pair is disconnected",
+                name(src),
+                name(dest)
+            ),
+        }
+    }
+    Ok(())
+}
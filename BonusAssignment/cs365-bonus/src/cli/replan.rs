@@ -0,0 +1,106 @@
+use super::{find_node, Args, Input};
+use cs365_bonus::dstar_lite::{parse_scenario, DStarLite, Step, BLOCKED};
+use cs365_bonus::view::{FilterView, Reweighted};
+use cs365_bonus::{find_shortest_path, Weight};
+use std::time::{Duration, Instant};
+
+/// Play the scenario in `--scenario <file>` on the graph, for an agent going from `--from`
+/// to `--to`, benchmarking D* Lite's incremental repairs against Dijkstra from scratch.
+///
+/// The scenario has one step per line: `set u v <cost>` changes an edge's cost, or with
+/// `blocked` removes it, `move <node>` moves the agent, and `plan` asks for the shortest
+/// path from where the agent is. Both planners answer the first plan before the script
+/// starts and each `plan` after, and for each the command prints the cost along with the
+/// nodes D* Lite expanded and the time each planner took, then the totals. Costs must be
+/// positive.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let from = args
+        .value("--from")?
+        .ok_or("'replan' requires '--from <node>'")?;
+    let to = args
+        .value("--to")?
+        .ok_or("'replan' requires '--to <node>'")?;
+    let scenario = args
+        .value("--scenario")?
+        .ok_or("'replan' requires '--scenario <file>'")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    if graph.edges().any(|(_, _, _, cost)| cost <= 0) {
+        return Err("'replan' needs positive costs".into());
+    }
+    let (start, goal) = (find_node(&graph, &from)?, find_node(&graph, &to)?);
+    let text = std::fs::read_to_string(&scenario)
+        .map_err(|e| format!("Failed to open '{}': {}", scenario, e))?;
+    let steps = parse_scenario(&graph, &text, input.scale)
+        .map_err(|e| format!("Failed to parse '{}': {}", scenario, e))?;
+
+    let mut planner = DStarLite::new(&graph, start, goal, |_, _| 0);
+    let mut cost: Vec<Weight> = graph.edges().map(|(_, _, _, cost)| cost).collect();
+    let mut here = start;
+    let (mut incremental, mut scratch) = (Duration::ZERO, Duration::ZERO);
+    let mut plans = 0;
+    let plan = std::iter::once(&Step::Plan);
+    for &step in plan.chain(&steps) {
+        match step {
+            Step::SetCost(edge, new) => {
+                cost[edge.index()] = new;
+                planner.set_cost(edge, new);
+                continue;
+            }
+            Step::Move(node) => {
+                here = node;
+                planner.move_to(node);
+                continue;
+            }
+            Step::Plan => {}
+        }
+        let expanded = planner.expanded();
+        let timer = Instant::now();
+        let repaired = planner.replan();
+        let repair_time = timer.elapsed();
+
+        let timer = Instant::now();
+        let open = FilterView::new(&graph, |_| true, |_, edge| cost[edge.id.index()] != BLOCKED);
+        let view = Reweighted::new(&open, |edge| cost[edge.id.index()]);
+        let fresh = find_shortest_path(&view, here, goal).map(|path| path.cost);
+        let fresh_time = timer.elapsed();
+
+        if repaired != fresh {
+            return Err(format!(
+                "plan {}: D* Lite found {:?} but Dijkstra found {:?}",
+                plans, repaired, fresh
+            ));
+        }
+        let name = graph.get_node_name(here).unwrap_or("");
+        let found = match repaired {
+            Some(found) => format!("cost {}", input.scale.format(found)),
+            None => "unreachable".into(),
+        };
+        println!(
+            "Plan {} from '{}': {}; D* Lite expanded {} nodes in {:.3} ms, Dijkstra took {:.3} ms",
+            plans,
+            name,
+            found,
+            planner.expanded() - expanded,
+            ms(repair_time),
+            ms(fresh_time)
+        );
+        incremental += repair_time;
+        scratch += fresh_time;
+        plans += 1;
+    }
+    println!(
+        "Total over {} plans: D* Lite {:.3} ms ({} nodes expanded), Dijkstra {:.3} ms",
+        plans,
+        ms(incremental),
+        planner.expanded(),
+        ms(scratch)
+    );
+    Ok(())
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,41 @@
+use super::{write_output, Args};
+use cs365_bonus::schedule::{parse_tasks, schedule};
+
+/// The task file read when none is given.
+const DEFAULT_TASKS: &str = "tasks.txt";
+
+/// Print earliest and latest start times, slack, and the critical path for a task file of
+/// `name duration [dependency...]` lines.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [] => DEFAULT_TASKS,
+        [path] => path.as_str(),
+        _ => return Err("'schedule' takes a single task file".into()),
+    };
+
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let tasks = parse_tasks(&data).map_err(|e| format!("Failed to parse '{}': {}", path, e))?;
+    let schedule = schedule(&tasks).map_err(|e| format!("Failed to schedule tasks: {}", e))?;
+
+    write_output(output.as_deref(), |w| {
+        writeln!(w, "task duration earliest latest slack")?;
+        for (task, times) in tasks.iter().zip(schedule.times.iter()) {
+            writeln!(
+                w,
+                "{} {} {} {} {}",
+                task.name, task.duration, times.earliest_start, times.latest_start, times.slack
+            )?;
+        }
+        writeln!(w, "Project length: {}", schedule.length)?;
+        let critical: Vec<&str> = schedule
+            .critical_path
+            .iter()
+            .map(|&i| tasks[i].name.as_str())
+            .collect();
+        writeln!(w, "Critical path: {}", critical.join(" -> "))?;
+        w.flush()
+    })
+}
@@ -0,0 +1,116 @@
+use super::{Args, Input};
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::server::{watch, ApiKeys, Limits, Server};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Serve route queries over HTTP on `--addr` (default `127.0.0.1:8080`) with `--threads`
+/// workers (default all cores). See [`cs365_bonus::server`] for the endpoints.
+///
+/// With `--watch`, the graph is reloaded when the input files change, checking every
+/// `--watch-interval` seconds (default 1). Requests already running finish on the old
+/// graph, and a file that fails to load leaves the old graph in place.
+///
+/// `--rate <n>` allows each client address `n` requests per second after a `--burst`
+/// (default 10), `--max-k` caps `k` in route queries (default 10), `--max-batch` the
+/// queries in one batch request (default 1000), `--search-budget <n>` rejects queries
+/// whose searches would expand more than `n` nodes, and `--max-body-bytes` bounds request
+/// bodies (default 1 MiB).
+///
+/// Clients may upload up to `--max-uploads` graphs of their own (default 16, 0 turns
+/// uploads off), each kept until it goes unused for `--upload-ttl` seconds (default 900).
+///
+/// With `--attributes <file>`, queries may pass `weight_factor` to reweigh edges by their
+/// `label` attribute.
+///
+/// `--api-key-file <path>` only serves requests sending one of the keys in that file, one
+/// per line, as `Authorization: Bearer <key>` or `X-API-Key: <key>`.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let addr = args
+        .value("--addr")?
+        .unwrap_or_else(|| "127.0.0.1:8080".into());
+    let threads = match args.parsed::<usize>("--threads")? {
+        Some(threads) => threads.max(1),
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let watching = args.flag("--watch");
+    let interval = args.parsed::<f64>("--watch-interval")?.unwrap_or(1.0);
+    let defaults = Limits::default();
+    let limits = Limits {
+        rate: args.parsed("--rate")?,
+        burst: args.parsed("--burst")?.unwrap_or(defaults.burst),
+        max_k: args.parsed("--max-k")?.unwrap_or(defaults.max_k),
+        max_batch: args.parsed("--max-batch")?.unwrap_or(defaults.max_batch),
+        search_budget: args.parsed("--search-budget")?,
+        max_body_bytes: args
+            .parsed("--max-body-bytes")?
+            .unwrap_or(defaults.max_body_bytes),
+        max_uploads: args
+            .parsed("--max-uploads")?
+            .unwrap_or(defaults.max_uploads),
+        upload_ttl: match args.parsed::<f64>("--upload-ttl")? {
+            Some(seconds) if seconds > 0.0 && seconds.is_finite() => {
+                Duration::from_secs_f64(seconds)
+            }
+            Some(_) => return Err("'--upload-ttl' must be a positive number of seconds".into()),
+            None => defaults.upload_ttl,
+        },
+        ..defaults
+    };
+    let key_file = args.value("--api-key-file")?;
+    let files = args.finish()?;
+    if !(interval > 0.0 && interval.is_finite()) {
+        return Err("'--watch-interval' must be a positive number of seconds".into());
+    }
+    if limits
+        .rate
+        .is_some_and(|rate| !(rate > 0.0 && rate.is_finite()))
+    {
+        return Err("'--rate' must be a positive number of requests per second".into());
+    }
+
+    let load = || -> Result<_, String> {
+        let graph = input.load(&files)?;
+        if has_negative_edges(&graph) {
+            return Err("'serve' does not support negative costs".into());
+        }
+        let attributes = input.load_attributes(&graph)?.unwrap_or_default();
+        Ok(graph.freeze_with(attributes))
+    };
+    let mut server = Server::with_limits(load()?, limits);
+    if let Some(path) = key_file {
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+        let keys = ApiKeys::parse(&text);
+        if keys.is_empty() {
+            return Err(format!("'{}' does not contain any API keys", path));
+        }
+        server = server.with_auth(keys);
+    }
+    let listener =
+        TcpListener::bind(&addr).map_err(|e| format!("Failed to listen on '{}': {}", addr, e))?;
+    println!("Listening on http://{}", addr);
+    std::thread::scope(|scope| {
+        if watching {
+            let sources: Vec<PathBuf> = input
+                .sources(&files)
+                .into_iter()
+                .map(PathBuf::from)
+                .collect();
+            let (server, load) = (&server, &load);
+            scope.spawn(move || {
+                watch(&sources, Duration::from_secs_f64(interval), || match server
+                    .reload_frozen(load)
+                {
+                    Ok(()) => eprintln!("Reloaded the graph."),
+                    Err(e) => eprintln!("Keeping the current graph: {}", e),
+                })
+            });
+        }
+        server
+            .serve(&listener, threads)
+            .map_err(|e| format!("Server stopped: {}", e))
+    })
+}
@@ -0,0 +1,58 @@
+use super::{find_node, Args, Input};
+use cs365_bonus::negative_edges::{shortest_paths_with_signed_edges, SignedEdge};
+use cs365_bonus::Graph;
+
+/// Shortest path from `--from` to `--to` (default `a` to `z`) over the input graph plus
+/// the directed `u v cost` edges in the `--signed` file, whose costs may be negative.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let signed = args
+        .value("--signed")?
+        .ok_or("'signed-query' requires '--signed <file>'")?;
+    let from = args.value("--from")?.unwrap_or_else(|| "a".into());
+    let to = args.value("--to")?.unwrap_or_else(|| "z".into());
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let edges = read_signed_edges(&graph, &signed)?;
+    let (start, end) = (find_node(&graph, &from)?, find_node(&graph, &to)?);
+    let paths = shortest_paths_with_signed_edges(&graph, &edges, start)
+        .ok_or("a negative cycle is reachable, so shortest paths are undefined")?;
+
+    match paths.path_to(end) {
+        Some((nodes, costs)) => {
+            println!("Located a minimum path of cost: {}", costs[costs.len() - 1]);
+            let route: Vec<String> = nodes
+                .iter()
+                .zip(costs.iter())
+                .map(|(&node, cost)| {
+                    format!("{} ({})", graph.get_node_name(node).unwrap_or(""), cost)
+                })
+                .collect();
+            println!("{}", route.join(" -> "));
+        }
+        None => eprintln!("There is no path from '{}' to '{}'.", from, to),
+    }
+    Ok(())
+}
+
+fn read_signed_edges(graph: &Graph, path: &str) -> Result<Vec<SignedEdge>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let mut edges = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            [src, dest, cost] => edges.push(SignedEdge {
+                src: find_node(graph, src)?,
+                dest: find_node(graph, dest)?,
+                cost: cost
+                    .parse()
+                    .map_err(|_| format!("invalid cost on line {} of '{}'", i + 1, path))?,
+            }),
+            _ => return Err(format!("invalid edge on line {} of '{}'", i + 1, path)),
+        }
+    }
+    Ok(edges)
+}
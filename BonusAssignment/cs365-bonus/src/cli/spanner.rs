@@ -0,0 +1,62 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::rng::Rng;
+use cs365_bonus::spanner::{greedy_spanner, stretch_stats};
+use cs365_bonus::{write_edge_list_scaled, Graph, NodeId, Weight};
+use std::collections::HashSet;
+
+/// Write a greedy t-spanner as an edge list, reporting size and stretch statistics on stderr.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let stretch = args
+        .parsed::<f64>("--stretch")?
+        .ok_or("'spanner' requires '--stretch <t>'")?;
+    let sources = args.parsed::<usize>("--sample-sources")?.unwrap_or(32);
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    if stretch.is_nan() || stretch < 1.0 {
+        return Err(format!("'--stretch' must be at least 1, got {}", stretch));
+    }
+
+    let graph = input.load(&files)?;
+    let kept: HashSet<_> = greedy_spanner(&graph, stretch).into_iter().collect();
+    let spanner = graph.edge_subgraph(|id| kept.contains(&id));
+    write_output(output.as_deref(), |w| {
+        write_edge_list_scaled(&spanner, input.scale, w)
+    })?;
+
+    let sample = sample_nodes(&graph, sources, seed);
+    let stats = stretch_stats(&graph, &spanner, &sample);
+    eprintln!(
+        "edges {} -> {}, total weight {} -> {}",
+        graph.edge_count(),
+        spanner.edge_count(),
+        input.scale.format(total_weight(&graph)),
+        input.scale.format(total_weight(&spanner))
+    );
+    eprintln!(
+        "stretch over {} pairs from {} sources: max {:.4}, mean {:.4}",
+        stats.pairs,
+        sample.len(),
+        stats.max,
+        stats.mean
+    );
+    Ok(())
+}
+
+fn total_weight(graph: &Graph) -> Weight {
+    graph.edges().map(|(_, _, _, cost)| cost).sum()
+}
+
+/// Pick up to `count` distinct nodes at random.
+fn sample_nodes(graph: &Graph, count: usize, seed: u64) -> Vec<NodeId> {
+    let mut nodes: Vec<NodeId> = graph.node_ids().collect();
+    let mut rng = Rng::new(seed);
+    let count = count.min(nodes.len());
+    for i in 0..count {
+        let j = i + rng.below(nodes.len() - i);
+        nodes.swap(i, j);
+    }
+    nodes.truncate(count);
+    nodes
+}
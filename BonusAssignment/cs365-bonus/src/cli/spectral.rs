@@ -0,0 +1,58 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::coarsen::Hierarchy;
+use cs365_bonus::spectral::{fiedler, spectral_bisection};
+
+/// Estimate the algebraic connectivity and print the Fiedler vector with the spectral bisection.
+///
+/// `--coarsen <n>` first merges nodes along heavy edges until at most `n` remain, solves
+/// the smaller problem, and gives each node the value of the node it was merged into. The
+/// connectivity printed is then the coarse graph's.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let weighted = args.flag("--weighted");
+    let iterations = args.parsed::<usize>("--iterations")?.unwrap_or(10_000);
+    let tolerance = args.parsed::<f64>("--tolerance")?.unwrap_or(1e-9);
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let coarsen = args.parsed::<usize>("--coarsen")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let hierarchy = match coarsen {
+        Some(target) => Hierarchy::build(&graph, weighted, target.max(2)),
+        None => Hierarchy::default(),
+    };
+    let (solved, weighted) = match hierarchy.coarsest() {
+        Some(coarse) => (coarse, true),
+        None => (&graph, weighted),
+    };
+    let mut result = fiedler(solved, weighted, iterations, tolerance, seed)
+        .ok_or("the graph needs at least two nodes")?;
+    result.vector = hierarchy.project(&result.vector);
+    if !result.converged {
+        eprintln!(
+            "warning: power iteration did not converge within {} iterations",
+            result.iterations
+        );
+    }
+
+    let side = spectral_bisection(&result.vector);
+    write_output(output.as_deref(), |w| {
+        writeln!(w, "algebraic-connectivity {:.9}", result.value)?;
+        writeln!(w, "iterations {}", result.iterations)?;
+        if hierarchy.coarsest().is_some() {
+            writeln!(w, "coarsened-nodes {}", solved.node_count())?;
+        }
+        for ((node, value), side) in graph.node_ids().zip(result.vector.iter()).zip(side.iter()) {
+            let side = if *side { "+" } else { "-" };
+            writeln!(
+                w,
+                "{} {:.9} {}",
+                graph.get_node_name(node).unwrap_or(""),
+                value,
+                side
+            )?;
+        }
+        w.flush()
+    })
+}
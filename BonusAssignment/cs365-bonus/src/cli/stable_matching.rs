@@ -0,0 +1,78 @@
+use super::{write_output, Args};
+use cs365_bonus::stable_matching::{Market, Preferences};
+
+/// Match the people of `--proposers <file>` with those of `--receivers <file>` by
+/// Gale-Shapley, giving the stable matching best for every proposer, and write one pair per
+/// line. Each file holds one person per line: their name, then the names from the other
+/// file they would accept, best first.
+///
+/// The matching is then checked for blocking pairs, two people who would both rather have
+/// each other, and the check's outcome is printed along with who was left unmatched and how
+/// many proposals it took.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let proposers = args
+        .value("--proposers")?
+        .ok_or("'stable-matching' requires '--proposers <file>'")?;
+    let receivers = args
+        .value("--receivers")?
+        .ok_or("'stable-matching' requires '--receivers <file>'")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    if !files.is_empty() {
+        return Err("'stable-matching' reads '--proposers' and '--receivers' only".into());
+    }
+
+    let read = |path: &str| {
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+        Preferences::parse(&data).map_err(|e| format!("Failed to parse '{}': {}", path, e))
+    };
+    let (proposers, receivers) = (read(&proposers)?, read(&receivers)?);
+    let market = Market::new(&proposers, &receivers)?;
+    let matching = market.gale_shapley();
+
+    let mut matched = vec![false; receivers.len()];
+    for receiver in matching.partner.iter().flatten() {
+        matched[*receiver] = true;
+    }
+    let unmatched: Vec<&str> = (0..proposers.len())
+        .filter(|&proposer| matching.partner[proposer].is_none())
+        .map(|proposer| proposers.name(proposer))
+        .chain(
+            (0..receivers.len())
+                .filter(|&receiver| !matched[receiver])
+                .map(|receiver| receivers.name(receiver)),
+        )
+        .collect();
+    eprintln!(
+        "Matched {} pairs after {} proposals",
+        matching.partner.iter().flatten().count(),
+        matching.proposals
+    );
+    if !unmatched.is_empty() {
+        eprintln!("Unmatched: {}", unmatched.join(" "));
+    }
+    let blocking = market.blocking_pairs(&matching.partner);
+    if !blocking.is_empty() {
+        let pairs: Vec<String> = blocking
+            .iter()
+            .map(|&(p, r)| format!("{}-{}", proposers.name(p), receivers.name(r)))
+            .collect();
+        return Err(format!("the matching is blocked by {}", pairs.join(" ")));
+    }
+    eprintln!("Verified stable: no blocking pairs");
+
+    write_output(output.as_deref(), |w| {
+        for (proposer, receiver) in matching.partner.iter().enumerate() {
+            if let Some(receiver) = *receiver {
+                writeln!(
+                    w,
+                    "{} {}",
+                    proposers.name(proposer),
+                    receivers.name(receiver)
+                )?;
+            }
+        }
+        w.flush()
+    })
+}
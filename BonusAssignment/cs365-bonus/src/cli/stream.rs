@@ -0,0 +1,95 @@
+use super::{format_route, Args};
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::streaming::{Event, SlidingWindow};
+use cs365_bonus::Weight;
+use std::io::{BufRead, Write};
+
+/// Read time-stamped edges from stdin, `src dest cost [->] @time` in time order, keeping
+/// the graph of those in the last `--window <width>` time units. Blank lines and `#`
+/// comments are skipped, and `--decimals` reads decimal costs.
+///
+/// Every `--every <interval>` time units of the stream's own clock (default the window
+/// width), and once more at its end, prints the window's node, edge and component counts,
+/// then answers each standing `--watch from:to` query with the shortest path between the
+/// two over the edges in the window.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let places = args.parsed::<u32>("--decimals")?.unwrap_or(0);
+    let scale = FixedPoint::new(places).ok_or_else(|| {
+        format!(
+            "'--decimals' may be at most {}",
+            cs365_bonus::decimal::MAX_PLACES
+        )
+    })?;
+    let width = args
+        .parsed::<Weight>("--window")?
+        .ok_or("'stream' requires '--window <width>'")?;
+    let every = args.parsed::<Weight>("--every")?.unwrap_or(width);
+    let watches = args
+        .values("--watch")?
+        .iter()
+        .map(|watch| match watch.split_once(':') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+                Ok((from.to_string(), to.to_string()))
+            }
+            _ => Err(format!("'--watch' expects 'from:to', got '{}'", watch)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    args.finish()?;
+    if width <= 0 || every <= 0 {
+        return Err("'--window' and '--every' must be positive".into());
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut report = |window: &SlidingWindow, time: Weight| -> Result<(), String> {
+        let stats = window.stats();
+        let result = writeln!(
+            out,
+            "t={} nodes {} edges {} components {} largest {}",
+            time, stats.nodes, stats.edges, stats.components, stats.largest
+        )
+        .and_then(|_| {
+            for (from, to) in &watches {
+                match window.shortest_path(from, to) {
+                    Some(path) => writeln!(
+                        out,
+                        "  {} -> {}: {}: {}",
+                        from,
+                        to,
+                        scale.format(path.cost),
+                        format_route(window.graph(), &path, scale)
+                    )?,
+                    None => writeln!(out, "  {} -> {}: unreachable", from, to)?,
+                }
+            }
+            out.flush()
+        });
+        result.map_err(|e| format!("Failed to write output: {}", e))
+    };
+
+    let mut window = SlidingWindow::new(width);
+    let mut next_report = None;
+    let mut reported = None;
+    for (i, line) in std::io::stdin().lock().lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read stdin: {}", e))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let event = Event::parse(line, scale).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        let next = next_report.get_or_insert(event.time.saturating_add(every));
+        while event.time > *next {
+            window.advance(*next);
+            report(&window, *next)?;
+            reported = Some(*next);
+            *next = next.saturating_add(every);
+        }
+        window
+            .insert(event)
+            .map_err(|e| format!("line {}: {}", i + 1, e))?;
+    }
+    match window.now() {
+        Some(now) if reported != Some(now) => report(&window, now),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,126 @@
+use super::{find_node, write_output, Args, DEFAULT_INPUT};
+use cs365_bonus::decimal::FixedPoint;
+use cs365_bonus::temporal::TemporalGraph;
+use cs365_bonus::{shortest_path_tree, Weight};
+use std::io::Read;
+
+/// Time-respecting routing on an edge list whose edges may end in `@time`, the time they
+/// can be taken, with untimed edges available at any time. Reads one file, defaulting to
+/// `input.txt`, with `-` for stdin, and `--decimals` for decimal costs; the other input
+/// options don't apply.
+///
+/// Journeys start at the beginning of `--window T0:T1` and take edges stamped within it
+/// at non-decreasing times, an edge being taken on arrival at its time. The window
+/// defaults to the earliest to the latest time in the file.
+///
+/// Prints the journey from `--from` to `--to` (default `a` to `z`) that arrives first,
+/// with the time each edge is taken at. `--reachability` instead prints, for every node,
+/// how many others it reaches by such journeys and how many it reaches ignoring time,
+/// then the totals over all pairs.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let places = args.parsed::<u32>("--decimals")?.unwrap_or(0);
+    let scale = FixedPoint::new(places).ok_or_else(|| {
+        format!(
+            "'--decimals' may be at most {}",
+            cs365_bonus::decimal::MAX_PLACES
+        )
+    })?;
+    let from = args.value("--from")?.unwrap_or_else(|| "a".into());
+    let to = args.value("--to")?.unwrap_or_else(|| "z".into());
+    let window = args.value("--window")?;
+    let reachability = args.flag("--reachability");
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [] => DEFAULT_INPUT,
+        [file] => file.as_str(),
+        _ => return Err("'temporal' reads a single input file".into()),
+    };
+
+    let mut text = String::new();
+    let read = if path == "-" {
+        std::io::stdin().read_to_string(&mut text)
+    } else {
+        std::fs::File::open(path).and_then(|mut file| file.read_to_string(&mut text))
+    };
+    read.map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+    let network = TemporalGraph::parse_scaled(&text, scale)
+        .map_err(|e| format!("Failed to parse input graph '{}': {}", path, e))?;
+    let graph = network.graph();
+
+    let (t0, t1) = match window {
+        Some(window) => window
+            .split_once(':')
+            .and_then(|(t0, t1)| Some((t0.parse().ok()?, t1.parse().ok()?)))
+            .filter(|(t0, t1): &(Weight, Weight)| t0 <= t1)
+            .ok_or_else(|| format!("'--window' expects 'start:end', got '{}'", window))?,
+        None => {
+            let times = graph.edges().filter_map(|(id, ..)| network.time(id));
+            let (t0, t1) = times.fold((Weight::MAX, Weight::MIN), |(t0, t1), t| {
+                (t0.min(t), t1.max(t))
+            });
+            if t0 > t1 {
+                (0, 0)
+            } else {
+                (t0, t1)
+            }
+        }
+    };
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+
+    if reachability {
+        let temporal = network.reachability(t0, t1);
+        return write_output(output.as_deref(), |w| {
+            let (mut total, mut total_static) = (0, 0);
+            for (node, reached) in graph.node_ids().zip(temporal) {
+                let tree = shortest_path_tree(graph, node);
+                let reached_static = graph
+                    .node_ids()
+                    .filter(|&other| other != node && tree.distance(other).is_some())
+                    .count();
+                writeln!(w, "{} {} {}", name(node), reached, reached_static)?;
+                total += reached;
+                total_static += reached_static;
+            }
+            writeln!(
+                w,
+                "# {} of {} ordered pairs are reachable within {}:{}, {} ignoring time",
+                total,
+                graph.node_count() * graph.node_count().saturating_sub(1),
+                t0,
+                t1,
+                total_static
+            )?;
+            w.flush()
+        });
+    }
+
+    let (start, end) = (find_node(graph, &from)?, find_node(graph, &to)?);
+    let journey = match network.earliest_arrival(start, end, t0, t1) {
+        Some(journey) => journey,
+        None => {
+            eprintln!(
+                "There is no time-respecting path from '{}' to '{}' within {}:{}.",
+                from, to, t0, t1
+            );
+            return Ok(());
+        }
+    };
+    write_output(output.as_deref(), |w| {
+        writeln!(
+            w,
+            "Located an earliest arrival at {} with a cost of: {}",
+            journey.arrival,
+            scale.format(journey.path.cost)
+        )?;
+        write!(w, "{}", name(journey.path.nodes[0]))?;
+        for (node, time) in journey.path.nodes[1..].iter().zip(&journey.times) {
+            match time {
+                Some(time) => write!(w, " -@{}-> {}", time, name(*node))?,
+                None => write!(w, " -> {}", name(*node))?,
+            }
+        }
+        writeln!(w)?;
+        w.flush()
+    })
+}
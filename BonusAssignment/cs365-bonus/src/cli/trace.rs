@@ -0,0 +1,47 @@
+use super::Args;
+use cs365_bonus::lts::TransitionSystem;
+
+/// Find the shortest action trace from state `--from` to state `--to` in a transition
+/// system read from a file of `from action to` lines, printed as `a --action--> b ...`.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let from = args
+        .value("--from")?
+        .ok_or("'trace' requires '--from <state>'")?;
+    let to = args
+        .value("--to")?
+        .ok_or("'trace' requires '--to <state>'")?;
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [path] => path.as_str(),
+        _ => return Err("'trace' takes a single transition file".into()),
+    };
+
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let lts =
+        TransitionSystem::parse(&data).map_err(|e| format!("Failed to parse '{}': {}", path, e))?;
+    let state = |name: &str| {
+        lts.get(name)
+            .ok_or_else(|| format!("state '{}' does not exist", name))
+    };
+    let (start, end) = (state(&from)?, state(&to)?);
+    let trace = match lts.shortest_trace(start, end) {
+        Some(trace) => trace,
+        None => {
+            println!("'{}' is not reachable from '{}'", to, from);
+            return Ok(());
+        }
+    };
+    println!("reachable in {} steps", trace.len());
+    let mut line = from;
+    for &transition in &trace {
+        let (_, next) = lts
+            .graph()
+            .edge_endpoints(transition)
+            .expect("traces are made of the system's transitions");
+        let next = lts.graph().get_node_name(next).unwrap_or("");
+        line.push_str(&format!(" --{}--> {}", lts.action(transition), next));
+    }
+    println!("{}", line);
+    Ok(())
+}
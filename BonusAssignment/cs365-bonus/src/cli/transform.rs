@@ -0,0 +1,24 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::transform::{transform_weights_scaled, WeightOp};
+use cs365_bonus::write_edge_list_scaled;
+
+/// Rewrite edge weights through the `--op` steps, applied in the order given.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let ops = args
+        .values("--op")?
+        .iter()
+        .map(|op| op.parse::<WeightOp>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    if ops.is_empty() {
+        return Err("'transform' requires at least one '--op <operation>'".into());
+    }
+
+    let graph = input.load(&files)?;
+    let graph = transform_weights_scaled(&graph, &ops, input.scale)?;
+    write_output(output.as_deref(), |w| {
+        write_edge_list_scaled(&graph, input.scale, w)
+    })
+}
@@ -0,0 +1,72 @@
+use super::{find_node, Args, Input};
+use cs365_bonus::hld::HeavyLight;
+use cs365_bonus::lca::Lca;
+use cs365_bonus::mst::minimum_spanning_subgraph;
+use std::io::{BufRead, Write};
+
+/// Answer `lca u v`, `tree-distance u v`, `path-max u v`, `path-sum u v`, and
+/// `minimax u v` queries, one per line, read from the `--queries` file or stdin.
+///
+/// The input must be a forest, unless `--mst` is given to query its minimum spanning
+/// forest instead. Pairs in different trees print `none`, as does the maximum of an
+/// empty path.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let queries = args.value("--queries")?;
+    let mst = args.flag("--mst");
+    let files = args.finish()?;
+
+    let mut graph = input.load(&files)?;
+    if mst {
+        graph = minimum_spanning_subgraph(&graph);
+    }
+    let not_forest = "'tree-query' requires the input to be a forest; pass '--mst' to use its minimum spanning forest";
+    let lca = Lca::new(&graph).ok_or(not_forest)?;
+    let hld = HeavyLight::new(&graph).ok_or(not_forest)?;
+
+    let reader: Box<dyn BufRead> = match &queries {
+        Some(path) => Box::new(std::io::BufReader::new(
+            std::fs::File::open(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?,
+        )),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read queries: {}", e))?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let pair = |a, b| Ok::<_, String>((find_node(&graph, a)?, find_node(&graph, b)?));
+        let answer = match words.as_slice() {
+            [] => continue,
+            [word, ..] if word.starts_with('#') => continue,
+            ["lca", a, b] => {
+                let (a, b) = pair(a, b)?;
+                lca.lca(a, b)
+                    .map(|node| graph.get_node_name(node).unwrap_or("").to_string())
+            }
+            ["tree-distance", a, b] => {
+                let (a, b) = pair(a, b)?;
+                lca.distance(a, b).map(|distance| distance.to_string())
+            }
+            ["path-max", a, b] | ["minimax", a, b] => {
+                let (a, b) = pair(a, b)?;
+                hld.path_max(a, b).map(|max| max.to_string())
+            }
+            ["path-sum", a, b] => {
+                let (a, b) = pair(a, b)?;
+                hld.path_sum(a, b).map(|sum| sum.to_string())
+            }
+            _ => {
+                return Err(format!(
+                    "line {}: unrecognized query '{}'",
+                    i + 1,
+                    line.trim()
+                ))
+            }
+        };
+        writeln!(out, "{}", answer.as_deref().unwrap_or("none"))
+            .and_then(|_| out.flush())
+            .map_err(|e| format!("Failed to write output: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use super::{write_output, Args, Input};
+use cs365_bonus::rng::Rng;
+use cs365_bonus::triangles::{count_triangles, estimate_triangles, motif_census};
+
+/// Count triangles exactly, or estimate them with `--sample <p>` on very large graphs.
+///
+/// `--census` instead reports wedge, triangle, and 4-cycle counts and the transitivity.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let census = args.flag("--census");
+    let sample = args.parsed::<f64>("--sample")?;
+    let seed = args.parsed::<u64>("--seed")?.unwrap_or(0);
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    if census {
+        if sample.is_some() {
+            return Err("'--census' can't be combined with '--sample'".into());
+        }
+        let census = motif_census(&graph);
+        return write_output(output.as_deref(), |w| {
+            writeln!(w, "wedges {}", census.wedges)?;
+            writeln!(w, "triangles {}", census.triangles)?;
+            writeln!(w, "four-cycles {}", census.four_cycles)?;
+            writeln!(w, "transitivity {:.6}", census.transitivity)?;
+            w.flush()
+        });
+    }
+
+    let counts = match sample {
+        Some(p) if p > 0.0 && p <= 1.0 => estimate_triangles(&graph, p, &mut Rng::new(seed)),
+        Some(p) => return Err(format!("'--sample' must be in (0, 1], got {}", p)),
+        None => count_triangles(&graph),
+    };
+
+    write_output(output.as_deref(), |w| {
+        if let Some(p) = sample {
+            writeln!(w, "# estimated from an edge sample with p = {}", p)?;
+        }
+        writeln!(w, "triangles {}", counts.total)?;
+        for (node, count) in graph.node_ids().zip(counts.per_node.iter()) {
+            writeln!(w, "{} {}", graph.get_node_name(node).unwrap_or(""), count)?;
+        }
+        w.flush()
+    })
+}
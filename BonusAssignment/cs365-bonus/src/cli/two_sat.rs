@@ -0,0 +1,43 @@
+use super::{write_output, Args};
+use cs365_bonus::two_sat::Formula;
+
+/// Decide a 2-SAT formula read from a file of clauses, one or two literals per line such
+/// as `a -b`, and print a satisfying value for each variable.
+///
+/// An unsatisfiable formula prints which variable is contradictory and fails, so scripts
+/// can tell the two outcomes apart by the exit status.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+    let path = match files.as_slice() {
+        [path] => path.as_str(),
+        _ => return Err("'2sat' takes a single clause file".into()),
+    };
+
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let formula =
+        Formula::parse(&data).map_err(|e| format!("Failed to parse '{}': {}", path, e))?;
+    let values = match formula.solve() {
+        Ok(values) => values,
+        Err(contradiction) => {
+            let variable = &formula.variables()[contradiction.variable];
+            write_output(output.as_deref(), |w| {
+                writeln!(
+                    w,
+                    "unsatisfiable: '{}' implies its own negation and is implied by it",
+                    variable
+                )?;
+                w.flush()
+            })?;
+            return Err(format!("'{}' is unsatisfiable", path));
+        }
+    };
+    write_output(output.as_deref(), |w| {
+        writeln!(w, "satisfiable")?;
+        for (name, value) in formula.variables().iter().zip(values) {
+            writeln!(w, "{} {}", name, value)?;
+        }
+        w.flush()
+    })
+}
@@ -0,0 +1,46 @@
+use super::{find_node, format_route, Args, Input};
+use cs365_bonus::replacement::{most_vital_edge, most_vital_node};
+use cs365_bonus::Path;
+
+/// Report the edge and the node whose failure hurts the route from `--from` to `--to`
+/// (default `a` to `z`) the most.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let from = args.value("--from")?.unwrap_or_else(|| "a".into());
+    let to = args.value("--to")?.unwrap_or_else(|| "z".into());
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    let (start, end) = (find_node(&graph, &from)?, find_node(&graph, &to)?);
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+    let describe = |path: &Option<Path>| match path {
+        Some(path) => format!(
+            "cost {}: {}",
+            input.scale.format(path.cost),
+            format_route(&graph, path, input.scale)
+        ),
+        None => "the pair is disconnected".to_string(),
+    };
+
+    match most_vital_edge(&graph, start, end) {
+        Some(vital) => {
+            let (src, dest) = graph.edge_endpoints(vital.edge).expect("edge exists");
+            println!(
+                "Most vital edge: {} - {}, without it {}",
+                name(src),
+                name(dest),
+                describe(&vital.path)
+            );
+        }
+        None => eprintln!("There is no path from '{}' to '{}'.", from, to),
+    }
+    match most_vital_node(&graph, start, end) {
+        Some(vital) => println!(
+            "Most vital node: {}, without it {}",
+            name(vital.node),
+            describe(&vital.path)
+        ),
+        None => println!("The path has no intermediate nodes."),
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use super::{find_node, write_output, Args, Input};
+use cs365_bonus::path::has_negative_edges;
+use cs365_bonus::vrp::solve_vrp;
+use cs365_bonus::{Graph, NodeId};
+
+/// Plan delivery routes from `--depot` for vehicles holding `--capacity <n>`, serving the
+/// customers in the `--demands` file, one `node demand` pair per line.
+///
+/// Prints each vehicle's load, cost, and stops, then the total cost.
+pub fn run(mut args: Args) -> Result<(), String> {
+    let input = Input::from_args(&mut args)?;
+    let depot = args
+        .value("--depot")?
+        .ok_or("'vrp' requires '--depot <name>'")?;
+    let demands = args
+        .value("--demands")?
+        .ok_or("'vrp' requires '--demands <file>'")?;
+    let capacity = args
+        .parsed::<u64>("--capacity")?
+        .ok_or("'vrp' requires '--capacity <n>'")?;
+    let output = args.value("--output")?;
+    let files = args.finish()?;
+
+    let graph = input.load(&files)?;
+    if has_negative_edges(&graph) {
+        return Err("'vrp' does not support negative costs".into());
+    }
+    let depot = find_node(&graph, &depot)?;
+    let demands = read_demands(&graph, &demands)?;
+    let solution = solve_vrp(&graph, depot, &demands, capacity)?;
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+    write_output(output.as_deref(), |w| {
+        for (i, route) in solution.routes.iter().enumerate() {
+            let stops: Vec<&str> = route.stops.iter().map(|&stop| name(stop)).collect();
+            writeln!(
+                w,
+                "vehicle {} (load {}/{}, cost {}): {} -> {} -> {}",
+                i + 1,
+                route.load,
+                capacity,
+                input.scale.format(route.cost),
+                name(depot),
+                stops.join(" -> "),
+                name(depot)
+            )?;
+        }
+        writeln!(w, "total cost {}", input.scale.format(solution.cost))?;
+        w.flush()
+    })
+}
+
+fn read_demands(graph: &Graph, path: &str) -> Result<Vec<(NodeId, u64)>, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    let mut demands = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            [node, demand] => demands.push((
+                find_node(graph, node)?,
+                demand
+                    .parse()
+                    .map_err(|_| format!("invalid demand on line {} of '{}'", i + 1, path))?,
+            )),
+            _ => return Err(format!("invalid entry on line {} of '{}'", i + 1, path)),
+        }
+    }
+    Ok(demands)
+}
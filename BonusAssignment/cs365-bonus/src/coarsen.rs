@@ -0,0 +1,236 @@
+use crate::graph::{Graph, NodeId, Weight};
+use std::collections::HashMap;
+
+/// One round of coarsening: a smaller graph and where each node of the finer one went.
+#[derive(Debug, Clone)]
+pub struct Level {
+    /// The coarse graph, whose edges are undirected and weighted by the total weight of
+    /// the finer edges they stand for. Each node is named after its first finer node.
+    pub graph: Graph,
+    /// For each node of the finer graph, the coarse node holding it.
+    pub parent: Vec<NodeId>,
+}
+
+/// Merge pairs of nodes joined by heavy edges into single nodes, roughly halving the graph.
+///
+/// Nodes are visited from the lowest degree up, and each one not yet merged is matched with
+/// the unmatched neighbor it is most strongly connected to, summing parallel edges. Edge
+/// weights are taken as connection strengths, the way spectral methods read them, or as 1
+/// each unless `weighted`. Edge direction and self-loops are ignored.
+pub fn coarsen(graph: &Graph, weighted: bool) -> Level {
+    let n = graph.node_count();
+    let mut strength: Vec<HashMap<NodeId, Weight>> = vec![HashMap::new(); n];
+    for (_, src, dest, cost) in graph.edges() {
+        if src != dest {
+            let weight = if weighted { cost } else { 1 };
+            *strength[src.index()].entry(dest).or_default() += weight;
+            *strength[dest.index()].entry(src).or_default() += weight;
+        }
+    }
+
+    let mut order: Vec<NodeId> = graph.node_ids().collect();
+    order.sort_by_key(|node| strength[node.index()].len());
+    let mut mate: Vec<Option<NodeId>> = vec![None; n];
+    for &node in &order {
+        if mate[node.index()].is_some() {
+            continue;
+        }
+        let heaviest = strength[node.index()]
+            .iter()
+            .filter(|(neighbor, _)| mate[neighbor.index()].is_none())
+            .max_by_key(|&(&neighbor, &weight)| (weight, std::cmp::Reverse(neighbor)))
+            .map(|(&neighbor, _)| neighbor);
+        let partner = heaviest.unwrap_or(node);
+        mate[node.index()] = Some(partner);
+        mate[partner.index()] = Some(node);
+    }
+
+    let mut coarse = Graph::new();
+    let mut parent = vec![NodeId::new(0); n];
+    for node in graph.node_ids() {
+        let partner = mate[node.index()].unwrap_or(node);
+        parent[node.index()] = if partner < node {
+            parent[partner.index()]
+        } else {
+            coarse.get_or_insert_node(graph.get_node_name(node).unwrap_or(""))
+        };
+    }
+    let mut merged: HashMap<(NodeId, NodeId), Weight> = HashMap::new();
+    for node in graph.node_ids() {
+        for (&neighbor, &weight) in &strength[node.index()] {
+            let (u, v) = (parent[node.index()], parent[neighbor.index()]);
+            if u < v {
+                *merged.entry((u, v)).or_default() += weight;
+            }
+        }
+    }
+    let mut merged: Vec<_> = merged.into_iter().collect();
+    merged.sort_unstable();
+    for ((u, v), weight) in merged {
+        coarse.add_bidirectional_edge(u, v, weight);
+    }
+    Level {
+        graph: coarse,
+        parent,
+    }
+}
+
+/// A graph coarsened level by level, for solving a problem such as partitioning or layout
+/// on a small graph and carrying the answer back to the original.
+///
+/// ```
+/// use cs365_bonus::coarsen::Hierarchy;
+///
+/// let graph: cs365_bonus::Graph = "a b 5\nc d 5\nb c 1\nd e 5\ne f 5".parse().unwrap();
+/// let hierarchy = Hierarchy::build(&graph, true, 3);
+/// let coarse = hierarchy.coarsest().unwrap();
+/// assert_eq!(coarse.node_count(), 3);
+/// // Label each coarse node by its id, and see which original nodes were merged.
+/// let labels: Vec<usize> = coarse.node_ids().map(|node| node.index()).collect();
+/// assert_eq!(hierarchy.project(&labels), [0, 0, 1, 1, 2, 2]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Hierarchy {
+    levels: Vec<Level>,
+}
+
+impl Hierarchy {
+    /// Coarsen `graph` until it has at most `target` nodes, or until a round no longer
+    /// shrinks it by a tenth, as happens with star-like graphs whose leaves can't pair up.
+    ///
+    /// `weighted` applies to the first round; later rounds always weigh edges, since by
+    /// then the weights count the original edges merged.
+    pub fn build(graph: &Graph, weighted: bool, target: usize) -> Hierarchy {
+        let mut levels: Vec<Level> = Vec::new();
+        loop {
+            let current = levels.last().map_or(graph, |level| &level.graph);
+            if current.node_count() <= target {
+                break;
+            }
+            let level = coarsen(current, weighted || !levels.is_empty());
+            if level.graph.node_count() * 10 > current.node_count() * 9 {
+                break;
+            }
+            levels.push(level);
+        }
+        Hierarchy { levels }
+    }
+
+    /// The rounds of coarsening, from the original graph's first round to the coarsest.
+    pub fn levels(&self) -> &[Level] {
+        &self.levels
+    }
+
+    /// The smallest graph, or `None` if the original was never coarsened.
+    pub fn coarsest(&self) -> Option<&Graph> {
+        self.levels.last().map(|level| &level.graph)
+    }
+
+    /// Carry one value per node of the coarsest graph back to the original, giving each
+    /// original node the value of the coarse node it was merged into.
+    pub fn project<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        let mut values = values.to_vec();
+        for level in self.levels.iter().rev() {
+            values = level
+                .parent
+                .iter()
+                .map(|node| values[node.index()].clone())
+                .collect();
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_graph(rng: &mut Rng) -> Graph {
+        let n = 1 + rng.below(20);
+        let mut graph = Graph::new();
+        for i in 0..n {
+            graph.get_or_insert_node(&i.to_string());
+        }
+        for _ in 0..rng.below(3 * n) {
+            let (u, v) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+            let cost = 1 + rng.below(9) as Weight;
+            if rng.chance(0.3) {
+                graph.add_edge(u, v, cost);
+            } else {
+                graph.add_bidirectional_edge(u, v, cost);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn merges_a_maximal_matching_and_keeps_weight() {
+        let mut rng = Rng::new(45);
+        for _ in 0..200 {
+            let graph = random_graph(&mut rng);
+            let weighted = rng.chance(0.5);
+            let level = coarsen(&graph, weighted);
+            let parent = |node: NodeId| level.parent[node.index()];
+            let mut members = vec![Vec::new(); level.graph.node_count()];
+            for node in graph.node_ids() {
+                members[parent(node).index()].push(node);
+            }
+            let adjacent = |u: NodeId, v: NodeId| {
+                graph
+                    .edges()
+                    .any(|(_, a, b, _)| (a, b) == (u, v) || (a, b) == (v, u))
+            };
+            for (coarse, nodes) in members.iter().enumerate() {
+                // Each coarse node is named after its first member.
+                let name = level.graph.get_node_name(NodeId::new(coarse));
+                assert_eq!(name, graph.get_node_name(nodes[0]));
+                match nodes[..] {
+                    [_] => {}
+                    [u, v] => assert!(adjacent(u, v)),
+                    _ => panic!("{} nodes merged into one", nodes.len()),
+                }
+            }
+            let single = |node: NodeId| members[parent(node).index()].len() == 1;
+            let mut expected: Weight = 0;
+            for (_, u, v, cost) in graph.edges() {
+                assert!(
+                    u == v || !(single(u) && single(v)),
+                    "the matching isn't maximal"
+                );
+                if parent(u) != parent(v) {
+                    expected += if weighted { cost } else { 1 };
+                }
+            }
+            let total: Weight = level.graph.edges().map(|(_, _, _, cost)| cost).sum();
+            assert_eq!(total, expected);
+        }
+    }
+
+    #[test]
+    fn projections_follow_every_level() {
+        let mut rng = Rng::new(46);
+        for _ in 0..100 {
+            let graph = random_graph(&mut rng);
+            let target = rng.below(6);
+            let hierarchy = Hierarchy::build(&graph, rng.chance(0.5), target);
+            let coarsest = hierarchy.coarsest().unwrap_or(&graph);
+            let labels: Vec<usize> = coarsest.node_ids().map(|node| node.index()).collect();
+            let projected = hierarchy.project(&labels);
+            assert_eq!(projected.len(), graph.node_count());
+            for node in graph.node_ids() {
+                let mut coarse = node;
+                for level in hierarchy.levels() {
+                    coarse = level.parent[coarse.index()];
+                }
+                assert_eq!(projected[node.index()], coarse.index());
+            }
+            // Every level shrinks its graph by at least a tenth.
+            let mut size = graph.node_count();
+            for level in hierarchy.levels() {
+                assert!(level.graph.node_count() * 10 <= size * 9);
+                size = level.graph.node_count();
+            }
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::ShortestPathTree;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::Write;
+
+/// The first bytes of every compressed graph.
+pub const MAGIC: &[u8; 4] = b"CSG\x01";
+
+/// One byte offset is stored for every this many nodes; lookups decode forward from the
+/// nearest one.
+const BLOCK: usize = 64;
+
+/// Error returned when a compressed graph is truncated or malformed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    offset: usize,
+}
+
+impl DecodeError {
+    /// The byte offset at which decoding failed.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid compressed graph at byte {}", self.offset)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Decode a LEB128 varint starting at `*pos`, advancing past it.
+fn get_varint(data: &[u8], pos: &mut usize) -> Result<u128, DecodeError> {
+    let mut value = 0u128;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*pos).ok_or(DecodeError { offset: *pos })?;
+        if shift >= 128 {
+            return Err(DecodeError { offset: *pos });
+        }
+        value |= u128::from(byte & 0x7f) << shift;
+        *pos += 1;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn zigzag(value: i64) -> u128 {
+    ((value << 1) ^ (value >> 63)) as u64 as u128
+}
+
+fn unzigzag(value: u128) -> i64 {
+    let value = value as u64;
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// Write `graph` in the compressed binary format read by [`CompressedGraph`].
+///
+/// Each adjacency list is sorted by neighbor and stored as varint gaps, the first one
+/// relative to the node itself, so lists of nearby neighbors, as produced by
+/// [`reorder`](crate::reorder), take about a byte per entry plus the cost. The cost and
+/// whether the edge is one-way share a varint. Names are stored as length-prefixed
+/// strings. Both sections are indexed by a byte offset every 64 nodes.
+///
+/// Edge ids are not stored; decompressing numbers edges in adjacency order.
+pub fn write_compressed(graph: &Graph, writer: &mut dyn Write) -> std::io::Result<()> {
+    let n = graph.node_count();
+    let mut names = Vec::new();
+    let mut name_index = Vec::new();
+    let mut adjacency = Vec::new();
+    let mut adjacency_index = Vec::new();
+    for node in graph.node_ids() {
+        if node.index() % BLOCK == 0 {
+            name_index.push(names.len() as u64);
+            adjacency_index.push(adjacency.len() as u64);
+        }
+        let name = graph.get_node_name(node).unwrap_or("");
+        put_varint(&mut names, name.len() as u128);
+        names.extend_from_slice(name.as_bytes());
+
+        let mut edges: Vec<(usize, u128)> = graph
+            .edges_of(node)
+            .iter()
+            .map(|edge| {
+                let directed = graph.is_edge_directed(edge.id) == Some(true);
+                (edge.node.index(), zigzag(edge.cost) << 1 | directed as u128)
+            })
+            .collect();
+        edges.sort_unstable();
+        put_varint(&mut adjacency, edges.len() as u128);
+        let mut previous = None;
+        for (target, cost) in edges {
+            match previous {
+                None => put_varint(&mut adjacency, zigzag(target as i64 - node.index() as i64)),
+                Some(previous) => put_varint(&mut adjacency, (target - previous) as u128),
+            }
+            put_varint(&mut adjacency, cost);
+            previous = Some(target);
+        }
+    }
+
+    let mut header = Vec::new();
+    header.extend_from_slice(MAGIC);
+    put_varint(&mut header, n as u128);
+    put_varint(&mut header, names.len() as u128);
+    put_varint(&mut header, adjacency.len() as u128);
+    writer.write_all(&header)?;
+    for offset in name_index.iter().chain(adjacency_index.iter()) {
+        writer.write_all(&offset.to_le_bytes())?;
+    }
+    writer.write_all(&names)?;
+    writer.write_all(&adjacency)?;
+    writer.flush()
+}
+
+/// A graph in the compressed format, queried in place.
+///
+/// Only the small header is parsed up front; adjacency lists and names are decoded on
+/// demand, so the memory used is about the size of the file.
+#[derive(Debug, Clone)]
+pub struct CompressedGraph {
+    data: Vec<u8>,
+    node_count: usize,
+    name_index: usize,
+    adjacency_index: usize,
+    names: usize,
+    adjacency: usize,
+}
+
+impl CompressedGraph {
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, DecodeError> {
+        if !data.starts_with(MAGIC) {
+            return Err(DecodeError { offset: 0 });
+        }
+        let mut pos = MAGIC.len();
+        let node_count = get_varint(&data, &mut pos)? as usize;
+        let names_len = get_varint(&data, &mut pos)? as usize;
+        let adjacency_len = get_varint(&data, &mut pos)? as usize;
+        let index_len = node_count.div_ceil(BLOCK).checked_mul(8);
+        let name_index = pos;
+        let adjacency_index = index_len.and_then(|len| name_index.checked_add(len));
+        let names = adjacency_index
+            .zip(index_len)
+            .and_then(|(at, len)| at.checked_add(len));
+        let adjacency = names.and_then(|at| at.checked_add(names_len));
+        let end = adjacency.and_then(|at| at.checked_add(adjacency_len));
+        let (adjacency_index, names, adjacency) = match (adjacency_index, names, adjacency, end) {
+            (Some(a), Some(b), Some(c), Some(end)) if end == data.len() => (a, b, c),
+            _ => return Err(DecodeError { offset: pos }),
+        };
+        Ok(Self {
+            data,
+            node_count,
+            name_index,
+            adjacency_index,
+            names,
+            adjacency,
+        })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// The byte position of `node`'s entry in the section starting at `section`, found by
+    /// decoding forward from its block's indexed offset with `skip`.
+    fn seek(
+        &self,
+        index: usize,
+        section: usize,
+        node: NodeId,
+        skip: impl Fn(&[u8], &mut usize) -> Result<(), DecodeError>,
+    ) -> Result<usize, DecodeError> {
+        let at = index + node.index() / BLOCK * 8;
+        let bytes = self
+            .data
+            .get(at..at + 8)
+            .ok_or(DecodeError { offset: at })?;
+        let mut offset = [0; 8];
+        offset.copy_from_slice(bytes);
+        let mut pos = section + u64::from_le_bytes(offset) as usize;
+        for _ in 0..node.index() % BLOCK {
+            skip(&self.data, &mut pos)?;
+        }
+        Ok(pos)
+    }
+
+    pub fn get_node_name(&self, node: NodeId) -> Option<&str> {
+        if node.index() >= self.node_count {
+            return None;
+        }
+        let skip = |data: &[u8], pos: &mut usize| {
+            let len = get_varint(data, pos)? as usize;
+            *pos += len;
+            Ok(())
+        };
+        let mut pos = self.seek(self.name_index, self.names, node, skip).ok()?;
+        let len = get_varint(&self.data, &mut pos).ok()? as usize;
+        std::str::from_utf8(self.data.get(pos..pos + len)?).ok()
+    }
+
+    /// Find a node by name. This scans every name, so look nodes up once and keep the id.
+    pub fn get_node(&self, name: &str) -> Option<NodeId> {
+        let mut pos = self.names;
+        for i in 0..self.node_count {
+            let len = get_varint(&self.data, &mut pos).ok()? as usize;
+            if self.data.get(pos..pos + len)? == name.as_bytes() {
+                return Some(NodeId::new(i));
+            }
+            pos += len;
+        }
+        None
+    }
+
+    /// Decode the adjacency list of `node` as `(neighbor, cost, directed)` triples.
+    fn adjacency(&self, node: NodeId) -> Result<Vec<(NodeId, Weight, bool)>, DecodeError> {
+        let skip = |data: &[u8], pos: &mut usize| {
+            let degree = get_varint(data, pos)?;
+            for _ in 0..degree * 2 {
+                get_varint(data, pos)?;
+            }
+            Ok(())
+        };
+        let mut pos = self.seek(self.adjacency_index, self.adjacency, node, skip)?;
+        let degree = get_varint(&self.data, &mut pos)? as usize;
+        let mut edges = Vec::with_capacity(degree);
+        let mut target = node.index() as i64;
+        for i in 0..degree {
+            let gap = get_varint(&self.data, &mut pos)?;
+            target += if i == 0 { unzigzag(gap) } else { gap as i64 };
+            let cost = get_varint(&self.data, &mut pos)?;
+            if target < 0 || target as usize >= self.node_count {
+                return Err(DecodeError { offset: pos });
+            }
+            edges.push((
+                NodeId::new(target as usize),
+                unzigzag(cost >> 1),
+                cost & 1 == 1,
+            ));
+        }
+        Ok(edges)
+    }
+
+    /// Nodes reachable from `node` over a single edge, with the cost of that edge.
+    pub fn neighbors(&self, node: NodeId) -> Result<Vec<(NodeId, Weight)>, DecodeError> {
+        Ok(self
+            .adjacency(node)?
+            .into_iter()
+            .map(|(target, cost, _)| (target, cost))
+            .collect())
+    }
+
+    /// Dijkstra from `start` decoding adjacency lists as nodes are settled.
+    pub fn shortest_path_tree(&self, start: NodeId) -> Result<ShortestPathTree, DecodeError> {
+        let mut distance: Vec<Option<Weight>> = vec![None; self.node_count];
+        let mut parent: Vec<Option<NodeId>> = vec![None; self.node_count];
+        let mut heap = BinaryHeap::new();
+        if start.index() < self.node_count {
+            distance[start.index()] = Some(0);
+            heap.push(Reverse((0, start)));
+        }
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if distance[node.index()].is_some_and(|best| cost > best) {
+                continue;
+            }
+            for (target, weight) in self.neighbors(node)? {
+                let next = cost + weight;
+                if distance[target.index()].is_none_or(|best| next < best) {
+                    distance[target.index()] = Some(next);
+                    parent[target.index()] = Some(node);
+                    heap.push(Reverse((next, target)));
+                }
+            }
+        }
+        Ok(ShortestPathTree::new(start, distance, parent))
+    }
+
+    /// Fully decompress into a [`Graph`].
+    pub fn to_graph(&self) -> Result<Graph, DecodeError> {
+        let mut graph = Graph::with_capacity(self.node_count, 0);
+        for i in 0..self.node_count {
+            let name = self
+                .get_node_name(NodeId::new(i))
+                .ok_or(DecodeError { offset: self.names })?;
+            graph.get_or_insert_node(name);
+        }
+        for i in 0..self.node_count {
+            let node = NodeId::new(i);
+            // A bidirectional edge is listed by both ends; a self-loop twice by its node.
+            let mut loops = 0;
+            for (target, cost, directed) in self.adjacency(node)? {
+                if directed {
+                    graph.add_edge(node, target, cost);
+                } else if node < target || (node == target && loops % 2 == 0) {
+                    graph.add_bidirectional_edge(node, target, cost);
+                }
+                if node == target && !directed {
+                    loops += 1;
+                }
+            }
+        }
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::shortest_path_tree;
+    use crate::rng::Rng;
+
+    fn compress(graph: &Graph) -> Vec<u8> {
+        let mut data = Vec::new();
+        write_compressed(graph, &mut data).unwrap();
+        data
+    }
+
+    /// Every edge as `(src, dest, cost, directed)`, with undirected edges ordered.
+    fn edge_set(graph: &Graph) -> Vec<(NodeId, NodeId, Weight, bool)> {
+        let mut edges: Vec<_> = graph
+            .edges()
+            .map(|(id, src, dest, cost)| {
+                let directed = graph.is_edge_directed(id) == Some(true);
+                let (a, b) = if directed {
+                    (src, dest)
+                } else {
+                    (src.min(dest), src.max(dest))
+                };
+                (a, b, cost, directed)
+            })
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn round_trips_random_graphs() {
+        let mut rng = Rng::new(33);
+        for _ in 0..20 {
+            // Enough nodes to span several indexed blocks.
+            let n = 1 + rng.below(3 * BLOCK);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&format!("node{}", i));
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                let cost = rng.below(1000) as Weight - 100;
+                if rng.chance(0.5) {
+                    graph.add_edge(a, b, cost);
+                } else {
+                    graph.add_bidirectional_edge(a, b, cost);
+                }
+            }
+            let compressed = CompressedGraph::from_bytes(compress(&graph)).unwrap();
+            assert_eq!(compressed.node_count(), n);
+            for node in graph.node_ids() {
+                let name = graph.get_node_name(node).unwrap();
+                assert_eq!(compressed.get_node_name(node), Some(name));
+                assert_eq!(compressed.get_node(name), Some(node));
+                let mut expected: Vec<(NodeId, Weight)> = graph
+                    .edges_of(node)
+                    .iter()
+                    .map(|e| (e.node, e.cost))
+                    .collect();
+                expected.sort_unstable();
+                let mut neighbors = compressed.neighbors(node).unwrap();
+                neighbors.sort_unstable();
+                assert_eq!(neighbors, expected);
+            }
+            assert_eq!(compressed.get_node("missing"), None);
+            assert_eq!(compressed.get_node_name(NodeId::new(n)), None);
+            assert_eq!(edge_set(&compressed.to_graph().unwrap()), edge_set(&graph));
+
+            let positive = graph.map_weights(|_, cost| cost.abs());
+            let compressed = CompressedGraph::from_bytes(compress(&positive)).unwrap();
+            let start = NodeId::new(rng.below(n));
+            assert_eq!(
+                compressed.shortest_path_tree(start).unwrap().distances(),
+                shortest_path_tree(&positive, start).distances()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_damaged_data() {
+        let graph: Graph = "a b 1\nb c -2 ->\nc c 3".parse().unwrap();
+        let data = compress(&graph);
+        assert_eq!(
+            CompressedGraph::from_bytes(b"XSG\x01".to_vec())
+                .unwrap_err()
+                .offset(),
+            0
+        );
+        for len in 0..data.len() {
+            assert!(CompressedGraph::from_bytes(data[..len].to_vec()).is_err());
+        }
+        let mut longer = data.clone();
+        longer.push(0);
+        assert!(CompressedGraph::from_bytes(longer).is_err());
+
+        for value in [0, 1, -1, 63, -64, i64::MAX, i64::MIN] {
+            let mut bytes = Vec::new();
+            put_varint(&mut bytes, zigzag(value));
+            let mut pos = 0;
+            assert_eq!(unzigzag(get_varint(&bytes, &mut pos).unwrap()), value);
+            assert_eq!(pos, bytes.len());
+        }
+        assert!(get_varint(&[0x80, 0x80], &mut 0).is_err());
+    }
+}
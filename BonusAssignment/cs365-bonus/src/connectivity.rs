@@ -0,0 +1,473 @@
+use crate::disjoint_set::DisjointSet;
+use crate::graph::{Graph, NodeId};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// One line of a connectivity script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `add a b [cost]` inserts an edge, creating unknown nodes. The cost is ignored.
+    Add(String, String),
+    /// `remove a b` deletes one edge between the nodes. Only supported offline.
+    Remove(String, String),
+    /// `connected a b` asks whether a path joins the two nodes.
+    Connected(String, String),
+    /// `components` asks for the number of connected components.
+    Components,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["add", a, b] | ["add", a, b, _] => Ok(Command::Add(a.to_string(), b.to_string())),
+            ["remove", a, b] => Ok(Command::Remove(a.to_string(), b.to_string())),
+            ["connected", a, b] => Ok(Command::Connected(a.to_string(), b.to_string())),
+            ["components"] => Ok(Command::Components),
+            _ => Err(format!("unrecognized command '{}'", line.trim())),
+        }
+    }
+}
+
+/// The nodes of `graph` grouped by connected component, ignoring edge direction. Each
+/// component and the list of components are in increasing node order.
+pub fn connected_components(graph: &Graph) -> Vec<Vec<NodeId>> {
+    let mut sets = DisjointSet::new(graph.node_count());
+    for (_, src, dest, _) in graph.edges() {
+        sets.union(src.index(), dest.index());
+    }
+    sets.sets()
+        .into_iter()
+        .map(|set| set.into_iter().map(NodeId::new).collect())
+        .collect()
+}
+
+/// Connectivity of a graph that only grows, answering queries as edges arrive.
+#[derive(Debug, Clone)]
+pub struct IncrementalConnectivity {
+    names: HashMap<String, usize>,
+    sets: DisjointSet,
+}
+
+impl IncrementalConnectivity {
+    pub fn new() -> Self {
+        Self {
+            names: HashMap::new(),
+            sets: DisjointSet::new(0),
+        }
+    }
+
+    /// Start from the nodes and edges of `graph`, ignoring direction.
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut connectivity = Self::new();
+        for node in graph.node_ids() {
+            connectivity.node(graph.get_node_name(node).unwrap_or(""));
+        }
+        for (_, src, dest, _) in graph.edges() {
+            connectivity.sets.union(src.index(), dest.index());
+        }
+        connectivity
+    }
+
+    /// The element for `name`, adding it as an isolated node if it is new.
+    fn node(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.names.get(name) {
+            return id;
+        }
+        let id = self.sets.push();
+        self.names.insert(name.to_string(), id);
+        id
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.sets.len()
+    }
+
+    /// Insert an edge between `a` and `b`, returning whether it merged two components.
+    pub fn add_edge(&mut self, a: &str, b: &str) -> bool {
+        let (a, b) = (self.node(a), self.node(b));
+        self.sets.union(a, b)
+    }
+
+    /// Whether `a` and `b` are joined by a path. Unknown nodes are isolated, so they are
+    /// only connected to themselves.
+    pub fn connected(&mut self, a: &str, b: &str) -> bool {
+        match (self.names.get(a), self.names.get(b)) {
+            (Some(&a), Some(&b)) => self.sets.connected(a, b),
+            _ => a == b,
+        }
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.sets.set_count()
+    }
+}
+
+impl Default for IncrementalConnectivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The answer to a query command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    Connected(bool),
+    Components(usize),
+}
+
+/// A `remove` command that named an edge which wasn't present.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingEdge {
+    /// The index of the offending command.
+    pub command: usize,
+}
+
+impl std::fmt::Display for MissingEdge {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "command {} removes an edge that doesn't exist",
+            self.command + 1
+        )
+    }
+}
+
+impl std::error::Error for MissingEdge {}
+
+/// Union-find without path compression, so unions can be undone in reverse order.
+struct RollbackSets {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    sets: usize,
+    history: Vec<usize>,
+}
+
+impl RollbackSets {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            sets: len,
+            history: Vec::new(),
+        }
+    }
+
+    fn find(&self, mut element: usize) -> usize {
+        while self.parent[element] != element {
+            element = self.parent[element];
+        }
+        element
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.sets -= 1;
+        self.history.push(b);
+    }
+
+    /// Undo unions until only `len` remain in the history.
+    fn rollback(&mut self, len: usize) {
+        while self.history.len() > len {
+            let b = self.history.pop().unwrap();
+            let a = self.parent[b];
+            self.parent[b] = b;
+            self.size[a] -= self.size[b];
+            self.sets += 1;
+        }
+    }
+}
+
+/// Answer a script that also deletes edges, starting from `initial`.
+///
+/// Each edge is alive for an interval of queries. The intervals are stored in a segment
+/// tree over query time, and a depth-first walk of the tree applies each edge on entry
+/// and rolls it back on exit, so every leaf sees exactly the edges alive at that query.
+/// This takes O((m + q) log q log n) time for m edge events and q queries.
+pub fn offline_connectivity(
+    initial: &Graph,
+    commands: &[Command],
+) -> Result<Vec<Answer>, MissingEdge> {
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    // The number of queries answered before each node first appeared.
+    let mut born = Vec::new();
+    for node in initial.node_ids() {
+        intern(
+            &mut names,
+            &mut born,
+            initial.get_node_name(node).unwrap_or(""),
+            0,
+        );
+    }
+
+    // Interval of query times during which each edge is alive, and for every pair, the
+    // start times of its copies that are still open.
+    let mut intervals: Vec<(usize, usize, (usize, usize))> = Vec::new();
+    let mut open: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    let pair = |a: usize, b: usize| (a.min(b), a.max(b));
+    for (_, src, dest, _) in initial.edges() {
+        open.entry(pair(src.index(), dest.index()))
+            .or_default()
+            .push(0);
+    }
+
+    let mut queries = Vec::new();
+    for (index, command) in commands.iter().enumerate() {
+        let time = queries.len();
+        match command {
+            Command::Add(a, b) => {
+                let a = intern(&mut names, &mut born, a, time);
+                let b = intern(&mut names, &mut born, b, time);
+                open.entry(pair(a, b)).or_default().push(time);
+            }
+            Command::Remove(a, b) => {
+                let missing = MissingEdge { command: index };
+                let key = match (names.get(a.as_str()), names.get(b.as_str())) {
+                    (Some(&a), Some(&b)) => pair(a, b),
+                    _ => return Err(missing),
+                };
+                let start = open
+                    .get_mut(&key)
+                    .and_then(|starts| starts.pop())
+                    .ok_or(missing)?;
+                intervals.push((start, time, key));
+            }
+            Command::Connected(a, b) => {
+                queries.push(match (names.get(a.as_str()), names.get(b.as_str())) {
+                    (Some(&a), Some(&b)) => Query::Pair(a, b),
+                    _ => Query::Fixed(a == b),
+                });
+            }
+            Command::Components => queries.push(Query::Components),
+        }
+    }
+    for (key, starts) in open {
+        for start in starts {
+            intervals.push((start, queries.len(), key));
+        }
+    }
+
+    let q = queries.len();
+    if q == 0 {
+        return Ok(Vec::new());
+    }
+    let mut tree: Vec<Vec<(usize, usize)>> = vec![Vec::new(); 4 * q];
+    for (start, end, key) in intervals {
+        if start < end {
+            insert(&mut tree, 1, 0, q, start, end, key);
+        }
+    }
+
+    // Nodes that don't exist yet are still singletons in the sets, so they are
+    // subtracted from the component count.
+    let mut unborn = vec![0; q + 1];
+    for &time in born.iter() {
+        unborn[time] += 1;
+    }
+    let mut later = 0;
+    for count in unborn.iter_mut().rev() {
+        let here = *count;
+        *count = later;
+        later += here;
+    }
+
+    let mut sets = RollbackSets::new(names.len());
+    let mut answers = Vec::with_capacity(q);
+    let mut walker = Walker {
+        tree: &tree,
+        queries: &queries,
+        unborn: &unborn,
+        sets: &mut sets,
+        answers: &mut answers,
+    };
+    walker.walk(1, 0, q);
+    Ok(answers)
+}
+
+enum Query {
+    Pair(usize, usize),
+    Fixed(bool),
+    Components,
+}
+
+fn intern<'a>(
+    names: &mut HashMap<&'a str, usize>,
+    born: &mut Vec<usize>,
+    name: &'a str,
+    time: usize,
+) -> usize {
+    let next = names.len();
+    *names.entry(name).or_insert_with(|| {
+        born.push(time);
+        next
+    })
+}
+
+/// Store `edge` in the nodes covering `[start, end)`, where node `node` spans `[lo, hi)`.
+fn insert(
+    tree: &mut [Vec<(usize, usize)>],
+    node: usize,
+    lo: usize,
+    hi: usize,
+    start: usize,
+    end: usize,
+    edge: (usize, usize),
+) {
+    if end <= lo || hi <= start {
+        return;
+    }
+    if start <= lo && hi <= end {
+        tree[node].push(edge);
+        return;
+    }
+    let mid = (lo + hi) / 2;
+    insert(tree, 2 * node, lo, mid, start, end, edge);
+    insert(tree, 2 * node + 1, mid, hi, start, end, edge);
+}
+
+struct Walker<'a> {
+    tree: &'a [Vec<(usize, usize)>],
+    queries: &'a [Query],
+    unborn: &'a [usize],
+    sets: &'a mut RollbackSets,
+    answers: &'a mut Vec<Answer>,
+}
+
+impl Walker<'_> {
+    /// Answer the queries in `[lo, hi)`, the span of tree node `node`.
+    fn walk(&mut self, node: usize, lo: usize, hi: usize) {
+        let checkpoint = self.sets.history.len();
+        for &(a, b) in self.tree[node].iter() {
+            self.sets.union(a, b);
+        }
+        if hi - lo == 1 {
+            self.answers.push(match self.queries[lo] {
+                Query::Pair(a, b) => Answer::Connected(self.sets.find(a) == self.sets.find(b)),
+                Query::Fixed(answer) => Answer::Connected(answer),
+                Query::Components => Answer::Components(self.sets.sets - self.unborn[lo]),
+            });
+        } else {
+            let mid = (lo + hi) / 2;
+            self.walk(2 * node, lo, mid);
+            self.walk(2 * node + 1, mid, hi);
+        }
+        self.sets.rollback(checkpoint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Whether `a` and `b` are in the same component of `graph`, by name.
+    fn joined(graph: &Graph, a: &str, b: &str) -> bool {
+        match (graph.get_node(a), graph.get_node(b)) {
+            (Some(a), Some(b)) => graph
+                .connected_components()
+                .iter()
+                .any(|component| component.contains(&a) && component.contains(&b)),
+            _ => a == b,
+        }
+    }
+
+    #[test]
+    fn incremental_matches_components() {
+        let mut rng = Rng::new(13);
+        for _ in 0..30 {
+            let mut graph: Graph = "0 1 1".parse().unwrap();
+            let mut connectivity = IncrementalConnectivity::from_graph(&graph);
+            for _ in 0..30 {
+                let (a, b) = (rng.below(12).to_string(), rng.below(12).to_string());
+                if rng.chance(0.5) {
+                    let before = graph.connected_components().len();
+                    let (src, dest) = (graph.get_or_insert_node(&a), graph.get_or_insert_node(&b));
+                    graph.add_bidirectional_edge(src, dest, 1);
+                    // Two new nodes form one component, not a merge of two.
+                    let new = graph.node_count() - connectivity.node_count();
+                    let merged = connectivity.add_edge(&a, &b);
+                    assert_eq!(
+                        merged,
+                        before + new == graph.connected_components().len() + 1
+                    );
+                } else {
+                    assert_eq!(connectivity.connected(&a, &b), joined(&graph, &a, &b));
+                }
+                assert_eq!(connectivity.node_count(), graph.node_count());
+                let components = graph.connected_components().len();
+                assert_eq!(connectivity.component_count(), components);
+            }
+        }
+    }
+
+    #[test]
+    fn offline_matches_replaying_the_script() {
+        let mut rng = Rng::new(14);
+        for _ in 0..30 {
+            let initial: Graph = "0 1 1\n1 2 1".parse().unwrap();
+            let mut graph = initial.clone();
+            let mut edges: Vec<(String, String)> =
+                vec![("0".into(), "1".into()), ("1".into(), "2".into())];
+            let mut commands = Vec::new();
+            let mut expected = Vec::new();
+            for _ in 0..40 {
+                let (a, b) = (rng.below(8).to_string(), rng.below(8).to_string());
+                let command = match rng.below(4) {
+                    0 if !edges.is_empty() => {
+                        let (a, b) = edges.swap_remove(rng.below(edges.len()));
+                        Command::Remove(a, b)
+                    }
+                    0 | 1 => {
+                        edges.push((a.clone(), b.clone()));
+                        Command::Add(a, b)
+                    }
+                    2 => Command::Connected(a, b),
+                    _ => Command::Components,
+                };
+                // Rebuild the graph from the edges alive now, keeping every node seen.
+                if let Command::Add(a, b) = &command {
+                    graph.get_or_insert_node(a);
+                    graph.get_or_insert_node(b);
+                }
+                let mut alive = graph.edge_subgraph(|_| false);
+                for (a, b) in &edges {
+                    let (a, b) = (alive.get_node(a).unwrap(), alive.get_node(b).unwrap());
+                    alive.add_bidirectional_edge(a, b, 1);
+                }
+                match &command {
+                    Command::Connected(a, b) => {
+                        expected.push(Answer::Connected(joined(&alive, a, b)));
+                    }
+                    Command::Components => {
+                        expected.push(Answer::Components(alive.connected_components().len()));
+                    }
+                    _ => {}
+                }
+                commands.push(command);
+            }
+            assert_eq!(offline_connectivity(&initial, &commands), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn removing_a_missing_edge_fails() {
+        let initial: Graph = "a b 1".parse().unwrap();
+        let commands: Vec<Command> = ["remove a b", "components", "remove a b"]
+            .iter()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        let error = offline_connectivity(&initial, &commands).unwrap_err();
+        assert_eq!(error.command, 2);
+        assert!("delete a b".parse::<Command>().is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::path::{shortest_path_tree, Path};
+
+/// The cheapest walk from `start` to `end` that traverses `edge`, in either direction if
+/// it is undirected.
+///
+/// Combines distances from `start` with distances to `end`, found by searching the
+/// reversed graph, around each way of crossing the edge. The result may revisit nodes,
+/// since the required edge can lie off every simple shortest path.
+pub fn shortest_path_through_edge(
+    graph: &Graph,
+    start: NodeId,
+    end: NodeId,
+    edge: EdgeId,
+) -> Option<Path> {
+    let (src, dest) = graph.edge_endpoints(edge)?;
+    let cost = graph.edge_weight(edge)?;
+    let forward = shortest_path_tree(graph, start);
+    let reversed = graph.reversed();
+    let backward = shortest_path_tree(&reversed, end);
+
+    let mut crossings = vec![(src, dest)];
+    if !graph.is_edge_directed(edge)? {
+        crossings.push((dest, src));
+    }
+    let (from, to, total) = crossings
+        .into_iter()
+        .filter_map(|(from, to)| {
+            let total = forward.distance(from)? + cost + backward.distance(to)?;
+            Some((from, to, total))
+        })
+        .min_by_key(|&(_, _, total)| total)?;
+
+    let mut path = forward.path_to(from)?;
+    let tail = backward.path_to(to)?;
+    for &node in tail.nodes.iter().rev() {
+        path.nodes.push(node);
+        path.distance.push(total - backward.distance(node)?);
+    }
+    path.cost = total;
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Weight;
+    use crate::rng::Rng;
+
+    #[test]
+    fn walks_match_floyd_warshall_around_the_edge() {
+        let mut rng = Rng::new(21);
+        for _ in 0..30 {
+            let n = 1 + rng.below(8);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                let cost = rng.below(10) as Weight;
+                if rng.chance(0.5) {
+                    graph.add_edge(a, b, cost);
+                } else {
+                    graph.add_bidirectional_edge(a, b, cost);
+                }
+            }
+            let mut dist = vec![vec![None; n]; n];
+            for (i, row) in dist.iter_mut().enumerate() {
+                row[i] = Some(0);
+                for edge in graph.edges_of(NodeId::new(i)) {
+                    let d = &mut row[edge.node.index()];
+                    *d = Some(d.map_or(edge.cost, |d: Weight| d.min(edge.cost)));
+                }
+            }
+            for k in 0..n {
+                for i in 0..n {
+                    for j in 0..n {
+                        if let (Some(a), Some(b)) = (dist[i][k], dist[k][j]) {
+                            dist[i][j] = Some(dist[i][j].map_or(a + b, |d: Weight| d.min(a + b)));
+                        }
+                    }
+                }
+            }
+
+            for (id, src, dest, cost) in graph.edges().collect::<Vec<_>>() {
+                let mut crossings = vec![(src, dest)];
+                if graph.is_edge_directed(id) == Some(false) {
+                    crossings.push((dest, src));
+                }
+                for start in graph.node_ids() {
+                    for end in graph.node_ids() {
+                        let expected = crossings
+                            .iter()
+                            .filter_map(|&(from, to)| {
+                                Some(
+                                    dist[start.index()][from.index()]?
+                                        + cost
+                                        + dist[to.index()][end.index()]?,
+                                )
+                            })
+                            .min();
+                        let path = shortest_path_through_edge(&graph, start, end, id);
+                        assert_eq!(path.as_ref().map(|p| p.cost), expected);
+                        let path = match path {
+                            Some(path) => path,
+                            None => continue,
+                        };
+                        assert_eq!(path.nodes.first(), Some(&start));
+                        assert_eq!(path.nodes.last(), Some(&end));
+                        assert_eq!(path.distance.last(), Some(&path.cost));
+                        for i in 1..path.nodes.len() {
+                            let step = path.distance[i] - path.distance[i - 1];
+                            assert!(graph
+                                .edges_of(path.nodes[i - 1])
+                                .iter()
+                                .any(|e| e.node == path.nodes[i] && e.cost == step));
+                        }
+                        assert!(path
+                            .nodes
+                            .windows(2)
+                            .any(|pair| crossings.contains(&(pair[0], pair[1]))));
+                    }
+                }
+            }
+        }
+    }
+}
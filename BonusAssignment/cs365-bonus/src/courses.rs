@@ -0,0 +1,239 @@
+use crate::dag::topological_order;
+use crate::graph::{Graph, NodeId};
+
+/// Courses, their credits, and the prerequisites each one needs finished first.
+///
+/// ```
+/// use cs365_bonus::courses::Catalog;
+///
+/// let catalog = Catalog::parse("intro 4\ndata 4 intro\nalgo 4 data\nethics 3\ndb 3 data").unwrap();
+/// assert_eq!(catalog.min_semesters(), Ok(3));
+/// // Seven credits a semester still fit everything into three semesters.
+/// let plan = catalog.schedule(7).unwrap();
+/// assert_eq!(plan.len(), 3);
+/// assert_eq!(catalog.names(&plan[0]), ["intro", "ethics"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    /// An edge from each prerequisite to each course needing it.
+    graph: Graph,
+    credits: Vec<u32>,
+}
+
+impl Catalog {
+    /// Read one course per line as its name, its credits, and the names of its
+    /// prerequisites. Every prerequisite needs a line of its own. Blank lines and lines
+    /// starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut graph = Graph::new();
+        let mut credits: Vec<Option<u32>> = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (name, value, prerequisites) = match words.as_slice() {
+                [name, value, prerequisites @ ..] => (name, value, prerequisites),
+                _ => return Err(format!("line {}: expected 'course credits ...'", i + 1)),
+            };
+            let value = value
+                .parse()
+                .map_err(|_| format!("line {}: invalid credits '{}'", i + 1, value))?;
+            let course = graph.get_or_insert_node(name);
+            credits.resize(graph.node_count(), None);
+            if credits[course.index()].replace(value).is_some() {
+                return Err(format!("line {}: '{}' is listed twice", i + 1, name));
+            }
+            for prerequisite in prerequisites {
+                let prerequisite = graph.get_or_insert_node(prerequisite);
+                graph.add_edge(prerequisite, course, 1);
+            }
+            credits.resize(graph.node_count(), None);
+        }
+        let credits = graph
+            .node_ids()
+            .map(|course| {
+                credits[course.index()].ok_or_else(|| {
+                    let name = graph.get_node_name(course).unwrap_or("");
+                    format!("prerequisite '{}' has no line of its own", name)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { graph, credits })
+    }
+
+    /// The graph with an edge from each prerequisite to each course needing it.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn credits(&self, course: NodeId) -> u32 {
+        self.credits[course.index()]
+    }
+
+    pub fn names(&self, courses: &[NodeId]) -> Vec<&str> {
+        courses
+            .iter()
+            .map(|&course| self.graph.get_node_name(course).unwrap_or(""))
+            .collect()
+    }
+
+    /// The number of courses on the longest chain of prerequisites, which is the fewest
+    /// semesters any schedule can take without a credit limit.
+    pub fn min_semesters(&self) -> Result<usize, String> {
+        let order = self.order()?;
+        let mut depth = vec![1; self.graph.node_count()];
+        for &course in &order {
+            for edge in self.graph.edges_of(course) {
+                depth[edge.node.index()] = depth[edge.node.index()].max(depth[course.index()] + 1);
+            }
+        }
+        Ok(depth.into_iter().max().unwrap_or(0))
+    }
+
+    /// Courses to take each semester so every prerequisite is finished in an earlier
+    /// semester and no semester carries more than `cap` credits.
+    ///
+    /// Each semester is filled from the courses that are ready, those heading the longest
+    /// chain of later courses first, skipping any that no longer fit. Limiting credits makes
+    /// the fewest semesters NP-hard to find, so this may take more than the minimum, though
+    /// it does reach [`min_semesters`](Self::min_semesters) when the cap never binds.
+    pub fn schedule(&self, cap: u32) -> Result<Vec<Vec<NodeId>>, String> {
+        if let Some(course) = self.graph.node_ids().find(|&c| self.credits(c) > cap) {
+            return Err(format!(
+                "'{}' alone is worth more than {} credits",
+                self.names(&[course])[0],
+                cap
+            ));
+        }
+        let order = self.order()?;
+        let mut height = vec![1usize; self.graph.node_count()];
+        for &course in order.iter().rev() {
+            for edge in self.graph.edges_of(course) {
+                height[course.index()] = height[course.index()].max(height[edge.node.index()] + 1);
+            }
+        }
+        let mut waiting: Vec<usize> = vec![0; self.graph.node_count()];
+        for (_, _, course, _) in self.graph.edges() {
+            waiting[course.index()] += 1;
+        }
+
+        let mut ready: Vec<NodeId> = self
+            .graph
+            .node_ids()
+            .filter(|course| waiting[course.index()] == 0)
+            .collect();
+        let mut semesters = Vec::new();
+        while !ready.is_empty() {
+            ready.sort_by_key(|course| (std::cmp::Reverse(height[course.index()]), *course));
+            let mut load = 0u64;
+            let (taken, left): (Vec<NodeId>, Vec<NodeId>) =
+                ready.into_iter().partition(|&course| {
+                    let fits = load + u64::from(self.credits(course)) <= u64::from(cap);
+                    if fits {
+                        load += u64::from(self.credits(course));
+                    }
+                    fits
+                });
+            ready = left;
+            for &course in &taken {
+                for edge in self.graph.edges_of(course) {
+                    waiting[edge.node.index()] -= 1;
+                    if waiting[edge.node.index()] == 0 {
+                        ready.push(edge.node);
+                    }
+                }
+            }
+            semesters.push(taken);
+        }
+        Ok(semesters)
+    }
+
+    fn order(&self) -> Result<Vec<NodeId>, String> {
+        topological_order(&self.graph).ok_or_else(|| "the prerequisites form a cycle".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// The number of courses on the longest chain ending at `course`.
+    fn chain(catalog: &Catalog, course: NodeId) -> usize {
+        let graph = catalog.graph();
+        let before = graph.edges().filter(|&(_, _, after, _)| after == course);
+        1 + before
+            .map(|(_, prerequisite, ..)| chain(catalog, prerequisite))
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn schedules_respect_prerequisites_and_caps() {
+        let mut rng = Rng::new(48);
+        for _ in 0..200 {
+            let n = 1 + rng.below(10);
+            let mut text = String::new();
+            for course in 0..n {
+                text += &format!("c{} {}", course, 1 + rng.below(5));
+                // Prerequisites come earlier, so the catalog is acyclic.
+                for _ in 0..rng.below(course.min(3) + 1) {
+                    text += &format!(" c{}", rng.below(course.max(1)));
+                }
+                text += "\n";
+            }
+            let catalog = Catalog::parse(&text).unwrap();
+            let graph = catalog.graph();
+            let longest = graph.node_ids().map(|c| chain(&catalog, c)).max();
+            assert_eq!(catalog.min_semesters(), Ok(longest.unwrap()));
+
+            let total: u32 = graph.node_ids().map(|c| catalog.credits(c)).sum();
+            let cap = 5 + rng.below(10) as u32;
+            for cap in [cap, total] {
+                let plan = catalog.schedule(cap).unwrap();
+                let mut semester = vec![usize::MAX; n];
+                for (i, courses) in plan.iter().enumerate() {
+                    assert!(!courses.is_empty());
+                    let load: u32 = courses.iter().map(|&c| catalog.credits(c)).sum();
+                    assert!(load <= cap, "{} credits over a cap of {}", load, cap);
+                    for &course in courses {
+                        assert_eq!(semester[course.index()], usize::MAX);
+                        semester[course.index()] = i;
+                    }
+                }
+                for (_, prerequisite, course, _) in graph.edges() {
+                    assert!(semester[prerequisite.index()] < semester[course.index()]);
+                }
+                if cap == total {
+                    assert_eq!(Ok(plan.len()), catalog.min_semesters());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_plans() {
+        let cyclic = Catalog::parse("a 3 c\nb 3 a\nc 3 b\nd 3").unwrap();
+        let cycle = "the prerequisites form a cycle";
+        assert_eq!(cyclic.min_semesters().unwrap_err(), cycle);
+        assert_eq!(cyclic.schedule(10).unwrap_err(), cycle);
+
+        let heavy = Catalog::parse("a 3\nthesis 12 a").unwrap();
+        assert_eq!(
+            heavy.schedule(10),
+            Err("'thesis' alone is worth more than 10 credits".to_string())
+        );
+
+        let errors = [
+            ("a 3 b", "prerequisite 'b' has no line of its own"),
+            ("a 3\na 4", "line 2: 'a' is listed twice"),
+            ("\na three", "line 2: invalid credits 'three'"),
+            ("a", "line 1: expected 'course credits ...'"),
+        ];
+        for (text, error) in errors {
+            assert_eq!(Catalog::parse(text).unwrap_err(), error);
+        }
+    }
+}
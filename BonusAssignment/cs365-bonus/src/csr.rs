@@ -0,0 +1,91 @@
+use crate::graph::{Edge, Graph, GraphRef, NodeId};
+use crate::path::{shortest_path_tree, ShortestPathTree};
+
+/// A frozen compressed sparse row copy of a graph's adjacency lists.
+///
+/// Every edge lives in one contiguous array, so scanning consecutive nodes touches
+/// consecutive memory. Pair it with [`reorder`](crate::reorder) to make neighbors
+/// likely to be stored close together as well.
+#[derive(Debug, Clone)]
+pub struct Csr {
+    offsets: Vec<usize>,
+    edges: Vec<Edge>,
+}
+
+impl Csr {
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut offsets = Vec::with_capacity(graph.node_count() + 1);
+        let mut edges = Vec::new();
+        offsets.push(0);
+        for node in graph.node_ids() {
+            edges.extend_from_slice(graph.edges_of(node));
+            offsets.push(edges.len());
+        }
+        Self { offsets, edges }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn edges_of(&self, node: NodeId) -> &[Edge] {
+        &self.edges[self.offsets[node.index()]..self.offsets[node.index() + 1]]
+    }
+
+    /// Compute shortest paths from `start` to every reachable node.
+    pub fn shortest_path_tree(&self, start: NodeId) -> ShortestPathTree {
+        shortest_path_tree(self, start)
+    }
+}
+
+impl GraphRef for Csr {
+    fn node_count(&self) -> usize {
+        Csr::node_count(self)
+    }
+
+    fn edges_of(&self, node: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        Csr::edges_of(self, node).iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{EdgeId, Weight};
+    use crate::rng::Rng;
+
+    #[test]
+    fn matches_the_graph() {
+        let mut rng = Rng::new(36);
+        for _ in 0..50 {
+            let n = 1 + rng.below(12);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                let cost = rng.below(10) as Weight;
+                if rng.chance(0.5) {
+                    graph.add_edge(a, b, cost);
+                } else {
+                    graph.add_bidirectional_edge(a, b, cost);
+                }
+            }
+            let csr = Csr::from_graph(&graph);
+            assert_eq!(csr.node_count(), n);
+            for node in graph.node_ids() {
+                let fields = |edges: &[Edge]| -> Vec<(NodeId, Weight, EdgeId)> {
+                    edges.iter().map(|e| (e.node, e.cost, e.id)).collect()
+                };
+                assert_eq!(fields(csr.edges_of(node)), fields(graph.edges_of(node)));
+                let tree = csr.shortest_path_tree(node);
+                assert_eq!(
+                    tree.distances(),
+                    shortest_path_tree(&graph, node).distances()
+                );
+            }
+        }
+        assert_eq!(Csr::from_graph(&Graph::new()).node_count(), 0);
+    }
+}
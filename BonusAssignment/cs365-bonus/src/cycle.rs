@@ -0,0 +1,278 @@
+use crate::disjoint_set::DisjointSet;
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use crate::path::{dijkstra, shortest_path_tree, Path};
+
+/// Find the cheapest cycle that passes through `node`.
+///
+/// Each edge leaving `node` is tried as the first step, followed by the shortest way
+/// back that doesn't use that same edge, so walking an undirected edge there and back
+/// never counts as a cycle. The ways back are found with Dijkstra's algorithm, so no cost
+/// may be negative.
+pub fn shortest_cycle_through(graph: &Graph, node: NodeId) -> Result<Option<Path>, String> {
+    if graph.edges().any(|(.., cost)| cost < 0) {
+        return Err("a shortest cycle can't be found with negative costs".into());
+    }
+    Ok(cycle_through(graph, node, None, &[]))
+}
+
+/// Find the globally cheapest cycle, returning its weight and node sequence.
+///
+/// Nodes are processed in order, and each search ignores nodes that were already
+/// processed, since every cycle through them has been considered. Searches are also
+/// cut off at the cost of the best cycle found so far. As for [`shortest_cycle_through`],
+/// no cost may be negative.
+pub fn girth(graph: &Graph) -> Result<Option<Path>, String> {
+    if graph.edges().any(|(.., cost)| cost < 0) {
+        return Err("the girth can't be found with negative costs".into());
+    }
+    let mut best: Option<Path> = None;
+    let mut done = vec![false; graph.node_count()];
+    for node in graph.node_ids() {
+        let bound = best.as_ref().map(|best| best.cost);
+        if let Some(cycle) = cycle_through(graph, node, bound, &done) {
+            best = Some(cycle);
+        }
+        done[node.index()] = true;
+    }
+    Ok(best)
+}
+
+/// A minimum cycle basis: the cheapest set of cycles, by total cost, from which every
+/// cycle of the graph can be built by taking the symmetric difference of their edges.
+///
+/// This is Horton's algorithm. For each node and edge, the cycle running from the node
+/// along its shortest paths to both ends of the edge is a candidate, and the candidates
+/// are taken cheapest first whenever their edges are independent, over GF(2), of those
+/// already taken, until there are `m - n + c` of them for `m` edges, `n` nodes and `c`
+/// components. Each cycle starts and ends at the node it was found from. Every edge must be
+/// undirected and no cost negative.
+///
+/// ```
+/// use cs365_bonus::cycle::minimum_cycle_basis;
+///
+/// // A square with a diagonal: its two triangles, rather than the square and a triangle.
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1\nc d 1\nd a 1\na c 1".parse().unwrap();
+/// let basis = minimum_cycle_basis(&graph).unwrap();
+/// let costs: Vec<_> = basis.iter().map(|cycle| cycle.cost).collect();
+/// assert_eq!(costs, [3, 3]);
+/// ```
+pub fn minimum_cycle_basis(graph: &Graph) -> Result<Vec<Path>, String> {
+    if graph
+        .edges()
+        .any(|(id, ..)| graph.is_edge_directed(id) == Some(true))
+    {
+        return Err("a cycle basis needs every edge to be undirected".into());
+    }
+    if graph.edges().any(|(.., cost)| cost < 0) {
+        return Err("a cycle basis can't be found with negative costs".into());
+    }
+    let mut components = DisjointSet::new(graph.node_count());
+    for (_, src, dest, _) in graph.edges() {
+        components.union(src.index(), dest.index());
+    }
+    let dimension = graph.edge_count() + components.set_count() - graph.node_count();
+
+    // Each candidate is a cost, the node it runs from, and the edge it closes with.
+    let mut candidates: Vec<(Weight, NodeId, EdgeId, NodeId, NodeId)> = Vec::new();
+    let mut trees = Vec::with_capacity(graph.node_count());
+    for root in graph.node_ids() {
+        let tree = shortest_path_tree(graph, root);
+        // The edge each node is reached by, and the first node after the root on the way.
+        let mut parent_edge = vec![None; graph.node_count()];
+        let mut branch = vec![None; graph.node_count()];
+        let mut order: Vec<NodeId> = graph
+            .node_ids()
+            .filter(|&node| tree.distance(node).is_some())
+            .collect();
+        order.sort_by_key(|&node| (tree.distance(node), node.index()));
+        for &node in &order {
+            let parent = match tree.parent(node) {
+                Some(parent) if node != root => parent,
+                _ => continue,
+            };
+            let step = tree
+                .distance(node)
+                .zip(tree.distance(parent))
+                .map(|(a, b)| a - b);
+            parent_edge[node.index()] = graph
+                .edges_of(parent)
+                .iter()
+                .find(|edge| edge.node == node && Some(edge.cost) == step)
+                .map(|edge| edge.id);
+            branch[node.index()] = if parent == root {
+                Some(node)
+            } else {
+                branch[parent.index()]
+            };
+        }
+        for (id, x, y, cost) in graph.edges() {
+            let (dx, dy) = match (tree.distance(x), tree.distance(y)) {
+                (Some(dx), Some(dy)) => (dx, dy),
+                _ => continue,
+            };
+            let closes = if x == y {
+                x == root
+            } else {
+                let in_tree =
+                    parent_edge[x.index()] == Some(id) || parent_edge[y.index()] == Some(id);
+                // The two paths may only meet at the root.
+                let apart = x == root || y == root || branch[x.index()] != branch[y.index()];
+                !in_tree && apart
+            };
+            if closes {
+                candidates.push((dx + cost + dy, root, id, x, y));
+            }
+        }
+        trees.push((tree, parent_edge));
+    }
+    candidates.sort_by_key(|&(cost, root, id, ..)| (cost, root.index(), id.index()));
+
+    let words = graph.edge_count().div_ceil(64);
+    // Rows of the eliminated basis, each with the lowest edge it holds.
+    let mut rows: Vec<(usize, Vec<u64>)> = Vec::new();
+    let mut basis = Vec::new();
+    for (cost, root, id, x, y) in candidates {
+        if basis.len() == dimension {
+            break;
+        }
+        let (tree, parent_edge) = &trees[root.index()];
+        let mut edges = vec![0u64; words];
+        let mut toggle = |edge: EdgeId| edges[edge.index() / 64] ^= 1 << (edge.index() % 64);
+        toggle(id);
+        for end in [x, y] {
+            let mut node = end;
+            while let Some(edge) = parent_edge[node.index()] {
+                toggle(edge);
+                node = tree.parent(node).expect("a tree edge has a parent");
+            }
+        }
+        let mut reduced = edges;
+        for (pivot, row) in &rows {
+            if reduced[pivot / 64] >> (pivot % 64) & 1 == 1 {
+                for (word, bits) in reduced.iter_mut().zip(row) {
+                    *word ^= bits;
+                }
+            }
+        }
+        let pivot = match reduced.iter().position(|&word| word != 0) {
+            Some(i) => i * 64 + reduced[i].trailing_zeros() as usize,
+            None => continue,
+        };
+        rows.push((pivot, reduced));
+
+        let there = tree.path_to(x).expect("the candidate's ends are reachable");
+        let back = tree.path_to(y).expect("the candidate's ends are reachable");
+        let mut nodes = there.nodes;
+        let mut distance = there.distance;
+        if x != y {
+            for (&node, &d) in back.nodes.iter().zip(&back.distance).rev() {
+                nodes.push(node);
+                distance.push(cost - d);
+            }
+        } else {
+            // A self-loop at the root is a cycle on its own.
+            nodes.push(root);
+            distance.push(cost);
+        }
+        basis.push(Path {
+            nodes,
+            distance,
+            cost,
+        });
+    }
+    Ok(basis)
+}
+
+/// Find the cheapest cycle through `node` costing less than `bound` and avoiding the
+/// nodes marked in `excluded`.
+fn cycle_through(
+    graph: &Graph,
+    node: NodeId,
+    mut bound: Option<Weight>,
+    excluded: &[bool],
+) -> Option<Path> {
+    let is_excluded = |n: NodeId| excluded.get(n.index()).copied().unwrap_or(false);
+
+    let mut best: Option<Path> = None;
+    for first in graph.edges_of(node) {
+        if is_excluded(first.node) || bound.is_some_and(|bound| first.cost >= bound) {
+            continue;
+        }
+
+        let limit = bound.map(|bound| bound - first.cost);
+        let tree = dijkstra(graph, first.node, Some(node), limit, |_, edge| {
+            edge.id != first.id && !is_excluded(edge.node)
+        });
+        let back = match tree.path_to(node) {
+            Some(back) if first.node != node || back.nodes.len() == 1 => back,
+            _ => continue,
+        };
+        let cost = first.cost + back.cost;
+        if bound.is_some_and(|bound| cost >= bound) {
+            continue;
+        }
+
+        let mut nodes = vec![node];
+        let mut distance = vec![0];
+        if first.node != node {
+            nodes.extend(back.nodes.iter().copied());
+            distance.extend(back.distance.iter().map(|d| d + first.cost));
+        } else {
+            // A self-loop is a cycle on its own.
+            nodes.push(node);
+            distance.push(first.cost);
+        }
+        bound = Some(cost);
+        best = Some(Path {
+            nodes,
+            distance,
+            cost,
+        });
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_through_a_node_leave_by_another_edge() {
+        // A triangle with a tail: the tail's edge can't be walked there and back.
+        let graph: Graph = "a b 1\nb c 2\nc a 3\nc d 1".parse().unwrap();
+        let node = |name| graph.get_node(name).unwrap();
+        let cycle = shortest_cycle_through(&graph, node("a")).unwrap().unwrap();
+        assert_eq!(cycle.cost, 6);
+        assert_eq!(cycle.nodes.first(), cycle.nodes.last());
+        assert!(shortest_cycle_through(&graph, node("d")).unwrap().is_none());
+
+        let one_way: Graph = "a b 1 ->\nb a 2 ->".parse().unwrap();
+        let cycle = shortest_cycle_through(&one_way, NodeId::new(1)).unwrap();
+        assert_eq!(cycle.map(|cycle| cycle.cost), Some(3));
+
+        let negative: Graph = "a b 1\nb c -2\nc a 3".parse().unwrap();
+        assert!(shortest_cycle_through(&negative, NodeId::new(0)).is_err());
+    }
+
+    #[test]
+    fn girth_is_the_cheapest_cycle() {
+        // A square of cost 4 beside a triangle of cost 6, joined by a bridge.
+        let graph: Graph = "a b 1\nb c 1\nc d 1\nd a 1\nd x 5\nx y 2\ny z 2\nz x 2"
+            .parse()
+            .unwrap();
+        let cycle = girth(&graph).unwrap().unwrap();
+        assert_eq!((cycle.cost, cycle.nodes.len()), (4, 5));
+        let through = graph
+            .node_ids()
+            .filter_map(|node| shortest_cycle_through(&graph, node).unwrap())
+            .map(|cycle| cycle.cost)
+            .min();
+        assert_eq!(through, Some(4));
+
+        // A path has no cycles, even though each edge can be walked both ways.
+        let path: Graph = "a b 1\nb c 1".parse().unwrap();
+        assert!(girth(&path).unwrap().is_none());
+        let negative: Graph = "a b -1 ->\nb a 3 ->".parse().unwrap();
+        assert!(girth(&negative).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::ShortestPathTree;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::str::FromStr;
+
+/// Which paths a DAG search optimizes for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    Shortest,
+    /// The longest path, which in a task graph is the critical path.
+    Longest,
+}
+
+impl FromStr for Objective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shortest" => Ok(Objective::Shortest),
+            "longest" => Ok(Objective::Longest),
+            _ => Err(format!(
+                "unknown objective '{}', expected 'shortest' or 'longest'",
+                s
+            )),
+        }
+    }
+}
+
+/// The nodes of `graph` ordered so every edge points forward, or `None` if it has a cycle.
+///
+/// Undirected edges count as a cycle of length two. Among nodes that are ready at the same
+/// time, lower numbered nodes come first.
+pub fn topological_order(graph: &Graph) -> Option<Vec<NodeId>> {
+    let mut in_degree = vec![0usize; graph.node_count()];
+    for node in graph.node_ids() {
+        for edge in graph.edges_of(node) {
+            in_degree[edge.node.index()] += 1;
+        }
+    }
+
+    let mut ready: VecDeque<NodeId> = graph
+        .node_ids()
+        .filter(|node| in_degree[node.index()] == 0)
+        .collect();
+    let mut order = Vec::with_capacity(graph.node_count());
+    while let Some(node) = ready.pop_front() {
+        order.push(node);
+        for edge in graph.edges_of(node) {
+            in_degree[edge.node.index()] -= 1;
+            if in_degree[edge.node.index()] == 0 {
+                ready.push_back(edge.node);
+            }
+        }
+    }
+
+    if order.len() == graph.node_count() {
+        Some(order)
+    } else {
+        None
+    }
+}
+
+/// Like [`topological_order`], but among nodes that are ready at the same time, the one
+/// with the smallest `key` comes first, with ties going to the lower numbered node.
+pub fn topological_order_by_key<K: Ord>(
+    graph: &Graph,
+    key: impl Fn(NodeId) -> K,
+) -> Option<Vec<NodeId>> {
+    let mut in_degree = vec![0usize; graph.node_count()];
+    for node in graph.node_ids() {
+        for edge in graph.edges_of(node) {
+            in_degree[edge.node.index()] += 1;
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<(K, NodeId)>> = graph
+        .node_ids()
+        .filter(|node| in_degree[node.index()] == 0)
+        .map(|node| Reverse((key(node), node)))
+        .collect();
+    let mut order = Vec::with_capacity(graph.node_count());
+    while let Some(Reverse((_, node))) = ready.pop() {
+        order.push(node);
+        for edge in graph.edges_of(node) {
+            in_degree[edge.node.index()] -= 1;
+            if in_degree[edge.node.index()] == 0 {
+                ready.push(Reverse((key(edge.node), edge.node)));
+            }
+        }
+    }
+
+    (order.len() == graph.node_count()).then_some(order)
+}
+
+/// Shortest or longest paths from `start` in a directed acyclic graph, relaxing edges in
+/// topological order in linear time. Returns `None` if `graph` has a cycle.
+pub fn dag_paths(graph: &Graph, start: NodeId, objective: Objective) -> Option<ShortestPathTree> {
+    let order = topological_order(graph)?;
+    Some(relax_in_order(graph, &order, start, 0, objective))
+}
+
+/// Like [`dag_paths`], but a path is worth the total of `values` over the nodes it visits,
+/// including `start`, instead of the cost of its edges: the best route for collecting the
+/// items found at each node. Returns `None` if `graph` has a cycle.
+///
+/// ```
+/// use cs365_bonus::dag::{dag_paths_by_node_value, Objective};
+///
+/// let mut graph = cs365_bonus::Graph::new();
+/// let [s, a, b, t] = ["s", "a", "b", "t"].map(|name| graph.get_or_insert_node(name));
+/// for (u, v) in [(s, a), (s, b), (a, t), (b, t)] {
+///     graph.add_edge(u, v, 1);
+/// }
+/// let values = [1, 5, 3, 2];
+/// let tree = dag_paths_by_node_value(&graph, &values, s, Objective::Longest).unwrap();
+/// let path = tree.path_to(t).unwrap();
+/// assert_eq!((path.nodes, path.cost), (vec![s, a, t], 8));
+/// ```
+pub fn dag_paths_by_node_value(
+    graph: &Graph,
+    values: &[Weight],
+    start: NodeId,
+    objective: Objective,
+) -> Option<ShortestPathTree> {
+    let order = topological_order(graph)?;
+    let ends: Vec<NodeId> = graph.edges().map(|(_, _, dest, _)| dest).collect();
+    let entering = graph.map_weights(|id, _| values[ends[id.index()].index()]);
+    Some(relax_in_order(
+        &entering,
+        &order,
+        start,
+        values[start.index()],
+        objective,
+    ))
+}
+
+/// Relax the edges of `graph` in `order`, with paths from `start` beginning at `origin`.
+fn relax_in_order(
+    graph: &Graph,
+    order: &[NodeId],
+    start: NodeId,
+    origin: Weight,
+    objective: Objective,
+) -> ShortestPathTree {
+    let mut distance: Vec<Option<Weight>> = vec![None; graph.node_count()];
+    let mut parent = vec![None; graph.node_count()];
+    distance[start.index()] = Some(origin);
+
+    for &node in order.iter() {
+        let cost = match distance[node.index()] {
+            Some(cost) => cost,
+            None => continue,
+        };
+        for edge in graph.edges_of(node) {
+            let next = cost + edge.cost;
+            let better = distance[edge.node.index()].is_none_or(|current| match objective {
+                Objective::Shortest => next < current,
+                Objective::Longest => next > current,
+            });
+            if better {
+                distance[edge.node.index()] = Some(next);
+                parent[edge.node.index()] = Some(node);
+            }
+        }
+    }
+
+    ShortestPathTree::new(start, distance, parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bellman_ford::bellman_ford;
+    use crate::rng::Rng;
+
+    #[test]
+    fn paths_match_bellman_ford() {
+        let mut rng = Rng::new(18);
+        for _ in 0..40 {
+            // Edges only run from lower to higher numbered nodes, so there are no cycles.
+            let n = 1 + rng.below(12);
+            let mut graph = Graph::new();
+            let nodes: Vec<NodeId> = (0..n)
+                .map(|i| graph.get_or_insert_node(&i.to_string()))
+                .collect();
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (rng.below(n), rng.below(n));
+                if a < b {
+                    graph.add_edge(nodes[a], nodes[b], rng.below(21) as Weight - 10);
+                }
+            }
+            let order = topological_order(&graph).unwrap();
+            let mut position = vec![0; n];
+            for (i, node) in order.iter().enumerate() {
+                position[node.index()] = i;
+            }
+            assert!(graph
+                .edges()
+                .all(|(_, src, dest, _)| position[src.index()] < position[dest.index()]));
+
+            let negated = graph.map_weights(|_, cost| -cost);
+            for &start in &nodes {
+                let shortest = dag_paths(&graph, start, Objective::Shortest).unwrap();
+                let expected = bellman_ford(&graph, start).unwrap();
+                assert_eq!(shortest.distances(), expected.distances());
+                let longest = dag_paths(&graph, start, Objective::Longest).unwrap();
+                let expected = bellman_ford(&negated, start).unwrap();
+                let negated: Vec<_> = expected.distances().iter().map(|d| d.map(|d| -d)).collect();
+                assert_eq!(longest.distances(), negated);
+            }
+        }
+    }
+
+    #[test]
+    fn cycles_have_no_order() {
+        let cycle: Graph = "a b 1 ->\nb c 1 ->\nc a 1 ->".parse().unwrap();
+        assert!(topological_order(&cycle).is_none());
+        assert!(dag_paths(&cycle, NodeId::new(0), Objective::Longest).is_none());
+        let undirected: Graph = "a b 1".parse().unwrap();
+        assert!(topological_order(&undirected).is_none());
+
+        let ties: Graph = "c a 1 ->\nb a 1 ->".parse().unwrap();
+        let names = |order: Vec<NodeId>| -> Vec<&str> {
+            order
+                .iter()
+                .map(|&node| ties.get_node_name(node).unwrap())
+                .collect()
+        };
+        assert_eq!(names(topological_order(&ties).unwrap()), ["c", "b", "a"]);
+        let by_name = topological_order_by_key(&ties, |node| ties.get_node_name(node).unwrap());
+        assert_eq!(names(by_name.unwrap()), ["b", "c", "a"]);
+    }
+}
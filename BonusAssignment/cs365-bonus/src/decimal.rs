@@ -0,0 +1,172 @@
+use crate::graph::Weight;
+
+/// The most decimal places a [`FixedPoint`] may keep, leaving costs up to about 9.2e9
+/// representable.
+pub const MAX_PLACES: u32 = 9;
+
+/// Decimal costs stored as integer weights counted in units of `10^-places`.
+///
+/// Searches keep running on exact integers with a total order, so real-valued inputs
+/// such as distances in kilometers need no special handling beyond parsing and printing.
+///
+/// ```
+/// use cs365_bonus::decimal::FixedPoint;
+///
+/// let km = FixedPoint::new(3).unwrap();
+/// assert_eq!(km.parse("1.25"), Some(1250));
+/// assert_eq!(km.format(1250 + 2500), "3.75");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FixedPoint {
+    places: u32,
+    /// Round extra digits after the point instead of rejecting them.
+    round: bool,
+}
+
+impl FixedPoint {
+    /// Plain integer costs.
+    pub const INTEGER: FixedPoint = FixedPoint {
+        places: 0,
+        round: false,
+    };
+
+    /// Keep `places` digits after the decimal point, or `None` if that is more than
+    /// [`MAX_PLACES`].
+    pub fn new(places: u32) -> Option<Self> {
+        (places <= MAX_PLACES).then_some(Self {
+            places,
+            round: false,
+        })
+    }
+
+    /// The same scale, but with [`parse`](Self::parse) rounding costs that have more
+    /// digits after the point than it keeps to the nearest unit, halves away from zero.
+    ///
+    /// ```
+    /// use cs365_bonus::decimal::FixedPoint;
+    ///
+    /// let cents = FixedPoint::new(2).unwrap();
+    /// assert_eq!(cents.parse("1.005"), None);
+    /// assert_eq!(cents.rounding().parse("1.005"), Some(101));
+    /// assert_eq!(FixedPoint::INTEGER.rounding().parse("-2.5"), Some(-3));
+    /// ```
+    pub fn rounding(self) -> Self {
+        Self {
+            round: true,
+            ..self
+        }
+    }
+
+    pub fn places(&self) -> u32 {
+        self.places
+    }
+
+    fn unit(&self) -> Weight {
+        (10 as Weight).pow(self.places)
+    }
+
+    /// Parse a cost like `-12.5`. Returns `None` for anything else, including costs with
+    /// more digits after the point than this keeps, which couldn't be stored exactly,
+    /// unless this scale is [`rounding`](Self::rounding).
+    pub fn parse(&self, text: &str) -> Option<Weight> {
+        let (whole, fraction) = match text.split_once('.') {
+            Some((whole, fraction)) if !fraction.is_empty() => (whole, fraction),
+            Some(_) => return None,
+            None => (text, ""),
+        };
+        if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let (fraction, round_up) = match fraction.split_at_checked(self.places as usize) {
+            Some((kept, dropped)) if !dropped.is_empty() => {
+                if !self.round {
+                    return None;
+                }
+                (kept, dropped.as_bytes()[0] >= b'5')
+            }
+            _ => (fraction, false),
+        };
+        let negative = whole.starts_with('-');
+        let digits = whole.strip_prefix(['-', '+']).unwrap_or(whole);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let padding = (10 as Weight).pow(self.places - fraction.len() as u32);
+        let fraction: Weight = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse().ok()?
+        };
+        let magnitude = digits
+            .parse::<Weight>()
+            .ok()?
+            .checked_mul(self.unit())?
+            .checked_add(fraction * padding)?
+            .checked_add(round_up as Weight)?;
+        Some(if negative { -magnitude } else { magnitude })
+    }
+
+    /// The cost `weight` stands for, e.g. `3.75` for 3750 with three places.
+    pub fn to_f64(&self, weight: Weight) -> f64 {
+        weight as f64 / self.unit() as f64
+    }
+
+    /// The weight closest to the cost `value`, or `None` if it is not finite or too large
+    /// to store.
+    pub fn from_f64(&self, value: f64) -> Option<Weight> {
+        let scaled = (value * self.unit() as f64).round();
+        (scaled.is_finite() && scaled >= Weight::MIN as f64 && scaled <= Weight::MAX as f64)
+            .then_some(scaled as Weight)
+    }
+
+    /// Print `weight` with as many decimal places as it needs, e.g. `3.75` or `4`.
+    pub fn format(&self, weight: Weight) -> String {
+        if self.places == 0 {
+            return weight.to_string();
+        }
+        let unit = self.unit();
+        let sign = if weight < 0 { "-" } else { "" };
+        let magnitude = weight.unsigned_abs();
+        let (whole, fraction) = (magnitude / unit as u64, magnitude % unit as u64);
+        if fraction == 0 {
+            return format!("{}{}", sign, whole);
+        }
+        let fraction = format!("{:0width$}", fraction, width = self.places as usize);
+        format!("{}{}.{}", sign, whole, fraction.trim_end_matches('0'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_prints_exactly() {
+        let scale = FixedPoint::new(3).unwrap();
+        assert_eq!(scale.parse("7"), Some(7000));
+        assert_eq!(scale.parse("0.005"), Some(5));
+        assert_eq!(scale.parse("-1.2"), Some(-1200));
+        assert_eq!(scale.parse("1.2345"), None);
+        assert_eq!(scale.parse("1."), None);
+        assert_eq!(scale.parse(".5"), None);
+        assert_eq!(scale.parse("1.-5"), None);
+        assert_eq!(scale.format(-1200), "-1.2");
+        assert_eq!(scale.format(-5), "-0.005");
+        assert_eq!(scale.format(7000), "7");
+        assert_eq!(FixedPoint::INTEGER.parse("1.5"), None);
+        assert_eq!(FixedPoint::INTEGER.parse("-15"), Some(-15));
+        assert!(FixedPoint::new(MAX_PLACES + 1).is_none());
+    }
+
+    #[test]
+    fn rounds_extra_digits_when_asked() {
+        let scale = FixedPoint::new(1).unwrap().rounding();
+        assert_eq!(scale.parse("1.25"), Some(13));
+        assert_eq!(scale.parse("1.2499"), Some(12));
+        assert_eq!(scale.parse("-1.25"), Some(-13));
+        assert_eq!(scale.parse("0.96"), Some(10));
+        assert_eq!(scale.parse("3"), Some(30));
+        assert_eq!(scale.parse("1.2x"), None);
+        assert_eq!(scale.format(13), "1.3");
+    }
+}
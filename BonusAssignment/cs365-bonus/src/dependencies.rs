@@ -0,0 +1,187 @@
+use crate::cycle::girth;
+use crate::dag::topological_order_by_key;
+use crate::graph::{Graph, NodeId, Weight};
+
+/// Packages and the packages each one needs installed first.
+///
+/// ```
+/// use cs365_bonus::dependencies::Dependencies;
+///
+/// let deps = Dependencies::parse("app web db\nweb http\ndb\nhttp");
+/// let order = deps.install_order(|_| 0).unwrap();
+/// assert_eq!(deps.names(&order), ["db", "http", "web", "app"]);
+///
+/// let broken = Dependencies::parse("a b\nb c\nc a\nd a");
+/// let cycle = broken.install_order(|_| 0).unwrap_err();
+/// assert_eq!(broken.names(&cycle), ["a", "b", "c"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Dependencies {
+    /// An edge from each dependency to each package needing it, costing 1.
+    graph: Graph,
+}
+
+impl Dependencies {
+    /// Read one package per line, followed by the names of the packages it depends on.
+    /// A package may appear on several lines. Blank lines and lines starting with `#` are
+    /// skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut graph = Graph::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let package = graph.get_or_insert_node(words.next().unwrap_or(""));
+            for dependency in words {
+                let dependency = graph.get_or_insert_node(dependency);
+                graph.add_edge(dependency, package, 1);
+            }
+        }
+        Self { graph }
+    }
+
+    /// The graph with an edge from each dependency to each package that needs it.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn get(&self, name: &str) -> Option<NodeId> {
+        self.graph.get_node(name)
+    }
+
+    pub fn names(&self, packages: &[NodeId]) -> Vec<&str> {
+        packages
+            .iter()
+            .map(|&package| self.graph.get_node_name(package).unwrap_or(""))
+            .collect()
+    }
+
+    /// An order to install every package in after its dependencies. Among packages whose
+    /// dependencies are all installed, the one with the lowest `weight` goes first, such
+    /// as the oldest version or the least risky upgrade, then the first one listed.
+    ///
+    /// If the dependencies form a cycle, returns instead a cycle with the fewest packages,
+    /// each depending on the next and the last on the first.
+    pub fn install_order(
+        &self,
+        weight: impl Fn(NodeId) -> Weight,
+    ) -> Result<Vec<NodeId>, Vec<NodeId>> {
+        if let Some(order) = topological_order_by_key(&self.graph, weight) {
+            return Ok(order);
+        }
+        let mut cycle = girth(&self.graph)
+            .ok()
+            .flatten()
+            .expect("a graph with no topological order has a cycle");
+        // The cycle runs along the edges, from dependencies to what needs them, and ends
+        // where it began.
+        cycle.nodes.pop();
+        cycle.nodes.reverse();
+        let first = cycle
+            .nodes
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, node)| node)
+            .map_or(0, |(i, _)| i);
+        cycle.nodes.rotate_left(first);
+        Err(cycle.nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// The fewest packages on any dependency cycle, found by a search from every package.
+    fn shortest_cycle(graph: &Graph) -> Option<usize> {
+        graph
+            .node_ids()
+            .filter_map(|start| {
+                let mut hops = vec![None; graph.node_count()];
+                let mut frontier = vec![start];
+                for depth in 1..=graph.node_count() {
+                    let mut next = Vec::new();
+                    for node in frontier {
+                        for edge in graph.edges_of(node) {
+                            if edge.node == start {
+                                return Some(depth);
+                            }
+                            if hops[edge.node.index()].is_none() {
+                                hops[edge.node.index()] = Some(depth);
+                                next.push(edge.node);
+                            }
+                        }
+                    }
+                    frontier = next;
+                }
+                None
+            })
+            .min()
+    }
+
+    #[test]
+    fn orders_or_finds_a_shortest_cycle() {
+        let mut rng = Rng::new(47);
+        for _ in 0..200 {
+            let n = 1 + rng.below(8);
+            let mut text = String::new();
+            for package in 0..n {
+                text += &format!("p{}", package);
+                for _ in 0..rng.below(3) {
+                    // Mostly depend on earlier packages, so that some inputs are acyclic.
+                    let dependency = if rng.chance(0.9) {
+                        rng.below(package + 1)
+                    } else {
+                        rng.below(n)
+                    };
+                    if dependency != package || rng.chance(0.2) {
+                        text += &format!(" p{}", dependency);
+                    }
+                }
+                text += "\n";
+            }
+            let deps = Dependencies::parse(&text);
+            let graph = deps.graph();
+            let weights: Vec<Weight> = (0..n).map(|_| rng.below(3) as Weight).collect();
+            match deps.install_order(|node| weights[node.index()]) {
+                Ok(order) => {
+                    assert_eq!(shortest_cycle(graph), None, "{}", text);
+                    let mut installed = vec![false; n];
+                    for &package in &order {
+                        let ready = |node: NodeId| {
+                            !installed[node.index()]
+                                && graph
+                                    .edges()
+                                    .all(|(_, dep, p, _)| p != node || installed[dep.index()])
+                        };
+                        assert!(ready(package));
+                        // Nothing else ready should have gone first.
+                        let key = |node: NodeId| (weights[node.index()], node);
+                        let best = graph.node_ids().filter(|&node| ready(node)).map(key).min();
+                        assert_eq!(best, Some(key(package)));
+                        installed[package.index()] = true;
+                    }
+                    assert!(installed.iter().all(|&done| done));
+                }
+                Err(cycle) => {
+                    assert_eq!(Some(cycle.len()), shortest_cycle(graph), "{}", text);
+                    assert_eq!(cycle.iter().min(), cycle.first());
+                    for (i, &package) in cycle.iter().enumerate() {
+                        let dependency = cycle[(i + 1) % cycle.len()];
+                        assert!(graph.find_edge(dependency, package).is_some());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn self_dependencies_are_cycles() {
+        let deps = Dependencies::parse("# comments are skipped\n\na a b\nb");
+        let cycle = deps.install_order(|_| 0).unwrap_err();
+        assert_eq!(deps.names(&cycle), ["a"]);
+        assert_eq!(deps.get("c"), None);
+    }
+}
@@ -0,0 +1,183 @@
+/// A union-find structure over the elements `0..len`, with path compression and union by
+/// rank, so any sequence of operations runs in near-constant amortized time each.
+///
+/// ```
+/// use cs365_bonus::disjoint_set::DisjointSet;
+///
+/// let mut sets = DisjointSet::new(5);
+/// assert!(sets.union(0, 1));
+/// assert!(sets.union(3, 4));
+/// assert!(!sets.union(1, 0));
+/// assert!(sets.connected(0, 1));
+/// assert!(!sets.connected(1, 3));
+/// assert_eq!(sets.set_count(), 3);
+/// assert_eq!(sets.set_size(4), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    size: Vec<usize>,
+    sets: usize,
+}
+
+impl DisjointSet {
+    /// Create `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            rank: vec![0; len],
+            size: vec![1; len],
+            sets: len,
+        }
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The number of disjoint sets.
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    /// Add a new singleton set, returning its element.
+    ///
+    /// ```
+    /// # use cs365_bonus::disjoint_set::DisjointSet;
+    /// let mut sets = DisjointSet::new(1);
+    /// assert_eq!(sets.push(), 1);
+    /// assert_eq!(sets.set_count(), 2);
+    /// ```
+    pub fn push(&mut self) -> usize {
+        let element = self.parent.len();
+        self.parent.push(element);
+        self.rank.push(0);
+        self.size.push(1);
+        self.sets += 1;
+        element
+    }
+
+    /// The representative of the set containing `element`.
+    ///
+    /// Every element visited on the way is pointed straight at the representative.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut node = element;
+        while self.parent[node] != root {
+            let next = self.parent[node];
+            self.parent[node] = root;
+            node = next;
+        }
+        root
+    }
+
+    /// Merge the sets containing `a` and `b`, returning `false` if they were already one.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.rank[a] < self.rank[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        if self.rank[a] == self.rank[b] {
+            self.rank[a] += 1;
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.sets -= 1;
+        true
+    }
+
+    /// Whether `a` and `b` are in the same set.
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of elements in the set containing `element`.
+    pub fn set_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.size[root]
+    }
+
+    /// The elements grouped by set, each group and the list of groups in increasing order.
+    ///
+    /// ```
+    /// # use cs365_bonus::disjoint_set::DisjointSet;
+    /// let mut sets = DisjointSet::new(4);
+    /// sets.union(3, 1);
+    /// assert_eq!(sets.sets(), vec![vec![0], vec![1, 3], vec![2]]);
+    /// ```
+    pub fn sets(&mut self) -> Vec<Vec<usize>> {
+        let mut index = vec![usize::MAX; self.len()];
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for element in 0..self.len() {
+            let root = self.find(element);
+            if index[root] == usize::MAX {
+                index[root] = groups.len();
+                groups.push(Vec::new());
+            }
+            groups[index[root]].push(element);
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn matches_a_labelling() {
+        // Naively relabel every member of the absorbed set on each union.
+        let mut rng = Rng::new(37);
+        for _ in 0..50 {
+            let len = 1 + rng.below(20);
+            let mut sets = DisjointSet::new(len);
+            let mut label: Vec<usize> = (0..len).collect();
+            for _ in 0..rng.below(3 * len) {
+                if rng.chance(0.1) {
+                    label.push(label.len());
+                    assert_eq!(sets.push(), label.len() - 1);
+                }
+                let (a, b) = (rng.below(label.len()), rng.below(label.len()));
+                let (from, to) = (label[b], label[a]);
+                assert_eq!(sets.union(a, b), from != to);
+                for l in &mut label {
+                    if *l == from {
+                        *l = to;
+                    }
+                }
+            }
+            assert_eq!(sets.len(), label.len());
+            let mut distinct = label.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            assert_eq!(sets.set_count(), distinct.len());
+            for a in 0..label.len() {
+                let size = label.iter().filter(|&&l| l == label[a]).count();
+                assert_eq!(sets.set_size(a), size);
+                for b in 0..label.len() {
+                    assert_eq!(sets.connected(a, b), label[a] == label[b]);
+                }
+            }
+            let groups = sets.sets();
+            assert_eq!(groups.len(), distinct.len());
+            assert!(groups.windows(2).all(|pair| pair[0][0] < pair[1][0]));
+            for group in groups {
+                assert!(group.iter().all(|&e| label[e] == label[group[0]]));
+            }
+        }
+        assert!(DisjointSet::new(0).is_empty());
+    }
+}
@@ -0,0 +1,511 @@
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use crate::path::Path;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Error returned when a Graphviz DOT file can't be read as a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDotError {
+    line: usize,
+    message: String,
+}
+
+impl ParseDotError {
+    /// The 1-based line number where the problem was found.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl std::fmt::Display for ParseDotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid DOT on line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseDotError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Id(String),
+    Arrow,
+    Dash,
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    Equals,
+    Separator,
+}
+
+/// Split DOT source into tokens tagged with their line, dropping comments.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ParseDotError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    let error = |line, message: &str| ParseDotError {
+        line,
+        message: message.to_string(),
+    };
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            c if c.is_whitespace() => i += 1,
+            '/' if next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            // Lines starting with `#` are C preprocessor output, which DOT ignores.
+            '#' if tokens.last().is_none_or(|&(_, at)| at < line) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    line += (chars[i] == '\n') as usize;
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err(error(line, "unterminated comment"));
+                }
+                i += 2;
+            }
+            '-' if next == Some('>') => {
+                tokens.push((Token::Arrow, line));
+                i += 2;
+            }
+            '-' if next == Some('-') => {
+                tokens.push((Token::Dash, line));
+                i += 2;
+            }
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' => {
+                let token = match c {
+                    '{' => Token::Open,
+                    '}' => Token::Close,
+                    '[' => Token::OpenBracket,
+                    ']' => Token::CloseBracket,
+                    '=' => Token::Equals,
+                    _ => Token::Separator,
+                };
+                tokens.push((token, line));
+                i += 1;
+            }
+            '"' => {
+                let start = line;
+                let mut id = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(error(start, "unterminated string")),
+                        Some('"') => break,
+                        Some('\\') if chars.get(i + 1) == Some(&'"') => {
+                            id.push('"');
+                            i += 1;
+                        }
+                        // A backslash before a newline continues the string.
+                        Some('\\') if chars.get(i + 1) == Some(&'\n') => {
+                            line += 1;
+                            i += 1;
+                        }
+                        Some(&c) => {
+                            line += (c == '\n') as usize;
+                            id.push(c);
+                        }
+                    }
+                    i += 1;
+                }
+                tokens.push((Token::Id(id), start));
+                i += 1;
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let start = i;
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+                {
+                    i += 1;
+                }
+                tokens.push((Token::Id(chars[start..i].iter().collect()), line));
+            }
+            '<' => return Err(error(line, "HTML labels are not supported")),
+            c => return Err(error(line, &format!("unexpected character '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the token stream, adding to `graph` as statements are read.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    graph: Graph,
+    /// The `weight` or `label` given by `edge [...]` defaults, per nesting level.
+    default_weight: Vec<Option<Weight>>,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |&(_, line)| line)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseDotError {
+        ParseDotError {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.peek() == Some(token);
+        self.pos += found as usize;
+        found
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), ParseDotError> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {}", what)))
+        }
+    }
+
+    fn id(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Id(id)) => {
+                let id = id.clone();
+                self.pos += 1;
+                Some(id)
+            }
+            _ => None,
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Id(id)) if id.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// `graph_type [name] { statements }`
+    fn graph(mut self) -> Result<Graph, ParseDotError> {
+        self.keyword("strict");
+        if !self.keyword("graph") && !self.keyword("digraph") {
+            return Err(self.error("expected 'graph' or 'digraph'"));
+        }
+        self.id();
+        self.block()?;
+        if self.pos < self.tokens.len() {
+            return Err(self.error("unexpected input after the graph"));
+        }
+        Ok(self.graph)
+    }
+
+    /// `{ statements }`, returning the nodes mentioned inside for use as an edge endpoint.
+    fn block(&mut self) -> Result<Vec<NodeId>, ParseDotError> {
+        self.expect(Token::Open, "'{'")?;
+        let inherited = self.default_weight.last().copied().flatten();
+        self.default_weight.push(inherited);
+        let mut nodes = Vec::new();
+        while !self.eat(&Token::Close) {
+            if self.peek().is_none() {
+                return Err(self.error("expected '}'"));
+            }
+            if !self.eat(&Token::Separator) {
+                nodes.extend(self.statement()?);
+            }
+        }
+        self.default_weight.pop();
+        Ok(nodes)
+    }
+
+    fn statement(&mut self) -> Result<Vec<NodeId>, ParseDotError> {
+        let start = self.pos;
+        let line = self.line();
+        let invalid = |message| ParseDotError { line, message };
+        if self.keyword("graph") || self.keyword("node") {
+            if self.peek() == Some(&Token::OpenBracket) {
+                self.attributes()?;
+                return Ok(Vec::new());
+            }
+            self.pos = start;
+        }
+        if self.keyword("edge") {
+            if self.peek() == Some(&Token::OpenBracket) {
+                let weight = weight_of(&self.attributes()?).map_err(invalid)?;
+                if let Some(weight) = weight {
+                    *self.default_weight.last_mut().unwrap() = Some(weight);
+                }
+                return Ok(Vec::new());
+            }
+            self.pos = start;
+        }
+        if let Some(Token::Id(_)) = self.peek() {
+            if self.tokens.get(self.pos + 1).map(|(token, _)| token) == Some(&Token::Equals) {
+                self.id();
+                self.pos += 1;
+                self.id()
+                    .ok_or_else(|| self.error("expected a value after '='"))?;
+                return Ok(Vec::new());
+            }
+        }
+
+        let mut group = self.endpoint()?;
+        let mut mentioned = group.clone();
+        let mut edges = Vec::new();
+        loop {
+            let directed = match self.peek() {
+                Some(Token::Arrow) => true,
+                Some(Token::Dash) => false,
+                _ => break,
+            };
+            self.pos += 1;
+            let next = self.endpoint()?;
+            edges.push((group, next.clone(), directed));
+            mentioned.extend(next.iter().copied());
+            group = next;
+        }
+        let attributes = if self.peek() == Some(&Token::OpenBracket) {
+            self.attributes()?
+        } else {
+            Vec::new()
+        };
+        let weight = weight_of(&attributes)
+            .map_err(invalid)?
+            .or(self.default_weight.last().copied().flatten())
+            .unwrap_or(1);
+        // `to_dot` writes the undirected edges of a digraph this way.
+        let no_arrows = attributes
+            .iter()
+            .any(|(key, value)| key == "dir" && (value == "none" || value == "both"));
+        for (from, to, directed) in edges {
+            let directed = directed && !no_arrows;
+            for &src in from.iter() {
+                for &dest in to.iter() {
+                    if directed {
+                        self.graph.add_edge(src, dest, weight);
+                    } else {
+                        self.graph.add_bidirectional_edge(src, dest, weight);
+                    }
+                }
+            }
+        }
+        Ok(mentioned)
+    }
+
+    /// A node id or a subgraph.
+    fn endpoint(&mut self) -> Result<Vec<NodeId>, ParseDotError> {
+        if self.keyword("subgraph") {
+            self.id();
+            return self.block();
+        }
+        if self.peek() == Some(&Token::Open) {
+            return self.block();
+        }
+        let id = self.id().ok_or_else(|| self.error("expected a node"))?;
+        Ok(vec![self.graph.get_or_insert_node(&id)])
+    }
+
+    /// One or more `[key=value, ...]` lists.
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, ParseDotError> {
+        let mut attributes = Vec::new();
+        while self.eat(&Token::OpenBracket) {
+            while !self.eat(&Token::CloseBracket) {
+                if self.eat(&Token::Separator) {
+                    continue;
+                }
+                let key = self
+                    .id()
+                    .ok_or_else(|| self.error("expected an attribute name"))?;
+                let value = if self.eat(&Token::Equals) {
+                    self.id()
+                        .ok_or_else(|| self.error("expected an attribute value"))?
+                } else {
+                    "true".to_string()
+                };
+                attributes.push((key, value));
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+/// The edge weight given by `weight`, or failing that `label`.
+fn weight_of(attributes: &[(String, String)]) -> Result<Option<Weight>, String> {
+    let find = |name| attributes.iter().rev().find(|(key, _)| key == name);
+    match find("weight").or_else(|| find("label")) {
+        Some((key, value)) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("invalid edge {} '{}'", key, value)),
+        None => Ok(None),
+    }
+}
+
+/// Quote `name` as a DOT id.
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Graph {
+    /// Read a Graphviz `graph` or `digraph`.
+    ///
+    /// `--` adds an undirected edge and `->` a directed one, unless it has `dir=none` or
+    /// `dir=both`. Each edge's cost is its
+    /// `weight` attribute, or failing that its `label`, or the same from an enclosing
+    /// `edge [...]` statement, and otherwise 1. Subgraphs are flattened and other
+    /// attributes are ignored.
+    ///
+    /// ```
+    /// let graph = cs365_bonus::Graph::from_dot("digraph { a -> b [weight=2]; b -- c }").unwrap();
+    /// assert_eq!(graph.node_count(), 3);
+    /// assert_eq!(graph.edge_count(), 2);
+    /// ```
+    pub fn from_dot(text: &str) -> Result<Graph, ParseDotError> {
+        let parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+            graph: Graph::new(),
+            default_weight: Vec::new(),
+        };
+        parser.graph()
+    }
+
+    /// Write this graph in the DOT format, labelling edges with their costs and drawing
+    /// the nodes and edges of `highlight` in red.
+    ///
+    /// A graph with any directed edge is written as a `digraph` whose undirected edges have
+    /// no arrowheads.
+    pub fn to_dot(&self, highlight: Option<&Path>) -> String {
+        self.to_dot_with_paths(highlight)
+    }
+
+    /// Like [`to_dot`](Graph::to_dot), but drawing the nodes and edges of every path in
+    /// `highlight` in red.
+    pub fn to_dot_with_paths<'a>(&self, highlight: impl IntoIterator<Item = &'a Path>) -> String {
+        let directed = self
+            .edges()
+            .any(|(id, ..)| self.is_edge_directed(id) == Some(true));
+        let mut on_path: HashSet<EdgeId> = HashSet::new();
+        let mut path_nodes: HashSet<NodeId> = HashSet::new();
+        for path in highlight {
+            path_nodes.extend(path.nodes.iter().copied());
+            on_path.extend(
+                path.nodes
+                    .windows(2)
+                    .filter_map(|pair| self.find_edge(pair[0], pair[1])),
+            );
+        }
+
+        let mut out = String::new();
+        out.push_str(if directed { "digraph {\n" } else { "graph {\n" });
+        for node in self.node_ids() {
+            let name = quote(self.get_node_name(node).unwrap_or(""));
+            if path_nodes.contains(&node) {
+                let _ = writeln!(out, "    {} [color=red, fontcolor=red];", name);
+            } else {
+                let _ = writeln!(out, "    {};", name);
+            }
+        }
+        for (id, src, dest, cost) in self.edges() {
+            let name = |node| quote(self.get_node_name(node).unwrap_or(""));
+            let edge_directed = self.is_edge_directed(id) == Some(true);
+            let op = if directed { "->" } else { "--" };
+            let mut attributes = format!("weight={}, label=\"{}\"", cost, cost);
+            if directed && !edge_directed {
+                attributes.push_str(", dir=none");
+            }
+            if on_path.contains(&id) {
+                attributes.push_str(", color=red, penwidth=2");
+            }
+            let _ = writeln!(
+                out,
+                "    {} {} {} [{}];",
+                name(src),
+                op,
+                name(dest),
+                attributes
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_shortest_path;
+
+    #[test]
+    fn reads_chains_defaults_and_subgraphs() {
+        let text = r#"
+            /* roads */
+            strict graph "town" {
+                rankdir=LR
+                edge [weight=5];
+                a -- b -- c [label="2"];
+                subgraph cluster { c; d }
+                c -- d // defaulted to 5
+                x -> {y "z 1"} [weight=-1]
+            }
+        "#;
+        let graph = Graph::from_dot(text).unwrap();
+        let node = |name| graph.get_node(name).unwrap();
+        let path = find_shortest_path(&graph, node("a"), node("d")).unwrap();
+        assert_eq!(path.cost, 9);
+        assert_eq!(
+            graph.edge_weight(graph.find_edge(node("x"), node("z 1")).unwrap()),
+            Some(-1)
+        );
+        assert!(graph.find_edge(node("y"), node("x")).is_none());
+    }
+
+    #[test]
+    fn reports_errors_with_lines() {
+        let error = Graph::from_dot("graph {\n a -- b [weight=heavy]\n}").unwrap_err();
+        assert_eq!(error.line(), 2);
+        assert_eq!(
+            error.to_string(),
+            "invalid DOT on line 2: invalid edge weight 'heavy'"
+        );
+        assert!(Graph::from_dot("graph { a -- }").is_err());
+    }
+
+    #[test]
+    fn written_dot_reads_back() {
+        let graph: Graph = "a b 1 ->\nb \"c\" 2".parse().unwrap();
+        let (a, c) = (
+            graph.get_node("a").unwrap(),
+            graph.get_node("\"c\"").unwrap(),
+        );
+        let path = find_shortest_path(&graph, a, c).unwrap();
+        let dot = graph.to_dot(Some(&path));
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains("\"a\" -> \"b\" [weight=1, label=\"1\", color=red, penwidth=2];"));
+
+        let reread = Graph::from_dot(&dot).unwrap();
+        assert_eq!(reread.get_node_name(c), Some("\"c\""));
+        assert!(graph.edges().eq(reread.edges()));
+    }
+}
@@ -0,0 +1,355 @@
+use crate::decimal::FixedPoint;
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// The cost of an edge that can't be taken, and the distance of a node that can't reach the
+/// goal.
+pub const BLOCKED: Weight = Weight::MAX;
+
+type Key = (Weight, Weight);
+
+/// D* Lite (Koenig and Likhachev, 2002): shortest paths to a fixed goal from a start that
+/// moves, kept up to date as edge costs change without searching from scratch.
+///
+/// The search runs backward from the goal, like Dijkstra's algorithm on the reversed graph,
+/// but remembers each node's distance `g` alongside a one-step lookahead `rhs`. A cost
+/// change only makes the nodes whose lookahead it alters inconsistent, and replanning
+/// repairs those in order of distance from the goal plus a heuristic estimate of their
+/// distance from the start, stopping once the start's distance is settled. The heuristic
+/// `h(u, v)` must never overestimate the distance from `u` to `v` and must obey the
+/// triangle inequality; `|_, _| 0` always does. Costs must be positive: around a cycle of
+/// free edges, nodes can keep vouching for each other's stale distances.
+///
+/// ```
+/// use cs365_bonus::dstar_lite::{DStarLite, BLOCKED};
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1\na d 2\nd c 2".parse().unwrap();
+/// let [a, b, c] = ["a", "b", "c"].map(|name| graph.get_node(name).unwrap());
+/// let mut planner = DStarLite::new(&graph, a, c, |_, _| 0);
+/// assert_eq!(planner.replan(), Some(2));
+/// planner.move_to(b);
+/// planner.set_cost(graph.find_edge(b, c).unwrap(), BLOCKED);
+/// assert_eq!(planner.replan(), Some(5));
+/// let path = planner.path().unwrap();
+/// let names: Vec<&str> = path.iter().map(|&node| graph.get_node_name(node).unwrap()).collect();
+/// assert_eq!(names, ["b", "a", "d", "c"]);
+/// ```
+pub struct DStarLite<'a, H> {
+    graph: &'a Graph,
+    /// The same graph with directed edges reversed, to find each node's predecessors.
+    reversed: Graph,
+    cost: Vec<Weight>,
+    heuristic: H,
+    start: NodeId,
+    goal: NodeId,
+    /// Where the start was when keys were last offset, and the total offset since.
+    last: NodeId,
+    offset: Weight,
+    g: Vec<Weight>,
+    rhs: Vec<Weight>,
+    /// The key each node is queued with, if it is; the heap may hold stale entries too.
+    queued: Vec<Option<Key>>,
+    queue: BinaryHeap<Reverse<(Key, NodeId)>>,
+    expanded: usize,
+}
+
+impl<'a, H: Fn(NodeId, NodeId) -> Weight> DStarLite<'a, H> {
+    /// A planner for paths from `start` to `goal` over `graph`, whose edges start out at
+    /// their own costs. Nothing is searched until [`replan`](Self::replan).
+    pub fn new(graph: &'a Graph, start: NodeId, goal: NodeId, heuristic: H) -> Self {
+        let n = graph.node_count();
+        let mut planner = Self {
+            graph,
+            reversed: graph.reversed(),
+            cost: (0..graph.edge_count())
+                .map(|i| graph.edge_weight(EdgeId::new(i)).unwrap_or(BLOCKED))
+                .collect(),
+            heuristic,
+            start,
+            goal,
+            last: start,
+            offset: 0,
+            g: vec![BLOCKED; n],
+            rhs: vec![BLOCKED; n],
+            queued: vec![None; n],
+            queue: BinaryHeap::new(),
+            expanded: 0,
+        };
+        planner.rhs[goal.index()] = 0;
+        planner.enqueue(goal);
+        planner
+    }
+
+    /// How many nodes replanning has expanded in total.
+    pub fn expanded(&self) -> usize {
+        self.expanded
+    }
+
+    /// The current cost of `edge`.
+    pub fn cost(&self, edge: EdgeId) -> Weight {
+        self.cost[edge.index()]
+    }
+
+    /// The distance from `node` to the goal as of the last replan.
+    pub fn distance(&self, node: NodeId) -> Option<Weight> {
+        Some(self.g[node.index()]).filter(|&g| g != BLOCKED)
+    }
+
+    /// Move the start to `node`, usually a neighbor along the planned path.
+    pub fn move_to(&mut self, node: NodeId) {
+        self.start = node;
+    }
+
+    /// Change the cost of `edge`, in both directions if it is undirected, to `cost` or
+    /// [`BLOCKED`].
+    pub fn set_cost(&mut self, edge: EdgeId, cost: Weight) {
+        if self.cost[edge.index()] == cost {
+            return;
+        }
+        // Keys queued so far were computed from where the start used to be. Rather than
+        // recompute them all, later keys are raised by how far the start has moved since.
+        if self.start != self.last {
+            self.offset += (self.heuristic)(self.last, self.start);
+            self.last = self.start;
+        }
+        self.cost[edge.index()] = cost;
+        let (src, dest) = self
+            .graph
+            .edge_endpoints(edge)
+            .expect("edges come from the planner's graph");
+        self.update(src);
+        if self.graph.is_edge_directed(edge) == Some(false) {
+            self.update(dest);
+        }
+    }
+
+    /// Repair the distances until the start's is settled, returning it, or `None` if the
+    /// goal can't be reached from the start.
+    pub fn replan(&mut self) -> Option<Weight> {
+        while let Some(&Reverse((key, node))) = self.queue.peek() {
+            if self.queued[node.index()] != Some(key) {
+                self.queue.pop();
+                continue;
+            }
+            let start = self.start.index();
+            if key >= self.key(self.start) && self.rhs[start] == self.g[start] {
+                break;
+            }
+            self.queue.pop();
+            self.queued[node.index()] = None;
+            let fresh = self.key(node);
+            if key < fresh {
+                self.queued[node.index()] = Some(fresh);
+                self.queue.push(Reverse((fresh, node)));
+                continue;
+            }
+            self.expanded += 1;
+            let i = node.index();
+            if self.g[i] > self.rhs[i] {
+                self.g[i] = self.rhs[i];
+            } else {
+                self.g[i] = BLOCKED;
+                self.update(node);
+            }
+            for index in 0..self.reversed.edges_of(node).len() {
+                let predecessor = self.reversed.edges_of(node)[index].node;
+                self.update(predecessor);
+            }
+        }
+        self.distance(self.start)
+    }
+
+    /// The planned path from the start to the goal, taking the cheapest next step from each
+    /// node, or `None` if there is none.
+    pub fn path(&self) -> Option<Vec<NodeId>> {
+        self.distance(self.start)?;
+        let mut nodes = vec![self.start];
+        let mut node = self.start;
+        while node != self.goal {
+            node = self.next_step(node)?;
+            nodes.push(node);
+            if nodes.len() > self.graph.node_count() {
+                return None;
+            }
+        }
+        Some(nodes)
+    }
+
+    /// The neighbor of `node` the planned path goes through next.
+    pub fn next_step(&self, node: NodeId) -> Option<NodeId> {
+        self.graph
+            .edges_of(node)
+            .iter()
+            .map(|edge| (self.through(edge.id, edge.node), edge.node))
+            .filter(|&(distance, _)| distance != BLOCKED)
+            .min()
+            .map(|(_, next)| next)
+    }
+
+    /// The distance to the goal by way of `edge` to `next`.
+    fn through(&self, edge: EdgeId, next: NodeId) -> Weight {
+        match (self.cost[edge.index()], self.g[next.index()]) {
+            (BLOCKED, _) | (_, BLOCKED) => BLOCKED,
+            (cost, g) => cost + g,
+        }
+    }
+
+    fn key(&self, node: NodeId) -> Key {
+        let best = self.g[node.index()].min(self.rhs[node.index()]);
+        if best == BLOCKED {
+            return (BLOCKED, BLOCKED);
+        }
+        let h = (self.heuristic)(self.start, node);
+        (best + h + self.offset, best)
+    }
+
+    /// Recompute the lookahead of `node` and queue it if it no longer matches its distance.
+    fn update(&mut self, node: NodeId) {
+        let i = node.index();
+        if node != self.goal {
+            self.rhs[i] = self
+                .graph
+                .edges_of(node)
+                .iter()
+                .map(|edge| self.through(edge.id, edge.node))
+                .min()
+                .unwrap_or(BLOCKED);
+        }
+        self.queued[i] = None;
+        if self.g[i] != self.rhs[i] {
+            self.enqueue(node);
+        }
+    }
+
+    fn enqueue(&mut self, node: NodeId) {
+        let key = self.key(node);
+        self.queued[node.index()] = Some(key);
+        self.queue.push(Reverse((key, node)));
+    }
+}
+
+/// One step of a scripted replanning scenario.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// An edge's cost changes, or it becomes [`BLOCKED`].
+    SetCost(EdgeId, Weight),
+    /// The agent moves to a node.
+    Move(NodeId),
+    /// The agent asks for the shortest path from where it is.
+    Plan,
+}
+
+/// Read a scenario with one step per line: `set u v <cost>` to change the cost of the
+/// cheapest edge from `u` to `v`, with `blocked` for a cost to remove it; `move <node>`;
+/// and `plan`. Costs are read with `scale`. Blank lines and lines starting with `#` are
+/// skipped.
+///
+/// ```
+/// use cs365_bonus::decimal::FixedPoint;
+/// use cs365_bonus::dstar_lite::{parse_scenario, Step, BLOCKED};
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1".parse().unwrap();
+/// let steps = parse_scenario(&graph, "move b\nset b c blocked\nplan", FixedPoint::default());
+/// let [b, c] = ["b", "c"].map(|name| graph.get_node(name).unwrap());
+/// let edge = graph.find_edge(b, c).unwrap();
+/// assert_eq!(steps, Ok(vec![Step::Move(b), Step::SetCost(edge, BLOCKED), Step::Plan]));
+/// ```
+pub fn parse_scenario(graph: &Graph, text: &str, scale: FixedPoint) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let node = |name: &str| {
+            graph
+                .get_node(name)
+                .ok_or_else(|| format!("line {}: node '{}' does not exist", i + 1, name))
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        steps.push(match words.as_slice() {
+            ["set", u, v, cost] => {
+                let edge = graph
+                    .find_edge(node(u)?, node(v)?)
+                    .ok_or_else(|| format!("line {}: there is no edge '{}-{}'", i + 1, u, v))?;
+                let cost = match *cost {
+                    "blocked" => BLOCKED,
+                    cost => scale
+                        .parse(cost)
+                        .filter(|&cost| cost > 0)
+                        .ok_or_else(|| format!("line {}: invalid cost '{}'", i + 1, cost))?,
+                };
+                Step::SetCost(edge, cost)
+            }
+            ["move", name] => Step::Move(node(name)?),
+            ["plan"] => Step::Plan,
+            _ => {
+                return Err(format!(
+                    "line {}: expected 'set u v cost', 'move node', or 'plan'",
+                    i + 1
+                ))
+            }
+        });
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::shortest_path_tree;
+    use crate::rng::Rng;
+
+    #[test]
+    fn matches_a_fresh_search_after_each_change() {
+        let mut rng = Rng::new(11);
+        let mut graph = Graph::new();
+        let nodes: Vec<NodeId> = (0..50)
+            .map(|i| graph.get_or_insert_node(&i.to_string()))
+            .collect();
+        for _ in 0..150 {
+            let (u, v) = (nodes[rng.below(50)], nodes[rng.below(50)]);
+            let cost = 1 + rng.below(9) as Weight;
+            if rng.below(2) == 0 {
+                graph.add_edge(u, v, cost);
+            } else {
+                graph.add_bidirectional_edge(u, v, cost);
+            }
+        }
+        let goal = nodes[0];
+        let mut start = nodes[1];
+        let mut planner = DStarLite::new(&graph, start, goal, |_, _| 0);
+        let mut costs: Vec<Weight> = graph.edges().map(|(_, _, _, cost)| cost).collect();
+        for _ in 0..40 {
+            for _ in 0..3 {
+                let edge = rng.below(costs.len());
+                costs[edge] = match rng.below(3) {
+                    0 => BLOCKED,
+                    _ => 1 + rng.below(9) as Weight,
+                };
+                planner.set_cost(EdgeId::new(edge), costs[edge]);
+            }
+            let current = graph.map_weights(|id, _| costs[id.index()]);
+            let mut kept = Graph::new();
+            for node in graph.node_ids() {
+                kept.get_or_insert_node(graph.get_node_name(node).unwrap());
+            }
+            for (id, src, dest, cost) in current.edges() {
+                if cost == BLOCKED {
+                    continue;
+                }
+                match current.is_edge_directed(id) {
+                    Some(true) => kept.add_edge(src, dest, cost),
+                    _ => kept.add_bidirectional_edge(src, dest, cost),
+                };
+            }
+            let expected = shortest_path_tree(&kept.reversed(), goal).distance(start);
+            assert_eq!(planner.replan(), expected);
+            if let Some(next) = planner.next_step(start) {
+                start = next;
+                planner.move_to(start);
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::shortest_path_tree;
+
+/// The eccentricity of every node: the distance to the node farthest from it.
+///
+/// `None` means some node is unreachable, so the eccentricity is infinite.
+pub fn eccentricities(graph: &Graph) -> Vec<Option<Weight>> {
+    graph
+        .node_ids()
+        .map(|node| {
+            let tree = shortest_path_tree(graph, node);
+            tree.distances()
+                .iter()
+                .try_fold(0, |max, d| d.map(|d| max.max(d)))
+        })
+        .collect()
+}
+
+/// Orders finite eccentricities before the infinite (`None`) ones.
+fn key(ecc: Option<Weight>) -> (bool, Weight) {
+    match ecc {
+        Some(ecc) => (false, ecc),
+        None => (true, 0),
+    }
+}
+
+/// The minimum eccentricity, or `None` if every eccentricity is infinite.
+pub fn radius(eccentricities: &[Option<Weight>]) -> Option<Weight> {
+    eccentricities.iter().flatten().min().copied()
+}
+
+/// The maximum eccentricity, or `None` if it is infinite.
+pub fn diameter(eccentricities: &[Option<Weight>]) -> Option<Weight> {
+    eccentricities
+        .iter()
+        .try_fold(0, |max, ecc| ecc.map(|ecc| max.max(ecc)))
+}
+
+/// Nodes whose eccentricity equals the radius.
+pub fn center(eccentricities: &[Option<Weight>]) -> Vec<NodeId> {
+    extreme_nodes(eccentricities, |a, b| a < b)
+}
+
+/// Nodes whose eccentricity equals the diameter.
+pub fn periphery(eccentricities: &[Option<Weight>]) -> Vec<NodeId> {
+    extreme_nodes(eccentricities, |a, b| a > b)
+}
+
+fn extreme_nodes(
+    eccentricities: &[Option<Weight>],
+    better: impl Fn((bool, Weight), (bool, Weight)) -> bool,
+) -> Vec<NodeId> {
+    let mut best = None;
+    let mut nodes = Vec::new();
+    for (i, &ecc) in eccentricities.iter().enumerate() {
+        let ecc = key(ecc);
+        match best {
+            Some(b) if better(b, ecc) => {}
+            Some(b) if b == ecc => nodes.push(NodeId::new(i)),
+            _ => {
+                best = Some(ecc);
+                nodes.clear();
+                nodes.push(NodeId::new(i));
+            }
+        }
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_graph_ends_are_the_periphery() {
+        let graph: Graph = "a b 1\nb c 2\nc d 1".parse().unwrap();
+        let ecc = eccentricities(&graph);
+        assert_eq!(ecc, [Some(4), Some(3), Some(3), Some(4)]);
+        assert_eq!(radius(&ecc), Some(3));
+        assert_eq!(diameter(&ecc), Some(4));
+        let name = |nodes: Vec<NodeId>| -> Vec<&str> {
+            nodes
+                .into_iter()
+                .map(|n| graph.get_node_name(n).unwrap())
+                .collect()
+        };
+        assert_eq!(name(center(&ecc)), ["b", "c"]);
+        assert_eq!(name(periphery(&ecc)), ["a", "d"]);
+    }
+
+    #[test]
+    fn unreachable_nodes_make_eccentricities_infinite() {
+        let mut graph = Graph::from_directed_edges([("a", "b", 2), ("b", "c", 3), ("c", "a", 1)]);
+        graph.get_or_insert_node("z");
+        let ecc = eccentricities(&graph);
+        assert_eq!(ecc, [None, None, None, None]);
+        assert_eq!(radius(&ecc), None);
+        assert_eq!(diameter(&ecc), None);
+        assert_eq!(center(&ecc).len(), 4);
+
+        let ecc = [Some(5), None, Some(3), Some(3)];
+        assert_eq!(radius(&ecc), Some(3));
+        assert_eq!(diameter(&ecc), None);
+        assert_eq!(center(&ecc), [NodeId::new(2), NodeId::new(3)]);
+        assert_eq!(periphery(&ecc), [NodeId::new(1)]);
+        assert!(center(&[]).is_empty());
+    }
+}
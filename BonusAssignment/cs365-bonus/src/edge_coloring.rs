@@ -0,0 +1,200 @@
+use crate::graph::{simple_adjacency, EdgeId, Graph, NodeId};
+
+/// A proper edge coloring, where no two edges sharing a node have the same color.
+#[derive(Debug, Clone)]
+pub struct EdgeColoring {
+    /// The color of each edge, by edge id.
+    pub colors: Vec<usize>,
+    /// How many colors were used, numbered from 0.
+    pub color_count: usize,
+    /// The largest number of edges at any node, a lower bound on the colors needed.
+    pub max_degree: usize,
+}
+
+impl EdgeColoring {
+    /// The edges of each color, as rounds in which no node takes part twice.
+    pub fn rounds(&self) -> Vec<Vec<EdgeId>> {
+        let mut rounds = vec![Vec::new(); self.color_count];
+        for (i, &color) in self.colors.iter().enumerate() {
+            rounds[color].push(EdgeId::new(i));
+        }
+        rounds
+    }
+}
+
+/// Color the edges of a simple graph, ignoring direction, with at most one color more than
+/// its maximum degree, as Vizing's theorem promises.
+///
+/// This is the Misra and Gries algorithm. Each uncolored edge `u-v` gets a color by
+/// building a fan of `u`'s neighbors around it, swapping the two colors along one
+/// alternating path so that a color missing at `u` is also missing at some node of the
+/// fan, and rotating the fan's colors so that that node's edge can take it. It takes
+/// O(m (n + Δ²)) time for `m` edges, `n` nodes and maximum degree `Δ`.
+///
+/// ```
+/// use cs365_bonus::edge_coloring::edge_coloring;
+///
+/// // Every pair of five teams meets once; no team can play twice in a round.
+/// let graph: cs365_bonus::Graph =
+///     "a b 1\na c 1\na d 1\na e 1\nb c 1\nb d 1\nb e 1\nc d 1\nc e 1\nd e 1".parse().unwrap();
+/// let coloring = edge_coloring(&graph).unwrap();
+/// assert_eq!(coloring.max_degree, 4);
+/// assert!(coloring.color_count <= 5);
+/// let edges: Vec<_> = graph.edges().collect();
+/// for round in coloring.rounds() {
+///     let mut teams: Vec<_> = round.iter().flat_map(|id| {
+///         let (_, home, away, _) = edges[id.index()];
+///         [home, away]
+///     }).collect();
+///     let count = teams.len();
+///     teams.sort();
+///     teams.dedup();
+///     assert_eq!(teams.len(), count);
+/// }
+/// ```
+pub fn edge_coloring(graph: &Graph) -> Result<EdgeColoring, String> {
+    let adjacency = simple_adjacency(graph);
+    if let Some((_, node, ..)) = graph.edges().find(|&(_, src, dest, _)| src == dest) {
+        return Err(format!(
+            "the self-loop at '{}' can't be colored",
+            graph.get_node_name(node).unwrap_or_default()
+        ));
+    }
+    let pairs: usize = adjacency.iter().map(Vec::len).sum::<usize>() / 2;
+    if pairs != graph.edge_count() {
+        return Err("edge coloring needs a simple graph, without parallel edges".into());
+    }
+    let max_degree = adjacency.iter().map(Vec::len).max().unwrap_or(0);
+
+    let mut table = Table {
+        at: vec![vec![None; max_degree + 1]; graph.node_count()],
+    };
+    let mut in_fan = vec![false; graph.node_count()];
+    for (_, u, v, _) in graph.edges() {
+        // A fan of u: each node's edge to u has a color missing at the node before it.
+        let mut fan = vec![v];
+        in_fan[v.index()] = true;
+        'grow: loop {
+            let last = *fan.last().expect("the fan starts with v");
+            for color in 0..=max_degree {
+                if table.at[last.index()][color].is_some() {
+                    continue;
+                }
+                if let Some(next) = table.at[u.index()][color] {
+                    if !in_fan[next.index()] {
+                        in_fan[next.index()] = true;
+                        fan.push(next);
+                        continue 'grow;
+                    }
+                }
+            }
+            break;
+        }
+        for node in &fan {
+            in_fan[node.index()] = false;
+        }
+
+        let c = table.missing(u);
+        let d = table.missing(*fan.last().expect("the fan starts with v"));
+        table.invert(u, c, d);
+        let end = fan
+            .iter()
+            .position(|&node| table.at[node.index()][d].is_none())
+            .expect("the inverted path leaves d missing at some node of the fan");
+        for i in 0..end {
+            let color = table.color(u, fan[i + 1]).expect("fan edges are colored");
+            table.set(u, fan[i + 1], color, false);
+            table.set(u, fan[i], color, true);
+        }
+        table.set(u, fan[end], d, true);
+    }
+
+    let colors: Vec<usize> = graph
+        .edges()
+        .map(|(_, u, v, _)| table.color(u, v).expect("every edge is colored"))
+        .collect();
+    let color_count = colors.iter().map(|&color| color + 1).max().unwrap_or(0);
+    Ok(EdgeColoring {
+        colors,
+        color_count,
+        max_degree,
+    })
+}
+
+/// For each node and color, the neighbor across the edge of that color, if any.
+struct Table {
+    at: Vec<Vec<Option<NodeId>>>,
+}
+
+impl Table {
+    /// The lowest color on none of `node`'s edges.
+    fn missing(&self, node: NodeId) -> usize {
+        self.at[node.index()]
+            .iter()
+            .position(Option::is_none)
+            .expect("a node has fewer edges than colors")
+    }
+
+    fn color(&self, u: NodeId, v: NodeId) -> Option<usize> {
+        self.at[u.index()].iter().position(|&w| w == Some(v))
+    }
+
+    /// Give the edge `u-v` `color`, or take it away.
+    fn set(&mut self, u: NodeId, v: NodeId, color: usize, on: bool) {
+        self.at[u.index()][color] = if on { Some(v) } else { None };
+        self.at[v.index()][color] = if on { Some(u) } else { None };
+    }
+
+    /// Swap colors `c` and `d` along the path from `u` whose edges alternate between them,
+    /// starting with `d`.
+    fn invert(&mut self, u: NodeId, c: usize, d: usize) {
+        let mut path = Vec::new();
+        let (mut node, mut color) = (u, d);
+        while let Some(next) = self.at[node.index()][color] {
+            path.push((node, next, color));
+            node = next;
+            color = if color == d { c } else { d };
+        }
+        for &(a, b, color) in &path {
+            self.set(a, b, color, false);
+        }
+        for &(a, b, color) in &path {
+            self.set(a, b, if color == d { c } else { d }, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn random_graphs_get_proper_colorings() {
+        let mut rng = Rng::new(3);
+        for _ in 0..200 {
+            let n = 2 + rng.below(14);
+            let mut graph = Graph::new();
+            let nodes: Vec<NodeId> = (0..n)
+                .map(|i| graph.get_or_insert_node(&i.to_string()))
+                .collect();
+            let p = rng.next_f64();
+            for i in 0..n {
+                for j in i + 1..n {
+                    if rng.chance(p) {
+                        graph.add_bidirectional_edge(nodes[i], nodes[j], 1);
+                    }
+                }
+            }
+            let coloring = edge_coloring(&graph).unwrap();
+            assert!(coloring.color_count <= coloring.max_degree + 1);
+            let mut seen = vec![vec![false; coloring.color_count]; n];
+            for ((_, u, v, _), &color) in graph.edges().zip(&coloring.colors) {
+                for node in [u, v] {
+                    assert!(!seen[node.index()][color]);
+                    seen[node.index()][color] = true;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::dijkstra;
+use std::collections::VecDeque;
+
+/// How far around a node to look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radius {
+    /// At most this many edges away.
+    Hops(usize),
+    /// At most this far by shortest path cost.
+    Cost(Weight),
+}
+
+/// The nodes within `radius` of `center`, in id order, following edges in their direction.
+pub fn neighborhood(graph: &Graph, center: NodeId, radius: Radius) -> Vec<NodeId> {
+    let mut within = vec![false; graph.node_count()];
+    match radius {
+        Radius::Hops(hops) => {
+            let mut queue = VecDeque::from([(center, 0)]);
+            within[center.index()] = true;
+            while let Some((node, depth)) = queue.pop_front() {
+                if depth == hops {
+                    continue;
+                }
+                for edge in graph.edges_of(node) {
+                    if !within[edge.node.index()] {
+                        within[edge.node.index()] = true;
+                        queue.push_back((edge.node, depth + 1));
+                    }
+                }
+            }
+        }
+        Radius::Cost(limit) => {
+            // Every node within the limit is settled, so its distance is exact.
+            let tree = dijkstra(graph, center, None, Some(limit), |_, _| true);
+            for node in graph.node_ids() {
+                within[node.index()] = tree.distance(node).is_some_and(|d| d <= limit);
+            }
+        }
+    }
+    graph
+        .node_ids()
+        .filter(|node| within[node.index()])
+        .collect()
+}
+
+/// The subgraph induced by the nodes within `radius` of `center`: the usual way to look at
+/// the structure around one point of interest in a graph too big to draw whole.
+///
+/// ```
+/// use cs365_bonus::ego::{ego_graph, Radius};
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1\nc d 1\na c 5".parse().unwrap();
+/// let a = graph.get_node("a").unwrap();
+/// assert_eq!(ego_graph(&graph, a, Radius::Hops(1)).node_count(), 3);
+/// let near = ego_graph(&graph, a, Radius::Cost(1));
+/// assert_eq!((near.node_count(), near.edge_count()), (2, 1));
+/// ```
+pub fn ego_graph(graph: &Graph, center: NodeId, radius: Radius) -> Graph {
+    let mut keep = vec![false; graph.node_count()];
+    for node in neighborhood(graph, center, radius) {
+        keep[node.index()] = true;
+    }
+    graph.induced_subgraph(|node| keep[node.index()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// The fewest edges from `center` to every node, by relaxing every edge until nothing
+    /// changes.
+    fn hop_counts(graph: &Graph, center: NodeId) -> Vec<Option<usize>> {
+        let mut hops = vec![None; graph.node_count()];
+        hops[center.index()] = Some(0);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in graph.node_ids() {
+                if let Some(depth) = hops[node.index()] {
+                    for edge in graph.edges_of(node) {
+                        if hops[edge.node.index()].is_none_or(|known| depth + 1 < known) {
+                            hops[edge.node.index()] = Some(depth + 1);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        hops
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = Rng::new(42);
+        for _ in 0..100 {
+            let n = 1 + rng.below(15);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(2 * n) {
+                let (u, v) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                let cost = rng.below(10) as Weight;
+                if rng.chance(0.5) {
+                    graph.add_edge(u, v, cost);
+                } else {
+                    graph.add_bidirectional_edge(u, v, cost);
+                }
+            }
+            let center = NodeId::new(rng.below(n));
+            let (hops, limit) = (rng.below(4), rng.below(15) as Weight);
+            let counts = hop_counts(&graph, center);
+            let tree = crate::shortest_path_tree(&graph, center);
+            let expected = |radius| -> Vec<NodeId> {
+                graph
+                    .node_ids()
+                    .filter(|node| match radius {
+                        Radius::Hops(hops) => counts[node.index()].is_some_and(|c| c <= hops),
+                        Radius::Cost(limit) => tree.distance(*node).is_some_and(|d| d <= limit),
+                    })
+                    .collect()
+            };
+            for radius in [Radius::Hops(hops), Radius::Cost(limit)] {
+                let within = neighborhood(&graph, center, radius);
+                assert_eq!(within, expected(radius));
+                assert!(within.contains(&center));
+
+                let ego = ego_graph(&graph, center, radius);
+                let kept = |node: NodeId| within.contains(&node);
+                let edges = graph.edges().filter(|&(_, u, v, _)| kept(u) && kept(v));
+                assert_eq!(ego.node_count(), within.len());
+                assert_eq!(ego.edge_count(), edges.count());
+            }
+        }
+    }
+
+    #[test]
+    fn follows_edges_in_their_direction() {
+        let graph: Graph = "a b 1 ->\nc a 1 ->".parse().unwrap();
+        let a = graph.get_node("a").unwrap();
+        let names: Vec<&str> = neighborhood(&graph, a, Radius::Hops(5))
+            .into_iter()
+            .map(|node| graph.get_node_name(node).unwrap())
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(neighborhood(&graph, a, Radius::Cost(0)), [a]);
+    }
+}
@@ -0,0 +1,279 @@
+use std::str::FromStr;
+
+/// A small arithmetic expression over named values, such as `distance / speed`.
+///
+/// Expressions may use numbers, names made of letters, digits, `_`, and `.`, the operators
+/// `+ - * / ^` with the usual precedence, parentheses, and the functions `min`, `max`,
+/// `abs`, and `sqrt`. Names are looked up when the expression is evaluated.
+///
+/// ```
+/// use cs365_bonus::expr::Expr;
+///
+/// let expr: Expr = "distance / speed + max(0, climb) * 2".parse().unwrap();
+/// let value = expr.eval(&mut |name| match name {
+///     "distance" => Some(120.0),
+///     "speed" => Some(60.0),
+///     "climb" => Some(-3.0),
+///     _ => None,
+/// });
+/// assert_eq!(value, Ok(2.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Name(String),
+    Negate(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Min,
+    Max,
+    Abs,
+    Sqrt,
+}
+
+impl Expr {
+    /// The value of this expression, with `lookup` giving the value of each name. Fails on
+    /// the first name `lookup` doesn't know.
+    pub fn eval(&self, lookup: &mut impl FnMut(&str) -> Option<f64>) -> Result<f64, String> {
+        Ok(match self {
+            Expr::Number(value) => *value,
+            Expr::Name(name) => lookup(name).ok_or_else(|| format!("no value for '{}'", name))?,
+            Expr::Negate(expr) => -expr.eval(lookup)?,
+            Expr::Binary(operator, left, right) => {
+                let (left, right) = (left.eval(lookup)?, right.eval(lookup)?);
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                    Operator::Power => left.powf(right),
+                }
+            }
+            Expr::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(lookup))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match function {
+                    Function::Min => args.into_iter().fold(f64::INFINITY, f64::min),
+                    Function::Max => args.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                    Function::Abs => args[0].abs(),
+                    Function::Sqrt => args[0].sqrt(),
+                }
+            }
+        })
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+        let expr = parser.sum()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {} in '{}'", token.describe(), s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Number(value) => format!("number {}", value),
+            Token::Name(name) => format!("name '{}'", name),
+            Token::Symbol(c) => format!("'{}'", c),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let number = s[start..end]
+                .parse()
+                .map_err(|_| format!("invalid number '{}'", &s[start..end]))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Name(s[start..end].to_string()));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push(Token::Symbol(c));
+            chars.next();
+        } else {
+            return Err(format!("unexpected '{}' in '{}'", c, s));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser, one method per precedence level.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn eat(&mut self, symbol: char) -> bool {
+        let found = self.tokens.get(self.position) == Some(&Token::Symbol(symbol));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        if self.eat(symbol) {
+            return Ok(());
+        }
+        match self.tokens.get(self.position) {
+            Some(token) => Err(format!("expected '{}', found {}", symbol, token.describe())),
+            None => Err(format!("expected '{}' at the end", symbol)),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        loop {
+            let operator = if self.eat('+') {
+                Operator::Add
+            } else if self.eat('-') {
+                Operator::Subtract
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(operator, Box::new(expr), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            let operator = if self.eat('*') {
+                Operator::Multiply
+            } else if self.eat('/') {
+                Operator::Divide
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(operator, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        let base = self.atom()?;
+        if self.eat('^') {
+            // Right associative, and binding tighter than a minus sign on its left.
+            return Ok(Expr::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        match token {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Symbol('(')) => {
+                let expr = self.sum()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(Token::Name(name)) if self.eat('(') => {
+                let (function, arity) = match name.as_str() {
+                    "min" => (Function::Min, None),
+                    "max" => (Function::Max, None),
+                    "abs" => (Function::Abs, Some(1)),
+                    "sqrt" => (Function::Sqrt, Some(1)),
+                    _ => return Err(format!("unknown function '{}'", name)),
+                };
+                let mut args = vec![self.sum()?];
+                while self.eat(',') {
+                    args.push(self.sum()?);
+                }
+                self.expect(')')?;
+                if arity.is_some_and(|arity| arity != args.len()) {
+                    return Err(format!("'{}' takes one argument", name));
+                }
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::Name(name)) => Ok(Expr::Name(name)),
+            Some(token) => Err(format!("unexpected {}", token.describe())),
+            None => Err("unexpected end of expression".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(s: &str) -> Result<f64, String> {
+        s.parse::<Expr>()?
+            .eval(&mut |name| (name == "x").then_some(3.0))
+    }
+
+    #[test]
+    fn follows_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(eval("10 - 4 - 3"), Ok(3.0));
+        assert_eq!(eval("-x ^ 2"), Ok(-9.0));
+        assert_eq!(eval("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(eval("min(x, 1.5, 4) + abs(-2)"), Ok(3.5));
+        assert!(eval("y").is_err());
+        assert!(eval("1 +").is_err());
+        assert!(eval("abs(1, 2)").is_err());
+        assert!(eval("(1").is_err());
+    }
+}
@@ -0,0 +1,574 @@
+use crate::arena::ParseArena;
+use crate::decimal::FixedPoint;
+use crate::graph::{Graph, NodeId, Weight};
+use crate::parse::{parse_edge, ParseErrorKind, ParseGraphError};
+use crate::path::{Path, ShortestPathTree};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+
+/// The first bytes of every external graph file.
+pub const MAGIC: &[u8; 8] = b"CS365EXT";
+
+/// Size of the header: the magic, the node count, and the adjacency entry count.
+const HEADER: u64 = 24;
+
+/// Size of one adjacency entry: the neighbor and the cost, as little-endian `u64`s.
+const ENTRY: u64 = 16;
+
+/// How many edge list lines the streaming converter places per batch of writes.
+const BATCH_LINES: usize = 1 << 20;
+
+/// Write `graph` as a CSR file that [`ExternalGraph`] searches from disk.
+///
+/// The layout is the header, `n + 1` adjacency offsets, the adjacency entries, and then
+/// the node names as length-prefixed strings, all integers little-endian `u64`s so that
+/// no value straddles a page. Adjacency lists are stored in node order, so renumbering
+/// the graph with [`reorder`](crate::reorder) first keeps a search's reads close together.
+pub fn write_external(graph: &Graph, writer: &mut dyn Write) -> std::io::Result<()> {
+    let entries: usize = graph.node_ids().map(|n| graph.edges_of(n).len()).sum();
+    writer.write_all(MAGIC)?;
+    writer.write_all(&(graph.node_count() as u64).to_le_bytes())?;
+    writer.write_all(&(entries as u64).to_le_bytes())?;
+    let mut offset = 0u64;
+    writer.write_all(&offset.to_le_bytes())?;
+    for node in graph.node_ids() {
+        offset += graph.edges_of(node).len() as u64;
+        writer.write_all(&offset.to_le_bytes())?;
+    }
+    for node in graph.node_ids() {
+        for edge in graph.edges_of(node) {
+            writer.write_all(&(edge.node.index() as u64).to_le_bytes())?;
+            writer.write_all(&(edge.cost as u64).to_le_bytes())?;
+        }
+    }
+    write_names(
+        writer,
+        graph
+            .node_ids()
+            .map(|n| graph.get_node_name(n).unwrap_or("")),
+    )?;
+    writer.flush()
+}
+
+fn write_names<'a>(
+    writer: &mut dyn Write,
+    names: impl Iterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    for name in names {
+        writer.write_all(&(name.len() as u64).to_le_bytes())?;
+        writer.write_all(name.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn invalid_line(line: usize, kind: ParseErrorKind) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        ParseGraphError::new(line, kind),
+    )
+}
+
+/// Convert the edge list at `input` to an external graph file at `output`
+/// without loading the edges into memory.
+///
+/// Two passes over the input: the first interns names and counts degrees, the second
+/// places each edge into its slots in the output in batches sorted by file position.
+/// Only the names and per-node offsets are kept in memory.
+pub fn convert_edge_list(input: &str, output: &str) -> std::io::Result<()> {
+    let mut arena = ParseArena::with_capacity(0);
+    let mut degree: Vec<u64> = Vec::new();
+    for (i, line) in BufReader::new(File::open(input)?).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (src, dest, _, directed) =
+            parse_edge(line, FixedPoint::INTEGER).map_err(|kind| invalid_line(i + 1, kind))?;
+        for (name, leaves) in [(src, true), (dest, !directed)] {
+            let id = arena
+                .intern(name)
+                .ok_or_else(|| invalid_line(i + 1, ParseErrorKind::TooManyNodes))?
+                as usize;
+            if id == degree.len() {
+                degree.push(0);
+            }
+            degree[id] += leaves as u64;
+        }
+    }
+
+    let n = arena.node_count();
+    let mut offsets = Vec::with_capacity(n + 1);
+    offsets.push(0u64);
+    for d in degree.iter() {
+        offsets.push(offsets[offsets.len() - 1] + d);
+    }
+    let entries = offsets[n];
+    let edges_start = HEADER + 8 * (n as u64 + 1);
+
+    let mut file = File::create(output)?;
+    {
+        let mut writer = BufWriter::new(&mut file);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(n as u64).to_le_bytes())?;
+        writer.write_all(&entries.to_le_bytes())?;
+        for offset in offsets.iter() {
+            writer.write_all(&offset.to_le_bytes())?;
+        }
+        writer.flush()?;
+    }
+    file.set_len(edges_start + entries * ENTRY)?;
+
+    // `offsets[v]` now serves as the next free slot of `v`.
+    let mut batch: Vec<(u64, u64, u64)> = Vec::with_capacity(2 * BATCH_LINES);
+    let flush = |file: &mut File, batch: &mut Vec<(u64, u64, u64)>| -> std::io::Result<()> {
+        batch.sort_unstable();
+        let mut i = 0;
+        while i < batch.len() {
+            // Write each run of consecutive slots with a single call.
+            let mut j = i + 1;
+            while j < batch.len() && batch[j].0 == batch[j - 1].0 + 1 {
+                j += 1;
+            }
+            let mut bytes = Vec::with_capacity((j - i) * ENTRY as usize);
+            for &(_, node, cost) in batch[i..j].iter() {
+                bytes.extend_from_slice(&node.to_le_bytes());
+                bytes.extend_from_slice(&cost.to_le_bytes());
+            }
+            file.seek(SeekFrom::Start(edges_start + batch[i].0 * ENTRY))?;
+            file.write_all(&bytes)?;
+            i = j;
+        }
+        batch.clear();
+        Ok(())
+    };
+    for (i, line) in BufReader::new(File::open(input)?).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (src, dest, cost, directed) =
+            parse_edge(line, FixedPoint::INTEGER).map_err(|kind| invalid_line(i + 1, kind))?;
+        // Every name was interned by the first pass, so these are lookups.
+        let mut id = |name| arena.intern(name).expect("interned by the first pass") as usize;
+        let (src, dest) = (id(src), id(dest));
+        let reverse = (!directed).then_some((dest, src));
+        for (from, to) in std::iter::once((src, dest)).chain(reverse) {
+            batch.push((offsets[from], to as u64, cost as u64));
+            offsets[from] += 1;
+        }
+        if batch.len() >= 2 * BATCH_LINES {
+            flush(&mut file, &mut batch)?;
+        }
+    }
+    flush(&mut file, &mut batch)?;
+
+    file.seek(SeekFrom::End(0))?;
+    let mut writer = BufWriter::new(&mut file);
+    write_names(&mut writer, (0..n as u32).map(|id| arena.name(id)))?;
+    writer.flush()
+}
+
+/// Page cache statistics for an [`ExternalGraph`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub bytes_read: u64,
+}
+
+struct Page {
+    data: Vec<u8>,
+    used: u64,
+}
+
+/// A graph file written by [`write_external`] or [`convert_edge_list`], read through a
+/// bounded page cache instead of being loaded.
+///
+/// Searches still keep a distance and parent per node in memory, but the adjacency
+/// entries, which dominate the size of a graph, are only read as nodes are expanded.
+/// Explicit reads are used rather than a memory map so that memory use stays within the
+/// configured budget on any platform.
+pub struct ExternalGraph {
+    file: File,
+    node_count: usize,
+    names_start: u64,
+    page_size: u64,
+    capacity: usize,
+    pages: HashMap<u64, Page>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl ExternalGraph {
+    /// Open the file at `path`, caching up to `cache_bytes` of it in pages of
+    /// `page_size` bytes, which is rounded up to a multiple of 8.
+    pub fn open(path: &str, page_size: usize, cache_bytes: usize) -> std::io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut header = [0; HEADER as usize];
+        file.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "not an external graph file",
+            ));
+        }
+        let word = |i: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&header[i..i + 8]);
+            u64::from_le_bytes(bytes)
+        };
+        let (nodes, entries) = (word(8), word(16));
+        let names_start = nodes
+            .checked_add(1)
+            .and_then(|offsets| offsets.checked_mul(8))
+            .and_then(|offsets| entries.checked_mul(ENTRY)?.checked_add(offsets))
+            .and_then(|body| body.checked_add(HEADER))
+            .filter(|&end| end <= file.metadata().map_or(0, |m| m.len()))
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, "truncated external graph")
+            })?;
+        let page_size = page_size.max(8).div_ceil(8) as u64 * 8;
+        Ok(Self {
+            file,
+            node_count: nodes as usize,
+            names_start,
+            page_size,
+            capacity: (cache_bytes / page_size as usize).max(1),
+            pages: HashMap::new(),
+            clock: 0,
+            stats: CacheStats::default(),
+        })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Make sure page `page` is cached, evicting the least recently used page if full.
+    fn load(&mut self, page: u64) -> std::io::Result<()> {
+        self.clock += 1;
+        if let Some(cached) = self.pages.get_mut(&page) {
+            cached.used = self.clock;
+            self.stats.hits += 1;
+            return Ok(());
+        }
+        if self.pages.len() >= self.capacity {
+            let oldest = self
+                .pages
+                .iter()
+                .min_by_key(|(_, p)| p.used)
+                .map(|(&k, _)| k);
+            if let Some(oldest) = oldest {
+                self.pages.remove(&oldest);
+            }
+        }
+        let mut data = Vec::with_capacity(self.page_size as usize);
+        self.file.seek(SeekFrom::Start(page * self.page_size))?;
+        (&mut self.file)
+            .take(self.page_size)
+            .read_to_end(&mut data)?;
+        self.stats.misses += 1;
+        self.stats.bytes_read += data.len() as u64;
+        self.pages.insert(
+            page,
+            Page {
+                data,
+                used: self.clock,
+            },
+        );
+        Ok(())
+    }
+
+    /// The bytes `start..start + len` of the file.
+    fn read(&mut self, start: u64, len: u64) -> std::io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(len as usize);
+        let mut pos = start;
+        while pos < start + len {
+            let page = pos / self.page_size;
+            self.load(page)?;
+            let data = &self.pages[&page].data;
+            let from = (pos - page * self.page_size) as usize;
+            let to = ((start + len - page * self.page_size) as usize).min(data.len());
+            if from >= to {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            bytes.extend_from_slice(&data[from..to]);
+            pos += (to - from) as u64;
+        }
+        Ok(bytes)
+    }
+
+    fn read_u64(&mut self, pos: u64) -> std::io::Result<u64> {
+        let bytes = self.read(pos, 8)?;
+        let mut word = [0; 8];
+        word.copy_from_slice(&bytes);
+        Ok(u64::from_le_bytes(word))
+    }
+
+    fn offset_position(node: NodeId) -> u64 {
+        HEADER + 8 * node.index() as u64
+    }
+
+    fn edges_start(&self) -> u64 {
+        HEADER + 8 * (self.node_count as u64 + 1)
+    }
+
+    /// The position and length of the entries of the edges leaving `node`, which must lie
+    /// within the edges section.
+    fn edge_range(&mut self, node: NodeId) -> std::io::Result<(u64, u64)> {
+        let at = Self::offset_position(node);
+        let (first, last) = (self.read_u64(at)?, self.read_u64(at + 8)?);
+        let start = first
+            .checked_mul(ENTRY)
+            .and_then(|offset| offset.checked_add(self.edges_start()));
+        let len = last
+            .checked_sub(first)
+            .and_then(|count| count.checked_mul(ENTRY));
+        start
+            .zip(len)
+            .filter(|&(start, len)| {
+                start
+                    .checked_add(len)
+                    .is_some_and(|end| end <= self.names_start)
+            })
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "corrupt external graph offsets",
+                )
+            })
+    }
+
+    /// The neighbors of `node` and the costs of the edges to them.
+    pub fn edges_of(&mut self, node: NodeId) -> std::io::Result<Vec<(NodeId, Weight)>> {
+        let (start, len) = self.edge_range(node)?;
+        let bytes = self.read(start, len)?;
+        Ok(bytes
+            .chunks_exact(ENTRY as usize)
+            .map(|entry| {
+                let mut node = [0; 8];
+                let mut cost = [0; 8];
+                node.copy_from_slice(&entry[..8]);
+                cost.copy_from_slice(&entry[8..]);
+                (
+                    NodeId::new(u64::from_le_bytes(node) as usize),
+                    u64::from_le_bytes(cost) as Weight,
+                )
+            })
+            .collect())
+    }
+
+    /// Load the offset entries and first adjacency pages of `nodes` that aren't cached
+    /// yet, in file order, so that expanding them later doesn't seek back and forth.
+    pub fn prefetch(&mut self, nodes: &mut [NodeId]) -> std::io::Result<()> {
+        nodes.sort_unstable();
+        for &node in nodes.iter() {
+            let (start, _) = self.edge_range(node)?;
+            self.load(start / self.page_size)?;
+        }
+        Ok(())
+    }
+
+    /// Stream through the names section, calling `visit` with each node and its name
+    /// until it returns `false`. This bypasses the page cache.
+    fn scan_names(&mut self, mut visit: impl FnMut(NodeId, &str) -> bool) -> std::io::Result<()> {
+        let mut file = self.file.try_clone()?;
+        file.seek(SeekFrom::Start(self.names_start))?;
+        let mut reader = BufReader::new(file);
+        let mut len = [0; 8];
+        let mut name = Vec::new();
+        for i in 0..self.node_count {
+            reader.read_exact(&mut len)?;
+            name.resize(u64::from_le_bytes(len) as usize, 0);
+            reader.read_exact(&mut name)?;
+            if !visit(NodeId::new(i), &String::from_utf8_lossy(&name)) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find a node by name, scanning the names section.
+    pub fn get_node(&mut self, name: &str) -> std::io::Result<Option<NodeId>> {
+        let mut found = None;
+        self.scan_names(|node, candidate| {
+            if candidate == name {
+                found = Some(node);
+            }
+            found.is_none()
+        })?;
+        Ok(found)
+    }
+
+    /// The names of `nodes`, in one scan of the names section.
+    pub fn node_names(&mut self, nodes: &[NodeId]) -> std::io::Result<Vec<String>> {
+        let mut wanted: HashMap<NodeId, String> =
+            nodes.iter().map(|&n| (n, String::new())).collect();
+        let last = nodes.iter().max().copied();
+        self.scan_names(|node, name| {
+            if let Some(slot) = wanted.get_mut(&node) {
+                *slot = name.to_string();
+            }
+            Some(node) < last
+        })?;
+        Ok(nodes.iter().map(|node| wanted[node].clone()).collect())
+    }
+
+    /// The shortest path from `start` to `end`, stopping once `end` is settled.
+    ///
+    /// After expanding a node, the neighbors whose distance improved are prefetched in
+    /// file order. With a locality-preserving node order most of them share pages with
+    /// the node just expanded, so the search mostly reads the file sequentially.
+    pub fn shortest_path(&mut self, start: NodeId, end: NodeId) -> std::io::Result<Option<Path>> {
+        let mut distance: Vec<Option<Weight>> = vec![None; self.node_count];
+        let mut parent: Vec<Option<NodeId>> = vec![None; self.node_count];
+        let mut heap = BinaryHeap::new();
+        if start.index() < self.node_count {
+            distance[start.index()] = Some(0);
+            heap.push(Reverse((0, start)));
+        }
+        let mut improved = Vec::new();
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if distance[node.index()].is_some_and(|best| cost > best) {
+                continue;
+            }
+            if node == end {
+                break;
+            }
+            for (target, weight) in self.edges_of(node)? {
+                if target.index() >= self.node_count {
+                    return Err(std::io::ErrorKind::InvalidData.into());
+                }
+                let next = cost + weight;
+                if distance[target.index()].is_none_or(|best| next < best) {
+                    distance[target.index()] = Some(next);
+                    parent[target.index()] = Some(node);
+                    heap.push(Reverse((next, target)));
+                    improved.push(target);
+                }
+            }
+            self.prefetch(&mut improved)?;
+            improved.clear();
+        }
+        Ok(ShortestPathTree::new(start, distance, parent).path_to(end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn temp_path(name: &str) -> String {
+        let file = format!("cs365-external-{}-{}", std::process::id(), name);
+        std::env::temp_dir()
+            .join(file)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn searches_from_disk_match_the_graph() {
+        let mut rng = Rng::new(34);
+        let (text_path, written, converted) = (
+            temp_path("in.txt"),
+            temp_path("written"),
+            temp_path("converted"),
+        );
+        for _ in 0..10 {
+            let n = 1 + rng.below(40);
+            let mut text = String::new();
+            for _ in 0..rng.below(3 * n) {
+                let arrow = if rng.chance(0.5) { " ->" } else { "" };
+                let (a, b, cost) = (rng.below(n), rng.below(n), rng.below(20));
+                text.push_str(&format!("n{} n{} {}{}\n\n", a, b, cost, arrow));
+            }
+            text.push_str("n0 n0 0\n");
+            let graph: Graph = text.parse().unwrap();
+            std::fs::write(&text_path, &text).unwrap();
+            write_external(&graph, &mut File::create(&written).unwrap()).unwrap();
+            convert_edge_list(&text_path, &converted).unwrap();
+            // Both writers lay out nodes and edges in the same order.
+            assert_eq!(
+                std::fs::read(&written).unwrap(),
+                std::fs::read(&converted).unwrap()
+            );
+
+            // A cache of two tiny pages forces evictions on almost every read.
+            let mut external = ExternalGraph::open(&written, 16, 32).unwrap();
+            assert_eq!(external.node_count(), graph.node_count());
+            for node in graph.node_ids() {
+                let expected: Vec<(NodeId, Weight)> = graph
+                    .edges_of(node)
+                    .iter()
+                    .map(|e| (e.node, e.cost))
+                    .collect();
+                assert_eq!(external.edges_of(node).unwrap(), expected);
+            }
+            let mut nodes: Vec<NodeId> = graph.node_ids().collect();
+            nodes.reverse();
+            let names = external.node_names(&nodes).unwrap();
+            for (node, name) in nodes.iter().zip(names.iter()) {
+                assert_eq!(graph.get_node_name(*node), Some(name.as_str()));
+                assert_eq!(external.get_node(name).unwrap(), Some(*node));
+            }
+            assert_eq!(external.get_node("missing").unwrap(), None);
+
+            let (start, end) = (NodeId::new(rng.below(graph.node_count())), NodeId::new(0));
+            let expected = crate::find_shortest_path(&graph, start, end);
+            let found = external.shortest_path(start, end).unwrap();
+            assert_eq!(found.map(|p| p.cost), expected.map(|p| p.cost));
+            let stats = external.stats();
+            assert!(stats.misses > 0 && stats.bytes_read <= stats.misses * 16);
+        }
+        for path in [text_path, written, converted] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let path = temp_path("bad");
+        std::fs::write(&path, b"CS365EXX\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0").unwrap();
+        assert!(ExternalGraph::open(&path, 64, 1024).is_err());
+        let mut truncated = MAGIC.to_vec();
+        truncated.extend_from_slice(&5u64.to_le_bytes());
+        truncated.extend_from_slice(&0u64.to_le_bytes());
+        std::fs::write(&path, truncated).unwrap();
+        assert!(ExternalGraph::open(&path, 64, 1024).is_err());
+        std::fs::write(&path, "a b 1\nb c\n").unwrap();
+        let error = convert_edge_list(&path, &temp_path("bad-out")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_corrupt_offsets() {
+        let path = temp_path("corrupt");
+        let graph: Graph = "a b 1\nb c 2".parse().unwrap();
+        let mut data = Vec::new();
+        write_external(&graph, &mut data).unwrap();
+        let offset = |node: u64| (HEADER + 8 * node) as usize;
+        // Offsets running backwards, and running past the edges section.
+        for (node, value) in [(2, 0), (3, u64::MAX / 2)] {
+            let mut corrupt = data.clone();
+            corrupt[offset(node)..offset(node) + 8].copy_from_slice(&u64::to_le_bytes(value));
+            std::fs::write(&path, corrupt).unwrap();
+            let mut external = ExternalGraph::open(&path, 64, 1024).unwrap();
+            let broken = NodeId::new(node as usize - 1);
+            let error = external.edges_of(broken).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+            assert!(external.prefetch(&mut [broken]).is_err());
+            assert_eq!(external.edges_of(NodeId::new(0)).unwrap().len(), 1);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::SearchContext;
+use std::str::FromStr;
+
+/// What a choice of facilities tries to keep small.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Objective {
+    /// The sum over all nodes of the distance to the nearest facility: the p-median.
+    #[default]
+    Total,
+    /// The largest distance from any node to its nearest facility: the p-center.
+    Max,
+}
+
+impl FromStr for Objective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "total" => Ok(Objective::Total),
+            "max" => Ok(Objective::Max),
+            _ => Err(format!(
+                "unknown objective '{}', expected 'total' or 'max'",
+                s
+            )),
+        }
+    }
+}
+
+/// A set of facility nodes and how well it serves the graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Facilities {
+    /// The chosen nodes, in id order.
+    pub nodes: Vec<NodeId>,
+    /// The objective's value over the nodes some facility reaches.
+    pub cost: Weight,
+    /// How many nodes no facility reaches.
+    pub unreached: usize,
+}
+
+/// Choose `k` facility nodes that keep the distance from every node to its nearest facility
+/// small under `objective`.
+///
+/// Facilities are first added greedily, each time picking the node that most improves the
+/// objective, then swapped one at a time for any other node while that helps. Reaching more
+/// nodes always counts for more than a lower cost. The result is a local optimum, not
+/// necessarily the best possible choice, which is NP-hard to find.
+///
+/// Each candidate is scored with one search from all its facilities, so a round of swaps
+/// takes `k * n` searches; this is meant for graphs of up to a few thousand nodes. Distances
+/// are measured from each node to a facility, along the direction of directed edges.
+///
+/// ```
+/// use cs365_bonus::facility::{p_median, Objective};
+///
+/// // Two triangles joined by a long edge want one facility each.
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1\na c 1\nc d 10\nd e 1\ne f 1\nd f 1"
+///     .parse()
+///     .unwrap();
+/// let facilities = p_median(&graph, 2, Objective::Total);
+/// assert_eq!((facilities.cost, facilities.unreached), (4, 0));
+/// ```
+pub fn p_median(graph: &Graph, k: usize, objective: Objective) -> Facilities {
+    // Searching the reversed graph from the facilities measures distances toward them.
+    let reversed = graph.reversed();
+    let mut context = SearchContext::new();
+    let mut score = |nodes: &[NodeId]| {
+        let distances = context.nearest_source_distances(&reversed, nodes);
+        let reached = distances.iter().flatten();
+        let cost = match objective {
+            Objective::Total => reached.clone().sum(),
+            Objective::Max => reached.clone().copied().max().unwrap_or(0),
+        };
+        (distances.len() - reached.count(), cost)
+    };
+
+    let k = k.min(graph.node_count());
+    let mut chosen: Vec<NodeId> = Vec::with_capacity(k);
+    let mut is_chosen = vec![false; graph.node_count()];
+    let mut best = score(&chosen);
+    while chosen.len() < k {
+        let mut pick = None;
+        for candidate in graph.node_ids().filter(|node| !is_chosen[node.index()]) {
+            chosen.push(candidate);
+            let value = score(&chosen);
+            chosen.pop();
+            if pick.is_none_or(|(_, best)| value < best) {
+                pick = Some((candidate, value));
+            }
+        }
+        let (node, value) = pick.expect("fewer facilities than nodes leaves a candidate");
+        chosen.push(node);
+        is_chosen[node.index()] = true;
+        best = value;
+    }
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..chosen.len() {
+            for candidate in graph.node_ids() {
+                if is_chosen[candidate.index()] {
+                    continue;
+                }
+                let previous = std::mem::replace(&mut chosen[i], candidate);
+                let value = score(&chosen);
+                if value < best {
+                    is_chosen[previous.index()] = false;
+                    is_chosen[candidate.index()] = true;
+                    best = value;
+                    improved = true;
+                } else {
+                    chosen[i] = previous;
+                }
+            }
+        }
+    }
+
+    chosen.sort_unstable();
+    Facilities {
+        nodes: chosen,
+        cost: best.1,
+        unreached: best.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// `(unreached, cost)` of `facilities`, from a shortest path tree out of every node.
+    fn evaluate(graph: &Graph, facilities: &[NodeId], objective: Objective) -> (usize, Weight) {
+        let nearest: Vec<Option<Weight>> = graph
+            .node_ids()
+            .map(|node| {
+                let tree = crate::shortest_path_tree(graph, node);
+                facilities.iter().filter_map(|&f| tree.distance(f)).min()
+            })
+            .collect();
+        let reached = nearest.iter().flatten();
+        let cost = match objective {
+            Objective::Total => reached.clone().sum(),
+            Objective::Max => reached.clone().copied().max().unwrap_or(0),
+        };
+        (nearest.len() - reached.count(), cost)
+    }
+
+    #[test]
+    fn finds_local_optima() {
+        let mut rng = Rng::new(43);
+        for _ in 0..60 {
+            let n = 1 + rng.below(8);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(2 * n) {
+                let (u, v) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                let cost = rng.below(10) as Weight;
+                if rng.chance(0.3) {
+                    graph.add_edge(u, v, cost);
+                } else {
+                    graph.add_bidirectional_edge(u, v, cost);
+                }
+            }
+            let objective = if rng.chance(0.5) {
+                Objective::Total
+            } else {
+                Objective::Max
+            };
+            let k = 1 + rng.below(3);
+            let found = p_median(&graph, k, objective);
+            assert_eq!(found.nodes.len(), k.min(n));
+            assert!(found.nodes.windows(2).all(|w| w[0] < w[1]));
+            let value = (found.unreached, found.cost);
+            assert_eq!(evaluate(&graph, &found.nodes, objective), value);
+
+            // No single swap helps, which for one facility makes it the best choice.
+            for i in 0..found.nodes.len() {
+                for other in graph.node_ids().filter(|node| !found.nodes.contains(node)) {
+                    let mut swapped = found.nodes.clone();
+                    swapped[i] = other;
+                    assert!(evaluate(&graph, &swapped, objective) >= value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unreachable_nodes_count_before_cost() {
+        // `b` is cheap to serve from `a`, but `d` is reached by more nodes.
+        let graph: Graph = "a b 1\nc d 100 ->\ne d 100 ->".parse().unwrap();
+        let found = p_median(&graph, 1, Objective::Max);
+        assert_eq!((found.unreached, found.cost), (2, 100));
+        let every = p_median(&graph, 10, Objective::Total);
+        assert_eq!((every.nodes.len(), every.unreached, every.cost), (5, 0, 0));
+        assert_eq!("max".parse(), Ok(Objective::Max));
+        assert!("median".parse::<Objective>().is_err());
+    }
+}
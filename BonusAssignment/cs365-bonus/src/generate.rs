@@ -0,0 +1,59 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::rng::Rng;
+
+/// A graph on `nodes` nodes named `0`, `1`, ... with `edges` undirected edges between
+/// uniformly random pairs of distinct nodes, costing between 1 and `max_cost`.
+///
+/// Parallel edges may occur, as in the G(n, m) model with replacement.
+///
+/// ```
+/// use cs365_bonus::generate::random_graph;
+/// use cs365_bonus::rng::Rng;
+///
+/// let graph = random_graph(100, 300, 10, &mut Rng::new(7));
+/// assert_eq!((graph.node_count(), graph.edge_count()), (100, 300));
+/// ```
+pub fn random_graph(nodes: usize, edges: usize, max_cost: Weight, rng: &mut Rng) -> Graph {
+    let mut graph = Graph::with_capacity(nodes, edges);
+    let ids: Vec<NodeId> = (0..nodes)
+        .map(|i| graph.get_or_insert_node(&i.to_string()))
+        .collect();
+    if nodes < 2 {
+        return graph;
+    }
+    for _ in 0..edges {
+        let src = rng.below(nodes);
+        // Pick from the other n - 1 nodes, so there are no self-loops.
+        let dest = (src + 1 + rng.below(nodes - 1)) % nodes;
+        graph.add_bidirectional_edge(ids[src], ids[dest], cost(max_cost, rng));
+    }
+    graph
+}
+
+/// A `width` by `height` grid whose nodes are named `x,y`, each joined to its horizontal
+/// and vertical neighbors by undirected edges costing between 1 and `max_cost`.
+pub fn grid_graph(width: usize, height: usize, max_cost: Weight, rng: &mut Rng) -> Graph {
+    let mut graph = Graph::with_capacity(width * height, 2 * width * height);
+    let mut ids = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            ids.push(graph.get_or_insert_node(&format!("{},{}", x, y)));
+        }
+    }
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            if x + 1 < width {
+                graph.add_bidirectional_edge(ids[i], ids[i + 1], cost(max_cost, rng));
+            }
+            if y + 1 < height {
+                graph.add_bidirectional_edge(ids[i], ids[i + width], cost(max_cost, rng));
+            }
+        }
+    }
+    graph
+}
+
+fn cost(max_cost: Weight, rng: &mut Rng) -> Weight {
+    1 + rng.below(max_cost.max(1) as usize) as Weight
+}
@@ -0,0 +1,769 @@
+use crate::attributes::Attributes;
+use crate::decimal::FixedPoint;
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::server::json::Value;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// A position as GeoJSON writes it: longitude, then latitude, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub lon: f64,
+    pub lat: f64,
+}
+
+/// The mean radius of the Earth, in meters.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+impl Point {
+    /// Read `lat,lon` in degrees, the order maps and GPS devices usually give them.
+    ///
+    /// ```
+    /// use cs365_bonus::geo::Point;
+    ///
+    /// assert_eq!(Point::parse_lat_lon("40.7,-74.0"), Some(Point { lon: -74.0, lat: 40.7 }));
+    /// assert_eq!(Point::parse_lat_lon("91,0"), None);
+    /// assert_eq!(Point::parse_lat_lon("a"), None);
+    /// ```
+    pub fn parse_lat_lon(text: &str) -> Option<Point> {
+        let (lat, lon) = text.split_once(',')?;
+        let (lat, lon) = (
+            lat.trim().parse::<f64>().ok()?,
+            lon.trim().parse::<f64>().ok()?,
+        );
+        (lat.abs() <= 90.0 && lon.abs() <= 180.0).then_some(Point { lon, lat })
+    }
+
+    /// The great-circle distance to `other` in meters, on a spherical Earth.
+    pub fn distance(self, other: Point) -> f64 {
+        let (a, b) = (self.unit_vector(), other.unit_vector());
+        let chord = a
+            .iter()
+            .zip(&b)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt();
+        2.0 * EARTH_RADIUS * (chord / 2.0).min(1.0).asin()
+    }
+
+    /// Where this point is on the unit sphere, so that straight-line distances between
+    /// these vectors order points the same way as distances over the surface.
+    fn unit_vector(self) -> [f64; 3] {
+        let (lat, lon) = (self.lat.to_radians(), self.lon.to_radians());
+        [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+    }
+}
+
+/// Where the nodes of a graph are, for the nodes that have a position.
+///
+/// Positions come from the `lat` and `lon` node attributes, so a graph gets coordinates by
+/// way of an `--attributes` file with lines like `node a lat=40.71 lon=-74.00`.
+///
+/// ```
+/// use cs365_bonus::attributes::Attributes;
+/// use cs365_bonus::geo::{Coordinates, Point};
+///
+/// let graph: cs365_bonus::Graph = "a b 1".parse().unwrap();
+/// let attributes = Attributes::parse(&graph, "node a lat=40.71 lon=-74.00").unwrap();
+/// let coordinates = Coordinates::from_attributes(&graph, &attributes).unwrap();
+/// let (a, b) = (graph.get_node("a").unwrap(), graph.get_node("b").unwrap());
+/// assert_eq!(coordinates.get(a), Some(Point { lon: -74.0, lat: 40.71 }));
+/// assert_eq!(coordinates.get(b), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Coordinates {
+    points: Vec<Option<Point>>,
+}
+
+impl Coordinates {
+    /// Read each node's `lat` and `lon` attributes. A node may have neither, but not just
+    /// one, and both must be numbers within range.
+    pub fn from_attributes(graph: &Graph, attributes: &Attributes) -> Result<Self, String> {
+        let pairs = node_pairs(graph, attributes, [("lat", 90.0), ("lon", 180.0)])?;
+        let points = pairs
+            .into_iter()
+            .map(|pair| pair.map(|(lat, lon)| Point { lon, lat }))
+            .collect();
+        Ok(Self { points })
+    }
+
+    pub fn get(&self, node: NodeId) -> Option<Point> {
+        self.points.get(node.index()).copied().flatten()
+    }
+
+    /// Whether no node has a position.
+    pub fn is_empty(&self) -> bool {
+        self.points.iter().all(Option::is_none)
+    }
+}
+
+/// An index over the nodes with coordinates, for finding the node nearest to a point.
+///
+/// It is a k-d tree over points on the unit sphere rather than latitude and longitude, so
+/// that it stays exact across the antimeridian and near the poles. Building it takes
+/// O(n log n) time, and a query about O(log n) for points spread out evenly.
+///
+/// ```
+/// use cs365_bonus::attributes::Attributes;
+/// use cs365_bonus::geo::{Coordinates, NodeIndex, Point};
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1".parse().unwrap();
+/// let attributes = Attributes::parse(
+///     &graph,
+///     "node a lat=40.70 lon=-74.00\nnode b lat=40.75 lon=-73.98\nnode c lat=40.80 lon=-73.95",
+/// )
+/// .unwrap();
+/// let index = NodeIndex::new(&Coordinates::from_attributes(&graph, &attributes).unwrap());
+/// let (node, meters) = index.nearest(Point { lon: -73.99, lat: 40.74 }).unwrap();
+/// assert_eq!(graph.get_node_name(node), Some("b"));
+/// assert!((meters - 1400.0).abs() < 100.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct NodeIndex {
+    tree: KdTree,
+    coordinates: Coordinates,
+}
+
+impl NodeIndex {
+    pub fn new(coordinates: &Coordinates) -> Self {
+        let items = coordinates
+            .points
+            .iter()
+            .enumerate()
+            .filter_map(|(i, point)| Some((i, point.as_ref()?.unit_vector())))
+            .collect();
+        Self {
+            tree: KdTree::new(items),
+            coordinates: coordinates.clone(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.items.is_empty()
+    }
+
+    /// The node nearest to `point` and how many meters away it is, or `None` if no node
+    /// has coordinates.
+    pub fn nearest(&self, point: Point) -> Option<(NodeId, f64)> {
+        let (i, _) = *self.tree.nearest(point.unit_vector(), 1, None).first()?;
+        let node = NodeId::new(i);
+        Some((node, point.distance(self.coordinates.get(node)?)))
+    }
+}
+
+/// A k-d tree over points in space, each tagged with an index.
+#[derive(Debug, Clone)]
+struct KdTree {
+    /// Each subtree is a slice with its splitting point in the middle, split on the axis
+    /// given by its depth.
+    items: Vec<(usize, [f64; 3])>,
+}
+
+impl KdTree {
+    fn new(mut items: Vec<(usize, [f64; 3])>) -> Self {
+        build(&mut items, 0);
+        Self { items }
+    }
+
+    /// The indices of the `k` points nearest to `target`, leaving out the one tagged
+    /// `skip`, nearest first, with their squared distances.
+    fn nearest(&self, target: [f64; 3], k: usize, skip: Option<usize>) -> Vec<(usize, f64)> {
+        let mut best = Vec::with_capacity(k + 1);
+        if k > 0 {
+            nearest(&self.items, 0, target, k, skip, &mut best);
+        }
+        best
+    }
+}
+
+/// Arrange `items` so that its middle element splits the rest on axis `depth % 3`, and so
+/// on recursively for each half.
+fn build(items: &mut [(usize, [f64; 3])], depth: usize) {
+    if items.len() <= 1 {
+        return;
+    }
+    let (axis, middle) = (depth % 3, items.len() / 2);
+    items.select_nth_unstable_by(middle, |a, b| a.1[axis].total_cmp(&b.1[axis]));
+    let (left, right) = items.split_at_mut(middle);
+    build(left, depth + 1);
+    build(&mut right[1..], depth + 1);
+}
+
+/// Search the subtree `items` for points nearer to `target` than the `k` in `best`, which
+/// is kept sorted by squared distance.
+fn nearest(
+    items: &[(usize, [f64; 3])],
+    depth: usize,
+    target: [f64; 3],
+    k: usize,
+    skip: Option<usize>,
+    best: &mut Vec<(usize, f64)>,
+) {
+    if items.is_empty() {
+        return;
+    }
+    let middle = items.len() / 2;
+    let (tag, here) = items[middle];
+    let worst = |best: &Vec<(usize, f64)>| match best.len() {
+        n if n < k => f64::INFINITY,
+        n => best[n - 1].1,
+    };
+    let squared: f64 = here
+        .iter()
+        .zip(&target)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum();
+    if Some(tag) != skip && squared < worst(best) {
+        let at = best.partition_point(|&(_, distance)| distance <= squared);
+        best.insert(at, (tag, squared));
+        best.truncate(k);
+    }
+    let gap = target[depth % 3] - here[depth % 3];
+    let (left, right) = (&items[..middle], &items[middle + 1..]);
+    let (near, far) = if gap < 0.0 {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    nearest(near, depth + 1, target, k, skip, best);
+    // The far side can only hold a nearer point if the splitting plane is nearer.
+    if gap * gap < worst(best) {
+        nearest(far, depth + 1, target, k, skip, best);
+    }
+}
+
+/// A graph joining each of `points` to its `k` nearest other points, and so also to any
+/// point that counts it among its `k` nearest, at the distance between them by `metric`,
+/// written with `scale`. Points are `(name, first, second)`, with `first` and `second` the
+/// latitude and longitude for [`Metric::Haversine`] or `x` and `y` for
+/// [`Metric::Euclidean`]. Neighbors are found with a k-d tree, so building the graph takes
+/// about O(n k log n) time.
+///
+/// ```
+/// use cs365_bonus::decimal::FixedPoint;
+/// use cs365_bonus::geo::{knn_graph, Metric};
+///
+/// let points = [("a", 0.0, 0.0), ("b", 1.0, 0.0), ("c", 5.0, 0.0), ("d", 5.0, 2.0)];
+/// let points: Vec<_> = points.iter().map(|&(name, x, y)| (name.to_string(), x, y)).collect();
+/// let graph = knn_graph(&points, 1, Metric::Euclidean, FixedPoint::INTEGER).unwrap();
+/// let edges: Vec<_> = graph
+///     .edges()
+///     .map(|(_, u, v, cost)| (graph.get_node_name(u).unwrap(), graph.get_node_name(v).unwrap(), cost))
+///     .collect();
+/// // Each point's nearest neighbor is on its own side, so the two pairs stay apart.
+/// assert_eq!(edges, [("a", "b", 1), ("c", "d", 2)]);
+/// ```
+pub fn knn_graph(
+    points: &[(String, f64, f64)],
+    k: usize,
+    metric: Metric,
+    scale: FixedPoint,
+) -> Result<Graph, String> {
+    let mut graph = Graph::with_capacity(points.len(), points.len() * k);
+    for (name, ..) in points {
+        if graph.get_node(name).is_some() {
+            return Err(format!("the point '{}' is listed twice", name));
+        }
+        graph.get_or_insert_node(name);
+    }
+    let vector = |&(_, first, second): &(String, f64, f64)| match metric {
+        Metric::Haversine => Point {
+            lon: second,
+            lat: first,
+        }
+        .unit_vector(),
+        Metric::Euclidean => [first, second, 0.0],
+    };
+    let distance = |a: &(String, f64, f64), b: &(String, f64, f64)| match metric {
+        Metric::Haversine => Point { lon: a.2, lat: a.1 }.distance(Point { lon: b.2, lat: b.1 }),
+        Metric::Euclidean => (a.1 - b.1).hypot(a.2 - b.2),
+    };
+    let tree = KdTree::new(points.iter().map(vector).enumerate().collect());
+    let mut joined = HashSet::new();
+    for (i, point) in points.iter().enumerate() {
+        for (j, _) in tree.nearest(vector(point), k, Some(i)) {
+            if !joined.insert((i.min(j), i.max(j))) {
+                continue;
+            }
+            let cost = scale.from_f64(distance(point, &points[j])).ok_or_else(|| {
+                format!(
+                    "the distance from '{}' to '{}' is too large to store",
+                    point.0, points[j].0
+                )
+            })?;
+            graph.add_bidirectional_edge(NodeId::new(i), NodeId::new(j), cost);
+        }
+    }
+    Ok(graph)
+}
+
+/// Each node's values for the two attributes in `keys`, each with the largest magnitude
+/// allowed. A node may have neither, but not just one.
+fn node_pairs(
+    graph: &Graph,
+    attributes: &Attributes,
+    keys: [(&str, f64); 2],
+) -> Result<Vec<Option<(f64, f64)>>, String> {
+    let mut pairs = Vec::with_capacity(graph.node_count());
+    for node in graph.node_ids() {
+        let name = graph.get_node_name(node).unwrap_or("?");
+        let number = |(key, limit): (&str, f64)| {
+            attributes
+                .node(node, key)
+                .map(|value| match value.parse::<f64>() {
+                    Ok(number) if number.abs() <= limit => Ok(number),
+                    _ => Err(format!(
+                        "node '{}' has an invalid {} '{}'",
+                        name, key, value
+                    )),
+                })
+                .transpose()
+        };
+        pairs.push(match (number(keys[0])?, number(keys[1])?) {
+            (Some(first), Some(second)) => Some((first, second)),
+            (None, None) => None,
+            _ => {
+                return Err(format!(
+                    "node '{}' needs both '{}' and '{}'",
+                    name, keys[0].0, keys[1].0
+                ))
+            }
+        });
+    }
+    Ok(pairs)
+}
+
+/// How [`distance_weights`] measures an edge from the positions of its ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Meters along the surface of the Earth between the `lat` and `lon` positions, by the
+    /// haversine formula.
+    Haversine,
+    /// The straight-line distance between the `x` and `y` positions, in their units.
+    Euclidean,
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "haversine" => Ok(Metric::Haversine),
+            "euclidean" => Ok(Metric::Euclidean),
+            _ => Err(format!(
+                "unknown metric '{}', expected 'haversine' or 'euclidean'",
+                s
+            )),
+        }
+    }
+}
+
+/// A copy of `graph` with each edge's weight replaced by the distance between its ends by
+/// `metric`, written with `scale`, for data that gives the layout of a network but no
+/// costs. Fails on the first edge with an end that has no position.
+///
+/// ```
+/// use cs365_bonus::attributes::Attributes;
+/// use cs365_bonus::decimal::FixedPoint;
+/// use cs365_bonus::geo::{distance_weights, Metric};
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1".parse().unwrap();
+/// let positions = "node a x=0 y=0\nnode b x=3 y=4\nnode c x=3 y=5.5";
+/// let attributes = Attributes::parse(&graph, positions).unwrap();
+/// let scale = FixedPoint::new(1).unwrap();
+/// let measured = distance_weights(&graph, &attributes, Metric::Euclidean, scale).unwrap();
+/// let costs: Vec<_> = measured.edges().map(|(_, _, _, cost)| scale.format(cost)).collect();
+/// assert_eq!(costs, ["5", "1.5"]);
+/// ```
+pub fn distance_weights(
+    graph: &Graph,
+    attributes: &Attributes,
+    metric: Metric,
+    scale: FixedPoint,
+) -> Result<Graph, String> {
+    let distance: Box<dyn Fn(NodeId, NodeId) -> Option<f64>> = match metric {
+        Metric::Haversine => {
+            let coordinates = Coordinates::from_attributes(graph, attributes)?;
+            Box::new(move |u, v| Some(coordinates.get(u)?.distance(coordinates.get(v)?)))
+        }
+        Metric::Euclidean => {
+            let positions = node_pairs(graph, attributes, [("x", f64::MAX), ("y", f64::MAX)])?;
+            Box::new(move |u, v| {
+                let ((ux, uy), (vx, vy)) = (positions[u.index()]?, positions[v.index()]?);
+                Some((ux - vx).hypot(uy - vy))
+            })
+        }
+    };
+    let mut weights = vec![0; graph.edge_count()];
+    for (id, u, v, _) in graph.edges() {
+        let name = |node| graph.get_node_name(node).unwrap_or("?");
+        let edge = || format!("edge '{}-{}'", name(u), name(v));
+        let length =
+            distance(u, v).ok_or_else(|| format!("{} has an end without a position", edge()))?;
+        weights[id.index()] = scale
+            .from_f64(length)
+            .ok_or_else(|| format!("{} is too long to store", edge()))?;
+    }
+    Ok(graph.map_weights(|id, _| weights[id.index()]))
+}
+
+/// An area bounded by an outer ring and any number of holes, each a closed line of points.
+///
+/// Coordinates are treated as flat, which is close enough for areas a few hundred
+/// kilometers across away from the poles and the antimeridian.
+///
+/// ```
+/// use cs365_bonus::geo::{Point, Polygon};
+///
+/// let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+/// let polygon = Polygon::new(vec![square
+///     .iter()
+///     .map(|&(lon, lat)| Point { lon, lat })
+///     .collect()]);
+/// let point = |lon, lat| Point { lon, lat };
+/// assert!(polygon.contains(point(1.0, 1.0)));
+/// assert!(!polygon.contains(point(3.0, 1.0)));
+/// // A line straight through the square, with both ends outside it.
+/// assert!(polygon.crosses(point(-1.0, 1.0), point(3.0, 1.0)));
+/// assert!(!polygon.crosses(point(-1.0, 3.0), point(3.0, 3.0)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    rings: Vec<Vec<Point>>,
+}
+
+impl Polygon {
+    /// The polygon bounded by `rings`, the outer one first. A ring may repeat its first
+    /// point at the end, as GeoJSON requires, or leave it to be closed implicitly.
+    pub fn new(rings: Vec<Vec<Point>>) -> Self {
+        Self { rings }
+    }
+
+    pub fn rings(&self) -> &[Vec<Point>] {
+        &self.rings
+    }
+
+    /// Whether `point` is inside the outer ring and outside every hole.
+    pub fn contains(&self, point: Point) -> bool {
+        // A ray from `point` crosses the boundary an odd number of times from inside.
+        let mut inside = false;
+        for (a, b) in self.sides() {
+            if (a.lat > point.lat) != (b.lat > point.lat)
+                && point.lon < a.lon + (point.lat - a.lat) / (b.lat - a.lat) * (b.lon - a.lon)
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Whether the straight segment from `a` to `b` touches the boundary.
+    pub fn crosses(&self, a: Point, b: Point) -> bool {
+        self.sides().any(|(c, d)| segments_meet(a, b, c, d))
+    }
+
+    /// Whether any of the segment from `a` to `b` lies inside the polygon.
+    pub fn overlaps(&self, a: Point, b: Point) -> bool {
+        self.contains(a) || self.contains(b) || self.crosses(a, b)
+    }
+
+    /// Each side of each ring, closing rings that don't repeat their first point.
+    fn sides(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.rings
+            .iter()
+            .flat_map(|ring| (0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()])))
+    }
+}
+
+/// Which side of the line from `a` to `b` the point `c` is on: positive to the left,
+/// negative to the right, and zero on the line.
+fn orientation(a: Point, b: Point, c: Point) -> f64 {
+    (b.lon - a.lon) * (c.lat - a.lat) - (b.lat - a.lat) * (c.lon - a.lon)
+}
+
+/// Whether the segments from `a` to `b` and from `c` to `d` have a point in common.
+fn segments_meet(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let (d1, d2) = (orientation(c, d, a), orientation(c, d, b));
+    let (d3, d4) = (orientation(a, b, c), orientation(a, b, d));
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return true;
+    }
+    // Collinear cases: an end of one segment lies on the other.
+    let within = |p: Point, q: Point, r: Point| {
+        r.lon >= p.lon.min(q.lon)
+            && r.lon <= p.lon.max(q.lon)
+            && r.lat >= p.lat.min(q.lat)
+            && r.lat <= p.lat.max(q.lat)
+    };
+    (d1 == 0.0 && within(c, d, a))
+        || (d2 == 0.0 && within(c, d, b))
+        || (d3 == 0.0 && within(a, b, c))
+        || (d4 == 0.0 && within(a, b, d))
+}
+
+/// Read every polygon in a GeoJSON document: `Polygon` and `MultiPolygon` geometries, on
+/// their own or inside features, feature collections, and geometry collections. Other
+/// geometries, such as points and lines, enclose no area and are skipped.
+///
+/// ```
+/// let polygons = cs365_bonus::geo::parse_polygons(
+///     r#"{"type": "FeatureCollection", "features": [{"type": "Feature", "properties": {},
+///         "geometry": {"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [0, 1], [0, 0]]]}}]}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(polygons.len(), 1);
+/// assert_eq!(polygons[0].rings()[0].len(), 4);
+/// ```
+pub fn parse_polygons(text: &str) -> Result<Vec<Polygon>, String> {
+    let mut polygons = Vec::new();
+    collect_polygons(&Value::parse(text)?, &mut polygons)?;
+    Ok(polygons)
+}
+
+fn collect_polygons(value: &Value, polygons: &mut Vec<Polygon>) -> Result<(), String> {
+    let member = |key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_array)
+            .ok_or_else(|| format!("a GeoJSON object is missing its '{}'", key))
+    };
+    match value.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            for feature in member("features")? {
+                collect_polygons(feature, polygons)?;
+            }
+        }
+        Some("Feature") => match value.get("geometry") {
+            Some(Value::Null) | None => {}
+            Some(geometry) => collect_polygons(geometry, polygons)?,
+        },
+        Some("GeometryCollection") => {
+            for geometry in member("geometries")? {
+                collect_polygons(geometry, polygons)?;
+            }
+        }
+        Some("Polygon") => polygons.push(polygon(member("coordinates")?)?),
+        Some("MultiPolygon") => {
+            for rings in member("coordinates")? {
+                let rings = rings.as_array().ok_or("a MultiPolygon holds polygons")?;
+                polygons.push(polygon(rings)?);
+            }
+        }
+        Some(_) => {}
+        None => return Err("a GeoJSON object is missing its 'type'".into()),
+    }
+    Ok(())
+}
+
+/// The polygon whose rings are the GeoJSON arrays of positions in `rings`.
+fn polygon(rings: &[Value]) -> Result<Polygon, String> {
+    let rings = rings
+        .iter()
+        .map(|ring| {
+            let ring = ring
+                .as_array()
+                .ok_or("a polygon ring must be an array of positions")?;
+            let points = ring
+                .iter()
+                .map(position)
+                .collect::<Result<Vec<Point>, String>>()?;
+            if points.len() < 3 {
+                return Err("a polygon ring needs at least 3 positions".to_string());
+            }
+            Ok(points)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if rings.is_empty() {
+        return Err("a polygon needs at least one ring".into());
+    }
+    Ok(Polygon::new(rings))
+}
+
+/// A GeoJSON position `[lon, lat]`, ignoring any altitude after them.
+fn position(value: &Value) -> Result<Point, String> {
+    match value.as_array() {
+        Some([lon, lat, ..]) => match (lon.as_f64(), lat.as_f64()) {
+            (Some(lon), Some(lat)) => Ok(Point { lon, lat }),
+            _ => Err("a position must be made of numbers".into()),
+        },
+        _ => Err("a position must be an array '[lon, lat]'".into()),
+    }
+}
+
+/// The nodes of `graph` inside any of `polygons`, and the edges with any of their length
+/// inside one, taking each edge as a straight line between its ends. Nodes and edges
+/// without coordinates are never inside.
+pub fn inside_polygons(
+    graph: &Graph,
+    coordinates: &Coordinates,
+    polygons: &[Polygon],
+) -> (Vec<NodeId>, Vec<EdgeId>) {
+    let nodes = graph
+        .node_ids()
+        .filter(|&node| {
+            coordinates
+                .get(node)
+                .is_some_and(|point| polygons.iter().any(|polygon| polygon.contains(point)))
+        })
+        .collect();
+    let edges = graph
+        .edges()
+        .filter(
+            |&(_, u, v, _)| match (coordinates.get(u), coordinates.get(v)) {
+                (Some(a), Some(b)) => polygons.iter().any(|polygon| polygon.overlaps(a, b)),
+                _ => false,
+            },
+        )
+        .map(|(id, ..)| id)
+        .collect();
+    (nodes, edges)
+}
+
+/// The corners of the smallest convex polygon around `points`, counterclockwise from the
+/// one farthest west (the southernmost of those), without repeating it at the end. Points
+/// on the edges are left out, so fewer than three points come back if all of `points` lie
+/// on one line.
+///
+/// ```
+/// use cs365_bonus::geo::{convex_hull, Point};
+///
+/// let points: Vec<Point> = [(0.0, 0.0), (2.0, 0.0), (1.0, 1.0), (2.0, 2.0), (0.0, 2.0), (1.0, 0.0)]
+///     .iter()
+///     .map(|&(lon, lat)| Point { lon, lat })
+///     .collect();
+/// let hull: Vec<(f64, f64)> = convex_hull(&points).iter().map(|p| (p.lon, p.lat)).collect();
+/// assert_eq!(hull, vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]);
+/// ```
+pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.lon.total_cmp(&b.lon).then(a.lat.total_cmp(&b.lat)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    // Andrew's monotone chain: the lower half west to east, then the upper half back.
+    let mut hull: Vec<Point> = Vec::with_capacity(2 * points.len());
+    for _ in 0..2 {
+        let floor = hull.len();
+        for &point in &points {
+            while hull.len() >= floor + 2
+                && orientation(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+        points.reverse();
+    }
+    hull
+}
+
+/// A GeoJSON `FeatureCollection` of `features`, each a GeoJSON `Feature` object.
+pub fn feature_collection(features: &[String]) -> String {
+    format!(
+        "{{\"type\":\"FeatureCollection\",\"features\":[{}]}}",
+        features.join(",")
+    )
+}
+
+/// A GeoJSON `Feature` with a `geometry` and `properties`, both JSON objects.
+pub fn feature(geometry: &str, properties: &str) -> String {
+    format!(
+        "{{\"type\":\"Feature\",\"geometry\":{},\"properties\":{}}}",
+        geometry, properties
+    )
+}
+
+/// A GeoJSON `LineString` geometry through `points`.
+pub fn line_string(points: &[Point]) -> String {
+    format!(
+        "{{\"type\":\"LineString\",\"coordinates\":{}}}",
+        positions(points)
+    )
+}
+
+/// A GeoJSON geometry covering the convex hull of `points`: a `Polygon`, or a `LineString`
+/// or `Point` if the points all lie on one line or at one place.
+pub fn hull_geometry(points: &[Point]) -> String {
+    let mut hull = convex_hull(points);
+    match hull.len() {
+        0 => "null".to_string(),
+        1 => format!(
+            "{{\"type\":\"Point\",\"coordinates\":[{},{}]}}",
+            hull[0].lon, hull[0].lat
+        ),
+        2 => line_string(&hull),
+        _ => {
+            hull.push(hull[0]);
+            format!(
+                "{{\"type\":\"Polygon\",\"coordinates\":[{}]}}",
+                positions(&hull)
+            )
+        }
+    }
+}
+
+/// `points` as a JSON array of GeoJSON positions.
+fn positions(points: &[Point]) -> String {
+    let positions: Vec<String> = points
+        .iter()
+        .map(|point| format!("[{},{}]", point.lon, point.lat))
+        .collect();
+    format!("[{}]", positions.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn nearest_matches_a_linear_scan() {
+        let mut rng = Rng::new(3);
+        let mut random_point = || Point {
+            lon: rng.next_f64() * 360.0 - 180.0,
+            lat: rng.next_f64() * 180.0 - 90.0,
+        };
+        let points: Vec<Option<Point>> = (0..500).map(|_| Some(random_point())).collect();
+        let index = NodeIndex::new(&Coordinates {
+            points: points.clone(),
+        });
+        for _ in 0..200 {
+            let target = random_point();
+            let (node, meters) = index.nearest(target).unwrap();
+            let closest = points
+                .iter()
+                .map(|point| target.distance(point.unwrap()))
+                .fold(f64::INFINITY, f64::min);
+            assert_eq!(meters, closest, "{:?} went to {:?}", target, node);
+        }
+    }
+
+    #[test]
+    fn k_nearest_match_a_linear_scan() {
+        let mut rng = Rng::new(8);
+        let items: Vec<(usize, [f64; 3])> = (0..300)
+            .map(|i| (i, [rng.next_f64(), rng.next_f64(), rng.next_f64()]))
+            .collect();
+        let tree = KdTree::new(items.clone());
+        let squared = |a: [f64; 3], b: [f64; 3]| -> f64 {
+            a.iter().zip(&b).map(|(a, b)| (a - b) * (a - b)).sum()
+        };
+        for &(i, target) in items.iter().take(50) {
+            let found = tree.nearest(target, 7, Some(i));
+            let mut scan: Vec<f64> = items
+                .iter()
+                .filter(|&&(j, _)| j != i)
+                .map(|&(_, point)| squared(target, point))
+                .collect();
+            scan.sort_by(f64::total_cmp);
+            let found: Vec<f64> = found.iter().map(|&(_, distance)| distance).collect();
+            assert_eq!(found, scan[..7]);
+        }
+    }
+}
@@ -0,0 +1,713 @@
+use crate::arena::ParseArena;
+use crate::view::FilterView;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+/// Distinct neighbors of every node, ignoring edge direction, weights, and self-loops.
+pub(crate) fn simple_adjacency(graph: &Graph) -> Vec<Vec<NodeId>> {
+    let mut adjacency = vec![Vec::new(); graph.node_count()];
+    for (_, src, dest, _) in graph.edges() {
+        if src != dest {
+            adjacency[src.index()].push(dest);
+            adjacency[dest.index()].push(src);
+        }
+    }
+    for list in adjacency.iter_mut() {
+        list.sort_unstable();
+        list.dedup();
+    }
+    adjacency
+}
+
+/// Read-only access to a graph's adjacency, implemented by [`Graph`] and by cheap views
+/// over it, so searches can run without materializing a copy.
+pub trait GraphRef {
+    /// The number of node ids, including any hidden by a view.
+    fn node_count(&self) -> usize;
+
+    /// Whether `node` is part of this graph.
+    fn contains_node(&self, node: NodeId) -> bool {
+        node.index() < self.node_count()
+    }
+
+    /// The edges leaving `node`.
+    fn edges_of(&self, node: NodeId) -> impl Iterator<Item = &Edge> + '_;
+
+    /// What crossing `edge` costs, which views may change without copying the graph.
+    fn cost(&self, edge: &Edge) -> Weight {
+        edge.cost
+    }
+}
+
+impl GraphRef for Graph {
+    fn node_count(&self) -> usize {
+        Graph::node_count(self)
+    }
+
+    fn edges_of(&self, node: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        Graph::edges_of(self, node).iter()
+    }
+}
+
+/// Storage type for node indices.
+///
+/// Enabling the `u32-indices` feature halves the size of edges, queue entries, and parent
+/// arrays, which matters for road-network sized inputs with fewer than 4B nodes.
+#[cfg(feature = "u32-indices")]
+type Index = u32;
+#[cfg(not(feature = "u32-indices"))]
+type Index = usize;
+
+/// Panic unless `index` can be stored as an [`Index`].
+#[allow(clippy::unnecessary_cast)]
+fn assert_fits(index: usize, what: &str) {
+    assert!(
+        index <= Index::MAX as usize,
+        "{} index overflows the index type",
+        what
+    );
+}
+
+/// Edge cost type.
+pub type Weight = i64;
+
+/// A handle to a node in a [`Graph`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(Index);
+
+impl NodeId {
+    /// Wrap `index`, which must fit in the index type.
+    ///
+    /// This is only checked in debug builds, since it runs in every search loop; the
+    /// places that grow a graph check it unconditionally.
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(index: usize) -> Self {
+        debug_assert!(
+            index <= Index::MAX as usize,
+            "node index overflows the index type"
+        );
+        NodeId(index as Index)
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl From<usize> for NodeId {
+    fn from(index: usize) -> Self {
+        NodeId::new(index)
+    }
+}
+
+impl From<NodeId> for usize {
+    fn from(id: NodeId) -> Self {
+        id.index()
+    }
+}
+
+impl std::fmt::Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A stable handle to an edge in a [`Graph`].
+///
+/// Both directions of a bidirectional edge share the same id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeId(Index);
+
+impl EdgeId {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(index: usize) -> Self {
+        debug_assert!(
+            index <= Index::MAX as usize,
+            "edge index overflows the index type"
+        );
+        EdgeId(index as Index)
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl From<usize> for EdgeId {
+    fn from(index: usize) -> Self {
+        EdgeId::new(index)
+    }
+}
+
+impl From<EdgeId> for usize {
+    fn from(id: EdgeId) -> Self {
+        id.index()
+    }
+}
+
+impl std::fmt::Display for EdgeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// An entry in a node's adjacency list.
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub node: NodeId,
+    pub cost: Weight,
+    pub id: EdgeId,
+}
+
+#[derive(Debug, Clone)]
+struct EdgeRecord {
+    src: NodeId,
+    dest: NodeId,
+    cost: Weight,
+    directed: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    nodes: Vec<String>,
+    /// The id of every node by name, so lookups don't scan `nodes`.
+    index: HashMap<String, NodeId>,
+    list: Vec<Vec<Edge>>,
+    edges: Vec<EdgeRecord>,
+}
+
+/// Map each of `nodes` to its position.
+fn name_index(nodes: &[String]) -> HashMap<String, NodeId> {
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), NodeId::new(i)))
+        .collect()
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty graph with room for `nodes` nodes and `edges` edges.
+    pub fn with_capacity(nodes: usize, edges: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(nodes),
+            index: HashMap::with_capacity(nodes),
+            list: Vec::with_capacity(nodes),
+            edges: Vec::with_capacity(edges),
+        }
+    }
+
+    /// Build an undirected graph from `(src, dest, cost)` triples.
+    pub fn from_edges<'a, I>(edges: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str, Weight)>,
+    {
+        edges.into_iter().collect()
+    }
+
+    /// Build a directed graph from `(src, dest, cost)` triples.
+    pub fn from_directed_edges<'a, I>(edges: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str, Weight)>,
+    {
+        let mut graph = Self::new();
+        for (src, dest, cost) in edges {
+            let src = graph.get_or_insert_node(src);
+            let dest = graph.get_or_insert_node(dest);
+            graph.add_edge(src, dest, cost);
+        }
+        graph
+    }
+
+    /// Release spare capacity once construction is complete.
+    pub fn finalize(&mut self) {
+        self.nodes.shrink_to_fit();
+        for name in self.nodes.iter_mut() {
+            name.shrink_to_fit();
+        }
+        self.index.shrink_to_fit();
+        self.list.shrink_to_fit();
+        for edges in self.list.iter_mut() {
+            edges.shrink_to_fit();
+        }
+        self.edges.shrink_to_fit();
+    }
+
+    /// Build a graph from parsed temporaries, sizing every adjacency list exactly.
+    pub(crate) fn from_arena(arena: &ParseArena) -> Self {
+        let mut degree = vec![0; arena.node_count()];
+        for &(src, dest, _, directed) in arena.edges() {
+            degree[src as usize] += 1;
+            if !directed {
+                degree[dest as usize] += 1;
+            }
+        }
+
+        let nodes = arena.take_names();
+        let mut graph = Self {
+            index: name_index(&nodes),
+            nodes,
+            list: degree.into_iter().map(Vec::with_capacity).collect(),
+            edges: Vec::with_capacity(arena.edges().len()),
+        };
+        for &(src, dest, cost, directed) in arena.edges() {
+            let (src, dest) = (NodeId::new(src as usize), NodeId::new(dest as usize));
+            if directed {
+                graph.add_edge(src, dest, cost);
+            } else {
+                graph.add_bidirectional_edge(src, dest, cost);
+            }
+        }
+
+        graph
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn get_node_name(&self, node: NodeId) -> Option<&str> {
+        self.nodes.get(node.index()).map(|s| s.as_str())
+    }
+
+    pub fn get_node(&self, name: &str) -> Option<NodeId> {
+        self.index.get(name).copied()
+    }
+
+    pub fn get_or_insert_node(&mut self, name: &str) -> NodeId {
+        if let Some(n) = self.get_node(name) {
+            n
+        } else {
+            assert_fits(self.nodes.len(), "node");
+            let ret = NodeId::new(self.nodes.len());
+            self.nodes.push(name.into());
+            self.index.insert(name.into(), ret);
+            self.list.push(Vec::new());
+            ret
+        }
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Add a one-way edge from `src` to `dest`.
+    pub fn add_edge(&mut self, src: NodeId, dest: NodeId, cost: Weight) -> EdgeId {
+        assert_fits(self.edges.len(), "edge");
+        let id = EdgeId::new(self.edges.len());
+        self.edges.push(EdgeRecord {
+            src,
+            dest,
+            cost,
+            directed: true,
+        });
+        self.list[src.index()].push(Edge {
+            node: dest,
+            cost,
+            id,
+        });
+        id
+    }
+
+    pub fn add_bidirectional_edge(&mut self, src: NodeId, dest: NodeId, cost: Weight) -> EdgeId {
+        assert_fits(self.edges.len(), "edge");
+        let id = EdgeId::new(self.edges.len());
+        self.edges.push(EdgeRecord {
+            src,
+            dest,
+            cost,
+            directed: false,
+        });
+        self.list[src.index()].push(Edge {
+            node: dest,
+            cost,
+            id,
+        });
+        self.list[dest.index()].push(Edge {
+            node: src,
+            cost,
+            id,
+        });
+        id
+    }
+
+    /// Find the edge from `src` to `dest`, picking the cheapest one if there are several.
+    pub fn find_edge(&self, src: NodeId, dest: NodeId) -> Option<EdgeId> {
+        self.list
+            .get(src.index())?
+            .iter()
+            .filter(|edge| edge.node == dest)
+            .min_by_key(|edge| edge.cost)
+            .map(|edge| edge.id)
+    }
+
+    pub fn edge_weight(&self, id: EdgeId) -> Option<Weight> {
+        self.edges.get(id.index()).map(|edge| edge.cost)
+    }
+
+    pub fn edge_endpoints(&self, id: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.edges.get(id.index()).map(|edge| (edge.src, edge.dest))
+    }
+
+    pub fn is_edge_directed(&self, id: EdgeId) -> Option<bool> {
+        self.edges.get(id.index()).map(|edge| edge.directed)
+    }
+
+    /// The adjacency list of `node`, including edge ids.
+    pub fn edges_of(&self, node: NodeId) -> &[Edge] {
+        &self.list[node.index()]
+    }
+
+    /// Nodes reachable from `node` over a single edge, with the cost of that edge.
+    pub fn neighbors(&self, node: NodeId) -> impl Iterator<Item = (NodeId, Weight)> + '_ {
+        self.list[node.index()]
+            .iter()
+            .map(|edge| (edge.node, edge.cost))
+    }
+
+    /// Every edge once, as `(id, src, dest, cost)`.
+    pub fn edges(&self) -> impl Iterator<Item = (EdgeId, NodeId, NodeId, Weight)> + '_ {
+        self.edges
+            .iter()
+            .enumerate()
+            .map(|(i, edge)| (EdgeId::new(i), edge.src, edge.dest, edge.cost))
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len()).map(NodeId::new)
+    }
+
+    /// A copy of this graph with node `i` renamed to `names[i]`.
+    ///
+    /// Returns `None` if `names` has the wrong length or contains duplicates.
+    pub fn relabeled(&self, names: Vec<String>) -> Option<Graph> {
+        if names.len() != self.nodes.len() {
+            return None;
+        }
+        let index = name_index(&names);
+        if index.len() != names.len() {
+            return None;
+        }
+
+        Some(Graph {
+            nodes: names,
+            index,
+            list: self.list.clone(),
+            edges: self.edges.clone(),
+        })
+    }
+
+    /// The subgraph made of the nodes accepted by `keep` and the edges between them.
+    ///
+    /// Kept nodes retain their names and relative order.
+    pub fn induced_subgraph(&self, keep: impl Fn(NodeId) -> bool) -> Graph {
+        let mut graph = Graph::new();
+        let remap: Vec<Option<NodeId>> = self
+            .node_ids()
+            .map(|node| {
+                if keep(node) {
+                    Some(graph.get_or_insert_node(&self.nodes[node.index()]))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        for edge in self.edges.iter() {
+            if let (Some(src), Some(dest)) = (remap[edge.src.index()], remap[edge.dest.index()]) {
+                if edge.directed {
+                    graph.add_edge(src, dest, edge.cost);
+                } else {
+                    graph.add_bidirectional_edge(src, dest, edge.cost);
+                }
+            }
+        }
+        graph
+    }
+
+    /// A view of this graph that hides nodes rejected by `node_pred` and edges rejected by
+    /// `edge_pred`, without copying anything.
+    ///
+    /// `edge_pred` is called with the node an edge leaves and the edge itself.
+    pub fn filter_view<N, E>(&self, node_pred: N, edge_pred: E) -> FilterView<'_, N, E>
+    where
+        N: Fn(NodeId) -> bool,
+        E: Fn(NodeId, &Edge) -> bool,
+    {
+        FilterView::new(self, node_pred, edge_pred)
+    }
+
+    /// A copy of this graph with its nodes renumbered, so that `order[i]` becomes node `i`.
+    ///
+    /// Edge ids are preserved. Returns `None` if `order` isn't a permutation of the nodes.
+    pub fn permuted(&self, order: &[NodeId]) -> Option<Graph> {
+        let mut position = vec![None; self.node_count()];
+        if order.len() != position.len() {
+            return None;
+        }
+        for (i, node) in order.iter().enumerate() {
+            let slot = position.get_mut(node.index())?;
+            if slot.is_some() {
+                return None;
+            }
+            *slot = Some(NodeId::new(i));
+        }
+        let position: Vec<NodeId> = position.into_iter().collect::<Option<_>>()?;
+
+        let nodes: Vec<String> = order
+            .iter()
+            .map(|node| self.nodes[node.index()].clone())
+            .collect();
+        let mut graph = Self {
+            index: name_index(&nodes),
+            nodes,
+            list: order
+                .iter()
+                .map(|node| Vec::with_capacity(self.list[node.index()].len()))
+                .collect(),
+            edges: Vec::with_capacity(self.edges.len()),
+        };
+        for edge in self.edges.iter() {
+            let (src, dest) = (position[edge.src.index()], position[edge.dest.index()]);
+            if edge.directed {
+                graph.add_edge(src, dest, edge.cost);
+            } else {
+                graph.add_bidirectional_edge(src, dest, edge.cost);
+            }
+        }
+        Some(graph)
+    }
+
+    /// A copy of this graph where every undirected edge only runs from its first endpoint
+    /// to its second, as they were given. Edge ids are preserved.
+    pub fn to_directed(&self) -> Graph {
+        let mut graph = Self {
+            nodes: self.nodes.clone(),
+            index: self.index.clone(),
+            list: vec![Vec::new(); self.node_count()],
+            edges: Vec::with_capacity(self.edges.len()),
+        };
+        for edge in self.edges.iter() {
+            graph.add_edge(edge.src, edge.dest, edge.cost);
+        }
+        graph
+    }
+
+    /// A copy of this graph with every directed edge pointing the other way. Undirected
+    /// edges and edge ids are unchanged.
+    pub fn reversed(&self) -> Graph {
+        let mut graph = Self {
+            nodes: self.nodes.clone(),
+            index: self.index.clone(),
+            list: self
+                .list
+                .iter()
+                .map(|list| Vec::with_capacity(list.len()))
+                .collect(),
+            edges: Vec::with_capacity(self.edges.len()),
+        };
+        for edge in self.edges.iter() {
+            if edge.directed {
+                graph.add_edge(edge.dest, edge.src, edge.cost);
+            } else {
+                graph.add_bidirectional_edge(edge.src, edge.dest, edge.cost);
+            }
+        }
+        graph
+    }
+
+    /// A copy of this graph with every edge weight replaced by `f(id, weight)`.
+    pub fn map_weights(&self, mut f: impl FnMut(EdgeId, Weight) -> Weight) -> Graph {
+        let mut graph = self.clone();
+        for (i, edge) in graph.edges.iter_mut().enumerate() {
+            edge.cost = f(EdgeId::new(i), edge.cost);
+        }
+        for list in graph.list.iter_mut() {
+            for edge in list.iter_mut() {
+                edge.cost = graph.edges[edge.id.index()].cost;
+            }
+        }
+        graph
+    }
+
+    /// A copy of this graph that keeps every node but only the edges accepted by `keep`.
+    pub fn edge_subgraph(&self, keep: impl Fn(EdgeId) -> bool) -> Graph {
+        let mut graph = Graph::with_capacity(self.nodes.len(), 0);
+        for name in self.nodes.iter() {
+            graph.get_or_insert_node(name);
+        }
+        for (i, edge) in self.edges.iter().enumerate() {
+            if !keep(EdgeId::new(i)) {
+                continue;
+            }
+            if edge.directed {
+                graph.add_edge(edge.src, edge.dest, edge.cost);
+            } else {
+                graph.add_bidirectional_edge(edge.src, edge.dest, edge.cost);
+            }
+        }
+        graph
+    }
+
+    /// The edges of a minimum spanning tree, or a forest if the graph is disconnected,
+    /// ignoring edge direction, as found by [`crate::mst::minimum_spanning_forest`].
+    pub fn minimum_spanning_tree(&self) -> Vec<EdgeId> {
+        crate::mst::minimum_spanning_forest(self)
+    }
+
+    /// The nodes grouped by connected component, ignoring edge direction.
+    ///
+    /// ```
+    /// # use cs365_bonus::Graph;
+    /// let graph: Graph = "a b 1\nc d 2\nb e 3".parse().unwrap();
+    /// let components = graph.connected_components();
+    /// assert_eq!(components.len(), 2);
+    /// assert_eq!(components[0].len(), 3);
+    /// assert_eq!(graph.minimum_spanning_tree().len(), 3);
+    /// ```
+    pub fn connected_components(&self) -> Vec<Vec<NodeId>> {
+        crate::connectivity::connected_components(self)
+    }
+
+    /// Copy every node and edge of `other` into this graph, prefixing its node names.
+    ///
+    /// Nodes whose prefixed name already exists are shared, so an empty prefix unions
+    /// the two graphs.
+    pub fn merge(&mut self, other: &Graph, prefix: &str) {
+        let remap: Vec<NodeId> = other
+            .nodes
+            .iter()
+            .map(|name| self.get_or_insert_node(&format!("{}{}", prefix, name)))
+            .collect();
+        for edge in other.edges.iter() {
+            let src = remap[edge.src.index()];
+            let dest = remap[edge.dest.index()];
+            if edge.directed {
+                self.add_edge(src, dest, edge.cost);
+            } else {
+                self.add_bidirectional_edge(src, dest, edge.cost);
+            }
+        }
+    }
+}
+
+/// Streams undirected edges into the graph, creating nodes as needed.
+impl<'a> Extend<(&'a str, &'a str, Weight)> for Graph {
+    fn extend<I: IntoIterator<Item = (&'a str, &'a str, Weight)>>(&mut self, iter: I) {
+        for (src, dest, cost) in iter {
+            let src = self.get_or_insert_node(src);
+            let dest = self.get_or_insert_node(dest);
+            self.add_bidirectional_edge(src, dest, cost);
+        }
+    }
+}
+
+impl Extend<(String, String, Weight)> for Graph {
+    fn extend<I: IntoIterator<Item = (String, String, Weight)>>(&mut self, iter: I) {
+        for (src, dest, cost) in iter {
+            self.extend(std::iter::once((src.as_str(), dest.as_str(), cost)));
+        }
+    }
+}
+
+impl<'a> FromIterator<(&'a str, &'a str, Weight)> for Graph {
+    fn from_iter<I: IntoIterator<Item = (&'a str, &'a str, Weight)>>(iter: I) -> Self {
+        let mut graph = Graph::new();
+        graph.extend(iter);
+        graph
+    }
+}
+
+impl FromIterator<(String, String, Weight)> for Graph {
+    fn from_iter<I: IntoIterator<Item = (String, String, Weight)>>(iter: I) -> Self {
+        let mut graph = Graph::new();
+        graph.extend(iter);
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every edge as `(src, dest, cost, directed)` by node name.
+    fn named_edges(graph: &Graph) -> Vec<(&str, &str, Weight, bool)> {
+        let name = |node| graph.get_node_name(node).unwrap();
+        graph
+            .edges()
+            .map(|(id, src, dest, cost)| {
+                let directed = graph.is_edge_directed(id).unwrap();
+                (name(src), name(dest), cost, directed)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn from_edges_shares_nodes_by_name() {
+        let graph = Graph::from_edges([("a", "b", 1), ("b", "c", 2), ("c", "a", 3)]);
+        assert_eq!((graph.node_count(), graph.edge_count()), (3, 3));
+        let (a, b) = (graph.get_node("a").unwrap(), graph.get_node("b").unwrap());
+        assert_eq!(graph.find_edge(b, a), graph.find_edge(a, b));
+        assert_eq!(named_edges(&graph)[2], ("c", "a", 3, false));
+
+        let directed = Graph::from_directed_edges([("a", "b", 1), ("b", "a", 2)]);
+        assert_eq!(directed.edge_count(), 2);
+        assert_eq!(named_edges(&directed)[1], ("b", "a", 2, true));
+    }
+
+    #[test]
+    fn extend_streams_undirected_edges() {
+        let mut graph: Graph = vec![("a".to_string(), "b".to_string(), 1)]
+            .into_iter()
+            .collect();
+        graph.extend([("b", "c", 2)]);
+        graph.extend(vec![("c".to_string(), "a".to_string(), 3)]);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(
+            named_edges(&graph),
+            [
+                ("a", "b", 1, false),
+                ("b", "c", 2, false),
+                ("c", "a", 3, false)
+            ]
+        );
+        let c = graph.get_node("c").unwrap();
+        assert_eq!(graph.edges_of(c).len(), 2);
+    }
+
+    #[test]
+    fn merge_prefixes_names_and_shares_matches() {
+        let mut roads: Graph = "a b 1\nb c 2".parse().unwrap();
+        let rail: Graph = "a c 5 ->".parse().unwrap();
+        roads.merge(&rail, "rail:");
+        assert_eq!(roads.node_count(), 5);
+        assert_eq!(named_edges(&roads)[2], ("rail:a", "rail:c", 5, true));
+
+        // Without a prefix, nodes with the same name are the same node.
+        let bus: Graph = "c a 4".parse().unwrap();
+        roads.merge(&bus, "");
+        assert_eq!(roads.node_count(), 5);
+        assert_eq!(named_edges(&roads)[3], ("c", "a", 4, false));
+    }
+
+    #[test]
+    fn relabeled_renames_in_place() {
+        let graph: Graph = "a b 1\nb c 2 ->".parse().unwrap();
+        let names = vec!["x".to_string(), "y".to_string(), "z".to_string()];
+        let renamed = graph.relabeled(names).unwrap();
+        assert_eq!(
+            named_edges(&renamed),
+            [("x", "y", 1, false), ("y", "z", 2, true)]
+        );
+        assert_eq!(renamed.get_node("z"), Some(NodeId::new(2)));
+        assert_eq!(renamed.get_node("a"), None);
+
+        assert!(graph.relabeled(vec!["x".into(), "y".into()]).is_none());
+        let duplicate = vec!["x".to_string(), "y".to_string(), "x".to_string()];
+        assert!(graph.relabeled(duplicate).is_none());
+    }
+}
@@ -0,0 +1,489 @@
+use crate::graph::Weight;
+use crate::gridworld::GridWorld;
+use crate::path::Path;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// The cost of a horizontal or vertical step, in the units grid searches report.
+pub const STRAIGHT: Weight = 1000;
+/// The cost of a diagonal step, `STRAIGHT` times the square root of 2, rounded down.
+pub const DIAGONAL: Weight = 1414;
+
+/// A route found by one of the grid searches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridRoute {
+    /// Every cell the route passes through, from the start to the goal.
+    pub cells: Vec<(usize, usize)>,
+    /// The start, the cells where the route turns, and the goal. Straight lines join each
+    /// to the next.
+    pub waypoints: Vec<(usize, usize)>,
+    /// The cost in units of [`STRAIGHT`] for a step.
+    pub cost: Weight,
+    /// How many cells the search expanded.
+    pub expanded: usize,
+}
+
+type Cell = (usize, usize);
+
+/// Whether `(x, y)` offset by `(dx, dy)` is inside `world` and not a wall, and where.
+fn step(world: &GridWorld, (x, y): Cell, (dx, dy): (isize, isize)) -> Option<Cell> {
+    let (x, y) = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+    (x < world.width() && y < world.height() && !world.is_wall((x, y))).then_some((x, y))
+}
+
+/// Whether a move by `(dx, dy)` from `cell` is allowed: the target must be free, and a
+/// diagonal move may not cut the corner of a wall, so both cells beside it must be free.
+fn can_move(world: &GridWorld, cell: Cell, (dx, dy): (isize, isize)) -> Option<Cell> {
+    if dx != 0
+        && dy != 0
+        && (step(world, cell, (dx, 0)).is_none() || step(world, cell, (0, dy)).is_none())
+    {
+        return None;
+    }
+    step(world, cell, (dx, dy))
+}
+
+/// The cost of the cheapest route between two cells on an open grid: the octile distance
+/// with diagonal moves, or the Manhattan distance without.
+fn estimate((x, y): Cell, (gx, gy): Cell, diagonal: bool) -> Weight {
+    let (dx, dy) = (x.abs_diff(gx) as Weight, y.abs_diff(gy) as Weight);
+    if diagonal {
+        STRAIGHT * dx.max(dy) + (DIAGONAL - STRAIGHT) * dx.min(dy)
+    } else {
+        STRAIGHT * (dx + dy)
+    }
+}
+
+fn cost((dx, dy): (isize, isize)) -> Weight {
+    if dx != 0 && dy != 0 {
+        DIAGONAL
+    } else {
+        STRAIGHT
+    }
+}
+
+const DIRECTIONS: [(isize, isize); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// An A* search over the cells of `world` towards its goal, estimating the remaining cost
+/// from a cell with `heuristic`. Successors come from `successors`, given a cell and its
+/// parent, as the cell to record as their parent (usually the given cell), the successor,
+/// and its cost from that parent.
+fn search(
+    world: &GridWorld,
+    heuristic: impl Fn(Cell) -> Weight,
+    mut successors: impl FnMut(Cell, Option<Cell>) -> Vec<(Cell, Cell, Weight)>,
+) -> Option<GridRoute> {
+    let index = |(x, y): Cell| y * world.width() + x;
+    let (start, goal) = (world.start(), world.goal());
+    let mut distance = vec![Weight::MAX; world.width() * world.height()];
+    let mut parent: Vec<Option<Cell>> = vec![None; distance.len()];
+    let mut open = BinaryHeap::new();
+    distance[index(start)] = 0;
+    open.push(Reverse((heuristic(start), Reverse(0), start)));
+    let mut expanded = 0;
+    while let Some(Reverse((_, Reverse(g), cell))) = open.pop() {
+        if g > distance[index(cell)] {
+            continue;
+        }
+        expanded += 1;
+        if cell == goal {
+            break;
+        }
+        for (from, next, cost) in successors(cell, parent[index(cell)]) {
+            let g = distance[index(from)] + cost;
+            if g < distance[index(next)] {
+                distance[index(next)] = g;
+                parent[index(next)] = Some(from);
+                open.push(Reverse((g + heuristic(next), Reverse(g), next)));
+            }
+        }
+    }
+
+    let cost = distance[index(goal)];
+    if cost == Weight::MAX {
+        return None;
+    }
+    let mut chain = vec![goal];
+    while let Some(previous) = parent[index(chain[chain.len() - 1])] {
+        chain.push(previous);
+    }
+    chain.reverse();
+    Some(along(turns(chain), cost, expanded))
+}
+
+/// The route along straight lines between `waypoints`.
+fn along(waypoints: Vec<Cell>, cost: Weight, expanded: usize) -> GridRoute {
+    let mut cells = vec![waypoints[0]];
+    for pair in waypoints.windows(2) {
+        cells.extend(trace(pair[0], pair[1]).0.into_iter().skip(1));
+    }
+    GridRoute {
+        cells,
+        waypoints,
+        cost,
+        expanded,
+    }
+}
+
+/// `chain` without the cells in the middle of a straight line.
+fn turns(chain: Vec<Cell>) -> Vec<Cell> {
+    let mut waypoints: Vec<Cell> = Vec::with_capacity(chain.len());
+    for cell in chain {
+        if let [.., a, b] = waypoints[..] {
+            let (ux, uy) = (b.0 as isize - a.0 as isize, b.1 as isize - a.1 as isize);
+            let (vx, vy) = (
+                cell.0 as isize - b.0 as isize,
+                cell.1 as isize - b.1 as isize,
+            );
+            if ux * vy == uy * vx && ux * vx + uy * vy > 0 {
+                waypoints.pop();
+            }
+        }
+        waypoints.push(cell);
+    }
+    waypoints
+}
+
+/// The cells the straight line between the centers of `a` and `b` passes through, in
+/// order, and the pairs of cells beside each grid corner it passes exactly through. The
+/// line goes diagonally from one cell to the next at such a corner, touching the cells
+/// beside it only at a point.
+fn trace(a: Cell, b: Cell) -> (Vec<Cell>, Vec<(Cell, Cell)>) {
+    let (dx, dy) = (a.0.abs_diff(b.0) as isize, a.1.abs_diff(b.1) as isize);
+    let (sx, sy) = direction_from(a, b);
+    let (mut x, mut y) = (a.0 as isize, a.1 as isize);
+    let mut cells = vec![a];
+    let mut corners = Vec::new();
+    // Which of the next vertical and horizontal cell borders the line crosses first, as
+    // the difference of the distances to them scaled by 2 * dx * dy.
+    let mut error = dx - dy;
+    let mut remaining = dx + dy;
+    while remaining > 0 {
+        if error > 0 {
+            x += sx;
+            error -= 2 * dy;
+            remaining -= 1;
+        } else if error < 0 {
+            y += sy;
+            error += 2 * dx;
+            remaining -= 1;
+        } else {
+            corners.push((
+                ((x + sx) as usize, y as usize),
+                (x as usize, (y + sy) as usize),
+            ));
+            x += sx;
+            y += sy;
+            error += 2 * (dx - dy);
+            remaining -= 2;
+        }
+        cells.push((x as usize, y as usize));
+    }
+    (cells, corners)
+}
+
+/// Whether a straight line joins the centers of `a` and `b` without entering a wall or
+/// squeezing between two walls that meet at a corner, as with a diagonal step.
+fn line_of_sight(world: &GridWorld, a: Cell, b: Cell) -> bool {
+    let (cells, corners) = trace(a, b);
+    cells.into_iter().all(|cell| !world.is_wall(cell))
+        && corners
+            .into_iter()
+            .all(|(u, v)| !world.is_wall(u) && !world.is_wall(v))
+}
+
+/// The straight-line distance between the centers of `a` and `b`, in units of `STRAIGHT`.
+fn euclidean(a: Cell, b: Cell) -> f64 {
+    let (dx, dy) = (a.0.abs_diff(b.0) as f64, a.1.abs_diff(b.1) as f64);
+    STRAIGHT as f64 * dx.hypot(dy)
+}
+
+/// The shortest route from the start of `world` to its goal by A*, moving horizontally and
+/// vertically, and also diagonally if `diagonal`.
+///
+/// ```
+/// use cs365_bonus::grid_search::{astar, DIAGONAL, STRAIGHT};
+/// use cs365_bonus::gridworld::GridWorld;
+///
+/// let world = GridWorld::parse("S..\n...\n..G").unwrap();
+/// assert_eq!(astar(&world, false).unwrap().cost, 4 * STRAIGHT);
+/// assert_eq!(astar(&world, true).unwrap().cost, 2 * DIAGONAL);
+/// ```
+pub fn astar(world: &GridWorld, diagonal: bool) -> Option<GridRoute> {
+    let directions = if diagonal {
+        &DIRECTIONS[..]
+    } else {
+        &DIRECTIONS[..4]
+    };
+    let goal = world.goal();
+    search(
+        world,
+        |cell| estimate(cell, goal, diagonal),
+        |cell, _| {
+            directions
+                .iter()
+                .filter_map(|&direction| {
+                    Some((cell, can_move(world, cell, direction)?, cost(direction)))
+                })
+                .collect()
+        },
+    )
+}
+
+/// The shortest route from the start of `world` to its goal moving in eight directions, by
+/// Jump Point Search (Harabor and Grastien, 2011).
+///
+/// On a grid where every step of a kind costs the same, many shortest routes are
+/// permutations of the same moves. JPS breaks those ties by never expanding the cells in
+/// between: from each cell it keeps only the directions no other order of moves reaches
+/// more cheaply, and then jumps along each in a straight line until a wall forces a turn or
+/// the goal is in line. It finds the same cost as [`astar`] while expanding only the cells
+/// where the route could turn. Diagonal moves can't cut corners, as with `astar`.
+///
+/// ```
+/// use cs365_bonus::grid_search::{astar, jump_point_search};
+/// use cs365_bonus::gridworld::GridWorld;
+///
+/// let world = GridWorld::parse("S.......\n........\n....#...\n....#..G").unwrap();
+/// let (plain, jps) = (astar(&world, true).unwrap(), jump_point_search(&world).unwrap());
+/// assert_eq!(plain.cost, jps.cost);
+/// assert!(jps.expanded < plain.expanded);
+/// ```
+pub fn jump_point_search(world: &GridWorld) -> Option<GridRoute> {
+    let goal = world.goal();
+    search(
+        world,
+        |cell| estimate(cell, goal, true),
+        |cell, parent| {
+            let directions = match parent {
+                None => DIRECTIONS.to_vec(),
+                Some(parent) => pruned(world, cell, direction_from(parent, cell)),
+            };
+            directions
+                .into_iter()
+                .filter_map(|direction| {
+                    let point = jump(world, cell, direction)?;
+                    let steps = point.0.abs_diff(cell.0).max(point.1.abs_diff(cell.1));
+                    Some((cell, point, steps as Weight * cost(direction)))
+                })
+                .collect()
+        },
+    )
+}
+
+/// A short route from the start of `world` to its goal along straight lines at any angle,
+/// by Theta* (Nash, Daniel, Koenig, and Felner, 2007).
+///
+/// Theta* searches the eight neighbors of each cell like [`astar`], but when the parent of
+/// a cell can see a neighbor directly, it also tries joining the neighbor straight to that
+/// parent, at the straight-line distance between their centers. Routes then turn only at
+/// the corners of walls, rather than zig-zagging between the eight directions. The routes are
+/// not always the shortest possible at any angle, but keeping the plain steps as an option
+/// too keeps them, in practice, no longer than eight-directional ones. A line of sight may not pass through a wall or squeeze between two walls that
+/// meet at a corner.
+///
+/// ```
+/// use cs365_bonus::grid_search::{astar, theta_star, STRAIGHT};
+/// use cs365_bonus::gridworld::GridWorld;
+///
+/// let world = GridWorld::parse("S.....\n......\n.....G").unwrap();
+/// let route = theta_star(&world).unwrap();
+/// // One straight line of length sqrt(5 * 5 + 2 * 2), against 3 straight and 2 diagonal steps.
+/// assert_eq!(route.waypoints, vec![(0, 0), (5, 2)]);
+/// assert_eq!(route.cost, 5385);
+/// assert!(route.cost < astar(&world, true).unwrap().cost);
+/// ```
+pub fn theta_star(world: &GridWorld) -> Option<GridRoute> {
+    let goal = world.goal();
+    search(
+        world,
+        |cell| euclidean(cell, goal) as Weight,
+        |cell, parent| {
+            let mut successors = Vec::new();
+            for &direction in &DIRECTIONS {
+                let next = match can_move(world, cell, direction) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                successors.push((cell, next, cost(direction)));
+                if let Some(parent) = parent.filter(|&parent| line_of_sight(world, parent, next)) {
+                    successors.push((parent, next, euclidean(parent, next).round() as Weight));
+                }
+            }
+            successors
+        },
+    )
+}
+
+/// `route` with the corners cut wherever a straight line at any angle is clear and no
+/// longer than the stretch of route it replaces, as a post-processing pass with
+/// [`Path::simplified_with`] over the route's cells. It smooths the zig-zags of a route
+/// limited to four or eight directions, though unlike [`theta_star`] it only straightens
+/// the route it is given rather than searching for a better one.
+///
+/// ```
+/// use cs365_bonus::grid_search::{astar, smoothed};
+/// use cs365_bonus::gridworld::GridWorld;
+///
+/// let world = GridWorld::parse("S.....\n......\n.....G").unwrap();
+/// let route = astar(&world, false).unwrap();
+/// let smooth = smoothed(&world, &route);
+/// assert_eq!(smooth.waypoints, vec![(0, 0), (5, 2)]);
+/// assert!(smooth.cost < route.cost);
+/// ```
+pub fn smoothed(world: &GridWorld, route: &GridRoute) -> GridRoute {
+    let mut distance = vec![0];
+    for pair in route.cells.windows(2) {
+        distance.push(distance[distance.len() - 1] + cost(direction_from(pair[0], pair[1])));
+    }
+    let path = Path {
+        nodes: route.cells.iter().map(|&cell| world.node(cell)).collect(),
+        distance,
+        cost: route.cost,
+    };
+    let simple = path.simplified_with(|u, v| {
+        let (a, b) = (world.cell(u), world.cell(v));
+        line_of_sight(world, a, b).then(|| euclidean(a, b).round() as Weight)
+    });
+    let waypoints = simple.nodes.iter().map(|&node| world.cell(node)).collect();
+    along(turns(waypoints), simple.cost, route.expanded)
+}
+
+/// The unit step from `from` towards `to`.
+fn direction_from(from: Cell, to: Cell) -> (isize, isize) {
+    (
+        (to.0 as isize - from.0 as isize).signum(),
+        (to.1 as isize - from.1 as isize).signum(),
+    )
+}
+
+/// The directions worth searching from `cell` after arriving by `(dx, dy)`: straight on,
+/// the two straight parts of a diagonal, and any turn a wall behind makes necessary.
+fn pruned(world: &GridWorld, cell: Cell, (dx, dy): (isize, isize)) -> Vec<(isize, isize)> {
+    let mut directions = vec![(dx, dy)];
+    if dx != 0 && dy != 0 {
+        directions.extend([(dx, 0), (0, dy)]);
+        return directions;
+    }
+    // Arriving straight, a side cell whose neighbor behind is a wall can't be reached
+    // more cheaply without passing through `cell`.
+    let (sx, sy) = (dy.abs(), dx.abs());
+    for side in [1, -1] {
+        let (px, py) = (sx * side, sy * side);
+        if step(world, cell, (px, py)).is_some() && step(world, cell, (px - dx, py - dy)).is_none()
+        {
+            directions.extend([(px, py), (px + dx, py + dy)]);
+        }
+    }
+    directions
+}
+
+/// The first cell worth expanding going from `cell` in `direction`: the goal, a cell with
+/// a forced turn, or for a diagonal, a cell from which a straight jump finds one.
+fn jump(world: &GridWorld, mut cell: Cell, (dx, dy): (isize, isize)) -> Option<Cell> {
+    loop {
+        cell = can_move(world, cell, (dx, dy))?;
+        if cell == world.goal() {
+            return Some(cell);
+        }
+        if dx != 0 && dy != 0 {
+            if jump(world, cell, (dx, 0)).is_some() || jump(world, cell, (0, dy)).is_some() {
+                return Some(cell);
+            }
+        } else if pruned(world, cell, (dx, dy)).len() > 1 {
+            return Some(cell);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// A map of up to 13 by 13 cells with about 30% walls.
+    fn random_map(rng: &mut Rng) -> String {
+        let (width, height) = (2 + rng.below(12), 2 + rng.below(12));
+        let mut map = String::new();
+        let start = rng.below(width * height);
+        let mut goal = rng.below(width * height);
+        if goal == start {
+            goal = (goal + 1) % (width * height);
+        }
+        for i in 0..width * height {
+            map.push(match i {
+                _ if i == start => 'S',
+                _ if i == goal => 'G',
+                _ if rng.chance(0.3) => '#',
+                _ => '.',
+            });
+            if i % width == width - 1 {
+                map.push('\n');
+            }
+        }
+        map
+    }
+
+    #[test]
+    fn jumping_finds_the_same_cost() {
+        let mut rng = Rng::new(5);
+        for _ in 0..200 {
+            let map = random_map(&mut rng);
+            let world = GridWorld::parse(&map).unwrap();
+            let plain = astar(&world, true).map(|route| route.cost);
+            let jps = jump_point_search(&world);
+            assert_eq!(plain, jps.as_ref().map(|route| route.cost), "{}", map);
+            if let Some(jps) = jps {
+                // The filled-in route really is a sequence of legal moves of that cost.
+                let walked: Weight = jps
+                    .cells
+                    .windows(2)
+                    .map(|pair| {
+                        let direction = direction_from(pair[0], pair[1]);
+                        assert_eq!(can_move(&world, pair[0], direction), Some(pair[1]));
+                        cost(direction)
+                    })
+                    .sum();
+                assert_eq!(walked, jps.cost);
+            }
+        }
+    }
+
+    #[test]
+    fn any_angle_routes_are_no_longer() {
+        let mut rng = Rng::new(11);
+        for _ in 0..500 {
+            let map = random_map(&mut rng);
+            let world = GridWorld::parse(&map).unwrap();
+            let plain = astar(&world, true).map(|route| route.cost);
+            let theta = theta_star(&world);
+            assert_eq!(plain.is_some(), theta.is_some(), "{}", map);
+            if let (Some(plain), Some(theta)) = (plain, theta) {
+                assert!(theta.cost <= plain, "{}", map);
+                for pair in theta.waypoints.windows(2) {
+                    assert!(line_of_sight(&world, pair[0], pair[1]), "{}", map);
+                }
+                let smooth = smoothed(&world, &astar(&world, true).unwrap());
+                assert!(smooth.cost <= plain, "{}", map);
+                for pair in smooth.waypoints.windows(2) {
+                    assert!(line_of_sight(&world, pair[0], pair[1]), "{}", map);
+                }
+                for route in [&theta, &smooth] {
+                    for pair in route.cells.windows(2) {
+                        let direction = direction_from(pair[0], pair[1]);
+                        assert_eq!(can_move(&world, pair[0], direction), Some(pair[1]));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,428 @@
+use crate::dstar_lite::{DStarLite, BLOCKED};
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::shortest_path_tree;
+use crate::rng::Rng;
+use std::fmt;
+
+/// A maze of square cells, some of them walls, with a start and a goal.
+///
+/// Moving to a horizontal or vertical neighbor costs 1. Cells are nodes of
+/// [`graph`](Self::graph) numbered row by row and named `x,y`.
+///
+/// ```
+/// use cs365_bonus::gridworld::GridWorld;
+///
+/// let world = GridWorld::parse("S....#G\n.###.#.\n.#...#.\n.#####.\n.......").unwrap();
+/// assert_eq!(world.optimal_cost(), Some(14));
+/// // Seeing only its neighbors, the agent first heads right into the pocket.
+/// let walk = world.explore(1);
+/// assert!(walk.reached);
+/// assert_eq!(walk.cost(), 30);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridWorld {
+    width: usize,
+    height: usize,
+    walls: Vec<bool>,
+    start: (usize, usize),
+    goal: (usize, usize),
+}
+
+/// What the agent knows about a cell while exploring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Belief {
+    Unknown,
+    Free,
+    Wall,
+}
+
+/// The outcome of [`GridWorld::explore`].
+#[derive(Debug, Clone)]
+pub struct Exploration {
+    /// The cells the agent stepped through, from the start.
+    pub route: Vec<(usize, usize)>,
+    /// Whether the route ends at the goal. If not, the agent learned the goal is walled off.
+    pub reached: bool,
+    /// How many times a newly seen wall sent the agent back to its planner.
+    pub replans: usize,
+    /// How many nodes the planner expanded over the whole walk.
+    pub expanded: usize,
+    /// What the agent knew about each cell at the end, row by row.
+    pub belief: Vec<Belief>,
+}
+
+impl Exploration {
+    /// The cost of the route, one per step.
+    pub fn cost(&self) -> Weight {
+        self.route.len() as Weight - 1
+    }
+}
+
+impl GridWorld {
+    /// Read a map with one row per line: `#` for a wall, `.` or a space for a free cell,
+    /// and `S` and `G` for the start and the goal. Short rows are padded with free cells.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let rows: Vec<&str> = text.lines().collect();
+        let height = rows.len();
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut walls = vec![false; width * height];
+        let (mut start, mut goal) = (None, None);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '#' => walls[y * width + x] = true,
+                    '.' | ' ' => {}
+                    'S' if start.is_none() => start = Some((x, y)),
+                    'G' if goal.is_none() => goal = Some((x, y)),
+                    'S' | 'G' => return Err(format!("line {}: a second '{}'", y + 1, c)),
+                    _ => return Err(format!("line {}: unexpected '{}'", y + 1, c)),
+                }
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            walls,
+            start: start.ok_or("the map has no start 'S'")?,
+            goal: goal.ok_or("the map has no goal 'G'")?,
+        })
+    }
+
+    /// A random maze of `width` by `height` cells, rounded down to odd sizes of at least 3,
+    /// with walls between corridors one cell wide. It starts as a perfect maze carved by a
+    /// random depth-first search, with a single route between any two cells, and then a
+    /// `loops` fraction of the remaining inner walls are knocked down to open alternatives.
+    /// The start is in the top left corner and the goal in the bottom right.
+    pub fn maze(width: usize, height: usize, loops: f64, rng: &mut Rng) -> Self {
+        let (width, height) = ((width.max(3) - 1) | 1, (height.max(3) - 1) | 1);
+        let mut walls = vec![true; width * height];
+        walls[width + 1] = false;
+        let mut stack: Vec<(usize, usize)> = vec![(1, 1)];
+        while let Some(&(x, y)) = stack.last() {
+            let unvisited: Vec<(usize, usize)> = [
+                (x.wrapping_sub(2), y),
+                (x + 2, y),
+                (x, y.wrapping_sub(2)),
+                (x, y + 2),
+            ]
+            .iter()
+            .copied()
+            .filter(|&(nx, ny)| nx < width - 1 && ny < height - 1 && walls[ny * width + nx])
+            .collect();
+            if unvisited.is_empty() {
+                stack.pop();
+                continue;
+            }
+            let (nx, ny) = unvisited[rng.below(unvisited.len())];
+            walls[(y + ny) / 2 * width + (x + nx) / 2] = false;
+            walls[ny * width + nx] = false;
+            stack.push((nx, ny));
+        }
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                // A wall between two corridors, rather than at a corner where walls meet.
+                let between = (x + y) % 2 == 1;
+                if between && walls[y * width + x] && rng.chance(loops) {
+                    walls[y * width + x] = false;
+                }
+            }
+        }
+        Self {
+            width,
+            height,
+            walls,
+            start: (1, 1),
+            goal: (width - 2, height - 2),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn start(&self) -> (usize, usize) {
+        self.start
+    }
+
+    pub fn goal(&self) -> (usize, usize) {
+        self.goal
+    }
+
+    pub fn is_wall(&self, (x, y): (usize, usize)) -> bool {
+        self.walls[y * self.width + x]
+    }
+
+    /// Every cell joined to its neighbors, walls included, so that a planner can start out
+    /// not knowing where the walls are.
+    pub fn graph(&self) -> Graph {
+        self.build(false)
+    }
+
+    /// The cells of the shortest route from the start to the goal for an agent that knows
+    /// the whole map, or `None` if the goal is walled off.
+    pub fn optimal_route(&self) -> Option<Vec<(usize, usize)>> {
+        let tree = shortest_path_tree(&self.build(true), self.node(self.start));
+        let path = tree.path_to(self.node(self.goal))?;
+        Some(path.nodes.into_iter().map(|node| self.cell(node)).collect())
+    }
+
+    pub fn optimal_cost(&self) -> Option<Weight> {
+        self.optimal_route().map(|route| route.len() as Weight - 1)
+    }
+
+    /// An SVG drawing of the map with a route through the centers of `waypoints`, each
+    /// joined to the next by a straight line. Cells are 10 units across.
+    pub fn to_svg(&self, waypoints: &[(usize, usize)]) -> String {
+        const CELL: usize = 10;
+        let center = |(x, y): (usize, usize)| (x * CELL + CELL / 2, y * CELL + CELL / 2);
+        let (width, height) = (self.width * CELL, self.height * CELL);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\">\n<rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n",
+            w = width,
+            h = height
+        );
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.is_wall((x, y)) {
+                    svg += &format!(
+                        "<rect x=\"{}\" y=\"{}\" width=\"{c}\" height=\"{c}\" fill=\"black\"/>\n",
+                        x * CELL,
+                        y * CELL,
+                        c = CELL
+                    );
+                }
+            }
+        }
+        if !waypoints.is_empty() {
+            let points: Vec<String> = waypoints
+                .iter()
+                .map(|&cell| {
+                    let (x, y) = center(cell);
+                    format!("{},{}", x, y)
+                })
+                .collect();
+            svg += &format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"blue\" stroke-width=\"2\"/>\n",
+                points.join(" ")
+            );
+        }
+        for (cell, color) in [(self.start, "green"), (self.goal, "red")] {
+            let (x, y) = center(cell);
+            svg += &format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+                x,
+                y,
+                CELL / 3,
+                color
+            );
+        }
+        svg + "</svg>\n"
+    }
+
+    /// Walk from the start to the goal seeing only the cells within `radius` steps in each
+    /// direction, as a square around the agent.
+    ///
+    /// The agent assumes unseen cells are free and plans with [`DStarLite`]. Each step it
+    /// looks around, blocks the edges into any wall it sees for the first time, repairs its
+    /// plan, and moves to the next cell of the plan.
+    pub fn explore(&self, radius: usize) -> Exploration {
+        let graph = self.graph();
+        let goal = self.node(self.goal);
+        let heuristic = |u: NodeId, v: NodeId| {
+            let ((ux, uy), (vx, vy)) = (self.cell(u), self.cell(v));
+            (ux.abs_diff(vx) + uy.abs_diff(vy)) as Weight
+        };
+        let mut planner = DStarLite::new(&graph, self.node(self.start), goal, heuristic);
+        let mut belief = vec![Belief::Unknown; self.walls.len()];
+        let mut route = vec![self.start];
+        let mut replans = 0;
+        let mut here = self.start;
+        planner.replan();
+        loop {
+            let mut saw_wall = false;
+            for cell in self.around(here, radius) {
+                let i = self.node(cell).index();
+                if belief[i] != Belief::Unknown {
+                    continue;
+                }
+                belief[i] = if self.is_wall(cell) {
+                    Belief::Wall
+                } else {
+                    Belief::Free
+                };
+                if self.is_wall(cell) {
+                    saw_wall = true;
+                    for edge in graph.edges_of(self.node(cell)) {
+                        planner.set_cost(edge.id, BLOCKED);
+                    }
+                }
+            }
+            if saw_wall {
+                replans += 1;
+                planner.replan();
+            }
+            if here == self.goal {
+                break;
+            }
+            match planner.next_step(self.node(here)) {
+                Some(next) => {
+                    here = self.cell(next);
+                    planner.move_to(next);
+                    route.push(here);
+                }
+                None => break,
+            }
+        }
+        Exploration {
+            reached: here == self.goal,
+            route,
+            replans,
+            expanded: planner.expanded(),
+            belief,
+        }
+    }
+
+    /// The grid's graph, leaving walls without edges if `skip_walls`.
+    fn build(&self, skip_walls: bool) -> Graph {
+        let open = |cell| !(skip_walls && self.is_wall(cell));
+        let mut graph = Graph::with_capacity(self.walls.len(), 2 * self.walls.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                graph.get_or_insert_node(&format!("{},{}", x, y));
+            }
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let node = self.node((x, y));
+                if !open((x, y)) {
+                    continue;
+                }
+                if x + 1 < self.width && open((x + 1, y)) {
+                    graph.add_bidirectional_edge(node, self.node((x + 1, y)), 1);
+                }
+                if y + 1 < self.height && open((x, y + 1)) {
+                    graph.add_bidirectional_edge(node, self.node((x, y + 1)), 1);
+                }
+            }
+        }
+        graph
+    }
+
+    /// The node of [`graph`](Self::graph) for a cell.
+    pub fn node(&self, (x, y): (usize, usize)) -> NodeId {
+        NodeId::new(y * self.width + x)
+    }
+
+    /// The cell of a node of [`graph`](Self::graph).
+    pub fn cell(&self, node: NodeId) -> (usize, usize) {
+        (node.index() % self.width, node.index() / self.width)
+    }
+
+    /// The cells at most `radius` steps away from `(x, y)` along each axis.
+    fn around(&self, (x, y): (usize, usize), radius: usize) -> Vec<(usize, usize)> {
+        let xs = x.saturating_sub(radius)..=(x + radius).min(self.width - 1);
+        let ys = y.saturating_sub(radius)..=(y + radius).min(self.height - 1);
+        ys.flat_map(|y| xs.clone().map(move |x| (x, y))).collect()
+    }
+}
+
+/// The map in the format [`parse`](GridWorld::parse) reads.
+impl fmt::Display for GridWorld {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height {
+            let row: String = (0..self.width)
+                .map(|x| match (x, y) {
+                    cell if cell == self.start => 'S',
+                    cell if cell == self.goal => 'G',
+                    cell if self.is_wall(cell) => '#',
+                    _ => '.',
+                })
+                .collect();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether `route` takes single steps between free cells.
+    fn is_walk(world: &GridWorld, route: &[(usize, usize)]) -> bool {
+        route.iter().all(|&cell| !world.is_wall(cell))
+            && route
+                .windows(2)
+                .all(|pair| pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1) == 1)
+    }
+
+    #[test]
+    fn explores_random_mazes() {
+        let mut rng = Rng::new(52);
+        for _ in 0..50 {
+            let (width, height) = (3 + rng.below(20), 3 + rng.below(20));
+            let world = GridWorld::maze(width, height, rng.next_f64() / 2.0, &mut rng);
+            assert_eq!(world.width() % 2, 1);
+            assert_eq!(world.height() % 2, 1);
+            assert_eq!(GridWorld::parse(&world.to_string()).unwrap(), world);
+
+            // Mazes are connected, so the goal is always reachable.
+            let optimal = world.optimal_route().unwrap();
+            assert!(is_walk(&world, &optimal));
+            assert_eq!(optimal[0], world.start());
+            assert_eq!(optimal[optimal.len() - 1], world.goal());
+
+            let radius = 1 + rng.below(3);
+            let walk = world.explore(radius);
+            assert!(walk.reached);
+            assert!(is_walk(&world, &walk.route));
+            assert!(walk.cost() >= world.optimal_cost().unwrap());
+            for &cell in &walk.route {
+                assert_eq!(walk.belief[world.node(cell).index()], Belief::Free);
+            }
+
+            // Seeing the whole map from the start, the agent walks straight to the goal.
+            let walk = world.explore(world.width().max(world.height()));
+            assert_eq!(walk.cost(), world.optimal_cost().unwrap());
+            assert!(walk.replans <= 1);
+        }
+    }
+
+    #[test]
+    fn gives_up_on_a_walled_off_goal() {
+        let world = GridWorld::parse("S..#.\n.#.#G\n...#.").unwrap();
+        assert_eq!(world.optimal_route(), None);
+        let walk = world.explore(1);
+        assert!(!walk.reached);
+        assert!(is_walk(&world, &walk.route));
+        // It gives up only once it has seen the whole wall in the way.
+        for y in 0..3 {
+            assert_eq!(walk.belief[world.node((3, y)).index()], Belief::Wall);
+        }
+
+        assert_eq!(
+            GridWorld::parse("S.\nSG").unwrap_err(),
+            "line 2: a second 'S'"
+        );
+        assert_eq!(
+            GridWorld::parse("S?G").unwrap_err(),
+            "line 1: unexpected '?'"
+        );
+        assert_eq!(
+            GridWorld::parse("S..").unwrap_err(),
+            "the map has no goal 'G'"
+        );
+        // Short rows are padded with free cells.
+        let world = GridWorld::parse("S\n..G").unwrap();
+        assert_eq!(world.optimal_cost(), Some(3));
+    }
+}
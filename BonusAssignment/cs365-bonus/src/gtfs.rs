@@ -0,0 +1,269 @@
+//! Import of GTFS transit feeds, extracted into a directory.
+//!
+//! Stops become nodes named by `stop_id`, and each pair of consecutive stops on a trip
+//! becomes a directed edge whose travel time depends on when you arrive to catch it.
+
+use crate::graph::{Graph, Weight};
+use crate::time_dependent::TravelTime;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Seconds in a day. Service after the last departure waits for the next day's first.
+const DAY: Weight = 24 * 60 * 60;
+
+/// A transit network: directed edges between stops and their travel time functions.
+#[derive(Debug, Clone)]
+pub struct GtfsNetwork {
+    pub graph: Graph,
+    /// The travel time of each edge in seconds, indexed by edge id.
+    pub profiles: Vec<Option<TravelTime>>,
+}
+
+/// Load `stops.txt` and `stop_times.txt` from an extracted GTFS feed.
+///
+/// Every trip is assumed to run daily; calendars and frequencies are not read.
+pub fn load_gtfs(dir: &Path) -> Result<GtfsNetwork, String> {
+    let stops = read_table(&dir.join("stops.txt"))?;
+    let stop_times = read_table(&dir.join("stop_times.txt"))?;
+
+    let mut graph = Graph::new();
+    let stop_id = stops.column("stop_id")?;
+    for row in stops.rows.iter() {
+        graph.get_or_insert_node(&node_name(&row[stop_id]));
+    }
+
+    let (trip, stop, sequence, arrival, departure) = (
+        stop_times.column("trip_id")?,
+        stop_times.column("stop_id")?,
+        stop_times.column("stop_sequence")?,
+        stop_times.column("arrival_time")?,
+        stop_times.column("departure_time")?,
+    );
+    let mut trips: HashMap<&str, Vec<(u32, &str, Weight, Weight)>> = HashMap::new();
+    for (i, row) in stop_times.rows.iter().enumerate() {
+        let invalid = || format!("invalid row {} in stop_times.txt", i + 2);
+        let seq: u32 = row[sequence].parse().map_err(|_| invalid())?;
+        let arrive = parse_time(&row[arrival]).ok_or_else(invalid)?;
+        let depart = parse_time(&row[departure]).ok_or_else(invalid)?;
+        trips.entry(row[trip].as_str()).or_default().push((
+            seq,
+            row[stop].as_str(),
+            arrive,
+            depart,
+        ));
+    }
+
+    // Departure and arrival time of every scheduled hop between two stops.
+    let mut connections: HashMap<(String, String), Vec<(Weight, Weight)>> = HashMap::new();
+    for (_, mut calls) in trips {
+        calls.sort_by_key(|&(seq, ..)| seq);
+        for pair in calls.windows(2) {
+            let (_, from, _, depart) = pair[0];
+            let (_, to, arrive, _) = pair[1];
+            if arrive >= depart {
+                connections
+                    .entry((node_name(from), node_name(to)))
+                    .or_default()
+                    .push((depart, arrive));
+            }
+        }
+    }
+
+    let mut pairs: Vec<_> = connections.into_iter().collect();
+    pairs.sort();
+    let mut profiles = Vec::with_capacity(pairs.len());
+    for ((from, to), hops) in pairs {
+        let from = graph.get_or_insert_node(&from);
+        let to = graph.get_or_insert_node(&to);
+        let fastest = hops.iter().map(|&(d, a)| a - d).min().unwrap_or(0);
+        graph.add_edge(from, to, fastest);
+        profiles.push(Some(timetable_profile(hops)));
+    }
+
+    Ok(GtfsNetwork { graph, profiles })
+}
+
+/// The travel time function for a stop pair: wait for the next hop, then ride it.
+///
+/// Between departures this falls with slope -1 towards the next hop's ride time, which is
+/// exactly representable with two breakpoints per departure.
+fn timetable_profile(mut hops: Vec<(Weight, Weight)>) -> TravelTime {
+    // Only the earliest arrival reachable from each departure time matters.
+    hops.sort_unstable();
+    let mut best: Vec<(Weight, Weight)> = Vec::with_capacity(hops.len());
+    for &(depart, arrive) in hops.iter().rev() {
+        match best.last_mut() {
+            Some(last) if last.0 == depart => last.1 = last.1.min(arrive),
+            Some(last) if last.1 <= arrive => continue,
+            _ => best.push((depart, arrive)),
+        }
+    }
+    best.reverse();
+
+    let mut points = Vec::with_capacity(2 * best.len() + 2);
+    let mut previous: Option<Weight> = None;
+    for &(depart, arrive) in best.iter() {
+        let start = previous.map_or(0, |p| p + 1);
+        if start < depart {
+            points.push((start, arrive - start));
+        }
+        points.push((depart, arrive - depart));
+        previous = Some(depart);
+    }
+    if let (Some(&(first_depart, first_arrive)), Some(&(last, last_arrive))) =
+        (best.first(), best.last())
+    {
+        let next_day = first_depart + DAY;
+        if last + 1 < next_day && last_arrive <= first_arrive + DAY {
+            points.push((last + 1, first_arrive + DAY - (last + 1)));
+            points.push((next_day, first_arrive - first_depart));
+        }
+    }
+    TravelTime::new(points).expect("timetable profiles are FIFO")
+}
+
+/// Node names can't contain whitespace in the edge list format.
+fn node_name(stop_id: &str) -> String {
+    stop_id.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+/// Parse `HH:MM:SS`, where hours may exceed 23 for trips running past midnight.
+fn parse_time(s: &str) -> Option<Weight> {
+    let mut parts = s.trim().split(':');
+    let h: Weight = parts.next()?.parse().ok()?;
+    let m: Weight = parts.next()?.parse().ok()?;
+    let sec: Weight = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || m >= 60 || sec >= 60 {
+        return None;
+    }
+    Some(h * 3600 + m * 60 + sec)
+}
+
+struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn column(&self, name: &str) -> Result<usize, String> {
+        self.header
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| format!("GTFS table is missing the '{}' column", name))
+    }
+}
+
+fn read_table(path: &Path) -> Result<Table, String> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to open '{}': {}", path.display(), e))?;
+    let data = data.strip_prefix('\u{feff}').unwrap_or(&data);
+    let mut lines = data.lines().filter(|line| !line.trim().is_empty());
+    let header = split_csv(lines.next().unwrap_or(""));
+    let rows: Vec<Vec<String>> = lines.map(split_csv).collect();
+    if let Some(i) = rows.iter().position(|row| row.len() != header.len()) {
+        return Err(format!(
+            "row {} of '{}' has the wrong number of fields",
+            i + 2,
+            path.display()
+        ));
+    }
+    Ok(Table { header, rows })
+}
+
+/// Split one CSV record, honoring double-quoted fields with `""` escapes.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields.iter().map(|f| f.trim().to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn profiles_match_waiting_for_the_next_hop() {
+        let mut rng = Rng::new(28);
+        for _ in 0..50 {
+            let hops: Vec<(Weight, Weight)> = (0..1 + rng.below(6))
+                .map(|_| {
+                    let depart = rng.below(2000) as Weight;
+                    (depart, depart + 1 + rng.below(300) as Weight)
+                })
+                .collect();
+            let profile = timetable_profile(hops.clone());
+            for t in (0..2400).step_by(7) {
+                // Catch a hop later today, or wait for one tomorrow.
+                let today = hops.iter().filter(|&&(d, _)| d >= t).map(|&(_, a)| a);
+                let tomorrow = hops.iter().map(|&(_, a)| a + DAY);
+                let arrive = today.chain(tomorrow).min().unwrap();
+                assert_eq!(
+                    profile.at(t),
+                    arrive - t,
+                    "departing at {} on {:?}",
+                    t,
+                    hops
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fields_and_times_parse() {
+        assert_eq!(
+            split_csv(r#"a, "b,c" ,"say ""hi""""#),
+            ["a", "b,c", r#"say "hi""#]
+        );
+        assert_eq!(parse_time("25:01:02"), Some(25 * 3600 + 62));
+        assert_eq!(parse_time("08:60:00"), None);
+        assert_eq!(parse_time("8:00"), None);
+        assert_eq!(node_name("Main St"), "Main_St");
+    }
+
+    #[test]
+    fn loads_a_feed() {
+        let dir = std::env::temp_dir().join(format!("cs365-gtfs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("stops.txt"),
+            "\u{feff}stop_id,stop_name\na,A\nb,B\nc,C\n",
+        )
+        .unwrap();
+        let stop_times = "trip_id,arrival_time,departure_time,stop_id,stop_sequence\n\
+                          t1,08:10:00,08:10:00,b,2\n\
+                          t1,08:00:00,08:00:00,a,1\n\
+                          t1,08:30:00,08:30:00,c,3\n\
+                          t2,09:00:00,09:00:00,a,1\n\
+                          t2,09:05:00,09:05:00,b,2\n";
+        std::fs::write(dir.join("stop_times.txt"), stop_times).unwrap();
+        let network = load_gtfs(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let network = network.unwrap();
+        let graph = &network.graph;
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 2);
+        let (a, b) = (graph.get_node("a").unwrap(), graph.get_node("b").unwrap());
+        let edge = graph.find_edge(a, b).unwrap();
+        assert_eq!(graph.edge_weight(edge), Some(5 * 60));
+        let profile = network.profiles[edge.index()].as_ref().unwrap();
+        // Arriving at 08:30 means waiting half an hour for the 09:00 trip.
+        assert_eq!(profile.at(8 * 3600 + 1800), 35 * 60);
+        assert_eq!(profile.at(8 * 3600), 10 * 60);
+    }
+}
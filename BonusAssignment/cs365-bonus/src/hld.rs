@@ -0,0 +1,237 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::lca::RootedForest;
+
+/// Heavy-light decomposition of a forest, answering sum and maximum queries over the edge
+/// weights of tree paths.
+///
+/// Every node follows its largest child on the same chain, so any path crosses O(log n)
+/// chains. Chains are laid out contiguously in a segment tree, making each query
+/// O(log² n).
+#[derive(Debug, Clone)]
+pub struct HeavyLight {
+    parent: Vec<Option<NodeId>>,
+    depth: Vec<usize>,
+    root: Vec<NodeId>,
+    /// The top node of each node's chain.
+    head: Vec<NodeId>,
+    /// Each node's position in the segment tree, which holds the weight of its parent edge.
+    position: Vec<usize>,
+    tree: SegmentTree,
+}
+
+impl HeavyLight {
+    /// Decompose `graph`, ignoring edge direction. Returns `None` if it has a cycle,
+    /// including self-loops and parallel edges.
+    pub fn new(graph: &Graph) -> Option<Self> {
+        let forest = RootedForest::new(graph)?;
+        let n = graph.node_count();
+
+        let mut size = vec![1; n];
+        let mut heavy: Vec<Option<NodeId>> = vec![None; n];
+        for &node in forest.order.iter().rev() {
+            if let Some(parent) = forest.parent[node.index()] {
+                size[parent.index()] += size[node.index()];
+                let current = heavy[parent.index()];
+                if current.is_none_or(|child| size[child.index()] < size[node.index()]) {
+                    heavy[parent.index()] = Some(node);
+                }
+            }
+        }
+        let mut children: Vec<Vec<NodeId>> = vec![Vec::new(); n];
+        for &node in forest.order.iter() {
+            if let Some(parent) = forest.parent[node.index()] {
+                children[parent.index()].push(node);
+            }
+        }
+
+        // Pushing the heavy child last makes it the next node numbered, so each chain
+        // occupies consecutive positions.
+        let mut head: Vec<NodeId> = graph.node_ids().collect();
+        let mut position = vec![0; n];
+        let mut weights = vec![0; n];
+        let mut next = 0;
+        let mut stack = Vec::new();
+        for root in graph.node_ids() {
+            if forest.parent[root.index()].is_some() {
+                continue;
+            }
+            stack.push(root);
+            while let Some(node) = stack.pop() {
+                position[node.index()] = next;
+                weights[next] = forest.parent_cost[node.index()];
+                next += 1;
+                let heavy = heavy[node.index()];
+                for &child in children[node.index()].iter() {
+                    if Some(child) != heavy {
+                        stack.push(child);
+                    }
+                }
+                if let Some(child) = heavy {
+                    head[child.index()] = head[node.index()];
+                    stack.push(child);
+                }
+            }
+        }
+
+        Some(Self {
+            parent: forest.parent,
+            depth: forest.depth,
+            root: forest.root,
+            head,
+            position,
+            tree: SegmentTree::new(&weights),
+        })
+    }
+
+    /// The sum and maximum of the edge weights on the path between `a` and `b`, or `None`
+    /// if they are in different trees. The maximum is `None` when `a == b`.
+    pub fn path_aggregate(&self, a: NodeId, b: NodeId) -> Option<(Weight, Option<Weight>)> {
+        if self.root[a.index()] != self.root[b.index()] {
+            return None;
+        }
+        let (mut a, mut b) = (a, b);
+        let mut total = Aggregate::EMPTY;
+        while self.head[a.index()] != self.head[b.index()] {
+            if self.depth[self.head[a.index()].index()] < self.depth[self.head[b.index()].index()] {
+                std::mem::swap(&mut a, &mut b);
+            }
+            let head = self.head[a.index()];
+            total = total.join(
+                self.tree
+                    .query(self.position[head.index()], self.position[a.index()] + 1),
+            );
+            a = self.parent[head.index()]?;
+        }
+        if self.depth[a.index()] > self.depth[b.index()] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        // `a` is now the common ancestor, whose own parent edge isn't on the path.
+        total = total.join(
+            self.tree
+                .query(self.position[a.index()] + 1, self.position[b.index()] + 1),
+        );
+        Some((total.sum, total.max))
+    }
+
+    /// The total weight of the path between `a` and `b`.
+    pub fn path_sum(&self, a: NodeId, b: NodeId) -> Option<Weight> {
+        self.path_aggregate(a, b).map(|(sum, _)| sum)
+    }
+
+    /// The heaviest edge on the path between `a` and `b`.
+    pub fn path_max(&self, a: NodeId, b: NodeId) -> Option<Weight> {
+        self.path_aggregate(a, b).and_then(|(_, max)| max)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Aggregate {
+    sum: Weight,
+    max: Option<Weight>,
+}
+
+impl Aggregate {
+    const EMPTY: Aggregate = Aggregate { sum: 0, max: None };
+
+    fn join(self, other: Aggregate) -> Aggregate {
+        Aggregate {
+            sum: self.sum + other.sum,
+            max: self.max.max(other.max),
+        }
+    }
+}
+
+/// A bottom-up segment tree of sums and maxima.
+#[derive(Debug, Clone)]
+struct SegmentTree {
+    len: usize,
+    nodes: Vec<Aggregate>,
+}
+
+impl SegmentTree {
+    fn new(values: &[Weight]) -> Self {
+        let len = values.len();
+        let mut nodes = vec![Aggregate::EMPTY; 2 * len];
+        for (i, &value) in values.iter().enumerate() {
+            nodes[len + i] = Aggregate {
+                sum: value,
+                max: Some(value),
+            };
+        }
+        for i in (1..len).rev() {
+            nodes[i] = nodes[2 * i].join(nodes[2 * i + 1]);
+        }
+        Self { len, nodes }
+    }
+
+    /// The aggregate of positions `[lo, hi)`.
+    fn query(&self, lo: usize, hi: usize) -> Aggregate {
+        let mut total = Aggregate::EMPTY;
+        let (mut lo, mut hi) = (lo + self.len, hi + self.len);
+        while lo < hi {
+            if lo & 1 == 1 {
+                total = total.join(self.nodes[lo]);
+                lo += 1;
+            }
+            if hi & 1 == 1 {
+                hi -= 1;
+                total = total.join(self.nodes[hi]);
+            }
+            lo /= 2;
+            hi /= 2;
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::find_shortest_path;
+    use crate::rng::Rng;
+
+    #[test]
+    fn path_queries_match_the_tree_path() {
+        let mut rng = Rng::new(16);
+        for _ in 0..50 {
+            // A random forest, each node joined to an earlier one or starting a tree.
+            let n = 1 + rng.below(25);
+            let mut graph = Graph::new();
+            let nodes: Vec<NodeId> = (0..n)
+                .map(|i| graph.get_or_insert_node(&i.to_string()))
+                .collect();
+            for i in 1..n {
+                if rng.chance(0.9) {
+                    let cost = rng.below(20) as Weight;
+                    graph.add_bidirectional_edge(nodes[rng.below(i)], nodes[i], cost);
+                }
+            }
+            let hld = HeavyLight::new(&graph).unwrap();
+            for &a in &nodes {
+                for &b in &nodes {
+                    let path = find_shortest_path(&graph, a, b);
+                    let costs: Option<Vec<Weight>> = path.as_ref().map(|path| {
+                        path.distance
+                            .windows(2)
+                            .map(|pair| pair[1] - pair[0])
+                            .collect()
+                    });
+                    let sum = costs.as_ref().map(|costs| costs.iter().sum());
+                    let max = costs.as_ref().and_then(|costs| costs.iter().copied().max());
+                    assert_eq!(hld.path_sum(a, b), sum);
+                    assert_eq!(hld.path_max(a, b), max);
+                    assert_eq!(
+                        hld.path_aggregate(a, b).map(|(_, max)| max),
+                        costs.map(|_| max)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let square: Graph = "a b 1\nb c 1\nc d 1\nd a 1".parse().unwrap();
+        assert!(HeavyLight::new(&square).is_none());
+    }
+}
@@ -0,0 +1,108 @@
+//! Cooperative Ctrl-C handling for long-running jobs.
+//!
+//! The first interrupt only raises a flag that jobs poll with [`interrupted`], giving them
+//! a chance to flush partial results. A second interrupt exits immediately.
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static SIMULATED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Exit status conventionally used for processes stopped by SIGINT.
+pub const EXIT_CODE: i32 = 130;
+
+/// Whether an interrupt has been received since [`install_handler`] was called.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst) || SIMULATED.with(Cell::get)
+}
+
+/// Make [`interrupted`] return `true` on the current thread only, until cleared.
+///
+/// For tests: they share one process, so raising the real flag would stop the jobs of
+/// every other test too.
+#[doc(hidden)]
+pub fn simulate(raised: bool) {
+    SIMULATED.with(|flag| flag.set(raised));
+}
+
+fn on_interrupt() {
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        sys::exit_now(EXIT_CODE);
+    }
+}
+
+/// Install the Ctrl-C handler, returning `false` if the platform isn't supported.
+pub fn install_handler() -> bool {
+    sys::install()
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::os::raw::c_int;
+
+    const SIGINT: c_int = 2;
+    const SIG_ERR: usize = !0;
+
+    extern "C" {
+        fn signal(signum: c_int, handler: usize) -> usize;
+        fn _exit(status: c_int) -> !;
+    }
+
+    extern "C" fn handler(_: c_int) {
+        super::on_interrupt();
+    }
+
+    pub fn install() -> bool {
+        let handler: extern "C" fn(c_int) = handler;
+        unsafe { signal(SIGINT, handler as usize) != SIG_ERR }
+    }
+
+    pub fn exit_now(code: i32) -> ! {
+        // `_exit` is async-signal-safe, unlike `std::process::exit`.
+        unsafe { _exit(code) }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    const CTRL_C_EVENT: u32 = 0;
+
+    extern "system" {
+        fn SetConsoleCtrlHandler(
+            handler: Option<unsafe extern "system" fn(u32) -> i32>,
+            add: i32,
+        ) -> i32;
+    }
+
+    unsafe extern "system" fn handler(event: u32) -> i32 {
+        if event == CTRL_C_EVENT {
+            super::on_interrupt();
+            1
+        } else {
+            0
+        }
+    }
+
+    pub fn install() -> bool {
+        unsafe { SetConsoleCtrlHandler(Some(handler), 1) != 0 }
+    }
+
+    pub fn exit_now(code: i32) -> ! {
+        std::process::exit(code)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub fn install() -> bool {
+        false
+    }
+
+    pub fn exit_now(code: i32) -> ! {
+        std::process::exit(code)
+    }
+}
@@ -0,0 +1,579 @@
+use crate::graph::{Graph, NodeId, Weight};
+use std::collections::{HashMap, VecDeque};
+
+/// What joins an ordered pair of nodes: whether each edge from the first to the second is
+/// directed, and its cost if costs count, sorted.
+type Label = Vec<(bool, Weight)>;
+
+/// What a node's color is refined from: its previous color, and for each neighbor the
+/// edges to and from it and its color.
+type Signature = (usize, Vec<(Label, Label, usize)>);
+
+/// A graph's edges between each ordered pair of nodes, the form isomorphism compares.
+struct Structure {
+    arcs: HashMap<(usize, usize), Label>,
+    /// Each node's neighbors either way, other than itself.
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Structure {
+    fn new(graph: &Graph, weighted: bool) -> Self {
+        let mut arcs: HashMap<(usize, usize), Label> = HashMap::new();
+        let mut neighbors = vec![Vec::new(); graph.node_count()];
+        for (id, src, dest, cost) in graph.edges() {
+            let (u, v) = (src.index(), dest.index());
+            let directed = graph.is_edge_directed(id) == Some(true);
+            let cost = if weighted { cost } else { 0 };
+            arcs.entry((u, v)).or_default().push((directed, cost));
+            if !directed && u != v {
+                arcs.entry((v, u)).or_default().push((false, cost));
+            }
+            if u != v {
+                neighbors[u].push(v);
+                neighbors[v].push(u);
+            }
+        }
+        for label in arcs.values_mut() {
+            label.sort_unstable();
+        }
+        for list in &mut neighbors {
+            list.sort_unstable();
+            list.dedup();
+        }
+        Self { arcs, neighbors }
+    }
+
+    fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    fn arcs(&self, u: usize, v: usize) -> &[(bool, Weight)] {
+        self.arcs.get(&(u, v)).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// A one-to-one mapping of `a`'s nodes onto `b`'s that carries every edge onto an edge, or
+/// `None` if the graphs aren't isomorphic. Names are ignored, as are costs unless
+/// `weighted`, but direction counts: an undirected edge only maps onto an undirected edge.
+///
+/// Nodes are first colored by refining their degrees with their neighbors' colors until
+/// nothing changes, over both graphs at once, which settles most pairs of graphs that
+/// aren't isomorphic without any search. A VF2-style backtracking search then maps nodes
+/// one at a time, each onto a node of the same color joined the same way to those already
+/// mapped. The search is exponential at worst, so this is meant for small graphs.
+///
+/// ```
+/// use cs365_bonus::isomorphism::find_isomorphism;
+///
+/// let square: cs365_bonus::Graph = "a b 1\nb c 1\nc d 1\nd a 1".parse().unwrap();
+/// let renamed: cs365_bonus::Graph = "w y 1\ny x 1\nx z 1\nz w 1".parse().unwrap();
+/// let path: cs365_bonus::Graph = "a b 1\nb c 1\nc d 1\nd e 1".parse().unwrap();
+/// let mapping = find_isomorphism(&square, &renamed, true).unwrap();
+/// assert_eq!(renamed.get_node_name(mapping[1]), Some("y"));
+/// assert!(find_isomorphism(&square, &path, false).is_none());
+/// ```
+pub fn find_isomorphism(a: &Graph, b: &Graph, weighted: bool) -> Option<Vec<NodeId>> {
+    if a.node_count() != b.node_count() || a.edge_count() != b.edge_count() {
+        return None;
+    }
+    let (a, b) = (Structure::new(a, weighted), Structure::new(b, weighted));
+    let (colors_a, colors_b) = refine(&a, &b);
+    let mut histogram = HashMap::new();
+    for &color in &colors_a {
+        *histogram.entry(color).or_insert(0i64) += 1;
+    }
+    for &color in &colors_b {
+        *histogram.entry(color).or_insert(0) -= 1;
+    }
+    if histogram.values().any(|&count| count != 0) {
+        return None;
+    }
+
+    let mut search = Search {
+        a: &a,
+        b: &b,
+        colors_a: &colors_a,
+        colors_b: &colors_b,
+        order: search_order(&a, &colors_a),
+        mapping: vec![None; a.len()],
+        used: vec![false; b.len()],
+    };
+    if !search.extend(0) {
+        return None;
+    }
+    Some(
+        search
+            .mapping
+            .into_iter()
+            .map(|node| NodeId::new(node.expect("every node is mapped")))
+            .collect(),
+    )
+}
+
+/// Color the nodes of both graphs alike: first by the edges from each node to itself and
+/// its degree, then by the color of each neighbor and the edges to it, until the number of
+/// colors stops growing.
+fn refine(a: &Structure, b: &Structure) -> (Vec<usize>, Vec<usize>) {
+    let mut ids: HashMap<Signature, usize> = HashMap::new();
+    let mut colors = [vec![0; a.len()], vec![0; b.len()]];
+    let graphs = [a, b];
+    let mut count = 0;
+    for round in 0..=a.len() {
+        let mut next = [Vec::new(), Vec::new()];
+        for (side, graph) in graphs.iter().enumerate() {
+            for u in 0..graph.len() {
+                let key = if round == 0 {
+                    let own = graph.arcs(u, u).to_vec();
+                    (graph.neighbors[u].len(), vec![(own, Label::new(), 0)])
+                } else {
+                    let mut around: Vec<(Label, Label, usize)> = graph.neighbors[u]
+                        .iter()
+                        .map(|&v| {
+                            (
+                                graph.arcs(u, v).to_vec(),
+                                graph.arcs(v, u).to_vec(),
+                                colors[side][v],
+                            )
+                        })
+                        .collect();
+                    around.sort_unstable();
+                    (colors[side][u], around)
+                };
+                let fresh = ids.len();
+                next[side].push(*ids.entry(key).or_insert(fresh));
+            }
+        }
+        ids.clear();
+        colors = next;
+        let distinct = {
+            let mut all: Vec<usize> = colors.iter().flatten().copied().collect();
+            all.sort_unstable();
+            all.dedup();
+            all.len()
+        };
+        if round > 0 && distinct == count {
+            break;
+        }
+        count = distinct;
+    }
+    let [a, b] = colors;
+    (a, b)
+}
+
+/// A digest of a graph's shape that ignores node names, so that isomorphic graphs always
+/// get the same one, counting costs if `weighted` and edge directions in any case.
+///
+/// A tree's digest is that of its [canonical form](tree_canonical_form), which no other
+/// tree shares. Other graphs are hashed by color refinement, the Weisfeiler-Lehman test:
+/// each node's color starts from its degree and is rehashed with its neighbors' colors
+/// until the colors stop splitting, and the digest covers the resulting colors. Graphs that
+/// refinement can't tell apart, such as two regular graphs of the same size and degree,
+/// share a digest, so a match is worth confirming with [`find_isomorphism`]. Hashing is
+/// FNV-1a, which stays the same across builds.
+///
+/// ```
+/// use cs365_bonus::isomorphism::graph_hash;
+///
+/// let a: cs365_bonus::Graph = "a b 1\nb c 2\nc a 3\nc d 1".parse().unwrap();
+/// let b: cs365_bonus::Graph = "z y 1\ny x 2\nx w 1\nx z 3".parse().unwrap();
+/// let c: cs365_bonus::Graph = "a b 1\nb c 1\nc a 1\nc d 1".parse().unwrap();
+/// assert_eq!(graph_hash(&a, true), graph_hash(&b, true));
+/// assert_ne!(graph_hash(&a, true), graph_hash(&c, true));
+/// assert_eq!(graph_hash(&a, false), graph_hash(&c, false));
+/// ```
+pub fn graph_hash(graph: &Graph, weighted: bool) -> u64 {
+    if let Some(form) = tree_canonical_form(graph, weighted) {
+        return fnv(form.bytes().map(u64::from).chain([0, 1]));
+    }
+    let structure = Structure::new(graph, weighted);
+    let mut colors = hashed_colors(&structure, None).pop().unwrap_or_default();
+    colors.sort_unstable();
+    let size = [graph.node_count() as u64, graph.edge_count() as u64];
+    fnv(size.iter().copied().chain(colors))
+}
+
+/// How alike two graphs' shapes are by the Weisfeiler-Lehman subtree kernel, from 0 for
+/// nothing in common to 1 for graphs refinement can't tell apart, counting costs if
+/// `weighted`. There is one value for each number of refinement rounds from 0 to
+/// `iterations`, each covering the rounds up to it, so later values compare ever larger
+/// neighborhoods.
+///
+/// Each round gives each node a color hashed from its color and its neighbors' colors in
+/// the round before, starting from its degree, exactly as [`graph_hash`] does. The kernel
+/// counts the pairs of nodes, one from each graph, that share a color in each round, and
+/// is normalized by each graph's kernel with itself.
+///
+/// ```
+/// use cs365_bonus::isomorphism::wl_similarity;
+///
+/// let square: cs365_bonus::Graph = "a b 1\nb c 1\nc d 1\nd a 1".parse().unwrap();
+/// let path: cs365_bonus::Graph = "a b 1\nb c 1\nc d 1".parse().unwrap();
+/// let star: cs365_bonus::Graph = "a b 1\na c 1\na d 1".parse().unwrap();
+/// let to_path = *wl_similarity(&square, &path, 2, false).last().unwrap();
+/// let to_star = *wl_similarity(&square, &star, 2, false).last().unwrap();
+/// assert!(to_star < to_path && to_path < 1.0);
+/// assert_eq!(wl_similarity(&square, &square, 2, false), [1.0, 1.0, 1.0]);
+/// ```
+pub fn wl_similarity(a: &Graph, b: &Graph, iterations: usize, weighted: bool) -> Vec<f64> {
+    let rounds = |graph| hashed_colors(&Structure::new(graph, weighted), Some(iterations));
+    let (a, b) = (rounds(a), rounds(b));
+    let histogram = |colors: &[u64]| {
+        let mut counts: HashMap<u64, f64> = HashMap::new();
+        for &color in colors {
+            *counts.entry(color).or_default() += 1.0;
+        }
+        counts
+    };
+    let dot = |x: &HashMap<u64, f64>, y: &HashMap<u64, f64>| -> f64 {
+        x.iter()
+            .filter_map(|(color, count)| Some(count * y.get(color)?))
+            .sum()
+    };
+    let mut kernels = [0.0; 3];
+    let mut similarity = Vec::with_capacity(iterations + 1);
+    for (colors_a, colors_b) in a.iter().zip(&b) {
+        let (x, y) = (histogram(colors_a), histogram(colors_b));
+        kernels[0] += dot(&x, &y);
+        kernels[1] += dot(&x, &x);
+        kernels[2] += dot(&y, &y);
+        similarity.push(match kernels {
+            [_, 0.0, 0.0] => 1.0,
+            [_, 0.0, _] | [_, _, 0.0] => 0.0,
+            [both, a, b] => both / (a * b).sqrt(),
+        });
+    }
+    similarity
+}
+
+/// The colors of each round of refinement, starting from each node's degree and the edges
+/// from it to itself, up to `rounds` rounds or until the colors stop splitting.
+fn hashed_colors(structure: &Structure, rounds: Option<usize>) -> Vec<Vec<u64>> {
+    let label = |u: usize, v: usize| {
+        fnv(structure
+            .arcs(u, v)
+            .iter()
+            .flat_map(|&(directed, cost)| [directed as u64, cost as u64]))
+    };
+    let mut colors: Vec<u64> = (0..structure.len())
+        .map(|u| {
+            fnv([structure.neighbors[u].len() as u64, label(u, u)]
+                .iter()
+                .copied())
+        })
+        .collect();
+    let distinct = |colors: &[u64]| {
+        let mut sorted = colors.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        sorted.len()
+    };
+    let mut count = distinct(&colors);
+    let mut history = vec![colors.clone()];
+    for _ in 0..rounds.unwrap_or(structure.len()) {
+        colors = (0..structure.len())
+            .map(|u| {
+                let mut around: Vec<[u64; 3]> = structure.neighbors[u]
+                    .iter()
+                    .map(|&v| [label(u, v), label(v, u), colors[v]])
+                    .collect();
+                around.sort_unstable();
+                fnv(std::iter::once(colors[u]).chain(around.into_iter().flatten()))
+            })
+            .collect();
+        history.push(colors.clone());
+        let split = distinct(&colors);
+        if rounds.is_none() && split == count {
+            break;
+        }
+        count = split;
+    }
+    history
+}
+
+/// FNV-1a over the little-endian bytes of `words`.
+fn fnv(words: impl IntoIterator<Item = u64>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for word in words {
+        for b in word.to_le_bytes() {
+            hash ^= u64::from(b);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// The order to map `a`'s nodes in: each component breadth first from a node of its
+/// rarest color, so that every node after the first of its component has a neighbor
+/// mapped before it.
+fn search_order(a: &Structure, colors: &[usize]) -> Vec<usize> {
+    let mut frequency = HashMap::new();
+    for &color in colors {
+        *frequency.entry(color).or_insert(0) += 1;
+    }
+    let mut starts: Vec<usize> = (0..a.len()).collect();
+    starts.sort_by_key(|&u| {
+        (
+            frequency[&colors[u]],
+            std::cmp::Reverse(a.neighbors[u].len()),
+        )
+    });
+    let mut seen = vec![false; a.len()];
+    let mut order = Vec::with_capacity(a.len());
+    for start in starts {
+        if std::mem::replace(&mut seen[start], true) {
+            continue;
+        }
+        let mut queue = VecDeque::from(vec![start]);
+        while let Some(u) = queue.pop_front() {
+            order.push(u);
+            for &v in &a.neighbors[u] {
+                if !std::mem::replace(&mut seen[v], true) {
+                    queue.push_back(v);
+                }
+            }
+        }
+    }
+    order
+}
+
+/// A partial mapping from `a` to `b` being extended by backtracking.
+struct Search<'a> {
+    a: &'a Structure,
+    b: &'a Structure,
+    colors_a: &'a [usize],
+    colors_b: &'a [usize],
+    order: Vec<usize>,
+    mapping: Vec<Option<usize>>,
+    used: Vec<bool>,
+}
+
+impl Search<'_> {
+    /// Map the nodes from `order[depth]` on, returning whether it worked out.
+    fn extend(&mut self, depth: usize) -> bool {
+        let u = match self.order.get(depth) {
+            Some(&u) => u,
+            None => return true,
+        };
+        // Candidates are the neighbors of a mapped neighbor's image, if there is one.
+        let anchor = self.a.neighbors[u].iter().find_map(|&w| self.mapping[w]);
+        let candidates: Vec<usize> = match anchor {
+            Some(image) => self.b.neighbors[image].clone(),
+            None => (0..self.b.len()).collect(),
+        };
+        for c in candidates {
+            if self.used[c] || self.colors_b[c] != self.colors_a[u] || !self.feasible(u, c) {
+                continue;
+            }
+            self.mapping[u] = Some(c);
+            self.used[c] = true;
+            if self.extend(depth + 1) {
+                return true;
+            }
+            self.mapping[u] = None;
+            self.used[c] = false;
+        }
+        false
+    }
+
+    /// Whether `u` can map onto `c`: the edges between `u` and each mapped node must match
+    /// those between `c` and its image, and `c` may have no other mapped neighbors.
+    fn feasible(&self, u: usize, c: usize) -> bool {
+        let mut joined = 0;
+        for &w in &self.a.neighbors[u] {
+            if let Some(image) = self.mapping[w] {
+                if self.a.arcs(u, w) != self.b.arcs(c, image)
+                    || self.a.arcs(w, u) != self.b.arcs(image, c)
+                {
+                    return false;
+                }
+                joined += 1;
+            }
+        }
+        let images = self.b.neighbors[c]
+            .iter()
+            .filter(|&&x| self.used[x])
+            .count();
+        joined == images
+    }
+}
+
+/// A canonical form of an undirected tree: two trees get the same form exactly when they
+/// are isomorphic, counting costs if `weighted`. It is `None` if the graph isn't a tree,
+/// with a directed edge, a self-loop, a cycle, or more than one component.
+///
+/// This is the AHU encoding. The tree is rooted at its center, found by peeling off leaves
+/// until one or two nodes remain, and each node is written as its children's forms sorted
+/// and wrapped in parentheses, each prefixed with its edge's cost. With two centers, the
+/// smaller of the two forms counts. It takes O(n log n) time outside of comparing forms.
+///
+/// ```
+/// use cs365_bonus::isomorphism::tree_canonical_form;
+///
+/// let a: cs365_bonus::Graph = "r x 1\nr y 1\ny z 1".parse().unwrap();
+/// let b: cs365_bonus::Graph = "p q 1\nq s 1\nq t 1".parse().unwrap();
+/// let c: cs365_bonus::Graph = "p q 1\nq s 1\ns t 1".parse().unwrap();
+/// // a and c are both paths of four nodes, while b is a star.
+/// assert_eq!(tree_canonical_form(&a, false), tree_canonical_form(&c, false));
+/// assert_ne!(tree_canonical_form(&a, false), tree_canonical_form(&b, false));
+/// ```
+pub fn tree_canonical_form(graph: &Graph, weighted: bool) -> Option<String> {
+    let n = graph.node_count();
+    if n == 0 {
+        return Some(String::new());
+    }
+    if graph.edge_count() != n - 1 {
+        return None;
+    }
+    let mut adjacency: Vec<Vec<(usize, Weight)>> = vec![Vec::new(); n];
+    for (id, src, dest, cost) in graph.edges() {
+        if src == dest || graph.is_edge_directed(id) == Some(true) {
+            return None;
+        }
+        let cost = if weighted { cost } else { 0 };
+        adjacency[src.index()].push((dest.index(), cost));
+        adjacency[dest.index()].push((src.index(), cost));
+    }
+
+    // Peel leaves layer by layer; with n - 1 edges, reaching every node means no cycles.
+    let mut degree: Vec<usize> = adjacency.iter().map(Vec::len).collect();
+    let mut layer: Vec<usize> = (0..n).filter(|&u| degree[u] <= 1).collect();
+    let mut remaining = n;
+    while remaining > 2 {
+        remaining -= layer.len();
+        let mut next = Vec::new();
+        for &u in &layer {
+            for &(v, _) in &adjacency[u] {
+                degree[v] -= 1;
+                if degree[v] == 1 {
+                    next.push(v);
+                }
+            }
+        }
+        if next.is_empty() {
+            return None;
+        }
+        layer = next;
+    }
+    if layer.is_empty() || layer.len() > 2 {
+        return None;
+    }
+
+    let encode = |root: usize| -> Option<String> {
+        let mut parent = vec![usize::MAX; n];
+        let mut order = vec![root];
+        parent[root] = root;
+        let mut i = 0;
+        while let Some(&u) = order.get(i) {
+            i += 1;
+            for &(v, _) in &adjacency[u] {
+                if parent[v] == usize::MAX {
+                    parent[v] = u;
+                    order.push(v);
+                }
+            }
+        }
+        if order.len() != n {
+            return None;
+        }
+        let mut forms = vec![String::new(); n];
+        for &u in order.iter().rev() {
+            let mut children: Vec<String> = adjacency[u]
+                .iter()
+                .filter(|&&(v, _)| parent[v] == u && v != root)
+                .map(|&(v, cost)| {
+                    let form = std::mem::take(&mut forms[v]);
+                    if weighted {
+                        format!("{}:{}", cost, form)
+                    } else {
+                        form
+                    }
+                })
+                .collect();
+            children.sort_unstable();
+            forms[u] = format!("({})", children.concat());
+        }
+        Some(std::mem::take(&mut forms[root]))
+    };
+    layer.iter().map(|&root| encode(root)).min()?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// `graph` with its nodes renamed by a random permutation and its edges shuffled.
+    fn shuffled(graph: &Graph, rng: &mut Rng) -> Graph {
+        let mut names: Vec<usize> = (0..graph.node_count()).collect();
+        rng.shuffle(&mut names);
+        let mut edges: Vec<_> = graph.edges().collect();
+        rng.shuffle(&mut edges);
+        let mut copy = Graph::new();
+        for &name in &names {
+            copy.get_or_insert_node(&name.to_string());
+        }
+        for (id, src, dest, cost) in edges {
+            let (u, v) = (
+                copy.get_node(&names[src.index()].to_string()).unwrap(),
+                copy.get_node(&names[dest.index()].to_string()).unwrap(),
+            );
+            if graph.is_edge_directed(id) == Some(true) {
+                copy.add_edge(u, v, cost);
+            } else {
+                copy.add_bidirectional_edge(u, v, cost);
+            }
+        }
+        copy
+    }
+
+    #[test]
+    fn shuffled_graphs_are_isomorphic_and_hash_alike() {
+        let mut rng = Rng::new(9);
+        for round in 0..200 {
+            let n = 1 + rng.below(9);
+            let mut graph = Graph::new();
+            let nodes: Vec<NodeId> = (0..n)
+                .map(|i| graph.get_or_insert_node(&i.to_string()))
+                .collect();
+            for v in 1..n {
+                // Every other round makes a tree, the rest a random graph.
+                if round % 2 == 0 {
+                    let u = rng.below(v);
+                    graph.add_bidirectional_edge(nodes[u], nodes[v], rng.below(3) as Weight);
+                }
+            }
+            if round % 2 == 1 {
+                for _ in 0..rng.below(2 * n) {
+                    let (u, v) = (nodes[rng.below(n)], nodes[rng.below(n)]);
+                    if rng.chance(0.5) {
+                        graph.add_edge(u, v, 1);
+                    } else {
+                        graph.add_bidirectional_edge(u, v, 1);
+                    }
+                }
+            }
+            let copy = shuffled(&graph, &mut rng);
+            assert_eq!(graph_hash(&graph, true), graph_hash(&copy, true));
+            let mapping = find_isomorphism(&graph, &copy, true).unwrap();
+            for (_, src, dest, _) in graph.edges() {
+                let (u, v) = (mapping[src.index()], mapping[dest.index()]);
+                assert!(copy.find_edge(u, v).is_some() || copy.find_edge(v, u).is_some());
+            }
+            if round % 2 == 0 {
+                let form = tree_canonical_form(&graph, true);
+                assert!(form.is_some());
+                assert_eq!(form, tree_canonical_form(&copy, true));
+            }
+        }
+    }
+
+    #[test]
+    fn tell_apart_graphs_color_refinement_cannot() {
+        // Two triangles and a hexagon are both 2-regular on six nodes.
+        let triangles: Graph = "a b 1\nb c 1\nc a 1\nd e 1\ne f 1\nf d 1".parse().unwrap();
+        let hexagon: Graph = "a b 1\nb c 1\nc d 1\nd e 1\ne f 1\nf a 1".parse().unwrap();
+        assert!(find_isomorphism(&triangles, &hexagon, false).is_none());
+        assert!(find_isomorphism(&hexagon, &hexagon, false).is_some());
+        assert_eq!(tree_canonical_form(&hexagon, false), None);
+    }
+}
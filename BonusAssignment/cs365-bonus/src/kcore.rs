@@ -0,0 +1,120 @@
+use crate::graph::{simple_adjacency, Graph};
+
+/// The core number of every node, computed by repeatedly peeling a minimum degree node.
+///
+/// This is the bucket-based algorithm of Batagelj and Zaversnik, linear in the graph size.
+pub fn core_numbers(graph: &Graph) -> Vec<usize> {
+    let adjacency = simple_adjacency(graph);
+    let n = adjacency.len();
+    let mut degree: Vec<usize> = adjacency.iter().map(|list| list.len()).collect();
+    let max_degree = degree.iter().copied().max().unwrap_or(0);
+
+    // Nodes sorted by degree, with `bin[d]` the index of the first node of degree `d`.
+    let mut bin = vec![0; max_degree + 1];
+    for &d in degree.iter() {
+        bin[d] += 1;
+    }
+    let mut start = 0;
+    for count in bin.iter_mut() {
+        let c = *count;
+        *count = start;
+        start += c;
+    }
+    let mut order = vec![0; n];
+    let mut position = vec![0; n];
+    for node in 0..n {
+        position[node] = bin[degree[node]];
+        order[position[node]] = node;
+        bin[degree[node]] += 1;
+    }
+    for d in (1..=max_degree).rev() {
+        bin[d] = bin[d - 1];
+    }
+    bin[0] = 0;
+
+    for i in 0..n {
+        let node = order[i];
+        for &neighbor in adjacency[node].iter() {
+            let neighbor = neighbor.index();
+            if degree[neighbor] > degree[node] {
+                // Move the neighbor to the front of its bucket, then shrink its degree.
+                let d = degree[neighbor];
+                let first = order[bin[d]];
+                if first != neighbor {
+                    order.swap(position[neighbor], bin[d]);
+                    position[first] = position[neighbor];
+                    position[neighbor] = bin[d];
+                }
+                bin[d] += 1;
+                degree[neighbor] -= 1;
+            }
+        }
+    }
+
+    degree
+}
+
+/// The induced subgraph of nodes whose core number is at least `k`.
+pub fn k_core(graph: &Graph, k: usize) -> Graph {
+    let cores = core_numbers(graph);
+    graph.induced_subgraph(|node| cores[node.index()] >= k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::NodeId;
+    use crate::rng::Rng;
+
+    #[test]
+    fn cores_match_repeated_peeling() {
+        let mut rng = Rng::new(3);
+        for _ in 0..50 {
+            let n = 1 + rng.below(15);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (rng.below(n), rng.below(n));
+                graph.add_edge(NodeId::new(a), NodeId::new(b), 1);
+            }
+            let adjacency = simple_adjacency(&graph);
+            let cores = core_numbers(&graph);
+            for k in 0..n {
+                // Remove nodes with fewer than k neighbors left until none remain.
+                let mut alive = vec![true; n];
+                loop {
+                    let weak = (0..n).find(|&v| {
+                        alive[v] && adjacency[v].iter().filter(|u| alive[u.index()]).count() < k
+                    });
+                    match weak {
+                        Some(v) => alive[v] = false,
+                        None => break,
+                    }
+                }
+                for v in 0..n {
+                    assert_eq!(cores[v] >= k, alive[v]);
+                }
+                let core = k_core(&graph, k);
+                assert_eq!(core.node_count(), alive.iter().filter(|&&a| a).count());
+            }
+        }
+    }
+
+    #[test]
+    fn k_core_keeps_the_edges_between_its_nodes() {
+        // A triangle with a tail and a self-loop on the tail's end.
+        let graph: Graph = "a b 1\nb c 2 ->\nc a 3\nc d 4\nd d 5".parse().unwrap();
+        let core = k_core(&graph, 2);
+        let names: Vec<&str> = core
+            .node_ids()
+            .map(|node| core.get_node_name(node).unwrap())
+            .collect();
+        assert_eq!(names, ["a", "b", "c"]);
+        assert_eq!(core.edge_count(), 3);
+        let (b, c) = (core.get_node("b").unwrap(), core.get_node("c").unwrap());
+        assert!(core.find_edge(c, b).is_none());
+        assert_eq!(core.edge_weight(core.find_edge(b, c).unwrap()), Some(2));
+    }
+}
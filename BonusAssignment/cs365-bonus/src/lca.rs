@@ -0,0 +1,234 @@
+use crate::graph::{Graph, NodeId, Weight};
+
+/// A forest rooted at the lowest numbered node of each tree.
+#[derive(Debug, Clone)]
+pub(crate) struct RootedForest {
+    pub parent: Vec<Option<NodeId>>,
+    /// The weight of the edge to the parent, or zero for roots.
+    pub parent_cost: Vec<Weight>,
+    pub depth: Vec<usize>,
+    /// Weighted distance from the root.
+    pub distance: Vec<Weight>,
+    pub root: Vec<NodeId>,
+    /// Every node, with parents before their children.
+    pub order: Vec<NodeId>,
+}
+
+impl RootedForest {
+    /// Root every tree of `graph`, ignoring edge direction. Returns `None` if it has a
+    /// cycle, including self-loops and parallel edges.
+    pub fn new(graph: &Graph) -> Option<Self> {
+        let n = graph.node_count();
+        let mut parent = vec![None; n];
+        let mut parent_cost = vec![0; n];
+        let mut depth = vec![0; n];
+        let mut distance = vec![0; n];
+        let mut root: Vec<Option<NodeId>> = vec![None; n];
+        let mut order = Vec::with_capacity(n);
+
+        let mut stack = Vec::new();
+        for start in graph.node_ids() {
+            if root[start.index()].is_some() {
+                continue;
+            }
+            root[start.index()] = Some(start);
+            stack.push((start, None));
+            while let Some((node, via)) = stack.pop() {
+                order.push(node);
+                for edge in graph.edges_of(node) {
+                    if Some(edge.id) == via {
+                        continue;
+                    }
+                    let next = edge.node.index();
+                    if root[next].is_some() {
+                        return None;
+                    }
+                    root[next] = Some(start);
+                    parent[next] = Some(node);
+                    parent_cost[next] = edge.cost;
+                    depth[next] = depth[node.index()] + 1;
+                    distance[next] = distance[node.index()] + edge.cost;
+                    stack.push((edge.node, Some(edge.id)));
+                }
+            }
+        }
+
+        Some(Self {
+            parent,
+            parent_cost,
+            depth,
+            distance,
+            root: root.into_iter().map(|r| r.unwrap()).collect(),
+            order,
+        })
+    }
+}
+
+/// Lowest common ancestor queries on a forest, using binary lifting.
+///
+/// Each tree is rooted at its lowest numbered node. Preprocessing takes O(n log n) time and
+/// each query O(log n).
+#[derive(Debug, Clone)]
+pub struct Lca {
+    /// `up[k][v]` is the `2^k`-th ancestor of `v`, or the root if the tree is shallower.
+    up: Vec<Vec<NodeId>>,
+    depth: Vec<usize>,
+    /// Weighted distance from the root of the node's tree.
+    distance: Vec<Weight>,
+    root: Vec<NodeId>,
+}
+
+impl Lca {
+    /// Preprocess `graph`, ignoring edge direction. Returns `None` if it has a cycle,
+    /// including self-loops and parallel edges.
+    pub fn new(graph: &Graph) -> Option<Self> {
+        let forest = RootedForest::new(graph)?;
+        let first: Vec<NodeId> = graph
+            .node_ids()
+            .map(|node| forest.parent[node.index()].unwrap_or(node))
+            .collect();
+        let levels = (usize::BITS - graph.node_count().leading_zeros()).max(1) as usize;
+        let mut up = vec![first];
+        for k in 1..levels {
+            let prev = &up[k - 1];
+            let next = prev.iter().map(|&node| prev[node.index()]).collect();
+            up.push(next);
+        }
+
+        Some(Self {
+            up,
+            depth: forest.depth,
+            distance: forest.distance,
+            root: forest.root,
+        })
+    }
+
+    /// The number of edges between `node` and the root of its tree.
+    pub fn depth(&self, node: NodeId) -> usize {
+        self.depth[node.index()]
+    }
+
+    /// The ancestor `steps` levels above `node`, if the tree is that deep.
+    pub fn ancestor(&self, mut node: NodeId, steps: usize) -> Option<NodeId> {
+        if steps > self.depth(node) {
+            return None;
+        }
+        for (k, up) in self.up.iter().enumerate() {
+            if steps >> k & 1 == 1 {
+                node = up[node.index()];
+            }
+        }
+        Some(node)
+    }
+
+    /// The deepest node that is an ancestor of both `a` and `b`, or `None` if they are in
+    /// different trees.
+    pub fn lca(&self, a: NodeId, b: NodeId) -> Option<NodeId> {
+        if self.root[a.index()] != self.root[b.index()] {
+            return None;
+        }
+        let (mut a, mut b) = if self.depth(a) >= self.depth(b) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        a = self.ancestor(a, self.depth(a) - self.depth(b))?;
+        if a == b {
+            return Some(a);
+        }
+        for up in self.up.iter().rev() {
+            if up[a.index()] != up[b.index()] {
+                a = up[a.index()];
+                b = up[b.index()];
+            }
+        }
+        Some(self.up[0][a.index()])
+    }
+
+    /// The weighted length of the tree path between `a` and `b`.
+    pub fn distance(&self, a: NodeId, b: NodeId) -> Option<Weight> {
+        let lca = self.lca(a, b)?;
+        Some(self.distance[a.index()] + self.distance[b.index()] - 2 * self.distance[lca.index()])
+    }
+
+    /// The number of edges on the tree path between `a` and `b`.
+    pub fn hops(&self, a: NodeId, b: NodeId) -> Option<usize> {
+        let lca = self.lca(a, b)?;
+        Some(self.depth(a) + self.depth(b) - 2 * self.depth(lca))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::shortest_path_tree;
+    use crate::rng::Rng;
+
+    /// A random forest on up to 20 nodes, each joined to an earlier node or starting a tree.
+    fn random_forest(rng: &mut Rng) -> Graph {
+        let n = 1 + rng.below(20);
+        let mut graph = Graph::new();
+        let nodes: Vec<NodeId> = (0..n)
+            .map(|i| graph.get_or_insert_node(&i.to_string()))
+            .collect();
+        for i in 1..n {
+            if rng.chance(0.85) {
+                let cost = 1 + rng.below(9) as Weight;
+                graph.add_bidirectional_edge(nodes[rng.below(i)], nodes[i], cost);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn queries_match_tree_paths() {
+        let mut rng = Rng::new(15);
+        for _ in 0..50 {
+            let graph = random_forest(&mut rng);
+            let lca = Lca::new(&graph).unwrap();
+            let unit = graph.map_weights(|_, _| 1);
+            let hops: Vec<Vec<Option<Weight>>> = graph
+                .node_ids()
+                .map(|node| shortest_path_tree(&unit, node).distances().to_vec())
+                .collect();
+            let hop = |a: NodeId, b: NodeId| hops[a.index()][b.index()];
+            for a in graph.node_ids() {
+                // The root is the lowest numbered node of the tree.
+                let root = graph.node_ids().find(|&r| hop(r, a).is_some()).unwrap();
+                assert_eq!(Some(lca.depth(a) as Weight), hop(root, a));
+                let weighted = shortest_path_tree(&graph, a);
+                for b in graph.node_ids() {
+                    assert_eq!(lca.distance(a, b), weighted.distance(b));
+                    assert_eq!(lca.hops(a, b).map(|h| h as Weight), hop(a, b));
+                    // The common ancestor is the node on the path closest to the root.
+                    let expected = graph
+                        .node_ids()
+                        .filter(|&w| match (hop(a, w), hop(w, b)) {
+                            (Some(x), Some(y)) => Some(x + y) == hop(a, b),
+                            _ => false,
+                        })
+                        .min_by_key(|&w| hop(root, w));
+                    assert_eq!(lca.lca(a, b), expected);
+                }
+                let depth = lca.depth(a);
+                for steps in 0..=depth + 1 {
+                    let expected = graph.node_ids().find(|&w| {
+                        hop(w, a) == Some(steps as Weight)
+                            && hop(root, w).map(|d| d + steps as Weight) == Some(depth as Weight)
+                    });
+                    assert_eq!(lca.ancestor(a, steps), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let triangle: Graph = "a b 1\nb c 1\nc a 1".parse().unwrap();
+        assert!(Lca::new(&triangle).is_none());
+        let parallel: Graph = "a b 1\na b 2".parse().unwrap();
+        assert!(Lca::new(&parallel).is_none());
+        let self_loop: Graph = "a b 1\nb b 1".parse().unwrap();
+        assert!(Lca::new(&self_loop).is_none());
+    }
+}
@@ -0,0 +1,89 @@
+pub mod all_pairs;
+pub mod alternatives;
+pub mod arbitrage;
+mod arena;
+pub mod astar;
+pub mod attributes;
+pub mod bellman_ford;
+pub mod checkpoint;
+pub mod coarsen;
+pub mod compressed;
+pub mod connectivity;
+pub mod constrained;
+pub mod courses;
+pub mod csr;
+pub mod cycle;
+pub mod dag;
+pub mod decimal;
+pub mod dependencies;
+pub mod disjoint_set;
+pub mod dot;
+pub mod dstar_lite;
+pub mod eccentricity;
+pub mod edge_coloring;
+pub mod ego;
+pub mod expr;
+pub mod external;
+pub mod facility;
+pub mod generate;
+pub mod geo;
+pub mod graph;
+pub mod grid_search;
+pub mod gridworld;
+#[cfg(feature = "gtfs")]
+pub mod gtfs;
+pub mod hld;
+pub mod interrupt;
+pub mod isomorphism;
+pub mod kcore;
+pub mod lca;
+pub mod link_prediction;
+pub mod lts;
+pub mod markov;
+pub mod matching;
+pub mod mst;
+pub mod multimodal;
+pub mod negative_edges;
+pub mod node2vec;
+pub mod oracle;
+pub mod parse;
+pub mod path;
+pub mod reachability;
+pub mod realtime;
+pub mod reorder;
+pub mod replacement;
+pub mod resource;
+pub mod rng;
+pub mod robustness;
+pub mod router;
+pub mod schedule;
+pub mod server;
+pub mod similarity;
+pub mod snapshot;
+pub mod spanner;
+pub mod spanning_tree;
+pub mod spans;
+pub mod spectral;
+pub mod stable_matching;
+pub mod stats;
+pub mod streaming;
+pub mod temporal;
+pub mod time_dependent;
+pub mod transform;
+pub mod triangles;
+pub mod turns;
+pub mod two_sat;
+pub mod view;
+pub mod vrp;
+
+pub use crate::alternatives::{find_k_shortest_paths, find_k_shortest_paths_within};
+pub use crate::graph::{Edge, EdgeId, Graph, GraphRef, NodeId, Weight};
+pub use crate::parse::{
+    parse_parallel, parse_scaled, write_edge_list, write_edge_list_scaled, ParseErrorKind,
+    ParseGraphError,
+};
+pub use crate::path::{
+    find_shortest_path, find_shortest_paths_from_sources, shortest_path_tree,
+    shortest_path_tree_using, Algorithm, BudgetExceeded, Path, PathConstraints, SearchContext,
+    SearchVisitor, ShortestPathTree,
+};
@@ -0,0 +1,152 @@
+use crate::graph::{simple_adjacency, Graph, NodeId};
+use std::str::FromStr;
+
+/// How [`predict_links`] scores a pair of nodes by the neighbors they share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkIndex {
+    /// The number of shared neighbors.
+    CommonNeighbors,
+    /// Shared neighbors weighted by `1 / ln(degree)`, so that a shared neighbor with few
+    /// others counts for more than a hub.
+    AdamicAdar,
+    /// Shared neighbors over the neighbors of either, from 0 to 1.
+    Jaccard,
+}
+
+impl FromStr for LinkIndex {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "common-neighbors" => Ok(LinkIndex::CommonNeighbors),
+            "adamic-adar" => Ok(LinkIndex::AdamicAdar),
+            "jaccard" => Ok(LinkIndex::Jaccard),
+            _ => Err(format!(
+                "unknown index '{}', expected 'common-neighbors', 'adamic-adar' or 'jaccard'",
+                s
+            )),
+        }
+    }
+}
+
+/// The `top` pairs of nodes not already joined by an edge that `index` scores highest, as
+/// `(a, b, score)` with `a < b`, best first and ties broken by node id. Direction and
+/// self-loops are ignored. Only pairs with a shared neighbor are scored, since every index
+/// gives the rest 0.
+///
+/// ```
+/// use cs365_bonus::link_prediction::{predict_links, LinkIndex};
+///
+/// // b and d both know a and c, but aren't linked themselves.
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1\nc d 1\nd a 1\na c 1\nd e 1".parse().unwrap();
+/// let links = predict_links(&graph, LinkIndex::CommonNeighbors, 1);
+/// let (b, d) = (graph.get_node("b").unwrap(), graph.get_node("d").unwrap());
+/// assert_eq!(links, [(b, d, 2.0)]);
+/// ```
+pub fn predict_links(graph: &Graph, index: LinkIndex, top: usize) -> Vec<(NodeId, NodeId, f64)> {
+    let adjacency = simple_adjacency(graph);
+    let mut shared = vec![0.0; graph.node_count()];
+    let mut touched = Vec::new();
+    let mut links = Vec::new();
+    for a in graph.node_ids() {
+        for &middle in &adjacency[a.index()] {
+            let credit = match index {
+                LinkIndex::AdamicAdar => 1.0 / (adjacency[middle.index()].len() as f64).ln(),
+                _ => 1.0,
+            };
+            for &b in &adjacency[middle.index()] {
+                if b <= a {
+                    continue;
+                }
+                if shared[b.index()] == 0.0 {
+                    touched.push(b);
+                }
+                shared[b.index()] += credit;
+            }
+        }
+        for b in touched.drain(..) {
+            let score = std::mem::take(&mut shared[b.index()]);
+            if adjacency[a.index()].binary_search(&b).is_ok() {
+                continue;
+            }
+            let score = match index {
+                LinkIndex::Jaccard => {
+                    let union =
+                        adjacency[a.index()].len() + adjacency[b.index()].len() - score as usize;
+                    score / union as f64
+                }
+                _ => score,
+            };
+            links.push((a, b, score));
+        }
+    }
+    links.sort_by(|x, y| y.2.total_cmp(&x.2).then((x.0, x.1).cmp(&(y.0, y.1))));
+    links.truncate(top);
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn scores_match_neighbor_sets() {
+        let mut rng = Rng::new(8);
+        for _ in 0..50 {
+            let n = 2 + rng.below(12);
+            let mut graph = Graph::new();
+            let nodes: Vec<NodeId> = (0..n)
+                .map(|i| graph.get_or_insert_node(&i.to_string()))
+                .collect();
+            for i in 0..n {
+                for j in 0..n {
+                    if rng.chance(0.2) {
+                        graph.add_edge(nodes[i], nodes[j], 1);
+                    }
+                }
+            }
+            let adjacency = simple_adjacency(&graph);
+            for index in [
+                LinkIndex::CommonNeighbors,
+                LinkIndex::AdamicAdar,
+                LinkIndex::Jaccard,
+            ] {
+                let links = predict_links(&graph, index, usize::MAX);
+                let mut expected = Vec::new();
+                for a in 0..n {
+                    for b in a + 1..n {
+                        let (na, nb) = (&adjacency[a], &adjacency[b]);
+                        if na.contains(&nodes[b]) {
+                            continue;
+                        }
+                        let common: Vec<&NodeId> = na.iter().filter(|w| nb.contains(w)).collect();
+                        if common.is_empty() {
+                            continue;
+                        }
+                        let score = match index {
+                            LinkIndex::CommonNeighbors => common.len() as f64,
+                            LinkIndex::AdamicAdar => common
+                                .iter()
+                                .map(|w| 1.0 / (adjacency[w.index()].len() as f64).ln())
+                                .sum(),
+                            LinkIndex::Jaccard => {
+                                common.len() as f64 / (na.len() + nb.len() - common.len()) as f64
+                            }
+                        };
+                        expected.push((nodes[a], nodes[b], score));
+                    }
+                }
+                assert_eq!(links.len(), expected.len());
+                for (a, b, score) in expected {
+                    let found = links
+                        .iter()
+                        .find(|link| (link.0, link.1) == (a, b))
+                        .unwrap();
+                    assert!((found.2 - score).abs() < 1e-9);
+                }
+                assert!(links.windows(2).all(|pair| pair[0].2 >= pair[1].2));
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::graph::{EdgeId, Graph, NodeId};
+use std::collections::VecDeque;
+
+/// States joined by transitions labelled with the action taken, as in a model checker's
+/// labelled transition system.
+///
+/// ```
+/// use cs365_bonus::lts::TransitionSystem;
+///
+/// let lts = TransitionSystem::parse("idle start running\nrunning pause paused\n\
+///     paused resume running\nrunning stop idle\npaused stop idle").unwrap();
+/// let (idle, paused) = (lts.get("idle").unwrap(), lts.get("paused").unwrap());
+/// let trace = lts.shortest_trace(paused, idle).unwrap();
+/// assert_eq!(lts.actions(&trace), ["stop"]);
+/// assert_eq!(lts.shortest_trace(idle, paused).unwrap().len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransitionSystem {
+    graph: Graph,
+    /// The action of each transition, by edge id.
+    actions: Vec<String>,
+}
+
+impl TransitionSystem {
+    /// Read one transition per line as `from action to`. Blank lines and lines starting
+    /// with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut graph = Graph::new();
+        let mut actions = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (from, action, to) = match words.as_slice() {
+                [from, action, to] => (from, action, to),
+                _ => return Err(format!("line {}: expected 'from action to'", i + 1)),
+            };
+            let (from, to) = (graph.get_or_insert_node(from), graph.get_or_insert_node(to));
+            graph.add_edge(from, to, 1);
+            actions.push(action.to_string());
+        }
+        Ok(Self { graph, actions })
+    }
+
+    /// The states and transitions as a directed graph with every edge costing 1.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn get(&self, state: &str) -> Option<NodeId> {
+        self.graph.get_node(state)
+    }
+
+    pub fn action(&self, transition: EdgeId) -> &str {
+        &self.actions[transition.index()]
+    }
+
+    /// The actions of `transitions`, in order.
+    pub fn actions(&self, transitions: &[EdgeId]) -> Vec<&str> {
+        transitions
+            .iter()
+            .map(|&transition| self.action(transition))
+            .collect()
+    }
+
+    /// The transitions of a shortest trace from `from` to `to`, by breadth-first search,
+    /// or `None` if `to` is unreachable. The trace from a state to itself is empty.
+    pub fn shortest_trace(&self, from: NodeId, to: NodeId) -> Option<Vec<EdgeId>> {
+        let mut via: Vec<Option<(NodeId, EdgeId)>> = vec![None; self.graph.node_count()];
+        let mut seen = vec![false; self.graph.node_count()];
+        let mut queue = VecDeque::from([from]);
+        seen[from.index()] = true;
+        while let Some(state) = queue.pop_front() {
+            if state == to {
+                let mut trace = Vec::new();
+                let mut at = to;
+                while let Some((previous, transition)) = via[at.index()] {
+                    trace.push(transition);
+                    at = previous;
+                }
+                trace.reverse();
+                return Some(trace);
+            }
+            for edge in self.graph.edges_of(state) {
+                if !seen[edge.node.index()] {
+                    seen[edge.node.index()] = true;
+                    via[edge.node.index()] = Some((state, edge.id));
+                    queue.push_back(edge.node);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn traces_are_shortest_and_follow_transitions() {
+        let mut rng = Rng::new(49);
+        for _ in 0..100 {
+            let n = 1 + rng.below(10);
+            let mut text = String::new();
+            for _ in 0..rng.below(3 * n) {
+                let (from, to) = (rng.below(n), rng.below(n));
+                text += &format!("s{} act{} s{}\n", from, rng.below(4), to);
+            }
+            let lts = TransitionSystem::parse(&text).unwrap();
+            let graph = lts.graph();
+            let ends: Vec<(NodeId, NodeId)> = graph.edges().map(|(_, u, v, _)| (u, v)).collect();
+            for from in graph.node_ids() {
+                let tree = crate::shortest_path_tree(graph, from);
+                for to in graph.node_ids() {
+                    let trace = lts.shortest_trace(from, to);
+                    let expected = tree.distance(to).map(|hops| hops as usize);
+                    assert_eq!(trace.as_ref().map(Vec::len), expected);
+                    let mut at = from;
+                    for &transition in trace.iter().flatten() {
+                        let (src, dest) = ends[transition.index()];
+                        assert_eq!(src, at);
+                        at = dest;
+                    }
+                    assert!(trace.is_none() || at == to);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn names_actions_and_rejects_malformed_lines() {
+        let lts = TransitionSystem::parse("# a comment\n\na go b\nb go a\na stay a").unwrap();
+        let (a, b) = (lts.get("a").unwrap(), lts.get("b").unwrap());
+        assert_eq!(lts.shortest_trace(a, a), Some(Vec::new()));
+        assert_eq!(lts.actions(&lts.shortest_trace(b, a).unwrap()), ["go"]);
+        assert_eq!(lts.get("c"), None);
+        let error = TransitionSystem::parse("a go b\na b").unwrap_err();
+        assert_eq!(error, "line 2: expected 'from action to'");
+    }
+}
@@ -0,0 +1,319 @@
+use crate::graph::{Graph, NodeId};
+use std::collections::VecDeque;
+
+/// A Markov chain whose states are a graph's nodes, leaving each node along its outgoing
+/// edges with probability proportional to their weights.
+///
+/// Undirected edges can be taken either way. A node with no outgoing weight is absorbing:
+/// once there, the chain stays.
+///
+/// ```
+/// use cs365_bonus::markov::MarkovChain;
+///
+/// // A walk on a path of three nodes spends twice as long in the middle.
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1".parse().unwrap();
+/// let chain = MarkovChain::new(&graph).unwrap();
+/// let stationary = chain.stationary(10_000, 1e-12);
+/// assert!((stationary.values[1] - 0.5).abs() < 1e-9);
+/// // Starting at a, reaching c takes four steps on average.
+/// let c = graph.get_node("c").unwrap();
+/// let hitting = chain.hitting_times(&[c], 10_000, 1e-12);
+/// assert!((hitting.values[0] - 4.0).abs() < 1e-6);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarkovChain {
+    /// The states each state moves to and with what probability.
+    transitions: Vec<Vec<(usize, f64)>>,
+}
+
+/// The result of an iterative solve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    /// One value per state.
+    pub values: Vec<f64>,
+    pub iterations: usize,
+    /// Whether the last iteration changed no value by more than the tolerance.
+    pub converged: bool,
+}
+
+impl MarkovChain {
+    /// The chain over `graph`'s edges, or an error naming a node with a negative edge,
+    /// since weights are probabilities up to scale.
+    pub fn new(graph: &Graph) -> Result<Self, String> {
+        let mut transitions = Vec::with_capacity(graph.node_count());
+        for node in graph.node_ids() {
+            let edges = graph.edges_of(node);
+            if edges.iter().any(|edge| edge.cost < 0) {
+                let name = graph.get_node_name(node).unwrap_or("");
+                return Err(format!("'{}' has an edge of negative weight", name));
+            }
+            let total: f64 = edges.iter().map(|edge| edge.cost as f64).sum();
+            transitions.push(if total > 0.0 {
+                edges
+                    .iter()
+                    .filter(|edge| edge.cost > 0)
+                    .map(|edge| (edge.node.index(), edge.cost as f64 / total))
+                    .collect()
+            } else {
+                vec![(node.index(), 1.0)]
+            });
+        }
+        Ok(Self { transitions })
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+
+    /// The long-run fraction of time spent in each state, by power iteration from the
+    /// uniform distribution.
+    ///
+    /// The iteration runs on the lazy chain that stays put half the time, which has the
+    /// same stationary distribution but also converges on periodic chains. A chain that
+    /// isn't irreducible has several stationary distributions, and this finds the one its
+    /// starting point leads to.
+    pub fn stationary(&self, max_iterations: usize, tolerance: f64) -> Solution {
+        let n = self.len();
+        let mut x = vec![1.0 / n as f64; n];
+        let mut y = vec![0.0; n];
+        let mut iterations = 0;
+        let mut converged = false;
+        while iterations < max_iterations {
+            iterations += 1;
+            y.iter_mut().zip(&x).for_each(|(y, x)| *y = x / 2.0);
+            for (from, transitions) in self.transitions.iter().enumerate() {
+                for &(to, p) in transitions {
+                    y[to] += x[from] * p / 2.0;
+                }
+            }
+            let change = x.iter().zip(&y).map(|(a, b)| (a - b).abs()).sum::<f64>();
+            std::mem::swap(&mut x, &mut y);
+            if change < tolerance {
+                converged = true;
+                break;
+            }
+        }
+        Solution {
+            values: x,
+            iterations,
+            converged,
+        }
+    }
+
+    /// The expected number of steps from each state to the first visit to any of
+    /// `targets`, by Gauss-Seidel iteration.
+    ///
+    /// States from which the chain might never arrive, because it can get somewhere the
+    /// targets can't be reached from, have an infinite expected time.
+    pub fn hitting_times(
+        &self,
+        targets: &[NodeId],
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> Solution {
+        let n = self.len();
+        let mut is_target = vec![false; n];
+        for target in targets {
+            is_target[target.index()] = true;
+        }
+        // States that can't reach a target, then those that can reach one of those first.
+        let reaches_target = self.reaching(|state| is_target[state], |_| true);
+        let lost = self.reaching(|state| !reaches_target[state], |state| !is_target[state]);
+        let values: Vec<f64> = (0..n)
+            .map(|state| if lost[state] { f64::INFINITY } else { 0.0 })
+            .collect();
+        let free: Vec<usize> = (0..n)
+            .filter(|&state| !is_target[state] && !lost[state])
+            .collect();
+        self.gauss_seidel(values, &free, 1.0, max_iterations, tolerance)
+    }
+
+    /// The probability that the chain, started from each state, is eventually absorbed
+    /// at `absorbing`, or if that state has a way out, ever visits it.
+    pub fn absorption_probabilities(
+        &self,
+        absorbing: NodeId,
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> Solution {
+        let n = self.len();
+        let mut values = vec![0.0; n];
+        values[absorbing.index()] = 1.0;
+        let reaches = self.reaching(|state| state == absorbing.index(), |_| true);
+        let free: Vec<usize> = (0..n)
+            .filter(|&state| reaches[state] && !self.is_absorbing(state))
+            .collect();
+        self.gauss_seidel(values, &free, 0.0, max_iterations, tolerance)
+    }
+
+    /// Whether the chain never leaves `state`.
+    pub fn is_absorbing(&self, state: usize) -> bool {
+        self.transitions[state] == [(state, 1.0)]
+    }
+
+    /// Solve `x[s] = step + sum of p * x[t]` over the transitions of each state `s` in
+    /// `free`, keeping the other entries of `values` fixed.
+    fn gauss_seidel(
+        &self,
+        mut values: Vec<f64>,
+        free: &[usize],
+        step: f64,
+        max_iterations: usize,
+        tolerance: f64,
+    ) -> Solution {
+        let mut iterations = 0;
+        let mut converged = free.is_empty();
+        while !converged && iterations < max_iterations {
+            iterations += 1;
+            let mut change: f64 = 0.0;
+            for &state in free {
+                let value = step
+                    + self.transitions[state]
+                        .iter()
+                        .map(|&(to, p)| p * values[to])
+                        .sum::<f64>();
+                change = change.max((value - values[state]).abs());
+                values[state] = value;
+            }
+            converged = change < tolerance;
+        }
+        Solution {
+            values,
+            iterations,
+            converged,
+        }
+    }
+
+    /// The states with a path to some state in `start`, moving only through states
+    /// `through` accepts.
+    fn reaching(
+        &self,
+        start: impl Fn(usize) -> bool,
+        through: impl Fn(usize) -> bool,
+    ) -> Vec<bool> {
+        let n = self.len();
+        let mut predecessors = vec![Vec::new(); n];
+        for (from, transitions) in self.transitions.iter().enumerate() {
+            for &(to, _) in transitions {
+                predecessors[to].push(from);
+            }
+        }
+        let mut seen: Vec<bool> = (0..n).map(&start).collect();
+        let mut queue: VecDeque<usize> = (0..n).filter(|&state| seen[state]).collect();
+        while let Some(state) = queue.pop_front() {
+            for &previous in &predecessors[state] {
+                if !seen[previous] && through(previous) {
+                    seen[previous] = true;
+                    queue.push_back(previous);
+                }
+            }
+        }
+        seen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::Weight;
+
+    /// A chain whose last `absorbing` states have no way out and every other state has an
+    /// edge towards a later one, so the chain is absorbed with probability one.
+    fn absorbing_chain(rng: &mut Rng, n: usize, absorbing: usize) -> Graph {
+        let mut graph = Graph::new();
+        let nodes: Vec<NodeId> = (0..n)
+            .map(|i| graph.get_or_insert_node(&format!("s{}", i)))
+            .collect();
+        for i in 0..n - absorbing {
+            let later = i + 1 + rng.below(n - i - 1);
+            graph.add_edge(nodes[i], nodes[later], 1 + rng.below(5) as Weight);
+            for _ in 0..rng.below(3) {
+                let to = rng.below(n);
+                graph.add_edge(nodes[i], nodes[to], 1 + rng.below(5) as Weight);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn absorbing_chains_solve_their_equations() {
+        let mut rng = Rng::new(50);
+        for _ in 0..100 {
+            let absorbing = 1 + rng.below(3);
+            let n = absorbing + 1 + rng.below(8);
+            let graph = absorbing_chain(&mut rng, n, absorbing);
+            let chain = MarkovChain::new(&graph).unwrap();
+            let ends: Vec<NodeId> = graph.node_ids().skip(n - absorbing).collect();
+            for state in 0..n {
+                assert_eq!(chain.is_absorbing(state), state >= n - absorbing);
+            }
+
+            // Every run ends at exactly one of the absorbing states.
+            let probabilities: Vec<Solution> = ends
+                .iter()
+                .map(|&end| chain.absorption_probabilities(end, 100_000, 1e-12))
+                .collect();
+            for state in 0..n {
+                let total: f64 = probabilities.iter().map(|p| p.values[state]).sum();
+                assert!((total - 1.0).abs() < 1e-6, "{} at {}", total, state);
+            }
+
+            // Hitting times are finite and one step more than the average over successors.
+            let hitting = chain.hitting_times(&ends, 100_000, 1e-12);
+            assert!(hitting.converged);
+            for state in 0..n - absorbing {
+                let next: f64 = chain.transitions[state]
+                    .iter()
+                    .map(|&(to, p)| p * hitting.values[to])
+                    .sum();
+                assert!((hitting.values[state] - 1.0 - next).abs() < 1e-6);
+            }
+
+            // In the long run, all the mass sits on the absorbing states.
+            let stationary = chain.stationary(100_000, 1e-12);
+            assert!(stationary.converged);
+            let absorbed: f64 = stationary.values[n - absorbing..].iter().sum();
+            assert!((absorbed - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn gamblers_ruin() {
+        // Fair steps between a and e, which both end the game.
+        let graph: Graph = "b a 1 ->\nb c 1 ->\nc b 1 ->\nc d 1 ->\nd c 1 ->\nd e 1 ->"
+            .parse()
+            .unwrap();
+        let chain = MarkovChain::new(&graph).unwrap();
+        let node = |name| graph.get_node(name).unwrap();
+        let index = |name| node(name).index();
+
+        let win = chain.absorption_probabilities(node("e"), 10_000, 1e-12);
+        for (name, expected) in
+            [("a", 0.0), ("b", 0.25), ("c", 0.5), ("d", 0.75), ("e", 1.0)].iter()
+        {
+            assert!((win.values[index(name)] - expected).abs() < 1e-9);
+        }
+
+        // The game lasts k * (4 - k) steps from k dollars.
+        let duration = chain.hitting_times(&[node("a"), node("e")], 10_000, 1e-12);
+        for (name, expected) in [("b", 3.0), ("c", 4.0), ("d", 3.0)].iter() {
+            assert!((duration.values[index(name)] - expected).abs() < 1e-6);
+        }
+
+        // Going broke first means never reaching e.
+        let to_e = chain.hitting_times(&[node("e")], 10_000, 1e-12);
+        assert!(to_e.values[index("b")].is_infinite());
+        assert_eq!(to_e.values[index("e")], 0.0);
+
+        let negative: Graph = "a b -1 ->".parse().unwrap();
+        assert_eq!(
+            MarkovChain::new(&negative).unwrap_err(),
+            "'a' has an edge of negative weight"
+        );
+    }
+}
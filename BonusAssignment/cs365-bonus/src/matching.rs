@@ -0,0 +1,689 @@
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use std::collections::VecDeque;
+
+/// A set of edges no two of which share a node.
+#[derive(Debug, Clone)]
+pub struct Matching {
+    /// The matched edges, in order of edge id.
+    pub edges: Vec<EdgeId>,
+    /// The total cost of the matched edges.
+    pub weight: Weight,
+}
+
+/// The two sides of a bipartite graph, ignoring edge direction, as whether each node is on
+/// the second side, or a node on an odd cycle if the graph isn't bipartite.
+pub fn bipartition(graph: &Graph) -> Result<Vec<bool>, NodeId> {
+    let mut neighbors = vec![Vec::new(); graph.node_count()];
+    for (_, src, dest, _) in graph.edges() {
+        neighbors[src.index()].push(dest);
+        neighbors[dest.index()].push(src);
+    }
+    let mut side: Vec<Option<bool>> = vec![None; graph.node_count()];
+    for start in graph.node_ids() {
+        if side[start.index()].is_some() {
+            continue;
+        }
+        side[start.index()] = Some(false);
+        let mut queue = VecDeque::from(vec![start]);
+        while let Some(node) = queue.pop_front() {
+            let here = side[node.index()];
+            for &next in &neighbors[node.index()] {
+                match side[next.index()] {
+                    None => {
+                        side[next.index()] = here.map(|side| !side);
+                        queue.push_back(next);
+                    }
+                    there if there == here => return Err(next),
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(side.into_iter().map(|side| side == Some(true)).collect())
+}
+
+/// A matching of the greatest total cost, ignoring edge direction, in any graph, bipartite
+/// or not. With `max_cardinality`, it is the costliest among the matchings with the most
+/// edges instead. Edges costing less than nothing are only matched to add an edge then,
+/// and self-loops never are.
+///
+/// This is Edmonds' blossom algorithm with dual variables, after Galil's description, taking
+/// O(n³) time for `n` nodes. Each stage grows alternating trees from the unmatched nodes
+/// over edges whose dual slack is zero, shrinking odd cycles into blossoms as they close,
+/// until it finds a path to augment along, adjusting the duals whenever it gets stuck.
+///
+/// ```
+/// use cs365_bonus::matching::max_weight_matching;
+///
+/// // A triangle and a pendant edge: no bipartite matcher can take this shape.
+/// let graph: cs365_bonus::Graph = "a b 6\nb c 5\nc a 4\nc d 3".parse().unwrap();
+/// let matching = max_weight_matching(&graph, false);
+/// assert_eq!(matching.weight, 9);
+/// assert_eq!(matching.edges.len(), 2);
+/// ```
+pub fn max_weight_matching(graph: &Graph, max_cardinality: bool) -> Matching {
+    let edges: Vec<(EdgeId, usize, usize, Weight)> = graph
+        .edges()
+        .filter(|&(_, src, dest, _)| src != dest)
+        .map(|(id, src, dest, cost)| (id, src.index(), dest.index(), cost))
+        .collect();
+    let mut blossom = Blossom::new(
+        graph.node_count(),
+        edges.iter().map(|&(_, i, j, w)| (i, j, w)).collect(),
+    );
+    blossom.solve(max_cardinality);
+
+    let mut matched = Vec::new();
+    let mut weight = 0;
+    for (k, &(id, i, _, cost)) in edges.iter().enumerate() {
+        // Each node's mate is an endpoint, 2k or 2k + 1 for the ends of edge k.
+        if blossom.mate[i] != NONE && blossom.mate[i] / 2 == k {
+            matched.push(id);
+            weight += cost;
+        }
+    }
+    Matching {
+        edges: matched,
+        weight,
+    }
+}
+
+/// No node, endpoint, edge or blossom.
+const NONE: usize = usize::MAX;
+
+/// The state of the blossom algorithm over nodes `0..n` and blossoms `n..2n`.
+///
+/// Edge `k` has endpoints `2k` and `2k + 1`, at its first and second node, so an endpoint
+/// `p` names both a node and the edge to reach it by, and `p ^ 1` is the other end. Duals
+/// are kept doubled, so that they stay whole numbers.
+struct Blossom {
+    n: usize,
+    edges: Vec<(usize, usize, Weight)>,
+    /// The node at each endpoint.
+    endpoint: Vec<usize>,
+    /// For each node, the endpoints across its edges.
+    neighbend: Vec<Vec<usize>>,
+    /// For each node, the endpoint of its matched edge at its mate.
+    mate: Vec<usize>,
+    /// For each top-level blossom and node: 0 unlabeled, 1 an S node, 2 a T node.
+    label: Vec<u8>,
+    /// The endpoint through which each labeled blossom or node got its label.
+    labelend: Vec<usize>,
+    /// The top-level blossom holding each node.
+    inblossom: Vec<usize>,
+    blossomparent: Vec<usize>,
+    /// The sub-blossoms of each blossom, around its cycle starting at its base.
+    blossomchilds: Vec<Vec<usize>>,
+    blossombase: Vec<usize>,
+    /// The endpoints joining each sub-blossom to the next around the cycle.
+    blossomendps: Vec<Vec<usize>>,
+    /// The least-slack edge from each node or S blossom to a different S blossom.
+    bestedge: Vec<usize>,
+    /// For each S blossom, the least-slack edges to each other S blossom.
+    blossombestedges: Vec<Option<Vec<usize>>>,
+    unusedblossoms: Vec<usize>,
+    dualvar: Vec<Weight>,
+    /// Whether each edge has zero slack, so that it may be used.
+    allowedge: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+impl Blossom {
+    fn new(n: usize, edges: Vec<(usize, usize, Weight)>) -> Self {
+        let max_weight = edges.iter().map(|&(.., w)| w).max().unwrap_or(0).max(0);
+        let mut neighbend = vec![Vec::new(); n];
+        let mut endpoint = Vec::with_capacity(2 * edges.len());
+        for (k, &(i, j, _)) in edges.iter().enumerate() {
+            neighbend[i].push(2 * k + 1);
+            neighbend[j].push(2 * k);
+            endpoint.push(i);
+            endpoint.push(j);
+        }
+        let mut dualvar = vec![max_weight; n];
+        dualvar.resize(2 * n, 0);
+        let mut blossombase: Vec<usize> = (0..n).collect();
+        blossombase.resize(2 * n, NONE);
+        Self {
+            n,
+            endpoint,
+            neighbend,
+            mate: vec![NONE; n],
+            label: vec![0; 2 * n],
+            labelend: vec![NONE; 2 * n],
+            inblossom: (0..n).collect(),
+            blossomparent: vec![NONE; 2 * n],
+            blossomchilds: vec![Vec::new(); 2 * n],
+            blossombase,
+            blossomendps: vec![Vec::new(); 2 * n],
+            bestedge: vec![NONE; 2 * n],
+            blossombestedges: vec![None; 2 * n],
+            unusedblossoms: (n..2 * n).collect(),
+            dualvar,
+            allowedge: vec![false; edges.len()],
+            queue: Vec::new(),
+            edges,
+        }
+    }
+
+    fn slack(&self, k: usize) -> Weight {
+        let (i, j, w) = self.edges[k];
+        self.dualvar[i] + self.dualvar[j] - 2 * w
+    }
+
+    /// The nodes inside blossom `b`.
+    fn leaves(&self, b: usize) -> Vec<usize> {
+        let mut leaves = Vec::new();
+        let mut stack = vec![b];
+        while let Some(b) = stack.pop() {
+            if b < self.n {
+                leaves.push(b);
+            } else {
+                stack.extend(self.blossomchilds[b].iter().rev());
+            }
+        }
+        leaves
+    }
+
+    /// Label node `w`'s blossom `t`, reached through endpoint `p`, and label the mate of a
+    /// T blossom's base S in turn.
+    fn assign_label(&mut self, w: usize, t: u8, p: usize) {
+        let b = self.inblossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.labelend[w] = p;
+        self.labelend[b] = p;
+        self.bestedge[w] = NONE;
+        self.bestedge[b] = NONE;
+        if t == 1 {
+            let leaves = self.leaves(b);
+            self.queue.extend(leaves);
+        } else {
+            let base = self.blossombase[b];
+            let mate = self.mate[base];
+            self.assign_label(self.endpoint[mate], 1, mate ^ 1);
+        }
+    }
+
+    /// Trace back from S nodes `v` and `w` towards their roots, returning the base of the
+    /// new blossom if they meet, or `NONE` if they reach different roots, which makes an
+    /// augmenting path.
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize {
+        let mut path = Vec::new();
+        let mut base = NONE;
+        while v != NONE || w != NONE {
+            let mut b = self.inblossom[v];
+            if self.label[b] & 4 != 0 {
+                base = self.blossombase[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            if self.labelend[b] == NONE {
+                v = NONE;
+            } else {
+                v = self.endpoint[self.labelend[b]];
+                b = self.inblossom[v];
+                v = self.endpoint[self.labelend[b]];
+            }
+            if w != NONE {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    /// Shrink the cycle closed by edge `k` into a new S blossom with base `base`.
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.inblossom[base];
+        let mut bv = self.inblossom[v];
+        let mut bw = self.inblossom[w];
+        let b = self
+            .unusedblossoms
+            .pop()
+            .expect("there are at most n blossoms");
+        self.blossombase[b] = base;
+        self.blossomparent[b] = NONE;
+        self.blossomparent[bb] = b;
+        let mut path = Vec::new();
+        let mut endps = Vec::new();
+        while bv != bb {
+            self.blossomparent[bv] = b;
+            path.push(bv);
+            endps.push(self.labelend[bv]);
+            v = self.endpoint[self.labelend[bv]];
+            bv = self.inblossom[v];
+        }
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+        while bw != bb {
+            self.blossomparent[bw] = b;
+            path.push(bw);
+            endps.push(self.labelend[bw] ^ 1);
+            w = self.endpoint[self.labelend[bw]];
+            bw = self.inblossom[w];
+        }
+        self.label[b] = 1;
+        self.labelend[b] = self.labelend[bb];
+        self.dualvar[b] = 0;
+        self.blossomchilds[b] = path.clone();
+        self.blossomendps[b] = endps;
+        for v in self.leaves(b) {
+            if self.label[self.inblossom[v]] == 2 {
+                // T nodes become S nodes, so their edges need scanning.
+                self.queue.push(v);
+            }
+            self.inblossom[v] = b;
+        }
+
+        let mut bestedgeto = vec![NONE; 2 * self.n];
+        for &bv in &path {
+            let lists: Vec<Vec<usize>> = match self.blossombestedges[bv].take() {
+                Some(list) => vec![list],
+                None => self
+                    .leaves(bv)
+                    .into_iter()
+                    .map(|v| self.neighbend[v].iter().map(|p| p / 2).collect())
+                    .collect(),
+            };
+            for k in lists.into_iter().flatten() {
+                let (i, j, _) = self.edges[k];
+                let j = if self.inblossom[j] == b { i } else { j };
+                let bj = self.inblossom[j];
+                if bj != b
+                    && self.label[bj] == 1
+                    && (bestedgeto[bj] == NONE || self.slack(k) < self.slack(bestedgeto[bj]))
+                {
+                    bestedgeto[bj] = k;
+                }
+            }
+            self.bestedge[bv] = NONE;
+        }
+        let best: Vec<usize> = bestedgeto.into_iter().filter(|&k| k != NONE).collect();
+        self.bestedge[b] = NONE;
+        for &k in &best {
+            if self.bestedge[b] == NONE || self.slack(k) < self.slack(self.bestedge[b]) {
+                self.bestedge[b] = k;
+            }
+        }
+        self.blossombestedges[b] = Some(best);
+    }
+
+    /// Undo blossom `b`, relabeling its sub-blossoms if it is a T blossom in the middle of
+    /// a stage, or recursively expanding those with zero dual at the end of one.
+    fn expand_blossom(&mut self, b: usize, endstage: bool) {
+        for s in self.blossomchilds[b].clone() {
+            self.blossomparent[s] = NONE;
+            if s < self.n {
+                self.inblossom[s] = s;
+            } else if endstage && self.dualvar[s] == 0 {
+                self.expand_blossom(s, endstage);
+            } else {
+                for v in self.leaves(s) {
+                    self.inblossom[v] = s;
+                }
+            }
+        }
+        if !endstage && self.label[b] == 2 {
+            let childs = self.blossomchilds[b].clone();
+            let endps = self.blossomendps[b].clone();
+            let len = childs.len() as isize;
+            let at = |j: isize| j.rem_euclid(len) as usize;
+            let entrychild = self.inblossom[self.endpoint[self.labelend[b] ^ 1]];
+            let mut j = childs
+                .iter()
+                .position(|&child| child == entrychild)
+                .expect("the entry child is in the blossom") as isize;
+            // Walk the even-length way around the cycle from the entry to the base.
+            let (jstep, endptrick) = if j & 1 == 1 {
+                j -= len;
+                (1, 0usize)
+            } else {
+                (-1, 1)
+            };
+            let mut p = self.labelend[b];
+            while j != 0 {
+                let q = endps[at(j - endptrick as isize)];
+                self.label[self.endpoint[p ^ 1]] = 0;
+                self.label[self.endpoint[q ^ endptrick ^ 1]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p);
+                self.allowedge[q / 2] = true;
+                j += jstep;
+                p = endps[at(j - endptrick as isize)] ^ endptrick;
+                self.allowedge[p / 2] = true;
+                j += jstep;
+            }
+            let bv = childs[at(j)];
+            self.label[self.endpoint[p ^ 1]] = 2;
+            self.label[bv] = 2;
+            self.labelend[self.endpoint[p ^ 1]] = p;
+            self.labelend[bv] = p;
+            self.bestedge[bv] = NONE;
+            j += jstep;
+            while childs[at(j)] != entrychild {
+                let bv = childs[at(j)];
+                if self.label[bv] == 1 {
+                    j += jstep;
+                    continue;
+                }
+                // A sub-blossom the tree reached from outside keeps its T label.
+                if let Some(v) = self.leaves(bv).into_iter().find(|&v| self.label[v] != 0) {
+                    self.label[v] = 0;
+                    let mate = self.mate[self.blossombase[bv]];
+                    self.label[self.endpoint[mate]] = 0;
+                    self.assign_label(v, 2, self.labelend[v]);
+                }
+                j += jstep;
+            }
+        }
+        self.label[b] = 0;
+        self.labelend[b] = NONE;
+        self.blossomchilds[b] = Vec::new();
+        self.blossomendps[b] = Vec::new();
+        self.blossombase[b] = NONE;
+        self.blossombestedges[b] = None;
+        self.bestedge[b] = NONE;
+        self.unusedblossoms.push(b);
+    }
+
+    /// Swap matched and unmatched edges along the even path inside blossom `b` from node
+    /// `v` to its base, making `v` the new base.
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossomparent[t] != b {
+            t = self.blossomparent[t];
+        }
+        if t >= self.n {
+            self.augment_blossom(t, v);
+        }
+        let len = self.blossomchilds[b].len() as isize;
+        let at = |j: isize| j.rem_euclid(len) as usize;
+        let i = self.blossomchilds[b]
+            .iter()
+            .position(|&child| child == t)
+            .expect("the child is in the blossom");
+        let mut j = i as isize;
+        let (jstep, endptrick) = if i & 1 == 1 {
+            j -= len;
+            (1, 0usize)
+        } else {
+            (-1, 1)
+        };
+        while j != 0 {
+            j += jstep;
+            let t = self.blossomchilds[b][at(j)];
+            let p = self.blossomendps[b][at(j - endptrick as isize)] ^ endptrick;
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += jstep;
+            let t = self.blossomchilds[b][at(j)];
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+        self.blossomchilds[b].rotate_left(i);
+        self.blossomendps[b].rotate_left(i);
+        self.blossombase[b] = self.blossombase[self.blossomchilds[b][0]];
+    }
+
+    /// Augment the matching along the path through edge `k` between two S nodes' roots.
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.inblossom[s];
+                if bs >= self.n {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p;
+                if self.labelend[bs] == NONE {
+                    break;
+                }
+                let t = self.endpoint[self.labelend[bs]];
+                let bt = self.inblossom[t];
+                s = self.endpoint[self.labelend[bt]];
+                let j = self.endpoint[self.labelend[bt] ^ 1];
+                if bt >= self.n {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.labelend[bt];
+                p = self.labelend[bt] ^ 1;
+            }
+        }
+    }
+
+    fn solve(&mut self, max_cardinality: bool) {
+        let n = self.n;
+        for _ in 0..n {
+            self.label.iter_mut().for_each(|label| *label = 0);
+            self.bestedge.iter_mut().for_each(|edge| *edge = NONE);
+            self.blossombestedges[n..]
+                .iter_mut()
+                .for_each(|list| *list = None);
+            self.allowedge
+                .iter_mut()
+                .for_each(|allowed| *allowed = false);
+            self.queue.clear();
+            for v in 0..n {
+                if self.mate[v] == NONE && self.label[self.inblossom[v]] == 0 {
+                    self.assign_label(v, 1, NONE);
+                }
+            }
+
+            let mut augmented = false;
+            loop {
+                while !augmented {
+                    let v = match self.queue.pop() {
+                        Some(v) => v,
+                        None => break,
+                    };
+                    for pi in 0..self.neighbend[v].len() {
+                        let p = self.neighbend[v][pi];
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.inblossom[v] == self.inblossom[w] {
+                            continue;
+                        }
+                        let mut kslack = 0;
+                        if !self.allowedge[k] {
+                            kslack = self.slack(k);
+                            if kslack <= 0 {
+                                self.allowedge[k] = true;
+                            }
+                        }
+                        let bw = self.inblossom[w];
+                        if self.allowedge[k] {
+                            if self.label[bw] == 0 {
+                                self.assign_label(w, 2, p ^ 1);
+                            } else if self.label[bw] == 1 {
+                                let base = self.scan_blossom(v, w);
+                                if base != NONE {
+                                    self.add_blossom(base, k);
+                                } else {
+                                    self.augment_matching(k);
+                                    augmented = true;
+                                    break;
+                                }
+                            } else if self.label[w] == 0 {
+                                self.label[w] = 2;
+                                self.labelend[w] = p ^ 1;
+                            }
+                        } else if self.label[bw] == 1 {
+                            let b = self.inblossom[v];
+                            if self.bestedge[b] == NONE || kslack < self.slack(self.bestedge[b]) {
+                                self.bestedge[b] = k;
+                            }
+                        } else if self.label[w] == 0
+                            && (self.bestedge[w] == NONE || kslack < self.slack(self.bestedge[w]))
+                        {
+                            self.bestedge[w] = k;
+                        }
+                    }
+                }
+                if augmented {
+                    break;
+                }
+
+                // Nothing left to scan: find the smallest dual change that lets the
+                // trees grow, or that ends the search.
+                let mut delta: Option<(u8, Weight, usize)> = None;
+                if !max_cardinality {
+                    let least = self.dualvar[..n].iter().copied().min().unwrap_or(0);
+                    delta = Some((1, least, NONE));
+                }
+                let mut improve = |kind, d, at| {
+                    if delta.is_none_or(|(_, best, _)| d < best) {
+                        delta = Some((kind, d, at));
+                    }
+                };
+                for v in 0..n {
+                    if self.label[self.inblossom[v]] == 0 && self.bestedge[v] != NONE {
+                        improve(2, self.slack(self.bestedge[v]), self.bestedge[v]);
+                    }
+                }
+                for b in 0..2 * n {
+                    if self.blossomparent[b] == NONE
+                        && self.label[b] == 1
+                        && self.bestedge[b] != NONE
+                    {
+                        improve(3, self.slack(self.bestedge[b]) / 2, self.bestedge[b]);
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b] != NONE
+                        && self.blossomparent[b] == NONE
+                        && self.label[b] == 2
+                    {
+                        improve(4, self.dualvar[b], b);
+                    }
+                }
+                let (kind, delta, at) = delta.unwrap_or_else(|| {
+                    // Only with max_cardinality: no more edges can be matched.
+                    let least = self.dualvar[..n].iter().copied().min().unwrap_or(0);
+                    (1, least.max(0), NONE)
+                });
+
+                for v in 0..n {
+                    match self.label[self.inblossom[v]] {
+                        1 => self.dualvar[v] -= delta,
+                        2 => self.dualvar[v] += delta,
+                        _ => {}
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b] != NONE && self.blossomparent[b] == NONE {
+                        match self.label[b] {
+                            1 => self.dualvar[b] += delta,
+                            2 => self.dualvar[b] -= delta,
+                            _ => {}
+                        }
+                    }
+                }
+                match kind {
+                    1 => break,
+                    2 => {
+                        self.allowedge[at] = true;
+                        let (mut i, j, _) = self.edges[at];
+                        if self.label[self.inblossom[i]] == 0 {
+                            i = j;
+                        }
+                        self.queue.push(i);
+                    }
+                    3 => {
+                        self.allowedge[at] = true;
+                        self.queue.push(self.edges[at].0);
+                    }
+                    _ => self.expand_blossom(at, false),
+                }
+            }
+            if !augmented {
+                break;
+            }
+            for b in n..2 * n {
+                if self.blossomparent[b] == NONE
+                    && self.blossombase[b] != NONE
+                    && self.label[b] == 1
+                    && self.dualvar[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// The best matching by trying every subset of edges.
+    fn brute_force(edges: &[(usize, usize, Weight)], max_cardinality: bool) -> (usize, Weight) {
+        let mut best = (0, 0);
+        for set in 0u32..1 << edges.len() {
+            let mut used = 0u32;
+            let mut value = (0, 0);
+            let mut ok = true;
+            for (k, &(i, j, w)) in edges.iter().enumerate() {
+                if set >> k & 1 == 1 {
+                    ok &= used >> i & 1 == 0 && used >> j & 1 == 0;
+                    used |= 1 << i | 1 << j;
+                    value = (value.0 + 1, value.1 + w);
+                }
+            }
+            let better = if max_cardinality {
+                value > best
+            } else {
+                value.1 > best.1
+            };
+            if ok && better {
+                best = value;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn matches_brute_force_on_small_graphs() {
+        let mut rng = Rng::new(11);
+        for round in 0..400 {
+            let n = 2 + rng.below(7);
+            let mut graph = Graph::new();
+            let nodes: Vec<NodeId> = (0..n)
+                .map(|i| graph.get_or_insert_node(&i.to_string()))
+                .collect();
+            let mut edges = Vec::new();
+            for _ in 0..rng.below(12) {
+                let (i, j) = (rng.below(n), rng.below(n));
+                if i != j {
+                    let w = rng.below(20) as Weight - 4;
+                    graph.add_bidirectional_edge(nodes[i], nodes[j], w);
+                    edges.push((i, j, w));
+                }
+            }
+            let max_cardinality = round % 2 == 1;
+            let matching = max_weight_matching(&graph, max_cardinality);
+            let (count, weight) = brute_force(&edges, max_cardinality);
+            assert_eq!(matching.weight, weight, "{:?}", edges);
+            if max_cardinality {
+                assert_eq!(matching.edges.len(), count, "{:?}", edges);
+            }
+            let mut used = vec![false; n];
+            for (id, src, dest, _) in graph.edges() {
+                if matching.edges.contains(&id) {
+                    assert!(!used[src.index()] && !used[dest.index()]);
+                    used[src.index()] = true;
+                    used[dest.index()] = true;
+                }
+            }
+        }
+    }
+}
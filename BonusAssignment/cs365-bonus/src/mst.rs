@@ -0,0 +1,102 @@
+use crate::disjoint_set::DisjointSet;
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use crate::hld::HeavyLight;
+
+/// The edges of a minimum spanning forest, found with Kruskal's algorithm.
+///
+/// Edge direction is ignored. Ties are broken by edge id, so the result is deterministic.
+pub fn minimum_spanning_forest(graph: &Graph) -> Vec<EdgeId> {
+    let mut edges: Vec<_> = graph.edges().collect();
+    edges.sort_by_key(|&(id, _, _, cost)| (cost, id));
+
+    let mut sets = DisjointSet::new(graph.node_count());
+    let mut forest = Vec::with_capacity(graph.node_count().saturating_sub(1));
+    for (id, src, dest, _) in edges {
+        if sets.union(src.index(), dest.index()) {
+            forest.push(id);
+        }
+    }
+    forest.sort_unstable();
+    forest
+}
+
+/// A copy of `graph` keeping every node but only the edges of a minimum spanning forest.
+pub fn minimum_spanning_subgraph(graph: &Graph) -> Graph {
+    let mut keep = vec![false; graph.edge_count()];
+    for id in minimum_spanning_forest(graph) {
+        keep[id.index()] = true;
+    }
+    graph.edge_subgraph(|id| keep[id.index()])
+}
+
+/// Minimax queries: the smallest possible heaviest edge over all paths between two nodes.
+///
+/// Every minimum spanning tree contains a minimax path between each pair of nodes, so this
+/// builds one and answers path maximum queries on it.
+#[derive(Debug, Clone)]
+pub struct Bottleneck {
+    tree: HeavyLight,
+}
+
+impl Bottleneck {
+    pub fn new(graph: &Graph) -> Self {
+        let forest = minimum_spanning_subgraph(graph);
+        Self {
+            tree: HeavyLight::new(&forest).expect("a spanning forest has no cycles"),
+        }
+    }
+
+    /// The minimax edge weight between `a` and `b`, or `None` if they aren't connected or
+    /// are the same node.
+    pub fn minimax(&self, a: NodeId, b: NodeId) -> Option<Weight> {
+        self.tree.path_max(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::spanning_tree::random_spanning_tree;
+
+    #[test]
+    fn minimax_matches_growing_thresholds() {
+        let mut rng = Rng::new(17);
+        for _ in 0..40 {
+            let n = 1 + rng.below(12);
+            let mut graph = Graph::new();
+            let nodes: Vec<NodeId> = (0..n)
+                .map(|i| graph.get_or_insert_node(&i.to_string()))
+                .collect();
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (nodes[rng.below(n)], nodes[rng.below(n)]);
+                graph.add_bidirectional_edge(a, b, rng.below(10) as Weight);
+            }
+            let forest = minimum_spanning_forest(&graph);
+            assert_eq!(forest.len(), n - graph.connected_components().len());
+            let weight = |edges: &[EdgeId]| -> Weight {
+                edges.iter().map(|&id| graph.edge_weight(id).unwrap()).sum()
+            };
+            for _ in 0..5 {
+                assert!(weight(&forest) <= weight(&random_spanning_tree(&graph, &mut rng)));
+            }
+
+            let bottleneck = Bottleneck::new(&graph);
+            for &a in &nodes {
+                for &b in &nodes {
+                    // The lowest threshold whose cheaper edges join a and b.
+                    let expected = (0..10).find(|&limit| {
+                        let mut sets = DisjointSet::new(n);
+                        for (_, src, dest, cost) in graph.edges() {
+                            if cost <= limit {
+                                sets.union(src.index(), dest.index());
+                            }
+                        }
+                        sets.connected(a.index(), b.index())
+                    });
+                    assert_eq!(bottleneck.minimax(a, b), expected.filter(|_| a != b));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use crate::path::Path;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Transport mode tags on a graph's edges, such as walk, bus, or drive.
+///
+/// Untagged edges are neutral: they are always allowed and don't count as a change of
+/// mode.
+#[derive(Debug, Clone, Default)]
+pub struct Modes {
+    names: Vec<String>,
+    edge_mode: Vec<Option<usize>>,
+}
+
+impl Modes {
+    pub fn new(graph: &Graph) -> Self {
+        Self {
+            names: Vec::new(),
+            edge_mode: vec![None; graph.edge_count()],
+        }
+    }
+
+    /// The id of the mode called `name`, registering it if it is new.
+    pub fn mode(&mut self, name: &str) -> usize {
+        match self.names.iter().position(|mode| mode == name) {
+            Some(id) => id,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() - 1
+            }
+        }
+    }
+
+    /// Look up a mode without registering it.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|mode| mode == name)
+    }
+
+    pub fn name(&self, mode: usize) -> &str {
+        &self.names[mode]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn tag(&mut self, edge: EdgeId, mode: usize) {
+        self.edge_mode[edge.index()] = Some(mode);
+    }
+
+    pub fn of(&self, edge: EdgeId) -> Option<usize> {
+        self.edge_mode.get(edge.index()).copied().flatten()
+    }
+}
+
+/// A multi-modal route and the mode of each of its legs.
+#[derive(Debug)]
+pub struct ModalPath {
+    pub path: Path,
+    /// The mode used to reach `path.nodes[i + 1]`, or `None` for untagged edges.
+    pub legs: Vec<Option<usize>>,
+    pub transfers: usize,
+}
+
+/// The cheapest route from `start` to `end` using only modes marked in `allowed`, paying
+/// `penalty` each time the route switches from one mode to another.
+///
+/// Dijkstra over (node, current mode) pairs, so a node can be reached once per mode.
+pub fn multimodal_path(
+    graph: &Graph,
+    modes: &Modes,
+    start: NodeId,
+    end: NodeId,
+    allowed: &[bool],
+    penalty: Weight,
+) -> Option<ModalPath> {
+    // State `node * width + m` means at `node` with current mode `m - 1`, or none if 0.
+    let width = modes.len() + 1;
+    let state =
+        |node: NodeId, mode: Option<usize>| node.index() * width + mode.map_or(0, |m| m + 1);
+    let mut distance: Vec<Option<Weight>> = vec![None; graph.node_count() * width];
+    let mut parent: Vec<Option<(usize, Option<usize>)>> = vec![None; distance.len()];
+    let mut heap = BinaryHeap::new();
+    distance[state(start, None)] = Some(0);
+    heap.push(Reverse((0, state(start, None))));
+
+    let mut found = None;
+    while let Some(Reverse((cost, current))) = heap.pop() {
+        if distance[current].is_some_and(|best| cost > best) {
+            continue;
+        }
+        let node = NodeId::new(current / width);
+        let mode = (current % width).checked_sub(1);
+        if node == end {
+            found = Some(current);
+            break;
+        }
+        for edge in graph.edges_of(node) {
+            let edge_mode = modes.of(edge.id);
+            if edge_mode.is_some_and(|m| !allowed.get(m).copied().unwrap_or(false)) {
+                continue;
+            }
+            let (next_mode, extra) = match (mode, edge_mode) {
+                (Some(a), Some(b)) if a != b => (Some(b), penalty),
+                (_, Some(b)) => (Some(b), 0),
+                (current, None) => (current, 0),
+            };
+            let next_cost = cost + edge.cost + extra;
+            let next = state(edge.node, next_mode);
+            if distance[next].is_none_or(|best| next_cost < best) {
+                distance[next] = Some(next_cost);
+                parent[next] = Some((current, edge_mode));
+                heap.push(Reverse((next_cost, next)));
+            }
+        }
+    }
+
+    let last = found?;
+    let mut states = vec![last];
+    let mut legs = Vec::new();
+    while let Some((previous, leg)) = parent[states[states.len() - 1]] {
+        states.push(previous);
+        legs.push(leg);
+    }
+    states.reverse();
+    legs.reverse();
+
+    let mut transfers = 0;
+    let mut current = None;
+    for leg in legs.iter().flatten() {
+        if current.is_some_and(|m| m != *leg) {
+            transfers += 1;
+        }
+        current = Some(*leg);
+    }
+    let distance: Vec<Weight> = states.iter().map(|&s| distance[s]).collect::<Option<_>>()?;
+    Some(ModalPath {
+        path: Path {
+            nodes: states.iter().map(|&s| NodeId::new(s / width)).collect(),
+            cost: distance[distance.len() - 1],
+            distance,
+        },
+        legs,
+        transfers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn routes_match_relaxing_every_mode() {
+        let mut rng = Rng::new(27);
+        for _ in 0..40 {
+            let n = 1 + rng.below(8);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(4 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                graph.add_edge(a, b, rng.below(10) as Weight);
+            }
+            let mut modes = Modes::new(&graph);
+            let ids = [modes.mode("walk"), modes.mode("bus"), modes.mode("train")];
+            for (id, ..) in graph.edges().collect::<Vec<_>>() {
+                if rng.chance(0.8) {
+                    modes.tag(id, ids[rng.below(3)]);
+                }
+            }
+            let allowed: Vec<bool> = (0..3).map(|_| rng.chance(0.7)).collect();
+            let penalty = rng.below(6) as Weight;
+            let (start, end) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+
+            // The cheapest cost of being at each node having last used each mode.
+            let mut best: Vec<[Option<Weight>; 4]> = vec![[None; 4]; n];
+            best[start.index()][0] = Some(0);
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for node in graph.node_ids() {
+                    for last in 0..4 {
+                        let cost = match best[node.index()][last] {
+                            Some(cost) => cost,
+                            None => continue,
+                        };
+                        for edge in graph.edges_of(node) {
+                            let (next, extra) = match modes.of(edge.id) {
+                                Some(m) if !allowed[m] => continue,
+                                Some(m) if last != 0 && last != m + 1 => (m + 1, penalty),
+                                Some(m) => (m + 1, 0),
+                                None => (last, 0),
+                            };
+                            let slot = &mut best[edge.node.index()][next];
+                            if slot.is_none_or(|c| cost + edge.cost + extra < c) {
+                                *slot = Some(cost + edge.cost + extra);
+                                changed = true;
+                            }
+                        }
+                    }
+                }
+            }
+            let expected = best[end.index()].iter().flatten().copied().min();
+
+            let found = multimodal_path(&graph, &modes, start, end, &allowed, penalty);
+            assert_eq!(found.as_ref().map(|f| f.path.cost), expected);
+            let found = match found {
+                Some(found) => found,
+                None => continue,
+            };
+            assert_eq!(found.legs.len() + 1, found.path.nodes.len());
+            assert!(found.legs.iter().flatten().all(|&m| allowed[m]));
+            // The cost is the edges' costs plus a penalty per transfer.
+            let mut total = found.transfers as Weight * penalty;
+            for (i, pair) in found.path.nodes.windows(2).enumerate() {
+                total += graph
+                    .edges_of(pair[0])
+                    .iter()
+                    .filter(|e| e.node == pair[1] && modes.of(e.id) == found.legs[i])
+                    .map(|e| e.cost)
+                    .min()
+                    .expect("legs follow edges of their mode");
+            }
+            assert_eq!(total, found.path.cost);
+        }
+    }
+
+    #[test]
+    fn transfers_cost_the_penalty() {
+        // The bus is faster but needs a walk to the stop and back.
+        let graph: Graph = "a s 1 ->\ns t 2 ->\nt z 1 ->\na z 6 ->".parse().unwrap();
+        let mut modes = Modes::new(&graph);
+        let (walk, bus) = (modes.mode("walk"), modes.mode("bus"));
+        for (id, &mode) in [walk, bus, walk, walk].iter().enumerate() {
+            modes.tag(EdgeId::new(id), mode);
+        }
+        let (a, z) = (graph.get_node("a").unwrap(), graph.get_node("z").unwrap());
+        let found = multimodal_path(&graph, &modes, a, z, &[true, true], 0).unwrap();
+        assert_eq!((found.path.cost, found.transfers), (4, 2));
+        assert_eq!(found.legs, [Some(walk), Some(bus), Some(walk)]);
+        let found = multimodal_path(&graph, &modes, a, z, &[true, true], 2).unwrap();
+        assert_eq!((found.path.cost, found.transfers), (6, 0));
+        assert!(multimodal_path(&graph, &modes, a, z, &[false, true], 0).is_none());
+        assert_eq!(modes.find("bus"), Some(bus));
+        assert_eq!(modes.find("ferry"), None);
+    }
+}
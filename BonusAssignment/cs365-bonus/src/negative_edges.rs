@@ -0,0 +1,171 @@
+use crate::graph::{Graph, NodeId};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A directed edge whose cost may be negative, kept apart from the graph's own edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedEdge {
+    pub src: NodeId,
+    pub dest: NodeId,
+    pub cost: i64,
+}
+
+/// Distances from a search that may use signed edges.
+#[derive(Debug, Clone)]
+pub struct SignedPaths {
+    distance: Vec<Option<i64>>,
+    parent: Vec<Option<NodeId>>,
+}
+
+impl SignedPaths {
+    pub fn distance(&self, node: NodeId) -> Option<i64> {
+        self.distance[node.index()]
+    }
+
+    /// The nodes from the source to `end` and their cumulative costs.
+    pub fn path_to(&self, end: NodeId) -> Option<(Vec<NodeId>, Vec<i64>)> {
+        self.distance(end)?;
+        let mut nodes = vec![end];
+        while let Some(parent) = self.parent[nodes[nodes.len() - 1].index()] {
+            nodes.push(parent);
+        }
+        nodes.reverse();
+        let costs = nodes
+            .iter()
+            .map(|&node| self.distance(node))
+            .collect::<Option<_>>()?;
+        Some((nodes, costs))
+    }
+}
+
+/// Shortest paths from `start` over the non-negative edges of `graph` plus a few extra
+/// `signed` edges. Returns `None` if a negative cycle is reachable.
+///
+/// A shortest simple path uses each signed edge at most once, so after one Dijkstra pass
+/// over the graph, each of `k` further rounds relaxes the signed edges and continues
+/// Dijkstra from whichever nodes improved. That is O(k m log n), much less than
+/// Bellman-Ford's O(nm) when `k` is small. A signed edge that still improves something
+/// after the last round proves a negative cycle.
+pub fn shortest_paths_with_signed_edges(
+    graph: &Graph,
+    signed: &[SignedEdge],
+    start: NodeId,
+) -> Option<SignedPaths> {
+    let mut paths = SignedPaths {
+        distance: vec![None; graph.node_count()],
+        parent: vec![None; graph.node_count()],
+    };
+    paths.distance[start.index()] = Some(0);
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((0, start)));
+
+    for round in 0..=signed.len() + 1 {
+        if round > 0 {
+            for edge in signed {
+                if let Some(cost) = paths.distance[edge.src.index()] {
+                    let next = cost + edge.cost;
+                    if paths.distance[edge.dest.index()].is_none_or(|d| next < d) {
+                        if round == signed.len() + 1 {
+                            return None;
+                        }
+                        paths.distance[edge.dest.index()] = Some(next);
+                        paths.parent[edge.dest.index()] = Some(edge.src);
+                        heap.push(Reverse((next, edge.dest)));
+                    }
+                }
+            }
+        }
+        if heap.is_empty() && round > 0 {
+            break;
+        }
+        run_dijkstra(graph, &mut paths, &mut heap);
+    }
+    Some(paths)
+}
+
+/// Continue Dijkstra over the non-negative graph edges until `heap` is empty.
+fn run_dijkstra(
+    graph: &Graph,
+    paths: &mut SignedPaths,
+    heap: &mut BinaryHeap<Reverse<(i64, NodeId)>>,
+) {
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if paths.distance[node.index()].is_some_and(|d| cost > d) {
+            continue;
+        }
+        for edge in graph.edges_of(node) {
+            let next = cost + edge.cost;
+            if paths.distance[edge.node.index()].is_none_or(|d| next < d) {
+                paths.distance[edge.node.index()] = Some(next);
+                paths.parent[edge.node.index()] = Some(node);
+                heap.push(Reverse((next, edge.node)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bellman_ford::bellman_ford;
+    use crate::rng::Rng;
+
+    #[test]
+    fn distances_match_bellman_ford() {
+        let mut rng = Rng::new(24);
+        for _ in 0..60 {
+            let n = 1 + rng.below(10);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                graph.add_edge(a, b, rng.below(10) as i64);
+            }
+            let signed: Vec<SignedEdge> = (0..rng.below(4))
+                .map(|_| SignedEdge {
+                    src: NodeId::new(rng.below(n)),
+                    dest: NodeId::new(rng.below(n)),
+                    cost: rng.below(12) as i64 - 8,
+                })
+                .collect();
+            let mut combined = graph.clone();
+            for edge in signed.iter() {
+                combined.add_edge(edge.src, edge.dest, edge.cost);
+            }
+
+            let start = NodeId::new(rng.below(n));
+            let expected = bellman_ford(&combined, start);
+            let paths = shortest_paths_with_signed_edges(&graph, &signed, start);
+            let (expected, paths) = match (expected, paths) {
+                (Ok(expected), Some(paths)) => (expected, paths),
+                (Err(_), None) => continue,
+                (expected, _) => panic!(
+                    "only one search found a negative cycle: {:?}",
+                    expected.err()
+                ),
+            };
+            for node in graph.node_ids() {
+                assert_eq!(paths.distance(node), expected.distance(node));
+                if let Some((nodes, costs)) = paths.path_to(node) {
+                    assert_eq!(nodes[0], start);
+                    assert_eq!(costs.last().copied(), paths.distance(node));
+                    for (i, pair) in nodes.windows(2).enumerate() {
+                        let step = costs[i + 1] - costs[i];
+                        let plain = graph
+                            .edges_of(pair[0])
+                            .iter()
+                            .any(|e| e.node == pair[1] && e.cost == step);
+                        let extra = signed.contains(&SignedEdge {
+                            src: pair[0],
+                            dest: pair[1],
+                            cost: step,
+                        });
+                        assert!(plain || extra);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::graph::{Graph, NodeId};
+use crate::rng::Rng;
+
+/// How [`biased_walks`] walks the graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Walks {
+    /// Nodes in each walk, counting the one it starts from.
+    pub length: usize,
+    /// Walks started from each node.
+    pub per_node: usize,
+    /// The return parameter: stepping straight back to the previous node is `1 / p` times
+    /// as likely, so a high `p` keeps walks moving on.
+    pub p: f64,
+    /// The in-out parameter: stepping to a node not next to the previous one is `1 / q`
+    /// times as likely, so a high `q` keeps walks local, like a breadth-first search, and a
+    /// low one sends them outwards, like a depth-first search.
+    pub q: f64,
+    /// Whether edges are followed in proportion to their cost rather than equally.
+    pub weighted: bool,
+}
+
+impl Default for Walks {
+    fn default() -> Self {
+        Self {
+            length: 80,
+            per_node: 10,
+            p: 1.0,
+            q: 1.0,
+            weighted: false,
+        }
+    }
+}
+
+/// Random walks from every node in turn, as in node2vec, each step biased by where the walk
+/// just came from as `walks` describes. Walks stop early at nodes without edges out, and
+/// with `weighted`, edges costing nothing or less are never followed.
+///
+/// ```
+/// use cs365_bonus::node2vec::{biased_walks, Walks};
+/// use cs365_bonus::rng::Rng;
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1\nc a 1\nc d 1".parse().unwrap();
+/// let walks = biased_walks(&graph, &Walks { length: 5, per_node: 2, ..Walks::default() }, &mut Rng::new(1));
+/// assert_eq!(walks.len(), 8);
+/// assert!(walks.iter().all(|walk| walk.len() == 5));
+/// ```
+pub fn biased_walks(graph: &Graph, walks: &Walks, rng: &mut Rng) -> Vec<Vec<NodeId>> {
+    let weight = |cost| if walks.weighted { cost as f64 } else { 1.0 };
+    // Each node's neighbors with the total weight of the edges to them, by node.
+    let neighbors: Vec<Vec<(NodeId, f64)>> = graph
+        .node_ids()
+        .map(|node| {
+            let mut list: Vec<(NodeId, f64)> = graph
+                .edges_of(node)
+                .iter()
+                .map(|edge| (edge.node, weight(edge.cost)))
+                .filter(|&(_, w)| w > 0.0)
+                .collect();
+            list.sort_unstable_by_key(|&(next, _)| next);
+            list.dedup_by(|next, kept| {
+                let same = next.0 == kept.0;
+                if same {
+                    kept.1 += next.1;
+                }
+                same
+            });
+            list
+        })
+        .collect();
+    let adjacent = |a: NodeId, b: NodeId| {
+        neighbors[a.index()]
+            .binary_search_by_key(&b, |&(next, _)| next)
+            .is_ok()
+    };
+
+    let mut corpus = Vec::with_capacity(graph.node_count() * walks.per_node);
+    let mut weights = Vec::new();
+    for _ in 0..walks.per_node {
+        for start in graph.node_ids() {
+            let mut walk = vec![start];
+            while walk.len() < walks.length {
+                let here = walk[walk.len() - 1];
+                let previous = walk.len().checked_sub(2).map(|i| walk[i]);
+                let choices = &neighbors[here.index()];
+                weights.clear();
+                weights.extend(choices.iter().map(|&(next, w)| match previous {
+                    Some(previous) if next == previous => w / walks.p,
+                    Some(previous) if !adjacent(previous, next) => w / walks.q,
+                    _ => w,
+                }));
+                match pick(&weights, rng) {
+                    Some(i) => walk.push(choices[i].0),
+                    None => break,
+                }
+            }
+            corpus.push(walk);
+        }
+    }
+    corpus
+}
+
+/// An index drawn with probability in proportion to `weights`, or `None` if they are all 0.
+fn pick(weights: &[f64], rng: &mut Rng) -> Option<usize> {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut target = rng.next_f64() * total;
+    for (i, &w) in weights.iter().enumerate() {
+        if target < w {
+            return Some(i);
+        }
+        target -= w;
+    }
+    weights.iter().rposition(|&w| w > 0.0)
+}
+
+/// A skip-gram model with negative sampling, as in word2vec, that learns a vector for each
+/// node from a corpus of walks, so that nodes that turn up near each other in walks get
+/// similar vectors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkipGram {
+    pub dimensions: usize,
+    /// How many nodes on either side of each node in a walk count as its context, at most.
+    pub window: usize,
+    /// Nodes drawn at random as counterexamples for each pair in context.
+    pub negative: usize,
+    /// Passes over the corpus.
+    pub epochs: usize,
+    /// The step size at the start, which falls linearly towards zero over training.
+    pub learning_rate: f64,
+}
+
+impl Default for SkipGram {
+    fn default() -> Self {
+        Self {
+            dimensions: 16,
+            window: 5,
+            negative: 5,
+            epochs: 1,
+            learning_rate: 0.025,
+        }
+    }
+}
+
+impl SkipGram {
+    /// Train on `walks` over `node_count` nodes, returning each node's vector. Nodes that
+    /// no walk visits keep small random vectors.
+    ///
+    /// Like word2vec, each node's window is shrunk at random, counterexamples are drawn in
+    /// proportion to each node's count in the corpus to the power 3/4, and each pair in
+    /// context takes one step of stochastic gradient descent on the logistic loss.
+    pub fn train(&self, walks: &[Vec<NodeId>], node_count: usize, rng: &mut Rng) -> Vec<Vec<f64>> {
+        let d = self.dimensions;
+        let mut input: Vec<Vec<f64>> = (0..node_count)
+            .map(|_| (0..d).map(|_| (rng.next_f64() - 0.5) / d as f64).collect())
+            .collect();
+        let mut output = vec![vec![0.0; d]; node_count];
+
+        let mut counts = vec![0.0; node_count];
+        for node in walks.iter().flatten() {
+            counts[node.index()] += 1.0;
+        }
+        let mut cumulative = Vec::with_capacity(node_count);
+        let mut total = 0.0;
+        for count in &counts {
+            total += f64::powf(*count, 0.75);
+            cumulative.push(total);
+        }
+        if total == 0.0 {
+            return input;
+        }
+
+        let steps = (self.epochs * walks.iter().map(Vec::len).sum::<usize>()).max(1) as f64;
+        let mut step = 0.0;
+        let mut gradient = vec![0.0; d];
+        for _ in 0..self.epochs {
+            for walk in walks {
+                for (i, center) in walk.iter().enumerate() {
+                    let rate = self.learning_rate * (1.0 - step / steps).max(1e-4);
+                    step += 1.0;
+                    let reach = 1 + rng.below(self.window.max(1));
+                    let context = i.saturating_sub(reach)..(i + reach + 1).min(walk.len());
+                    for j in context.filter(|&j| j != i) {
+                        let target = walk[j].index();
+                        gradient.iter_mut().for_each(|g| *g = 0.0);
+                        for k in 0..=self.negative {
+                            let (other, label) = if k == 0 {
+                                (target, 1.0)
+                            } else {
+                                let draw = rng.next_f64() * total;
+                                let other = cumulative.partition_point(|&c| c <= draw);
+                                if other == target || other >= node_count {
+                                    continue;
+                                }
+                                (other, 0.0)
+                            };
+                            let vector = &input[center.index()];
+                            let out = &mut output[other];
+                            let score: f64 =
+                                vector.iter().zip(out.iter()).map(|(a, b)| a * b).sum();
+                            let g = (label - sigmoid(score)) * rate;
+                            for ((grad, c), o) in
+                                gradient.iter_mut().zip(vector).zip(out.iter_mut())
+                            {
+                                *grad += g * *o;
+                                *o += g * c;
+                            }
+                        }
+                        for (c, grad) in input[center.index()].iter_mut().zip(&gradient) {
+                            *c += grad;
+                        }
+                    }
+                }
+            }
+        }
+        input
+    }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x.clamp(-30.0, 30.0)).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cosine(a: &[f64], b: &[f64]) -> f64 {
+        let dot: f64 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+        let norm = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        dot / (norm(a) * norm(b))
+    }
+
+    #[test]
+    fn communities_embed_apart() {
+        // Two cliques of six joined by a single edge.
+        let mut text = String::new();
+        for group in ["a", "b"] {
+            for i in 0..6 {
+                for j in i + 1..6 {
+                    text += &format!("{}{} {}{} 1\n", group, i, group, j);
+                }
+            }
+        }
+        text += "a0 b0 1\n";
+        let graph: Graph = text.parse().unwrap();
+        let mut rng = Rng::new(4);
+        let walks = biased_walks(
+            &graph,
+            &Walks {
+                length: 20,
+                ..Walks::default()
+            },
+            &mut rng,
+        );
+        let model = SkipGram {
+            dimensions: 8,
+            epochs: 5,
+            ..SkipGram::default()
+        };
+        let vectors = model.train(&walks, graph.node_count(), &mut rng);
+        let vector = |name: &str| &vectors[graph.get_node(name).unwrap().index()];
+        let (mut inside, mut across) = (0.0, 0.0);
+        for i in 1..6 {
+            for j in 1..6 {
+                let (a, b) = (format!("a{}", i), format!("b{}", j));
+                across += cosine(vector(&a), vector(&b));
+                if i != j {
+                    inside += cosine(vector(&a), vector(&format!("a{}", j)));
+                }
+            }
+        }
+        assert!(inside / 20.0 > across / 25.0 + 0.2);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::rng::Rng;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// A Thorup–Zwick approximate distance oracle for undirected graphs.
+///
+/// With parameter `k` it answers distance queries with stretch at most `2k - 1`, i.e. the
+/// answer is between the true distance and `2k - 1` times it, in `O(k)` time, while
+/// storing about `k * n^(1 + 1/k)` entries instead of the `n^2` of an all-pairs table.
+/// `k = 1` is an exact table.
+///
+/// Preprocessing samples a hierarchy of node sets `V = A_0 ⊇ A_1 ⊇ ... ⊇ A_k = ∅`, each
+/// keeping the previous one's nodes with probability `n^(-1/k)`. Every node remembers its
+/// nearest node in each level, plus a "bunch" of the nodes of each level closer to it
+/// than the next level.
+#[derive(Debug)]
+pub struct DistanceOracle {
+    k: usize,
+    /// `pivot[i][v]` is the nearest node of `A_i` to `v` and its distance.
+    pivot: Vec<Vec<Option<(NodeId, Weight)>>>,
+    bunch: Vec<HashMap<NodeId, Weight>>,
+}
+
+impl DistanceOracle {
+    /// Build an oracle with stretch `2k - 1` over the undirected `graph`. `k` must be at
+    /// least 1.
+    pub fn new(graph: &Graph, k: usize, rng: &mut Rng) -> Self {
+        assert!(k >= 1, "the oracle needs at least one level");
+        let n = graph.node_count();
+        let p = (n.max(1) as f64).powf(-1.0 / k as f64);
+
+        // `level[v]` is the highest `i` with `v` in `A_i`.
+        let mut level = vec![0; n];
+        for i in 1..k {
+            let mut sampled: Vec<usize> = (0..n)
+                .filter(|&v| level[v] == i - 1 && rng.chance(p))
+                .collect();
+            // An empty top level would leave far apart pairs without a common pivot.
+            if i == k - 1 && sampled.is_empty() && n > 0 {
+                let candidates: Vec<usize> = (0..n).filter(|&v| level[v] == i - 1).collect();
+                sampled.push(candidates[rng.below(candidates.len())]);
+            }
+            for v in sampled {
+                level[v] = i;
+            }
+        }
+
+        let pivot: Vec<Vec<Option<(NodeId, Weight)>>> = (0..=k)
+            .map(|i| nearest(graph, (0..n).filter(|&v| i < k && level[v] >= i)))
+            .collect();
+
+        let mut bunch = vec![HashMap::new(); n];
+        for w in graph.node_ids() {
+            let i = level[w.index()];
+            let bound = &pivot[i + 1];
+            for (v, distance) in cluster(graph, w, bound) {
+                bunch[v.index()].insert(w, distance);
+            }
+        }
+
+        Self {
+            k,
+            pivot: pivot.into_iter().take(k).collect(),
+            bunch,
+        }
+    }
+
+    /// The guaranteed stretch, `2k - 1`.
+    pub fn stretch(&self) -> usize {
+        2 * self.k - 1
+    }
+
+    /// The number of stored bunch entries, which dominates the oracle's size.
+    pub fn size(&self) -> usize {
+        self.bunch.iter().map(|bunch| bunch.len()).sum()
+    }
+
+    /// An estimate of the distance between `u` and `v`, at least the true distance and at
+    /// most `2k - 1` times it. Returns `None` if they are disconnected.
+    pub fn distance(&self, u: NodeId, v: NodeId) -> Option<Weight> {
+        let (mut u, mut v) = (u, v);
+        let (mut w, mut to_u) = (u, 0);
+        for i in 1..=self.k {
+            if let Some(&to_v) = self.bunch[v.index()].get(&w) {
+                return Some(to_u + to_v);
+            }
+            if i == self.k {
+                break;
+            }
+            std::mem::swap(&mut u, &mut v);
+            let (next, distance) = self.pivot[i][u.index()]?;
+            w = next;
+            to_u = distance;
+        }
+        None
+    }
+}
+
+/// Multi-source Dijkstra: the nearest of `sources` to every node.
+fn nearest(graph: &Graph, sources: impl Iterator<Item = usize>) -> Vec<Option<(NodeId, Weight)>> {
+    let mut best: Vec<Option<(NodeId, Weight)>> = vec![None; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    for source in sources {
+        let source = NodeId::new(source);
+        best[source.index()] = Some((source, 0));
+        heap.push(Reverse((0, source, source)));
+    }
+    while let Some(Reverse((cost, node, origin))) = heap.pop() {
+        if best[node.index()].is_some_and(|(_, best)| cost > best) {
+            continue;
+        }
+        for edge in graph.edges_of(node) {
+            let next = cost + edge.cost;
+            if best[edge.node.index()].is_none_or(|(_, best)| next < best) {
+                best[edge.node.index()] = Some((origin, next));
+                heap.push(Reverse((next, edge.node, origin)));
+            }
+        }
+    }
+    best
+}
+
+/// The cluster of `w`: every node strictly closer to `w` than to the next level, whose
+/// distances to it are given by `bound`, with its distance from `w`.
+///
+/// A Dijkstra from `w` that only relaxes into nodes it reaches within their bound. Clusters
+/// are connected along shortest paths, so the pruning loses nothing.
+fn cluster(graph: &Graph, w: NodeId, bound: &[Option<(NodeId, Weight)>]) -> Vec<(NodeId, Weight)> {
+    let within = |node: NodeId, distance: Weight| {
+        bound[node.index()].is_none_or(|(_, limit)| distance < limit)
+    };
+    let mut members = Vec::new();
+    if !within(w, 0) {
+        return members;
+    }
+    let mut distance: HashMap<NodeId, Weight> = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    distance.insert(w, 0);
+    heap.push(Reverse((0, w)));
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        if distance.get(&node).is_some_and(|&best| cost > best) {
+            continue;
+        }
+        members.push((node, cost));
+        for edge in graph.edges_of(node) {
+            let next = cost + edge.cost;
+            if within(edge.node, next) && distance.get(&edge.node).is_none_or(|&best| next < best) {
+                distance.insert(edge.node, next);
+                heap.push(Reverse((next, edge.node)));
+            }
+        }
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::shortest_path_tree;
+
+    #[test]
+    fn estimates_stay_within_the_stretch() {
+        let mut rng = Rng::new(32);
+        for _ in 0..30 {
+            let n = 1 + rng.below(25);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(2 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                graph.add_bidirectional_edge(a, b, 1 + rng.below(10) as Weight);
+            }
+            for k in 1..=3 {
+                let oracle = DistanceOracle::new(&graph, k, &mut rng);
+                assert_eq!(oracle.stretch(), 2 * k - 1);
+                for u in graph.node_ids() {
+                    let tree = shortest_path_tree(&graph, u);
+                    for v in graph.node_ids() {
+                        let estimate = oracle.distance(u, v);
+                        match tree.distance(v) {
+                            Some(exact) => {
+                                let estimate = estimate.unwrap();
+                                assert!(exact <= estimate);
+                                assert!(estimate <= oracle.stretch() as Weight * exact);
+                            }
+                            None => assert_eq!(estimate, None),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn one_level_is_an_exact_table() {
+        let graph: Graph = "a b 1\nb c 2\nc d 3\nx y 1".parse().unwrap();
+        let oracle = DistanceOracle::new(&graph, 1, &mut Rng::new(0));
+        // Each node stores its whole component.
+        assert_eq!(oracle.size(), 4 * 4 + 2 * 2);
+        let [a, d, x] = ["a", "d", "x"].map(|name| graph.get_node(name).unwrap());
+        assert_eq!(oracle.distance(a, d), Some(6));
+        assert_eq!(oracle.distance(a, x), None);
+    }
+}
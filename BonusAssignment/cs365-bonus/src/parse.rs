@@ -0,0 +1,485 @@
+use crate::arena::ParseArena;
+use crate::decimal::FixedPoint;
+use crate::graph::{Graph, Weight};
+use std::convert::TryFrom;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+/// Inputs smaller than this are parsed on the calling thread.
+const MIN_CHUNK_BYTES: usize = 1 << 20;
+
+/// Why a line of the edge list format was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The line didn't have the three fields `src dest cost`; holds how many it had.
+    WrongFieldCount(usize),
+    /// The cost field, given here, isn't a valid weight.
+    InvalidCost(String),
+    /// The field after the cost, given here, is neither `->` nor `<->`.
+    InvalidDirection(String),
+    /// The time after `@`, given here, isn't a valid integer.
+    InvalidTime(String),
+    /// The line names a new node, but the graph already has as many as node ids can index.
+    TooManyNodes,
+}
+
+impl std::fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::WrongFieldCount(found) => {
+                write!(f, "expected 3 fields, found {}", found)
+            }
+            ParseErrorKind::InvalidCost(cost) => write!(f, "invalid cost '{}'", cost),
+            ParseErrorKind::InvalidDirection(direction) => {
+                write!(f, "expected '->' or '<->', found '{}'", direction)
+            }
+            ParseErrorKind::InvalidTime(time) => write!(f, "invalid time '@{}'", time),
+            ParseErrorKind::TooManyNodes => write!(f, "too many distinct nodes"),
+        }
+    }
+}
+
+/// Error returned when the edge list text format can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGraphError {
+    line: usize,
+    kind: ParseErrorKind,
+    text: Option<String>,
+}
+
+impl ParseGraphError {
+    pub(crate) fn new(line: usize, kind: ParseErrorKind) -> Self {
+        Self {
+            line,
+            kind,
+            text: None,
+        }
+    }
+
+    /// The 1-based line number of the offending line.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    /// The offending line itself, when the input was read by [`Graph::from_reader`] and
+    /// can't be looked up again.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+}
+
+impl std::fmt::Display for ParseGraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid edge on line {}", self.line)?;
+        if let Some(text) = &self.text {
+            write!(f, " '{}'", text)?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl std::error::Error for ParseGraphError {}
+
+/// Parses the `src dest cost` edge list format, one edge per line.
+///
+/// Edges are undirected unless the cost is followed by `->`, which makes the edge only run
+/// from `src` to `dest`. `<->` marks an undirected edge explicitly. An `@time` field last
+/// stamps the edge for [`TemporalGraph`](crate::temporal::TemporalGraph) and is otherwise
+/// ignored.
+///
+/// ```
+/// let graph: cs365_bonus::Graph = "a b 1\nb z 2 ->".parse().unwrap();
+/// assert_eq!(graph.node_count(), 3);
+/// let (b, z) = (graph.get_node("b").unwrap(), graph.get_node("z").unwrap());
+/// assert!(cs365_bonus::find_shortest_path(&graph, z, b).is_none());
+/// ```
+impl FromStr for Graph {
+    type Err = ParseGraphError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut arena = ParseArena::with_capacity(input.len());
+        parse_chunk(&mut arena, input, FixedPoint::INTEGER)?;
+        Ok(Graph::from_arena(&arena))
+    }
+}
+
+impl Graph {
+    /// Parse an edge list one line at a time, so the input never has to be in memory all
+    /// at once.
+    ///
+    /// Accepts the same format as [`FromStr`]. Parse errors come back as
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData) errors wrapping a
+    /// [`ParseGraphError`] that holds the offending line's text.
+    ///
+    /// ```
+    /// let graph = cs365_bonus::Graph::from_reader("a b 1\nb z 2 ->".as_bytes()).unwrap();
+    /// assert_eq!(graph.edge_count(), 2);
+    /// ```
+    pub fn from_reader(reader: impl BufRead) -> std::io::Result<Graph> {
+        Self::from_reader_scaled(reader, FixedPoint::INTEGER)
+    }
+
+    /// Like [`from_reader`](Self::from_reader), but reads decimal costs with `scale`.
+    pub fn from_reader_scaled(
+        mut reader: impl BufRead,
+        scale: FixedPoint,
+    ) -> std::io::Result<Graph> {
+        let mut arena = ParseArena::with_capacity(0);
+        let mut buffer = String::new();
+        let mut number = 0;
+        loop {
+            buffer.clear();
+            if reader.read_line(&mut buffer)? == 0 {
+                break;
+            }
+            number += 1;
+            let line = buffer.trim();
+            if line.is_empty() {
+                continue;
+            }
+            parse_line(&mut arena, line, scale).map_err(|kind| {
+                let mut error = ParseGraphError::new(number, kind);
+                error.text = Some(line.to_string());
+                std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+            })?;
+        }
+        Ok(Graph::from_arena(&arena))
+    }
+}
+
+impl TryFrom<&str> for Graph {
+    type Error = ParseGraphError;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input.parse()
+    }
+}
+
+/// Split a trimmed, non-empty `src dest cost [->|<->] [@time]` line into its endpoints,
+/// cost, and whether it is directed, reading the cost with `scale`. The time is checked but
+/// dropped, and any fields after it are ignored.
+pub(crate) fn parse_edge(
+    line: &str,
+    scale: FixedPoint,
+) -> Result<(&str, &str, Weight, bool), ParseErrorKind> {
+    let (src, dest, cost, directed, _) = parse_timed_edge(line, scale)?;
+    Ok((src, dest, cost, directed))
+}
+
+/// An edge line's endpoints, cost, whether it is directed, and its time, if any.
+pub(crate) type TimedEdge<'a> = (&'a str, &'a str, Weight, bool, Option<Weight>);
+
+/// Like [`parse_edge`], but also returns the edge's `@time`, if it has one.
+pub(crate) fn parse_timed_edge(
+    line: &str,
+    scale: FixedPoint,
+) -> Result<TimedEdge<'_>, ParseErrorKind> {
+    let mut iter = line.split(' ');
+    let (src, dest, cost) = match (iter.next(), iter.next(), iter.next()) {
+        (Some(src), Some(dest), Some(cost)) => (src, dest, cost),
+        (_, dest, _) => return Err(ParseErrorKind::WrongFieldCount(1 + dest.is_some() as usize)),
+    };
+    let cost = scale
+        .parse(cost)
+        .ok_or_else(|| ParseErrorKind::InvalidCost(cost.to_string()))?;
+    let mut next = iter.next();
+    let directed = match next {
+        None | Some("<->") => false,
+        Some("->") => true,
+        Some(other) if other.starts_with('@') => false,
+        Some(other) => return Err(ParseErrorKind::InvalidDirection(other.to_string())),
+    };
+    if next.is_some_and(|field| !field.starts_with('@')) {
+        next = iter.next();
+    }
+    let time = match next.and_then(|field| field.strip_prefix('@')) {
+        Some(time) => Some(
+            time.parse()
+                .map_err(|_| ParseErrorKind::InvalidTime(time.to_string()))?,
+        ),
+        None => None,
+    };
+    Ok((src, dest, cost, directed, time))
+}
+
+/// Parse every line of `text` into `arena`, returning the number of lines consumed.
+fn parse_chunk(
+    arena: &mut ParseArena,
+    text: &str,
+    scale: FixedPoint,
+) -> Result<usize, ParseGraphError> {
+    let mut lines = 0;
+    for (i, line) in text.lines().enumerate() {
+        lines = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        parse_line(arena, line, scale).map_err(|kind| ParseGraphError::new(i + 1, kind))?;
+    }
+
+    Ok(lines)
+}
+
+/// Parse one trimmed, non-empty line into `arena`.
+fn parse_line(arena: &mut ParseArena, line: &str, scale: FixedPoint) -> Result<(), ParseErrorKind> {
+    let (src, dest, cost, directed) = parse_edge(line, scale)?;
+    let src = arena.intern(src).ok_or(ParseErrorKind::TooManyNodes)?;
+    let dest = arena.intern(dest).ok_or(ParseErrorKind::TooManyNodes)?;
+    arena.push_edge(src, dest, cost, directed);
+    Ok(())
+}
+
+/// Split `input` into at most `count` pieces that each end on a line boundary.
+fn split_lines(input: &str, count: usize) -> Vec<&str> {
+    let bytes = input.as_bytes();
+    let mut chunks = Vec::with_capacity(count);
+    let mut start = 0;
+    for i in 1..=count {
+        let mut end = if i == count {
+            input.len()
+        } else {
+            (input.len() * i / count).max(start)
+        };
+        while end > 0 && end < input.len() && bytes[end - 1] != b'\n' {
+            end += 1;
+        }
+        if end > start {
+            chunks.push(&input[start..end]);
+        }
+        start = end;
+    }
+    chunks
+}
+
+/// Parse `input` using up to `threads` threads.
+///
+/// Each thread parses a line-aligned chunk into its own arena with local interning. The
+/// partial arenas are then merged in input order, so node ids match what [`FromStr`]
+/// would produce for the same input.
+pub fn parse_parallel(input: &str, threads: usize) -> Result<Graph, ParseGraphError> {
+    parse_scaled(input, FixedPoint::INTEGER, threads)
+}
+
+/// Like [`parse_parallel`], but reads decimal costs with `scale`.
+pub fn parse_scaled(
+    input: &str,
+    scale: FixedPoint,
+    threads: usize,
+) -> Result<Graph, ParseGraphError> {
+    parse_in_chunks(input, scale, threads, MIN_CHUNK_BYTES)
+}
+
+/// Like [`parse_scaled`], but only splitting `input` into chunks of at least
+/// `min_chunk_bytes`.
+fn parse_in_chunks(
+    input: &str,
+    scale: FixedPoint,
+    threads: usize,
+    min_chunk_bytes: usize,
+) -> Result<Graph, ParseGraphError> {
+    let count = threads.min(input.len() / min_chunk_bytes).max(1);
+    if count == 1 {
+        let mut arena = ParseArena::with_capacity(input.len());
+        parse_chunk(&mut arena, input, scale)?;
+        return Ok(Graph::from_arena(&arena));
+    }
+
+    let chunks = split_lines(input, count);
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut arena = ParseArena::with_capacity(chunk.len());
+                    parse_chunk(&mut arena, chunk, scale).map(|lines| (arena, lines))
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("parser thread panicked"))
+            .collect()
+    });
+
+    let mut merged = ParseArena::with_capacity(input.len());
+    let mut line_offset = 0;
+    for result in results {
+        let (arena, lines) =
+            result.map_err(|e| ParseGraphError::new(e.line + line_offset, e.kind))?;
+        line_offset += lines;
+
+        let remap = (0..arena.node_count() as u32)
+            .map(|id| merged.intern(arena.name(id)))
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(|| ParseGraphError::new(line_offset, ParseErrorKind::TooManyNodes))?;
+        for &(src, dest, cost, directed) in arena.edges() {
+            merged.push_edge(remap[src as usize], remap[dest as usize], cost, directed);
+        }
+    }
+
+    Ok(Graph::from_arena(&merged))
+}
+
+/// Write `graph` in the edge list format accepted by [`FromStr`], one line per edge.
+///
+/// Nodes without any edges can't be represented and are omitted. Directed edges are
+/// marked with `->`.
+pub fn write_edge_list(graph: &Graph, writer: &mut dyn Write) -> std::io::Result<()> {
+    write_edge_list_scaled(graph, FixedPoint::INTEGER, writer)
+}
+
+/// Like [`write_edge_list`], but prints costs with `scale`, so that [`parse_scaled`] with
+/// the same scale reads the graph back.
+pub fn write_edge_list_scaled(
+    graph: &Graph,
+    scale: FixedPoint,
+    writer: &mut dyn Write,
+) -> std::io::Result<()> {
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+    for (id, src, dest, cost) in graph.edges() {
+        write!(
+            writer,
+            "{} {} {}",
+            name(src),
+            name(dest),
+            scale.format(cost)
+        )?;
+        if graph.is_edge_directed(id) == Some(true) {
+            write!(writer, " ->")?;
+        }
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::EdgeId;
+
+    fn error(input: &str) -> ParseGraphError {
+        input.parse::<Graph>().unwrap_err()
+    }
+
+    #[test]
+    fn reports_missing_fields() {
+        let e = error("a b 1\na b");
+        assert_eq!(e.line(), 2);
+        assert_eq!(e.kind(), &ParseErrorKind::WrongFieldCount(2));
+        assert_eq!(error("a").kind(), &ParseErrorKind::WrongFieldCount(1));
+    }
+
+    #[test]
+    fn reports_invalid_costs() {
+        let e = error("\n\na b x");
+        assert_eq!(e.line(), 3);
+        assert_eq!(e.kind(), &ParseErrorKind::InvalidCost("x".into()));
+        assert_eq!(e.to_string(), "invalid edge on line 3: invalid cost 'x'");
+    }
+
+    #[test]
+    fn reads_edge_directions() {
+        let graph: Graph = "a b 1 ->\nb c 1 <->\nc a 5".parse().unwrap();
+        let node = |name| graph.get_node(name).unwrap();
+        let (a, b, c) = (node("a"), node("b"), node("c"));
+        assert_eq!(crate::find_shortest_path(&graph, a, c).unwrap().cost, 2);
+        assert_eq!(crate::find_shortest_path(&graph, b, a).unwrap().cost, 6);
+        assert_eq!(graph.edges_of(b).len(), 1);
+
+        let e = error("a b 1 >");
+        assert_eq!(e.kind(), &ParseErrorKind::InvalidDirection(">".into()));
+    }
+
+    #[test]
+    fn reads_edge_times() {
+        let line = |line| parse_timed_edge(line, FixedPoint::INTEGER);
+        assert_eq!(line("a b 1 @5"), Ok(("a", "b", 1, false, Some(5))));
+        assert_eq!(line("a b 1 -> @-2"), Ok(("a", "b", 1, true, Some(-2))));
+        assert_eq!(line("a b 1 <-> note"), Ok(("a", "b", 1, false, None)));
+        let graph: Graph = "a b 1 @3\nb c 2 -> @4".parse().unwrap();
+        assert_eq!(graph.edge_count(), 2);
+        assert_eq!(
+            error("a b 1 @x").kind(),
+            &ParseErrorKind::InvalidTime("x".into())
+        );
+    }
+
+    #[test]
+    fn reader_reports_the_offending_line() {
+        let text = "a b 1\n\nb c 2 ->\n c d x \n";
+        let e = Graph::from_reader(text.as_bytes()).unwrap_err();
+        let e = e
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<ParseGraphError>()
+            .unwrap();
+        assert_eq!(e.line(), 4);
+        assert_eq!(e.text(), Some("c d x"));
+        assert_eq!(
+            e.to_string(),
+            "invalid edge on line 4 'c d x': invalid cost 'x'"
+        );
+
+        let streamed = Graph::from_reader(&text.as_bytes()[..15]).unwrap();
+        let parsed: Graph = text[..15].parse().unwrap();
+        assert!(streamed.edges().eq(parsed.edges()));
+    }
+
+    #[test]
+    fn reads_decimal_costs() {
+        let scale = FixedPoint::new(2).unwrap();
+        let graph = parse_scaled("a b 1.5\nb z 0.25 ->\na z 2", scale, 1).unwrap();
+        let (a, z) = (graph.get_node("a").unwrap(), graph.get_node("z").unwrap());
+        assert_eq!(crate::find_shortest_path(&graph, a, z).unwrap().cost, 175);
+        let e = parse_scaled("a b 0.125", scale, 1).unwrap_err();
+        assert_eq!(e.kind(), &ParseErrorKind::InvalidCost("0.125".into()));
+        assert!("a b 1.5".parse::<Graph>().is_err());
+    }
+
+    #[test]
+    fn parallel_parse_matches_serial_parse() {
+        // Later lines introduce new names, so each chunk interns nodes the earlier ones
+        // never saw, and the varying line lengths put chunk boundaries mid-line.
+        let text: String = (0..500)
+            .map(|i| format!("n{} n{} {}\n", i % 97, i / 3 + 40, i * 37 % 1000))
+            .collect();
+        let chunks = split_lines(&text, 4);
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks.concat(), text);
+        assert!(chunks.iter().all(|chunk| chunk.ends_with('\n')));
+        let straddles = (1..4).any(|i| text.as_bytes()[text.len() * i / 4 - 1] != b'\n');
+        assert!(straddles);
+
+        let serial: Graph = text.parse().unwrap();
+        let parallel = parse_in_chunks(&text, FixedPoint::INTEGER, 4, 64).unwrap();
+        assert_eq!(serial.node_count(), parallel.node_count());
+        for node in serial.node_ids() {
+            assert_eq!(serial.get_node_name(node), parallel.get_node_name(node));
+        }
+        assert!(serial.edges().eq(parallel.edges()));
+        assert!(parse_parallel(&text, 4).unwrap().edges().eq(serial.edges()));
+
+        // Errors in later chunks report their line in the whole input.
+        let broken = format!("{}a b\n", text);
+        let e = parse_in_chunks(&broken, FixedPoint::INTEGER, 4, 64).unwrap_err();
+        assert_eq!(e.line(), 501);
+        assert_eq!(e.kind(), &ParseErrorKind::WrongFieldCount(2));
+    }
+
+    #[test]
+    fn edge_list_round_trips() {
+        let graph: Graph = "a b 1 ->\nb c 2\nc a -3".parse().unwrap();
+        let mut out = Vec::new();
+        write_edge_list(&graph, &mut out).unwrap();
+        let reparsed: Graph = String::from_utf8(out).unwrap().parse().unwrap();
+        assert!(graph.edges().eq(reparsed.edges()));
+        assert_eq!(reparsed.is_edge_directed(EdgeId::new(0)), Some(true));
+        assert_eq!(reparsed.is_edge_directed(EdgeId::new(1)), Some(false));
+    }
+}
@@ -0,0 +1,982 @@
+use crate::bellman_ford::{bellman_ford, NegativeCycle};
+use crate::graph::{Edge, EdgeId, Graph, GraphRef, NodeId, Weight};
+use crate::snapshot::{ReachedNode, SearchSnapshot};
+use std::collections::{BinaryHeap, HashSet};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct State {
+    cost: Weight,
+    position: NodeId,
+}
+
+impl Ord for State {
+    fn cmp(&self, other: &State) -> std::cmp::Ordering {
+        other
+            .cost
+            .cmp(&self.cost)
+            .then_with(|| self.position.cmp(&other.position))
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &State) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A route through a graph.
+///
+/// `nodes` runs from the start node to the end node inclusive, and `distance[i]` is the
+/// cumulative cost of reaching `nodes[i]`.
+#[derive(Debug)]
+pub struct Path {
+    pub nodes: Vec<NodeId>,
+    pub distance: Vec<Weight>,
+    pub cost: Weight,
+}
+
+impl Path {
+    /// The route visiting `nodes` in order, taking the cheapest edge between each
+    /// consecutive pair. Returns `None` if `nodes` is empty or two consecutive nodes
+    /// aren't adjacent.
+    pub fn from_nodes(graph: &Graph, nodes: Vec<NodeId>) -> Option<Path> {
+        if nodes.is_empty() {
+            return None;
+        }
+        let mut distance = vec![0];
+        for pair in nodes.windows(2) {
+            let edge = graph.find_edge(pair[0], pair[1])?;
+            distance.push(distance[distance.len() - 1] + graph.edge_weight(edge)?);
+        }
+        Some(Path {
+            cost: distance[distance.len() - 1],
+            nodes,
+            distance,
+        })
+    }
+
+    /// The route without the intermediate nodes that a direct edge of `graph` skips for no
+    /// more than the stretch of route it replaces. See [`simplified_with`](Self::simplified_with).
+    ///
+    /// ```
+    /// use cs365_bonus::Path;
+    ///
+    /// let graph: cs365_bonus::Graph = "a b 1\nb c 1\na c 2\nc d 1".parse().unwrap();
+    /// let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| graph.get_node(name).unwrap());
+    /// let path = Path::from_nodes(&graph, vec![a, b, c, d]).unwrap();
+    /// let simple = path.simplified(&graph);
+    /// assert_eq!(simple.nodes, vec![a, c, d]);
+    /// assert_eq!(simple.cost, 3);
+    /// ```
+    pub fn simplified(&self, graph: &Graph) -> Path {
+        self.simplified_with(|u, v| graph.edge_weight(graph.find_edge(u, v)?))
+    }
+
+    /// The route without redundant intermediate nodes, where `connect` gives the cost of a
+    /// straight connection between two nodes, if there is one. From each node kept, it
+    /// tries connecting straight to the nodes after it in turn, and skips ahead to the last
+    /// one before a connection is missing or costs more than the stretch of route it would
+    /// replace, so the cost never goes up. A straight connection could be a direct edge, or
+    /// for nodes with coordinates, a clear line of sight between them.
+    pub fn simplified_with(
+        &self,
+        mut connect: impl FnMut(NodeId, NodeId) -> Option<Weight>,
+    ) -> Path {
+        let (mut nodes, mut distance) = (vec![self.nodes[0]], vec![0]);
+        let mut i = 0;
+        while i + 1 < self.nodes.len() {
+            let mut next = (i + 1, self.distance[i + 1] - self.distance[i]);
+            for j in i + 2..self.nodes.len() {
+                match connect(self.nodes[i], self.nodes[j]) {
+                    Some(cost) if cost <= self.distance[j] - self.distance[i] => next = (j, cost),
+                    _ => break,
+                }
+            }
+            let (j, cost) = next;
+            nodes.push(self.nodes[j]);
+            distance.push(distance[distance.len() - 1] + cost);
+            i = j;
+        }
+        Path {
+            cost: distance[distance.len() - 1],
+            nodes,
+            distance,
+        }
+    }
+}
+
+/// Distances and parent pointers from a single-source search.
+#[derive(Debug)]
+pub struct ShortestPathTree {
+    source: NodeId,
+    distance: Vec<Option<Weight>>,
+    parent: Vec<Option<NodeId>>,
+}
+
+impl ShortestPathTree {
+    pub(crate) fn new(
+        source: NodeId,
+        distance: Vec<Option<Weight>>,
+        parent: Vec<Option<NodeId>>,
+    ) -> Self {
+        Self {
+            source,
+            distance,
+            parent,
+        }
+    }
+
+    pub fn source(&self) -> NodeId {
+        self.source
+    }
+
+    /// The cost of the cheapest path from the source to `node`, if one exists.
+    pub fn distance(&self, node: NodeId) -> Option<Weight> {
+        self.distance[node.index()]
+    }
+
+    /// The predecessor of `node` on its shortest path, if `node` isn't the source.
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.parent[node.index()]
+    }
+
+    pub fn distances(&self) -> &[Option<Weight>] {
+        &self.distance
+    }
+
+    /// Reconstruct the path from the source to `end` by following parent pointers.
+    pub fn path_to(&self, end: NodeId) -> Option<Path> {
+        let cost = self.distance(end)?;
+        let mut node = end;
+        let mut nodes = vec![node];
+        let mut dist = vec![cost];
+
+        while let Some(index) = self.parent(node) {
+            nodes.push(index);
+            dist.push(self.distance(index)?);
+            node = index;
+        }
+
+        nodes.reverse();
+        dist.reverse();
+
+        Some(Path {
+            nodes,
+            distance: dist,
+            cost,
+        })
+    }
+}
+
+/// Error returned when a search needs to expand more nodes than it was allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    pub(crate) budget: usize,
+}
+
+impl BudgetExceeded {
+    /// The number of nodes the search was allowed to expand.
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "the search gave up after expanding {} nodes",
+            self.budget
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Hooks called as a search runs, e.g. to animate it. Both do nothing by default.
+pub trait SearchVisitor {
+    /// `node` was settled at `distance`, leaving `frontier` entries in the queue.
+    fn settled(&mut self, _node: NodeId, _distance: Weight, _frontier: usize) {}
+
+    /// `node` was reached at `distance`, less than it was known to cost before.
+    fn improved(&mut self, _node: NodeId, _distance: Weight) {}
+}
+
+impl SearchVisitor for () {}
+
+/// Nodes and edges a path may not use, and the most it may cost.
+///
+/// ```
+/// use cs365_bonus::path::{PathConstraints, SearchContext};
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb z 2\na c 2\nc z 2".parse().unwrap();
+/// let node = |name| graph.get_node(name).unwrap();
+/// let mut constraints = PathConstraints::default();
+/// constraints.forbidden_nodes.insert(node("b"));
+/// let mut context = SearchContext::new();
+/// let path = context.find_constrained_path(&graph, node("a"), node("z"), &constraints);
+/// assert_eq!(path.unwrap().cost, 4);
+///
+/// constraints.max_cost = Some(3);
+/// assert!(context
+///     .find_constrained_path(&graph, node("a"), node("z"), &constraints)
+///     .is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathConstraints {
+    pub forbidden_nodes: HashSet<NodeId>,
+    /// Forbidding one direction of a bidirectional edge forbids both, as they share an id.
+    pub forbidden_edges: HashSet<EdgeId>,
+    pub max_cost: Option<Weight>,
+}
+
+impl PathConstraints {
+    /// Whether these constraints allow every path.
+    pub fn is_empty(&self) -> bool {
+        self.forbidden_nodes.is_empty()
+            && self.forbidden_edges.is_empty()
+            && self.max_cost.is_none()
+    }
+
+    fn allows(&self, edge: &Edge) -> bool {
+        !self.forbidden_nodes.contains(&edge.node) && !self.forbidden_edges.contains(&edge.id)
+    }
+}
+
+/// Reusable buffers for repeated searches over graphs of similar size.
+///
+/// Each entry is stamped with the generation of the search that wrote it, so starting a
+/// new search only bumps the generation instead of clearing `O(V)` arrays, and the
+/// buffers and heap are allocated once rather than per query. Settled nodes are kept in
+/// a bitset, so stale heap entries and edges back into the settled region are skipped by
+/// testing one bit instead of loading a distance and its stamp.
+///
+/// ```
+/// use cs365_bonus::path::SearchContext;
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb z 2\na z 5".parse().unwrap();
+/// let (a, z) = (graph.get_node("a").unwrap(), graph.get_node("z").unwrap());
+/// let mut context = SearchContext::new();
+/// for _ in 0..3 {
+///     assert_eq!(context.find_shortest_path(&graph, a, z).unwrap().cost, 3);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SearchContext {
+    distance: Vec<Weight>,
+    parent: Vec<Option<NodeId>>,
+    stamp: Vec<u32>,
+    generation: u32,
+    settled: Vec<u64>,
+    heap: BinaryHeap<State>,
+    /// Whether to check the search's invariants each time a node is settled.
+    checked: bool,
+}
+
+impl SearchContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check after every node settled that the queue, distances, and parents are
+    /// consistent, panicking with what went wrong if not.
+    ///
+    /// This costs `O(V + E)` per node settled, so it is only for tracking down bugs, e.g.
+    /// in a new queue or parallel code.
+    pub fn set_invariant_checks(&mut self, enabled: bool) {
+        self.checked = enabled;
+    }
+
+    /// Forget the previous search and make room for `nodes` nodes.
+    fn reset(&mut self, nodes: usize) {
+        if self.stamp.len() < nodes {
+            self.distance.resize(nodes, 0);
+            self.parent.resize(nodes, None);
+            self.stamp.resize(nodes, 0);
+        }
+        // One bit per node, so clearing is cheap next to the other arrays.
+        let words = nodes.div_ceil(64);
+        self.settled.truncate(words);
+        self.settled.iter_mut().for_each(|word| *word = 0);
+        self.settled.resize(words, 0);
+        self.generation = self.generation.wrapping_add(1);
+        if self.generation == 0 {
+            // Stamps from 2^32 searches ago would look current again.
+            self.stamp.iter_mut().for_each(|stamp| *stamp = 0);
+            self.generation = 1;
+        }
+        self.heap.clear();
+    }
+
+    /// The distance found to `node` by the last search.
+    pub fn distance(&self, node: NodeId) -> Option<Weight> {
+        let i = node.index();
+        (self.stamp.get(i) == Some(&self.generation)).then(|| self.distance[i])
+    }
+
+    fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.distance(node).and(self.parent[node.index()])
+    }
+
+    fn is_settled(&self, node: NodeId) -> bool {
+        self.settled[node.index() / 64] & 1 << (node.index() % 64) != 0
+    }
+
+    fn settle(&mut self, node: NodeId) {
+        self.settled[node.index() / 64] |= 1 << (node.index() % 64);
+    }
+
+    fn set(&mut self, node: NodeId, distance: Weight, parent: Option<NodeId>) {
+        let i = node.index();
+        self.distance[i] = distance;
+        self.parent[i] = parent;
+        self.stamp[i] = self.generation;
+    }
+
+    /// Run Dijkstra from `start` over the edges accepted by `allow`, stopping early once
+    /// `target` is settled or every remaining node costs more than `limit`.
+    ///
+    /// After hitting the limit, distances above it are only upper bounds. Each node
+    /// expanded is taken from `budget`, and the search returns `false` if that runs out
+    /// first. `visitor` sees every node settled and improved along the way.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn run<G, V, F>(
+        &mut self,
+        graph: &G,
+        start: NodeId,
+        target: Option<NodeId>,
+        limit: Option<Weight>,
+        budget: Option<&mut usize>,
+        visitor: &mut V,
+        allow: F,
+    ) -> bool
+    where
+        G: GraphRef + ?Sized,
+        V: SearchVisitor + ?Sized,
+        F: Fn(NodeId, &Edge) -> bool,
+    {
+        let is_target = |node| Some(node) == target;
+        self.run_from(graph, &[start], is_target, limit, budget, visitor, allow)
+    }
+
+    /// Like [`run`](Self::run), but starting from every node of `starts` at once and
+    /// stopping at the first node settled that `is_target` accepts.
+    #[allow(clippy::too_many_arguments)]
+    fn run_from<G, V, T, F>(
+        &mut self,
+        graph: &G,
+        starts: &[NodeId],
+        is_target: T,
+        limit: Option<Weight>,
+        mut budget: Option<&mut usize>,
+        visitor: &mut V,
+        allow: F,
+    ) -> bool
+    where
+        G: GraphRef + ?Sized,
+        V: SearchVisitor + ?Sized,
+        T: Fn(NodeId) -> bool,
+        F: Fn(NodeId, &Edge) -> bool,
+    {
+        self.reset(graph.node_count());
+        for &start in starts {
+            if graph.contains_node(start) && self.distance(start).is_none() {
+                self.set(start, 0, None);
+                self.heap.push(State {
+                    cost: 0,
+                    position: start,
+                });
+            }
+        }
+
+        let mut previous = 0;
+        while let Some(State { cost, position }) = self.heap.pop() {
+            if self.is_settled(position) {
+                continue;
+            }
+            self.settle(position);
+            if self.checked {
+                self.check(graph, starts, position, cost, previous);
+                previous = cost;
+            }
+            visitor.settled(position, cost, self.heap.len());
+            if is_target(position) || limit.is_some_and(|limit| cost > limit) {
+                break;
+            }
+            if let Some(remaining) = budget.as_deref_mut() {
+                if *remaining == 0 {
+                    return false;
+                }
+                *remaining -= 1;
+            }
+
+            for edge in graph.edges_of(position) {
+                if self.is_settled(edge.node) || !allow(position, edge) {
+                    continue;
+                }
+                let next = State {
+                    cost: cost + graph.cost(edge),
+                    position: edge.node,
+                };
+
+                if self
+                    .distance(next.position)
+                    .is_none_or(|distance| next.cost < distance)
+                {
+                    self.heap.push(next);
+                    self.set(next.position, next.cost, Some(position));
+                    visitor.improved(next.position, next.cost);
+                }
+            }
+        }
+        true
+    }
+
+    /// Panic unless settling `node` at `cost`, after the last node settled at `previous`,
+    /// left the search consistent.
+    fn check<G: GraphRef + ?Sized>(
+        &self,
+        graph: &G,
+        starts: &[NodeId],
+        node: NodeId,
+        cost: Weight,
+        previous: Weight,
+    ) {
+        assert!(
+            cost >= previous,
+            "search invariant violated: settled {:?} at {} after settling a node at {}",
+            node,
+            cost,
+            previous
+        );
+        assert_eq!(
+            self.distance(node),
+            Some(cost),
+            "search invariant violated: settled {:?} at a cost other than its distance",
+            node
+        );
+        let mut queued = HashSet::new();
+        for state in &self.heap {
+            let distance = self.distance(state.position);
+            assert!(
+                state.cost >= cost && distance.is_some_and(|distance| state.cost >= distance),
+                "search invariant violated: {:?} is queued at {}, below the settled cost {} \
+                 or its distance {:?}",
+                state.position,
+                state.cost,
+                cost,
+                distance
+            );
+            queued.insert((state.position, state.cost));
+        }
+        for reached in (0..graph.node_count()).map(NodeId::new) {
+            let distance = match self.distance(reached) {
+                Some(distance) => distance,
+                None => continue,
+            };
+            assert!(
+                self.is_settled(reached) || queued.contains(&(reached, distance)),
+                "search invariant violated: {:?} was reached at {} but is not queued at it",
+                reached,
+                distance
+            );
+            if starts.contains(&reached) {
+                assert!(
+                    distance == 0 && self.parent(reached).is_none(),
+                    "search invariant violated: a start has a parent or a nonzero distance"
+                );
+                continue;
+            }
+            let parent = self.parent(reached);
+            let valid = parent.zip(parent.and_then(|parent| self.distance(parent)));
+            let valid = valid.is_some_and(|(parent, base)| {
+                self.is_settled(parent)
+                    && graph
+                        .edges_of(parent)
+                        .any(|edge| edge.node == reached && base + graph.cost(edge) == distance)
+            });
+            assert!(
+                valid,
+                "search invariant violated: {:?} at {} has parent {:?}, which is unsettled or \
+                 has no edge to it costing the difference",
+                reached, distance, parent
+            );
+        }
+    }
+
+    /// The shortest path from `start` to `end`, reusing this context's buffers.
+    pub fn find_shortest_path<G: GraphRef + ?Sized>(
+        &mut self,
+        graph: &G,
+        start: NodeId,
+        end: NodeId,
+    ) -> Option<Path> {
+        self.run(graph, start, Some(end), None, None, &mut (), |_, _| true);
+        self.path_to(end)
+    }
+
+    /// Like [`find_shortest_path`](Self::find_shortest_path), but gives up instead of
+    /// expanding more than `budget` nodes.
+    pub fn find_shortest_path_within<G: GraphRef + ?Sized>(
+        &mut self,
+        graph: &G,
+        start: NodeId,
+        end: NodeId,
+        budget: usize,
+    ) -> Result<Option<Path>, BudgetExceeded> {
+        let mut remaining = budget;
+        if self.run(
+            graph,
+            start,
+            Some(end),
+            None,
+            Some(&mut remaining),
+            &mut (),
+            |_, _| true,
+        ) {
+            Ok(self.path_to(end))
+        } else {
+            Err(BudgetExceeded { budget })
+        }
+    }
+
+    /// Like [`find_shortest_path_within`](Self::find_shortest_path_within), without a
+    /// budget if it is `None`, reporting the search's progress to `visitor`.
+    pub fn find_shortest_path_visited<G, V>(
+        &mut self,
+        graph: &G,
+        start: NodeId,
+        end: NodeId,
+        budget: Option<usize>,
+        visitor: &mut V,
+    ) -> Result<Option<Path>, BudgetExceeded>
+    where
+        G: GraphRef + ?Sized,
+        V: SearchVisitor + ?Sized,
+    {
+        let mut remaining = budget;
+        if self.run(
+            graph,
+            start,
+            Some(end),
+            None,
+            remaining.as_mut(),
+            visitor,
+            |_, _| true,
+        ) {
+            Ok(self.path_to(end))
+        } else {
+            Err(BudgetExceeded {
+                budget: budget.unwrap_or(0),
+            })
+        }
+    }
+
+    /// The shortest path from `start` to `end` that meets `constraints`.
+    ///
+    /// Forbidden nodes and edges are skipped as the search reaches them, and the search
+    /// stops once every remaining node costs more than the maximum.
+    pub fn find_constrained_path<G: GraphRef + ?Sized>(
+        &mut self,
+        graph: &G,
+        start: NodeId,
+        end: NodeId,
+        constraints: &PathConstraints,
+    ) -> Option<Path> {
+        if constraints.forbidden_nodes.contains(&start) {
+            return None;
+        }
+        self.run(
+            graph,
+            start,
+            Some(end),
+            constraints.max_cost,
+            None,
+            &mut (),
+            |_, edge| constraints.allows(edge),
+        );
+        self.path_to(end)
+            .filter(|path| constraints.max_cost.is_none_or(|max| path.cost <= max))
+    }
+
+    /// The cheapest path from any node of `sources` to any node of `targets`.
+    ///
+    /// This is a single search from all the sources at once, which stops as soon as it
+    /// settles a target, so it costs no more than one ordinary query however large the
+    /// sets are.
+    ///
+    /// ```
+    /// use cs365_bonus::SearchContext;
+    ///
+    /// let graph: cs365_bonus::Graph = "a x 4\nb x 1\nx y 2\nx z 1".parse().unwrap();
+    /// let node = |name| graph.get_node(name).unwrap();
+    /// let path = SearchContext::new()
+    ///     .find_path_between(&graph, &[node("a"), node("b")], &[node("y"), node("z")])
+    ///     .unwrap();
+    /// assert_eq!((path.nodes, path.cost), (vec![node("b"), node("x"), node("z")], 2));
+    /// ```
+    pub fn find_path_between<G: GraphRef + ?Sized>(
+        &mut self,
+        graph: &G,
+        sources: &[NodeId],
+        targets: &[NodeId],
+    ) -> Option<Path> {
+        let mut wanted = vec![false; graph.node_count()];
+        for &target in targets.iter().filter(|&&node| graph.contains_node(node)) {
+            wanted[target.index()] = true;
+        }
+        let is_target = |node: NodeId| wanted[node.index()];
+        self.run_from(graph, sources, is_target, None, None, &mut (), |_, _| true);
+        // The search stopped at the first target it settled, the only one that is.
+        let end = targets
+            .iter()
+            .copied()
+            .find(|&node| is_target(node) && self.is_settled(node))?;
+        self.path_to(end)
+    }
+
+    /// The distance to every node from the nearest node of `sources`, or `None` for nodes
+    /// none of them reach, found with a single search from all of them at once.
+    pub fn nearest_source_distances<G: GraphRef + ?Sized>(
+        &mut self,
+        graph: &G,
+        sources: &[NodeId],
+    ) -> Vec<Option<Weight>> {
+        self.run_from(graph, sources, |_| false, None, None, &mut (), |_, _| true);
+        (0..graph.node_count())
+            .map(|node| self.distance(NodeId::new(node)))
+            .collect()
+    }
+
+    /// Reconstruct the path to `end` found by the last search.
+    pub fn path_to(&self, end: NodeId) -> Option<Path> {
+        let cost = self.distance(end)?;
+        let mut nodes = vec![end];
+        let mut distance = vec![cost];
+        while let Some(previous) = self.parent(nodes[nodes.len() - 1]) {
+            nodes.push(previous);
+            distance.push(self.distance(previous)?);
+        }
+        nodes.reverse();
+        distance.reverse();
+        Some(Path {
+            nodes,
+            distance,
+            cost,
+        })
+    }
+
+    /// The distances, parents, and queue left by the last search, which may have stopped
+    /// part way.
+    pub fn snapshot(&self) -> SearchSnapshot {
+        let reached = (0..self.stamp.len())
+            .map(NodeId::new)
+            .filter_map(|node| {
+                Some(ReachedNode {
+                    node,
+                    distance: self.distance(node)?,
+                    parent: self.parent(node),
+                    settled: self.is_settled(node),
+                })
+            })
+            .collect();
+        let mut queue: Vec<_> = self
+            .heap
+            .iter()
+            .map(|state| (state.position, state.cost))
+            .collect();
+        queue.sort_by_key(|&(node, cost)| (cost, node));
+        SearchSnapshot { reached, queue }
+    }
+
+    /// Copy the last search's results out as a tree over `nodes` nodes.
+    fn to_tree(&self, source: NodeId, nodes: usize) -> ShortestPathTree {
+        let ids = (0..nodes).map(NodeId::new);
+        ShortestPathTree {
+            source,
+            distance: ids.clone().map(|node| self.distance(node)).collect(),
+            parent: ids.map(|node| self.parent(node)).collect(),
+        }
+    }
+}
+
+/// Run Dijkstra from `start` over the edges accepted by `allow`, stopping early once
+/// `target` is settled or every remaining node costs more than `limit`.
+///
+/// After hitting the limit, distances above it are only upper bounds.
+pub(crate) fn dijkstra<G, F>(
+    graph: &G,
+    start: NodeId,
+    target: Option<NodeId>,
+    limit: Option<Weight>,
+    allow: F,
+) -> ShortestPathTree
+where
+    G: GraphRef + ?Sized,
+    F: Fn(NodeId, &Edge) -> bool,
+{
+    let mut context = SearchContext::new();
+    context.run(graph, start, target, limit, None, &mut (), allow);
+    context.to_tree(start, graph.node_count())
+}
+
+/// Compute shortest paths from `start` to every reachable node.
+///
+/// Nothing is reachable if `start` is hidden by a view. This runs Dijkstra, so costs must
+/// not be negative; see [`shortest_path_tree_using`] for graphs where they may be.
+pub fn shortest_path_tree<G: GraphRef + ?Sized>(graph: &G, start: NodeId) -> ShortestPathTree {
+    dijkstra(graph, start, None, None, |_, _| true)
+}
+
+pub fn find_shortest_path<G: GraphRef + ?Sized>(
+    graph: &G,
+    start: NodeId,
+    end: NodeId,
+) -> Option<Path> {
+    dijkstra(graph, start, Some(end), None, |_, _| true).path_to(end)
+}
+
+/// The shortest path from each of `sources` to `target`, in the same order, searching from
+/// several sources at once on `threads` threads.
+///
+/// Each thread reuses one [`SearchContext`] for its share of the sources, so this only
+/// allocates once per thread. Costs must not be negative.
+///
+/// ```
+/// use cs365_bonus::find_shortest_paths_from_sources;
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb z 2\nx y 1".parse().unwrap();
+/// let node = |name| graph.get_node(name).unwrap();
+/// let paths = find_shortest_paths_from_sources(&graph, &[node("a"), node("x")], node("z"), 2);
+/// assert_eq!(paths[0].as_ref().map(|path| path.cost), Some(3));
+/// assert!(paths[1].is_none());
+/// ```
+pub fn find_shortest_paths_from_sources<G: GraphRef + Sync + ?Sized>(
+    graph: &G,
+    sources: &[NodeId],
+    target: NodeId,
+    threads: usize,
+) -> Vec<Option<Path>> {
+    let chunk = sources.len().div_ceil(threads.max(1)).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = sources
+            .chunks(chunk)
+            .map(|sources| {
+                scope.spawn(move || {
+                    let mut context = SearchContext::new();
+                    sources
+                        .iter()
+                        .map(|&source| context.find_shortest_path(graph, source, target))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("search thread panicked"))
+            .collect()
+    })
+}
+
+/// Which single-source shortest path algorithm to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// Bellman-Ford if any edge has a negative cost, otherwise Dijkstra.
+    #[default]
+    Auto,
+    /// Dijkstra, which may return wrong distances if any edge cost is negative.
+    Dijkstra,
+    /// Bellman-Ford, which is slower but allows negative costs.
+    BellmanFord,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Algorithm::Auto),
+            "dijkstra" => Ok(Algorithm::Dijkstra),
+            "bellman-ford" => Ok(Algorithm::BellmanFord),
+            _ => Err(format!(
+                "unknown algorithm '{}', expected 'auto', 'dijkstra', or 'bellman-ford'",
+                s
+            )),
+        }
+    }
+}
+
+/// Whether any edge of `graph` has a negative cost.
+pub fn has_negative_edges<G: GraphRef + ?Sized>(graph: &G) -> bool {
+    (0..graph.node_count())
+        .map(NodeId::new)
+        .filter(|&node| graph.contains_node(node))
+        .any(|node| graph.edges_of(node).any(|edge| graph.cost(edge) < 0))
+}
+
+/// Compute shortest paths from `start` with the chosen `algorithm`.
+///
+/// Only Bellman-Ford detects negative cycles, so this can only fail if it runs.
+pub fn shortest_path_tree_using<G: GraphRef + ?Sized>(
+    graph: &G,
+    start: NodeId,
+    algorithm: Algorithm,
+) -> Result<ShortestPathTree, NegativeCycle> {
+    let bellman = match algorithm {
+        Algorithm::Auto => has_negative_edges(graph),
+        Algorithm::Dijkstra => false,
+        Algorithm::BellmanFord => true,
+    };
+    if bellman {
+        bellman_ford(graph, start)
+    } else {
+        Ok(shortest_path_tree(graph, start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph {
+        "a b 1\nb c 2\na c 5\nc z 1\nx y 1".parse().unwrap()
+    }
+
+    fn node(graph: &Graph, name: &str) -> NodeId {
+        graph.get_node(name).unwrap()
+    }
+
+    fn names<'a>(graph: &'a Graph, path: &Path) -> Vec<&'a str> {
+        path.nodes
+            .iter()
+            .map(|&n| graph.get_node_name(n).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reconstructs_nodes_and_cumulative_distances() {
+        let graph = graph();
+        let path = find_shortest_path(&graph, node(&graph, "a"), node(&graph, "z")).unwrap();
+        assert_eq!(names(&graph, &path), ["a", "b", "c", "z"]);
+        assert_eq!(path.distance, [0, 1, 3, 4]);
+        assert_eq!(path.cost, 4);
+    }
+
+    #[test]
+    fn checked_searches_keep_their_invariants() {
+        let graph = crate::generate::grid_graph(12, 12, 9, &mut crate::rng::Rng::new(3));
+        let mut context = SearchContext::new();
+        context.set_invariant_checks(true);
+        for (from, to) in [("0,0", "11,11"), ("5,7", "0,3"), ("11,0", "11,0")] {
+            let path = context.find_shortest_path(&graph, node(&graph, from), node(&graph, to));
+            let expected = find_shortest_path(&graph, node(&graph, from), node(&graph, to));
+            assert_eq!(path.map(|path| path.cost), expected.map(|path| path.cost));
+        }
+    }
+
+    #[test]
+    fn path_to_source_is_a_single_node() {
+        let graph = graph();
+        let a = node(&graph, "a");
+        let path = shortest_path_tree(&graph, a).path_to(a).unwrap();
+        assert_eq!(path.nodes, [a]);
+        assert_eq!(path.distance, [0]);
+        assert_eq!(path.cost, 0);
+    }
+
+    #[test]
+    fn unreachable_nodes_have_no_path() {
+        let graph = graph();
+        let tree = shortest_path_tree(&graph, node(&graph, "a"));
+        assert!(tree.path_to(node(&graph, "x")).is_none());
+        assert_eq!(tree.distance(node(&graph, "y")), None);
+        assert_eq!(tree.parent(node(&graph, "a")), None);
+    }
+
+    #[test]
+    fn every_tree_path_ends_at_its_node() {
+        let graph = graph();
+        let tree = shortest_path_tree(&graph, node(&graph, "z"));
+        for end in graph.node_ids() {
+            if let Some(path) = tree.path_to(end) {
+                assert_eq!(path.nodes[0], tree.source());
+                assert_eq!(path.nodes[path.nodes.len() - 1], end);
+                assert_eq!(Some(path.cost), tree.distance(end));
+                assert!(path.distance.windows(2).all(|w| w[0] <= w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn from_nodes_follows_cheapest_edges() {
+        let graph: Graph = "a b 4\na b 2\nb c 1".parse().unwrap();
+        let nodes = vec![node(&graph, "a"), node(&graph, "b"), node(&graph, "c")];
+        let path = Path::from_nodes(&graph, nodes).unwrap();
+        assert_eq!(path.distance, [0, 2, 3]);
+        let skip = vec![node(&graph, "a"), node(&graph, "c")];
+        assert!(Path::from_nodes(&graph, skip).is_none());
+        assert!(Path::from_nodes(&graph, Vec::new()).is_none());
+    }
+
+    #[test]
+    fn search_context_forgets_previous_searches() {
+        let small: Graph = "a b 1".parse().unwrap();
+        let large = graph();
+        let mut context = SearchContext::new();
+        let (a, b) = (node(&small, "a"), node(&small, "b"));
+        assert_eq!(context.find_shortest_path(&small, a, b).unwrap().cost, 1);
+
+        let (x, z) = (node(&large, "x"), node(&large, "z"));
+        assert!(context.find_shortest_path(&large, x, z).is_none());
+        assert_eq!(context.distance(a), None);
+        let path = context
+            .find_shortest_path(&large, node(&large, "a"), z)
+            .unwrap();
+        assert_eq!(path.distance, [0, 1, 3, 4]);
+    }
+
+    #[test]
+    fn budgets_stop_long_searches() {
+        let graph = graph();
+        let (a, z) = (node(&graph, "a"), node(&graph, "z"));
+        let mut context = SearchContext::new();
+        let error = context
+            .find_shortest_path_within(&graph, a, z, 2)
+            .unwrap_err();
+        assert_eq!(error.budget(), 2);
+        let path = context.find_shortest_path_within(&graph, a, z, 3).unwrap();
+        assert_eq!(path.unwrap().cost, 4);
+    }
+
+    #[test]
+    fn auto_uses_bellman_ford_for_negative_costs() {
+        let graph = Graph::from_directed_edges([("a", "b", 4), ("a", "c", 1), ("b", "c", -5)]);
+        let (a, c) = (node(&graph, "a"), node(&graph, "c"));
+        let tree = shortest_path_tree_using(&graph, a, Algorithm::Auto).unwrap();
+        assert_eq!(tree.distance(c), Some(-1));
+        assert_eq!(names(&graph, &tree.path_to(c).unwrap()), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn bellman_ford_reports_reachable_negative_cycles() {
+        let cycle = [("a", "b", 1), ("b", "c", -3), ("c", "b", 1), ("x", "a", 1)];
+        let graph = Graph::from_directed_edges(cycle);
+        let error = shortest_path_tree_using(&graph, node(&graph, "a"), Algorithm::BellmanFord)
+            .unwrap_err();
+        let mut nodes: Vec<&str> = error
+            .nodes()
+            .iter()
+            .map(|&n| graph.get_node_name(n).unwrap())
+            .collect();
+        nodes.sort_unstable();
+        assert_eq!(nodes, ["b", "c"]);
+
+        let unreachable =
+            Graph::from_directed_edges([("a", "b", 1), ("c", "d", -1), ("d", "c", -1)]);
+        assert!(
+            shortest_path_tree_using(&unreachable, node(&unreachable, "a"), Algorithm::Auto)
+                .is_ok()
+        );
+    }
+}
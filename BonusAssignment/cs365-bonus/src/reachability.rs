@@ -0,0 +1,196 @@
+use crate::graph::{Graph, NodeId};
+use crate::rng::Rng;
+
+/// Per-node labels that can prove one node does not reach another without searching,
+/// following GRAIL (Yildirim et al., 2010).
+///
+/// Strongly connected components are contracted into a DAG, and each labeling is a random
+/// depth-first traversal of it giving every component the interval from the smallest
+/// post-order rank among its descendants to its own rank. If `u` reaches `v`, the interval
+/// of `v` lies inside that of `u` in every labeling, so any labeling where it doesn't proves
+/// there is no path. Labels take `O(d (n + m))` time and `O(d n)` space for `d` labelings,
+/// and more labelings rule out more pairs.
+///
+/// ```
+/// use cs365_bonus::reachability::ReachabilityIndex;
+///
+/// let mut graph = cs365_bonus::Graph::new();
+/// let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| graph.get_or_insert_node(name));
+/// graph.add_edge(a, b, 1);
+/// graph.add_edge(b, a, 1);
+/// graph.add_edge(b, c, 1);
+/// graph.add_edge(d, c, 1);
+/// let index = ReachabilityIndex::build(&graph, 2, 0);
+/// assert!(!index.may_reach(c, a) && !index.may_reach(c, d));
+/// assert!(index.may_reach(b, a) && index.may_reach(a, c));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReachabilityIndex {
+    /// The strongly connected component of each node.
+    component: Vec<usize>,
+    /// `labels[i]` holds the `(low, rank)` interval of every component in labeling `i`.
+    labels: Vec<Vec<(u32, u32)>>,
+}
+
+impl ReachabilityIndex {
+    /// Label `graph` with `labelings` random traversals drawn from `seed`.
+    pub fn build(graph: &Graph, labelings: usize, seed: u64) -> Self {
+        let component = strongly_connected_components(graph);
+        let count = component.iter().map(|&c| c + 1).max().unwrap_or(0);
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for node in graph.node_ids() {
+            let from = component[node.index()];
+            for edge in graph.edges_of(node) {
+                let to = component[edge.node.index()];
+                if from != to {
+                    successors[from].push(to);
+                }
+            }
+        }
+        for list in successors.iter_mut() {
+            list.sort_unstable();
+            list.dedup();
+        }
+        let mut has_predecessor = vec![false; count];
+        for &to in successors.iter().flatten() {
+            has_predecessor[to] = true;
+        }
+
+        let mut rng = Rng::new(seed);
+        let labels = (0..labelings)
+            .map(|_| {
+                for list in successors.iter_mut() {
+                    rng.shuffle(list);
+                }
+                let mut roots: Vec<usize> = (0..count).filter(|&c| !has_predecessor[c]).collect();
+                rng.shuffle(&mut roots);
+                label(&successors, &roots)
+            })
+            .collect();
+        Self { component, labels }
+    }
+
+    /// Whether `from` might reach `to`. `false` is certain; `true` only means no labeling
+    /// ruled it out, so a search is still needed.
+    pub fn may_reach(&self, from: NodeId, to: NodeId) -> bool {
+        let (u, v) = (self.component[from.index()], self.component[to.index()]);
+        u == v
+            || self.labels.iter().all(|labels| {
+                let ((u_low, u_rank), (v_low, v_rank)) = (labels[u], labels[v]);
+                u_low <= v_low && v_rank < u_rank
+            })
+    }
+}
+
+/// One GRAIL labeling of the DAG given by `successors`, traversed from `roots` in order.
+fn label(successors: &[Vec<usize>], roots: &[usize]) -> Vec<(u32, u32)> {
+    let mut labels = vec![(u32::MAX, 0); successors.len()];
+    let mut visited = vec![false; successors.len()];
+    let mut rank = 0;
+    // Each frame is a component and how many of its successors have been looked at.
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for &root in roots {
+        visited[root] = true;
+        stack.push((root, 0));
+        while let Some((node, next)) = stack.pop() {
+            if let Some(&child) = successors[node].get(next) {
+                stack.push((node, next + 1));
+                if !visited[child] {
+                    visited[child] = true;
+                    stack.push((child, 0));
+                }
+                continue;
+            }
+            let low = successors[node]
+                .iter()
+                .map(|&child| labels[child].0)
+                .fold(rank, u32::min);
+            labels[node] = (low, rank);
+            rank += 1;
+        }
+    }
+    labels
+}
+
+/// The strongly connected component of every node, numbered so that edges between
+/// components only lead to lower numbers, by an iterative version of Tarjan's algorithm.
+pub fn strongly_connected_components(graph: &Graph) -> Vec<usize> {
+    let n = graph.node_count();
+    let mut index = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut component = vec![usize::MAX; n];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut count = 0;
+    let mut frames: Vec<(NodeId, usize)> = Vec::new();
+    for root in graph.node_ids() {
+        if index[root.index()] != usize::MAX {
+            continue;
+        }
+        frames.push((root, 0));
+        while let Some((node, next)) = frames.pop() {
+            let i = node.index();
+            if next == 0 {
+                index[i] = next_index;
+                low[i] = next_index;
+                next_index += 1;
+                stack.push(node);
+                on_stack[i] = true;
+            }
+            if let Some(edge) = graph.edges_of(node).get(next) {
+                frames.push((node, next + 1));
+                let child = edge.node.index();
+                if index[child] == usize::MAX {
+                    frames.push((edge.node, 0));
+                } else if on_stack[child] {
+                    low[i] = low[i].min(index[child]);
+                }
+                continue;
+            }
+            if let Some(&(parent, _)) = frames.last() {
+                low[parent.index()] = low[parent.index()].min(low[i]);
+            }
+            if low[i] == index[i] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member.index()] = false;
+                    component[member.index()] = count;
+                    if member == node {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+    component
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::shortest_path_tree;
+
+    #[test]
+    fn never_rules_out_a_real_path() {
+        let mut rng = Rng::new(3);
+        let mut graph = Graph::new();
+        let nodes: Vec<NodeId> = (0..60)
+            .map(|i| graph.get_or_insert_node(&i.to_string()))
+            .collect();
+        for _ in 0..90 {
+            graph.add_edge(nodes[rng.below(60)], nodes[rng.below(60)], 1);
+        }
+        let index = ReachabilityIndex::build(&graph, 3, 1);
+        let mut ruled_out = 0;
+        for &from in &nodes {
+            let tree = shortest_path_tree(&graph, from);
+            for &to in &nodes {
+                let reaches = tree.distance(to).is_some();
+                assert!(!reaches || index.may_reach(from, to));
+                ruled_out += usize::from(!index.may_reach(from, to));
+            }
+        }
+        assert!(ruled_out > 0);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::Path;
+use std::collections::HashMap;
+
+/// An agent running Learning Real-Time A* (Korf, 1990): it can only look `lookahead` moves
+/// ahead before committing to a move, and learns better heuristic values as it goes.
+///
+/// Each move looks at every walk of `lookahead` edges from the current node, or fewer if it
+/// ends at the target, and takes the first edge of the one minimizing its cost plus the
+/// heuristic value of where it ends, counting each first edge as worth at least its cost
+/// plus the value of the node it leads to. That minimum is also a lower bound on the
+/// distance from the current node, so the agent raises the current node's value to it.
+/// Values start from an admissible heuristic and stay admissible, so the agent reaches the
+/// target whenever it is reachable from everywhere the agent can get to, and repeated
+/// trials from the same start converge to optimal routes. Costs must not be negative.
+///
+/// ```
+/// use cs365_bonus::realtime::RealTimeAgent;
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb d 5\na c 2\nc d 2".parse().unwrap();
+/// let [a, d] = ["a", "d"].map(|name| graph.get_node(name).unwrap());
+/// let mut agent = RealTimeAgent::new(&graph, d, vec![0; 4], 1);
+/// let first = agent.trial(a, 100);
+/// // It wanders a -> b -> a -> c -> d while learning that b is a detour.
+/// assert_eq!(first.path.unwrap().cost, 6);
+/// let mut last = agent.trial(a, 100);
+/// while last.updates > 0 {
+///     last = agent.trial(a, 100);
+/// }
+/// assert_eq!(last.path.unwrap().cost, 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RealTimeAgent<'a> {
+    graph: &'a Graph,
+    target: NodeId,
+    lookahead: usize,
+    heuristic: Vec<Weight>,
+}
+
+/// One run of a [`RealTimeAgent`] from a start node.
+#[derive(Debug)]
+pub struct Trial {
+    /// The route the agent walked, revisits and all, or `None` if it got stuck at a node
+    /// with no way out or ran out of moves before reaching the target.
+    pub path: Option<Path>,
+    pub moves: usize,
+    /// How many moves raised a heuristic value. Zero means the values have converged.
+    pub updates: usize,
+}
+
+impl<'a> RealTimeAgent<'a> {
+    /// An agent heading for `target`, starting from the admissible estimates in
+    /// `heuristic`, one per node, and looking `lookahead` moves ahead (at least 1).
+    pub fn new(graph: &'a Graph, target: NodeId, heuristic: Vec<Weight>, lookahead: usize) -> Self {
+        assert_eq!(heuristic.len(), graph.node_count());
+        Self {
+            graph,
+            target,
+            lookahead: lookahead.max(1),
+            heuristic,
+        }
+    }
+
+    /// What the agent has learned about each node's distance to the target.
+    pub fn heuristic(&self) -> &[Weight] {
+        &self.heuristic
+    }
+
+    /// Walk from `start` until reaching the target or making `max_moves` moves, keeping the
+    /// heuristic values learned for later trials.
+    pub fn trial(&mut self, start: NodeId, max_moves: usize) -> Trial {
+        let mut nodes = vec![start];
+        let mut distance = vec![0];
+        let mut updates = 0;
+        let mut node = start;
+        while node != self.target && nodes.len() <= max_moves {
+            let (value, (next, cost)) = match self.look_ahead(node) {
+                Some(best) => best,
+                None => break,
+            };
+            if value > self.heuristic[node.index()] {
+                self.heuristic[node.index()] = value;
+                updates += 1;
+            }
+            node = next;
+            nodes.push(next);
+            distance.push(distance[distance.len() - 1] + cost);
+        }
+        let moves = nodes.len() - 1;
+        let path = (node == self.target).then(|| Path {
+            cost: distance[distance.len() - 1],
+            nodes,
+            distance,
+        });
+        Trial {
+            path,
+            moves,
+            updates,
+        }
+    }
+
+    /// The smallest value of a first move out of `node`, and that move, or `None` if
+    /// `node` has no way out.
+    ///
+    /// A move's value is the smallest cost plus heuristic value over the walks starting
+    /// with it, but no less than its cost plus the value of where it leads, which may know
+    /// more than the walks beyond it after being raised on an earlier visit. Without that,
+    /// a detour whose walks end somewhere cheap can be taken forever without learning.
+    fn look_ahead(&self, node: NodeId) -> Option<(Weight, (NodeId, Weight))> {
+        // The cheapest walk of exactly `depth` edges to each node, by its first move.
+        let mut layer: HashMap<(NodeId, (NodeId, Weight)), Weight> = HashMap::new();
+        for edge in self.graph.edges_of(node) {
+            layer.insert((edge.node, (edge.node, edge.cost)), edge.cost);
+        }
+        let mut ends: HashMap<(NodeId, Weight), Weight> = HashMap::new();
+        for depth in 1..=self.lookahead {
+            let mut next: HashMap<(NodeId, (NodeId, Weight)), Weight> = HashMap::new();
+            for (&(end, first), &cost) in &layer {
+                if end == self.target || depth == self.lookahead {
+                    let value = cost + self.heuristic[end.index()];
+                    ends.entry(first)
+                        .and_modify(|best| *best = (*best).min(value))
+                        .or_insert(value);
+                    continue;
+                }
+                for edge in self.graph.edges_of(end) {
+                    let walk = cost + edge.cost;
+                    next.entry((edge.node, first))
+                        .and_modify(|best| *best = (*best).min(walk))
+                        .or_insert(walk);
+                }
+            }
+            layer = next;
+        }
+        ends.into_iter()
+            .map(|((next, cost), value)| {
+                let value = value.max(cost + self.heuristic[next.index()]);
+                (value, (next, cost))
+            })
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn repeated_trials_stay_admissible_and_converge_to_shortest_routes() {
+        let mut rng = Rng::new(51);
+        for _ in 0..100 {
+            // A random tree plus extra edges, so the target is reachable from everywhere.
+            let n = 2 + rng.below(10);
+            let mut graph = Graph::new();
+            let nodes: Vec<NodeId> = (0..n)
+                .map(|i| graph.get_or_insert_node(&i.to_string()))
+                .collect();
+            for i in 1..n {
+                let j = rng.below(i);
+                graph.add_bidirectional_edge(nodes[i], nodes[j], 1 + rng.below(9) as Weight);
+            }
+            for _ in 0..rng.below(2 * n) {
+                let (u, v) = (rng.below(n), rng.below(n));
+                graph.add_bidirectional_edge(nodes[u], nodes[v], 1 + rng.below(9) as Weight);
+            }
+            let (start, target) = (nodes[rng.below(n)], nodes[rng.below(n)]);
+            let tree = crate::shortest_path_tree(&graph, target);
+            let lookahead = 1 + rng.below(3);
+            let mut agent = RealTimeAgent::new(&graph, target, vec![0; n], lookahead);
+
+            let mut converged = false;
+            for _ in 0..1000 {
+                let trial = agent.trial(start, 10_000);
+                let path = trial.path.unwrap();
+                assert_eq!(path.nodes.len(), trial.moves + 1);
+                assert_eq!((path.nodes[0], path.nodes[trial.moves]), (start, target));
+                for (i, pair) in path.nodes.windows(2).enumerate() {
+                    let cost = path.distance[i + 1] - path.distance[i];
+                    assert!(graph
+                        .edges_of(pair[0])
+                        .iter()
+                        .any(|edge| edge.node == pair[1] && edge.cost == cost));
+                }
+                for node in graph.node_ids() {
+                    assert!(agent.heuristic()[node.index()] <= tree.distance(node).unwrap());
+                }
+                if trial.updates == 0 {
+                    assert_eq!(Some(path.cost), tree.distance(start));
+                    converged = true;
+                    break;
+                }
+            }
+            assert!(converged);
+        }
+    }
+
+    #[test]
+    fn gives_up_when_the_target_cannot_be_reached() {
+        // From a, the agent walks into the dead end at c.
+        let graph: Graph = "a b 1 ->\nb c 1 ->\nd a 1 ->".parse().unwrap();
+        let node = |name| graph.get_node(name).unwrap();
+        let mut agent = RealTimeAgent::new(&graph, node("d"), vec![0; 4], 1);
+        let trial = agent.trial(node("a"), 100);
+        assert!(trial.path.is_none());
+        assert_eq!(trial.moves, 2);
+
+        // Between a and b, it goes back and forth until it runs out of moves, raising its
+        // estimates every time.
+        let graph: Graph = "a b 1\nc d 1".parse().unwrap();
+        let node = |name| graph.get_node(name).unwrap();
+        let mut agent = RealTimeAgent::new(&graph, node("d"), vec![0; 4], 1);
+        let trial = agent.trial(node("a"), 10);
+        assert!(trial.path.is_none());
+        assert_eq!((trial.moves, trial.updates), (10, 10));
+        assert_eq!(agent.heuristic(), [9, 10, 0, 0]);
+
+        let trial = agent.trial(node("d"), 10);
+        assert_eq!(trial.path.unwrap().cost, 0);
+        assert_eq!(trial.moves, 0);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::graph::{simple_adjacency, Graph, NodeId};
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+/// A node numbering that places nearby nodes at nearby indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reordering {
+    /// Breadth-first order from the lowest numbered node of each component.
+    Bfs,
+    /// Reverse Cuthill–McKee, which minimizes the bandwidth of the adjacency matrix.
+    Rcm,
+}
+
+impl FromStr for Reordering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bfs" => Ok(Reordering::Bfs),
+            "rcm" => Ok(Reordering::Rcm),
+            _ => Err(format!(
+                "unknown reordering '{}', expected 'bfs' or 'rcm'",
+                s
+            )),
+        }
+    }
+}
+
+/// The nodes of `graph` in the given order, ignoring edge direction.
+pub fn node_order(graph: &Graph, reordering: Reordering) -> Vec<NodeId> {
+    let mut adjacency = simple_adjacency(graph);
+    let degree: Vec<usize> = adjacency.iter().map(|list| list.len()).collect();
+    let mut roots: Vec<NodeId> = graph.node_ids().collect();
+    if reordering == Reordering::Rcm {
+        // Cuthill–McKee starts each component from a low degree node and visits
+        // neighbors by increasing degree.
+        roots.sort_by_key(|node| degree[node.index()]);
+        for list in adjacency.iter_mut() {
+            list.sort_by_key(|node| degree[node.index()]);
+        }
+    }
+
+    let mut visited = vec![false; graph.node_count()];
+    let mut order = Vec::with_capacity(graph.node_count());
+    let mut queue = VecDeque::new();
+    for root in roots {
+        if visited[root.index()] {
+            continue;
+        }
+        visited[root.index()] = true;
+        queue.push_back(root);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for &next in adjacency[node.index()].iter() {
+                if !visited[next.index()] {
+                    visited[next.index()] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    if reordering == Reordering::Rcm {
+        order.reverse();
+    }
+    order
+}
+
+/// Renumber the nodes of `graph` in the given order, keeping names and edge ids.
+pub fn reorder(graph: &Graph, reordering: Reordering) -> Graph {
+    graph
+        .permuted(&node_order(graph, reordering))
+        .expect("node order is a permutation")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::Csr;
+    use crate::graph::Weight;
+    use crate::path::shortest_path_tree;
+    use crate::rng::Rng;
+
+    #[test]
+    fn renumbering_preserves_distances() {
+        let mut rng = Rng::new(10);
+        for _ in 0..30 {
+            let n = 1 + rng.below(15);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                let cost = rng.below(10) as Weight;
+                if rng.chance(0.3) {
+                    graph.add_edge(a, b, cost);
+                } else {
+                    graph.add_bidirectional_edge(a, b, cost);
+                }
+            }
+            let mut reversed: Vec<NodeId> = graph.node_ids().collect();
+            reversed.reverse();
+            for order in [
+                reversed,
+                node_order(&graph, Reordering::Bfs),
+                node_order(&graph, Reordering::Rcm),
+            ] {
+                let permuted = graph.permuted(&order).unwrap();
+                let csr = Csr::from_graph(&permuted);
+                for (i, &start) in order.iter().enumerate() {
+                    let before = shortest_path_tree(&graph, start);
+                    let after = csr.shortest_path_tree(NodeId::new(i));
+                    for (j, &end) in order.iter().enumerate() {
+                        assert_eq!(after.distance(NodeId::new(j)), before.distance(end));
+                        let name = permuted.get_node_name(NodeId::new(j));
+                        assert_eq!(name, graph.get_node_name(end));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rcm_narrows_a_scrambled_path() {
+        // A path whose nodes were numbered out of order.
+        let mut graph = Graph::new();
+        let ids: Vec<NodeId> = (0..8)
+            .map(|i| graph.get_or_insert_node(&i.to_string()))
+            .collect();
+        for pair in [0, 5, 2, 7, 1, 4, 6, 3].windows(2) {
+            graph.add_bidirectional_edge(ids[pair[0]], ids[pair[1]], 1);
+        }
+        let bandwidth = |graph: &Graph| {
+            graph
+                .edges()
+                .map(|(_, src, dest, _)| src.index().abs_diff(dest.index()))
+                .max()
+                .unwrap()
+        };
+        let mut seen: Vec<NodeId> = node_order(&graph, Reordering::Rcm);
+        seen.sort();
+        assert!(seen.iter().copied().eq(graph.node_ids()));
+        assert_eq!(bandwidth(&graph), 6);
+        assert_eq!(bandwidth(&reorder(&graph, Reordering::Rcm)), 1);
+        assert_eq!(bandwidth(&reorder(&graph, Reordering::Bfs)), 1);
+    }
+}
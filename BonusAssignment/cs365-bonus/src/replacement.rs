@@ -0,0 +1,188 @@
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use crate::path::{dijkstra, find_shortest_path, Path};
+
+/// The best route when one edge of the shortest path fails.
+#[derive(Debug)]
+pub struct Replacement {
+    pub edge: EdgeId,
+    /// The cheapest path avoiding `edge`, or `None` if removing it disconnects the pair.
+    pub path: Option<Path>,
+}
+
+/// The edges a path uses, taking the cheapest edge between each consecutive pair of nodes
+/// as the search does.
+pub fn path_edges(graph: &Graph, path: &Path) -> Vec<EdgeId> {
+    path.nodes
+        .windows(2)
+        .filter_map(|pair| graph.find_edge(pair[0], pair[1]))
+        .collect()
+}
+
+/// The shortest path from `start` to `end`, and for each of its edges the shortest path
+/// that avoids it.
+///
+/// Runs one search per edge of the path. Returns `None` if `end` is unreachable.
+pub fn replacement_paths(
+    graph: &Graph,
+    start: NodeId,
+    end: NodeId,
+) -> Option<(Path, Vec<Replacement>)> {
+    let path = find_shortest_path(graph, start, end)?;
+    let replacements = path_edges(graph, &path)
+        .into_iter()
+        .map(|edge| Replacement {
+            edge,
+            path: dijkstra(graph, start, Some(end), None, |_, e| e.id != edge).path_to(end),
+        })
+        .collect();
+    Some((path, replacements))
+}
+
+/// The best route when one intermediate node of the shortest path fails.
+#[derive(Debug)]
+pub struct NodeReplacement {
+    pub node: NodeId,
+    /// The cheapest path avoiding `node`, or `None` if removing it disconnects the pair.
+    pub path: Option<Path>,
+}
+
+/// For each intermediate node of the shortest path from `start` to `end`, the shortest
+/// path that avoids it. Returns `None` if `end` is unreachable.
+pub fn node_replacement_paths(
+    graph: &Graph,
+    start: NodeId,
+    end: NodeId,
+) -> Option<(Path, Vec<NodeReplacement>)> {
+    let path = find_shortest_path(graph, start, end)?;
+    let inner = &path.nodes[1..path.nodes.len().saturating_sub(1).max(1)];
+    let replacements = inner
+        .iter()
+        .map(|&node| NodeReplacement {
+            node,
+            path: dijkstra(graph, start, Some(end), None, |_, e| e.node != node).path_to(end),
+        })
+        .collect();
+    Some((path, replacements))
+}
+
+/// Disconnecting the pair counts as a larger increase than any detour.
+fn damage(path: &Option<Path>) -> (bool, Weight) {
+    match path {
+        Some(path) => (false, path.cost),
+        None => (true, 0),
+    }
+}
+
+/// The edge whose removal increases the cost from `start` to `end` the most.
+///
+/// Only edges of a shortest path can make it longer, so this picks the worst of their
+/// replacement paths. Returns `None` if there is no path or it has no edges.
+pub fn most_vital_edge(graph: &Graph, start: NodeId, end: NodeId) -> Option<Replacement> {
+    let (_, replacements) = replacement_paths(graph, start, end)?;
+    replacements
+        .into_iter()
+        .rev()
+        .max_by_key(|replacement| damage(&replacement.path))
+}
+
+/// The intermediate node whose removal increases the cost from `start` to `end` the most.
+///
+/// Returns `None` if there is no path or it has no intermediate nodes.
+pub fn most_vital_node(graph: &Graph, start: NodeId, end: NodeId) -> Option<NodeReplacement> {
+    let (_, replacements) = node_replacement_paths(graph, start, end)?;
+    replacements
+        .into_iter()
+        .rev()
+        .max_by_key(|replacement| damage(&replacement.path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_graph(rng: &mut Rng) -> Graph {
+        let n = 2 + rng.below(10);
+        let mut graph = Graph::new();
+        for i in 0..n {
+            graph.get_or_insert_node(&i.to_string());
+        }
+        for _ in 0..rng.below(4 * n) {
+            let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+            let cost = rng.below(10) as Weight;
+            if rng.chance(0.3) {
+                graph.add_edge(a, b, cost);
+            } else {
+                graph.add_bidirectional_edge(a, b, cost);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn replacements_match_searching_a_copy() {
+        let mut rng = Rng::new(22);
+        for _ in 0..40 {
+            let graph = random_graph(&mut rng);
+            let n = graph.node_count();
+            let (start, end) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+            let (path, replacements) = match replacement_paths(&graph, start, end) {
+                Some(found) => found,
+                None => {
+                    assert!(find_shortest_path(&graph, start, end).is_none());
+                    continue;
+                }
+            };
+            assert_eq!(path_edges(&graph, &path).len(), path.nodes.len() - 1);
+            for replacement in replacements.iter() {
+                let copy = graph.edge_subgraph(|id| id != replacement.edge);
+                let expected = find_shortest_path(&copy, start, end).map(|p| p.cost);
+                assert_eq!(replacement.path.as_ref().map(|p| p.cost), expected);
+            }
+
+            let (_, replacements) = node_replacement_paths(&graph, start, end).unwrap();
+            assert_eq!(replacements.len(), path.nodes.len().saturating_sub(2));
+            for replacement in replacements.iter() {
+                let node = replacement.node;
+                let copy = graph.edge_subgraph(|id| {
+                    let (src, dest) = graph.edge_endpoints(id).unwrap();
+                    src != node && dest != node
+                });
+                let expected = find_shortest_path(&copy, start, end).map(|p| p.cost);
+                assert_eq!(replacement.path.as_ref().map(|p| p.cost), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn vital_elements_do_the_most_damage() {
+        let mut rng = Rng::new(23);
+        for _ in 0..40 {
+            let graph = random_graph(&mut rng);
+            let n = graph.node_count();
+            let (start, end) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+            let edges = replacement_paths(&graph, start, end).map(|(_, r)| r);
+            let worst = edges.and_then(|r| r.iter().map(|r| damage(&r.path)).max());
+            let vital = most_vital_edge(&graph, start, end);
+            assert_eq!(vital.map(|v| damage(&v.path)), worst);
+
+            let nodes = node_replacement_paths(&graph, start, end).map(|(_, r)| r);
+            let worst = nodes.and_then(|r| r.iter().map(|r| damage(&r.path)).max());
+            let vital = most_vital_node(&graph, start, end);
+            assert_eq!(vital.map(|v| damage(&v.path)), worst);
+        }
+    }
+
+    #[test]
+    fn a_bridge_is_more_vital_than_a_detour() {
+        // Losing a-b costs a detour through c, but losing b-z disconnects z.
+        let graph: Graph = "a b 1\na c 2\nc b 2\nb z 1".parse().unwrap();
+        let [a, b, z] = ["a", "b", "z"].map(|name| graph.get_node(name).unwrap());
+        let vital = most_vital_edge(&graph, a, z).unwrap();
+        assert_eq!(vital.edge, graph.find_edge(b, z).unwrap());
+        assert!(vital.path.is_none());
+        let vital = most_vital_node(&graph, a, z).unwrap();
+        assert_eq!(vital.node, b);
+        assert!(most_vital_node(&graph, a, b).is_none());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::Path;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A path together with the total resource it consumes.
+#[derive(Debug)]
+pub struct ResourcePath {
+    pub path: Path,
+    pub resource: Weight,
+}
+
+/// A partial path in the label-setting search.
+struct Label {
+    node: NodeId,
+    cost: Weight,
+    resource: Weight,
+    previous: Option<usize>,
+}
+
+/// The cheapest path from `start` to `end` whose total resource, with `resource[id]`
+/// consumed by edge `id`, stays within `budget`.
+///
+/// A label-setting search: partial paths are expanded in order of cost, and a label is
+/// dropped when an earlier, and so no more expensive, label at the same node used no more
+/// resource. The first label to reach `end` is optimal. Unlike plain Dijkstra several
+/// labels may be kept per node, so the worst case is exponential, but dominance keeps it
+/// small in practice.
+pub fn resource_constrained_path(
+    graph: &Graph,
+    resource: &[Weight],
+    start: NodeId,
+    end: NodeId,
+    budget: Weight,
+) -> Option<ResourcePath> {
+    label_search(graph, resource, start, end, budget, |_| false)
+}
+
+/// The cheapest route from `start` to `end` for a vehicle with the given `range`, which
+/// consumes `consumption[id]` on edge `id` and is fully recharged at nodes marked in
+/// `stations`. The vehicle starts fully charged.
+///
+/// This is the resource-constrained search where the budget applies to the resource used
+/// since the last station rather than in total.
+pub fn range_constrained_path(
+    graph: &Graph,
+    consumption: &[Weight],
+    stations: &[bool],
+    start: NodeId,
+    end: NodeId,
+    range: Weight,
+) -> Option<ResourcePath> {
+    label_search(graph, consumption, start, end, range, |node| {
+        stations.get(node.index()).copied().unwrap_or(false)
+    })
+}
+
+fn label_search(
+    graph: &Graph,
+    resource: &[Weight],
+    start: NodeId,
+    end: NodeId,
+    budget: Weight,
+    recharge: impl Fn(NodeId) -> bool,
+) -> Option<ResourcePath> {
+    let mut labels = vec![Label {
+        node: start,
+        cost: 0,
+        resource: 0,
+        previous: None,
+    }];
+    // The least resource since recharging of any expanded label at each node.
+    let mut best: Vec<Option<Weight>> = vec![None; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((0, 0, 0)));
+
+    while let Some(Reverse((cost, used, index))) = heap.pop() {
+        let node = labels[index].node;
+        if best[node.index()].is_some_and(|best| best <= used) {
+            continue;
+        }
+        best[node.index()] = Some(used);
+        if node == end {
+            return Some(reconstruct(&labels, index));
+        }
+
+        for edge in graph.edges_of(node) {
+            let amount = resource.get(edge.id.index()).copied().unwrap_or(0);
+            let mut next_used = used + amount;
+            if next_used > budget {
+                continue;
+            }
+            if recharge(edge.node) {
+                next_used = 0;
+            }
+            if best[edge.node.index()].is_some_and(|best| best <= next_used) {
+                continue;
+            }
+            let next_cost = cost + edge.cost;
+            labels.push(Label {
+                node: edge.node,
+                cost: next_cost,
+                resource: labels[index].resource + amount,
+                previous: Some(index),
+            });
+            heap.push(Reverse((next_cost, next_used, labels.len() - 1)));
+        }
+    }
+    None
+}
+
+fn reconstruct(labels: &[Label], last: usize) -> ResourcePath {
+    let mut nodes = Vec::new();
+    let mut distance = Vec::new();
+    let mut index = Some(last);
+    while let Some(i) = index {
+        nodes.push(labels[i].node);
+        distance.push(labels[i].cost);
+        index = labels[i].previous;
+    }
+    nodes.reverse();
+    distance.reverse();
+    ResourcePath {
+        path: Path {
+            nodes,
+            distance,
+            cost: labels[last].cost,
+        },
+        resource: labels[last].resource,
+    }
+}
+
+/// Per-edge resource consumption from `u v amount` entries, applied to every edge
+/// between `u` and `v`. Unlisted edges consume nothing.
+pub fn edge_resources(graph: &Graph, entries: &[(NodeId, NodeId, Weight)]) -> Vec<Weight> {
+    let mut resource = vec![0; graph.edge_count()];
+    for &(u, v, amount) in entries {
+        for edge in graph.edges_of(u).iter().filter(|edge| edge.node == v) {
+            resource[edge.id.index()] = amount;
+        }
+    }
+    resource
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// The cheapest cost to reach `end`, by relaxing `(node, resource used)` states until
+    /// nothing changes.
+    fn brute_force(
+        graph: &Graph,
+        resource: &[Weight],
+        stations: &[bool],
+        start: NodeId,
+        end: NodeId,
+        budget: Weight,
+    ) -> Option<Weight> {
+        let b = budget as usize;
+        let mut cost: Vec<Vec<Option<Weight>>> = vec![vec![None; b + 1]; graph.node_count()];
+        cost[start.index()][0] = Some(0);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for node in graph.node_ids() {
+                for used in 0..=b {
+                    let here = match cost[node.index()][used] {
+                        Some(here) => here,
+                        None => continue,
+                    };
+                    for edge in graph.edges_of(node) {
+                        let mut next = used + resource[edge.id.index()] as usize;
+                        if next > b {
+                            continue;
+                        }
+                        if stations[edge.node.index()] {
+                            next = 0;
+                        }
+                        let slot = &mut cost[edge.node.index()][next];
+                        if slot.is_none_or(|c| here + edge.cost < c) {
+                            *slot = Some(here + edge.cost);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        cost[end.index()].iter().flatten().copied().min()
+    }
+
+    /// Check that `found` follows edges whose costs add up along the path.
+    fn check_path(graph: &Graph, found: &ResourcePath) {
+        let path = &found.path;
+        assert_eq!(path.distance.last(), Some(&path.cost));
+        for (i, pair) in path.nodes.windows(2).enumerate() {
+            let step = path.distance[i + 1] - path.distance[i];
+            assert!(graph
+                .edges_of(pair[0])
+                .iter()
+                .any(|e| e.node == pair[1] && e.cost == step));
+        }
+    }
+
+    #[test]
+    fn searches_match_the_state_graph() {
+        let mut rng = Rng::new(25);
+        for _ in 0..60 {
+            let n = 1 + rng.below(8);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                graph.add_edge(a, b, rng.below(10) as Weight);
+            }
+            let resource: Vec<Weight> = (0..graph.edge_count())
+                .map(|_| rng.below(5) as Weight)
+                .collect();
+            let budget = rng.below(10) as Weight;
+            let (start, end) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+
+            let none = vec![false; n];
+            let found = resource_constrained_path(&graph, &resource, start, end, budget);
+            let expected = brute_force(&graph, &resource, &none, start, end, budget);
+            assert_eq!(found.as_ref().map(|f| f.path.cost), expected);
+            if let Some(found) = found {
+                assert!(found.resource <= budget);
+                assert_eq!(found.path.nodes.first(), Some(&start));
+                assert_eq!(found.path.nodes.last(), Some(&end));
+                check_path(&graph, &found);
+            }
+
+            let stations: Vec<bool> = (0..n).map(|_| rng.chance(0.3)).collect();
+            let found = range_constrained_path(&graph, &resource, &stations, start, end, budget);
+            let expected = brute_force(&graph, &resource, &stations, start, end, budget);
+            assert_eq!(found.map(|f| f.path.cost), expected);
+        }
+    }
+
+    #[test]
+    fn cheap_paths_over_budget_are_skipped() {
+        let graph: Graph = "a b 1 ->\nb z 1 ->\na z 5 ->".parse().unwrap();
+        let (a, b, z) = (NodeId::new(0), NodeId::new(1), NodeId::new(2));
+        let resource = edge_resources(&graph, &[(a, b, 3), (b, z, 3), (a, z, 4)]);
+        let found = resource_constrained_path(&graph, &resource, a, z, 6).unwrap();
+        assert_eq!((found.path.cost, found.resource), (2, 6));
+        let found = resource_constrained_path(&graph, &resource, a, z, 5).unwrap();
+        assert_eq!((found.path.cost, found.resource), (5, 4));
+        assert!(resource_constrained_path(&graph, &resource, a, z, 3).is_none());
+        // Recharging at b makes the cheap route possible with a range of 3.
+        let found = range_constrained_path(&graph, &resource, &[false, true, false], a, z, 3);
+        assert_eq!(found.unwrap().path.nodes, [a, b, z]);
+    }
+}
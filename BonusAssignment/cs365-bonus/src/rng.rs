@@ -0,0 +1,98 @@
+/// A small seedable pseudo-random number generator (SplitMix64).
+///
+/// Randomized algorithms take one of these so runs are reproducible from a seed.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed value in `0..n`. `n` must be non-zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        // Rejection sampling avoids the bias of a plain modulo.
+        let n = n as u64;
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return (x % n) as usize;
+            }
+        }
+    }
+
+    /// A uniformly distributed value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    /// Put `items` in a uniformly random order (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_reproduce_their_sequence() {
+        let (mut a, mut b) = (Rng::new(38), Rng::new(38));
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn values_stay_in_range_and_spread_out() {
+        let mut rng = Rng::new(39);
+        let mut counts = [0; 6];
+        for _ in 0..6000 {
+            counts[rng.below(6)] += 1;
+            let x = rng.next_f64();
+            assert!((0.0..1.0).contains(&x));
+        }
+        assert!(counts.iter().all(|&c| (800..1200).contains(&c)));
+        assert_eq!(rng.below(1), 0);
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+    }
+
+    #[test]
+    fn shuffles_are_uniform_permutations() {
+        let mut rng = Rng::new(40);
+        let mut counts = [[0; 4]; 4];
+        for _ in 0..4000 {
+            let mut items = [0, 1, 2, 3];
+            rng.shuffle(&mut items);
+            let mut sorted = items;
+            sorted.sort_unstable();
+            assert_eq!(sorted, [0, 1, 2, 3]);
+            for (position, &item) in items.iter().enumerate() {
+                counts[item][position] += 1;
+            }
+        }
+        assert!(counts.iter().flatten().all(|&c| (850..1150).contains(&c)));
+        rng.shuffle::<u8>(&mut []);
+    }
+}
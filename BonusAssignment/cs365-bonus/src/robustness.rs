@@ -0,0 +1,344 @@
+use crate::disjoint_set::DisjointSet;
+use crate::graph::{simple_adjacency, EdgeId, Graph, NodeId};
+use crate::rng::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+
+/// Which nodes or edges fail first in a robustness experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// Failures at random.
+    Random,
+    /// An attack on the nodes with the most neighbors.
+    Degree,
+    /// An attack on the nodes or edges that the most shortest paths go through.
+    Betweenness,
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Target::Random),
+            "degree" => Ok(Target::Degree),
+            "betweenness" => Ok(Target::Betweenness),
+            _ => Err(format!(
+                "unknown target '{}', expected 'random', 'degree' or 'betweenness'",
+                s
+            )),
+        }
+    }
+}
+
+/// How many shortest paths between other pairs of nodes go through each node and edge.
+#[derive(Debug, Clone)]
+pub struct Betweenness {
+    /// The score of each node, by node id.
+    pub nodes: Vec<f64>,
+    /// The score of each edge, by edge id. Parallel edges share their pair's score, and
+    /// self-loops score 0.
+    pub edges: Vec<f64>,
+}
+
+/// Betweenness centrality ignoring direction and cost, where every pair of nodes splits one
+/// unit evenly between its shortest paths, by Brandes' algorithm in O(n m) time.
+///
+/// ```
+/// use cs365_bonus::robustness::betweenness;
+///
+/// // Every path between two leaves of a star goes through its center.
+/// let graph: cs365_bonus::Graph = "hub a 1\nhub b 1\nhub c 1\nhub d 1".parse().unwrap();
+/// let scores = betweenness(&graph);
+/// assert_eq!(scores.nodes[graph.get_node("hub").unwrap().index()], 6.0);
+/// assert_eq!(scores.nodes[graph.get_node("a").unwrap().index()], 0.0);
+/// // An edge to a leaf carries its paths to the 4 other nodes.
+/// assert_eq!(scores.edges, [4.0; 4]);
+/// ```
+pub fn betweenness(graph: &Graph) -> Betweenness {
+    let adjacency = simple_adjacency(graph);
+    let n = graph.node_count();
+    let key = |a: NodeId, b: NodeId| (a.min(b), a.max(b));
+    let mut nodes = vec![0.0; n];
+    let mut pairs: HashMap<(NodeId, NodeId), f64> = HashMap::new();
+
+    let mut distance = vec![usize::MAX; n];
+    let mut paths = vec![0.0; n];
+    let mut dependency = vec![0.0; n];
+    let mut order: Vec<NodeId> = Vec::with_capacity(n);
+    let mut queue = VecDeque::new();
+    for source in graph.node_ids() {
+        for &node in &order {
+            distance[node.index()] = usize::MAX;
+            paths[node.index()] = 0.0;
+            dependency[node.index()] = 0.0;
+        }
+        order.clear();
+        distance[source.index()] = 0;
+        paths[source.index()] = 1.0;
+        queue.push_back(source);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for &next in &adjacency[node.index()] {
+                if distance[next.index()] == usize::MAX {
+                    distance[next.index()] = distance[node.index()] + 1;
+                    queue.push_back(next);
+                }
+                if distance[next.index()] == distance[node.index()] + 1 {
+                    paths[next.index()] += paths[node.index()];
+                }
+            }
+        }
+        for &node in order.iter().rev() {
+            for &next in &adjacency[node.index()] {
+                if distance[next.index()] == distance[node.index()] + 1 {
+                    let share = paths[node.index()] / paths[next.index()]
+                        * (1.0 + dependency[next.index()]);
+                    dependency[node.index()] += share;
+                    *pairs.entry(key(node, next)).or_insert(0.0) += share;
+                }
+            }
+            if node != source {
+                nodes[node.index()] += dependency[node.index()];
+            }
+        }
+    }
+
+    // Each pair was counted from both ends.
+    for score in nodes.iter_mut() {
+        *score /= 2.0;
+    }
+    let edges = graph
+        .edges()
+        .map(|(_, src, dest, _)| pairs.get(&key(src, dest)).map_or(0.0, |score| score / 2.0))
+        .collect();
+    Betweenness { nodes, edges }
+}
+
+/// Every node in the order `target` removes them: shuffled with `rng`, or by score from
+/// highest to lowest, ties going to the lower id. Scores are taken once, on the whole
+/// graph.
+pub fn node_order(graph: &Graph, target: Target, rng: &mut Rng) -> Vec<NodeId> {
+    let scores: Vec<f64> = match target {
+        Target::Random => {
+            let mut nodes: Vec<NodeId> = graph.node_ids().collect();
+            rng.shuffle(&mut nodes);
+            return nodes;
+        }
+        Target::Degree => simple_adjacency(graph)
+            .iter()
+            .map(|list| list.len() as f64)
+            .collect(),
+        Target::Betweenness => betweenness(graph).nodes,
+    };
+    let mut order: Vec<NodeId> = graph.node_ids().collect();
+    order.sort_by(|a, b| {
+        scores[b.index()]
+            .total_cmp(&scores[a.index()])
+            .then(a.cmp(b))
+    });
+    order
+}
+
+/// Every edge in the order `target` removes them, as for [`node_order`]. Edges have no
+/// degree, so that target is an error.
+pub fn edge_order(graph: &Graph, target: Target, rng: &mut Rng) -> Result<Vec<EdgeId>, String> {
+    let scores = match target {
+        Target::Random => {
+            let mut edges: Vec<EdgeId> = graph.edges().map(|edge| edge.0).collect();
+            rng.shuffle(&mut edges);
+            return Ok(edges);
+        }
+        Target::Degree => return Err("edges can't be targeted by degree".into()),
+        Target::Betweenness => betweenness(graph).edges,
+    };
+    let mut order: Vec<EdgeId> = graph.edges().map(|edge| edge.0).collect();
+    order.sort_by(|a, b| {
+        scores[b.index()]
+            .total_cmp(&scores[a.index()])
+            .then(a.cmp(b))
+    });
+    Ok(order)
+}
+
+/// The state of the graph after some nodes have been removed, in [`attack_sweep`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepStep {
+    /// How many nodes have been removed.
+    pub removed: usize,
+    /// The nodes in the largest connected component left, ignoring direction.
+    pub giant: usize,
+    /// The mean number of hops between pairs of nodes left that are still connected, if
+    /// measured at this step and there are any.
+    pub average_path: Option<f64>,
+}
+
+/// Remove the nodes of `order` one at a time, recording the graph before the first and
+/// after each, so `order.len() + 1` steps in all.
+///
+/// Component sizes come cheaply from adding the nodes back in reverse with a
+/// [`DisjointSet`], but each average path length takes a breadth-first search from every
+/// node left, so it is only measured every `path_every` steps, or never if that is 0.
+///
+/// ```
+/// use cs365_bonus::robustness::attack_sweep;
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb c 1\nc d 1".parse().unwrap();
+/// let b = graph.get_node("b").unwrap();
+/// let steps = attack_sweep(&graph, &[b], 1);
+/// assert_eq!((steps[0].giant, steps[0].average_path), (4, Some(10.0 / 6.0)));
+/// assert_eq!((steps[1].giant, steps[1].average_path), (2, Some(1.0)));
+/// ```
+pub fn attack_sweep(graph: &Graph, order: &[NodeId], path_every: usize) -> Vec<SweepStep> {
+    let adjacency = simple_adjacency(graph);
+    let n = graph.node_count();
+    let mut present = vec![true; n];
+    for node in order {
+        present[node.index()] = false;
+    }
+
+    // Giant component sizes, from the last step back to the first.
+    let mut sets = DisjointSet::new(n);
+    let mut largest = 0;
+    let mut add = |node: NodeId, present: &mut [bool], largest: &mut usize| {
+        present[node.index()] = true;
+        *largest = (*largest).max(1);
+        for next in &adjacency[node.index()] {
+            if present[next.index()] {
+                sets.union(node.index(), next.index());
+                *largest = (*largest).max(sets.set_size(node.index()));
+            }
+        }
+    };
+    let remaining: Vec<NodeId> = graph
+        .node_ids()
+        .filter(|node| present[node.index()])
+        .collect();
+    for node in remaining {
+        add(node, &mut present, &mut largest);
+    }
+    let mut giant = vec![largest];
+    for &node in order.iter().rev() {
+        add(node, &mut present, &mut largest);
+        giant.push(largest);
+    }
+    giant.reverse();
+
+    let mut distance = vec![usize::MAX; n];
+    let mut queue = VecDeque::new();
+    let mut steps = Vec::with_capacity(order.len() + 1);
+    for (removed, giant) in giant.into_iter().enumerate() {
+        if removed > 0 {
+            present[order[removed - 1].index()] = false;
+        }
+        let average_path = if path_every > 0 && removed % path_every == 0 {
+            let (mut total, mut pairs) = (0, 0);
+            for source in graph.node_ids().filter(|node| present[node.index()]) {
+                let mut reached = vec![source];
+                distance[source.index()] = 0;
+                queue.push_back(source);
+                while let Some(node) = queue.pop_front() {
+                    for &next in &adjacency[node.index()] {
+                        if present[next.index()] && distance[next.index()] == usize::MAX {
+                            distance[next.index()] = distance[node.index()] + 1;
+                            total += distance[next.index()];
+                            pairs += 1;
+                            reached.push(next);
+                            queue.push_back(next);
+                        }
+                    }
+                }
+                for node in reached {
+                    distance[node.index()] = usize::MAX;
+                }
+            }
+            Some(total as f64 / pairs as f64).filter(|_| pairs > 0)
+        } else {
+            None
+        };
+        steps.push(SweepStep {
+            removed,
+            giant,
+            average_path,
+        });
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hop counts between every pair of nodes.
+    fn hops(adjacency: &[Vec<NodeId>]) -> Vec<Vec<usize>> {
+        (0..adjacency.len())
+            .map(|source| {
+                let mut distance = vec![usize::MAX; adjacency.len()];
+                distance[source] = 0;
+                let mut queue = VecDeque::from([source]);
+                while let Some(node) = queue.pop_front() {
+                    for next in &adjacency[node] {
+                        if distance[next.index()] == usize::MAX {
+                            distance[next.index()] = distance[node] + 1;
+                            queue.push_back(next.index());
+                        }
+                    }
+                }
+                distance
+            })
+            .collect()
+    }
+
+    #[test]
+    fn node_betweenness_matches_path_counts() {
+        let mut rng = Rng::new(6);
+        for _ in 0..50 {
+            let n = 1 + rng.below(10);
+            let mut graph = Graph::new();
+            let nodes: Vec<NodeId> = (0..n)
+                .map(|i| graph.get_or_insert_node(&i.to_string()))
+                .collect();
+            for i in 0..n {
+                for j in i + 1..n {
+                    if rng.chance(0.35) {
+                        graph.add_bidirectional_edge(nodes[i], nodes[j], 1);
+                    }
+                }
+            }
+            let adjacency = simple_adjacency(&graph);
+            let d = hops(&adjacency);
+            // Shortest path counts by dynamic programming over distance.
+            let mut count = vec![vec![0.0; n]; n];
+            for s in 0..n {
+                let mut by_distance: Vec<usize> =
+                    (0..n).filter(|&v| d[s][v] != usize::MAX).collect();
+                by_distance.sort_by_key(|&v| d[s][v]);
+                for v in by_distance {
+                    count[s][v] = if v == s {
+                        1.0
+                    } else {
+                        adjacency[v]
+                            .iter()
+                            .filter(|u| d[s][u.index()] + 1 == d[s][v])
+                            .map(|u| count[s][u.index()])
+                            .sum()
+                    };
+                }
+            }
+            let scores = betweenness(&graph);
+            for v in 0..n {
+                let mut expected = 0.0;
+                for s in 0..n {
+                    for t in s + 1..n {
+                        let through =
+                            s != v && t != v && d[s][v] != usize::MAX && d[v][t] != usize::MAX;
+                        if through && d[s][v] + d[v][t] == d[s][t] {
+                            expected += count[s][v] * count[v][t] / count[s][t];
+                        }
+                    }
+                }
+                assert!((scores.nodes[v] - expected).abs() < 1e-9);
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::attributes::Attributes;
+use crate::csr::Csr;
+use crate::graph::{Edge, Graph, NodeId, Weight};
+use crate::path::{BudgetExceeded, Path, SearchContext, SearchVisitor};
+use crate::view::Reweighted;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// An immutable graph prepared for sharing between threads, made with [`Graph::freeze`].
+///
+/// Keeps the [`Graph`] for name lookups alongside a [`Csr`] copy of its adjacency for
+/// searching, and any [`Attributes`] of its nodes and edges.
+#[derive(Debug)]
+pub struct FrozenGraph {
+    graph: Graph,
+    csr: Csr,
+    attributes: Attributes,
+}
+
+impl FrozenGraph {
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn csr(&self) -> &Csr {
+        &self.csr
+    }
+
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+}
+
+impl Graph {
+    /// Stop modifying this graph and share it, e.g. with a [`ConcurrentRouter`].
+    pub fn freeze(self) -> Arc<FrozenGraph> {
+        self.freeze_with(Attributes::new())
+    }
+
+    /// Like [`freeze`](Self::freeze), keeping `attributes` with the graph.
+    pub fn freeze_with(self, attributes: Attributes) -> Arc<FrozenGraph> {
+        let csr = Csr::from_graph(&self);
+        Arc::new(FrozenGraph {
+            graph: self,
+            csr,
+            attributes,
+        })
+    }
+}
+
+/// Answers shortest path queries on a [`FrozenGraph`] from any number of threads.
+///
+/// Each query borrows a [`SearchContext`] from a shared pool and returns it afterwards,
+/// so a thread only allocates the first time it runs a query concurrently with the
+/// others, and the lock is only held to take or return a context. Clones share the graph
+/// and the pool.
+///
+/// Every search is Dijkstra's, so costs must not be negative; callers that take graphs
+/// from users check with [`has_negative_edges`](crate::path::has_negative_edges) first.
+#[derive(Clone)]
+pub struct ConcurrentRouter {
+    graph: Arc<FrozenGraph>,
+    pool: Arc<Mutex<Vec<SearchContext>>>,
+    stats: Arc<PoolCounters>,
+}
+
+#[derive(Default)]
+struct PoolCounters {
+    reused: AtomicU64,
+    allocated: AtomicU64,
+}
+
+/// How often queries found a [`SearchContext`] waiting in a [`ConcurrentRouter`]'s pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    pub reused: u64,
+    pub allocated: u64,
+}
+
+impl ConcurrentRouter {
+    pub fn new(graph: Arc<FrozenGraph>) -> Self {
+        Self {
+            graph,
+            pool: Arc::new(Mutex::new(Vec::new())),
+            stats: Arc::default(),
+        }
+    }
+
+    pub fn graph(&self) -> &FrozenGraph {
+        &self.graph
+    }
+
+    /// The shortest path from `start` to `end`, if one exists.
+    pub fn route(&self, start: NodeId, end: NodeId) -> Option<Path> {
+        self.with_context(|context, csr| context.find_shortest_path(csr, start, end))
+    }
+
+    /// Like [`route`](Self::route), but gives up after expanding `budget` nodes.
+    pub fn route_within(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        budget: usize,
+    ) -> Result<Option<Path>, BudgetExceeded> {
+        self.with_context(|context, csr| context.find_shortest_path_within(csr, start, end, budget))
+    }
+
+    /// Like [`route_within`](Self::route_within), without a budget if it is `None`,
+    /// reporting the search's progress to `visitor`.
+    pub fn route_visited<V: SearchVisitor + ?Sized>(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        budget: Option<usize>,
+        visitor: &mut V,
+    ) -> Result<Option<Path>, BudgetExceeded> {
+        self.with_context(|context, csr| {
+            context.find_shortest_path_visited(csr, start, end, budget, visitor)
+        })
+    }
+
+    /// Like [`route_within`](Self::route_within), without a budget if it is `None`, with
+    /// each edge costing `weight(edge)` instead of its stored cost.
+    pub fn route_reweighted(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        budget: Option<usize>,
+        weight: impl Fn(&Edge) -> Weight,
+    ) -> Result<Option<Path>, BudgetExceeded> {
+        self.with_context(|context, csr| {
+            let view = Reweighted::new(csr, weight);
+            context.find_shortest_path_visited(&view, start, end, budget, &mut ())
+        })
+    }
+
+    /// Run `search` with a context from the pool, returning it afterwards.
+    fn with_context<T>(&self, search: impl FnOnce(&mut SearchContext, &Csr) -> T) -> T {
+        let taken = self.pool.lock().ok().and_then(|mut pool| pool.pop());
+        let counter = match taken {
+            Some(_) => &self.stats.reused,
+            None => &self.stats.allocated,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        let mut context = taken.unwrap_or_default();
+        let result = search(&mut context, &self.graph.csr);
+        if let Ok(mut pool) = self.pool.lock() {
+            pool.push(context);
+        }
+        result
+    }
+
+    pub fn pool_stats(&self) -> PoolStats {
+        PoolStats {
+            reused: self.stats.reused.load(Ordering::Relaxed),
+            allocated: self.stats.allocated.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// Shared across query threads, so these must stay free of interior `Rc`s and `Cell`s.
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+    let _ = assert_send_sync::<Graph>;
+    let _ = assert_send_sync::<FrozenGraph>;
+    let _ = assert_send_sync::<ConcurrentRouter>;
+};
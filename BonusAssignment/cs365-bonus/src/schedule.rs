@@ -0,0 +1,255 @@
+use crate::dag::topological_order;
+use crate::graph::{Graph, NodeId, Weight};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// A task with a duration that may only start once its dependencies have finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    pub name: String,
+    pub duration: Weight,
+    pub dependencies: Vec<String>,
+}
+
+/// Why a task list couldn't be scheduled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// A line wasn't `name duration [dependency...]`.
+    Parse {
+        line: usize,
+    },
+    DuplicateTask(String),
+    UnknownDependency {
+        task: String,
+        dependency: String,
+    },
+    /// The dependencies form a cycle, so no task on it can ever start.
+    Cycle,
+}
+
+impl std::fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ScheduleError::Parse { line } => write!(f, "invalid task on line {}", line),
+            ScheduleError::DuplicateTask(task) => write!(f, "task '{}' is defined twice", task),
+            ScheduleError::UnknownDependency { task, dependency } => write!(
+                f,
+                "task '{}' depends on unknown task '{}'",
+                task, dependency
+            ),
+            ScheduleError::Cycle => write!(f, "the task dependencies form a cycle"),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+/// Parse one task per line as `name duration [dependency...]`, skipping blank lines and
+/// `#` comments.
+pub fn parse_tasks(input: &str) -> Result<Vec<Task>, ScheduleError> {
+    let mut tasks = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let name = match words.next() {
+            Some(name) if !name.starts_with('#') => name,
+            _ => continue,
+        };
+        let duration = words
+            .next()
+            .and_then(|d| Weight::from_str(d).ok())
+            .ok_or(ScheduleError::Parse { line: i + 1 })?;
+        tasks.push(Task {
+            name: name.to_string(),
+            duration,
+            dependencies: words.map(String::from).collect(),
+        });
+    }
+    Ok(tasks)
+}
+
+/// Start times for one task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskTimes {
+    pub earliest_start: Weight,
+    pub latest_start: Weight,
+    /// How long the task can be delayed without delaying the project.
+    pub slack: Weight,
+}
+
+/// The result of the critical path method.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    /// Times for each task, in input order.
+    pub times: Vec<TaskTimes>,
+    /// The shortest possible duration of the whole project.
+    pub length: Weight,
+    /// Indices of a chain of zero-slack tasks spanning the project.
+    pub critical_path: Vec<usize>,
+}
+
+/// Schedule `tasks` with the critical path method.
+///
+/// Tasks become nodes of a DAG with an edge from each dependency, weighted by the
+/// dependency's duration, so earliest start times are longest path distances taken in
+/// topological order. Latest start times come from the same pass run backwards.
+pub fn schedule(tasks: &[Task]) -> Result<Schedule, ScheduleError> {
+    let mut graph = Graph::with_capacity(tasks.len(), 0);
+    let mut index = HashMap::new();
+    for task in tasks {
+        if index.insert(task.name.as_str(), index.len()).is_some() {
+            return Err(ScheduleError::DuplicateTask(task.name.clone()));
+        }
+        graph.get_or_insert_node(&task.name);
+    }
+    for (i, task) in tasks.iter().enumerate() {
+        for dependency in task.dependencies.iter() {
+            let &d =
+                index
+                    .get(dependency.as_str())
+                    .ok_or_else(|| ScheduleError::UnknownDependency {
+                        task: task.name.clone(),
+                        dependency: dependency.clone(),
+                    })?;
+            graph.add_edge(NodeId::new(d), NodeId::new(i), tasks[d].duration);
+        }
+    }
+    let order = topological_order(&graph).ok_or(ScheduleError::Cycle)?;
+
+    let mut earliest = vec![0; tasks.len()];
+    for &node in order.iter() {
+        for edge in graph.edges_of(node) {
+            let start = earliest[node.index()] + edge.cost;
+            earliest[edge.node.index()] = earliest[edge.node.index()].max(start);
+        }
+    }
+    let finish = |i: usize| earliest[i] + tasks[i].duration;
+    let length = (0..tasks.len()).map(finish).max().unwrap_or(0);
+
+    let mut latest: Vec<Weight> = tasks.iter().map(|task| length - task.duration).collect();
+    for &node in order.iter().rev() {
+        for edge in graph.edges_of(node) {
+            let start = latest[edge.node.index()] - edge.cost;
+            latest[node.index()] = latest[node.index()].min(start);
+        }
+    }
+
+    let times: Vec<TaskTimes> = (0..tasks.len())
+        .map(|i| TaskTimes {
+            earliest_start: earliest[i],
+            latest_start: latest[i],
+            slack: latest[i] - earliest[i],
+        })
+        .collect();
+
+    // Walk back from a critical task that ends the project through dependencies that
+    // finish exactly when it starts.
+    let mut critical_path = Vec::new();
+    let mut current = (0..tasks.len()).find(|&i| times[i].slack == 0 && finish(i) == length);
+    while let Some(i) = current {
+        critical_path.push(i);
+        current = tasks[i]
+            .dependencies
+            .iter()
+            .map(|d| index[d.as_str()])
+            .find(|&d| times[d].slack == 0 && finish(d) == earliest[i]);
+    }
+    critical_path.reverse();
+
+    Ok(Schedule {
+        times,
+        length,
+        critical_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn times_match_a_pass_in_input_order() {
+        let mut rng = Rng::new(19);
+        for _ in 0..40 {
+            // Tasks only depend on earlier ones, so input order is already topological.
+            let n = 1 + rng.below(12);
+            let tasks: Vec<Task> = (0..n)
+                .map(|i| Task {
+                    name: format!("t{}", i),
+                    duration: rng.below(10) as Weight,
+                    dependencies: (0..i)
+                        .filter(|_| rng.chance(0.3))
+                        .map(|d| format!("t{}", d))
+                        .collect(),
+                })
+                .collect();
+            let deps: Vec<Vec<usize>> = tasks
+                .iter()
+                .map(|task| {
+                    let names = task.dependencies.iter();
+                    names.map(|d| d[1..].parse().unwrap()).collect()
+                })
+                .collect();
+            let mut earliest = vec![0; n];
+            for i in 0..n {
+                for &d in deps[i].iter() {
+                    earliest[i] = earliest[i].max(earliest[d] + tasks[d].duration);
+                }
+            }
+            let length = (0..n)
+                .map(|i| earliest[i] + tasks[i].duration)
+                .max()
+                .unwrap();
+            let mut latest: Vec<Weight> = tasks.iter().map(|t| length - t.duration).collect();
+            for i in (0..n).rev() {
+                for &d in deps[i].iter() {
+                    latest[d] = latest[d].min(latest[i] - tasks[d].duration);
+                }
+            }
+
+            let result = schedule(&tasks).unwrap();
+            assert_eq!(result.length, length);
+            for i in 0..n {
+                assert_eq!(result.times[i].earliest_start, earliest[i]);
+                assert_eq!(result.times[i].latest_start, latest[i]);
+                assert!(result.times[i].slack >= 0);
+            }
+            let path = &result.critical_path;
+            assert_eq!(result.times[path[0]].earliest_start, 0);
+            let last = *path.last().unwrap();
+            assert_eq!(earliest[last] + tasks[last].duration, length);
+            for pair in path.windows(2) {
+                assert!(deps[pair[1]].contains(&pair[0]));
+                assert_eq!(
+                    earliest[pair[0]] + tasks[pair[0]].duration,
+                    earliest[pair[1]]
+                );
+            }
+            assert!(path.iter().all(|&i| result.times[i].slack == 0));
+        }
+    }
+
+    #[test]
+    fn bad_task_lists_are_errors() {
+        let tasks = parse_tasks("# plan\nwalls 5\n\nroof 3 walls\npaint 2 walls").unwrap();
+        let result = schedule(&tasks).unwrap();
+        assert_eq!(result.length, 8);
+        assert_eq!(result.critical_path, [0, 1]);
+        assert_eq!(result.times[2].slack, 1);
+
+        assert_eq!(
+            parse_tasks("a 1\nb x"),
+            Err(ScheduleError::Parse { line: 2 })
+        );
+        let check = |input: &str| schedule(&parse_tasks(input).unwrap()).unwrap_err();
+        assert_eq!(check("a 1\na 2"), ScheduleError::DuplicateTask("a".into()));
+        assert_eq!(
+            check("a 1 b"),
+            ScheduleError::UnknownDependency {
+                task: "a".into(),
+                dependency: "b".into()
+            }
+        );
+        assert_eq!(check("a 1 b\nb 1 a"), ScheduleError::Cycle);
+    }
+}
@@ -0,0 +1,75 @@
+use super::http::Request;
+
+/// Decides whether a request may be served.
+///
+/// [`Server::with_auth`](super::Server::with_auth) runs the validator before any
+/// endpoint, and answers 401 to requests it rejects.
+pub trait Authenticator: Send + Sync {
+    /// Whether `request` carries valid credentials.
+    fn authenticate(&self, request: &Request) -> bool;
+}
+
+impl<F> Authenticator for F
+where
+    F: Fn(&Request) -> bool + Send + Sync,
+{
+    fn authenticate(&self, request: &Request) -> bool {
+        self(request)
+    }
+}
+
+/// Accepts requests presenting one of a fixed set of keys, either as
+/// `Authorization: Bearer <key>` or as `X-API-Key: <key>`.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys {
+    keys: Vec<String>,
+}
+
+impl ApiKeys {
+    pub fn new(keys: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            keys: keys.into_iter().filter(|key| !key.is_empty()).collect(),
+        }
+    }
+
+    /// Read keys from `text`, one per line, ignoring blank lines and `#` comments.
+    pub fn parse(text: &str) -> Self {
+        Self::new(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with('#'))
+                .map(String::from),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The key presented by `request`, if any.
+    fn presented(request: &Request) -> Option<&str> {
+        let bearer = request.header("authorization").and_then(|value| {
+            let (scheme, token) = value.split_once(' ')?;
+            scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+        });
+        bearer.or_else(|| request.header("x-api-key"))
+    }
+}
+
+impl Authenticator for ApiKeys {
+    fn authenticate(&self, request: &Request) -> bool {
+        let presented = match Self::presented(request) {
+            Some(presented) => presented.as_bytes(),
+            None => return false,
+        };
+        // Check every key so the time taken doesn't say which one came close.
+        self.keys
+            .iter()
+            .fold(false, |found, key| found | same(key.as_bytes(), presented))
+    }
+}
+
+/// Compare `a` and `b` in time depending only on their lengths.
+fn same(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
@@ -0,0 +1,277 @@
+use std::io::{BufRead, Read, Write};
+use std::net::IpAddr;
+
+/// A parsed HTTP/1.x request.
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    /// The path without the query string, e.g. `/route`.
+    pub path: String,
+    /// Decoded query string parameters in the order given.
+    pub query: Vec<(String, String)>,
+    /// Header names are lowercased.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// The address the request came from, if it came over the network.
+    pub client: Option<IpAddr>,
+}
+
+impl Request {
+    /// Read one request from `reader`, or `None` if the connection closed before sending
+    /// anything.
+    pub fn read(reader: &mut impl BufRead) -> std::io::Result<Option<Request>> {
+        Self::read_within(reader, usize::MAX, usize::MAX)
+    }
+
+    /// Like [`read`](Self::read), but fails with a [`TooLarge`] error once the request
+    /// line and headers pass `max_header_bytes` or the body passes `max_body_bytes`,
+    /// without reading the rest.
+    pub fn read_within(
+        reader: &mut impl BufRead,
+        max_header_bytes: usize,
+        max_body_bytes: usize,
+    ) -> std::io::Result<Option<Request>> {
+        let mut remaining = max_header_bytes;
+        let mut line = String::new();
+        if read_header_line(reader, &mut remaining, &mut line)? == 0 {
+            return Ok(None);
+        }
+        let mut words = line.split_whitespace();
+        let (method, target) = match (words.next(), words.next(), words.next()) {
+            (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => {
+                (method, target)
+            }
+            _ => return Err(invalid("malformed request line")),
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let mut request = Request {
+            method: method.to_string(),
+            path: percent_decode(path).ok_or_else(|| invalid("malformed path"))?,
+            query: parse_query(query).ok_or_else(|| invalid("malformed query string"))?,
+            ..Request::default()
+        };
+
+        loop {
+            let mut line = String::new();
+            if read_header_line(reader, &mut remaining, &mut line)? == 0 {
+                return Err(invalid("connection closed inside the headers"));
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| invalid("malformed header"))?;
+            request
+                .headers
+                .push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+
+        if let Some(length) = request.header("content-length") {
+            let length: usize = length
+                .parse()
+                .map_err(|_| invalid("invalid content length"))?;
+            if length > max_body_bytes {
+                return Err(TooLarge::error(413, "request body", max_body_bytes));
+            }
+            request.body = vec![0; length];
+            reader.read_exact(&mut request.body)?;
+        }
+        Ok(Some(request))
+    }
+
+    /// The first query string parameter called `name`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The first header called `name`, which must be lowercase.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Read a line of at most `remaining` bytes, counting it against them.
+fn read_header_line(
+    reader: &mut impl BufRead,
+    remaining: &mut usize,
+    line: &mut String,
+) -> std::io::Result<usize> {
+    let limit = *remaining;
+    let read = reader.take(limit as u64).read_line(line)?;
+    if read == limit && !line.ends_with('\n') {
+        return Err(TooLarge::error(431, "request headers", limit));
+    }
+    *remaining -= read;
+    Ok(read)
+}
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// Why [`Request::read_within`] gave up on a request, carried inside the `io::Error` it
+/// returns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooLarge {
+    /// The status to answer with, 413 for bodies and 431 for headers.
+    pub status: u16,
+    message: String,
+}
+
+impl TooLarge {
+    fn error(status: u16, what: &str, limit: usize) -> std::io::Error {
+        let message = format!("{} larger than {} bytes", what, limit);
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            TooLarge { status, message },
+        )
+    }
+
+    /// The [`TooLarge`] inside `error`, if that is what it carries.
+    pub fn find(error: &std::io::Error) -> Option<&TooLarge> {
+        error.get_ref()?.downcast_ref()
+    }
+}
+
+impl std::fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for TooLarge {}
+
+/// Decode `%XX` escapes, and `+` as a space.
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+fn parse_query(query: &str) -> Option<Vec<(String, String)>> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Some((percent_decode(key)?, percent_decode(value)?))
+        })
+        .collect()
+}
+
+/// An HTTP response, always sent with `Connection: close`.
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    /// Headers besides the content type, length, and connection.
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16, content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            content_type,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    pub fn json(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self::new(status, "application/json", body)
+    }
+
+    /// A JSON `{"error": message}` body.
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::json(status, format!("{{\"error\":{}}}", json_string(message)))
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            reason(self.status),
+            self.content_type,
+            self.body.len()
+        )?;
+        for (name, value) in &self.headers {
+            write!(writer, "{}: {}\r\n", name, value)?;
+        }
+        writer.write_all(b"\r\n")?;
+        writer.write_all(&self.body)?;
+        writer.flush()
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Content Too Large",
+        422 => "Unprocessable Content",
+        426 => "Upgrade Required",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+/// `text` as a quoted JSON string.
+pub fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Clients idle for this long start over with a full bucket, so their entries can be
+/// dropped.
+const IDLE: Duration = Duration::from_secs(600);
+
+/// Prune idle clients once this many are tracked.
+const PRUNE_AT: usize = 4096;
+
+/// Bounds on what a single client or request may cost the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limits {
+    /// Requests per second allowed from each client address, or `None` for no limit.
+    pub rate: Option<f64>,
+    /// Requests a client may make at once before the rate applies.
+    pub burst: u32,
+    /// The largest `k` accepted for k-shortest-path queries.
+    pub max_k: usize,
+    /// The most queries accepted in one batch request.
+    pub max_batch: usize,
+    /// Nodes a search may expand before the query is rejected, or `None` for no limit.
+    pub search_budget: Option<usize>,
+    /// Uploaded graphs kept at once; uploads are refused while this many are live.
+    pub max_uploads: usize,
+    /// How long an uploaded graph is kept after it was last used.
+    pub upload_ttl: Duration,
+    /// Bytes allowed in the request line and headers together.
+    pub max_header_bytes: usize,
+    pub max_body_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            rate: None,
+            burst: 10,
+            max_k: 10,
+            max_batch: 1000,
+            search_budget: None,
+            max_uploads: 16,
+            upload_ttl: Duration::from_secs(15 * 60),
+            max_header_bytes: 8 * 1024,
+            max_body_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// A token bucket per client address.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    clients: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate,
+            burst: f64::from(burst.max(1)),
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `client`, or return how long until one is available.
+    pub fn check(&self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if clients.len() >= PRUNE_AT {
+            clients.retain(|_, bucket| now.saturating_duration_since(bucket.updated) < IDLE);
+        }
+        let bucket = clients.entry(client).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_refill_at_the_rate() {
+        let limiter = RateLimiter::new(2.0, 3);
+        let (client, other) = ([10, 0, 0, 1].into(), [10, 0, 0, 2].into());
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check(client, start).is_ok());
+        }
+        let wait = limiter.check(client, start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+        assert!(limiter.check(other, start).is_ok());
+        assert!(limiter.check(client, start + wait).is_ok());
+        assert!(limiter.check(client, start + wait).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::router::PoolStats;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds of the query latency histogram buckets, in seconds.
+const LATENCY_BUCKETS: [f64; 10] = [0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+/// How a route query ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Found,
+    Unreachable,
+    /// The request was missing a parameter or named an unknown node.
+    Invalid,
+    /// The search ran out of its budget before finishing.
+    OverBudget,
+}
+
+/// Counters for a running server, rendered in the OpenMetrics text format.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    found: AtomicU64,
+    unreachable: AtomicU64,
+    invalid: AtomicU64,
+    over_budget: AtomicU64,
+    /// Non-cumulative counts per bucket, plus one for latencies above the last bound.
+    latency: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    latency_nanos: AtomicU64,
+    reloads: AtomicU64,
+    failed_reloads: AtomicU64,
+    responses: Mutex<BTreeMap<u16, u64>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a route query that took `elapsed`.
+    pub fn record_query(&self, outcome: Outcome, elapsed: Duration) {
+        let counter = match outcome {
+            Outcome::Found => &self.found,
+            Outcome::Unreachable => &self.unreachable,
+            Outcome::Invalid => &self.invalid,
+            Outcome::OverBudget => &self.over_budget,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        let seconds = elapsed.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|&bound| seconds <= bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.latency[bucket].fetch_add(1, Ordering::Relaxed);
+        self.latency_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Record an attempt to load a new graph.
+    pub fn record_reload(&self, ok: bool) {
+        let counter = if ok {
+            &self.reloads
+        } else {
+            &self.failed_reloads
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a response sent with `status`.
+    pub fn record_response(&self, status: u16) {
+        if let Ok(mut responses) = self.responses.lock() {
+            *responses.entry(status).or_insert(0) += 1;
+        }
+    }
+
+    /// Render every metric, along with the graph size, the router's context pool
+    /// counters, which act as its cache, and the number of uploaded graphs.
+    pub fn render(&self, nodes: usize, edges: usize, pool: PoolStats, uploads: usize) -> String {
+        let mut out = String::new();
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+        out.push_str("# TYPE cs365_queries counter\n");
+        out.push_str("# HELP cs365_queries Route queries by outcome.\n");
+        for (result, counter) in [
+            ("found", &self.found),
+            ("unreachable", &self.unreachable),
+            ("invalid", &self.invalid),
+            ("over_budget", &self.over_budget),
+        ] {
+            let _ = writeln!(
+                out,
+                "cs365_queries_total{{result=\"{}\"}} {}",
+                result,
+                load(counter)
+            );
+        }
+
+        out.push_str("# TYPE cs365_query_duration_seconds histogram\n");
+        out.push_str("# UNIT cs365_query_duration_seconds seconds\n");
+        let mut cumulative = 0;
+        for (i, counter) in self.latency.iter().enumerate() {
+            cumulative += load(counter);
+            let bound = LATENCY_BUCKETS
+                .get(i)
+                .map_or("+Inf".to_string(), |b| b.to_string());
+            let _ = writeln!(
+                out,
+                "cs365_query_duration_seconds_bucket{{le=\"{}\"}} {}",
+                bound, cumulative
+            );
+        }
+        let sum = load(&self.latency_nanos) as f64 / 1e9;
+        let _ = writeln!(out, "cs365_query_duration_seconds_sum {}", sum);
+        let _ = writeln!(out, "cs365_query_duration_seconds_count {}", cumulative);
+
+        out.push_str("# TYPE cs365_search_contexts counter\n");
+        out.push_str("# HELP cs365_search_contexts Queries that reused a pooled search context or allocated one.\n");
+        let _ = writeln!(
+            out,
+            "cs365_search_contexts_total{{source=\"pool\"}} {}",
+            pool.reused
+        );
+        let _ = writeln!(
+            out,
+            "cs365_search_contexts_total{{source=\"new\"}} {}",
+            pool.allocated
+        );
+
+        out.push_str("# TYPE cs365_http_responses counter\n");
+        if let Ok(responses) = self.responses.lock() {
+            for (status, count) in responses.iter() {
+                let _ = writeln!(
+                    out,
+                    "cs365_http_responses_total{{status=\"{}\"}} {}",
+                    status, count
+                );
+            }
+        }
+
+        out.push_str("# TYPE cs365_graph_reloads counter\n");
+        let _ = writeln!(
+            out,
+            "cs365_graph_reloads_total{{result=\"ok\"}} {}",
+            load(&self.reloads)
+        );
+        let _ = writeln!(
+            out,
+            "cs365_graph_reloads_total{{result=\"failed\"}} {}",
+            load(&self.failed_reloads)
+        );
+
+        out.push_str("# TYPE cs365_graph_nodes gauge\n");
+        let _ = writeln!(out, "cs365_graph_nodes {}", nodes);
+        out.push_str("# TYPE cs365_graph_edges gauge\n");
+        let _ = writeln!(out, "cs365_graph_edges {}", edges);
+        out.push_str("# TYPE cs365_uploaded_graphs gauge\n");
+        let _ = writeln!(out, "cs365_uploaded_graphs {}", uploads);
+        out.push_str("# EOF\n");
+        out
+    }
+}
@@ -0,0 +1,1008 @@
+//! A small HTTP service answering route queries on a shared graph.
+//!
+//! Endpoints:
+//!
+//! - `GET /route?from=a&to=z` returns `{"cost": 3, "route": [{"node": "a", "distance":
+//!   0}, ...]}`, or a 404 with an `{"error": ...}` body if `to` is unreachable. With
+//!   `&k=3` it returns `{"routes": [...]}` holding up to the 3 shortest loopless routes,
+//!   each shaped like a single answer. With `&weight_factor=highway=0.8,ferry=3` the
+//!   cost of each edge is multiplied by the factor for its `label` attribute, see
+//!   [`WeightFactors`].
+//! - `POST /paths` takes a JSON array of `{"from": "a", "to": "z", "options": {"k": 3}}`
+//!   queries, `options` being optional and also taking `"weight_factors": {"highway":
+//!   0.8}`, and returns `{"results": [...]}` holding each
+//!   one's answer in order, or `{"status": 404, "error": ...}` for those that failed.
+//! - `POST /graphs` takes an edge list and returns `{"id": ..., "nodes": 3, "edges": 2,
+//!   "ttl_seconds": 900}`. Passing `graph=<id>` to `/route` or `/paths` then queries that
+//!   graph instead of the served one, until it goes unused for the time to live or is
+//!   removed with `DELETE /graphs/<id>`.
+//! - `GET /route/stream?from=a&to=z` upgrades to a WebSocket and streams the search as
+//!   it runs, one `{"event": "settled", "node": "b", "distance": 1, "frontier": 2,
+//!   "best": 3}` message per node settled, `best` being the cheapest route to `to` found
+//!   so far or `null`. The last message is the answer with `"event": "done"` added, or
+//!   `{"event": "error", "status": 404, "error": ...}`, followed by a close frame.
+//! - `GET /metrics` returns query counts, latencies, context pool usage, reloads, and
+//!   the graph size in the OpenMetrics text format.
+//!
+//! The graph can be swapped while serving, e.g. by [`watch`] when its file changes.
+//!
+//!
+//! [`WeightFactors`]: crate::attributes::WeightFactors
+//!
+//! [`Limits`] bound what one client can ask of the server: requests over the per-client
+//! rate get a 429 with a `Retry-After` header, oversized requests a 413 or 431, and
+//! queries whose search runs past its budget a 422.
+//!
+//! With an [`Authenticator`] such as [`ApiKeys`], every endpoint answers 401 to requests
+//! it rejects, so the service can be reached from beyond localhost.
+
+mod auth;
+pub mod http;
+pub(crate) mod json;
+pub mod limits;
+pub mod metrics;
+mod uploads;
+mod watch;
+mod websocket;
+
+pub use self::auth::{ApiKeys, Authenticator};
+pub use self::limits::Limits;
+pub use self::watch::watch;
+
+use self::http::{json_string, Request, Response, TooLarge};
+use self::json::Value;
+use self::limits::RateLimiter;
+use self::metrics::{Metrics, Outcome};
+use self::uploads::Uploads;
+use crate::alternatives::{find_k_shortest_paths, find_k_shortest_paths_within};
+use crate::attributes::WeightFactors;
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::{has_negative_edges, Path, SearchVisitor};
+use crate::router::{ConcurrentRouter, FrozenGraph, PoolStats};
+use std::io::{BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+/// Connections that send nothing for this long are dropped, so idle clients can't hold
+/// on to a worker.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a worker waits after a failed `accept` before trying again, so that running
+/// out of file descriptors doesn't turn into a busy loop until connections close.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+const OPENMETRICS: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// A route query's endpoints by name and its options, before they are checked against
+/// the graph.
+struct RouteQuery<'a> {
+    from: &'a str,
+    to: &'a str,
+    k: Option<usize>,
+    factors: Option<WeightFactors>,
+}
+
+impl<'a> RouteQuery<'a> {
+    /// Read a `{"from": ..., "to": ..., "options": {"k": ..., "weight_factors": {...}}}`
+    /// object.
+    fn from_json(item: &'a Value) -> Result<Self, String> {
+        let name = |key| {
+            item.get(key)
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("'{}' must be a node name", key))
+        };
+        let (from, to) = (name("from")?, name("to")?);
+        let mut k = None;
+        let mut factors = None;
+        match item.get("options") {
+            None | Some(Value::Null) => {}
+            Some(Value::Object(options)) => {
+                for (option, value) in options {
+                    match option.as_str() {
+                        "k" => {
+                            k = Some(
+                                value
+                                    .as_usize()
+                                    .ok_or_else(|| "'k' must be a whole number".to_string())?,
+                            )
+                        }
+                        "weight_factors" => factors = Some(weight_factors(value)?),
+                        _ => return Err(format!("unknown option '{}'", option)),
+                    }
+                }
+            }
+            Some(_) => return Err("'options' must be an object".into()),
+        }
+        Ok(Self {
+            from,
+            to,
+            k,
+            factors,
+        })
+    }
+}
+
+/// Read a `{"label": factor, ...}` object.
+fn weight_factors(value: &Value) -> Result<WeightFactors, String> {
+    let members = match value {
+        Value::Object(members) => members,
+        _ => return Err("'weight_factors' must be an object of numbers".into()),
+    };
+    let mut factors = WeightFactors::default();
+    for (label, factor) in members {
+        match factor {
+            Value::Number(factor) => factors.insert(label, *factor)?,
+            _ => return Err(format!("the factor for '{}' must be a number", label)),
+        }
+    }
+    Ok(factors)
+}
+
+/// Routes requests to handlers and keeps the metrics for them.
+pub struct Server {
+    current: RwLock<Current>,
+    metrics: Metrics,
+    limits: Limits,
+    limiter: Option<RateLimiter>,
+    auth: Option<Box<dyn Authenticator>>,
+    uploads: Uploads,
+}
+
+/// The router for the graph being served, and the pool counters of those it replaced.
+struct Current {
+    router: ConcurrentRouter,
+    retired: PoolStats,
+}
+
+impl Server {
+    pub fn new(graph: Arc<FrozenGraph>) -> Self {
+        Self::with_limits(graph, Limits::default())
+    }
+
+    pub fn with_limits(graph: Arc<FrozenGraph>, limits: Limits) -> Self {
+        Self {
+            current: RwLock::new(Current {
+                router: ConcurrentRouter::new(graph),
+                retired: PoolStats::default(),
+            }),
+            metrics: Metrics::new(),
+            limiter: limits.rate.map(|rate| RateLimiter::new(rate, limits.burst)),
+            uploads: Uploads::new(limits.upload_ttl, limits.max_uploads),
+            limits,
+            auth: None,
+        }
+    }
+
+    /// Only serve requests that `auth` accepts.
+    pub fn with_auth(mut self, auth: impl Authenticator + 'static) -> Self {
+        self.auth = Some(Box::new(auth));
+        self
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// The router for the current graph. Requests hold on to it until they finish, so a
+    /// replaced graph is only dropped once nothing is using it.
+    fn router(&self) -> ConcurrentRouter {
+        let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        current.router.clone()
+    }
+
+    /// The router for the uploaded graph named by the `graph` parameter, or for the
+    /// current graph without one.
+    fn router_for(&self, request: &Request) -> Result<ConcurrentRouter, Response> {
+        match request.param("graph") {
+            Some(id) => self.uploads.get(id, Instant::now()).ok_or_else(|| {
+                Response::error(404, &format!("no graph '{}', it may have expired", id))
+            }),
+            None => Ok(self.router()),
+        }
+    }
+
+    /// Serve `graph` from now on, with a fresh context pool.
+    pub fn replace_graph(&self, graph: Arc<FrozenGraph>) {
+        let router = ConcurrentRouter::new(graph);
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let old = current.router.pool_stats();
+        current.retired.reused += old.reused;
+        current.retired.allocated += old.allocated;
+        current.router = router;
+    }
+
+    /// Load a new graph with `load` and serve it, keeping the current one if that fails.
+    pub fn reload(&self, load: impl FnOnce() -> Result<Graph, String>) -> Result<(), String> {
+        self.reload_frozen(|| load().map(Graph::freeze))
+    }
+
+    /// Like [`reload`](Self::reload), for loaders that freeze the graph themselves, e.g.
+    /// to keep its attributes.
+    pub fn reload_frozen(
+        &self,
+        load: impl FnOnce() -> Result<Arc<FrozenGraph>, String>,
+    ) -> Result<(), String> {
+        let result = load().map(|graph| self.replace_graph(graph));
+        self.metrics.record_reload(result.is_ok());
+        result
+    }
+
+    fn pool_stats(&self) -> PoolStats {
+        let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        let stats = current.router.pool_stats();
+        PoolStats {
+            reused: current.retired.reused + stats.reused,
+            allocated: current.retired.allocated + stats.allocated,
+        }
+    }
+
+    /// Answer one request.
+    pub fn handle(&self, request: &Request) -> Response {
+        let response = match self.admit(request) {
+            Ok(()) => self.dispatch(request),
+            Err(response) => response,
+        };
+        self.metrics.record_response(response.status);
+        response
+    }
+
+    /// Check `request` against the client's rate limit, then its credentials. Rejected
+    /// attempts count against the rate too, which slows down guessing keys.
+    fn admit(&self, request: &Request) -> Result<(), Response> {
+        if let (Some(limiter), Some(client)) = (&self.limiter, request.client) {
+            limiter.check(client, Instant::now()).map_err(|wait| {
+                Response::error(429, "too many requests")
+                    .with_header("Retry-After", wait.as_secs_f64().ceil().to_string())
+            })?;
+        }
+        match &self.auth {
+            Some(auth) if !auth.authenticate(request) => {
+                Err(Response::error(401, "missing or invalid credentials")
+                    .with_header("WWW-Authenticate", "Bearer"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn dispatch(&self, request: &Request) -> Response {
+        match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/route") => self.route(request),
+            ("POST", "/paths") => self.paths(request),
+            ("POST", "/graphs") => self.upload(request),
+            ("GET", "/metrics") => {
+                let router = self.router();
+                let graph = router.graph().graph();
+                let body = self.metrics.render(
+                    graph.node_count(),
+                    graph.edge_count(),
+                    self.pool_stats(),
+                    self.uploads.len(Instant::now()),
+                );
+                Response::new(200, OPENMETRICS, body)
+            }
+            (_, "/route") | (_, "/metrics") => Response::error(405, "only GET is supported"),
+            (_, "/paths") | (_, "/graphs") => Response::error(405, "only POST is supported"),
+            (method, path) => match path.strip_prefix("/graphs/") {
+                Some(id) if method == "DELETE" => {
+                    if self.uploads.remove(id, Instant::now()) {
+                        Response::json(200, "{}")
+                    } else {
+                        Response::error(404, &format!("no graph '{}'", id))
+                    }
+                }
+                Some(_) => Response::error(405, "only DELETE is supported"),
+                None => Response::error(404, &format!("no endpoint at '{}'", path)),
+            },
+        }
+    }
+
+    fn route(&self, request: &Request) -> Response {
+        let param = |name| {
+            request
+                .param(name)
+                .ok_or_else(|| format!("missing '{}' parameter", name))
+        };
+        let k = match request.param("k").map(str::parse) {
+            None => Ok(None),
+            Some(Ok(k)) => Ok(Some(k)),
+            Some(Err(_)) => Err(self.k_error()),
+        };
+        let factors = request
+            .param("weight_factor")
+            .map(str::parse::<WeightFactors>)
+            .transpose()
+            .map_err(|e| format!("invalid 'weight_factor': {}", e));
+        let query = param("from").and_then(|from| {
+            Ok(RouteQuery {
+                from,
+                to: param("to")?,
+                k: k?,
+                factors: factors?,
+            })
+        });
+        let router = match self.router_for(request) {
+            Ok(router) => router,
+            Err(response) => return response,
+        };
+        match self.answer(&router, query) {
+            Ok(body) => Response::json(200, body),
+            Err((status, message)) => Response::error(status, &message),
+        }
+    }
+
+    /// Answer every query in a JSON array, in order, on the same graph.
+    fn paths(&self, request: &Request) -> Response {
+        let items = match std::str::from_utf8(&request.body)
+            .map_err(|_| "the body is not UTF-8".to_string())
+            .and_then(Value::parse)
+        {
+            Ok(Value::Array(items)) => items,
+            Ok(_) => return Response::error(400, "expected a JSON array of queries"),
+            Err(message) => return Response::error(400, &message),
+        };
+        if items.len() > self.limits.max_batch {
+            return Response::error(
+                413,
+                &format!(
+                    "at most {} queries are allowed per request",
+                    self.limits.max_batch
+                ),
+            );
+        }
+        let router = match self.router_for(request) {
+            Ok(router) => router,
+            Err(response) => return response,
+        };
+        let results: Vec<String> = items
+            .iter()
+            .map(
+                |item| match self.answer(&router, RouteQuery::from_json(item)) {
+                    Ok(body) => body,
+                    Err((status, message)) => format!(
+                        "{{\"status\":{},\"error\":{}}}",
+                        status,
+                        json_string(&message)
+                    ),
+                },
+            )
+            .collect();
+        Response::json(200, format!("{{\"results\":[{}]}}", results.join(",")))
+    }
+
+    /// Store the edge list in the body as a new graph.
+    fn upload(&self, request: &Request) -> Response {
+        let graph = match std::str::from_utf8(&request.body) {
+            Ok(text) => text.parse::<Graph>(),
+            Err(_) => return Response::error(400, "the body is not UTF-8"),
+        };
+        let graph = match graph {
+            Ok(graph) => graph,
+            Err(e) => return Response::error(400, &e.to_string()),
+        };
+        if has_negative_edges(&graph) {
+            return Response::error(400, "uploaded graphs can't have negative costs");
+        }
+        let (nodes, edges) = (graph.node_count(), graph.edge_count());
+        match self.uploads.insert(graph, Instant::now()) {
+            Some(id) => Response::json(
+                201,
+                format!(
+                    "{{\"id\":{},\"nodes\":{},\"edges\":{},\"ttl_seconds\":{}}}",
+                    json_string(&id),
+                    nodes,
+                    edges,
+                    self.uploads.ttl().as_secs()
+                ),
+            ),
+            None => Response::error(503, "too many graphs are uploaded, try again later"),
+        }
+    }
+
+    fn k_error(&self) -> String {
+        format!("'k' must be a number from 1 to {}", self.limits.max_k)
+    }
+
+    /// Answer one route query on `router`'s graph, as a JSON body or the status and
+    /// message of an error, recording it in the metrics.
+    fn answer(
+        &self,
+        router: &ConcurrentRouter,
+        query: Result<RouteQuery, String>,
+    ) -> Result<String, (u16, String)> {
+        let started = Instant::now();
+        let graph = router.graph().graph();
+        let lookup = |name: &str| {
+            graph
+                .get_node(name)
+                .ok_or_else(|| format!("unknown node '{}'", name))
+        };
+        let resolved = query.and_then(|query| match (query.k, query.factors) {
+            (Some(k), _) if !(1..=self.limits.max_k).contains(&k) => Err(self.k_error()),
+            (Some(_), Some(_)) => Err("weight factors can't be combined with 'k'".into()),
+            (k, factors) => Ok((lookup(query.from)?, lookup(query.to)?, k, factors)),
+        });
+        let (start, end, k, factors) = match resolved {
+            Ok(resolved) => resolved,
+            Err(message) => {
+                self.metrics
+                    .record_query(Outcome::Invalid, started.elapsed());
+                return Err((400, message));
+            }
+        };
+
+        let budget = self.limits.search_budget;
+        let attributes = router.graph().attributes();
+        let paths = match (k, budget, factors) {
+            (None, budget, Some(factors)) => router
+                .route_reweighted(start, end, budget, |edge| factors.apply(attributes, edge))
+                .map(|path| path.into_iter().collect()),
+            (None, None, None) => Ok(router.route(start, end).into_iter().collect()),
+            (None, Some(budget), None) => router
+                .route_within(start, end, budget)
+                .map(|path| path.into_iter().collect()),
+            (Some(k), None, _) => Ok(find_k_shortest_paths(graph, start, end, k)),
+            (Some(k), Some(budget), _) => {
+                find_k_shortest_paths_within(graph, start, end, k, budget)
+            }
+        };
+        let paths: Vec<Path> = match paths {
+            Ok(paths) => paths,
+            Err(e) => {
+                self.metrics
+                    .record_query(Outcome::OverBudget, started.elapsed());
+                return Err((422, e.to_string()));
+            }
+        };
+        let outcome = match paths.is_empty() {
+            false => Outcome::Found,
+            true => Outcome::Unreachable,
+        };
+        self.metrics.record_query(outcome, started.elapsed());
+        if paths.is_empty() {
+            return Err((404, unreachable(graph, start, end)));
+        }
+        let answer = |path: &Path| path_json(graph, path);
+        Ok(match k {
+            Some(_) => {
+                let routes: Vec<String> = paths.iter().map(answer).collect();
+                format!("{{\"routes\":[{}]}}", routes.join(","))
+            }
+            None => answer(&paths[0]),
+        })
+    }
+
+    /// Answer a WebSocket request to `/route/stream` by streaming the search's progress to
+    /// `writer`, or an HTTP error if the query can't be run.
+    fn stream(&self, request: &Request, writer: &mut impl Write) -> std::io::Result<()> {
+        let started = Instant::now();
+        let prepared = self.admit(request).and_then(|()| {
+            if request.method != "GET" {
+                return Err(Response::error(405, "only GET is supported"));
+            }
+            let accept = websocket::accept_key(request).ok_or_else(|| {
+                Response::error(426, "expected a WebSocket upgrade")
+                    .with_header("Upgrade", "websocket")
+            })?;
+            let router = self.router_for(request)?;
+            let graph = router.graph().graph();
+            let node = |name| {
+                let name = request
+                    .param(name)
+                    .ok_or_else(|| format!("missing '{}' parameter", name))?;
+                graph
+                    .get_node(name)
+                    .ok_or_else(|| format!("unknown node '{}'", name))
+            };
+            match node("from").and_then(|from| Ok((from, node("to")?))) {
+                Ok((start, end)) => Ok((accept, router.clone(), start, end)),
+                Err(message) => {
+                    self.metrics
+                        .record_query(Outcome::Invalid, started.elapsed());
+                    Err(Response::error(400, &message))
+                }
+            }
+        });
+        let (accept, router, start, end) = match prepared {
+            Ok(prepared) => prepared,
+            Err(response) => {
+                self.metrics.record_response(response.status);
+                return response.write_to(writer);
+            }
+        };
+        self.metrics.record_response(101);
+        websocket::handshake(writer, &accept)?;
+
+        let graph = router.graph().graph();
+        let mut progress = Progress {
+            writer: &mut *writer,
+            graph,
+            end,
+            best: None,
+            failed: None,
+        };
+        let found = router.route_visited(start, end, self.limits.search_budget, &mut progress);
+        if let Some(e) = progress.failed {
+            return Err(e);
+        }
+        let (outcome, event) = match found {
+            Ok(Some(path)) => (
+                Outcome::Found,
+                format!("{{\"event\":\"done\",{}", &path_json(graph, &path)[1..]),
+            ),
+            Ok(None) => (
+                Outcome::Unreachable,
+                stream_error(404, &unreachable(graph, start, end)),
+            ),
+            Err(e) => (Outcome::OverBudget, stream_error(422, &e.to_string())),
+        };
+        self.metrics.record_query(outcome, started.elapsed());
+        websocket::send_text(writer, &event)?;
+        websocket::send_close(writer)
+    }
+
+    /// Serve connections from `listener` on `threads` worker threads until the listener
+    /// itself fails.
+    ///
+    /// Failures that only affect one connection or pass once resources are freed, such as
+    /// a client aborting before it was accepted or running out of file descriptors, are
+    /// logged to stderr and retried.
+    pub fn serve(&self, listener: &TcpListener, threads: usize) -> std::io::Result<()> {
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.max(1))
+                .map(|_| {
+                    scope.spawn(|| -> std::io::Result<()> {
+                        loop {
+                            let stream = match listener.accept() {
+                                Ok((stream, _)) => stream,
+                                Err(e) if listener_broken(&e) => return Err(e),
+                                Err(e) => {
+                                    eprintln!("Failed to accept a connection: {}", e);
+                                    std::thread::sleep(ACCEPT_RETRY_DELAY);
+                                    continue;
+                                }
+                            };
+                            // A broken connection only affects its own client.
+                            let _ = self.connection(stream);
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .try_for_each(|worker| worker.join().expect("server thread panicked"))
+        })
+    }
+
+    fn connection(&self, stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream);
+        let read = Request::read_within(
+            &mut reader,
+            self.limits.max_header_bytes,
+            self.limits.max_body_bytes,
+        );
+        let response = match read {
+            Ok(Some(mut request)) => {
+                request.client = stream.peer_addr().ok().map(|addr| addr.ip());
+                if request.path == "/route/stream" {
+                    return self.stream(&request, &mut &stream);
+                }
+                self.handle(&request)
+            }
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                let status = TooLarge::find(&e).map_or(400, |too_large| too_large.status);
+                self.metrics.record_response(status);
+                Response::error(status, &e.to_string())
+            }
+        };
+        response.write_to(&mut &stream)
+    }
+}
+
+/// Sends an event for every node a streamed search settles.
+struct Progress<'a, W: Write> {
+    writer: &'a mut W,
+    graph: &'a Graph,
+    end: NodeId,
+    /// The cost of the best route to `end` found so far.
+    best: Option<Weight>,
+    /// The first failure to send, after which the rest of the search goes unreported.
+    failed: Option<std::io::Error>,
+}
+
+impl<W: Write> SearchVisitor for Progress<'_, W> {
+    fn settled(&mut self, node: NodeId, distance: Weight, frontier: usize) {
+        if self.failed.is_some() {
+            return;
+        }
+        let best = self
+            .best
+            .map_or("null".to_string(), |best| best.to_string());
+        let event = format!(
+            "{{\"event\":\"settled\",\"node\":{},\"distance\":{},\"frontier\":{},\"best\":{}}}",
+            json_string(self.graph.get_node_name(node).unwrap_or("")),
+            distance,
+            frontier,
+            best
+        );
+        if let Err(e) = websocket::send_text(self.writer, &event) {
+            self.failed = Some(e);
+        }
+    }
+
+    fn improved(&mut self, node: NodeId, distance: Weight) {
+        if node == self.end {
+            self.best = Some(distance);
+        }
+    }
+}
+
+/// A route as `{"cost": 3, "route": [{"node": "a", "distance": 0}, ...]}`.
+fn path_json(graph: &Graph, path: &Path) -> String {
+    let route: Vec<String> = path
+        .nodes
+        .iter()
+        .zip(path.distance.iter())
+        .map(|(&node, distance)| {
+            format!(
+                "{{\"node\":{},\"distance\":{}}}",
+                json_string(graph.get_node_name(node).unwrap_or("")),
+                distance
+            )
+        })
+        .collect();
+    format!("{{\"cost\":{},\"route\":[{}]}}", path.cost, route.join(","))
+}
+
+fn unreachable(graph: &Graph, start: NodeId, end: NodeId) -> String {
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+    format!("there is no path from '{}' to '{}'", name(start), name(end))
+}
+
+fn stream_error(status: u16, message: &str) -> String {
+    format!(
+        "{{\"event\":\"error\",\"status\":{},\"error\":{}}}",
+        status,
+        json_string(message)
+    )
+}
+
+/// Whether a failed `accept` means the listener can't accept anything again, such as when
+/// it isn't listening, rather than a failure of one connection or a lack of resources.
+fn listener_broken(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::InvalidInput | std::io::ErrorKind::Unsupported
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Graph;
+
+    fn server() -> Server {
+        let graph: Graph = "a b 1\nb z 2\nx y 1".parse().unwrap();
+        Server::new(graph.freeze())
+    }
+
+    fn get(server: &Server, target: &str) -> Response {
+        let text = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", target);
+        let request = Request::read(&mut text.as_bytes()).unwrap().unwrap();
+        server.handle(&request)
+    }
+
+    #[test]
+    fn answers_route_queries() {
+        let server = server();
+        let found = get(&server, "/route?from=a&to=%7A");
+        assert_eq!(found.status, 200);
+        assert_eq!(
+            String::from_utf8(found.body).unwrap(),
+            r#"{"cost":3,"route":[{"node":"a","distance":0},{"node":"b","distance":1},{"node":"z","distance":3}]}"#
+        );
+        assert_eq!(get(&server, "/route?from=a&to=x").status, 404);
+        assert_eq!(get(&server, "/route?from=a&to=q").status, 400);
+        assert_eq!(get(&server, "/nowhere").status, 404);
+    }
+
+    #[test]
+    fn reweighs_edges_by_label() {
+        let graph: Graph = "a b 1\nb z 1\na z 3".parse().unwrap();
+        let attributes = crate::attributes::Attributes::parse(&graph, "edge a z label=highway");
+        let server = Server::new(graph.freeze_with(attributes.unwrap()));
+        let cost = |target| {
+            let response = get(&server, target);
+            let body = String::from_utf8(response.body).unwrap();
+            (
+                response.status,
+                body[..body.find(',').unwrap_or(body.len())].to_string(),
+            )
+        };
+        assert_eq!(cost("/route?from=a&to=z"), (200, r#"{"cost":2"#.into()));
+        assert_eq!(
+            cost("/route?from=a&to=z&weight_factor=highway=0.5"),
+            (200, r#"{"cost":2"#.into())
+        );
+        assert_eq!(
+            cost("/route?from=a&to=z&weight_factor=highway=0.3,ferry=2"),
+            (200, r#"{"cost":1"#.into())
+        );
+        assert_eq!(
+            get(&server, "/route?from=a&to=z&weight_factor=highway").status,
+            400
+        );
+        assert_eq!(
+            get(&server, "/route?from=a&to=z&weight_factor=highway=-1").status,
+            400
+        );
+        assert_eq!(
+            get(&server, "/route?from=a&to=z&k=2&weight_factor=highway=1").status,
+            400
+        );
+        // The served graph itself is untouched.
+        assert_eq!(cost("/route?from=a&to=z"), (200, r#"{"cost":2"#.into()));
+    }
+
+    #[test]
+    fn metrics_count_queries() {
+        let server = server();
+        get(&server, "/route?from=a&to=z");
+        get(&server, "/route?from=a&to=x");
+        let metrics = get(&server, "/metrics");
+        assert_eq!(metrics.content_type, OPENMETRICS);
+        let text = String::from_utf8(metrics.body).unwrap();
+        assert!(text.contains("cs365_queries_total{result=\"found\"} 1\n"));
+        assert!(text.contains("cs365_queries_total{result=\"unreachable\"} 1\n"));
+        assert!(text.contains("cs365_query_duration_seconds_count 2\n"));
+        assert!(text.contains("cs365_http_responses_total{status=\"404\"} 1\n"));
+        assert!(text.contains("cs365_graph_nodes 5\n"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn reloads_keep_the_old_graph_on_failure() {
+        let server = server();
+        assert_eq!(get(&server, "/route?from=x&to=y").status, 200);
+        server
+            .reload(|| Ok("x q 1\nq y 1".parse().unwrap()))
+            .unwrap();
+        let body = String::from_utf8(get(&server, "/route?from=x&to=y").body).unwrap();
+        assert!(body.starts_with("{\"cost\":2,"));
+        assert!(server.reload(|| Err("bad file".into())).is_err());
+        assert_eq!(get(&server, "/route?from=q&to=y").status, 200);
+
+        let text = String::from_utf8(get(&server, "/metrics").body).unwrap();
+        assert!(text.contains("cs365_graph_reloads_total{result=\"ok\"} 1\n"));
+        assert!(text.contains("cs365_graph_reloads_total{result=\"failed\"} 1\n"));
+        assert!(text.contains("cs365_search_contexts_total{source=\"new\"} 2\n"));
+    }
+
+    #[test]
+    fn limits_reject_expensive_requests() {
+        let graph: Graph = "a b 1\nb z 2\na z 5\nx y 1".parse().unwrap();
+        let limits = Limits {
+            rate: Some(1.0),
+            burst: 4,
+            max_k: 2,
+            search_budget: Some(2),
+            max_header_bytes: 64,
+            ..Limits::default()
+        };
+        let server = Server::with_limits(graph.freeze(), limits);
+        let found = get(&server, "/route?from=a&to=z");
+        assert_eq!(found.status, 200);
+        assert!(String::from_utf8(found.body)
+            .unwrap()
+            .starts_with(r#"{"cost":3,"#));
+        assert_eq!(get(&server, "/route?from=a&to=z&k=3").status, 400);
+        assert_eq!(get(&server, "/route?from=a&to=z&k=2").status, 422);
+        let routes = get(&server, "/route?from=x&to=y&k=2");
+        assert_eq!(
+            String::from_utf8(routes.body).unwrap(),
+            r#"{"routes":[{"cost":1,"route":[{"node":"x","distance":0},{"node":"y","distance":1}]}]}"#
+        );
+
+        let mut request = Request::read(&mut "GET /metrics HTTP/1.1\r\n\r\n".as_bytes())
+            .unwrap()
+            .unwrap();
+        request.client = Some([10, 0, 0, 1].into());
+        for _ in 0..4 {
+            assert_eq!(server.handle(&request).status, 200);
+        }
+        let limited = server.handle(&request);
+        assert_eq!(limited.status, 429);
+        assert_eq!(limited.headers, [("Retry-After", "1".to_string())]);
+
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(64));
+        let error = Request::read_within(&mut long.as_bytes(), 64, 0).unwrap_err();
+        assert_eq!(TooLarge::find(&error).unwrap().status, 431);
+        let body = "POST /route HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello";
+        let error = Request::read_within(&mut body.as_bytes(), 64, 4).unwrap_err();
+        assert_eq!(TooLarge::find(&error).unwrap().status, 413);
+    }
+
+    #[test]
+    fn auth_rejects_unknown_keys() {
+        let keyed = server().with_auth(ApiKeys::parse("# lab keys\nsecret\n\nother\n"));
+        let send = |headers: &str| {
+            let text = format!("GET /route?from=a&to=z HTTP/1.1\r\n{}\r\n", headers);
+            let request = Request::read(&mut text.as_bytes()).unwrap().unwrap();
+            keyed.handle(&request)
+        };
+        let rejected = send("");
+        assert_eq!(rejected.status, 401);
+        assert_eq!(
+            rejected.headers,
+            [("WWW-Authenticate", "Bearer".to_string())]
+        );
+        assert_eq!(send("Authorization: Bearer secre\r\n").status, 401);
+        assert_eq!(send("Authorization: Basic secret\r\n").status, 401);
+        assert_eq!(send("Authorization: bearer secret\r\n").status, 200);
+        assert_eq!(send("X-API-Key: other\r\n").status, 200);
+
+        let open = server().with_auth(|request: &Request| request.param("from") == Some("a"));
+        assert_eq!(get(&open, "/route?from=a&to=z").status, 200);
+        assert_eq!(get(&open, "/route?from=b&to=z").status, 401);
+    }
+
+    #[test]
+    fn answers_batches_in_order() {
+        let server = server();
+        let post = |body: &str| {
+            let text = format!(
+                "POST /paths HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let request = Request::read(&mut text.as_bytes()).unwrap().unwrap();
+            server.handle(&request)
+        };
+        let batch = post(
+            r#"[{"from": "x", "to": "y"}, {"from": "a", "to": "x"},
+                {"from": "a", "to": "b", "options": {"k": 2}}, {"from": "a"}]"#,
+        );
+        assert_eq!(batch.status, 200);
+        assert_eq!(
+            String::from_utf8(batch.body).unwrap(),
+            concat!(
+                r#"{"results":[{"cost":1,"route":[{"node":"x","distance":0},{"node":"y","distance":1}]},"#,
+                r#"{"status":404,"error":"there is no path from 'a' to 'x'"},"#,
+                r#"{"routes":[{"cost":1,"route":[{"node":"a","distance":0},{"node":"b","distance":1}]}]},"#,
+                r#"{"status":400,"error":"'to' must be a node name"}]}"#
+            )
+        );
+        assert_eq!(post("{\"from\": \"a\"}").status, 400);
+        assert_eq!(post("[{]").status, 400);
+        assert_eq!(get(&server, "/paths").status, 405);
+    }
+
+    #[test]
+    fn queries_uploaded_graphs() {
+        let server = Server::with_limits(
+            "a b 1".parse::<Graph>().unwrap().freeze(),
+            Limits {
+                max_uploads: 1,
+                ..Limits::default()
+            },
+        );
+        let send = |method: &str, target: &str, body: &str| {
+            let text = format!(
+                "{} {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                method,
+                target,
+                body.len(),
+                body
+            );
+            let request = Request::read(&mut text.as_bytes()).unwrap().unwrap();
+            server.handle(&request)
+        };
+        let created = send("POST", "/graphs", "p q 4\nq r 5");
+        assert_eq!(created.status, 201);
+        let body = String::from_utf8(created.body).unwrap();
+        let id = json::Value::parse(&body)
+            .unwrap()
+            .get("id")
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_owned();
+        assert!(body.ends_with(r#""nodes":3,"edges":2,"ttl_seconds":900}"#));
+        assert_eq!(send("POST", "/graphs", "x y 1").status, 503);
+        assert_eq!(send("POST", "/graphs", "x y").status, 400);
+        // Dijkstra would route a to c directly for 1 instead of through b for -1.
+        let negative = send("POST", "/graphs", "a b 4 ->\na c 1 ->\nb c -5 ->");
+        assert_eq!(negative.status, 400);
+
+        let route = get(&server, &format!("/route?graph={}&from=p&to=r", id));
+        assert_eq!(route.status, 200);
+        assert!(String::from_utf8(route.body)
+            .unwrap()
+            .starts_with(r#"{"cost":9,"#));
+        let batch = send(
+            "POST",
+            &format!("/paths?graph={}", id),
+            r#"[{"from":"a","to":"b"}]"#,
+        );
+        assert!(String::from_utf8(batch.body)
+            .unwrap()
+            .contains("unknown node 'a'"));
+        assert_eq!(get(&server, "/route?from=a&to=b").status, 200);
+
+        assert_eq!(send("DELETE", &format!("/graphs/{}", id), "").status, 200);
+        assert_eq!(send("DELETE", &format!("/graphs/{}", id), "").status, 404);
+        assert_eq!(
+            get(&server, &format!("/route?graph={}&from=p&to=r", id)).status,
+            404
+        );
+        assert_eq!(get(&server, "/graphs").status, 405);
+    }
+
+    #[test]
+    fn streams_search_progress() {
+        let server = server();
+        let stream = |query: &str, upgrade: bool| {
+            let headers = match upgrade {
+                true => {
+                    "Upgrade: websocket\r\nConnection: Upgrade\r\n\
+                         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                         Sec-WebSocket-Version: 13\r\n"
+                }
+                false => "",
+            };
+            let text = format!("GET /route/stream?{} HTTP/1.1\r\n{}\r\n", query, headers);
+            let request = Request::read(&mut text.as_bytes()).unwrap().unwrap();
+            let mut out = Vec::new();
+            server.stream(&request, &mut out).unwrap();
+            out
+        };
+        // Split the frames after the handshake, which are all short and unmasked.
+        let messages = |out: &[u8]| {
+            let text = String::from_utf8_lossy(out);
+            let start = text.find("\r\n\r\n").unwrap() + 4;
+            let mut frames = &out[start..];
+            let mut messages = Vec::new();
+            while let [opcode, len, rest @ ..] = frames {
+                let (payload, next) = rest.split_at(*len as usize);
+                if *opcode == 0x81 {
+                    messages.push(String::from_utf8(payload.to_vec()).unwrap());
+                }
+                frames = next;
+            }
+            messages
+        };
+
+        let out = stream("from=a&to=z", true);
+        assert!(out.starts_with(b"HTTP/1.1 101 Switching Protocols\r\n"));
+        assert_eq!(
+            messages(&out),
+            [
+                r#"{"event":"settled","node":"a","distance":0,"frontier":0,"best":null}"#,
+                r#"{"event":"settled","node":"b","distance":1,"frontier":0,"best":null}"#,
+                r#"{"event":"settled","node":"z","distance":3,"frontier":0,"best":3}"#,
+                r#"{"event":"done","cost":3,"route":[{"node":"a","distance":0},{"node":"b","distance":1},{"node":"z","distance":3}]}"#,
+            ]
+        );
+        assert!(out.ends_with(&[0x88, 2, 0x03, 0xE8]));
+        let unreachable = messages(&stream("from=a&to=x", true));
+        assert_eq!(
+            unreachable.last().unwrap(),
+            r#"{"event":"error","status":404,"error":"there is no path from 'a' to 'x'"}"#
+        );
+        assert!(stream("from=a&to=z", false).starts_with(b"HTTP/1.1 426 "));
+        assert!(stream("from=a&to=q", true).starts_with(b"HTTP/1.1 400 "));
+    }
+
+    #[test]
+    fn transient_accept_failures_are_retried() {
+        use std::io::{Error, ErrorKind};
+
+        // EMFILE, too many open files.
+        assert!(!listener_broken(&Error::from_raw_os_error(24)));
+        assert!(!listener_broken(&ErrorKind::ConnectionAborted.into()));
+        assert!(!listener_broken(&ErrorKind::Interrupted.into()));
+        assert!(listener_broken(&ErrorKind::InvalidInput.into()));
+    }
+}
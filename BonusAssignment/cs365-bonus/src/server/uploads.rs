@@ -0,0 +1,113 @@
+use crate::graph::Graph;
+use crate::router::ConcurrentRouter;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Graphs uploaded by clients, each dropped once it goes unused for the time to live.
+pub struct Uploads {
+    ttl: Duration,
+    capacity: usize,
+    graphs: Mutex<HashMap<String, Upload>>,
+    /// Keys for generating ids, random for each process.
+    keys: RandomState,
+    counter: AtomicU64,
+}
+
+struct Upload {
+    router: ConcurrentRouter,
+    expires: Instant,
+}
+
+impl Uploads {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            graphs: Mutex::new(HashMap::new()),
+            keys: RandomState::new(),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Store `graph` and return its id, or `None` if there is no room for it.
+    pub fn insert(&self, graph: Graph, now: Instant) -> Option<String> {
+        let mut graphs = self.graphs.lock().unwrap_or_else(PoisonError::into_inner);
+        graphs.retain(|_, upload| upload.expires > now);
+        if graphs.len() >= self.capacity {
+            return None;
+        }
+        let id = self.new_id();
+        let upload = Upload {
+            router: ConcurrentRouter::new(graph.freeze()),
+            expires: now + self.ttl,
+        };
+        graphs.insert(id.clone(), upload);
+        Some(id)
+    }
+
+    /// The router for the graph called `id`, if it hasn't expired. Using a graph keeps it
+    /// alive for another time to live.
+    pub fn get(&self, id: &str, now: Instant) -> Option<ConcurrentRouter> {
+        let mut graphs = self.graphs.lock().unwrap_or_else(PoisonError::into_inner);
+        let upload = graphs.get_mut(id).filter(|upload| upload.expires > now)?;
+        upload.expires = now + self.ttl;
+        Some(upload.router.clone())
+    }
+
+    /// Drop the graph called `id`, returning whether there was one.
+    pub fn remove(&self, id: &str, now: Instant) -> bool {
+        let mut graphs = self.graphs.lock().unwrap_or_else(PoisonError::into_inner);
+        graphs.remove(id).is_some_and(|upload| upload.expires > now)
+    }
+
+    /// The number of graphs that haven't expired.
+    pub fn len(&self, now: Instant) -> usize {
+        let graphs = self.graphs.lock().unwrap_or_else(PoisonError::into_inner);
+        graphs
+            .values()
+            .filter(|upload| upload.expires > now)
+            .count()
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// An unguessable id: 128 bits hashed from the process's random keys, a counter, and
+    /// the time.
+    fn new_id(&self) -> String {
+        let count = self.counter.fetch_add(1, Ordering::Relaxed);
+        let half = |salt: u64| self.keys.hash_one((salt, count, SystemTime::now()));
+        format!("{:016x}{:016x}", half(0), half(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graphs_expire_when_unused() {
+        let uploads = Uploads::new(Duration::from_secs(10), 2);
+        let start = Instant::now();
+        let graph = || "a b 1".parse::<Graph>().unwrap();
+        let first = uploads.insert(graph(), start).unwrap();
+        let second = uploads.insert(graph(), start).unwrap();
+        assert_ne!(first, second);
+        assert!(uploads.insert(graph(), start).is_none());
+
+        let later = start + Duration::from_secs(8);
+        assert!(uploads.get(&first, later).is_some());
+        let expired = start + Duration::from_secs(12);
+        assert!(uploads.get(&first, expired).is_some());
+        assert!(uploads.get(&second, expired).is_none());
+        assert_eq!(uploads.len(expired), 1);
+        assert!(uploads.insert(graph(), expired).is_some());
+        assert!(uploads.remove(&first, expired));
+        assert!(!uploads.remove(&first, expired));
+    }
+}
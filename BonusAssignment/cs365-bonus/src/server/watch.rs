@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// What identifies a version of a file: its modification time and length, or `None` if
+/// it can't be read.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &PathBuf) -> Stamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Poll `paths` every `interval`, calling `changed` whenever any of them was modified.
+/// Never returns.
+///
+/// A change is only reported once the files look the same on two polls in a row, so a
+/// file that is still being written isn't read half-finished. Writers that replace the
+/// file with a rename are picked up on the next poll.
+pub fn watch(paths: &[PathBuf], interval: Duration, mut changed: impl FnMut()) -> ! {
+    let read = || paths.iter().map(stamp).collect::<Vec<Stamp>>();
+    let mut loaded = read();
+    let mut previous = loaded.clone();
+    loop {
+        std::thread::sleep(interval);
+        let current = read();
+        if current == previous && current != loaded {
+            changed();
+            loaded = current.clone();
+        }
+        previous = current;
+    }
+}
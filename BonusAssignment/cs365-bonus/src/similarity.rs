@@ -0,0 +1,187 @@
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use crate::path::Path;
+use crate::replacement::path_edges;
+use std::collections::HashSet;
+
+/// How alike two routes are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Similarity {
+    /// Shared edges over the edges used by either route, from 0 to 1.
+    pub shared_edges: f64,
+    /// The fraction of the first route's cost on edges the second also uses.
+    pub weighted_overlap: f64,
+    /// The discrete Fréchet distance between the routes, if coordinates were given.
+    pub divergence: Option<f64>,
+}
+
+/// Compare `a` against `b`, using `coordinates[node]` for the divergence if available.
+pub fn compare(
+    graph: &Graph,
+    a: &Path,
+    b: &Path,
+    coordinates: Option<&[(f64, f64)]>,
+) -> Similarity {
+    Similarity {
+        shared_edges: shared_edge_ratio(graph, a, b),
+        weighted_overlap: weighted_overlap(graph, a, b),
+        divergence: coordinates.map(|points| {
+            let trace = |path: &Path| -> Vec<(f64, f64)> {
+                path.nodes.iter().map(|node| points[node.index()]).collect()
+            };
+            frechet_distance(&trace(a), &trace(b))
+        }),
+    }
+}
+
+/// The Jaccard similarity of the edge sets of `a` and `b`. Two routes without edges are
+/// identical.
+pub fn shared_edge_ratio(graph: &Graph, a: &Path, b: &Path) -> f64 {
+    let a: HashSet<EdgeId> = path_edges(graph, a).into_iter().collect();
+    let b: HashSet<EdgeId> = path_edges(graph, b).into_iter().collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// The fraction of the cost of `a` that lies on edges `b` also uses.
+///
+/// This is not symmetric: a short route entirely contained in a long one overlaps it
+/// fully, but not the other way around. Free routes are counted by edges instead.
+pub fn weighted_overlap(graph: &Graph, a: &Path, b: &Path) -> f64 {
+    let edges = path_edges(graph, a);
+    let other: HashSet<EdgeId> = path_edges(graph, b).into_iter().collect();
+    let cost = |edge: EdgeId| graph.edge_weight(edge).unwrap_or(0);
+    let total: Weight = edges.iter().map(|&edge| cost(edge)).sum();
+    let shared: Vec<EdgeId> = edges
+        .iter()
+        .copied()
+        .filter(|edge| other.contains(edge))
+        .collect();
+    if total == 0 {
+        if edges.is_empty() {
+            return 1.0;
+        }
+        return shared.len() as f64 / edges.len() as f64;
+    }
+    shared.into_iter().map(cost).sum::<Weight>() as f64 / total as f64
+}
+
+/// The discrete Fréchet distance between two polylines: the shortest leash that lets two
+/// walkers traverse them from start to end, each moving forward one vertex at a time.
+///
+/// Returns infinity if either is empty.
+pub fn frechet_distance(a: &[(f64, f64)], b: &[(f64, f64)]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return f64::INFINITY;
+    }
+    let dist = |p: (f64, f64), q: (f64, f64)| (p.0 - q.0).hypot(p.1 - q.1);
+    // `row[j]` is the coupling distance for `a[..=i]` and `b[..=j]`.
+    let mut row = vec![0.0; b.len()];
+    for (i, &p) in a.iter().enumerate() {
+        let mut diagonal = 0.0;
+        for (j, &q) in b.iter().enumerate() {
+            let above = row[j];
+            let here = dist(p, q);
+            row[j] = match (i, j) {
+                (0, 0) => here,
+                (0, _) => here.max(row[j - 1]),
+                (_, 0) => here.max(above),
+                _ => here.max(above.min(row[j - 1]).min(diagonal)),
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len() - 1]
+}
+
+/// Read `name x y` lines into per-node coordinates. Nodes without a line sit at the
+/// origin.
+pub fn parse_coordinates(graph: &Graph, text: &str) -> Result<Vec<(f64, f64)>, String> {
+    let mut points = vec![(0.0, 0.0); graph.node_count()];
+    for (i, line) in text.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (node, point): (NodeId, (f64, f64)) = match words.as_slice() {
+            [] => continue,
+            [name, x, y] => {
+                let node = graph
+                    .get_node(name)
+                    .ok_or_else(|| format!("line {}: unknown node '{}'", i + 1, name))?;
+                match (x.parse(), y.parse()) {
+                    (Ok(x), Ok(y)) => (node, (x, y)),
+                    _ => return Err(format!("line {}: invalid coordinates", i + 1)),
+                }
+            }
+            _ => return Err(format!("line {}: expected 'name x y'", i + 1)),
+        };
+        points[node.index()] = point;
+    }
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// The smallest leash over every way of walking both polylines, tried one by one.
+    fn every_coupling(a: &[(f64, f64)], b: &[(f64, f64)], i: usize, j: usize) -> f64 {
+        let here = (a[i].0 - b[j].0).hypot(a[i].1 - b[j].1);
+        if i + 1 == a.len() && j + 1 == b.len() {
+            return here;
+        }
+        let mut rest = f64::INFINITY;
+        if i + 1 < a.len() {
+            rest = rest.min(every_coupling(a, b, i + 1, j));
+        }
+        if j + 1 < b.len() {
+            rest = rest.min(every_coupling(a, b, i, j + 1));
+        }
+        if i + 1 < a.len() && j + 1 < b.len() {
+            rest = rest.min(every_coupling(a, b, i + 1, j + 1));
+        }
+        here.max(rest)
+    }
+
+    #[test]
+    fn frechet_matches_every_coupling() {
+        let mut rng = Rng::new(31);
+        let polyline = |rng: &mut Rng| -> Vec<(f64, f64)> {
+            (0..1 + rng.below(6))
+                .map(|_| (rng.below(10) as f64, rng.below(10) as f64))
+                .collect()
+        };
+        for _ in 0..100 {
+            let (a, b) = (polyline(&mut rng), polyline(&mut rng));
+            let distance = frechet_distance(&a, &b);
+            assert_eq!(distance, every_coupling(&a, &b, 0, 0));
+            assert_eq!(distance, frechet_distance(&b, &a));
+        }
+        assert_eq!(frechet_distance(&[], &[(0.0, 0.0)]), f64::INFINITY);
+    }
+
+    #[test]
+    fn overlaps_weigh_shared_edges() {
+        let graph: Graph = "a b 1\nb c 3\na d 2\nd c 2\nc e 4".parse().unwrap();
+        let path = |names: &[&str]| {
+            let nodes = names.iter().map(|n| graph.get_node(n).unwrap()).collect();
+            Path::from_nodes(&graph, nodes).unwrap()
+        };
+        let (upper, lower) = (path(&["a", "b", "c", "e"]), path(&["a", "d", "c", "e"]));
+        assert_eq!(shared_edge_ratio(&graph, &upper, &lower), 0.2);
+        assert_eq!(weighted_overlap(&graph, &upper, &lower), 0.5);
+        assert_eq!(weighted_overlap(&graph, &path(&["c", "e"]), &upper), 1.0);
+        assert_eq!(weighted_overlap(&graph, &upper, &path(&["c", "e"])), 0.5);
+        let single = path(&["a"]);
+        assert_eq!(shared_edge_ratio(&graph, &single, &single), 1.0);
+
+        let points = parse_coordinates(&graph, "a 0 0\nb 1 1\nc 2 0\nd 1 -1\n\ne 3 0").unwrap();
+        let similarity = compare(&graph, &upper, &lower, Some(&points));
+        assert_eq!(similarity.divergence, Some(2f64.sqrt()));
+        assert!(compare(&graph, &upper, &lower, None).divergence.is_none());
+        assert!(parse_coordinates(&graph, "z 0 0").is_err());
+        assert!(parse_coordinates(&graph, "a 0").is_err());
+        assert!(parse_coordinates(&graph, "a 0 y").is_err());
+    }
+}
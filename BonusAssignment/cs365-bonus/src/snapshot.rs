@@ -0,0 +1,156 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::server::http::json_string;
+
+/// Everything a search knew at one moment, taken with [`SearchContext::snapshot`].
+///
+/// Stopping a search after a number of steps with [`find_shortest_path_within`] leaves
+/// its state in place, so a snapshot can be taken at any step.
+///
+/// ```
+/// use cs365_bonus::path::SearchContext;
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb z 2\na z 5".parse().unwrap();
+/// let (a, z) = (graph.get_node("a").unwrap(), graph.get_node("z").unwrap());
+/// let mut context = SearchContext::new();
+/// assert!(context.find_shortest_path_within(&graph, a, z, 1).is_err());
+/// let snapshot = context.snapshot();
+/// assert_eq!(snapshot.reached.len(), 3);
+/// // `a` was expanded and `b` settled, leaving `z` queued at the cost of the direct edge.
+/// assert_eq!(snapshot.queue, vec![(z, 5)]);
+/// assert!(snapshot.to_json(&graph).starts_with("{\"reached\":[{\"node\":\"a\""));
+/// ```
+///
+/// [`SearchContext::snapshot`]: crate::path::SearchContext::snapshot
+/// [`find_shortest_path_within`]: crate::path::SearchContext::find_shortest_path_within
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchSnapshot {
+    /// Every node the search has reached, in id order.
+    pub reached: Vec<ReachedNode>,
+    /// The queue's entries cheapest first, including stale ones for nodes that have since
+    /// been reached more cheaply.
+    pub queue: Vec<(NodeId, Weight)>,
+}
+
+/// One node's entry in a [`SearchSnapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReachedNode {
+    pub node: NodeId,
+    /// The cheapest cost found so far, final once the node is settled.
+    pub distance: Weight,
+    pub parent: Option<NodeId>,
+    pub settled: bool,
+}
+
+impl SearchSnapshot {
+    /// This snapshot as a JSON object with `reached` and `queue` arrays, naming nodes as
+    /// `graph` does.
+    pub fn to_json(&self, graph: &Graph) -> String {
+        let name = |node: NodeId| json_string(graph.get_node_name(node).unwrap_or(""));
+        let reached: Vec<String> = self
+            .reached
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{{\"node\":{},\"distance\":{},\"parent\":{},\"settled\":{}}}",
+                    name(entry.node),
+                    entry.distance,
+                    entry.parent.map_or_else(|| "null".to_string(), name),
+                    entry.settled
+                )
+            })
+            .collect();
+        let queue: Vec<String> = self
+            .queue
+            .iter()
+            .map(|&(node, cost)| format!("{{\"node\":{},\"cost\":{}}}", name(node), cost))
+            .collect();
+        format!(
+            "{{\"reached\":[{}],\"queue\":[{}]}}",
+            reached.join(","),
+            queue.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::SearchContext;
+    use crate::rng::Rng;
+    use crate::server::json::Value;
+
+    /// Read back a snapshot written by [`SearchSnapshot::to_json`].
+    fn from_json(graph: &Graph, json: &str) -> SearchSnapshot {
+        let value = Value::parse(json).unwrap();
+        let node = |value: &Value| graph.get_node(value.as_str().unwrap()).unwrap();
+        let number = |value: Option<&Value>| value.unwrap().as_f64().unwrap() as Weight;
+        let reached = value.get("reached").unwrap().as_array().unwrap();
+        let queue = value.get("queue").unwrap().as_array().unwrap();
+        SearchSnapshot {
+            reached: reached
+                .iter()
+                .map(|entry| ReachedNode {
+                    node: node(entry.get("node").unwrap()),
+                    distance: number(entry.get("distance")),
+                    parent: match entry.get("parent").unwrap() {
+                        Value::Null => None,
+                        parent => Some(node(parent)),
+                    },
+                    settled: entry.get("settled") == Some(&Value::Bool(true)),
+                })
+                .collect(),
+            queue: queue
+                .iter()
+                .map(|entry| (node(entry.get("node").unwrap()), number(entry.get("cost"))))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn json_round_trips_at_every_step() {
+        let mut rng = Rng::new(41);
+        for _ in 0..50 {
+            let n = 2 + rng.below(12);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                // Quotes and backslashes in names must survive the round trip.
+                graph.get_or_insert_node(&format!("n\"{}\\", i));
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (u, v) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                graph.add_bidirectional_edge(u, v, rng.below(10) as Weight);
+            }
+            let (start, end) = (NodeId::new(0), NodeId::new(rng.below(n)));
+            let tree = crate::shortest_path_tree(&graph, start);
+            let mut context = SearchContext::new();
+            let steps = rng.below(n + 1);
+            let finished = context.find_shortest_path_within(&graph, start, end, steps);
+            let snapshot = context.snapshot();
+            assert_eq!(from_json(&graph, &snapshot.to_json(&graph)), snapshot);
+
+            assert!(snapshot.reached.windows(2).all(|w| w[0].node < w[1].node));
+            assert!(snapshot.queue.windows(2).all(|w| w[0].1 <= w[1].1));
+            for entry in &snapshot.reached {
+                // Settled nodes are final, and everything else is an upper bound.
+                let best = tree.distance(entry.node).unwrap();
+                assert!(entry.distance >= best);
+                assert!(!entry.settled || entry.distance == best);
+                if let Some(parent) = entry.parent {
+                    let reached = snapshot.reached.iter().find(|e| e.node == parent);
+                    assert!(reached.is_some_and(|parent| parent.settled));
+                }
+            }
+            if let Ok(Some(path)) = finished {
+                assert_eq!(Some(path.cost), tree.distance(end));
+            }
+        }
+    }
+
+    #[test]
+    fn fresh_contexts_have_empty_snapshots() {
+        let graph: Graph = "a b 1".parse().unwrap();
+        let snapshot = SearchContext::new().snapshot();
+        assert_eq!(snapshot.reached, []);
+        assert_eq!(snapshot.to_json(&graph), "{\"reached\":[],\"queue\":[]}");
+    }
+}
@@ -0,0 +1,130 @@
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use crate::path::{dijkstra, shortest_path_tree};
+
+/// Build a greedy `stretch`-spanner and return the ids of the edges it keeps.
+///
+/// Edges are considered from cheapest to most expensive, and an edge is kept only if the
+/// spanner built so far has no path between its endpoints within `stretch` times its
+/// weight, both ways for undirected edges. Every shortest path in the result is then at most `stretch` times the original.
+pub fn greedy_spanner(graph: &Graph, stretch: f64) -> Vec<EdgeId> {
+    let mut edges: Vec<_> = graph.edges().collect();
+    edges.sort_by_key(|&(id, _, _, cost)| (cost, id));
+
+    let mut spanner = graph.edge_subgraph(|_| false);
+    let mut kept = Vec::new();
+    for (id, src, dest, cost) in edges {
+        let limit = (stretch * cost as f64).floor() as Weight;
+        let directed = graph.is_edge_directed(id) == Some(true);
+        let covered = |from, to| {
+            let tree = dijkstra(&spanner, from, Some(to), Some(limit), |_, _| true);
+            tree.distance(to).is_some_and(|d| d <= limit)
+        };
+        // Directed edges in the spanner may only cover an undirected edge one way.
+        if !covered(src, dest) || (!directed && !covered(dest, src)) {
+            if directed {
+                spanner.add_edge(src, dest, cost);
+            } else {
+                spanner.add_bidirectional_edge(src, dest, cost);
+            }
+            kept.push(id);
+        }
+    }
+    kept.sort_unstable();
+    kept
+}
+
+/// How much longer shortest paths became in a subgraph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StretchStats {
+    /// Pairs with a path of non-zero cost in the original graph.
+    pub pairs: usize,
+    /// Pairs that became unreachable in the subgraph.
+    pub disconnected: usize,
+    pub max: f64,
+    pub mean: f64,
+}
+
+/// Compare distances from each of `sources` in `original` and `sub`, which share node ids.
+pub fn stretch_stats(original: &Graph, sub: &Graph, sources: &[NodeId]) -> StretchStats {
+    let mut stats = StretchStats {
+        pairs: 0,
+        disconnected: 0,
+        max: 1.0,
+        mean: 0.0,
+    };
+    let mut sum = 0.0;
+    for &source in sources {
+        let before = shortest_path_tree(original, source);
+        let after = shortest_path_tree(sub, source);
+        for (b, a) in before.distances().iter().zip(after.distances().iter()) {
+            match (b, a) {
+                (Some(0), _) | (None, _) => {}
+                (Some(_), None) => {
+                    stats.pairs += 1;
+                    stats.disconnected += 1;
+                }
+                (Some(b), Some(a)) => {
+                    let ratio = *a as f64 / *b as f64;
+                    stats.pairs += 1;
+                    stats.max = stats.max.max(ratio);
+                    sum += ratio;
+                }
+            }
+        }
+    }
+    let connected = stats.pairs - stats.disconnected;
+    if connected > 0 {
+        stats.mean = sum / connected as f64;
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn spanners_keep_the_stretch_bound() {
+        let mut rng = Rng::new(9);
+        for _ in 0..30 {
+            let n = 2 + rng.below(12);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(4 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                let cost = 1 + rng.below(20) as Weight;
+                if rng.chance(0.2) {
+                    graph.add_edge(a, b, cost);
+                } else {
+                    graph.add_bidirectional_edge(a, b, cost);
+                }
+            }
+            let sources: Vec<NodeId> = graph.node_ids().collect();
+            for stretch in [1.0, 1.5, 3.0] {
+                let kept: HashSet<EdgeId> = greedy_spanner(&graph, stretch).into_iter().collect();
+                let spanner = graph.edge_subgraph(|id| kept.contains(&id));
+                let stats = stretch_stats(&graph, &spanner, &sources);
+                assert_eq!(stats.disconnected, 0);
+                assert!(stats.max <= stretch + 1e-9);
+                if stretch == 1.0 {
+                    assert_eq!(stats.max, 1.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn drops_edges_with_detours_in_the_bound() {
+        // The direct edge costs 5 and the way around 6.
+        let graph: Graph = "a b 2\nb c 4\na c 5".parse().unwrap();
+        assert_eq!(greedy_spanner(&graph, 1.0).len(), 3);
+        assert_eq!(
+            greedy_spanner(&graph, 1.2),
+            [EdgeId::new(0), EdgeId::new(1)]
+        );
+    }
+}
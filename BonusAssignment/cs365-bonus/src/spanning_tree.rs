@@ -0,0 +1,113 @@
+use crate::graph::{EdgeId, Graph, NodeId};
+use crate::rng::Rng;
+
+/// Sample a uniformly random spanning tree with Wilson's algorithm.
+///
+/// Edge directions and weights are ignored, and parallel edges count separately. A
+/// disconnected graph yields a uniformly random spanning forest with one tree per
+/// component. Returns the ids of the chosen edges.
+pub fn random_spanning_tree(graph: &Graph, rng: &mut Rng) -> Vec<EdgeId> {
+    let n = graph.node_count();
+    let mut adjacency: Vec<Vec<(NodeId, EdgeId)>> = vec![Vec::new(); n];
+    for (id, src, dest, _) in graph.edges() {
+        if src != dest {
+            adjacency[src.index()].push((dest, id));
+            adjacency[dest.index()].push((src, id));
+        }
+    }
+
+    let mut in_tree = vec![false; n];
+    let mut next: Vec<Option<(NodeId, EdgeId)>> = vec![None; n];
+    for root in component_roots(&adjacency) {
+        in_tree[root.index()] = true;
+    }
+
+    let mut tree = Vec::with_capacity(n.saturating_sub(1));
+    for start in 0..n {
+        // Random walk until the tree is hit. Overwriting `next` on each visit erases loops.
+        let mut u = start;
+        while !in_tree[u] {
+            let list = &adjacency[u];
+            let step = list[rng.below(list.len())];
+            next[u] = Some(step);
+            u = step.0.index();
+        }
+
+        let mut u = start;
+        while !in_tree[u] {
+            in_tree[u] = true;
+            let (v, id) = next[u].expect("walked nodes have a successor");
+            tree.push(id);
+            u = v.index();
+        }
+    }
+    tree
+}
+
+/// The lowest indexed node of every connected component.
+fn component_roots(adjacency: &[Vec<(NodeId, EdgeId)>]) -> Vec<NodeId> {
+    let mut seen = vec![false; adjacency.len()];
+    let mut roots = Vec::new();
+    let mut stack = Vec::new();
+    for root in 0..adjacency.len() {
+        if seen[root] {
+            continue;
+        }
+        seen[root] = true;
+        roots.push(NodeId::new(root));
+        stack.push(root);
+        while let Some(u) = stack.pop() {
+            for &(v, _) in adjacency[u].iter() {
+                if !seen[v.index()] {
+                    seen[v.index()] = true;
+                    stack.push(v.index());
+                }
+            }
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disjoint_set::DisjointSet;
+
+    #[test]
+    fn spans_every_component_without_cycles() {
+        let mut rng = Rng::new(7);
+        for _ in 0..50 {
+            let n = 1 + rng.below(12);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (rng.below(n), rng.below(n));
+                graph.add_edge(NodeId::new(a), NodeId::new(b), 1);
+            }
+            let tree = random_spanning_tree(&graph, &mut rng);
+            let mut sets = DisjointSet::new(n);
+            for &id in &tree {
+                let (src, dest) = graph.edge_endpoints(id).unwrap();
+                assert!(sets.union(src.index(), dest.index()));
+            }
+            assert_eq!(sets.set_count(), graph.connected_components().len());
+        }
+    }
+
+    #[test]
+    fn each_tree_of_a_triangle_is_equally_likely() {
+        let graph: Graph = "a b 1\nb c 1\nc a 1".parse().unwrap();
+        let mut rng = Rng::new(8);
+        let mut counts = [0; 3];
+        for _ in 0..3000 {
+            let tree = random_spanning_tree(&graph, &mut rng);
+            assert_eq!(tree.len(), 2);
+            // Each tree leaves out exactly one edge.
+            let left_out = (0..3).find(|&i| !tree.contains(&EdgeId::new(i))).unwrap();
+            counts[left_out] += 1;
+        }
+        assert!(counts.iter().all(|&count| (850..1150).contains(&count)));
+    }
+}
@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// Time spent in each nested phase of a run on the thread that started recording.
+///
+/// Phases are marked with [`span`], which costs one thread-local check when nothing is
+/// recording. [`write_folded`](Recording::write_folded) writes the result in the folded
+/// stack format read by flamegraph tools such as `inferno-flamegraph` and speedscope.
+///
+/// ```
+/// use cs365_bonus::spans::{self, span};
+///
+/// spans::start();
+/// {
+///     let _load = span("load");
+///     let _parse = span("parse");
+/// }
+/// let recording = spans::finish().unwrap();
+/// let mut folded = Vec::new();
+/// recording.write_folded(&mut folded).unwrap();
+/// assert!(String::from_utf8(folded).unwrap().contains("load;parse "));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    /// Time spent in each stack of phases, excluding the phases nested inside it.
+    exclusive: BTreeMap<String, Duration>,
+}
+
+#[derive(Default)]
+struct Recorder {
+    stack: Vec<Open>,
+    recording: Recording,
+}
+
+struct Open {
+    name: &'static str,
+    started: Instant,
+    nested: Duration,
+}
+
+/// Start recording spans on this thread, discarding any earlier recording.
+pub fn start() {
+    RECORDER.with(|recorder| *recorder.borrow_mut() = Some(Recorder::default()));
+}
+
+/// Stop recording on this thread and return what was recorded, if anything was.
+pub fn finish() -> Option<Recording> {
+    RECORDER.with(|recorder| recorder.borrow_mut().take().map(|r| r.recording))
+}
+
+/// Mark the phase called `name` until the returned guard is dropped.
+pub fn span(name: &'static str) -> Span {
+    let recording = RECORDER.with(|recorder| match recorder.borrow_mut().as_mut() {
+        Some(recorder) => {
+            recorder.stack.push(Open {
+                name,
+                started: Instant::now(),
+                nested: Duration::ZERO,
+            });
+            true
+        }
+        None => false,
+    });
+    Span { recording }
+}
+
+/// Ends its phase when dropped.
+#[must_use = "the span ends as soon as this is dropped"]
+pub struct Span {
+    recording: bool,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if !self.recording {
+            return;
+        }
+        RECORDER.with(|recorder| {
+            let mut recorder = recorder.borrow_mut();
+            let recorder = match recorder.as_mut() {
+                Some(recorder) => recorder,
+                None => return,
+            };
+            let open = match recorder.stack.pop() {
+                Some(open) => open,
+                None => return,
+            };
+            let elapsed = open.started.elapsed();
+            let mut path: Vec<&str> = recorder.stack.iter().map(|open| open.name).collect();
+            path.push(open.name);
+            *recorder
+                .recording
+                .exclusive
+                .entry(path.join(";"))
+                .or_default() += elapsed.saturating_sub(open.nested);
+            if let Some(parent) = recorder.stack.last_mut() {
+                parent.nested += elapsed;
+            }
+        });
+    }
+}
+
+impl Recording {
+    /// Write one `phase;nested-phase microseconds` line per stack of phases.
+    pub fn write_folded(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        for (stack, time) in &self.exclusive {
+            writeln!(writer, "{} {}", stack, time.as_micros())?;
+        }
+        writer.flush()
+    }
+
+    /// The total time spent in phases, nested or not.
+    pub fn total(&self) -> Duration {
+        self.exclusive.values().sum()
+    }
+}
@@ -0,0 +1,254 @@
+use crate::graph::Graph;
+use crate::rng::Rng;
+
+/// The graph Laplacian `L = D - W`, applied without ever building the matrix.
+///
+/// Edge direction and self-loops are ignored and parallel edges are summed. Unweighted
+/// Laplacians treat every edge as weight 1.
+pub(crate) struct Laplacian {
+    adjacency: Vec<Vec<(usize, f64)>>,
+    degree: Vec<f64>,
+}
+
+impl Laplacian {
+    pub(crate) fn new(graph: &Graph, weighted: bool) -> Self {
+        let mut adjacency: Vec<Vec<(usize, f64)>> = vec![Vec::new(); graph.node_count()];
+        for (_, src, dest, cost) in graph.edges() {
+            if src == dest {
+                continue;
+            }
+            let w = if weighted { cost as f64 } else { 1.0 };
+            adjacency[src.index()].push((dest.index(), w));
+            adjacency[dest.index()].push((src.index(), w));
+        }
+        for list in adjacency.iter_mut() {
+            list.sort_unstable_by_key(|&(node, _)| node);
+            list.dedup_by(|next, kept| {
+                if next.0 == kept.0 {
+                    kept.1 += next.1;
+                    true
+                } else {
+                    false
+                }
+            });
+        }
+        let degree = adjacency
+            .iter()
+            .map(|list| list.iter().map(|&(_, w)| w).sum())
+            .collect();
+        Self { adjacency, degree }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.degree.len()
+    }
+
+    /// An upper bound on the largest eigenvalue, by the Gershgorin circle theorem.
+    pub(crate) fn spectral_bound(&self) -> f64 {
+        2.0 * self.degree.iter().copied().fold(0.0, f64::max)
+    }
+
+    /// Compute `y = L x`.
+    pub(crate) fn apply(&self, x: &[f64], y: &mut [f64]) {
+        for (i, list) in self.adjacency.iter().enumerate() {
+            y[i] = list.iter().map(|&(j, w)| w * (x[i] - x[j])).sum();
+        }
+    }
+}
+
+/// The second smallest Laplacian eigenvalue and its eigenvector.
+#[derive(Debug, Clone)]
+pub struct Fiedler {
+    /// The algebraic connectivity, zero exactly when the graph is disconnected.
+    pub value: f64,
+    /// A unit vector with one entry per node.
+    pub vector: Vec<f64>,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+pub(crate) fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+pub(crate) fn normalize(x: &mut [f64]) -> f64 {
+    let norm = dot(x, x).sqrt();
+    if norm > 0.0 {
+        x.iter_mut().for_each(|v| *v /= norm);
+    }
+    norm
+}
+
+/// Remove the components of `x` along each of the unit vectors in `basis`.
+pub(crate) fn orthogonalize(x: &mut [f64], basis: &[Vec<f64>]) {
+    for b in basis {
+        let d = dot(x, b);
+        x.iter_mut().zip(b.iter()).for_each(|(v, b)| *v -= d * b);
+    }
+}
+
+/// Find eigenvectors of `laplacian` for its smallest eigenvalues above the ones in `basis`.
+///
+/// Power iteration runs on `cI - L`, whose largest eigenvalues are the smallest of `L`, while
+/// projecting out the already known eigenvectors in `basis`.
+pub(crate) fn smallest_eigenpair(
+    laplacian: &Laplacian,
+    basis: &[Vec<f64>],
+    max_iterations: usize,
+    tolerance: f64,
+    rng: &mut Rng,
+) -> Fiedler {
+    let n = laplacian.len();
+    let shift = laplacian.spectral_bound().max(1.0);
+    let mut x: Vec<f64> = (0..n).map(|_| rng.next_f64() - 0.5).collect();
+    orthogonalize(&mut x, basis);
+    normalize(&mut x);
+
+    let mut y = vec![0.0; n];
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < max_iterations {
+        iterations += 1;
+        laplacian.apply(&x, &mut y);
+        y.iter_mut()
+            .zip(x.iter())
+            .for_each(|(y, x)| *y = shift * x - *y);
+        orthogonalize(&mut y, basis);
+        if normalize(&mut y) == 0.0 {
+            break;
+        }
+
+        let change: f64 = x
+            .iter()
+            .zip(y.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt();
+        std::mem::swap(&mut x, &mut y);
+        if change < tolerance {
+            converged = true;
+            break;
+        }
+    }
+
+    laplacian.apply(&x, &mut y);
+    Fiedler {
+        value: dot(&x, &y),
+        vector: x,
+        iterations,
+        converged,
+    }
+}
+
+/// Estimate the Fiedler value and vector by power iteration, or `None` with fewer than two nodes.
+pub fn fiedler(
+    graph: &Graph,
+    weighted: bool,
+    max_iterations: usize,
+    tolerance: f64,
+    seed: u64,
+) -> Option<Fiedler> {
+    let n = graph.node_count();
+    if n < 2 {
+        return None;
+    }
+
+    let laplacian = Laplacian::new(graph, weighted);
+    let constant = vec![1.0 / (n as f64).sqrt(); n];
+    Some(smallest_eigenpair(
+        &laplacian,
+        &[constant],
+        max_iterations,
+        tolerance,
+        &mut Rng::new(seed),
+    ))
+}
+
+/// A Laplacian eigenmap: the eigenpairs for the `dimensions` smallest eigenvalues of the
+/// Laplacian above the trivial zero, whose vectors give each node `dimensions` coordinates
+/// that put nodes joined by heavy edges close together. The eigenpairs are found one after
+/// another by the same power iteration as [`fiedler`], each projecting out the ones before
+/// it. Returns `None` unless there are more nodes than dimensions.
+///
+/// ```
+/// use cs365_bonus::spectral::laplacian_eigenmap;
+///
+/// // A ring of six nodes lies on a circle in its first two coordinates.
+/// let ring: cs365_bonus::Graph = "a b 1\nb c 1\nc d 1\nd e 1\ne f 1\nf a 1".parse().unwrap();
+/// let pairs = laplacian_eigenmap(&ring, 2, false, 100_000, 1e-12, 0).unwrap();
+/// assert!(pairs.iter().all(|pair| (pair.value - 1.0).abs() < 1e-6));
+/// let radii: Vec<f64> = (0..6).map(|i| pairs[0].vector[i].hypot(pairs[1].vector[i])).collect();
+/// assert!(radii.iter().all(|r| (r - radii[0]).abs() < 1e-4));
+/// ```
+pub fn laplacian_eigenmap(
+    graph: &Graph,
+    dimensions: usize,
+    weighted: bool,
+    max_iterations: usize,
+    tolerance: f64,
+    seed: u64,
+) -> Option<Vec<Fiedler>> {
+    let n = graph.node_count();
+    if n <= dimensions {
+        return None;
+    }
+
+    let laplacian = Laplacian::new(graph, weighted);
+    let mut rng = Rng::new(seed);
+    let mut basis = vec![vec![1.0 / (n as f64).sqrt(); n]];
+    let mut pairs = Vec::with_capacity(dimensions);
+    for _ in 0..dimensions {
+        let pair = smallest_eigenpair(&laplacian, &basis, max_iterations, tolerance, &mut rng);
+        basis.push(pair.vector.clone());
+        pairs.push(pair);
+    }
+    Some(pairs)
+}
+
+/// Split the nodes by the sign of their Fiedler vector entry.
+pub fn spectral_bisection(vector: &[f64]) -> Vec<bool> {
+    vector.iter().map(|&v| v >= 0.0).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fiedler_of(text: &str) -> Fiedler {
+        let graph: Graph = text.parse().unwrap();
+        fiedler(&graph, false, 100_000, 1e-12, 1).unwrap()
+    }
+
+    #[test]
+    fn fiedler_values_match_known_spectra() {
+        // A path on four nodes has 2 - 2cos(pi/4), and a complete graph on four has 4.
+        let path = fiedler_of("a b 1\nb c 1\nc d 1");
+        assert!(path.converged);
+        assert!((path.value - (2.0 - 2f64.sqrt())).abs() < 1e-6);
+        let complete = fiedler_of("a b 1\na c 1\na d 1\nb c 1\nb d 1\nc d 1");
+        assert!((complete.value - 4.0).abs() < 1e-6);
+        let apart = fiedler_of("a b 1\nc d 1");
+        assert!(apart.value.abs() < 1e-6);
+
+        let single: Graph = "a a 1".parse().unwrap();
+        assert!(fiedler(&single, false, 10, 1e-12, 1).is_none());
+    }
+
+    #[test]
+    fn bisection_cuts_the_bridge() {
+        // Two triangles joined by one edge.
+        let graph: Graph = "a b 1\nb c 1\nc a 1\nc x 1\nx y 1\ny z 1\nz x 1"
+            .parse()
+            .unwrap();
+        let pair = fiedler(&graph, false, 100_000, 1e-12, 2).unwrap();
+        let laplacian = Laplacian::new(&graph, false);
+        let mut y = vec![0.0; graph.node_count()];
+        laplacian.apply(&pair.vector, &mut y);
+        for (ly, x) in y.iter().zip(&pair.vector) {
+            assert!((ly - pair.value * x).abs() < 1e-6);
+        }
+        let sides = spectral_bisection(&pair.vector);
+        assert!(sides[..3].iter().all(|&side| side == sides[0]));
+        assert!(sides[3..].iter().all(|&side| side != sides[0]));
+    }
+}
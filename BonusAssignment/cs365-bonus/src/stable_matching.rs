@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+/// One side's preference lists: each person's name and the people on the other side they
+/// would accept, best first.
+#[derive(Debug, Clone)]
+pub struct Preferences {
+    names: Vec<String>,
+    lists: Vec<Vec<String>>,
+}
+
+impl Preferences {
+    /// Read one person per line as their name followed by the names they would accept, best
+    /// first. Blank lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut names = Vec::new();
+        let mut lists = Vec::new();
+        let mut seen = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap_or_default();
+            if seen.insert(name.to_string(), i).is_some() {
+                return Err(format!("line {}: '{}' is listed twice", i + 1, name));
+            }
+            let list: Vec<String> = words.map(str::to_string).collect();
+            for (j, choice) in list.iter().enumerate() {
+                if list[..j].contains(choice) {
+                    return Err(format!("line {}: '{}' is ranked twice", i + 1, choice));
+                }
+            }
+            names.push(name.to_string());
+            lists.push(list);
+        }
+        Ok(Self { names, lists })
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn name(&self, person: usize) -> &str {
+        &self.names[person]
+    }
+}
+
+/// Two sides' preferences over each other, by index into each side. A pair can only be
+/// matched if each lists the other.
+///
+/// ```
+/// use cs365_bonus::stable_matching::{Market, Preferences};
+///
+/// let students = Preferences::parse("ann mit cmu\nbob mit cmu\ncat cmu mit").unwrap();
+/// let schools = Preferences::parse("mit bob ann cat\ncmu ann cat bob").unwrap();
+/// let market = Market::new(&students, &schools).unwrap();
+/// let matching = market.gale_shapley();
+/// // bob beats ann at mit, so ann falls back to cmu, which prefers ann to cat.
+/// assert_eq!(matching.partner, [Some(1), Some(0), None]);
+/// assert!(market.blocking_pairs(&matching.partner).is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Market {
+    /// Each proposer's acceptable receivers, best first.
+    choices: Vec<Vec<usize>>,
+    /// How each receiver ranks each proposer, lower being better, if at all.
+    receiver_rank: Vec<Vec<Option<usize>>>,
+    /// How each proposer ranks each receiver, if at all.
+    proposer_rank: Vec<Vec<Option<usize>>>,
+}
+
+/// The outcome of [`Market::gale_shapley`].
+#[derive(Debug, Clone)]
+pub struct StableMatching {
+    /// The receiver each proposer is matched with.
+    pub partner: Vec<Option<usize>>,
+    /// How many proposals were made in all.
+    pub proposals: usize,
+}
+
+impl Market {
+    /// Resolve the names in each side's lists against the other side, failing on any name
+    /// the other side doesn't have.
+    pub fn new(proposers: &Preferences, receivers: &Preferences) -> Result<Self, String> {
+        let ranks = |side: &Preferences, other: &Preferences| {
+            let index: HashMap<&str, usize> = other
+                .names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.as_str(), i))
+                .collect();
+            side.lists
+                .iter()
+                .enumerate()
+                .map(|(person, list)| {
+                    let mut rank = vec![None; other.len()];
+                    for (position, choice) in list.iter().enumerate() {
+                        let choice = *index.get(choice.as_str()).ok_or_else(|| {
+                            format!(
+                                "'{}' ranks '{}', who isn't on the other side",
+                                side.name(person),
+                                choice
+                            )
+                        })?;
+                        rank[choice] = Some(position);
+                    }
+                    Ok(rank)
+                })
+                .collect::<Result<Vec<_>, String>>()
+        };
+        let proposer_rank = ranks(proposers, receivers)?;
+        let receiver_rank = ranks(receivers, proposers)?;
+        let choices = proposer_rank
+            .iter()
+            .enumerate()
+            .map(|(proposer, rank)| {
+                let mut list: Vec<usize> = (0..rank.len())
+                    .filter(|&receiver| {
+                        rank[receiver].is_some() && receiver_rank[receiver][proposer].is_some()
+                    })
+                    .collect();
+                list.sort_by_key(|&receiver| rank[receiver]);
+                list
+            })
+            .collect();
+        Ok(Self {
+            choices,
+            receiver_rank,
+            proposer_rank,
+        })
+    }
+
+    /// The stable matching best for every proposer at once, by Gale and Shapley's deferred
+    /// acceptance: free proposers propose down their lists, and each receiver holds on to
+    /// the best proposal so far, letting go of the one it held. It takes O(n m) time for
+    /// `n` proposers and `m` receivers.
+    pub fn gale_shapley(&self) -> StableMatching {
+        let mut partner = vec![None; self.choices.len()];
+        let mut held: Vec<Option<usize>> = vec![None; self.receiver_rank.len()];
+        let mut next = vec![0; self.choices.len()];
+        let mut free: Vec<usize> = (0..self.choices.len()).rev().collect();
+        let mut proposals = 0;
+        while let Some(proposer) = free.pop() {
+            let receiver = match self.choices[proposer].get(next[proposer]) {
+                Some(&receiver) => receiver,
+                None => continue,
+            };
+            next[proposer] += 1;
+            proposals += 1;
+            let rank = &self.receiver_rank[receiver];
+            match held[receiver] {
+                Some(current) if rank[current] < rank[proposer] => free.push(proposer),
+                current => {
+                    if let Some(current) = current {
+                        partner[current] = None;
+                        free.push(current);
+                    }
+                    held[receiver] = Some(proposer);
+                    partner[proposer] = Some(receiver);
+                }
+            }
+        }
+        StableMatching { partner, proposals }
+    }
+
+    /// The pairs who would both rather have each other than whom `partner` gives them, as
+    /// (proposer, receiver). A matching is stable if there are none.
+    pub fn blocking_pairs(&self, partner: &[Option<usize>]) -> Vec<(usize, usize)> {
+        let mut held = vec![None; self.receiver_rank.len()];
+        for (proposer, receiver) in partner.iter().enumerate() {
+            if let Some(receiver) = *receiver {
+                held[receiver] = Some(proposer);
+            }
+        }
+        let mut pairs = Vec::new();
+        for (proposer, choices) in self.choices.iter().enumerate() {
+            let ranks = &self.proposer_rank[proposer];
+            for &receiver in choices {
+                // Choices are best first, so none after the current partner is better.
+                if partner[proposer].is_some_and(|current| ranks[current] <= ranks[receiver]) {
+                    break;
+                }
+                let rank = &self.receiver_rank[receiver];
+                if held[receiver].is_none_or(|current| rank[proposer] < rank[current]) {
+                    pairs.push((proposer, receiver));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Preferences of `count` people over `others`, each accepting a random subset in a
+    /// random order.
+    fn random_side(
+        rng: &mut Rng,
+        prefix: &str,
+        count: usize,
+        other: &str,
+        others: usize,
+    ) -> Preferences {
+        let text: Vec<String> = (0..count)
+            .map(|i| {
+                let mut list: Vec<usize> = (0..others).filter(|_| rng.chance(0.8)).collect();
+                for j in (1..list.len()).rev() {
+                    list.swap(j, rng.below(j + 1));
+                }
+                let list: Vec<String> = list.iter().map(|j| format!("{}{}", other, j)).collect();
+                format!("{}{} {}", prefix, i, list.join(" "))
+            })
+            .collect();
+        Preferences::parse(&text.join("\n")).unwrap()
+    }
+
+    #[test]
+    fn gale_shapley_leaves_no_blocking_pairs() {
+        let mut rng = Rng::new(5);
+        for _ in 0..200 {
+            let (n, m) = (1 + rng.below(8), 1 + rng.below(8));
+            let proposers = random_side(&mut rng, "p", n, "r", m);
+            let receivers = random_side(&mut rng, "r", m, "p", n);
+            let market = Market::new(&proposers, &receivers).unwrap();
+            let matching = market.gale_shapley();
+            assert!(market.blocking_pairs(&matching.partner).is_empty());
+            let mut taken = vec![false; m];
+            for &receiver in matching.partner.iter().flatten() {
+                assert!(!std::mem::replace(&mut taken[receiver], true));
+            }
+            // Leaving everyone single is blocked by any acceptable pair.
+            let single = vec![None; n];
+            let acceptable = market.choices.iter().any(|choices| !choices.is_empty());
+            assert_eq!(market.blocking_pairs(&single).is_empty(), !acceptable);
+        }
+    }
+}
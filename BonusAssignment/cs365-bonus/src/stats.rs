@@ -0,0 +1,177 @@
+/// The mean and spread of a sample of measurements.
+///
+/// ```
+/// use cs365_bonus::stats::Summary;
+///
+/// let summary = Summary::of(&[9.0, 10.0, 11.0, 10.0]);
+/// assert_eq!(summary.mean, 10.0);
+/// // A 95% confidence interval for the mean, from Student's t with 3 degrees of freedom.
+/// assert!((summary.confidence_interval(0.95) - 1.299).abs() < 1e-3);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    /// The sample variance, dividing by `count - 1`.
+    pub variance: f64,
+}
+
+impl Summary {
+    pub fn of(sample: &[f64]) -> Self {
+        let count = sample.len();
+        let mean = sample.iter().sum::<f64>() / count.max(1) as f64;
+        let squares: f64 = sample.iter().map(|x| (x - mean) * (x - mean)).sum();
+        Self {
+            count,
+            mean,
+            variance: squares / count.saturating_sub(1).max(1) as f64,
+        }
+    }
+
+    /// The half-width of a confidence interval for the mean at `level`, e.g. 0.95, or
+    /// NaN with fewer than two measurements.
+    pub fn confidence_interval(&self, level: f64) -> f64 {
+        if self.count < 2 {
+            return f64::NAN;
+        }
+        let df = (self.count - 1) as f64;
+        t_quantile(0.5 + level / 2.0, df) * (self.variance / self.count as f64).sqrt()
+    }
+}
+
+/// The result of comparing the means of two samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TTest {
+    pub t: f64,
+    /// Welch–Satterthwaite degrees of freedom, usually fractional.
+    pub df: f64,
+    /// The two-sided p-value: how likely a difference at least this large would be if
+    /// the means were equal.
+    pub p: f64,
+}
+
+/// Welch's t-test of whether `a` and `b` have different means, not assuming equal
+/// variances. Returns `None` unless both have at least two measurements and some spread.
+pub fn welch_t_test(a: &Summary, b: &Summary) -> Option<TTest> {
+    if a.count < 2 || b.count < 2 {
+        return None;
+    }
+    let (va, vb) = (a.variance / a.count as f64, b.variance / b.count as f64);
+    if va + vb == 0.0 {
+        return None;
+    }
+    let t = (a.mean - b.mean) / (va + vb).sqrt();
+    let df = (va + vb).powi(2) / (va * va / (a.count - 1) as f64 + vb * vb / (b.count - 1) as f64);
+    Some(TTest {
+        t,
+        df,
+        p: t_two_sided(t, df),
+    })
+}
+
+/// `P(|T| >= |t|)` for Student's t distribution with `df` degrees of freedom.
+fn t_two_sided(t: f64, df: f64) -> f64 {
+    incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// The `p` quantile of Student's t distribution for `p` above 0.5, found by bisection.
+fn t_quantile(p: f64, df: f64) -> f64 {
+    let tail = 2.0 * (1.0 - p);
+    let (mut low, mut high) = (0.0, 1.0);
+    while t_two_sided(high, df) > tail {
+        high *= 2.0;
+    }
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if t_two_sided(mid, df) > tail {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// The regularized incomplete beta function `I_x(a, b)`, by its continued fraction.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The fraction converges quickly on this side, and symmetry covers the other.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Lentz's method for the continued fraction of the incomplete beta function.
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    d = 1.0 / if d.abs() < TINY { TINY } else { d };
+    let mut result = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        for step in [even, odd] {
+            d = 1.0 + step * d;
+            d = 1.0 / if d.abs() < TINY { TINY } else { d };
+            c = 1.0 + step / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            result *= c * d;
+        }
+        if (c * d - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    result
+}
+
+/// `ln Γ(x)` for positive `x`, by the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series: f64 = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c / (x + 1.0 + i as f64))
+        .sum();
+    -tmp + (2.506_628_274_631_000_5 * (1.000_000_000_190_015 + series) / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_t_tables() {
+        assert!((t_two_sided(2.0, 10.0) - 0.07339).abs() < 1e-4);
+        assert!((t_quantile(0.975, 10.0) - 2.228).abs() < 1e-3);
+        assert!((t_quantile(0.975, 1000.0) - 1.962).abs() < 1e-3);
+
+        let fast = Summary::of(&[1.0, 1.1, 0.9, 1.0, 1.05]);
+        let slow = Summary::of(&[2.0, 2.2, 1.9, 2.1, 2.0]);
+        let test = welch_t_test(&fast, &slow).unwrap();
+        assert!(test.t < 0.0 && test.p < 1e-6);
+        let same = welch_t_test(&fast, &fast).unwrap();
+        assert!((same.p - 1.0).abs() < 1e-9);
+        assert!(welch_t_test(&Summary::of(&[1.0]), &slow).is_none());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::decimal::FixedPoint;
+use crate::disjoint_set::DisjointSet;
+use crate::graph::{Graph, Weight};
+use crate::parse::parse_timed_edge;
+use crate::path::{find_shortest_path, Path};
+
+/// One time-stamped edge of a stream, in the edge list format with a required `@time`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub src: String,
+    pub dest: String,
+    pub cost: Weight,
+    pub directed: bool,
+    pub time: Weight,
+}
+
+impl Event {
+    /// Read a `src dest cost [->|<->] @time` line, with decimal costs read with `scale`.
+    pub fn parse(line: &str, scale: FixedPoint) -> Result<Self, String> {
+        let (src, dest, cost, directed, time) =
+            parse_timed_edge(line.trim(), scale).map_err(|kind| kind.to_string())?;
+        let time = time.ok_or("streamed edges need an '@time'")?;
+        Ok(Self {
+            src: src.to_string(),
+            dest: dest.to_string(),
+            cost,
+            directed,
+            time,
+        })
+    }
+}
+
+/// Counts over the edges in a [`SlidingWindow`], ignoring direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowStats {
+    /// Nodes with at least one edge in the window.
+    pub nodes: usize,
+    pub edges: usize,
+    /// Connected components among those nodes.
+    pub components: usize,
+    /// The nodes in the largest of them.
+    pub largest: usize,
+}
+
+/// The graph of the edges seen in the last `width` time units of a stream, so at time
+/// `now`, those stamped after `now - width`.
+///
+/// Edges have to arrive in time order, so the ones that have expired are always the
+/// oldest, and they are hidden behind a view rather than deleted. Once they outnumber
+/// the live ones, the graph is rebuilt from the live edges alone, keeping memory in
+/// proportion to the window.
+///
+/// ```
+/// use cs365_bonus::streaming::{Event, SlidingWindow};
+/// use cs365_bonus::decimal::FixedPoint;
+///
+/// let mut window = SlidingWindow::new(10);
+/// for line in ["a b 1 @0", "b z 1 @4", "a z 5 @9"] {
+///     window.insert(Event::parse(line, FixedPoint::INTEGER).unwrap()).unwrap();
+/// }
+/// assert_eq!(window.shortest_path("a", "z").unwrap().cost, 2);
+/// window.advance(12);
+/// assert_eq!(window.shortest_path("a", "z").unwrap().cost, 5);
+/// assert_eq!(window.stats().edges, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SlidingWindow {
+    width: Weight,
+    now: Option<Weight>,
+    graph: Graph,
+    /// The time of each edge, by edge id.
+    times: Vec<Weight>,
+    /// The id of the oldest edge still in the window.
+    first_live: usize,
+}
+
+impl SlidingWindow {
+    pub fn new(width: Weight) -> Self {
+        Self {
+            width,
+            now: None,
+            graph: Graph::new(),
+            times: Vec::new(),
+            first_live: 0,
+        }
+    }
+
+    /// The latest time seen, if any.
+    pub fn now(&self) -> Option<Weight> {
+        self.now
+    }
+
+    /// Add `event`, moving the window up to its time. Fails if it is older than the latest
+    /// time seen or has a negative cost.
+    pub fn insert(&mut self, event: Event) -> Result<(), String> {
+        if self.now.is_some_and(|now| event.time < now) {
+            return Err(format!(
+                "the edge at {} arrived after time {}",
+                event.time,
+                self.now.unwrap_or_default()
+            ));
+        }
+        if event.cost < 0 {
+            return Err(format!("the edge at {} has a negative cost", event.time));
+        }
+        self.advance(event.time);
+        let src = self.graph.get_or_insert_node(&event.src);
+        let dest = self.graph.get_or_insert_node(&event.dest);
+        if event.directed {
+            self.graph.add_edge(src, dest, event.cost);
+        } else {
+            self.graph.add_bidirectional_edge(src, dest, event.cost);
+        }
+        self.times.push(event.time);
+        Ok(())
+    }
+
+    /// Move the window up to `now`, expiring the edges it leaves behind. Going back in time
+    /// does nothing.
+    pub fn advance(&mut self, now: Weight) {
+        if self.now.is_some_and(|latest| now <= latest) {
+            return;
+        }
+        self.now = Some(now);
+        let cutoff = now.saturating_sub(self.width);
+        while self
+            .times
+            .get(self.first_live)
+            .is_some_and(|&t| t <= cutoff)
+        {
+            self.first_live += 1;
+        }
+        if self.first_live * 2 > self.times.len() {
+            self.compact();
+        }
+    }
+
+    /// Rebuild the graph from the live edges, dropping nodes left without any.
+    fn compact(&mut self) {
+        let mut graph = Graph::new();
+        for (id, src, dest, cost) in self.graph.edges().skip(self.first_live) {
+            let src = graph.get_or_insert_node(self.graph.get_node_name(src).unwrap_or(""));
+            let dest = graph.get_or_insert_node(self.graph.get_node_name(dest).unwrap_or(""));
+            if self.graph.is_edge_directed(id) == Some(true) {
+                graph.add_edge(src, dest, cost);
+            } else {
+                graph.add_bidirectional_edge(src, dest, cost);
+            }
+        }
+        self.graph = graph;
+        self.times.drain(..self.first_live);
+        self.first_live = 0;
+    }
+
+    pub fn stats(&self) -> WindowStats {
+        let mut sets = DisjointSet::new(self.graph.node_count());
+        let mut active = vec![false; self.graph.node_count()];
+        let mut edges = 0;
+        for (_, src, dest, _) in self.graph.edges().skip(self.first_live) {
+            active[src.index()] = true;
+            active[dest.index()] = true;
+            sets.union(src.index(), dest.index());
+            edges += 1;
+        }
+        let nodes: Vec<usize> = (0..active.len()).filter(|&i| active[i]).collect();
+        let components = nodes.iter().filter(|&&i| sets.find(i) == i).count();
+        let largest = nodes.iter().map(|&i| sets.set_size(i)).max().unwrap_or(0);
+        WindowStats {
+            nodes: nodes.len(),
+            edges,
+            components,
+            largest,
+        }
+    }
+
+    /// The shortest path between the named nodes over the edges in the window, as node ids
+    /// of [`graph`](Self::graph), valid until the window next moves.
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Path> {
+        let (start, end) = (self.graph.get_node(from)?, self.graph.get_node(to)?);
+        let first = self.first_live;
+        let view = self
+            .graph
+            .filter_view(|_| true, |_, edge| edge.id.index() >= first);
+        find_shortest_path(&view, start, end)
+    }
+
+    /// Every node and edge kept, including expired edges not yet compacted away.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn window_matches_a_graph_of_recent_edges() {
+        let mut rng = Rng::new(11);
+        let mut window = SlidingWindow::new(8);
+        let mut events = Vec::new();
+        let mut time = 0;
+        for _ in 0..300 {
+            time += rng.below(3) as Weight;
+            let event = Event {
+                src: rng.below(6).to_string(),
+                dest: rng.below(6).to_string(),
+                cost: rng.below(5) as Weight,
+                directed: rng.chance(0.3),
+                time,
+            };
+            events.push(event.clone());
+            window.insert(event).unwrap();
+
+            let recent: Vec<&Event> = events.iter().filter(|e| e.time > time - 8).collect();
+            let mut graph = Graph::new();
+            for e in &recent {
+                let (src, dest) = (
+                    graph.get_or_insert_node(&e.src),
+                    graph.get_or_insert_node(&e.dest),
+                );
+                if e.directed {
+                    graph.add_edge(src, dest, e.cost);
+                } else {
+                    graph.add_bidirectional_edge(src, dest, e.cost);
+                }
+            }
+            let stats = window.stats();
+            assert_eq!(
+                (stats.nodes, stats.edges),
+                (graph.node_count(), recent.len())
+            );
+            let components = graph.connected_components();
+            assert_eq!(stats.components, components.len());
+            for from in graph.node_ids() {
+                for to in graph.node_ids() {
+                    let name = |node| graph.get_node_name(node).unwrap();
+                    let expected = find_shortest_path(&graph, from, to).map(|path| path.cost);
+                    let found = window.shortest_path(name(from), name(to));
+                    assert_eq!(found.map(|path| path.cost), expected);
+                }
+            }
+        }
+    }
+}
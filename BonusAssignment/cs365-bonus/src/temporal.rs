@@ -0,0 +1,257 @@
+use crate::arena::ParseArena;
+use crate::decimal::FixedPoint;
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use crate::parse::{parse_timed_edge, ParseErrorKind, ParseGraphError};
+use crate::path::Path;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::str::FromStr;
+
+/// A graph whose edges may be stamped with the time they are available, read from edge
+/// lines ending in `@time`. Edges without a time are available at any time.
+#[derive(Debug, Clone)]
+pub struct TemporalGraph {
+    graph: Graph,
+    times: Vec<Option<Weight>>,
+}
+
+/// A time-respecting path: it takes its timed edges at non-decreasing times.
+#[derive(Debug)]
+pub struct Journey {
+    pub path: Path,
+    /// The time each edge of the path is taken at, or `None` for untimed edges.
+    pub times: Vec<Option<Weight>>,
+    /// When the journey reaches its end: the time of its last timed edge, or the start of
+    /// the window if it has none.
+    pub arrival: Weight,
+}
+
+/// Parses the edge list format, with an optional `@time` after each edge's cost and
+/// direction.
+///
+/// ```
+/// use cs365_bonus::temporal::TemporalGraph;
+///
+/// // The bus from b to z leaves before the one from a gets to b.
+/// let network: TemporalGraph = "a b 5 @10\nb z 5 @8\nb z 9 @12".parse().unwrap();
+/// let node = |name| network.graph().get_node(name).unwrap();
+/// let journey = network.earliest_arrival(node("a"), node("z"), 0, 100).unwrap();
+/// assert_eq!((journey.arrival, journey.path.cost), (12, 14));
+/// assert!(network.earliest_arrival(node("a"), node("z"), 0, 11).is_none());
+/// ```
+impl FromStr for TemporalGraph {
+    type Err = ParseGraphError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse_scaled(input, FixedPoint::INTEGER)
+    }
+}
+
+impl TemporalGraph {
+    /// Like [`FromStr`], but reads decimal costs with `scale`. Times are always integers.
+    pub fn parse_scaled(input: &str, scale: FixedPoint) -> Result<Self, ParseGraphError> {
+        let mut arena = ParseArena::with_capacity(input.len());
+        let mut times = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |kind| ParseGraphError::new(i + 1, kind);
+            let (src, dest, cost, directed, time) = parse_timed_edge(line, scale).map_err(error)?;
+            let mut intern = |name| {
+                arena
+                    .intern(name)
+                    .ok_or_else(|| error(ParseErrorKind::TooManyNodes))
+            };
+            let (src, dest) = (intern(src)?, intern(dest)?);
+            arena.push_edge(src, dest, cost, directed);
+            times.push(time);
+        }
+        Ok(Self {
+            graph: Graph::from_arena(&arena),
+            times,
+        })
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// The time `edge` is available, or `None` if it always is.
+    pub fn time(&self, edge: EdgeId) -> Option<Weight> {
+        self.times[edge.index()]
+    }
+
+    /// The earliest time each node can be reached from `start` by a time-respecting path
+    /// starting at `from` whose timed edges all fall in `[from, to]`, by node id.
+    pub fn arrival_times(&self, start: NodeId, from: Weight, to: Weight) -> Vec<Option<Weight>> {
+        self.search(start, None, from, to).0
+    }
+
+    /// The time-respecting path from `start` to `end` within `[from, to]` that arrives
+    /// first. Of the journeys arriving together, this isn't necessarily the cheapest.
+    ///
+    /// Arriving earlier never rules out an edge, so this is Dijkstra's algorithm with
+    /// arrival times as distances: an edge at time `t` can be taken from a node reached by
+    /// `t`, arriving at `t`, and an untimed edge keeps the time it is taken at.
+    pub fn earliest_arrival(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        from: Weight,
+        to: Weight,
+    ) -> Option<Journey> {
+        let (arrival, parent) = self.search(start, Some(end), from, to);
+        let arrival = arrival[end.index()]?;
+        let mut edges = Vec::new();
+        let mut node = end;
+        while let Some((previous, edge, cost)) = parent[node.index()] {
+            edges.push((node, edge, cost));
+            node = previous;
+        }
+        edges.reverse();
+        let mut nodes = vec![start];
+        let mut distance = vec![0];
+        let mut times = Vec::with_capacity(edges.len());
+        for (node, edge, cost) in edges {
+            nodes.push(node);
+            distance.push(distance[distance.len() - 1] + cost);
+            times.push(self.time(edge));
+        }
+        Some(Journey {
+            arrival,
+            path: Path {
+                cost: distance[distance.len() - 1],
+                nodes,
+                distance,
+            },
+            times,
+        })
+    }
+
+    /// How many other nodes each node can reach by a time-respecting path within
+    /// `[from, to]`, by node id. Unlike in a static graph, reaching a node that reaches a
+    /// third doesn't mean reaching the third, as its edges may have gone by then.
+    pub fn reachability(&self, from: Weight, to: Weight) -> Vec<usize> {
+        self.graph
+            .node_ids()
+            .map(|start| {
+                let arrival = self.arrival_times(start, from, to);
+                arrival.iter().flatten().count() - 1
+            })
+            .collect()
+    }
+
+    /// Earliest arrival times and the `(previous node, edge, cost)` each was reached by,
+    /// stopping early once `end` is settled.
+    fn search(
+        &self,
+        start: NodeId,
+        end: Option<NodeId>,
+        from: Weight,
+        to: Weight,
+    ) -> (Vec<Option<Weight>>, Parents) {
+        let n = self.graph.node_count();
+        let mut arrival = vec![None; n];
+        let mut parent = vec![None; n];
+        let mut done = vec![false; n];
+        let mut heap = BinaryHeap::new();
+        arrival[start.index()] = Some(from);
+        heap.push(Reverse((from, start)));
+        while let Some(Reverse((time, node))) = heap.pop() {
+            if std::mem::replace(&mut done[node.index()], true) {
+                continue;
+            }
+            if Some(node) == end {
+                break;
+            }
+            for edge in self.graph.edges_of(node) {
+                let next = match self.time(edge.id) {
+                    Some(t) if t < time || t > to => continue,
+                    Some(t) => t,
+                    None => time,
+                };
+                if arrival[edge.node.index()].is_none_or(|best| next < best) {
+                    arrival[edge.node.index()] = Some(next);
+                    parent[edge.node.index()] = Some((node, edge.id, edge.cost));
+                    heap.push(Reverse((next, edge.node)));
+                }
+            }
+        }
+        (arrival, parent)
+    }
+}
+
+/// The `(previous node, edge, cost)` each node was reached by, by node id.
+type Parents = Vec<Option<(NodeId, EdgeId, Weight)>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn journeys_respect_time() {
+        let mut rng = Rng::new(9);
+        for _ in 0..100 {
+            let n = 2 + rng.below(8);
+            let lines: Vec<String> = (0..rng.below(25))
+                .map(|_| {
+                    let (a, b) = (rng.below(n), rng.below(n));
+                    let direction = if rng.chance(0.3) { " ->" } else { "" };
+                    let time = if rng.chance(0.9) {
+                        format!(" @{}", rng.below(20))
+                    } else {
+                        String::new()
+                    };
+                    format!("{} {} {}{}{}", a, b, 1 + rng.below(9), direction, time)
+                })
+                .collect();
+            let network: TemporalGraph = lines.join("\n").parse().unwrap();
+            let graph = network.graph();
+            let (from, to) = (rng.below(5) as Weight, 5 + rng.below(20) as Weight);
+            for start in graph.node_ids() {
+                // Brute force: relax every edge until nothing changes.
+                let mut best = vec![None; graph.node_count()];
+                best[start.index()] = Some(from);
+                loop {
+                    let mut changed = false;
+                    for node in graph.node_ids() {
+                        let time = match best[node.index()] {
+                            Some(time) => time,
+                            None => continue,
+                        };
+                        for edge in graph.edges_of(node) {
+                            let next = match network.time(edge.id) {
+                                Some(t) if t < time || t > to => continue,
+                                Some(t) => t,
+                                None => time,
+                            };
+                            if best[edge.node.index()].is_none_or(|b: Weight| next < b) {
+                                best[edge.node.index()] = Some(next);
+                                changed = true;
+                            }
+                        }
+                    }
+                    if !changed {
+                        break;
+                    }
+                }
+                assert_eq!(network.arrival_times(start, from, to), best);
+
+                for end in graph.node_ids() {
+                    let journey = network.earliest_arrival(start, end, from, to);
+                    assert_eq!(journey.as_ref().map(|j| j.arrival), best[end.index()]);
+                    if let Some(journey) = journey {
+                        let mut clock = from;
+                        for time in journey.times.iter().flatten() {
+                            assert!(clock <= *time && *time <= to);
+                            clock = *time;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
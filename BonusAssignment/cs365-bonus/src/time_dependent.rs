@@ -0,0 +1,312 @@
+use crate::graph::{EdgeId, Graph, NodeId, Weight};
+use crate::path::Path;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::str::FromStr;
+
+/// A travel time that depends on the departure time, linear between breakpoints and
+/// constant before the first and after the last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TravelTime {
+    /// `(departure, travel time)` pairs sorted by departure.
+    points: Vec<(Weight, Weight)>,
+}
+
+impl TravelTime {
+    /// Build a travel time function, or `None` if `points` is empty, isn't sorted by
+    /// strictly increasing departure, or lets a later departure arrive earlier.
+    ///
+    /// That last condition, known as FIFO, is what keeps Dijkstra exact: waiting never
+    /// helps, so the earliest arrival at a node is always the best one to continue from.
+    pub fn new(points: Vec<(Weight, Weight)>) -> Option<Self> {
+        if points.is_empty() {
+            return None;
+        }
+        for pair in points.windows(2) {
+            let ((t0, d0), (t1, d1)) = (pair[0], pair[1]);
+            if t1 <= t0 || t1 + d1 < t0 + d0 {
+                return None;
+            }
+        }
+        Some(Self { points })
+    }
+
+    /// The travel time when departing at `time`, rounded down between breakpoints.
+    pub fn at(&self, time: Weight) -> Weight {
+        let i = self.points.partition_point(|&(t, _)| t <= time);
+        if i == 0 {
+            return self.points[0].1;
+        }
+        if i == self.points.len() {
+            return self.points[i - 1].1;
+        }
+        let ((t0, d0), (t1, d1)) = (self.points[i - 1], self.points[i]);
+        let (span, offset) = ((t1 - t0) as i128, (time - t0) as i128);
+        let delta = (d1 as i128 - d0 as i128) * offset;
+        (d0 as i128 + delta.div_euclid(span)) as Weight
+    }
+}
+
+/// Writes the format read by [`FromStr`].
+impl std::fmt::Display for TravelTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, (t, d)) in self.points.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}:{}", t, d)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses space separated `departure:travel` pairs, e.g. `0:10 60:25 120:10`.
+impl FromStr for TravelTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let points = s
+            .split_whitespace()
+            .map(|point| {
+                let (t, d) = point.split_once(':')?;
+                Some((t.parse().ok()?, d.parse().ok()?))
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| format!("invalid travel time point in '{}'", s))?;
+        TravelTime::new(points).ok_or_else(|| {
+            format!(
+                "travel times '{}' must be non-empty, sorted, and never let a later departure arrive first",
+                s
+            )
+        })
+    }
+}
+
+/// A graph whose edges take a time-dependent travel time, falling back to their cost.
+#[derive(Debug, Clone)]
+pub struct TimeDependentGraph<'a> {
+    graph: &'a Graph,
+    profiles: Vec<Option<TravelTime>>,
+}
+
+impl<'a> TimeDependentGraph<'a> {
+    /// `profiles[id]` is the travel time of edge `id`, or `None` to use its cost.
+    pub fn new(graph: &'a Graph, mut profiles: Vec<Option<TravelTime>>) -> Self {
+        profiles.resize(graph.edge_count(), None);
+        Self { graph, profiles }
+    }
+
+    pub fn graph(&self) -> &'a Graph {
+        self.graph
+    }
+
+    /// The travel time of `edge` when entered at `time`.
+    pub fn travel_time(&self, edge: EdgeId, cost: Weight, time: Weight) -> Weight {
+        match &self.profiles[edge.index()] {
+            Some(profile) => profile.at(time),
+            None => cost,
+        }
+    }
+
+    /// The fastest route from `start` to `end` when leaving at `departure`.
+    ///
+    /// The returned path's distances are elapsed times since departure.
+    pub fn earliest_arrival(&self, start: NodeId, end: NodeId, departure: Weight) -> Option<Path> {
+        let n = self.graph.node_count();
+        let mut arrival: Vec<Option<Weight>> = vec![None; n];
+        let mut parent: Vec<Option<NodeId>> = vec![None; n];
+        let mut heap = BinaryHeap::new();
+        arrival[start.index()] = Some(departure);
+        heap.push(Reverse((departure, start)));
+
+        while let Some(Reverse((time, node))) = heap.pop() {
+            if arrival[node.index()].is_some_and(|best| time > best) {
+                continue;
+            }
+            if node == end {
+                break;
+            }
+            for edge in self.graph.edges_of(node) {
+                let next = time + self.travel_time(edge.id, edge.cost, time);
+                if arrival[edge.node.index()].is_none_or(|best| next < best) {
+                    arrival[edge.node.index()] = Some(next);
+                    parent[edge.node.index()] = Some(node);
+                    heap.push(Reverse((next, edge.node)));
+                }
+            }
+        }
+
+        let mut nodes = vec![end];
+        arrival[end.index()]?;
+        while let Some(previous) = parent[nodes[nodes.len() - 1].index()] {
+            nodes.push(previous);
+        }
+        nodes.reverse();
+        let distance: Vec<Weight> = nodes
+            .iter()
+            .map(|node| arrival[node.index()].map(|t| t - departure))
+            .collect::<Option<_>>()?;
+        Some(Path {
+            cost: distance[distance.len() - 1],
+            nodes,
+            distance,
+        })
+    }
+
+    /// Travel time from `start` to `end` as a function of departure time over
+    /// `[from, to]`, as the breakpoints of a piecewise linear function.
+    ///
+    /// Departures are evaluated every `step` time units, and consecutive samples on a
+    /// common line are merged, so with `step` 1 this is exact at integer times. Departures
+    /// for which `end` is unreachable have no travel time.
+    pub fn profile(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        from: Weight,
+        to: Weight,
+        step: Weight,
+    ) -> Vec<(Weight, Option<Weight>)> {
+        let mut samples = Vec::new();
+        let mut departure = from;
+        while departure <= to {
+            let travel = self
+                .earliest_arrival(start, end, departure)
+                .map(|path| path.cost);
+            samples.push((departure, travel));
+            departure = match departure.checked_add(step.max(1)) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        breakpoints(&samples)
+    }
+}
+
+/// Drop samples that lie on the line through their neighbors.
+fn breakpoints(samples: &[(Weight, Option<Weight>)]) -> Vec<(Weight, Option<Weight>)> {
+    let mut kept: Vec<(Weight, Option<Weight>)> = Vec::new();
+    for (i, &sample) in samples.iter().enumerate() {
+        let next = samples.get(i + 1);
+        let collinear = match (kept.last(), sample.1, next) {
+            (Some(&(t0, Some(d0))), Some(d1), Some(&(t2, Some(d2)))) => {
+                let (t1, d1) = (sample.0 as i128, d1 as i128);
+                let (t0, d0, t2, d2) = (t0 as i128, d0 as i128, t2 as i128, d2 as i128);
+                (d1 - d0) * (t2 - t1) == (d2 - d1) * (t1 - t0)
+            }
+            (Some(&(_, None)), None, Some(&(_, None))) => true,
+            _ => false,
+        };
+        if !collinear {
+            kept.push(sample);
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_profile(rng: &mut Rng) -> TravelTime {
+        let mut points = vec![(rng.below(20) as Weight, rng.below(20) as Weight)];
+        for _ in 0..rng.below(4) {
+            let (t0, d0) = points[points.len() - 1];
+            let t1 = t0 + 1 + rng.below(20) as Weight;
+            // Keep `t1 + d1 >= t0 + d0` so later departures never arrive first.
+            let least = (d0 - (t1 - t0)).max(0);
+            points.push((t1, least + rng.below(15) as Weight));
+        }
+        TravelTime::new(points).unwrap()
+    }
+
+    #[test]
+    fn travel_times_interpolate_and_round_trip() {
+        let profile: TravelTime = "0:10 10:20 20:12".parse().unwrap();
+        let times: Vec<Weight> = [-5, 0, 3, 10, 15, 19, 20, 100]
+            .iter()
+            .map(|&t| profile.at(t))
+            .collect();
+        assert_eq!(times, [10, 10, 13, 20, 16, 12, 12, 12]);
+        assert_eq!(profile.to_string().parse::<TravelTime>(), Ok(profile));
+
+        assert!(TravelTime::new(Vec::new()).is_none());
+        assert!(TravelTime::new(vec![(0, 1), (0, 2)]).is_none());
+        // Leaving at 10 would arrive at 11, before leaving at 0 does.
+        assert!(TravelTime::new(vec![(0, 20), (10, 1)]).is_none());
+        assert!("0:1 x".parse::<TravelTime>().is_err());
+    }
+
+    #[test]
+    fn arrivals_match_repeated_relaxation() {
+        let mut rng = Rng::new(26);
+        for _ in 0..40 {
+            let n = 1 + rng.below(8);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                graph.add_edge(a, b, rng.below(10) as Weight);
+            }
+            let profiles = (0..graph.edge_count())
+                .map(|_| rng.chance(0.6).then(|| random_profile(&mut rng)))
+                .collect();
+            let timed = TimeDependentGraph::new(&graph, profiles);
+            let (start, end) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+
+            for departure in [0, 7, 30] {
+                let mut arrival = vec![None; n];
+                arrival[start.index()] = Some(departure);
+                let mut changed = true;
+                while changed {
+                    changed = false;
+                    for node in graph.node_ids() {
+                        let time = match arrival[node.index()] {
+                            Some(time) => time,
+                            None => continue,
+                        };
+                        for edge in graph.edges_of(node) {
+                            let next = time + timed.travel_time(edge.id, edge.cost, time);
+                            if arrival[edge.node.index()].is_none_or(|best| next < best) {
+                                arrival[edge.node.index()] = Some(next);
+                                changed = true;
+                            }
+                        }
+                    }
+                }
+                let path = timed.earliest_arrival(start, end, departure);
+                let expected = arrival[end.index()].map(|t| t - departure);
+                assert_eq!(path.as_ref().map(|p| p.cost), expected);
+                if let Some(path) = path {
+                    assert_eq!(path.nodes.first(), Some(&start));
+                    assert_eq!(path.distance[0], 0);
+                }
+            }
+
+            // Every sampled departure lies on the line between the breakpoints around it.
+            let profile = timed.profile(start, end, 0, 40, 1);
+            for departure in 0..=40 {
+                let travel = timed
+                    .earliest_arrival(start, end, departure)
+                    .map(|p| p.cost);
+                let i = profile.partition_point(|&(t, _)| t <= departure);
+                let (t0, d0) = profile[i - 1];
+                if t0 == departure {
+                    assert_eq!(d0, travel);
+                    continue;
+                }
+                let (t2, d2) = profile[i];
+                match (d0, travel, d2) {
+                    (Some(d0), Some(d1), Some(d2)) => {
+                        assert_eq!((d1 - d0) * (t2 - t0), (d2 - d0) * (departure - t0))
+                    }
+                    (None, None, None) => {}
+                    samples => panic!("{:?} isn't between breakpoints", samples),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::decimal::FixedPoint;
+use crate::graph::Graph;
+use std::str::FromStr;
+
+/// How a fractional weight becomes an integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Nearest,
+    Floor,
+    Ceil,
+}
+
+/// A single step of a weight transformation pipeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightOp {
+    /// Multiply by a constant.
+    Scale(f64),
+    /// Replace `w` with `k / w`, turning similarities into distances.
+    Invert(f64),
+    /// Limit weights to `[min, max]`.
+    Clamp(f64, f64),
+    /// Round to an integer.
+    Round(Rounding),
+}
+
+/// Parses `scale:K`, `invert[:K]`, `clamp:MIN:MAX`, and `round[:nearest|floor|ceil]`.
+impl FromStr for WeightOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or("");
+        let args: Vec<&str> = parts.collect();
+        let number = |s: &str| {
+            s.parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(|| format!("invalid number '{}' in '{}'", s, name))
+        };
+        match (name, args.as_slice()) {
+            ("scale", [k]) => Ok(WeightOp::Scale(number(k)?)),
+            ("invert", []) => Ok(WeightOp::Invert(1.0)),
+            ("invert", [k]) => Ok(WeightOp::Invert(number(k)?)),
+            ("clamp", [min, max]) => {
+                let (min, max) = (number(min)?, number(max)?);
+                if min > max {
+                    return Err(format!("clamp minimum {} exceeds maximum {}", min, max));
+                }
+                Ok(WeightOp::Clamp(min, max))
+            }
+            ("round", []) | ("round", ["nearest"]) => Ok(WeightOp::Round(Rounding::Nearest)),
+            ("round", ["floor"]) => Ok(WeightOp::Round(Rounding::Floor)),
+            ("round", ["ceil"]) => Ok(WeightOp::Round(Rounding::Ceil)),
+            _ => Err(format!("unknown weight operation '{}'", s)),
+        }
+    }
+}
+
+impl WeightOp {
+    pub fn apply(self, w: f64) -> f64 {
+        match self {
+            WeightOp::Scale(k) => w * k,
+            WeightOp::Invert(k) => k / w,
+            WeightOp::Clamp(min, max) => w.max(min).min(max),
+            WeightOp::Round(Rounding::Nearest) => w.round(),
+            WeightOp::Round(Rounding::Floor) => w.floor(),
+            WeightOp::Round(Rounding::Ceil) => w.ceil(),
+        }
+    }
+}
+
+/// Apply `ops` in order to every edge weight.
+///
+/// Weights are integers, so a fractional result is rounded to the nearest integer at the
+/// end unless an earlier `round` step chose otherwise. Fails if any weight becomes
+/// non-finite, such as inverting a zero weight, or too large to store.
+pub fn transform_weights(graph: &Graph, ops: &[WeightOp]) -> Result<Graph, String> {
+    transform_weights_scaled(graph, ops, FixedPoint::INTEGER)
+}
+
+/// Like [`transform_weights`], but for weights standing for decimal costs with `scale`.
+///
+/// The steps work on the costs rather than the weights, so `scale:2` doubles `1.5` and
+/// `round` rounds it to `2`, and results are rounded to the places `scale` keeps.
+///
+/// ```
+/// use cs365_bonus::decimal::FixedPoint;
+/// use cs365_bonus::transform::{transform_weights_scaled, WeightOp};
+///
+/// let cents = FixedPoint::new(2).unwrap();
+/// let graph = cs365_bonus::parse_scaled("a b 1.5", cents, 1).unwrap();
+/// let ops = ["scale:3".parse().unwrap(), WeightOp::Clamp(0.0, 4.25)];
+/// let graph = transform_weights_scaled(&graph, &ops, cents).unwrap();
+/// assert_eq!(graph.edges().next().unwrap().3, 425);
+/// ```
+pub fn transform_weights_scaled(
+    graph: &Graph,
+    ops: &[WeightOp],
+    scale: FixedPoint,
+) -> Result<Graph, String> {
+    let mut error = None;
+    let graph = graph.map_weights(|id, w| {
+        let value = ops.iter().fold(scale.to_f64(w), |w, op| op.apply(w));
+        scale.from_f64(value).unwrap_or_else(|| {
+            error.get_or_insert(format!("edge {} would get invalid weight {}", id, value));
+            w
+        })
+    });
+    match error {
+        Some(error) => Err(error),
+        None => Ok(graph),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weights(graph: &Graph) -> Vec<crate::graph::Weight> {
+        graph.edges().map(|(.., cost)| cost).collect()
+    }
+
+    #[test]
+    fn parses_each_operation() {
+        assert_eq!("scale:2.5".parse(), Ok(WeightOp::Scale(2.5)));
+        assert_eq!("invert".parse(), Ok(WeightOp::Invert(1.0)));
+        assert_eq!("clamp:1:3".parse(), Ok(WeightOp::Clamp(1.0, 3.0)));
+        assert_eq!("round:floor".parse(), Ok(WeightOp::Round(Rounding::Floor)));
+        assert_eq!("round".parse(), Ok(WeightOp::Round(Rounding::Nearest)));
+        assert!("clamp:3:1".parse::<WeightOp>().is_err());
+        assert!("scale:inf".parse::<WeightOp>().is_err());
+        assert!("shift:1".parse::<WeightOp>().is_err());
+    }
+
+    #[test]
+    fn applies_operations_in_order() {
+        let graph: Graph = "a b 3\nb c 10\nc a 0".parse().unwrap();
+        // Inverting the zero weight is infinite, unless clamped back down.
+        let ops = ["invert:30", "clamp:0:5"].map(|op| op.parse().unwrap());
+        assert_eq!(
+            weights(&transform_weights(&graph, &ops).unwrap()),
+            [5, 3, 5]
+        );
+        assert!(transform_weights(&graph, &ops[..1]).is_err());
+
+        let ops = ["scale:0.5", "round:floor", "scale:3"].map(|op| op.parse().unwrap());
+        let graph = transform_weights(&graph, &ops).unwrap();
+        assert_eq!(weights(&graph), [3, 15, 0]);
+        assert_eq!(graph.edge_count(), 3);
+
+        let cents = FixedPoint::new(2).unwrap();
+        let ops = ["invert:1".parse().unwrap()];
+        let graph = crate::parse_scaled("a b 3", cents, 1).unwrap();
+        let inverted = transform_weights_scaled(&graph, &ops, cents).unwrap();
+        assert_eq!(weights(&inverted), [33]);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::graph::{simple_adjacency, Graph, NodeId};
+use crate::rng::Rng;
+
+/// Triangle counts for a whole graph and for each node.
+#[derive(Debug, Clone)]
+pub struct TriangleCount {
+    pub total: f64,
+    pub per_node: Vec<f64>,
+}
+
+/// Count triangles exactly, ignoring edge direction, weights, and parallel edges.
+///
+/// Edges are oriented from lower to higher degree so each triangle is found once, by
+/// intersecting sorted forward adjacency lists.
+pub fn count_triangles(graph: &Graph) -> TriangleCount {
+    let (total, per_node) = count_in(&simple_adjacency(graph));
+    TriangleCount {
+        total: total as f64,
+        per_node: per_node.into_iter().map(|c| c as f64).collect(),
+    }
+}
+
+/// Estimate triangle counts by keeping each edge with probability `p` and scaling the
+/// exact count on the sparsified graph by `1 / p^3`.
+pub fn estimate_triangles(graph: &Graph, p: f64, rng: &mut Rng) -> TriangleCount {
+    let adjacency = simple_adjacency(graph);
+    let mut sampled = vec![Vec::new(); adjacency.len()];
+    for (u, list) in adjacency.iter().enumerate() {
+        for &v in list.iter().filter(|v| v.index() > u) {
+            if rng.chance(p) {
+                sampled[u].push(v);
+                sampled[v.index()].push(NodeId::new(u));
+            }
+        }
+    }
+    for list in sampled.iter_mut() {
+        list.sort_unstable();
+    }
+
+    let scale = 1.0 / (p * p * p);
+    let (total, per_node) = count_in(&sampled);
+    TriangleCount {
+        total: total as f64 * scale,
+        per_node: per_node.into_iter().map(|c| c as f64 * scale).collect(),
+    }
+}
+
+/// Counts of small connected subgraphs, ignoring edge direction, weights, and parallel edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotifCensus {
+    /// Paths of length two, including those closed into triangles.
+    pub wedges: u64,
+    pub triangles: u64,
+    /// Cycles on four distinct nodes, not necessarily induced.
+    pub four_cycles: u64,
+    /// The fraction of wedges that are closed, `3 * triangles / wedges`.
+    pub transitivity: f64,
+}
+
+pub fn motif_census(graph: &Graph) -> MotifCensus {
+    let adjacency = simple_adjacency(graph);
+    let wedges: u64 = adjacency
+        .iter()
+        .map(|list| {
+            let d = list.len() as u64;
+            d * d.saturating_sub(1) / 2
+        })
+        .sum();
+    let (triangles, _) = count_in(&adjacency);
+    let four_cycles = count_four_cycles(&adjacency);
+    let transitivity = if wedges == 0 {
+        0.0
+    } else {
+        3.0 * triangles as f64 / wedges as f64
+    };
+
+    MotifCensus {
+        wedges,
+        triangles,
+        four_cycles,
+        transitivity,
+    }
+}
+
+/// Count 4-cycles by charging each one to its highest ranked node `u` and the node `w`
+/// opposite it, counting the paths `u - v - w` through lower ranked nodes.
+fn count_four_cycles(adjacency: &[Vec<NodeId>]) -> u64 {
+    let rank = |n: usize| (adjacency[n].len(), n);
+    let mut paths = vec![0u64; adjacency.len()];
+    let mut touched = Vec::new();
+    let mut total = 0;
+    for u in 0..adjacency.len() {
+        for v in adjacency[u].iter().map(|v| v.index()) {
+            if rank(v) >= rank(u) {
+                continue;
+            }
+            for w in adjacency[v].iter().map(|w| w.index()) {
+                if rank(w) >= rank(u) {
+                    continue;
+                }
+                if paths[w] == 0 {
+                    touched.push(w);
+                }
+                paths[w] += 1;
+            }
+        }
+        for w in touched.drain(..) {
+            total += paths[w] * (paths[w] - 1) / 2;
+            paths[w] = 0;
+        }
+    }
+    total
+}
+
+fn count_in(adjacency: &[Vec<NodeId>]) -> (u64, Vec<u64>) {
+    let rank = |n: usize| (adjacency[n].len(), n);
+    let forward: Vec<Vec<usize>> = adjacency
+        .iter()
+        .enumerate()
+        .map(|(u, list)| {
+            list.iter()
+                .map(|v| v.index())
+                .filter(|&v| rank(v) > rank(u))
+                .collect()
+        })
+        .collect();
+
+    let mut total = 0;
+    let mut per_node = vec![0; adjacency.len()];
+    for (u, list) in forward.iter().enumerate() {
+        for &v in list.iter() {
+            for w in sorted_intersection(list, &forward[v]) {
+                total += 1;
+                per_node[u] += 1;
+                per_node[v] += 1;
+                per_node[w] += 1;
+            }
+        }
+    }
+    (total, per_node)
+}
+
+pub(crate) fn sorted_intersection<'a>(
+    a: &'a [usize],
+    b: &'a [usize],
+) -> impl Iterator<Item = usize> + 'a {
+    let (mut i, mut j) = (0, 0);
+    std::iter::from_fn(move || {
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                    return Some(a[i - 1]);
+                }
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_graph(rng: &mut Rng) -> Graph {
+        let n = 1 + rng.below(12);
+        let mut graph = Graph::new();
+        for i in 0..n {
+            graph.get_or_insert_node(&i.to_string());
+        }
+        for _ in 0..rng.below(4 * n) {
+            let (a, b) = (rng.below(n), rng.below(n));
+            graph.add_edge(NodeId::new(a), NodeId::new(b), 1);
+        }
+        graph
+    }
+
+    #[test]
+    fn counts_match_every_triple() {
+        let mut rng = Rng::new(5);
+        for _ in 0..50 {
+            let graph = random_graph(&mut rng);
+            let adjacency = simple_adjacency(&graph);
+            let joined = |a: usize, b: usize| adjacency[a].contains(&NodeId::new(b));
+            let n = graph.node_count();
+            let mut per_node = vec![0.0; n];
+            for a in 0..n {
+                for b in a + 1..n {
+                    for c in b + 1..n {
+                        if joined(a, b) && joined(b, c) && joined(c, a) {
+                            for v in [a, b, c] {
+                                per_node[v] += 1.0;
+                            }
+                        }
+                    }
+                }
+            }
+            let count = count_triangles(&graph);
+            assert_eq!(count.per_node, per_node);
+            assert_eq!(count.total * 3.0, per_node.iter().sum::<f64>());
+            // Keeping every edge makes the estimate exact.
+            let estimate = estimate_triangles(&graph, 1.0, &mut rng);
+            assert_eq!(estimate.total, count.total);
+        }
+    }
+
+    #[test]
+    fn census_matches_every_quadruple() {
+        let mut rng = Rng::new(6);
+        for _ in 0..50 {
+            let graph = random_graph(&mut rng);
+            let adjacency = simple_adjacency(&graph);
+            let joined = |a: usize, b: usize| adjacency[a].contains(&NodeId::new(b));
+            let n = graph.node_count();
+            let mut four_cycles = 0;
+            for a in 0..n {
+                for b in a + 1..n {
+                    for c in b + 1..n {
+                        for d in c + 1..n {
+                            // The three ways to go around four nodes.
+                            for [w, x, y, z] in [[a, b, c, d], [a, b, d, c], [a, c, b, d]] {
+                                if joined(w, x) && joined(x, y) && joined(y, z) && joined(z, w) {
+                                    four_cycles += 1;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            let wedges: u64 = (0..n)
+                .map(|v| {
+                    let d = adjacency[v].len() as u64;
+                    d * d.saturating_sub(1) / 2
+                })
+                .sum();
+            let census = motif_census(&graph);
+            assert_eq!(census.four_cycles, four_cycles);
+            assert_eq!(census.wedges, wedges);
+            assert_eq!(census.triangles as f64, count_triangles(&graph).total);
+            assert!((0.0..=1.0).contains(&census.transitivity));
+        }
+    }
+
+    #[test]
+    fn a_square_has_one_four_cycle_and_no_triangles() {
+        let graph: Graph = "a b 1\nb c 1\nc d 1\nd a 1".parse().unwrap();
+        let census = motif_census(&graph);
+        assert_eq!(
+            (census.wedges, census.triangles, census.four_cycles),
+            (4, 0, 1)
+        );
+        assert_eq!(census.transitivity, 0.0);
+    }
+}
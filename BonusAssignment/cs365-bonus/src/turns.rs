@@ -0,0 +1,205 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::Path;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+/// Forbidden turns, such as a no-left-turn at an intersection.
+///
+/// A turn `(from, via, to)` forbids arriving at `via` from `from` and then leaving it
+/// towards `to`. Turning back the way one came is forbidden with `to == from`.
+#[derive(Debug, Clone, Default)]
+pub struct TurnRestrictions {
+    forbidden: HashSet<(NodeId, NodeId, NodeId)>,
+}
+
+impl TurnRestrictions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn forbid(&mut self, from: NodeId, via: NodeId, to: NodeId) {
+        self.forbidden.insert((from, via, to));
+    }
+
+    pub fn is_forbidden(&self, from: NodeId, via: NodeId, to: NodeId) -> bool {
+        self.forbidden.contains(&(from, via, to))
+    }
+
+    pub fn len(&self) -> usize {
+        self.forbidden.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forbidden.is_empty()
+    }
+}
+
+/// The cheapest route from `start` to `end` that makes none of the forbidden turns.
+///
+/// Dijkstra over edges rather than nodes: a state is the adjacency entry last travelled,
+/// so a node can be settled once per way of entering it. The route may therefore pass
+/// through a node more than once, e.g. going around the block to avoid a banned turn.
+pub fn turn_restricted_path(
+    graph: &Graph,
+    restrictions: &TurnRestrictions,
+    start: NodeId,
+    end: NodeId,
+) -> Option<Path> {
+    // State 0 is standing at `start`; state `offset[u] + i + 1` means having just
+    // travelled `graph.edges_of(u)[i]`.
+    let mut offset = Vec::with_capacity(graph.node_count() + 1);
+    offset.push(0);
+    for node in graph.node_ids() {
+        offset.push(offset[node.index()] + graph.edges_of(node).len());
+    }
+    let states = offset[graph.node_count()] + 1;
+    // The node each state is at, and the node it came from.
+    let mut at = vec![start; states];
+    let mut from = vec![None; states];
+    for node in graph.node_ids() {
+        for (i, edge) in graph.edges_of(node).iter().enumerate() {
+            at[offset[node.index()] + i + 1] = edge.node;
+            from[offset[node.index()] + i + 1] = Some(node);
+        }
+    }
+
+    let mut distance: Vec<Option<Weight>> = vec![None; states];
+    let mut parent: Vec<Option<usize>> = vec![None; states];
+    let mut heap = BinaryHeap::new();
+    distance[0] = Some(0);
+    heap.push(Reverse((0, 0)));
+
+    let mut found = None;
+    while let Some(Reverse((cost, current))) = heap.pop() {
+        if distance[current].is_some_and(|best| cost > best) {
+            continue;
+        }
+        let node = at[current];
+        if node == end {
+            found = Some(current);
+            break;
+        }
+        for (i, edge) in graph.edges_of(node).iter().enumerate() {
+            if from[current].is_some_and(|prev| restrictions.is_forbidden(prev, node, edge.node)) {
+                continue;
+            }
+            let next = offset[node.index()] + i + 1;
+            let next_cost = cost + edge.cost;
+            if distance[next].is_none_or(|best| next_cost < best) {
+                distance[next] = Some(next_cost);
+                parent[next] = Some(current);
+                heap.push(Reverse((next_cost, next)));
+            }
+        }
+    }
+
+    let mut state = found?;
+    let mut nodes = vec![at[state]];
+    let mut dist = vec![distance[state]?];
+    while let Some(previous) = parent[state] {
+        nodes.push(at[previous]);
+        dist.push(distance[previous]?);
+        state = previous;
+    }
+    nodes.reverse();
+    dist.reverse();
+    Some(Path {
+        cost: dist[dist.len() - 1],
+        nodes,
+        distance: dist,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn routes_match_relaxing_every_entry() {
+        let mut rng = Rng::new(29);
+        for _ in 0..40 {
+            let n = 1 + rng.below(7);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(4 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                if rng.chance(0.5) {
+                    graph.add_edge(a, b, rng.below(10) as Weight);
+                } else {
+                    graph.add_bidirectional_edge(a, b, rng.below(10) as Weight);
+                }
+            }
+            let mut restrictions = TurnRestrictions::new();
+            for _ in 0..rng.below(3 * n) {
+                let [a, b, c] = [(); 3].map(|_| NodeId::new(rng.below(n)));
+                restrictions.forbid(a, b, c);
+            }
+            let (start, end) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+
+            // The cheapest cost of standing at each node, indexed by where we came from.
+            let mut best = vec![vec![None; n + 1]; n];
+            best[start.index()][n] = Some(0);
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for node in graph.node_ids() {
+                    for prev in 0..=n {
+                        let cost: Weight = match best[node.index()][prev] {
+                            Some(cost) => cost,
+                            None => continue,
+                        };
+                        for edge in graph.edges_of(node) {
+                            if prev < n
+                                && restrictions.is_forbidden(NodeId::new(prev), node, edge.node)
+                            {
+                                continue;
+                            }
+                            let slot = &mut best[edge.node.index()][node.index()];
+                            if slot.is_none_or(|c| cost + edge.cost < c) {
+                                *slot = Some(cost + edge.cost);
+                                changed = true;
+                            }
+                        }
+                    }
+                }
+            }
+            let expected = best[end.index()].iter().flatten().copied().min();
+
+            let path = turn_restricted_path(&graph, &restrictions, start, end);
+            assert_eq!(path.as_ref().map(|p| p.cost), expected);
+            if let Some(path) = path {
+                assert_eq!(path.nodes.first(), Some(&start));
+                assert_eq!(path.nodes.last(), Some(&end));
+                assert!(path
+                    .nodes
+                    .windows(3)
+                    .all(|w| !restrictions.is_forbidden(w[0], w[1], w[2])));
+            }
+        }
+    }
+
+    #[test]
+    fn goes_around_the_block() {
+        // Turning from a onto c at b is banned, so the route loops b-x-y-b first.
+        let graph: Graph = "a b 1 ->\nb c 1 ->\nb x 1 ->\nx y 1 ->\ny b 1 ->"
+            .parse()
+            .unwrap();
+        let [a, b, c] = ["a", "b", "c"].map(|name| graph.get_node(name).unwrap());
+        let mut restrictions = TurnRestrictions::new();
+        restrictions.forbid(a, b, c);
+        let path = turn_restricted_path(&graph, &restrictions, a, c).unwrap();
+        assert_eq!(path.cost, 5);
+        let names: Vec<&str> = path
+            .nodes
+            .iter()
+            .map(|&n| graph.get_node_name(n).unwrap())
+            .collect();
+        assert_eq!(names, ["a", "b", "x", "y", "b", "c"]);
+        let y = graph.get_node("y").unwrap();
+        restrictions.forbid(y, b, c);
+        assert!(turn_restricted_path(&graph, &restrictions, a, c).is_none());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::graph::{Graph, NodeId};
+use crate::reachability::strongly_connected_components;
+use std::collections::HashMap;
+
+/// A variable or its negation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Literal {
+    pub variable: usize,
+    pub negated: bool,
+}
+
+impl Literal {
+    /// This literal's node in the implication graph: `2 * variable` for the variable and
+    /// the next node for its negation.
+    fn node(self) -> NodeId {
+        NodeId::new(2 * self.variable + usize::from(self.negated))
+    }
+
+    fn not(self) -> Literal {
+        Literal {
+            negated: !self.negated,
+            ..self
+        }
+    }
+}
+
+/// A boolean formula in conjunctive normal form with at most two literals per clause.
+///
+/// ```
+/// use cs365_bonus::two_sat::Formula;
+///
+/// let formula = Formula::parse("a b\n-a c\n-b -c\n-c").unwrap();
+/// let values = formula.solve().unwrap();
+/// assert_eq!(values, [false, true, false]);
+/// assert!(Formula::parse("a b\na -b\n-a b\n-a -b").unwrap().solve().is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Formula {
+    variables: Vec<String>,
+    clauses: Vec<(Literal, Literal)>,
+}
+
+/// A variable that the clauses force to equal its own negation, so no assignment exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction {
+    pub variable: usize,
+}
+
+impl Formula {
+    /// Read one clause per line as one or two literals, each a variable name optionally
+    /// preceded by `-`, `!`, or `~` to negate it. Blank lines and lines starting with `#`
+    /// are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut formula = Formula::default();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut literal = |word: &str| {
+                let name = word.trim_start_matches(['-', '!', '~']);
+                if name.is_empty() {
+                    return Err(format!("line {}: '{}' names no variable", i + 1, word));
+                }
+                let variable = *index.entry(name.to_string()).or_insert_with(|| {
+                    formula.variables.push(name.to_string());
+                    formula.variables.len() - 1
+                });
+                Ok(Literal {
+                    variable,
+                    negated: (word.len() - name.len()) % 2 == 1,
+                })
+            };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let clause = match words.as_slice() {
+                [only] => {
+                    let only = literal(only)?;
+                    (only, only)
+                }
+                [a, b] => (literal(a)?, literal(b)?),
+                _ => return Err(format!("line {}: expected one or two literals", i + 1)),
+            };
+            formula.clauses.push(clause);
+        }
+        Ok(formula)
+    }
+
+    /// The name of each variable, in order of first use.
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    pub fn clauses(&self) -> &[(Literal, Literal)] {
+        &self.clauses
+    }
+
+    /// The graph with an edge `!a -> b` and `!b -> a` for each clause `a b`: if one literal
+    /// of a clause is false, the other must be true. Nodes are named `x` and `!x`.
+    pub fn implication_graph(&self) -> Graph {
+        let mut graph = Graph::with_capacity(2 * self.variables.len(), 2 * self.clauses.len());
+        for name in &self.variables {
+            graph.get_or_insert_node(name);
+            graph.get_or_insert_node(&format!("!{}", name));
+        }
+        for &(a, b) in &self.clauses {
+            graph.add_edge(a.not().node(), b.node(), 1);
+            if a != b {
+                graph.add_edge(b.not().node(), a.node(), 1);
+            }
+        }
+        graph
+    }
+
+    /// A value for every variable that satisfies all the clauses, in linear time.
+    ///
+    /// The formula is unsatisfiable exactly when some variable and its negation are in the
+    /// same strongly connected component of the implication graph. Otherwise setting each
+    /// variable to whichever of its literals comes later in topological order satisfies it.
+    pub fn solve(&self) -> Result<Vec<bool>, Contradiction> {
+        let component = strongly_connected_components(&self.implication_graph());
+        (0..self.variables.len())
+            .map(|variable| {
+                let (yes, no) = (component[2 * variable], component[2 * variable + 1]);
+                if yes == no {
+                    return Err(Contradiction { variable });
+                }
+                // Components are numbered in reverse topological order.
+                Ok(yes < no)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn satisfies(formula: &Formula, values: &[bool]) -> bool {
+        let holds = |literal: Literal| values[literal.variable] != literal.negated;
+        formula.clauses().iter().all(|&(a, b)| holds(a) || holds(b))
+    }
+
+    #[test]
+    fn contradictory_unit_clauses_are_unsatisfiable() {
+        let formula = Formula::parse("a\n-a").unwrap();
+        assert_eq!(formula.solve(), Err(Contradiction { variable: 0 }));
+    }
+
+    #[test]
+    fn unit_clauses_force_their_literal() {
+        let formula = Formula::parse("-a\nb\n# a comment\na c").unwrap();
+        assert_eq!(formula.variables(), ["a", "b", "c"]);
+        assert_eq!(formula.solve(), Ok(vec![false, true, true]));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = Rng::new(40);
+        for _ in 0..200 {
+            let variables = 1 + rng.below(5);
+            let mut text = String::new();
+            for _ in 0..1 + rng.below(10) {
+                for _ in 0..1 + rng.below(2) {
+                    let sign = if rng.chance(0.5) { "-" } else { "" };
+                    text += &format!("{}v{} ", sign, rng.below(variables));
+                }
+                text += "\n";
+            }
+            let formula = Formula::parse(&text).unwrap();
+            let count = formula.variables().len();
+            let satisfiable = (0..1u32 << count).any(|mask| {
+                let values: Vec<bool> = (0..count).map(|i| mask >> i & 1 == 1).collect();
+                satisfies(&formula, &values)
+            });
+            match formula.solve() {
+                Ok(values) => assert!(satisfies(&formula, &values), "{}", text),
+                Err(_) => assert!(!satisfiable, "{}", text),
+            }
+        }
+    }
+}
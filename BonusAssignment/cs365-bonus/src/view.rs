@@ -0,0 +1,163 @@
+use crate::graph::{Edge, Graph, GraphRef, NodeId, Weight};
+
+/// A borrowed graph with some nodes and edges hidden, built by [`Graph::filter_view`].
+///
+/// Node ids are unchanged. Edges into hidden nodes are hidden too.
+pub struct FilterView<'a, N, E> {
+    graph: &'a Graph,
+    node_pred: N,
+    edge_pred: E,
+}
+
+impl<'a, N, E> FilterView<'a, N, E>
+where
+    N: Fn(NodeId) -> bool,
+    E: Fn(NodeId, &Edge) -> bool,
+{
+    pub fn new(graph: &'a Graph, node_pred: N, edge_pred: E) -> Self {
+        Self {
+            graph,
+            node_pred,
+            edge_pred,
+        }
+    }
+
+    /// The graph underneath this view.
+    pub fn graph(&self) -> &'a Graph {
+        self.graph
+    }
+}
+
+impl<N, E> GraphRef for FilterView<'_, N, E>
+where
+    N: Fn(NodeId) -> bool,
+    E: Fn(NodeId, &Edge) -> bool,
+{
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn contains_node(&self, node: NodeId) -> bool {
+        node.index() < self.graph.node_count() && (self.node_pred)(node)
+    }
+
+    fn edges_of(&self, node: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        let visible = self.contains_node(node);
+        self.graph.edges_of(node).iter().filter(move |edge| {
+            visible && (self.node_pred)(edge.node) && (self.edge_pred)(node, edge)
+        })
+    }
+}
+
+/// A graph searched with each edge costing `weight(edge)` instead of its stored cost, so
+/// per-query preferences don't need a modified copy of the graph.
+///
+/// ```
+/// use cs365_bonus::view::Reweighted;
+/// use cs365_bonus::find_shortest_path;
+///
+/// let graph: cs365_bonus::Graph = "a b 1\nb z 1\na z 3".parse().unwrap();
+/// let (a, z) = (graph.get_node("a").unwrap(), graph.get_node("z").unwrap());
+/// let direct = graph.find_edge(a, z).unwrap();
+/// let view = Reweighted::new(&graph, |edge| if edge.id == direct { 1 } else { edge.cost });
+/// assert_eq!(find_shortest_path(&view, a, z).unwrap().cost, 1);
+/// ```
+pub struct Reweighted<'a, G: ?Sized, F> {
+    graph: &'a G,
+    weight: F,
+}
+
+impl<'a, G, F> Reweighted<'a, G, F>
+where
+    G: GraphRef + ?Sized,
+    F: Fn(&Edge) -> Weight,
+{
+    pub fn new(graph: &'a G, weight: F) -> Self {
+        Self { graph, weight }
+    }
+}
+
+impl<G, F> GraphRef for Reweighted<'_, G, F>
+where
+    G: GraphRef + ?Sized,
+    F: Fn(&Edge) -> Weight,
+{
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn contains_node(&self, node: NodeId) -> bool {
+        self.graph.contains_node(node)
+    }
+
+    fn edges_of(&self, node: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        self.graph.edges_of(node)
+    }
+
+    fn cost(&self, edge: &Edge) -> Weight {
+        (self.weight)(edge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::shortest_path_tree;
+    use crate::rng::Rng;
+
+    #[test]
+    fn filtered_searches_match_a_copy() {
+        let mut rng = Rng::new(12);
+        for _ in 0..30 {
+            let n = 1 + rng.below(12);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            for _ in 0..rng.below(3 * n) {
+                let (a, b) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                let cost = rng.below(10) as Weight;
+                if rng.chance(0.3) {
+                    graph.add_edge(a, b, cost);
+                } else {
+                    graph.add_bidirectional_edge(a, b, cost);
+                }
+            }
+            let hidden: Vec<bool> = (0..n).map(|_| rng.chance(0.2)).collect();
+            let dropped: Vec<bool> = (0..graph.edge_count()).map(|_| rng.chance(0.2)).collect();
+            let view = graph.filter_view(
+                |node| !hidden[node.index()],
+                |_, edge| !dropped[edge.id.index()],
+            );
+            let copy = graph.edge_subgraph(|id| {
+                let (src, dest) = graph.edge_endpoints(id).unwrap();
+                !dropped[id.index()] && !hidden[src.index()] && !hidden[dest.index()]
+            });
+            for start in graph.node_ids() {
+                assert_eq!(view.contains_node(start), !hidden[start.index()]);
+                if hidden[start.index()] {
+                    assert_eq!(view.edges_of(start).count(), 0);
+                    continue;
+                }
+                let expected = shortest_path_tree(&copy, start);
+                assert_eq!(
+                    shortest_path_tree(&view, start).distances(),
+                    expected.distances()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reweighted_changes_only_costs() {
+        let graph: Graph = "a b 4\nb c 4\na c 10".parse().unwrap();
+        let doubled = Reweighted::new(&graph, |edge| 2 * edge.cost);
+        let a = graph.get_node("a").unwrap();
+        let tree = shortest_path_tree(&doubled, a);
+        assert_eq!(tree.distances(), [Some(0), Some(8), Some(16)]);
+        let view = graph.filter_view(|_| true, |_, edge| edge.cost < 10);
+        let nested = Reweighted::new(&view, |edge| edge.cost + 1);
+        assert_eq!(shortest_path_tree(&nested, a).distances()[2], Some(10));
+        assert_eq!(nested.edges_of(a).count(), 1);
+    }
+}
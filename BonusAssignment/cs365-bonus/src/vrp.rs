@@ -0,0 +1,257 @@
+use crate::graph::{Graph, NodeId, Weight};
+use crate::path::shortest_path_tree;
+
+/// One vehicle's trip out of the depot and back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VehicleRoute {
+    /// The customers in the order they are visited, not including the depot.
+    pub stops: Vec<NodeId>,
+    /// The total demand of the stops.
+    pub load: u64,
+    /// The shortest path distance from the depot through every stop and back.
+    pub cost: Weight,
+}
+
+/// Routes serving every customer of a vehicle routing problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VrpSolution {
+    pub routes: Vec<VehicleRoute>,
+    /// The sum of the routes' costs.
+    pub cost: Weight,
+}
+
+/// Plan routes for vehicles of the given `capacity` that leave `depot`, deliver each
+/// customer's demand in `demands`, and return, keeping the total distance low.
+///
+/// Routes are built with the Clarke-Wright savings algorithm: every customer starts on a
+/// route of its own, and routes are joined end to start, largest saving first, while the
+/// vehicle can carry the combined load. Each route's order is then improved with 2-opt.
+/// This is a heuristic; the problem is NP-hard, so the result need not be optimal.
+///
+/// Distances between the depot and customers are shortest path costs, from one search per
+/// customer, so costs must not be negative. Fails if a customer is listed twice, is the
+/// depot, or wants more than a vehicle holds, or if there is no path between two of the
+/// depot and customers.
+///
+/// ```
+/// use cs365_bonus::vrp::solve_vrp;
+///
+/// // Two customers each side of the depot, too much for one vehicle to carry.
+/// let graph: cs365_bonus::Graph = "d a 1\na b 1\nd x 1\nx y 1".parse().unwrap();
+/// let node = |name| graph.get_node(name).unwrap();
+/// let demands = [(node("a"), 2), (node("b"), 2), (node("x"), 2), (node("y"), 2)];
+/// let solution = solve_vrp(&graph, node("d"), &demands, 4).unwrap();
+/// assert_eq!((solution.routes.len(), solution.cost), (2, 8));
+/// ```
+pub fn solve_vrp(
+    graph: &Graph,
+    depot: NodeId,
+    demands: &[(NodeId, u64)],
+    capacity: u64,
+) -> Result<VrpSolution, String> {
+    let name = |node| graph.get_node_name(node).unwrap_or("");
+    // Point 0 is the depot and point `i` is customer `i - 1`.
+    let mut points = vec![depot];
+    for &(customer, demand) in demands {
+        if points.contains(&customer) {
+            return Err(format!("'{}' is the depot or listed twice", name(customer)));
+        }
+        if demand > capacity {
+            return Err(format!(
+                "'{}' wants {}, more than a vehicle's capacity of {}",
+                name(customer),
+                demand,
+                capacity
+            ));
+        }
+        points.push(customer);
+    }
+    let mut distance = Vec::with_capacity(points.len());
+    for &from in &points {
+        let tree = shortest_path_tree(graph, from);
+        let row = points
+            .iter()
+            .map(|&to| {
+                tree.distance(to).ok_or_else(|| {
+                    format!("there is no path from '{}' to '{}'", name(from), name(to))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        distance.push(row);
+    }
+    let d = |i: usize, j: usize| distance[i][j];
+
+    // Each route is a list of points, and `route_of` maps a point to the route holding it.
+    let mut routes: Vec<Option<Vec<usize>>> = (0..points.len()).map(|i| Some(vec![i])).collect();
+    routes[0] = None;
+    let mut route_of: Vec<usize> = (0..points.len()).collect();
+    let mut load: Vec<u64> = std::iter::once(0)
+        .chain(demands.iter().map(|&(_, demand)| demand))
+        .collect();
+    let mut savings = Vec::new();
+    for i in 1..points.len() {
+        for j in 1..points.len() {
+            if i != j {
+                savings.push((d(i, 0) + d(0, j) - d(i, j), i, j));
+            }
+        }
+    }
+    savings.sort_by_key(|&(saving, i, j)| (std::cmp::Reverse(saving), i, j));
+    for (saving, i, j) in savings {
+        let (a, b) = (route_of[i], route_of[j]);
+        if saving <= 0 || a == b || load[a] + load[b] > capacity {
+            continue;
+        }
+        // Only a route ending at `i` can be followed by one starting at `j`.
+        let ends = routes[a]
+            .as_ref()
+            .is_some_and(|route| route.last() == Some(&i));
+        let starts = routes[b]
+            .as_ref()
+            .is_some_and(|route| route.first() == Some(&j));
+        if !(ends && starts) {
+            continue;
+        }
+        let tail = routes[b].take().expect("the route was just checked");
+        for &point in &tail {
+            route_of[point] = a;
+        }
+        routes[a]
+            .as_mut()
+            .expect("the route was just checked")
+            .extend(tail);
+        load[a] += load[b];
+    }
+
+    let cost = |route: &[usize]| {
+        let inner: Weight = route.windows(2).map(|pair| d(pair[0], pair[1])).sum();
+        d(0, route[0]) + inner + d(route[route.len() - 1], 0)
+    };
+    let mut solution = VrpSolution {
+        routes: Vec::new(),
+        cost: 0,
+    };
+    for (i, route) in routes.into_iter().enumerate() {
+        let mut route = match route {
+            Some(route) => route,
+            None => continue,
+        };
+        two_opt(&mut route, cost);
+        let route_cost = cost(&route);
+        solution.cost += route_cost;
+        solution.routes.push(VehicleRoute {
+            stops: route.iter().map(|&point| points[point]).collect(),
+            load: load[i],
+            cost: route_cost,
+        });
+    }
+    Ok(solution)
+}
+
+/// Reverse segments of `route` while that lowers its `cost`.
+///
+/// The whole route is re-costed for each candidate, since with one-way edges a reversed
+/// segment costs something different.
+fn two_opt(route: &mut [usize], cost: impl Fn(&[usize]) -> Weight) {
+    let mut best = cost(route);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..route.len() {
+            for j in i + 1..route.len() {
+                route[i..=j].reverse();
+                let candidate = cost(route);
+                if candidate < best {
+                    best = candidate;
+                    improved = true;
+                } else {
+                    route[i..=j].reverse();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn routes_serve_every_customer_within_capacity() {
+        let mut rng = Rng::new(44);
+        for _ in 0..100 {
+            let n = 2 + rng.below(10);
+            let mut graph = Graph::new();
+            for i in 0..n {
+                graph.get_or_insert_node(&i.to_string());
+            }
+            // A path through every node keeps the graph connected.
+            for i in 1..n {
+                let cost = rng.below(10) as Weight;
+                graph.add_bidirectional_edge(NodeId::new(i - 1), NodeId::new(i), cost);
+            }
+            for _ in 0..rng.below(n) {
+                let (u, v) = (NodeId::new(rng.below(n)), NodeId::new(rng.below(n)));
+                graph.add_edge(u, v, rng.below(10) as Weight);
+            }
+            let capacity = 1 + rng.below(10) as u64;
+            let depot = NodeId::new(rng.below(n));
+            let mut demands = Vec::new();
+            for node in graph.node_ids() {
+                if node != depot && rng.chance(0.7) {
+                    demands.push((node, rng.below(capacity as usize + 1) as u64));
+                }
+            }
+            let solution = solve_vrp(&graph, depot, &demands, capacity).unwrap();
+
+            let trees: Vec<_> = graph
+                .node_ids()
+                .map(|node| shortest_path_tree(&graph, node))
+                .collect();
+            let d = |u: NodeId, v: NodeId| trees[u.index()].distance(v).unwrap();
+            let mut served: Vec<NodeId> = Vec::new();
+            for route in &solution.routes {
+                let demand = |stop| demands.iter().find(|&&(c, _)| c == stop).unwrap().1;
+                assert_eq!(route.load, route.stops.iter().map(|&s| demand(s)).sum());
+                assert!(route.load <= capacity);
+                let mut stops = vec![depot];
+                stops.extend(&route.stops);
+                stops.push(depot);
+                let cost: Weight = stops.windows(2).map(|w| d(w[0], w[1])).sum();
+                assert_eq!(route.cost, cost);
+                served.extend(&route.stops);
+            }
+            served.sort_unstable();
+            let mut customers: Vec<NodeId> = demands.iter().map(|&(c, _)| c).collect();
+            customers.sort_unstable();
+            assert_eq!(served, customers);
+            assert_eq!(
+                solution.cost,
+                solution.routes.iter().map(|r| r.cost).sum::<Weight>()
+            );
+            // Joining routes only ever saves distance over a trip per customer.
+            let separate: Weight = customers.iter().map(|&c| d(depot, c) + d(c, depot)).sum();
+            assert!(solution.cost <= separate);
+        }
+    }
+
+    #[test]
+    fn rejects_unservable_customers() {
+        let graph: Graph = "d a 1\na b 1\nc e 1".parse().unwrap();
+        let node = |name| graph.get_node(name).unwrap();
+        let (d, a, b, c) = (node("d"), node("a"), node("b"), node("c"));
+        assert_eq!(
+            solve_vrp(&graph, d, &[(a, 1), (b, 5)], 4),
+            Err("'b' wants 5, more than a vehicle's capacity of 4".to_string())
+        );
+        assert!(solve_vrp(&graph, d, &[(a, 1), (a, 1)], 4).is_err());
+        assert!(solve_vrp(&graph, d, &[(d, 1)], 4).is_err());
+        assert_eq!(
+            solve_vrp(&graph, d, &[(c, 1)], 4),
+            Err("there is no path from 'd' to 'c'".to_string())
+        );
+        let empty = solve_vrp(&graph, d, &[], 4).unwrap();
+        assert_eq!((empty.routes.len(), empty.cost), (0, 0));
+    }
+}